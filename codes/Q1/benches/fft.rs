@@ -0,0 +1,50 @@
+// FFT 基准测试：覆盖 Q1 流水线实际用到的 FFT 长度
+//
+// project.wav 的原始长度（可能含大素因子）、补零/截断到"快速长度"后的长度，
+// 以及 8192 点分段平均谱，运行 `cargo bench --bench fft`
+
+use am_demodulation_q1::audio_reader::AudioData;
+use am_demodulation_q1::fft_processor::{
+    next_fast_length, previous_fast_length, ChunkedSpectrum, FftResult,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// 与 q1 默认输入相同的音频文件
+const INPUT: &str = "../project.wav";
+
+/// 分段平均谱的段长，与 q1 主程序一致
+const SEGMENT_LEN: usize = 8192;
+
+fn fft_lengths(c: &mut Criterion) {
+    let audio = AudioData::open(INPUT).expect("无法读取 ../project.wav");
+    let samples = audio.samples;
+    let sample_rate = audio.sample_rate as f64;
+    let n = samples.len();
+
+    let mut group = c.benchmark_group("fft");
+    group.sample_size(30);
+    for (label, len) in [
+        ("raw", n),
+        ("next_fast", next_fast_length(n)),
+        ("previous_fast", previous_fast_length(n)),
+        ("pow2", n.next_power_of_two()),
+    ] {
+        let mut input = samples.clone();
+        input.resize(len, 0.0);
+        group.bench_with_input(BenchmarkId::new(label, len), &input, |b, input| {
+            b.iter(|| FftResult::compute_quiet(black_box(input), sample_rate))
+        });
+    }
+
+    group.bench_function(BenchmarkId::new("chunked", SEGMENT_LEN), |b| {
+        b.iter(|| {
+            let mut chunked = ChunkedSpectrum::new(SEGMENT_LEN, sample_rate);
+            chunked.push(black_box(&samples));
+            chunked.finish()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fft_lengths);
+criterion_main!(benches);
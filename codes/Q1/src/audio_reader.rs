@@ -1,8 +1,121 @@
 // 1. 音频文件读取模块
 // 负责读取 WAV 文件并提取采样数据、采样率和样本数
 
+use dsp_core::channels::ChannelMode;
+use dsp_core::error::{DspError, Result};
+use dsp_core::wav_io::{self, WavFormat, WavWriteOptions};
 use hound::{WavReader, WavSpec};
+use log::info;
+use memmap2::Mmap;
 use std::path::Path;
+use std::str::FromStr;
+
+/// 原始二进制采样文件的格式（小端序，无文件头）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    /// 交织排列的 16 位有符号整数
+    I16 { channels: u16 },
+    /// 交织排列的 32 位浮点数
+    F32 { channels: u16 },
+    /// SDR 复基带 IQ 数据，I/Q 交替的 16 位有符号整数
+    IqI16,
+    /// SDR 复基带 IQ 数据，I/Q 交替的 32 位浮点数
+    IqF32,
+}
+
+impl FromStr for RawFormat {
+    type Err = String;
+
+    /// 接受 `i16`、`f32`（单声道）、`i16x2`、`f32x2`（多声道）、`iq-i16`、`iq-f32`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        match s.as_str() {
+            "iq-i16" | "cs16" => return Ok(RawFormat::IqI16),
+            "iq-f32" | "cf32" => return Ok(RawFormat::IqF32),
+            _ => {}
+        }
+        let (kind, channels) = match s.split_once('x') {
+            Some((kind, channels)) => (
+                kind,
+                channels.parse::<u16>().map_err(|_| format!("无效的声道数: {}", channels))?,
+            ),
+            None => (s.as_str(), 1),
+        };
+        match kind {
+            "i16" => Ok(RawFormat::I16 { channels }),
+            "f32" => Ok(RawFormat::F32 { channels }),
+            other => Err(format!(
+                "未知的原始数据格式: {}（可选 i16、f32、i16x2、f32x2、iq-i16、iq-f32）",
+                other
+            )),
+        }
+    }
+}
+
+/// 内存映射的 WAV 文件，只解析文件头，采样点按需从映射区读取
+///
+/// 适用于长录音的分段/流式分析：操作系统按页换入数据，整个文件不必载入内存
+pub struct MappedWav {
+    mmap: Mmap,
+    /// data 块在文件中的起始偏移（字节）
+    data_offset: usize,
+    /// data 块长度（字节，已截断到整帧）
+    data_len: usize,
+    /// WAV 文件规格
+    pub spec: WavSpec,
+}
+
+impl MappedWav {
+    /// 每个采样点的字节数
+    fn bytes_per_sample(&self) -> usize {
+        self.spec.bits_per_sample as usize / 8
+    }
+
+    /// 每帧（所有声道各一个采样点）的字节数
+    fn bytes_per_frame(&self) -> usize {
+        self.bytes_per_sample() * self.spec.channels as usize
+    }
+
+    /// 总帧数
+    pub fn num_frames(&self) -> usize {
+        self.data_len / self.bytes_per_frame()
+    }
+
+    /// 解码单个采样点并归一化到 [-1, 1]
+    fn decode_sample(&self, bytes: &[u8]) -> f64 {
+        match (self.spec.sample_format, self.spec.bits_per_sample) {
+            (hound::SampleFormat::Float, _) => {
+                f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+            (hound::SampleFormat::Int, 8) => (bytes[0] as f64 - 128.0) / 128.0,
+            (hound::SampleFormat::Int, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32768.0,
+            (hound::SampleFormat::Int, 24) => {
+                // 左移 8 位后再算术右移，完成 24 位符号扩展
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f64 / 8_388_608.0
+            }
+            _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64 / 2_147_483_648.0,
+        }
+    }
+
+    /// 逐帧迭代，每帧为各声道的采样值
+    pub fn frames(&self) -> impl Iterator<Item = Vec<f64>> + '_ {
+        let sample_bytes = self.bytes_per_sample();
+        self.mmap[self.data_offset..self.data_offset + self.data_len]
+            .chunks_exact(self.bytes_per_frame())
+            .map(move |frame| {
+                frame
+                    .chunks_exact(sample_bytes)
+                    .map(|bytes| self.decode_sample(bytes))
+                    .collect()
+            })
+    }
+
+    /// 逐帧迭代单声道数据（多声道取平均）
+    pub fn mono(&self) -> impl Iterator<Item = f64> + '_ {
+        self.frames()
+            .map(|frame| frame.iter().sum::<f64>() / frame.len() as f64)
+    }
+}
 
 /// 音频数据结构
 #[derive(Debug, Clone)]
@@ -18,46 +131,316 @@ pub struct AudioData {
 }
 
 impl AudioData {
-    /// 从 WAV 文件读取音频数据
-    pub fn from_wav<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut reader = WavReader::open(path)?;
-        let spec = reader.spec();
+    /// 读取音频文件：WAV 直接用 hound 读取，MP3/FLAC/OGG 等格式用 symphonia 解码
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let is_wav = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        let (samples, spec) = if is_wav {
+            Self::read_wav(path)?
+        } else {
+            Self::decode(path)?
+        };
         let sample_rate = spec.sample_rate;
 
-        // 读取所有采样点并归一化
+        let num_samples = samples.len();
+
+        info!("音频文件读取成功:");
+        info!("  采样率: {} Hz", sample_rate);
+        info!("  样本数: {}", num_samples);
+        info!("  位深度: {} bits", spec.bits_per_sample);
+        info!("  声道数: {}", spec.channels);
+        info!("  时长: {:.2} 秒", num_samples as f64 / sample_rate as f64);
+
+        Ok(AudioData {
+            samples,
+            sample_rate,
+            num_samples,
+            spec,
+        })
+    }
+
+    /// 读取无文件头的原始采样文件（如 SDR 录制的 IQ 数据）
+    ///
+    /// IQ 数据 x(t) = I + jQ 只保留实部 I：调谐偏差为 f_d 时 x(t) = m(t)·e^{j2πf_d t}，
+    /// 其实部 m(t)·cos(2πf_d t) 正是后续解调流程所处理的信号模型
+    pub fn from_raw<P: AsRef<Path>>(
+        path: P,
+        format: RawFormat,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| DspError::io(path, e))?;
+        let i16_values = || {
+            bytes
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f64 / 32768.0)
+        };
+        let f32_values = || {
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+        };
+
+        let (mut samples, channels, bits_per_sample, sample_format): (Vec<f64>, u16, u16, _) = match format {
+            RawFormat::I16 { channels } => (i16_values().collect(), channels, 16, hound::SampleFormat::Int),
+            RawFormat::F32 { channels } => (f32_values().collect(), channels, 32, hound::SampleFormat::Float),
+            RawFormat::IqI16 => (i16_values().step_by(2).collect(), 1, 16, hound::SampleFormat::Int),
+            RawFormat::IqF32 => (f32_values().step_by(2).collect(), 1, 32, hound::SampleFormat::Float),
+        };
+        let channels = channels.max(1);
+        // 丢弃末尾不完整的帧
+        let frames = samples.len() / channels as usize;
+        samples.truncate(frames * channels as usize);
+
+        info!("原始数据读取成功:");
+        info!("  格式: {:?}", format);
+        info!("  采样率: {} Hz", sample_rate);
+        info!("  样本数: {}", samples.len());
+        info!("  时长: {:.2} 秒", frames as f64 / sample_rate as f64);
+
+        Ok(AudioData {
+            num_samples: samples.len(),
+            samples,
+            sample_rate,
+            spec: WavSpec {
+                channels,
+                sample_rate,
+                bits_per_sample,
+                sample_format,
+            },
+        })
+    }
+
+    /// 用 hound 读取 WAV 文件的全部采样点（交织排列）并归一化
+    fn read_wav(path: &Path) -> Result<(Vec<f64>, WavSpec)> {
+        let mut reader = WavReader::open(path).map_err(|e| DspError::wav(path, e))?;
+        let spec = reader.spec();
+
+        // 读取所有采样点并归一化；文件截断或损坏时返回错误而不是 panic
         let samples: Vec<f64> = match spec.sample_format {
             hound::SampleFormat::Float => {
                 reader
                     .samples::<f32>()
-                    .map(|s| s.unwrap() as f64)
-                    .collect()
+                    .map(|s| s.map(|v| v as f64))
+                    .collect::<std::result::Result<_, _>>()
             }
             hound::SampleFormat::Int => {
-                let max_value = (1 << (spec.bits_per_sample - 1)) as f64;
+                let max_value = (1i64 << (spec.bits_per_sample - 1)) as f64;
                 reader
                     .samples::<i32>()
-                    .map(|s| s.unwrap() as f64 / max_value)
-                    .collect()
+                    .map(|s| s.map(|v| v as f64 / max_value))
+                    .collect::<std::result::Result<_, _>>()
+            }
+        }
+        .map_err(|e| DspError::wav(path, e))?;
+        Ok((samples, spec))
+    }
+
+    /// 用 symphonia 解码压缩音频（第一条音轨），返回交织排列的采样点与等效 WAV 规格
+    ///
+    /// 解码结果为浮点数据；若源文件记录了位深则沿用为整型规格，否则记为 32 位浮点
+    fn decode(path: &Path) -> Result<(Vec<f64>, WavSpec)> {
+        use symphonia::core::audio::SampleBuffer;
+        use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+        use symphonia::core::errors::Error as SymphoniaError;
+        use symphonia::core::formats::FormatOptions;
+        use symphonia::core::io::MediaSourceStream;
+        use symphonia::core::meta::MetadataOptions;
+        use symphonia::core::probe::Hint;
+
+        let undecodable = |e: SymphoniaError| DspError::Data(format!("{}: {}", path.display(), e));
+        let file = std::fs::File::open(path).map_err(|e| DspError::io(path, e))?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(ext);
+        }
+
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        ).map_err(undecodable)?;
+        let mut format = probed.format;
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| DspError::Data(format!("{}: 文件中没有可解码的音轨", path.display())))?;
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .map_err(undecodable)?;
+
+        let mut samples = Vec::new();
+        let mut channels = params.channels.map(|c| c.count()).unwrap_or(0);
+        let mut sample_rate = params.sample_rate.unwrap_or(0);
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                // 读到文件末尾
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(undecodable(e)),
+            };
+            if packet.track_id() != track_id {
+                continue;
             }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // 个别损坏的数据包跳过即可
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(undecodable(e)),
+            };
+            let signal_spec = *decoded.spec();
+            channels = signal_spec.channels.count();
+            sample_rate = signal_spec.rate;
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, signal_spec);
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend(buffer.samples().iter().map(|&s| s as f64));
+        }
+
+        if channels == 0 || sample_rate == 0 {
+            return Err(DspError::Data(format!("{}: 无法确定声道数或采样率", path.display())));
+        }
+        let spec = match params.bits_per_sample {
+            Some(bits) => WavSpec {
+                channels: channels as u16,
+                sample_rate,
+                bits_per_sample: bits as u16,
+                sample_format: hound::SampleFormat::Int,
+            },
+            None => WavSpec {
+                channels: channels as u16,
+                sample_rate,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+            },
         };
+        Ok((samples, spec))
+    }
 
-        let num_samples = samples.len();
+    /// 以内存映射方式打开 WAV 文件，只解析 fmt/data 块头，不读入采样数据
+    ///
+    /// 支持 8/16/24/32 位整型与 32 位浮点（含 WAVE_FORMAT_EXTENSIBLE）
+    pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MappedWav> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).map_err(|e| DspError::io(path, e))?;
+        // SAFETY: 映射为只读；分析期间文件不应被其他进程截断或改写
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| DspError::io(path, e))?;
+        let malformed = |message: String| DspError::Data(format!("{}: {}", path.display(), message));
 
-        println!("音频文件读取成功:");
-        println!("  采样率: {} Hz", sample_rate);
-        println!("  样本数: {}", num_samples);
-        println!("  位深度: {} bits", spec.bits_per_sample);
-        println!("  声道数: {}", spec.channels);
-        println!("  时长: {:.2} 秒", num_samples as f64 / sample_rate as f64);
+        if mmap.len() < 12 || &mmap[0..4] != b"RIFF" || &mmap[8..12] != b"WAVE" {
+            return Err(malformed("不是 RIFF/WAVE 文件".to_string()));
+        }
+        let read_u16 = |at: usize| u16::from_le_bytes([mmap[at], mmap[at + 1]]);
+        let read_u32 = |at: usize| u32::from_le_bytes([mmap[at], mmap[at + 1], mmap[at + 2], mmap[at + 3]]);
 
-        Ok(AudioData {
-            samples,
-            sample_rate,
-            num_samples,
+        let mut spec = None;
+        let mut data = None;
+        let mut pos = 12;
+        while pos + 8 <= mmap.len() {
+            let id = &mmap[pos..pos + 4];
+            let size = read_u32(pos + 4) as usize;
+            let body = pos + 8;
+            if id == b"fmt " && size >= 16 && body + size <= mmap.len() {
+                let mut format_tag = read_u16(body);
+                if format_tag == 0xFFFE && size >= 26 {
+                    // WAVE_FORMAT_EXTENSIBLE：子格式 GUID 的前两个字节即实际格式
+                    format_tag = read_u16(body + 24);
+                }
+                let sample_format = match format_tag {
+                    1 => hound::SampleFormat::Int,
+                    3 => hound::SampleFormat::Float,
+                    other => return Err(malformed(format!("不支持的 WAV 编码格式: {}", other))),
+                };
+                spec = Some(WavSpec {
+                    channels: read_u16(body + 2),
+                    sample_rate: read_u32(body + 4),
+                    bits_per_sample: read_u16(body + 14),
+                    sample_format,
+                });
+            } else if id == b"data" {
+                // 录制中断的文件 data 长度可能超出实际大小，以文件实际长度为准
+                data = Some((body, size.min(mmap.len() - body)));
+                break;
+            }
+            // 块长度为奇数时有一个填充字节
+            pos = body + size + (size & 1);
+        }
+
+        let spec = spec.ok_or_else(|| malformed("缺少 fmt 块".to_string()))?;
+        let (data_offset, data_len) = data.ok_or_else(|| malformed("缺少 data 块".to_string()))?;
+        if spec.channels == 0 || !matches!(spec.bits_per_sample, 8 | 16 | 24 | 32) {
+            return Err(malformed(format!("不支持的声道数/位深: {} 声道, {} bits", spec.channels, spec.bits_per_sample)));
+        }
+        let frame_bytes = spec.channels as usize * spec.bits_per_sample as usize / 8;
+        Ok(MappedWav {
+            mmap,
+            data_offset,
+            data_len: data_len / frame_bytes * frame_bytes,
             spec,
         })
     }
 
+    /// 流式读取 WAV 文件，按块回调单声道数据，不把整个文件载入内存
+    ///
+    /// # 参数
+    /// - chunk_len: 每块的帧数（多声道取平均后为一个采样点）
+    /// - on_chunk: 每读满一块调用一次，最后一块可能较短
+    ///
+    /// # 返回
+    /// (采样率, 总帧数)
+    pub fn stream_mono<P: AsRef<Path>, F: FnMut(&[f64])>(
+        path: P,
+        chunk_len: usize,
+        mut on_chunk: F,
+    ) -> Result<(u32, usize)> {
+        let path = path.as_ref();
+        let mut reader = WavReader::open(path).map_err(|e| DspError::wav(path, e))?;
+        let spec = reader.spec();
+        let channels = spec.channels.max(1) as usize;
+        let chunk_len = chunk_len.max(1);
+
+        let raw: Box<dyn Iterator<Item = std::result::Result<f64, hound::Error>>> = match spec.sample_format {
+            hound::SampleFormat::Float => {
+                Box::new(reader.samples::<f32>().map(|s| s.map(|v| v as f64)))
+            }
+            hound::SampleFormat::Int => {
+                let max_value = (1i64 << (spec.bits_per_sample - 1)) as f64;
+                Box::new(reader.samples::<i32>().map(move |s| s.map(|v| v as f64 / max_value)))
+            }
+        };
+
+        let mut chunk = Vec::with_capacity(chunk_len);
+        let mut frame_sum = 0.0;
+        let mut frame_fill = 0;
+        let mut total_frames = 0;
+        for sample in raw {
+            frame_sum += sample.map_err(|e| DspError::wav(path, e))?;
+            frame_fill += 1;
+            if frame_fill == channels {
+                chunk.push(frame_sum / channels as f64);
+                frame_sum = 0.0;
+                frame_fill = 0;
+                total_frames += 1;
+                if chunk.len() == chunk_len {
+                    on_chunk(&chunk);
+                    chunk.clear();
+                }
+            }
+        }
+        if !chunk.is_empty() {
+            on_chunk(&chunk);
+        }
+
+        Ok((spec.sample_rate, total_frames))
+    }
+
     /// 获取信号时长（秒）
     pub fn duration(&self) -> f64 {
         self.num_samples as f64 / self.sample_rate as f64
@@ -76,12 +459,17 @@ impl AudioData {
         }
     }
 
+    /// 按声道模式拆分数据（Left/Right/Mix 返回一路，Both 返回每个声道）
+    pub fn channels(&self, mode: ChannelMode) -> Vec<Vec<f64>> {
+        mode.select(&self.samples, self.spec.channels as usize)
+    }
+
     /// 保存为 WAV 文件
     pub fn save_wav<P: AsRef<Path>>(
         &self,
         path: P,
         samples: &[f64],
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
         let spec = hound::WavSpec {
             channels: 1,
             sample_rate: self.sample_rate,
@@ -89,7 +477,8 @@ impl AudioData {
             sample_format: hound::SampleFormat::Int,
         };
 
-        let mut writer = hound::WavWriter::create(path, spec)?;
+        let path = path.as_ref();
+        let mut writer = hound::WavWriter::create(path, spec).map_err(|e| DspError::wav(path, e))?;
 
         // 归一化并转换为 i16
         let max_amplitude = samples.iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
@@ -101,11 +490,34 @@ impl AudioData {
 
         for &sample in samples {
             let sample_i16 = (sample * scale).clamp(-32768.0, 32767.0) as i16;
-            writer.write_sample(sample_i16)?;
+            writer.write_sample(sample_i16).map_err(|e| DspError::wav(path, e))?;
         }
 
-        writer.finalize()?;
-        println!("音频文件保存成功");
+        writer.finalize().map_err(|e| DspError::wav(path, e))?;
+        info!("音频文件保存成功");
+        Ok(())
+    }
+
+    /// 与原始文件相同的输出格式（16 位以内整型为 16 位，其余整型为 24 位，浮点为 32 位浮点）
+    pub fn source_format(&self) -> WavFormat {
+        WavFormat::from_spec(&self.spec)
+    }
+
+    /// 按指定位深/格式、归一化方式与抖动设置保存为单声道 WAV 文件
+    ///
+    /// `format` 取 `self.source_format()` 即可保留原始文件规格，避免额外量化
+    pub fn save_wav_as<P: AsRef<Path>>(
+        &self,
+        path: P,
+        samples: &[f64],
+        options: &WavWriteOptions,
+    ) -> Result<()> {
+        let path = path
+            .as_ref()
+            .to_str()
+            .ok_or_else(|| DspError::Argument("输出路径不是有效的 UTF-8".to_string()))?;
+        wav_io::write_channels(path, &[samples.to_vec()], self.sample_rate, options)?;
+        info!("音频文件保存成功 ({})", options);
         Ok(())
     }
 }
@@ -117,7 +529,7 @@ mod tests {
     #[test]
     fn test_audio_reader() {
         // 测试读取音频文件
-        let result = AudioData::from_wav("../project.wav");
+        let result = AudioData::open("../project.wav");
         assert!(result.is_ok());
         
         if let Ok(audio) = result {
@@ -126,4 +538,67 @@ mod tests {
             assert_eq!(audio.samples.len(), audio.num_samples);
         }
     }
+
+    #[test]
+    fn test_truncated_wav_is_an_error() {
+        // data 块头声明的长度比文件实际内容长：读取时报错而不是 panic
+        let bytes = std::fs::read("../project.wav").unwrap();
+        let path = std::env::temp_dir().join(format!("q1_truncated_{}.wav", std::process::id()));
+        std::fs::write(&path, &bytes[..bytes.len() / 2 + 1]).unwrap();
+        let result = AudioData::open(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(DspError::Io { .. }) | Err(DspError::Data(_))), "{:?}", result.err());
+    }
+
+    #[test]
+    fn test_symphonia_decode_matches_hound() {
+        // 以非 .wav 扩展名复制，强制走 symphonia 解码路径
+        let copy = std::env::temp_dir().join(format!("q1_decode_{}.audio", std::process::id()));
+        std::fs::copy("../project.wav", &copy).unwrap();
+
+        let wav = AudioData::open("../project.wav").unwrap();
+        let decoded = AudioData::open(&copy).unwrap();
+        std::fs::remove_file(&copy).ok();
+
+        assert_eq!(decoded.sample_rate, wav.sample_rate);
+        assert_eq!(decoded.spec.channels, wav.spec.channels);
+        assert_eq!(decoded.num_samples, wav.num_samples);
+        for (a, b) in decoded.samples.iter().zip(&wav.samples) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_from_raw_iq() {
+        // I = 0.5·cos, Q = 0.5·sin，读取后只保留 I
+        let path = std::env::temp_dir().join(format!("q1_raw_{}.cf32", std::process::id()));
+        let mut bytes = Vec::new();
+        for n in 0..100 {
+            let phase = n as f32 * 0.3;
+            bytes.extend_from_slice(&(0.5 * phase.cos()).to_le_bytes());
+            bytes.extend_from_slice(&(0.5 * phase.sin()).to_le_bytes());
+        }
+        std::fs::write(&path, &bytes).unwrap();
+
+        let format: RawFormat = "iq-f32".parse().unwrap();
+        let audio = AudioData::from_raw(&path, format, 48000).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(audio.num_samples, 100);
+        assert_eq!(audio.spec.channels, 1);
+        assert!((audio.samples[10] - 0.5 * (3.0f64).cos()).abs() < 1e-6);
+        assert_eq!("i16x2".parse::<RawFormat>().unwrap(), RawFormat::I16 { channels: 2 });
+    }
+
+    #[test]
+    fn test_open_mmap_matches_hound() {
+        let wav = AudioData::open("../project.wav").unwrap();
+        let mapped = AudioData::open_mmap("../project.wav").unwrap();
+
+        assert_eq!(mapped.spec, wav.spec);
+        assert_eq!(mapped.num_frames(), wav.to_mono().len());
+        for (a, b) in mapped.mono().zip(wav.to_mono()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
 }
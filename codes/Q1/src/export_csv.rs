@@ -1,50 +1,58 @@
 // 导出频谱分析结果为 CSV 文件
 
-mod audio_reader;
-mod fft_processor;
-mod frequency_estimator;
-
-use audio_reader::AudioData;
-use fft_processor::FftResult;
-use frequency_estimator::FrequencyEstimator;
+use am_demodulation_q1::audio_reader::AudioData;
+use am_demodulation_q1::fft_processor::FftResult;
+use am_demodulation_q1::preprocess::{PreprocessConfig, Preprocessor};
+use am_demodulation_q1::frequency_estimator::FrequencyEstimator;
+use dsp_core::error;
+use dsp_core::logging;
+use log::info;
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    println!("========================================");
-    println!("Q1: 导出频谱分析结果为 CSV");
-    println!("========================================\n");
+fn main() {
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run() {
+        error::exit(e.as_ref(), "错误");
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    info!("========================================");
+    info!("Q1: 导出频谱分析结果为 CSV");
+    info!("========================================\n");
 
     // 读取音频文件
-    println!("读取音频文件...");
+    info!("读取音频文件...");
     let audio_path = "../project.wav";
-    let audio = AudioData::from_wav(audio_path)?;
+    let audio = AudioData::open(audio_path)?;
     
-    let samples = audio.to_mono();
+    // 转换为单声道后去除直流偏移与线性趋势，避免直流 bin 主导幅度刻度
+    let samples = Preprocessor::apply(&audio.to_mono(), &PreprocessConfig::default());
     let sample_rate = audio.sample_rate as f64;
     let num_samples = samples.len();
     
-    println!("音频信息:");
-    println!("  采样率: {} Hz", sample_rate);
-    println!("  样本数: {}", num_samples);
-    println!("  时长: {:.2} 秒\n", num_samples as f64 / sample_rate);
+    info!("音频信息:");
+    info!("  采样率: {} Hz", sample_rate);
+    info!("  样本数: {}", num_samples);
+    info!("  时长: {:.2} 秒\n", num_samples as f64 / sample_rate);
 
     // 计算 FFT
-    println!("计算 FFT...");
+    info!("计算 FFT...");
     let fft_result = FftResult::compute(&samples, sample_rate);
     let frequencies = &fft_result.frequencies;
     let magnitude = &fft_result.magnitude;
     let magnitude_db = fft_result.get_magnitude_db();
     
-    println!("FFT 点数: {}", num_samples);
-    println!("频率分辨率: {:.4} Hz\n", sample_rate / num_samples as f64);
+    info!("FFT 点数: {}", num_samples);
+    info!("频率分辨率: {:.4} Hz\n", sample_rate / num_samples as f64);
 
     // 创建输出目录
     std::fs::create_dir_all("output")?;
 
     // 1. 导出完整频谱数据
-    println!("导出完整频谱数据到 output/Q1_spectrum_full.csv...");
+    info!("导出完整频谱数据到 output/Q1_spectrum_full.csv...");
     export_spectrum_data(
         frequencies,
         magnitude,
@@ -54,7 +62,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
 
     // 2. 导出低频段频谱 (0-4 kHz)
-    println!("导出低频段频谱 (0-4 kHz) 到 output/Q1_spectrum_lowfreq.csv...");
+    info!("导出低频段频谱 (0-4 kHz) 到 output/Q1_spectrum_lowfreq.csv...");
     export_spectrum_data(
         frequencies,
         magnitude,
@@ -64,7 +72,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
 
     // 3. 导出中频段频谱 (0-10 kHz)
-    println!("导出中频段频谱 (0-10 kHz) 到 output/Q1_spectrum_midfreq.csv...");
+    info!("导出中频段频谱 (0-10 kHz) 到 output/Q1_spectrum_midfreq.csv...");
     export_spectrum_data(
         frequencies,
         magnitude,
@@ -74,16 +82,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
 
     // 4. 导出时域波形数据
-    println!("导出时域波形数据到 output/Q1_waveform.csv...");
+    info!("导出时域波形数据到 output/Q1_waveform.csv...");
     export_waveform_data(&samples, sample_rate, "output/Q1_waveform.csv", Some(10000))?;
 
     // 5. 导出峰值检测结果
-    println!("导出峰值检测结果到 output/Q1_peaks.csv...");
+    info!("导出峰值检测结果到 output/Q1_peaks.csv...");
     let (f_d, peak_mag, peak_idx) = FrequencyEstimator::estimate_frequency_offset(
         frequencies,
         magnitude,
         (10.0, 10000.0),
-        true,
     );
     
     let f_d_refined = FrequencyEstimator::refined_frequency_estimate(
@@ -104,7 +111,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     export_peaks_data(&peaks, "output/Q1_peaks.csv")?;
 
     // 6. 导出能量分布数据
-    println!("导出能量分布数据到 output/Q1_energy_distribution.csv...");
+    info!("导出能量分布数据到 output/Q1_energy_distribution.csv...");
     let energy_bands = vec![
         (0.0, 500.0),
         (500.0, 1000.0),
@@ -119,13 +126,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     export_energy_distribution(magnitude, frequencies, &energy_bands, "output/Q1_energy_distribution.csv")?;
 
     // 7. 导出分析结果摘要
-    println!("导出分析结果摘要到 output/Q1_summary.csv...");
+    info!("导出分析结果摘要到 output/Q1_summary.csv...");
     export_summary(f_d, f_d_refined, peak_mag, sample_rate, num_samples, "output/Q1_summary.csv")?;
 
-    println!("\n========================================");
-    println!("导出完成！");
-    println!("所有 CSV 文件已保存到 output 目录");
-    println!("========================================\n");
+    info!("\n========================================");
+    info!("导出完成！");
+    info!("所有 CSV 文件已保存到 output 目录");
+    info!("========================================\n");
 
     Ok(())
 }
@@ -176,9 +183,9 @@ fn export_waveform_data(
     let num_samples = max_samples.unwrap_or(samples.len()).min(samples.len());
     
     // 写入数据
-    for i in 0..num_samples {
+    for (i, &sample) in samples.iter().enumerate().take(num_samples) {
         let time = i as f64 / sample_rate;
-        writeln!(file, "{:.6},{:.6}", time, samples[i])?;
+        writeln!(file, "{:.6},{:.6}", time, sample)?;
     }
     
     Ok(())
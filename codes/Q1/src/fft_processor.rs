@@ -1,6 +1,9 @@
 // 2. FFT 计算模块
 // 使用 rustfft 库对音频信号进行快速傅里叶变换
 
+use dsp_core::progress::Progress;
+use dsp_core::spectrum::{self, ShiftMethod};
+use log::debug;
 use rustfft::{FftPlanner, num_complex::Complex};
 use std::f64::consts::PI;
 
@@ -22,6 +25,18 @@ pub struct FftResult {
 impl FftResult {
     /// 计算信号的 FFT
     pub fn compute(samples: &[f64], sample_rate: f64) -> Self {
+        let n = samples.len();
+        let progress = Progress::spinner(format!("FFT（{} 点）", n));
+        let result = Self::compute_quiet(samples, sample_rate);
+        progress.finish();
+        debug!("FFT 计算完成:");
+        debug!("  FFT 点数: {}", n);
+        debug!("  频率分辨率: {:.2} Hz", sample_rate / n as f64);
+        result
+    }
+
+    /// 计算信号的 FFT（不输出信息，供蒙特卡洛评估等批量调用）
+    pub fn compute_quiet(samples: &[f64], sample_rate: f64) -> Self {
         let n = samples.len();
         let mut planner = FftPlanner::new();
         let fft = planner.plan_fft_forward(n);
@@ -52,10 +67,6 @@ impl FftResult {
             .map(|c| c.arg())
             .collect();
 
-        println!("FFT 计算完成:");
-        println!("  FFT 点数: {}", n);
-        println!("  频率分辨率: {:.2} Hz", sample_rate / n as f64);
-
         FftResult {
             spectrum: buffer,
             frequencies,
@@ -81,6 +92,15 @@ impl FftResult {
             .collect()
     }
 
+    /// 分段平均频谱：对内存中的信号使用 `ChunkedSpectrum`
+    ///
+    /// 频率分辨率为 f_s / segment_len，比整段 FFT 粗，但方差更小、内存有界
+    pub fn compute_chunked(samples: &[f64], sample_rate: f64, segment_len: usize) -> AveragedSpectrum {
+        let mut accumulator = ChunkedSpectrum::new(segment_len, sample_rate);
+        accumulator.push(samples);
+        accumulator.finish()
+    }
+
     /// 获取单边频谱（0 到 Nyquist 频率）
     pub fn get_single_sided(&self) -> (Vec<f64>, Vec<f64>) {
         let nyquist_index = self.frequencies.len() / 2;
@@ -89,6 +109,15 @@ impl FftResult {
         (freqs, mags)
     }
 
+    /// 获取以零频为中心的双边频谱（−f_s/2 到 +f_s/2，即 fftshift）
+    ///
+    /// 实信号的幅度谱关于零频对称，双边显示时 AM 边带在 ±f_d 两侧的对称性一目了然
+    pub fn get_two_sided_centered(&self) -> (Vec<f64>, Vec<f64>) {
+        let freqs = dsp_core::spectrum::centered_frequencies(self.magnitude.len(), self.sample_rate);
+        let mags = dsp_core::spectrum::fftshift(&self.magnitude);
+        (freqs, mags)
+    }
+
     /// 获取 dB 刻度的幅度谱
     pub fn get_magnitude_db(&self) -> Vec<f64> {
         self.magnitude
@@ -130,6 +159,183 @@ impl FftResult {
     }
 }
 
+/// FFT 长度处理策略
+///
+/// rustfft 对只含小素因子（2、3、5、7）的长度最快；含大素因子时退化为 Rader / Bluestein 算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FftLengthPolicy {
+    /// 保持原长度
+    Keep,
+    /// 补零到不小于原长度的最近友好长度（频率网格变密，不丢数据）
+    Pad,
+    /// 截断到不大于原长度的最近友好长度（丢弃尾部少量样本）
+    Truncate,
+}
+
+impl std::str::FromStr for FftLengthPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(FftLengthPolicy::Keep),
+            "pad" => Ok(FftLengthPolicy::Pad),
+            "truncate" => Ok(FftLengthPolicy::Truncate),
+            other => Err(format!("未知的 FFT 长度策略: {}（可选 keep / pad / truncate）", other)),
+        }
+    }
+}
+
+/// n 的最大素因子（n ≤ 1 时返回 1）
+pub fn largest_prime_factor(mut n: usize) -> usize {
+    let mut largest = 1;
+    let mut p = 2;
+    while p * p <= n {
+        while n.is_multiple_of(p) {
+            largest = p;
+            n /= p;
+        }
+        p += 1;
+    }
+    if n > 1 { n } else { largest }
+}
+
+/// 是否为 FFT 友好长度（所有素因子不超过 7）
+pub fn is_fast_length(n: usize) -> bool {
+    n > 0 && largest_prime_factor(n) <= 7
+}
+
+/// 不小于 n 的最近友好长度
+pub fn next_fast_length(n: usize) -> usize {
+    (n.max(1)..).find(|&m| is_fast_length(m)).unwrap()
+}
+
+/// 不大于 n 的最近友好长度（n = 0 时返回 0）
+pub fn previous_fast_length(n: usize) -> usize {
+    (1..=n).rev().find(|&m| is_fast_length(m)).unwrap_or(0)
+}
+
+/// 按策略调整信号长度，返回调整后的信号
+pub fn adjust_fft_length(samples: &[f64], policy: FftLengthPolicy) -> Vec<f64> {
+    match policy {
+        FftLengthPolicy::Keep => samples.to_vec(),
+        FftLengthPolicy::Pad => {
+            let mut padded = samples.to_vec();
+            padded.resize(next_fast_length(samples.len()), 0.0);
+            padded
+        }
+        FftLengthPolicy::Truncate => samples[..previous_fast_length(samples.len())].to_vec(),
+    }
+}
+
+/// 分段平均频谱（Welch 法）
+///
+/// 只保存功率谱的累加值，内存占用与段长有关而与信号总长度无关
+#[derive(Debug, Clone)]
+pub struct AveragedSpectrum {
+    /// 频率轴（Hz），0 到 Nyquist
+    pub frequencies: Vec<f64>,
+    /// 各段功率谱平均后开方得到的幅度谱，刻度与 `FftResult::compute` 的单边谱一致
+    pub magnitude: Vec<f64>,
+    /// 参与平均的段数
+    pub num_segments: usize,
+    /// 每段 FFT 点数
+    pub segment_len: usize,
+    /// 窗函数的噪声功率增益 Σw² / (Σw)²：方差 σ² 的白噪声在每个 bin 上的功率为 σ² 乘以该值
+    pub noise_gain: f64,
+    /// 采样率
+    pub sample_rate: f64,
+}
+
+/// 流式分段频谱累加器
+///
+/// 以 Hann 窗、50% 重叠把任意长度的数据块切成固定长度的段，逐段 FFT 并累加功率谱。
+/// 数据可以分多次 `push`，每次长度任意。
+pub struct ChunkedSpectrum {
+    segment_len: usize,
+    hop: usize,
+    sample_rate: f64,
+    window: Vec<f64>,
+    window_sum: f64,
+    fft: std::sync::Arc<dyn rustfft::Fft<f64>>,
+    pending: Vec<f64>,
+    power_sum: Vec<f64>,
+    num_segments: usize,
+}
+
+impl ChunkedSpectrum {
+    /// 创建累加器，segment_len 为每段 FFT 点数
+    pub fn new(segment_len: usize, sample_rate: f64) -> Self {
+        let segment_len = segment_len.max(2);
+        let window: Vec<f64> = (0..segment_len)
+            .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f64 / (segment_len - 1) as f64).cos()))
+            .collect();
+        let window_sum = window.iter().sum();
+        ChunkedSpectrum {
+            segment_len,
+            hop: segment_len / 2,
+            sample_rate,
+            window,
+            window_sum,
+            fft: FftPlanner::new().plan_fft_forward(segment_len),
+            pending: Vec::with_capacity(segment_len),
+            power_sum: vec![0.0; segment_len / 2 + 1],
+            num_segments: 0,
+        }
+    }
+
+    /// 追加一块数据，凑满一段即计算
+    pub fn push(&mut self, chunk: &[f64]) {
+        for &x in chunk {
+            self.pending.push(x);
+            if self.pending.len() == self.segment_len {
+                self.process_segment();
+                self.pending.drain(..self.hop);
+            }
+        }
+    }
+
+    fn process_segment(&mut self) {
+        let mut buffer: Vec<Complex<f64>> = self
+            .pending
+            .iter()
+            .zip(self.window.iter())
+            .map(|(&x, &w)| Complex::new(x * w, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+        for (acc, c) in self.power_sum.iter_mut().zip(buffer.iter()) {
+            *acc += c.norm_sqr();
+        }
+        self.num_segments += 1;
+    }
+
+    /// 结束累加。不足一段的尾部数据补零后作为最后一段（若此前没有完整段）
+    pub fn finish(mut self) -> AveragedSpectrum {
+        if self.num_segments == 0 && !self.pending.is_empty() {
+            self.pending.resize(self.segment_len, 0.0);
+            self.process_segment();
+        }
+
+        let count = self.num_segments.max(1) as f64;
+        let magnitude = self
+            .power_sum
+            .iter()
+            .map(|&p| (p / count).sqrt() / self.window_sum)
+            .collect();
+        let frequencies = (0..self.power_sum.len())
+            .map(|k| k as f64 * self.sample_rate / self.segment_len as f64)
+            .collect();
+
+        AveragedSpectrum {
+            frequencies,
+            magnitude,
+            num_segments: self.num_segments,
+            segment_len: self.segment_len,
+            noise_gain: self.window.iter().map(|w| w * w).sum::<f64>() / (self.window_sum * self.window_sum),
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
 /// 频谱搬移（循环移位）
 pub fn circshift(spectrum: &[Complex<f64>], shift: isize) -> Vec<Complex<f64>> {
     let n = spectrum.len();
@@ -143,11 +349,18 @@ pub fn circshift(spectrum: &[Complex<f64>], shift: isize) -> Vec<Complex<f64>> {
 }
 
 /// 计算频域搬移后的和（用于解调）
+///
+/// `ShiftMethod::NearestBin` 将搬移量取整到最近的频点，误差最多半个频点；
+/// `ShiftMethod::Mixing` 回到时域乘以 cos(2π f t) 后再做 FFT，可按任意非整数频点精确搬移。
 pub fn frequency_shift_and_add(
     spectrum: &[Complex<f64>],
     shift_hz: f64,
     sample_rate: f64,
+    method: ShiftMethod,
 ) -> Vec<Complex<f64>> {
+    if method == ShiftMethod::Mixing {
+        return spectrum::shift_and_add(spectrum, shift_hz, sample_rate, method);
+    }
     let n = spectrum.len();
     let shift_bins = (shift_hz * n as f64 / sample_rate).round() as isize;
 
@@ -198,6 +411,74 @@ mod tests {
         assert!(error < 1e-10);
     }
 
+    #[test]
+    fn test_two_sided_centered() {
+        // 10 Hz 正弦波的双边频谱应在 ±10 Hz 处出现对称峰值
+        let sample_rate = 100.0;
+        let n = 101;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * 10.0 * i as f64 / sample_rate).cos())
+            .collect();
+        let fft_result = FftResult::compute(&samples, sample_rate);
+        let (freqs, mags) = fft_result.get_two_sided_centered();
+
+        assert_eq!(freqs.len(), n);
+        assert!(freqs[0] < 0.0 && freqs[n - 1] > 0.0);
+        assert_eq!(freqs[n / 2], 0.0);
+        let peak = mags.iter().cloned().fold(0.0f64, f64::max);
+        let peak_freqs: Vec<f64> = freqs
+            .iter()
+            .zip(mags.iter())
+            .filter(|(_, &m)| m > 0.9 * peak)
+            .map(|(&f, _)| f)
+            .collect();
+        assert_eq!(peak_freqs.len(), 2);
+        assert!((peak_freqs[0] + peak_freqs[1]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chunked_spectrum_matches_tone() {
+        // 分段平均频谱的峰值位置与幅度应与整段 FFT 一致（幅度 A 的正弦对应 A/2）
+        let sample_rate = 1024.0;
+        let n = 16384;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| 0.8 * (2.0 * PI * 128.0 * i as f64 / sample_rate).sin())
+            .collect();
+
+        let averaged = FftResult::compute_chunked(&samples, sample_rate, 1024);
+        assert_eq!(averaged.num_segments, 31);
+        let (peak_idx, &peak) = averaged
+            .magnitude
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        assert_eq!(averaged.frequencies[peak_idx], 128.0);
+        assert!((peak - 0.4).abs() < 1e-6);
+
+        // 分多次、长度任意地送入数据，结果不变
+        let mut streaming = ChunkedSpectrum::new(1024, sample_rate);
+        for chunk in samples.chunks(777) {
+            streaming.push(chunk);
+        }
+        let streamed = streaming.finish();
+        assert_eq!(streamed.num_segments, averaged.num_segments);
+        assert!((streamed.magnitude[peak_idx] - peak).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fast_length_helpers() {
+        // 31265 = 5 × 13² × 37
+        assert_eq!(largest_prime_factor(31265), 37);
+        assert!(!is_fast_length(31265));
+        assert!(is_fast_length(31360)); // 2^7 × 5 × 7²
+        assert_eq!(next_fast_length(31265), 31360);
+        assert_eq!(previous_fast_length(31265), 31250); // 2 × 5^6
+        assert_eq!(adjust_fft_length(&[1.0; 11], FftLengthPolicy::Pad).len(), 12);
+        assert_eq!(adjust_fft_length(&[1.0; 11], FftLengthPolicy::Truncate).len(), 10);
+        assert_eq!("pad".parse::<FftLengthPolicy>(), Ok(FftLengthPolicy::Pad));
+    }
+
     #[test]
     fn test_circshift() {
         let data: Vec<Complex<f64>> = (0..5)
@@ -1,5 +1,94 @@
 // 4. 频率偏差估计模块
 // 通过分析频谱找出频率偏差 f_d
+use crate::fft_processor::AveragedSpectrum;
+use dsp_core::peaks;
+use log::{debug, info};
+
+// 对称峰值对与峰值检测放在 dsp_core 中，浏览器版本（codes/wasm）使用同一实现
+pub use dsp_core::peaks::SymmetricPair;
+
+/// 峰值插值所使用的幅度刻度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterpolationScale {
+    /// 线性幅度（原始三点抛物线插值）
+    Linear,
+    /// dB 幅度（加窗频谱主瓣在对数域更接近抛物线）
+    Decibel,
+}
+
+/// 峰值插值结果
+#[derive(Debug, Clone, Copy)]
+pub struct PeakInterpolation {
+    /// 插值后的峰值频率 (Hz)
+    pub frequency: f64,
+    /// 相对峰值 bin 的偏移（单位：bin）
+    pub offset_bins: f64,
+    /// 参与拟合的点数
+    pub num_points: usize,
+    /// 拟合残差的均方根（与所选刻度同单位）
+    pub residual_rms: f64,
+    /// 估计的插值误差 (Hz)
+    pub error_estimate: f64,
+}
+
+/// 频率估计的不确定度（均为标准差，单位 Hz）
+#[derive(Debug, Clone, Copy)]
+pub struct FrequencyUncertainty {
+    /// FFT 频率网格量化误差 Δf/√12
+    pub resolution: f64,
+    /// 峰值处的估计信噪比 (dB)
+    pub snr_db: f64,
+    /// 由 Cramér–Rao 下界给出的噪声引起的标准差
+    pub crlb: f64,
+    /// 峰值插值误差（拟合残差传播）
+    pub interpolation: f64,
+    /// 合成标准不确定度（各分量平方和开方）
+    pub std_dev: f64,
+}
+
+impl FrequencyUncertainty {
+    /// 对称轴 f_d = (f_lower + f_upper) / 2 的不确定度
+    ///
+    /// 两个峰值的误差视为独立，各分量按 σ = √(σ₁² + σ₂²) / 2 合成，信噪比取较小者
+    pub fn symmetric_axis(lower: &Self, upper: &Self) -> Self {
+        let half_rss = |a: f64, b: f64| (a * a + b * b).sqrt() / 2.0;
+        let resolution = half_rss(lower.resolution, upper.resolution);
+        let crlb = half_rss(lower.crlb, upper.crlb);
+        let interpolation = half_rss(lower.interpolation, upper.interpolation);
+        FrequencyUncertainty {
+            resolution,
+            snr_db: lower.snr_db.min(upper.snr_db),
+            crlb,
+            interpolation,
+            std_dev: (resolution.powi(2) + crlb.powi(2) + interpolation.powi(2)).sqrt(),
+        }
+    }
+
+    /// 正态近似下的置信区间，z 为分位数（95% 对应 1.96）
+    pub fn confidence_interval(&self, estimate: f64, z: f64) -> (f64, f64) {
+        (estimate - z * self.std_dev, estimate + z * self.std_dev)
+    }
+}
+
+/// 单个频带的能量
+#[derive(Debug, Clone, Copy)]
+pub struct BandEnergy {
+    /// 频带下边界 (Hz)
+    pub low: f64,
+    /// 频带上边界 (Hz)
+    pub high: f64,
+    /// 频带内幅度平方和
+    pub energy: f64,
+    /// 占单边谱总能量的百分比
+    pub percentage: f64,
+}
+
+impl BandEnergy {
+    /// 频带标签，例如 "1000-4000 Hz"
+    pub fn label(&self) -> String {
+        format!("{:.0}-{:.0} Hz", self.low, self.high)
+    }
+}
 
 /// 频率偏差估计器
 pub struct FrequencyEstimator;
@@ -11,77 +100,211 @@ impl FrequencyEstimator {
     /// - frequencies: 频率轴
     /// - magnitude: 幅度谱
     /// - search_range: 搜索范围 (Hz)，例如 (10.0, 10000.0)
-    /// - exclude_dc: 是否排除直流分量
-    /// 
+    ///
+    /// 直流分量应在 FFT 之前由 `preprocess` 模块去除
     /// # 返回
     /// (peak_frequency, peak_magnitude, peak_index)
     pub fn estimate_frequency_offset(
         frequencies: &[f64],
         magnitude: &[f64],
         search_range: (f64, f64),
-        exclude_dc: bool,
     ) -> (f64, f64, usize) {
-        let (min_freq, max_freq) = search_range;
-        
-        // 在指定范围内搜索峰值
-        let mut peak_magnitude = 0.0;
-        let mut peak_index = 0;
-        let mut peak_frequency = 0.0;
-
-        let start_idx = if exclude_dc { 1 } else { 0 };
-
-        for (i, (&freq, &mag)) in frequencies
-            .iter()
-            .zip(magnitude.iter())
-            .enumerate()
-            .skip(start_idx)
-        {
-            if freq >= min_freq && freq <= max_freq && mag > peak_magnitude {
-                peak_magnitude = mag;
-                peak_index = i;
-                peak_frequency = freq;
-            }
-        }
+        let (peak_frequency, peak_magnitude, peak_index) =
+            Self::peak_in_range(frequencies, magnitude, search_range);
 
-        println!("频率偏差估计结果:");
-        println!("  峰值频率 f_d = {:.2} Hz", peak_frequency);
-        println!("  峰值幅度 = {:.6}", peak_magnitude);
-        println!("  峰值索引 = {}", peak_index);
+        info!("频率偏差估计结果:");
+        info!("  峰值频率 f_d = {:.2} Hz", peak_frequency);
+        info!("  峰值幅度 = {:.6}", peak_magnitude);
+        info!("  峰值索引 = {}", peak_index);
 
         (peak_frequency, peak_magnitude, peak_index)
     }
 
+    /// 搜索范围内的最大峰值（不输出信息，供蒙特卡洛评估等批量调用）
+    ///
+    /// # 返回
+    /// (peak_frequency, peak_magnitude, peak_index)；范围内没有正幅度时 index 为 0
+    pub fn peak_in_range(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        search_range: (f64, f64),
+    ) -> (f64, f64, usize) {
+        peaks::peak_in_range(frequencies, magnitude, search_range)
+    }
+
     /// 精确估计频率（使用抛物线插值）
     pub fn refined_frequency_estimate(
         frequencies: &[f64],
         magnitude: &[f64],
         peak_index: usize,
     ) -> f64 {
-        if peak_index == 0 || peak_index >= magnitude.len() - 1 {
-            return frequencies[peak_index];
+        let result = Self::interpolate_peak(
+            frequencies,
+            magnitude,
+            peak_index,
+            InterpolationScale::Linear,
+            1,
+        );
+
+        debug!("精确频率估计:");
+        debug!("  原始峰值频率: {:.2} Hz", frequencies[peak_index]);
+        debug!("  精确频率: {:.4} Hz", result.frequency);
+
+        result.frequency
+    }
+
+    /// 峰值抛物线插值（可选 dB 刻度与多点最小二乘拟合）
+    ///
+    /// # 参数
+    /// - frequencies: 频率轴
+    /// - magnitude: 幅度谱（线性）
+    /// - peak_index: 峰值所在的 bin
+    /// - scale: 拟合所用的幅度刻度
+    /// - half_width: 峰值两侧各取的点数，1 为经典三点插值，更大时使用最小二乘拟合
+    ///
+    /// # 返回
+    /// 插值结果。误差估计：多于三点时由拟合残差传播到顶点位置的标准差给出；
+    /// 三点拟合没有残差自由度，改用线性与 dB 两种刻度的顶点差作为模型误差估计。
+    pub fn interpolate_peak(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        peak_index: usize,
+        scale: InterpolationScale,
+        half_width: usize,
+    ) -> PeakInterpolation {
+        let freq_resolution = if frequencies.len() > 1 {
+            frequencies[1] - frequencies[0]
+        } else {
+            1.0
+        };
+
+        let half_width = half_width.max(1);
+        let unrefined = PeakInterpolation {
+            frequency: frequencies[peak_index],
+            offset_bins: 0.0,
+            num_points: 1,
+            residual_rms: 0.0,
+            error_estimate: 0.5 * freq_resolution,
+        };
+        if peak_index < half_width || peak_index + half_width >= magnitude.len() {
+            return unrefined;
         }
 
-        // 使用三点抛物线插值
-        let y1 = magnitude[peak_index - 1];
-        let y2 = magnitude[peak_index];
-        let y3 = magnitude[peak_index + 1];
+        let fit = match fit_parabola(magnitude, peak_index, half_width, scale) {
+            Some(fit) => fit,
+            None => return unrefined,
+        };
+        // 顶点偏移限制在 ±half_width 之内，防止病态拟合跑出窗口
+        let offset = fit.vertex.clamp(-(half_width as f64), half_width as f64);
 
-        // 抛物线顶点位置
-        let delta = 0.5 * (y1 - y3) / (y1 - 2.0 * y2 + y3);
-        
+        let error_bins = if fit.num_points > 3 {
+            fit.vertex_std
+        } else {
+            let other_scale = match scale {
+                InterpolationScale::Linear => InterpolationScale::Decibel,
+                InterpolationScale::Decibel => InterpolationScale::Linear,
+            };
+            fit_parabola(magnitude, peak_index, half_width, other_scale)
+                .map(|other| (other.vertex - fit.vertex).abs())
+                .unwrap_or(0.5)
+        };
+
+        PeakInterpolation {
+            frequency: frequencies[peak_index] + offset * freq_resolution,
+            offset_bins: offset,
+            num_points: fit.num_points,
+            residual_rms: fit.residual_rms,
+            error_estimate: error_bins * freq_resolution,
+        }
+    }
+
+    /// 估计单个频谱峰值的频率不确定度
+    ///
+    /// 三个分量：
+    /// - FFT 频率网格的量化误差 Δf/√12
+    /// - 噪声引起的误差：单频信号频率估计的 Cramér–Rao 下界
+    ///   σ_f = f_s / (2π) · √(12 / (η·N·(N²−1)))，η 为每采样点信噪比，
+    ///   由峰值与噪声底（单边谱功率的中位数 / ln 2）之比换算
+    /// - 五点 dB 最小二乘插值的残差传播误差
+    ///
+    /// # 参数
+    /// - frequencies: 频率轴
+    /// - magnitude: 幅度谱（未加窗 FFT）
+    /// - peak_index: 峰值索引
+    pub fn frequency_uncertainty(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        peak_index: usize,
+    ) -> FrequencyUncertainty {
+        let n = magnitude.len();
         let freq_resolution = if frequencies.len() > 1 {
             frequencies[1] - frequencies[0]
         } else {
             1.0
         };
+        let sample_rate = freq_resolution * n as f64;
+
+        // 噪声功率：复高斯噪声的 |X|² 服从指数分布，中位数 = 均值 · ln 2
+        let noise_power = median_power(&magnitude[..(n / 2).max(1)]) / std::f64::consts::LN_2;
 
-        let refined_freq = frequencies[peak_index] + delta * freq_resolution;
+        // 实正弦幅度 A 在 bin 上的功率为 (A·N/2)²，噪声为 N·σ²，
+        // 二者之比等于 η·N/2（η = A²/(2σ²)）
+        let peak_ratio = magnitude[peak_index].powi(2) / noise_power;
+        let eta = 2.0 * peak_ratio / n as f64;
+        let crlb = tone_crlb(sample_rate, eta, n);
+        Self::combine_uncertainty(frequencies, magnitude, peak_index, eta, crlb)
+    }
 
-        println!("精确频率估计:");
-        println!("  原始峰值频率: {:.2} Hz", frequencies[peak_index]);
-        println!("  精确频率: {:.4} Hz", refined_freq);
+    /// 分段平均频谱（Welch 法，见 `ChunkedSpectrum`）上单个峰值的频率不确定度
+    ///
+    /// 分量与 [`frequency_uncertainty`](Self::frequency_uncertainty) 相同，按平均谱的构成换算：
+    /// - 频率网格为 f_s / L（L 为段长），噪声底取 0 到 Nyquist 的全部单边 bin
+    /// - K 段平均后每个 bin 的功率近似服从 Γ(K, 1/K)，中位数约为均值的 (1 − 1/(9K))³
+    /// - 幅度按窗函数之和归一化，白噪声在 bin 上的功率为 σ²·Σw²/(Σw)²，
+    ///   正弦为 (A/2)²，故 η = 2·(峰值/噪声)·Σw²/(Σw)²
+    /// - CRLB 按一段 L 点计算，再除以 √K（K 段各自估计后平均）；50% 重叠的相邻段并不独立，
+    ///   这一项略偏乐观
+    pub fn averaged_frequency_uncertainty(spectrum: &AveragedSpectrum, peak_index: usize) -> FrequencyUncertainty {
+        let segments = spectrum.num_segments.max(1) as f64;
+        let median_ratio = (1.0 - 1.0 / (9.0 * segments)).powi(3);
+        let noise_power = median_power(&spectrum.magnitude) / median_ratio;
+
+        let peak_ratio = spectrum.magnitude[peak_index].powi(2) / noise_power;
+        let eta = 2.0 * peak_ratio * spectrum.noise_gain;
+        let crlb = tone_crlb(spectrum.sample_rate, eta, spectrum.segment_len) / segments.sqrt();
+        Self::combine_uncertainty(&spectrum.frequencies, &spectrum.magnitude, peak_index, eta, crlb)
+    }
 
-        refined_freq
+    /// 由每采样点信噪比 η 与噪声分量 CRLB 补齐网格量化与插值分量，合成不确定度
+    fn combine_uncertainty(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        peak_index: usize,
+        eta: f64,
+        crlb: f64,
+    ) -> FrequencyUncertainty {
+        let freq_resolution = if frequencies.len() > 1 {
+            frequencies[1] - frequencies[0]
+        } else {
+            1.0
+        };
+        let interpolation = Self::interpolate_peak(
+            frequencies,
+            magnitude,
+            peak_index,
+            InterpolationScale::Decibel,
+            2,
+        )
+        .error_estimate;
+
+        let resolution = freq_resolution / 12f64.sqrt();
+        FrequencyUncertainty {
+            resolution,
+            snr_db: 10.0 * eta.log10(),
+            crlb,
+            interpolation,
+            std_dev: (resolution.powi(2) + crlb.powi(2) + interpolation.powi(2)).sqrt(),
+        }
     }
 
     /// 寻找多个峰值
@@ -92,92 +315,213 @@ impl FrequencyEstimator {
         min_distance: usize,
         threshold: f64,
     ) -> Vec<(f64, f64, usize)> {
-        let mut peaks = Vec::new();
-        let n = magnitude.len();
+        let filtered_peaks =
+            Self::detect_peaks(frequencies, magnitude, num_peaks, min_distance, threshold);
 
-        // 找出所有局部最大值
-        for i in 1..n-1 {
-            if magnitude[i] > magnitude[i-1] 
-                && magnitude[i] > magnitude[i+1] 
-                && magnitude[i] > threshold 
-            {
-                peaks.push((frequencies[i], magnitude[i], i));
-            }
+        debug!("找到 {} 个峰值:", filtered_peaks.len());
+        for (i, (freq, mag, idx)) in filtered_peaks.iter().enumerate() {
+            debug!("  峰值 {}: 频率 = {:.2} Hz, 幅度 = {:.6}, 索引 = {}", 
+                     i + 1, freq, mag, idx);
         }
 
-        // 按幅度降序排序
-        peaks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        filtered_peaks
+    }
 
-        // 移除距离太近的峰值
-        let mut filtered_peaks = Vec::new();
-        for peak in peaks {
-            let is_far_enough = filtered_peaks
-                .iter()
-                .all(|(_, _, idx)| (*idx as isize - peak.2 as isize).abs() >= min_distance as isize);
-            
-            if is_far_enough {
-                filtered_peaks.push(peak);
-                if filtered_peaks.len() >= num_peaks {
-                    break;
+    /// 峰值检测（不输出信息，供逐帧分析等批量调用）
+    ///
+    /// # 返回
+    /// 按幅度降序排列的 (frequency, magnitude, index)
+    pub fn detect_peaks(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        num_peaks: usize,
+        min_distance: usize,
+        threshold: f64,
+    ) -> Vec<(f64, f64, usize)> {
+        peaks::detect_peaks(frequencies, magnitude, num_peaks, min_distance, threshold)
+    }
+
+    /// 寻找对称峰值对（幅度相近的峰值）
+    ///
+    /// # 参数
+    /// - peaks: 峰值列表 (frequency, magnitude, index)
+    /// - max_freq: 只考虑该频率以下的峰值，频率偏差应在此范围内
+    /// - min_ratio: 两峰幅度比下限，例如 0.9 表示幅度相差小于 10%
+    pub fn find_symmetric_pairs(
+        peaks: &[(f64, f64, usize)],
+        max_freq: f64,
+        min_ratio: f64,
+    ) -> Vec<SymmetricPair> {
+        peaks::symmetric_pairs(peaks, max_freq, min_ratio)
+    }
+
+    /// 选择最佳的对称峰值对（幅度最大的对称峰值对）
+    pub fn best_symmetric_pair(pairs: &[SymmetricPair]) -> Option<SymmetricPair> {
+        peaks::best_pair(pairs)
+    }
+
+    /// 子空间法（MUSIC）高分辨率频率估计
+    ///
+    /// 在时域信号的短自相关矩阵上做特征分解，用噪声子空间构造伪谱并在搜索范围内找峰，
+    /// 分辨率不受 FFT bin 宽度限制，适合 FFT 中两个相距很近、已经合并的边带峰值。
+    ///
+    /// # 参数
+    /// - samples: 时域信号
+    /// - sample_rate: 采样率 (Hz)
+    /// - num_sinusoids: 信号中实正弦分量的个数（每个实正弦占两维信号子空间）
+    /// - order: 自相关矩阵阶数，需大于 2 × num_sinusoids
+    /// - search_range: 伪谱搜索范围 (Hz)
+    ///
+    /// # 返回
+    /// 搜索范围内伪谱最大的 num_sinusoids 个频率（升序）
+    pub fn music_frequency_estimate(
+        samples: &[f64],
+        sample_rate: f64,
+        num_sinusoids: usize,
+        order: usize,
+        search_range: (f64, f64),
+    ) -> Vec<f64> {
+        let signal_dim = 2 * num_sinusoids;
+        if num_sinusoids == 0 || order <= signal_dim || samples.len() <= order {
+            return Vec::new();
+        }
+
+        // 由长度为 order 的滑动快照构造自相关矩阵 R = (1/K)·Σ x_k·x_kᵀ，
+        // 并做前后向平均（实信号下即与反序快照的矩阵取平均），避免有偏估计对大延迟的压缩
+        let n = samples.len();
+        let num_snapshots = n - order + 1;
+        let mut matrix = vec![vec![0.0; order]; order];
+        for snapshot in samples.windows(order) {
+            for i in 0..order {
+                let xi = snapshot[i];
+                for j in i..order {
+                    matrix[i][j] += xi * snapshot[j];
                 }
             }
         }
+        let forward_only = matrix.clone();
+        for i in 0..order {
+            for j in i..order {
+                let forward = forward_only[i][j];
+                let backward = forward_only[order - 1 - j][order - 1 - i];
+                let value = 0.5 * (forward + backward) / num_snapshots as f64;
+                matrix[i][j] = value;
+                matrix[j][i] = value;
+            }
+        }
 
-        println!("找到 {} 个峰值:", filtered_peaks.len());
-        for (i, (freq, mag, idx)) in filtered_peaks.iter().enumerate() {
-            println!("  峰值 {}: 频率 = {:.2} Hz, 幅度 = {:.6}, 索引 = {}", 
-                     i + 1, freq, mag, idx);
+        // 特征值降序排列后，后 order - 2p 个特征向量张成噪声子空间
+        let (eigenvalues, eigenvectors) = symmetric_eigen(matrix);
+        let mut idx: Vec<usize> = (0..order).collect();
+        idx.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+        let noise_subspace: Vec<Vec<f64>> = idx[signal_dim..]
+            .iter()
+            .map(|&k| (0..order).map(|m| eigenvectors[m][k]).collect())
+            .collect();
+
+        let pseudo_spectrum = |freq: f64| -> f64 {
+            let omega = 2.0 * std::f64::consts::PI * freq / sample_rate;
+            let denom: f64 = noise_subspace
+                .iter()
+                .map(|v| {
+                    let (re, im) = v.iter().enumerate().fold((0.0, 0.0), |(re, im), (m, &vm)| {
+                        let phase = omega * m as f64;
+                        (re + vm * phase.cos(), im - vm * phase.sin())
+                    });
+                    re * re + im * im
+                })
+                .sum();
+            1.0 / denom.max(1e-300)
+        };
+
+        // 粗网格搜索局部极大值，再在每个峰附近细化
+        let (min_freq, max_freq) = search_range;
+        let grid_points = 4000;
+        let step = (max_freq - min_freq) / grid_points as f64;
+        let grid: Vec<(f64, f64)> = (0..=grid_points)
+            .map(|i| {
+                let f = min_freq + i as f64 * step;
+                (f, pseudo_spectrum(f))
+            })
+            .collect();
+        let mut peaks: Vec<(f64, f64)> = (1..grid.len() - 1)
+            .filter(|&i| grid[i].1 > grid[i - 1].1 && grid[i].1 >= grid[i + 1].1)
+            .map(|i| grid[i])
+            .collect();
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        peaks.truncate(num_sinusoids);
+
+        let mut estimates: Vec<f64> = peaks
+            .iter()
+            .map(|&(f0, _)| {
+                let fine_step = step / 100.0;
+                (-100..=100)
+                    .map(|k| f0 + k as f64 * fine_step)
+                    .map(|f| (f, pseudo_spectrum(f)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(f, _)| f)
+                    .unwrap_or(f0)
+            })
+            .collect();
+        estimates.sort_by(|a, b| a.total_cmp(b));
+
+        info!("MUSIC 高分辨率频率估计 (阶数 {}, {} 个正弦分量):", order, num_sinusoids);
+        for (i, f) in estimates.iter().enumerate() {
+            info!("  分量 {}: {:.4} Hz", i + 1, f);
         }
 
-        filtered_peaks
+        estimates
     }
 
     /// 计算信号的能量分布
+    ///
+    /// 百分比相对于单边谱（0 ~ f_s/2）的总能量；不输出信息
     pub fn compute_energy_distribution(
         magnitude: &[f64],
         frequencies: &[f64],
         bands: &[(f64, f64)], // 频带范围
-    ) -> Vec<(String, f64)> {
-        let total_energy: f64 = magnitude.iter().map(|&m| m * m).sum();
-        
-        let mut band_energies = Vec::new();
-        
-        for (low, high) in bands {
-            let energy: f64 = frequencies
-                .iter()
-                .zip(magnitude.iter())
-                .filter(|(&f, _)| f >= *low && f <= *high)
-                .map(|(_, &m)| m * m)
-                .sum();
-            
-            let percentage = (energy / total_energy) * 100.0;
-            band_energies.push((
-                format!("{:.0}-{:.0} Hz", low, high),
-                percentage,
-            ));
-        }
-
-        println!("\n能量分布:");
-        for (band, percent) in &band_energies {
-            println!("  {}: {:.2}%", band, percent);
-        }
+    ) -> Vec<BandEnergy> {
+        let nyquist = frequencies.len() as f64 / 2.0
+            * frequencies.get(1).copied().unwrap_or(0.0);
+        let total_energy: f64 = frequencies
+            .iter()
+            .zip(magnitude.iter())
+            .filter(|(&f, _)| f <= nyquist)
+            .map(|(_, &m)| m * m)
+            .sum();
 
-        band_energies
+        bands
+            .iter()
+            .map(|&(low, high)| {
+                let energy: f64 = frequencies
+                    .iter()
+                    .zip(magnitude.iter())
+                    .filter(|(&f, _)| f >= low && f <= high)
+                    .map(|(_, &m)| m * m)
+                    .sum();
+                let percentage = if total_energy > 0.0 {
+                    energy / total_energy * 100.0
+                } else {
+                    0.0
+                };
+                BandEnergy { low, high, energy, percentage }
+            })
+            .collect()
     }
 
     /// 判断 f_c_tilde 与 f_c 的大小关系
     /// 
     /// 注意：仅从频谱的对称性很难直接判断，通常需要相位信息或其他先验知识
     pub fn analyze_frequency_relationship(
-        frequencies: &[f64],
-        magnitude: &[f64],
+        _frequencies: &[f64],
+        _magnitude: &[f64],
         f_d: f64,
     ) -> String {
-        println!("\n频率关系分析:");
-        println!("  估计的频率偏差 f_d = {:.2} Hz", f_d);
-        println!("  仅从幅度谱无法唯一确定 f_c_tilde > f_c 还是 f_c_tilde < f_c");
-        println!("  原因：频谱的对称性使得两种情况产生相同的幅度谱");
-        println!("  需要：相位信息、时域分析或其他先验知识来确定符号");
+        info!("\n频率关系分析:");
+        info!("  估计的频率偏差 f_d = {:.2} Hz", f_d);
+        info!("  仅从幅度谱无法唯一确定 f_c_tilde > f_c 还是 f_c_tilde < f_c");
+        info!("  原因：频谱的对称性使得两种情况产生相同的幅度谱");
+        info!("  需要：相位信息、时域分析或其他先验知识来确定符号");
         
         String::from("无法仅从幅度谱确定频率偏差的符号")
     }
@@ -204,15 +548,178 @@ impl FrequencyEstimator {
             f64::INFINITY
         };
 
-        println!("\n信噪比估计:");
-        println!("  信号功率: {:.6}", signal_power);
-        println!("  噪声功率: {:.6}", noise_power);
-        println!("  SNR: {:.2} dB", snr_db);
+        info!("\n信噪比估计:");
+        info!("  信号功率: {:.6}", signal_power);
+        info!("  噪声功率: {:.6}", noise_power);
+        info!("  SNR: {:.2} dB", snr_db);
 
         snr_db
     }
 }
 
+/// 实对称矩阵特征分解（循环 Jacobi 法）
+///
+/// 返回 (特征值, 特征向量矩阵)，第 k 列为第 k 个特征值对应的特征向量
+fn symmetric_eigen(mut a: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = a.len();
+    let mut v: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for _sweep in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        let scale: f64 = (0..n).map(|i| a[i][i] * a[i][i]).sum();
+        if off_diagonal <= 1e-24 * scale.max(1e-300) {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let akp = row[p];
+                    let akq = row[q];
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let row_p = a[p].clone();
+                let row_q = a[q].clone();
+                a[p] = row_p.iter().zip(&row_q).map(|(&x, &y)| c * x - s * y).collect();
+                a[q] = row_p.iter().zip(&row_q).map(|(&x, &y)| s * x + c * y).collect();
+                for row in v.iter_mut() {
+                    let vkp = row[p];
+                    let vkq = row[q];
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let eigenvalues = (0..n).map(|i| a[i][i]).collect();
+    (eigenvalues, v)
+}
+
+/// 抛物线拟合结果（横坐标为相对峰值的 bin 偏移）
+struct ParabolaFit {
+    vertex: f64,
+    vertex_std: f64,
+    residual_rms: f64,
+    num_points: usize,
+}
+
+/// 在峰值附近对 y = a·x² + b·x + c 做最小二乘拟合
+fn fit_parabola(
+    magnitude: &[f64],
+    peak_index: usize,
+    half_width: usize,
+    scale: InterpolationScale,
+) -> Option<ParabolaFit> {
+    let points: Vec<(f64, f64)> = (peak_index - half_width..=peak_index + half_width)
+        .map(|i| {
+            let x = i as f64 - peak_index as f64;
+            let y = match scale {
+                InterpolationScale::Linear => magnitude[i],
+                InterpolationScale::Decibel => 20.0 * magnitude[i].max(1e-12).log10(),
+            };
+            (x, y)
+        })
+        .collect();
+    let n = points.len();
+
+    // 正规方程 XᵀX·[a, b, c]ᵀ = Xᵀy，其中 X 的行为 [x², x, 1]
+    let mut xtx = [[0.0; 3]; 3];
+    let mut xty = [0.0; 3];
+    for &(x, y) in &points {
+        let row = [x * x, x, 1.0];
+        for r in 0..3 {
+            for c in 0..3 {
+                xtx[r][c] += row[r] * row[c];
+            }
+            xty[r] += row[r] * y;
+        }
+    }
+    let inv = invert_3x3(&xtx)?;
+    let coef: Vec<f64> = (0..3)
+        .map(|r| (0..3).map(|c| inv[r][c] * xty[c]).sum())
+        .collect();
+    let (a, b, c) = (coef[0], coef[1], coef[2]);
+    if a >= 0.0 {
+        // 开口向上说明不是峰值
+        return None;
+    }
+
+    let sse: f64 = points
+        .iter()
+        .map(|&(x, y)| (y - (a * x * x + b * x + c)).powi(2))
+        .sum();
+    let residual_rms = (sse / n as f64).sqrt();
+
+    // 顶点 δ = -b / (2a)，通过系数协方差 σ²(XᵀX)⁻¹ 一阶传播得到 δ 的标准差
+    let vertex = -b / (2.0 * a);
+    let vertex_std = if n > 3 {
+        let sigma2 = sse / (n - 3) as f64;
+        let da = b / (2.0 * a * a);
+        let db = -1.0 / (2.0 * a);
+        let var = sigma2 * (da * da * inv[0][0] + 2.0 * da * db * inv[0][1] + db * db * inv[1][1]);
+        var.max(0.0).sqrt()
+    } else {
+        0.0
+    };
+
+    Some(ParabolaFit {
+        vertex,
+        vertex_std,
+        residual_rms,
+        num_points: n,
+    })
+}
+
+/// 3×3 矩阵求逆（伴随矩阵法）
+fn invert_3x3(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cof = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let det = m[0][0] * cof(1, 2, 1, 2) - m[0][1] * cof(1, 2, 0, 2) + m[0][2] * cof(1, 2, 0, 1);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let adj = [
+        [cof(1, 2, 1, 2), -cof(0, 2, 1, 2), cof(0, 1, 1, 2)],
+        [-cof(1, 2, 0, 2), cof(0, 2, 0, 2), -cof(0, 1, 0, 2)],
+        [cof(1, 2, 0, 1), -cof(0, 2, 0, 1), cof(0, 1, 0, 1)],
+    ];
+    let mut inv = [[0.0; 3]; 3];
+    for r in 0..3 {
+        for c in 0..3 {
+            inv[r][c] = adj[r][c] / det;
+        }
+    }
+    Some(inv)
+}
+
+/// 幅度谱各 bin 功率的中位数（不小于最小正数，避免除零）
+fn median_power(magnitude: &[f64]) -> f64 {
+    let mut power: Vec<f64> = magnitude.iter().map(|m| m * m).collect();
+    power.sort_by(|a, b| a.total_cmp(b));
+    power.get(power.len() / 2).copied().unwrap_or(0.0).max(f64::MIN_POSITIVE)
+}
+
+/// N 点单频信号频率估计的 Cramér–Rao 下界 σ_f = f_s / (2π) · √(12 / (η·N·(N²−1)))
+fn tone_crlb(sample_rate: f64, eta: f64, n: usize) -> f64 {
+    let n = n as f64;
+    sample_rate / (2.0 * std::f64::consts::PI) * (12.0 / (eta * n * (n * n - 1.0))).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,7 +747,6 @@ mod tests {
             &frequencies,
             &magnitude,
             (10.0, 500.0),
-            true,
         );
 
         assert!((peak_freq - 100.0).abs() < 2.0);
@@ -274,4 +780,230 @@ mod tests {
 
         assert_eq!(peaks.len(), 2);
     }
+
+    #[test]
+    fn test_energy_distribution_percentages() {
+        let n = 1000;
+        let sample_rate = 1000.0;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| {
+                let t = i as f64 / sample_rate;
+                (2.0 * PI * 50.0 * t).sin() + (2.0 * PI * 300.0 * t).sin()
+            })
+            .collect();
+        let fft_result = crate::fft_processor::FftResult::compute(&samples, sample_rate);
+
+        let bands = FrequencyEstimator::compute_energy_distribution(
+            &fft_result.magnitude,
+            &fft_result.frequencies,
+            &[(0.0, 100.0), (100.0, 200.0), (200.0, 500.0)],
+        );
+        assert_eq!(bands.len(), 3);
+        assert!((bands[0].percentage - 50.0).abs() < 1e-6);
+        assert!(bands[1].percentage.abs() < 1e-6);
+        assert!((bands[2].percentage - 50.0).abs() < 1e-6);
+        assert_eq!(bands[0].label(), "0-100 Hz");
+    }
+
+    #[test]
+    fn test_decibel_interpolation_on_windowed_tone() {
+        // Hanning 窗下的非整数 bin 单音：dB 刻度插值应比线性刻度更准确
+        let n = 1000;
+        let sample_rate = 1000.0;
+        let true_freq = 100.3;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * true_freq * i as f64 / sample_rate).sin())
+            .collect();
+        let windowed = crate::fft_processor::FftResult::apply_hanning_window(&samples);
+        let fft_result = crate::fft_processor::FftResult::compute(&windowed, sample_rate);
+        let frequencies = &fft_result.frequencies;
+        let magnitude = &fft_result.magnitude;
+
+        let (_, _, peak_idx) =
+            FrequencyEstimator::estimate_frequency_offset(frequencies, magnitude, (10.0, 400.0));
+        let linear = FrequencyEstimator::interpolate_peak(
+            frequencies, magnitude, peak_idx, InterpolationScale::Linear, 1,
+        );
+        let decibel = FrequencyEstimator::interpolate_peak(
+            frequencies, magnitude, peak_idx, InterpolationScale::Decibel, 1,
+        );
+        assert!((decibel.frequency - true_freq).abs() < (linear.frequency - true_freq).abs());
+        assert!((decibel.frequency - true_freq).abs() < 0.05);
+
+        let least_squares = FrequencyEstimator::interpolate_peak(
+            frequencies, magnitude, peak_idx, InterpolationScale::Decibel, 2,
+        );
+        assert_eq!(least_squares.num_points, 5);
+        assert!(least_squares.error_estimate > 0.0);
+        assert!((least_squares.frequency - true_freq).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_crlb_uncertainty_scales_with_noise() {
+        // 同一单音叠加两种强度的均匀噪声：噪声越强 CRLB 分量越大
+        let n = 2000;
+        let sample_rate = 1000.0;
+        let mut state: u64 = 12345;
+        let mut noise = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let mut uncertainty = |noise_level: f64| {
+            let samples: Vec<f64> = (0..n)
+                .map(|i| (2.0 * PI * 150.25 * i as f64 / sample_rate).sin() + noise_level * noise())
+                .collect();
+            let fft_result = crate::fft_processor::FftResult::compute(&samples, sample_rate);
+            let (_, _, peak_idx) = FrequencyEstimator::estimate_frequency_offset(
+                &fft_result.frequencies, &fft_result.magnitude, (10.0, 400.0),
+            );
+            FrequencyEstimator::frequency_uncertainty(
+                &fft_result.frequencies, &fft_result.magnitude, peak_idx,
+            )
+        };
+
+        let quiet = uncertainty(0.1);
+        let loud = uncertainty(1.0);
+        assert!(loud.crlb > 5.0 * quiet.crlb);
+        assert!(quiet.snr_db > loud.snr_db + 15.0);
+        assert!(quiet.std_dev >= quiet.resolution);
+
+        let axis = FrequencyUncertainty::symmetric_axis(&quiet, &quiet);
+        assert!((axis.std_dev - quiet.std_dev / 2f64.sqrt()).abs() < 1e-12);
+        let (low, high) = axis.confidence_interval(150.0, 1.96);
+        assert!(low < 150.0 && high > 150.0);
+    }
+
+    #[test]
+    fn test_batch_and_streaming_uncertainty_match_known_noise() {
+        // 单位幅度单音叠加方差已知的均匀白噪声：η = A² / (2σ²)，
+        // 整段双边谱与分段平均谱得到的 SNR 与 CRLB 都应与真实 η 给出的理论值一致
+        let n = 1 << 16;
+        let segment_len = 1024;
+        let sample_rate = 8192.0;
+        let tone = 1000.0; // 落在两种频率网格的 bin 中心，避免扇贝损失
+        let level = 2.0;
+        let true_eta = 1.0 / (2.0 * level * level / 12.0);
+        let mut state: u64 = 2024;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                (2.0 * PI * tone * i as f64 / sample_rate).sin() + level * noise
+            })
+            .collect();
+
+        let batch = crate::fft_processor::FftResult::compute(&samples, sample_rate);
+        let (_, _, peak_idx) =
+            FrequencyEstimator::estimate_frequency_offset(&batch.frequencies, &batch.magnitude, (10.0, 4000.0));
+        let batch_uncertainty = FrequencyEstimator::frequency_uncertainty(&batch.frequencies, &batch.magnitude, peak_idx);
+
+        let averaged = crate::fft_processor::FftResult::compute_chunked(&samples, sample_rate, segment_len);
+        let (_, _, peak_idx) =
+            FrequencyEstimator::estimate_frequency_offset(&averaged.frequencies, &averaged.magnitude, (10.0, 4000.0));
+        assert!((averaged.frequencies[peak_idx] - tone).abs() < 1e-9);
+        let streaming_uncertainty = FrequencyEstimator::averaged_frequency_uncertainty(&averaged, peak_idx);
+
+        let true_snr_db = 10.0 * true_eta.log10();
+        assert!((batch_uncertainty.snr_db - true_snr_db).abs() < 0.5, "batch SNR {:.2} dB", batch_uncertainty.snr_db);
+        assert!(
+            (streaming_uncertainty.snr_db - true_snr_db).abs() < 0.5,
+            "streaming SNR {:.2} dB",
+            streaming_uncertainty.snr_db
+        );
+
+        let batch_crlb = tone_crlb(sample_rate, true_eta, n);
+        let streaming_crlb = tone_crlb(sample_rate, true_eta, segment_len) / (averaged.num_segments as f64).sqrt();
+        assert!((batch_uncertainty.crlb / batch_crlb - 1.0).abs() < 0.1);
+        assert!((streaming_uncertainty.crlb / streaming_crlb - 1.0).abs() < 0.1);
+        // 网格量化分量随各自的 Δf 变化
+        assert!((batch_uncertainty.resolution - sample_rate / n as f64 / 12f64.sqrt()).abs() < 1e-12);
+        assert!((streaming_uncertainty.resolution - sample_rate / segment_len as f64 / 12f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_music_resolves_closely_spaced_tones() {
+        // 两个相距 3 Hz 的正弦（小于 4 Hz 的 FFT bin 宽度），FFT 无法分辨
+        let n = 250;
+        let sample_rate = 1000.0;
+        let (f1, f2) = (100.0, 103.0);
+        let samples: Vec<f64> = (0..n)
+            .map(|i| {
+                let t = i as f64 / sample_rate;
+                (2.0 * PI * f1 * t).cos() + (2.0 * PI * f2 * t + 0.7).cos()
+            })
+            .collect();
+
+        let estimates =
+            FrequencyEstimator::music_frequency_estimate(&samples, sample_rate, 2, 60, (90.0, 115.0));
+        assert_eq!(estimates.len(), 2);
+        assert!((estimates[0] - f1).abs() < 0.2);
+        assert!((estimates[1] - f2).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_symmetric_axis_on_synthetic_am() {
+        use crate::fft_processor::FftResult;
+        use dsp_core::signal_gen::{self, AmConfig, Baseband};
+
+        // 端到端：已知 f_d 的合成信号（DSB-SC，300 Hz 单音基带，SNR 20 dB）
+        let config = AmConfig {
+            offset: 2500.0,
+            baseband: Baseband::Tones(vec![300.0]),
+            snr_db: Some(20.0),
+            ..AmConfig::default()
+        };
+        let signal = signal_gen::generate(&config).unwrap();
+        let fft = FftResult::compute(&signal.samples, config.sample_rate);
+        let (frequencies, magnitude) = fft.get_single_sided();
+
+        let peaks = FrequencyEstimator::detect_peaks(&frequencies, &magnitude, 5, 5, 0.0);
+        let pairs = FrequencyEstimator::find_symmetric_pairs(&peaks, 5000.0, 0.9);
+        let best = FrequencyEstimator::best_symmetric_pair(&pairs).unwrap();
+
+        let bin_width = config.sample_rate / signal.samples.len() as f64;
+        assert!((best.axis - config.offset).abs() < bin_width, "axis = {}", best.axis);
+        assert!((best.baseband - 300.0).abs() < bin_width);
+    }
+
+    #[test]
+    fn test_snr_estimate_and_noise_floor_shape() {
+        use crate::fft_processor::FftResult;
+        use dsp_core::noise::NoiseKind;
+        use dsp_core::signal_gen::{self, AmConfig, Baseband};
+
+        // 300 Hz 单音调制在 f_d = 2500 Hz：谱线位于 2200 / 2800 Hz
+        let spectrum = |kind: NoiseKind, snr: f64| {
+            let config = AmConfig {
+                offset: 2500.0,
+                baseband: Baseband::Tones(vec![300.0]),
+                snr_db: Some(snr),
+                noise_kind: kind,
+                ..AmConfig::default()
+            };
+            let signal = signal_gen::generate(&config).unwrap();
+            let fft = FftResult::compute(&signal.samples, config.sample_rate);
+            let (frequencies, magnitude) = fft.get_single_sided();
+            let bin = |f: f64| frequencies.iter().position(|&x| x >= f).unwrap();
+            let band = |low: f64, high: f64| (bin(low), bin(high));
+            (magnitude, band(2190.0, 2210.0), band(1000.0, 2000.0), band(8000.0, 9000.0))
+        };
+
+        let cases = [
+            (NoiseKind::White, 0.0..1.5),
+            (NoiseKind::Pink, 4.0..11.0),
+            (NoiseKind::Brown, 12.0..40.0),
+        ];
+        for (kind, tilt) in cases {
+            // 同一噪声形状放大 10 倍（SNR 低 20 dB），谱线对噪底的估计 SNR 也应低约 20 dB
+            let (quiet, tone, _, floor) = spectrum(kind, 30.0);
+            let (loud, _, low, high) = spectrum(kind, 10.0);
+            let drop = FrequencyEstimator::estimate_snr(&quiet, tone, floor)
+                - FrequencyEstimator::estimate_snr(&loud, tone, floor);
+            assert!((drop - 20.0).abs() < 1.0, "{}: drop = {}", kind, drop);
+
+            // 噪底斜率：白噪声平坦，粉红 -3 dB/oct，布朗 -6 dB/oct
+            let slope = FrequencyEstimator::estimate_snr(&loud, low, high);
+            assert!(tilt.contains(&slope.abs()), "{}: tilt = {}", kind, slope);
+        }
+    }
 }
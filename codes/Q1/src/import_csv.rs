@@ -0,0 +1,208 @@
+// 导入外部频谱 CSV 并做频率偏差估计
+// 与 export_csv 相对：频谱由 MATLAB、Python 或频谱仪等给出，不需要音频文件
+
+use am_demodulation_q1::frequency_estimator::{FrequencyEstimator, InterpolationScale};
+use am_demodulation_q1::spectrum_import::{ImportedSpectrum, MagnitudeScale};
+use am_demodulation_q1::spectrum_visualizer::SpectrumVisualizer;
+use dsp_core::error::{self, DspError};
+use dsp_core::logging;
+use dsp_core::plot_output::PlotOutput;
+use dsp_core::reproducible::Reproducibility;
+use dsp_core::results::StageResults;
+use dsp_core::row;
+use log::info;
+use std::error::Error;
+use std::io::Write;
+
+const USAGE: &str = "\
+用法: import_csv <频谱.csv> [选项]
+
+读取频率/幅度 CSV（第一列为频率 Hz），估计频谱对称轴 f_d 并绘制频谱。
+
+  --db                  无表头文件的幅度列为 dB（有表头时按列名自动判断）
+  --range <低>:<高>     峰值搜索范围 (Hz)，默认为除第一个频点外的全部范围
+  --fs <Hz>             原信号采样率，写入结果文件后 Q2-Q4 可直接使用
+  --out <前缀>          输出文件前缀，默认 output/Q1_import
+  --deterministic       另存一份以参数哈希命名的结果 JSON
+  --plot-format 等      与 q1 相同的绘图选项";
+
+struct Options {
+    path: String,
+    scale: MagnitudeScale,
+    range: Option<(f64, f64)>,
+    sample_rate: Option<f64>,
+    prefix: String,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    logging::init_from_args(&args);
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(e) = run(&args) {
+        error::exit(e.as_ref(), "错误");
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let options = parse_args(args)?;
+    let plots = PlotOutput::from_args(args)?;
+
+    info!("========================================");
+    info!("Q1: 导入外部频谱并估计频率偏差");
+    info!("========================================\n");
+
+    let spectrum = ImportedSpectrum::read_csv(&options.path, options.scale)?;
+    let frequencies = &spectrum.frequencies;
+    let magnitude = &spectrum.magnitude;
+    let max_freq = frequencies[frequencies.len() - 1];
+    info!("频谱信息:");
+    info!("  文件: {}", options.path);
+    info!(
+        "  幅度列: {} ({})",
+        spectrum.column,
+        if spectrum.scale == MagnitudeScale::Decibel { "dB，已换算为线性" } else { "线性" }
+    );
+    info!("  频点数: {}", frequencies.len());
+    info!("  频率范围: {:.2} ~ {:.2} Hz", frequencies[0], max_freq);
+    info!("  平均频率间隔: {:.4} Hz", spectrum.resolution());
+    if spectrum.spacing_deviation() > 0.01 {
+        info!(
+            "  警告: 频点不等间隔（最大偏差 {:.1}%），抛物线插值结果仅供参考",
+            100.0 * spectrum.spacing_deviation()
+        );
+    }
+
+    // 峰值搜索与插值，与 q1 主程序相同
+    let range = options.range.unwrap_or((frequencies[1], max_freq));
+    info!("\n峰值搜索范围: {:.2} ~ {:.2} Hz", range.0, range.1);
+    let (f_peak, peak_mag, peak_idx) = FrequencyEstimator::estimate_frequency_offset(frequencies, magnitude, range);
+    if peak_mag <= 0.0 {
+        return Err(DspError::Data("搜索范围内没有正幅度的频点".to_string()).into());
+    }
+    let f_refined = FrequencyEstimator::refined_frequency_estimate(frequencies, magnitude, peak_idx);
+    let f_db_fit = FrequencyEstimator::interpolate_peak(frequencies, magnitude, peak_idx, InterpolationScale::Decibel, 2);
+
+    info!("");
+    let in_range: Vec<f64> = frequencies
+        .iter()
+        .zip(magnitude.iter())
+        .map(|(&f, &m)| if f >= range.0 && f <= range.1 { m } else { 0.0 })
+        .collect();
+    let peaks = FrequencyEstimator::find_multiple_peaks(frequencies, &in_range, 5, 20, peak_mag * 0.1);
+    let best_pair = FrequencyEstimator::best_symmetric_pair(&FrequencyEstimator::find_symmetric_pairs(&peaks, range.1, 0.9));
+    let f_d = match best_pair {
+        Some(pair) => {
+            info!("\n对称峰值对: {:.2} Hz / {:.2} Hz，对称轴 f_d = {:.4} Hz", pair.lower_freq, pair.upper_freq, pair.axis);
+            pair.axis
+        }
+        None => {
+            info!("\n未找到对称峰值对，使用插值后的峰值频率 f_d = {:.4} Hz", f_refined);
+            f_refined
+        }
+    };
+
+    // 输出
+    if let Some(dir) = std::path::Path::new(&options.prefix).parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let axis_label = format!("f_d = {:.0} Hz", f_d);
+    SpectrumVisualizer::plot_spectrum(
+        frequencies,
+        magnitude,
+        plots.target(&format!("{}_spectrum.png", options.prefix)),
+        "Imported Spectrum (f_d marked)",
+        Some(max_freq),
+        &[(f_d, axis_label.as_str())],
+    )?;
+    let magnitude_db: Vec<f64> = magnitude.iter().map(|&m| 20.0 * m.max(1e-12).log10()).collect();
+    SpectrumVisualizer::plot_spectrum_db(
+        frequencies,
+        &magnitude_db,
+        plots.target(&format!("{}_spectrum_db.png", options.prefix)),
+        "Imported Spectrum (dB)",
+        Some(max_freq),
+    )?;
+
+    let results_path = format!("{}_results.txt", options.prefix);
+    let mut file = std::fs::File::create(&results_path)?;
+    writeln!(file, "Q1 导入频谱分析结果")?;
+    writeln!(file, "===================")?;
+    writeln!(file, "频谱文件: {}", options.path)?;
+    writeln!(file, "频率偏差 f_d = {:.4} Hz", f_d)?;
+    // 以下各行不含 f_d 字样，避免被 Q3/Q4 的 f_d 解析误读
+    writeln!(file, "最大峰值 = {:.4} Hz (插值 {:.4} Hz, dB 拟合 {:.4} Hz)", f_peak, f_refined, f_db_fit.frequency)?;
+    if let Some(fs) = options.sample_rate {
+        writeln!(file, "采样率 f_s = {:.2} Hz", fs)?;
+    }
+    writeln!(file, "基带带宽 f_B = 4000 Hz")?;
+    info!("\n结果已保存到 {}", results_path);
+
+    let json_path = format!("{}_results.json", options.prefix);
+    let reproducibility = Reproducibility::from_args(args)?;
+    StageResults::new("Q1")
+        .parameter("input", options.path.as_str())
+        .parameter("f_s_hz", options.sample_rate)
+        .parameter("search_low_hz", range.0)
+        .parameter("search_high_hz", range.1)
+        .parameter("points", frequencies.len())
+        .metric("f_d_hz", f_d)
+        .metric("f_d_peak_hz", f_peak)
+        .metric("f_d_parabolic_hz", f_refined)
+        .metric("f_d_db_fit_hz", f_db_fit.frequency)
+        .metric("symmetric_pair_found", best_pair.is_some())
+        .table(
+            "peaks",
+            peaks.iter().map(|&(f, m, i)| row![("frequency_hz", f), ("magnitude", m), ("index", i)]).collect(),
+        )
+        .reproducibility(&reproducibility)
+        .save_with(&json_path, &reproducibility)?;
+    info!("结果已保存到 {}", json_path);
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<Options, DspError> {
+    let mut options = Options {
+        path: String::new(),
+        scale: MagnitudeScale::Linear,
+        range: None,
+        sample_rate: None,
+        prefix: "output/Q1_import".to_string(),
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or_else(|| DspError::Argument(format!("{} 缺少参数", name)));
+        match arg.as_str() {
+            "--db" => options.scale = MagnitudeScale::Decibel,
+            "--range" => {
+                let text = value("--range")?;
+                let invalid = || DspError::Argument("--range 格式应为 <低>:<高>".to_string());
+                let (low, high) = text.split_once(':').ok_or_else(invalid)?;
+                options.range = Some((
+                    low.trim().parse().map_err(|_| invalid())?,
+                    high.trim().parse().map_err(|_| invalid())?,
+                ));
+            }
+            "--fs" => {
+                let text = value("--fs")?;
+                options.sample_rate = Some(text.parse().map_err(|_| DspError::invalid_value("--fs", text))?)
+            }
+            "--out" => options.prefix = value("--out")?.clone(),
+            // 绘图选项与 --seed 分别由 PlotOutput 和 Reproducibility 解析，这里跳过其参数
+            "--plot-format" | "--plot-size" | "--plot-dpi" | "--plot-style" | "--colormap" | "--db-range" | "--seed" => {
+                value(arg)?;
+            }
+            "--deterministic" => {}
+            flag if logging::is_verbosity_flag(flag) => {}
+            other if other.starts_with("--") => return Err(DspError::Argument(format!("未知选项 '{}'", other))),
+            path if options.path.is_empty() => options.path = path.to_string(),
+            extra => return Err(DspError::Argument(format!("多余的参数 '{}'", extra))),
+        }
+    }
+    if options.path.is_empty() {
+        return Err(DspError::Argument("缺少频谱 CSV 文件".to_string()));
+    }
+    Ok(options)
+}
@@ -0,0 +1,11 @@
+// Q1 分析模块库
+// 由 q1、export_csv、import_csv 等可执行程序共享
+
+pub mod audio_reader;
+pub mod fft_processor;
+pub mod frequency_estimator;
+pub mod offset_tracker;
+pub mod preprocess;
+pub mod spectrum_import;
+pub mod spectrum_tui;
+pub mod spectrum_visualizer;
@@ -1,73 +1,204 @@
 // Q1 主程序：频谱分析与频率偏差估计
 // 整合四个模块完成完整的分析流程
 
-mod audio_reader;
-mod fft_processor;
-mod spectrum_visualizer;
-mod frequency_estimator;
-
-use audio_reader::AudioData;
-use fft_processor::FftResult;
-use spectrum_visualizer::SpectrumVisualizer;
-use frequency_estimator::FrequencyEstimator;
+use am_demodulation_q1::audio_reader::{AudioData, RawFormat};
+use am_demodulation_q1::fft_processor::{
+    adjust_fft_length, is_fast_length, largest_prime_factor, next_fast_length,
+    previous_fast_length, ChunkedSpectrum, FftLengthPolicy, FftResult,
+};
+use am_demodulation_q1::preprocess::{PreprocessConfig, Preprocessor};
+use am_demodulation_q1::spectrum_tui::{self, SpectrumView};
+use am_demodulation_q1::spectrum_visualizer::SpectrumVisualizer;
+use am_demodulation_q1::frequency_estimator::{
+    FrequencyEstimator, FrequencyUncertainty, InterpolationScale, SymmetricPair,
+};
+use am_demodulation_q1::offset_tracker;
+use dsp_core::activity::{self, ActivityDetector};
+use dsp_core::autocorrelation;
+use dsp_core::batch::{self, BatchSummary};
+use dsp_core::channels::ChannelMode;
+use dsp_core::error::{self, DspError};
+use dsp_core::logging;
+use dsp_core::html_plot::{self, InteractiveChart, InteractivePage};
+use dsp_core::mat;
+use dsp_core::npy::{self, Npz};
+use dsp_core::plot_output::PlotOutput;
+use dsp_core::reproducible::Reproducibility;
+use dsp_core::results::StageResults;
+use dsp_core::run_dir::RunDirectory;
+use dsp_core::row;
+use dsp_core::spectral_features::SpectralFeatures;
+use dsp_core::stft::{Stft, Window};
+use dsp_core::watch::Watch;
+use log::info;
+use rustfft::{num_complex::Complex, FftPlanner};
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    println!("========================================");
-    println!("Q1: 频谱分析与频率偏差估计");
-    println!("========================================\n");
+fn main() {
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run_or_watch() {
+        error::exit(e.as_ref(), "错误");
+    }
+}
+
+/// 给出 `--watch` 时先运行一次，之后输入音频（批处理时为目录中的各 WAV 文件）内容变化就重新运行
+fn run_or_watch() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(watch) = Watch::from_args(&args)? else {
+        return run();
+    };
+    let inputs = match batch::dir_from_args(&args)? {
+        Some(dir) => batch::wav_files(&dir)?,
+        None => vec![PathBuf::from(parse_input_path())],
+    };
+    Ok(watch.run(&inputs, run)?)
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    info!("========================================");
+    info!("Q1: 频谱分析与频率偏差估计");
+    info!("========================================\n");
 
     // ===== 步骤 1: 音频文件读取 =====
-    println!("步骤 1: 读取音频文件...");
-    let audio_path = "../project.wav";
-    let audio = AudioData::from_wav(audio_path)?;
+    if let Some(dir) = parse_batch_dir()? {
+        return run_batch(&dir);
+    }
+    if parse_streaming() {
+        return run_streaming();
+    }
+    info!("步骤 1: 读取音频文件...");
+    let run_directory = parse_run_directory()?;
+    let audio_path = parse_input_path();
+    let audio_path = audio_path.as_str();
+    let raw_input = parse_raw_format()?;
+    let audio = match raw_input {
+        Some((format, fs)) => AudioData::from_raw(audio_path, format, fs)?,
+        None => AudioData::open(audio_path)?,
+    };
     
-    // 转换为单声道（如果需要）
-    let samples = audio.to_mono();
+    // 按 --channels 选择声道；f_d 是整段录音的单一参数，Both 模式下仍用混合声道估计
+    let channel_mode = parse_channel_mode()?;
+    let plots = parse_plot_output()?;
+    let reproducibility = parse_reproducibility()?;
+    let analysis_mode = if channel_mode == ChannelMode::Both {
+        if audio.spec.channels > 1 {
+            info!("  注意: Q1 只估计一个 f_d，--channels both 时按混合声道分析");
+        }
+        ChannelMode::Mix
+    } else {
+        channel_mode
+    };
+    let mono = audio.channels(analysis_mode).remove(0);
+
+    // 去除直流偏移与线性趋势，避免直流 bin 主导幅度刻度
+    let mut samples = Preprocessor::apply(&mono, &PreprocessConfig::default());
     let sample_rate = audio.sample_rate as f64;
     let num_samples = samples.len();
+
+    // 可选：--active-only 时把静音与纯噪声段置零，频谱只由有效信号段决定；长度与时间轴不变
+    let active_fraction = match parse_activity_detector()? {
+        Some(detector) => {
+            let segments = detector.detect(&samples, sample_rate);
+            let fraction = activity::active_samples(&segments) as f64 / num_samples.max(1) as f64;
+            info!(
+                "有效段检测: {} 段，占 {:.1}%（帧能量高于噪声底 {} dB），其余样本置零",
+                segments.len(),
+                100.0 * fraction,
+                detector.threshold_db
+            );
+            samples = activity::gate(&samples, &segments);
+            Some(fraction)
+        }
+        None => None,
+    };
     
-    println!("\n音频信息:");
-    println!("  采样率 f_s = {} Hz", sample_rate);
-    println!("  样本数 N = {}", num_samples);
-    println!("  时长 = {:.2} 秒\n", num_samples as f64 / sample_rate);
+    info!("\n音频信息:");
+    info!("  采样率 f_s = {} Hz", sample_rate);
+    info!("  样本数 N = {}", num_samples);
+    info!("  时长 = {:.2} 秒\n", num_samples as f64 / sample_rate);
 
     // ===== 步骤 2: FFT 计算 =====
-    println!("步骤 2: 计算 FFT...");
-    let fft_result = FftResult::compute(&samples, sample_rate);
+    info!("步骤 2: 计算 FFT...");
+    let policy = parse_fft_length_policy()?;
+    if !is_fast_length(num_samples) {
+        info!(
+            "  注意: N = {} 含素因子 {}，不是 FFT 友好长度；可用 --fft-length pad ({} 点) 或 --fft-length truncate ({} 点)",
+            num_samples,
+            largest_prime_factor(num_samples),
+            next_fast_length(num_samples),
+            previous_fast_length(num_samples),
+        );
+    }
+    let fft_samples = adjust_fft_length(&samples, policy);
+    let fft_len = fft_samples.len();
+
+    let start = Instant::now();
+    let fft_result = FftResult::compute(&fft_samples, sample_rate);
+    let fft_time = start.elapsed();
     let frequencies = &fft_result.frequencies;
     let magnitude = &fft_result.magnitude;
     let magnitude_db = fft_result.get_magnitude_db();
     
-    println!("  FFT 点数: {}", num_samples);
-    println!("  频率分辨率: {:.4} Hz\n", sample_rate / num_samples as f64);
+    info!("  FFT 长度策略: {:?}", policy);
+    info!("  FFT 耗时: {:.3} ms", fft_time.as_secs_f64() * 1000.0);
+    if policy == FftLengthPolicy::Keep && !is_fast_length(fft_len) {
+        // 对比友好长度的耗时，让用户看到差别
+        let friendly = next_fast_length(fft_len);
+        info!(
+            "  对比: {} 点 FFT 耗时 {:.3} ms，{} 点 FFT 耗时 {:.3} ms",
+            fft_len,
+            time_fft(fft_len).as_secs_f64() * 1000.0,
+            friendly,
+            time_fft(friendly).as_secs_f64() * 1000.0,
+        );
+    }
+    info!("");
 
-    // ===== 步骤 3: 频谱可视化 =====
-    println!("步骤 3: 绘制频谱图...");
-    
-    // 绘制全频谱
-    SpectrumVisualizer::plot_spectrum(
-        frequencies,
-        magnitude,
-        "output/Q1_spectrum_full.png",
-        "Spectrum of Misdemodulated Signal (Full)",
-        Some(sample_rate / 2.0),
-    )?;
+    // 分段平均频谱：内存映射读取文件，按块送入，内存只与段长有关，适用于长录音
+    let mut chunked = ChunkedSpectrum::new(8192, sample_rate);
+    if raw_input.is_none() && audio_path.to_ascii_lowercase().ends_with(".wav") {
+        let mapped = AudioData::open_mmap(audio_path)?;
+        let mut chunk = Vec::with_capacity(4096);
+        for sample in mapped.mono() {
+            chunk.push(sample);
+            if chunk.len() == 4096 {
+                chunked.push(&chunk);
+                chunk.clear();
+            }
+        }
+        chunked.push(&chunk);
+    } else {
+        // 压缩格式与原始数据不走 WAV 流式读取，直接使用已读入的数据
+        for chunk in audio.to_mono().chunks(4096) {
+            chunked.push(chunk);
+        }
+    }
+    let averaged = chunked.finish();
+    if let Some((idx, _)) = averaged
+        .magnitude
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+    {
+        info!(
+            "  分段平均频谱 (段长 8192, Hann 窗, 50% 重叠, {} 段): 峰值 {:.2} Hz，分辨率 {:.2} Hz\n",
+            averaged.num_segments,
+            averaged.frequencies[idx],
+            sample_rate / 8192.0
+        );
+    }
 
-    // 绘制低频段频谱（0-10 kHz）
-    SpectrumVisualizer::plot_spectrum(
-        frequencies,
-        magnitude,
-        "output/Q1_spectrum_lowfreq.png",
-        "Spectrum of Misdemodulated Signal (0-4 kHz)",
-        Some(4000.0),
-    )?;
+    // ===== 步骤 3: 频谱可视化 =====
+    // 幅度谱（全频段与低频段）在步骤 4 找到对称峰值后绘制，以便标注 f_d 与边带
+    info!("步骤 3: 绘制频谱图...");
 
     // 绘制 dB 刻度的频谱
     SpectrumVisualizer::plot_spectrum_db(
         frequencies,
         &magnitude_db,
-        "output/Q1_spectrum_db.png",
+        plots.target("output/Q1_spectrum_db.png"),
         "Spectrum of Misdemodulated Signal (dB scale)",
         Some(10000.0),
     )?;
@@ -87,22 +218,21 @@ fn main() -> Result<(), Box<dyn Error>> {
     SpectrumVisualizer::plot_waveform(
         &samples,
         sample_rate,
-        "output/Q1_waveform.png",
+        plots.target("output/Q1_waveform.png"),
         "Waveform of Misdemodulated Signal",
         Some(samples_to_plot),
     )?;
 
-    println!();
+    info!("");
 
     // ===== 步骤 4: 频率偏差估计 =====
-    println!("步骤 4: 估计频率偏差 (First 0.1s)f_d...\n");
+    info!("步骤 4: 估计频率偏差 (First 0.1s)f_d...\n");
     
     // 基本频率估计（排除直流，搜索 10 Hz 到 10 kHz）
-    let (f_d, peak_mag, peak_idx) = FrequencyEstimator::estimate_frequency_offset(
+    let (f_d, _peak_mag, peak_idx) = FrequencyEstimator::estimate_frequency_offset(
         frequencies,
         magnitude,
         (10.0, 10000.0),
-        true, // 排除直流分量
     );
 
     // 精确频率估计（使用抛物线插值）
@@ -112,8 +242,21 @@ fn main() -> Result<(), Box<dyn Error>> {
         peak_idx,
     );
 
+    // dB 刻度下的五点最小二乘插值，并给出插值误差估计
+    let f_d_db_fit = FrequencyEstimator::interpolate_peak(
+        frequencies,
+        magnitude,
+        peak_idx,
+        InterpolationScale::Decibel,
+        2,
+    );
+    info!("dB 刻度最小二乘插值 ({} 点):", f_d_db_fit.num_points);
+    info!("  精确频率: {:.4} Hz", f_d_db_fit.frequency);
+    info!("  拟合残差 (RMS): {:.4} dB", f_d_db_fit.residual_rms);
+    info!("  插值误差估计: ±{:.4} Hz", f_d_db_fit.error_estimate);
+
     // 寻找多个峰值
-    println!();
+    info!("");
     let threshold = magnitude[peak_idx] * 0.1; // 设置阈值为主峰的 10%
     let peaks = FrequencyEstimator::find_multiple_peaks(
         frequencies,
@@ -124,52 +267,206 @@ fn main() -> Result<(), Box<dyn Error>> {
     );
     
     // 通过对称峰值分析确定真实的频率偏差
-    println!("\n=== 对称峰值分析 ===");
-    println!("检测到的峰值：");
+    info!("\n=== 对称峰值分析 ===");
+    info!("检测到的峰值：");
     for (i, (freq, mag, _)) in peaks.iter().enumerate() {
-        println!("  峰值 {}: {:.2} Hz (幅度: {:.6})", i + 1, freq, mag);
+        info!("  峰值 {}: {:.2} Hz (幅度: {:.6})", i + 1, freq, mag);
     }
     
     // 寻找对称峰值对（幅度相近的峰值）
-    // 仅在低频区域 (0-5000 Hz) 内搜索，因为频率偏差应该在这个范围内
-    let mut symmetric_pairs = Vec::new();
-    for i in 0..peaks.len() {
-        for j in (i+1)..peaks.len() {
-            let (f1, mag1, _) = peaks[i];
-            let (f2, mag2, _) = peaks[j];
-            
-            // 只考虑低频区域的峰值
-            if f1 > 5000.0 || f2 > 5000.0 {
-                continue;
-            }
-            
-            let mag_ratio = mag1.min(mag2) / mag1.max(mag2);
-            // 如果幅度相差小于10%，认为是对称峰值对
-            if mag_ratio > 0.9 {
-                let axis = (f1 + f2) / 2.0;
-                let baseband = (f2 - f1).abs() / 2.0;
-                symmetric_pairs.push((f1, f2, axis, mag1, mag2, baseband));
-            }
-        }
-    }
+    // 仅在低频区域 (0-5000 Hz) 内搜索，因为频率偏差应该在这个范围内；
+    // 幅度相差小于 10% 的两个峰值认为是对称峰值对
+    let symmetric_pairs = FrequencyEstimator::find_symmetric_pairs(&peaks, 5000.0, 0.9);
+    let best_pair = FrequencyEstimator::best_symmetric_pair(&symmetric_pairs);
     
     // 选择最佳的对称轴（幅度最大的对称峰值对）
-    let f_d_symmetric = if let Some(&(f1, f2, axis, mag1, mag2, baseband)) = symmetric_pairs
-        .iter()
-        .max_by(|a, b| a.3.partial_cmp(&b.3).unwrap()) {
-        println!("\n找到对称峰值对：");
-        let (lower_freq, lower_mag) = if f1 < f2 { (f1, mag1) } else { (f2, mag2) };
-        let (upper_freq, upper_mag) = if f1 > f2 { (f1, mag1) } else { (f2, mag2) };
-        println!("  下边带峰值: {:.2} Hz (幅度: {:.6})", lower_freq, lower_mag);
-        println!("  上边带峰值: {:.2} Hz (幅度: {:.6})", upper_freq, upper_mag);
-        println!("  频谱对称轴: {:.2} Hz ← 真实的频率偏差 f_d", axis);
-        println!("  基带频率成分: {:.2} Hz", baseband);
-        axis
+    let f_d_symmetric = if let Some(pair) = best_pair {
+        info!("\n找到对称峰值对：");
+        info!("  下边带峰值: {:.2} Hz (幅度: {:.6})", pair.lower_freq, pair.lower_mag);
+        info!("  上边带峰值: {:.2} Hz (幅度: {:.6})", pair.upper_freq, pair.upper_mag);
+        info!("  频谱对称轴: {:.2} Hz ← 真实的频率偏差 f_d", pair.axis);
+        info!("  基带频率成分: {:.2} Hz", pair.baseband);
+        pair.axis
     } else {
-        println!("警告：未找到明显的对称峰值对，使用峰值搜索结果");
+        info!("警告：未找到明显的对称峰值对，使用峰值搜索结果");
         f_d_refined
     };
 
+    // 幅度谱上标注对称轴 f_d：全频段标出基带带宽边界 f_d ± f_B，低频段标出对称峰值对
+    let f_b = 4000.0;
+    let axis_label = format!("f_d = {:.0} Hz", f_d_symmetric);
+    let band_labels = ["f_d - f_B".to_string(), "f_d + f_B".to_string()];
+    let pair_labels = best_pair.map(|pair| {
+        [
+            format!("f_d - {:.0} Hz", pair.baseband),
+            format!("f_d + {:.0} Hz", pair.baseband),
+        ]
+    });
+
+    // 绘制全频谱
+    SpectrumVisualizer::plot_spectrum(
+        frequencies,
+        magnitude,
+        plots.target("output/Q1_spectrum_full.png"),
+        "Spectrum of Misdemodulated Signal (Full)",
+        Some(sample_rate / 2.0),
+        &[
+            (f_d_symmetric, &axis_label),
+            (f_d_symmetric - f_b, &band_labels[0]),
+            (f_d_symmetric + f_b, &band_labels[1]),
+        ],
+    )?;
+
+    // 绘制低频段频谱（0-4 kHz）
+    let mut lowfreq_annotations = vec![(f_d_symmetric, axis_label.as_str())];
+    if let (Some(pair), Some(labels)) = (best_pair, &pair_labels) {
+        lowfreq_annotations.push((pair.lower_freq, &labels[0]));
+        lowfreq_annotations.push((pair.upper_freq, &labels[1]));
+    }
+    SpectrumVisualizer::plot_spectrum(
+        frequencies,
+        magnitude,
+        plots.target("output/Q1_spectrum_lowfreq.png"),
+        "Spectrum of Misdemodulated Signal (0-4 kHz)",
+        Some(4000.0),
+        &lowfreq_annotations,
+    )?;
+
+    // 双边频谱：AM 边带关于 ±f_d 对称
+    let (centered_freqs, centered_mags) = fft_result.get_two_sided_centered();
+    SpectrumVisualizer::plot_spectrum_centered(
+        &centered_freqs,
+        &centered_mags,
+        plots.target("output/Q1_spectrum_centered.png"),
+        "Two-Sided Spectrum of Misdemodulated Signal (±f_d marked)",
+        Some(5000.0),
+        &[-f_d_symmetric, f_d_symmetric],
+    )?;
+
+    // 可选：可缩放的交互式频谱与波形（--html），无需按不同 max_freq 反复生成 PNG
+    let args: Vec<String> = std::env::args().collect();
+    if html_plot::requested(&args) {
+        save_interactive_plots(&fft_result, &magnitude_db, &samples, f_d_symmetric)?;
+    }
+
+    // 可选：导出原始数组（--npz / --mat），便于在 NumPy 或 MATLAB 中直接核对时域信号与复数频谱
+    if npy::requested(&args) || mat::requested(&args) {
+        let arrays = Npz::new()
+            .array("f_s", vec![sample_rate])
+            .array("x", samples.as_slice())
+            .array("frequencies", frequencies.as_slice())
+            .array("X", fft_result.spectrum.as_slice());
+        if npy::requested(&args) {
+            arrays.save("output/Q1_signals.npz")?;
+            info!("结果已保存到 output/Q1_signals.npz");
+        }
+        if mat::requested(&args) {
+            mat::save("output/Q1_signals.mat", &arrays.arrays)?;
+            info!("结果已保存到 output/Q1_signals.mat");
+        }
+    }
+
+    // f_d 的不确定度：对称轴由两个边带峰值的平均得到，误差按两峰值合成
+    let f_d_uncertainty = match best_pair {
+        Some(pair) => FrequencyUncertainty::symmetric_axis(
+            &FrequencyEstimator::frequency_uncertainty(frequencies, magnitude, pair.lower_index),
+            &FrequencyEstimator::frequency_uncertainty(frequencies, magnitude, pair.upper_index),
+        ),
+        None => FrequencyEstimator::frequency_uncertainty(frequencies, magnitude, peak_idx),
+    };
+    let (f_d_ci_low, f_d_ci_high) = f_d_uncertainty.confidence_interval(f_d_symmetric, 1.96);
+    info!("\n=== f_d 不确定度 ===");
+    info!("  频率网格量化 (Δf/√12): {:.4} Hz", f_d_uncertainty.resolution);
+    info!("  噪声 (Cramér–Rao 下界, SNR = {:.1} dB): {:.4} Hz", f_d_uncertainty.snr_db, f_d_uncertainty.crlb);
+    info!("  插值残差: {:.4} Hz", f_d_uncertainty.interpolation);
+    info!("  合成标准不确定度: {:.4} Hz", f_d_uncertainty.std_dev);
+    info!("  95% 置信区间: [{:.4}, {:.4}] Hz", f_d_ci_low, f_d_ci_high);
+
+    // 子空间法（MUSIC）交叉验证：在对称峰值对附近做高分辨率估计，
+    // 不受 FFT 频率分辨率限制
+    let f_d_music = if let Some(pair) = best_pair {
+        info!("");
+        let estimates = FrequencyEstimator::music_frequency_estimate(
+            &samples,
+            sample_rate,
+            2,
+            64,
+            (pair.lower_freq - 100.0, pair.upper_freq + 100.0),
+        );
+        if estimates.len() == 2 {
+            let axis = (estimates[0] + estimates[1]) / 2.0;
+            info!("  MUSIC 对称轴: {:.4} Hz", axis);
+            Some(axis)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    // 自相关交叉验证：关于 f_d 对称的边带在自相关中合成频率为 f_d 的振荡，
+    // 在 0.5·f_d ~ 1.5·f_d 对应的延迟范围内寻找最强的自相关峰
+    info!("\n=== 自相关交叉验证 ===");
+    let f_d_acf = autocorrelation::estimate_periodicity(
+        &samples,
+        sample_rate,
+        (0.5 * f_d_symmetric, 1.5 * f_d_symmetric),
+    );
+    match f_d_acf {
+        Some(p) => {
+            info!("  自相关主峰延迟: {:.3} 个采样点 (相关系数 {:.3})", p.lag, p.strength);
+            info!("  对应振荡频率: {:.2} Hz", p.frequency);
+            info!("  与对称峰值法相差: {:.2} Hz", p.frequency - f_d_symmetric);
+        }
+        None => info!("  未找到自相关峰"),
+    }
+
+    // 时变频率偏差跟踪：在滑动窗口上重复对称峰值分析，检查 f_d 是否漂移
+    info!("\n=== 时变频率偏差跟踪 ===");
+    let track = offset_tracker::track_frequency_offset(
+        &samples,
+        sample_rate,
+        8192,
+        2048,
+        5000.0,
+        f_d_symmetric,
+        20.0,
+    );
+    let valid = track.valid_offsets();
+    info!("  帧长 8192, 帧移 2048, 单帧分辨率 {:.2} Hz", track.bin_width);
+    info!("  有效帧数: {}/{}", valid.len(), track.offsets.len());
+    if let Some(mean) = track.mean() {
+        info!("  f_d(t) 平均值: {:.2} Hz, 漂移范围: {:.2} Hz", mean, track.drift());
+    }
+    // 漂移超过两个单帧频率分辨率时，固定频率的二次解调会使基带频谱展宽
+    let drift_threshold = 2.0 * track.bin_width;
+    if track.drift() > drift_threshold {
+        info!(
+            "  ⚠ 警告: f_d 漂移 {:.2} Hz 超过阈值 {:.2} Hz，使用单一 f_d 解调会使基带信号模糊",
+            track.drift(),
+            drift_threshold
+        );
+    } else {
+        info!("  f_d 在整段信号内保持稳定（漂移 ≤ {:.2} Hz）", drift_threshold);
+    }
+    SpectrumVisualizer::plot_offset_track(
+        &track.times,
+        &track.offsets,
+        f_d_symmetric,
+        plots.target("output/Q1_offset_track.png"),
+        "Carrier Offset Tracking f_d(t)",
+    )?;
+    // 瀑布图：0 ~ 5 kHz 的逐帧短时频谱，红线为 f_d
+    let waterfall_stft = Stft::compute(&samples, sample_rate, 4096, 1024, Window::Hann);
+    SpectrumVisualizer::plot_waterfall(
+        &waterfall_stft,
+        5000.0,
+        60,
+        &[f_d_symmetric],
+        plots.target("output/Q1_waterfall.png"),
+        "Short-Time Spectrum Waterfall",
+    )?;
+
     // 计算能量分布
     let energy_bands = vec![
         (0.0, 1000.0),
@@ -177,11 +474,20 @@ fn main() -> Result<(), Box<dyn Error>> {
         (4000.0, 8000.0),
         (8000.0, sample_rate / 2.0),
     ];
-    FrequencyEstimator::compute_energy_distribution(
+    let band_energies = FrequencyEstimator::compute_energy_distribution(
         magnitude,
         frequencies,
         &energy_bands,
     );
+    info!("\n能量分布:");
+    for band in &band_energies {
+        info!("  {}: {:.2}%", band.label(), band.percentage);
+    }
+    SpectrumVisualizer::plot_energy_distribution(
+        &[("Received signal", &band_energies)],
+        plots.target("output/Q1_energy_distribution.png"),
+        "Energy Distribution by Frequency Band",
+    )?;
 
     // 分析频率关系
     FrequencyEstimator::analyze_frequency_relationship(
@@ -190,57 +496,480 @@ fn main() -> Result<(), Box<dyn Error>> {
         f_d_refined,
     );
 
+    // 频谱特征描述量（单边谱）
+    let (single_freqs, single_mags) = fft_result.get_single_sided();
+    let spectral_features = SpectralFeatures::compute(&single_freqs, &single_mags, 0.85);
+
     // ===== 结果总结 =====
-    println!("\n========================================");
-    println!("分析结果总结");
-    println!("========================================");
-    println!("1. 频率偏差估计:");
-    println!("   峰值搜索法: {:.2} Hz (单个峰值)", f_d);
-    println!("   抛物线插值: {:.4} Hz (精确峰值)", f_d_refined);
-    println!("   对称峰值法: {:.2} Hz (频谱对称轴) ← 推荐使用", f_d_symmetric);
-    println!();
-    println!("   说明：");
-    println!("   - 单个峰值 ({:.2} Hz) 反映的是原始信号的能量分布", f_d_refined);
-    println!("   - 频谱对称轴 ({:.2} Hz) 才是真实的频率偏差 f_d = f_c - f̃_c", f_d_symmetric);
-    println!();
-    println!("2. 关于 f̃_c 与 f_c 的大小关系:");
-    println!("   - 仅从幅度谱无法唯一确定 f̃_c > f_c 还是 f̃_c < f_c");
-    println!("   - 原因: AM 信号的频谱具有共轭对称性");
-    println!("   - 无论符号如何，错误解调后的幅度谱都相同");
-    println!();
-    println!("3. 对解调结果的影响:");
-    println!("   - 频率偏差的符号不影响二次解调的效果");
-    println!("   - 因为我们使用的是 |f_c - f̃_c| = f_d");
-    println!("   - 二次解调时使用 cos(2πf_d·t)，无论符号如何都能正确解调");
-    println!();
-    println!("4. 所有图形已保存到 output 目录:");
-    println!("   - Q1_spectrum_full.png: 全频段频谱");
-    println!("   - Q1_spectrum_lowfreq.png: 低频段频谱 (0-4 kHz)");
-    println!("   - Q1_spectrum_db.png: dB 刻度频谱");
-    println!("   - Q1_waveform.png: 时域波形");
-    println!("========================================\n");
+    info!("\n========================================");
+    info!("分析结果总结");
+    info!("========================================");
+    info!("1. 频率偏差估计:");
+    info!("   峰值搜索法: {:.2} Hz (单个峰值)", f_d);
+    info!("   抛物线插值: {:.4} Hz (精确峰值)", f_d_refined);
+    info!("   dB 最小二乘插值: {:.4} ± {:.4} Hz", f_d_db_fit.frequency, f_d_db_fit.error_estimate);
+    info!("   对称峰值法: {:.2} Hz (频谱对称轴) ← 推荐使用", f_d_symmetric);
+    info!("   95% 置信区间: [{:.4}, {:.4}] Hz", f_d_ci_low, f_d_ci_high);
+    if let Some(p) = f_d_acf {
+        info!("   自相关法: {:.2} Hz (交叉验证)", p.frequency);
+    }
+    if let Some(f_d_music) = f_d_music {
+        info!("   MUSIC 子空间法: {:.4} Hz (高分辨率对称轴)", f_d_music);
+    }
+    info!("");
+    info!("   说明：");
+    info!("   - 单个峰值 ({:.2} Hz) 反映的是原始信号的能量分布", f_d_refined);
+    info!("   - 频谱对称轴 ({:.2} Hz) 才是真实的频率偏差 f_d = f_c - f̃_c", f_d_symmetric);
+    info!("");
+    info!("2. 关于 f̃_c 与 f_c 的大小关系:");
+    info!("   - 仅从幅度谱无法唯一确定 f̃_c > f_c 还是 f̃_c < f_c");
+    info!("   - 原因: AM 信号的频谱具有共轭对称性");
+    info!("   - 无论符号如何，错误解调后的幅度谱都相同");
+    info!("");
+    info!("3. 对解调结果的影响:");
+    info!("   - 频率偏差的符号不影响二次解调的效果");
+    info!("   - 因为我们使用的是 |f_c - f̃_c| = f_d");
+    info!("   - 二次解调时使用 cos(2πf_d·t)，无论符号如何都能正确解调");
+    info!("");
+    if let Some(sf) = spectral_features {
+        info!("4. 频谱特征:");
+        info!("   频谱质心: {:.2} Hz", sf.centroid);
+        info!("   频谱展宽: {:.2} Hz", sf.spread);
+        info!("   频谱平坦度: {:.6} (0 = 纯音, 1 = 白噪声)", sf.flatness);
+        info!("   {:.0}% 滚降频率: {:.2} Hz", sf.rolloff_fraction * 100.0, sf.rolloff);
+        info!("");
+    }
+    info!("5. 所有图形已保存到 output 目录:");
+    info!("   - Q1_spectrum_full.png: 全频段频谱");
+    info!("   - Q1_spectrum_lowfreq.png: 低频段频谱 (0-4 kHz)");
+    info!("   - Q1_spectrum_db.png: dB 刻度频谱");
+    info!("   - Q1_spectrum_centered.png: 双边频谱 (±f_d 标注)");
+    info!("   - Q1_waveform.png: 时域波形");
+    info!("   - Q1_offset_track.png: 频率偏差漂移 f_d(t)");
+    info!("   - Q1_energy_distribution.png: 频带能量分布");
+    info!("========================================\n");
 
     // 保存关键数据供后续使用（使用对称峰值法确定的频率偏差）
-    save_results_for_q2(f_d_symmetric, sample_rate)?;
+    save_results_for_q2("output/Q1_results.txt", f_d_symmetric, &f_d_uncertainty, sample_rate)?;
+
+    // 同样的结果以统一的 JSON 格式保存，便于比对与其他工具读取
+    let mut results = StageResults::new("Q1")
+        .parameter("input", audio_path.to_string())
+        .parameter("f_s_hz", sample_rate)
+        .parameter("f_b_hz", f_b)
+        .parameter("samples", samples.len())
+        .parameter("fft_length", fft_len)
+        .parameter("active_only", active_fraction.is_some())
+        .metric("active_fraction", active_fraction)
+        .metric("f_d_hz", f_d_symmetric)
+        .metric("f_d_std_hz", f_d_uncertainty.std_dev)
+        .metric("f_d_ci95_low_hz", f_d_ci_low)
+        .metric("f_d_ci95_high_hz", f_d_ci_high)
+        .metric("f_d_peak_hz", f_d)
+        .metric("f_d_parabolic_hz", f_d_refined)
+        .metric("f_d_db_fit_hz", f_d_db_fit.frequency)
+        .metric("f_d_music_hz", f_d_music)
+        .metric("f_d_autocorrelation_hz", f_d_acf.map(|p| p.frequency))
+        .metric("peak_snr_db", f_d_uncertainty.snr_db)
+        .table(
+            "peaks",
+            peaks.iter().map(|&(freq, mag, index)| row![("frequency_hz", freq), ("magnitude", mag), ("index", index)]).collect(),
+        )
+        .table(
+            "energy_bands",
+            band_energies
+                .iter()
+                .map(|b| row![("low_hz", b.low), ("high_hz", b.high), ("energy", b.energy), ("percentage", b.percentage)])
+                .collect(),
+        );
+    if let Some(sf) = spectral_features {
+        results = results
+            .metric("spectral_centroid_hz", sf.centroid)
+            .metric("spectral_spread_hz", sf.spread)
+            .metric("spectral_flatness", sf.flatness)
+            .metric("spectral_rolloff_hz", sf.rolloff);
+    }
+    results.reproducibility(&reproducibility).save_with("output/Q1_results.json", &reproducibility)?;
+    info!("结果已保存到 output/Q1_results.json");
+
+    if let Some(run_directory) = run_directory {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let dir = run_directory.finish("Q1", &[audio_path], &args)?;
+        info!("本次运行已归档到 {}（output/latest 指向该目录）", dir.display());
+    }
+
+    // 可选：终端频谱查看器（--tui），在结果保存后打开，通过 SSH 也能核对峰值与 f_d
+    if spectrum_tui::requested(&args) {
+        let mut view = SpectrumView::new(frequencies, magnitude, &peaks, f_d_symmetric, best_pair, (0.0, 10000.0));
+        spectrum_tui::show(&mut view)?;
+    }
 
     Ok(())
 }
 
+/// 解析命令行参数 `--input <文件>`（WAV/MP3/FLAC/OGG），默认 ../project.wav
+fn parse_input_path() -> String {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut path = "../project.wav".to_string();
+    let mut i = 0;
+    while i < args.len() {
+        if let Some(value) = args[i].strip_prefix("--input=") {
+            path = value.to_string();
+        } else if args[i] == "--input" {
+            if let Some(value) = args.get(i + 1) {
+                path = value.clone();
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+    path
+}
+
+/// 将单边频谱（线性与 dB）、双边频谱和时域波形写入 output/Q1_interactive.html，f_d 处标注虚线
+fn save_interactive_plots(
+    fft_result: &FftResult,
+    magnitude_db: &[f64],
+    samples: &[f64],
+    f_d: f64,
+) -> Result<(), Box<dyn Error>> {
+    let sample_rate = fft_result.sample_rate;
+    let one_sided = |values: &[f64]| -> Vec<(f64, f64)> {
+        fft_result.frequencies.iter()
+            .zip(values)
+            .filter(|(&f, _)| f <= sample_rate / 2.0)
+            .map(|(&f, &v)| (f, v))
+            .collect()
+    };
+    let (centered_freqs, centered_mags) = fft_result.get_two_sided_centered();
+
+    let page = InteractivePage::new("Q1: Spectrum of Misdemodulated Signal")
+        .chart(
+            InteractiveChart::new("Magnitude Spectrum", "Frequency (Hz)", "Magnitude")
+                .series("|X(f)|", one_sided(&fft_result.magnitude))
+                .marker(f_d, "f_d"),
+        )
+        .chart(
+            InteractiveChart::new("Magnitude Spectrum (dB)", "Frequency (Hz)", "Magnitude (dB)")
+                .series("|X(f)| (dB)", one_sided(magnitude_db))
+                .marker(f_d, "f_d"),
+        )
+        .chart(
+            InteractiveChart::new("Two-Sided Spectrum", "Frequency (Hz)", "Magnitude")
+                .series("|X(f)|", centered_freqs.into_iter().zip(centered_mags))
+                .marker(-f_d, "-f_d")
+                .marker(f_d, "f_d"),
+        )
+        .chart(InteractiveChart::new("Waveform", "Time (s)", "Amplitude").waveform("x(t)", samples, sample_rate));
+    page.save("output/Q1_interactive.html")?;
+    info!("交互式频谱与波形已保存到: output/Q1_interactive.html");
+    Ok(())
+}
+
+/// 解析命令行参数 `--raw <格式> --fs <采样率>`，用于读取无文件头的原始采样/IQ 文件
+fn parse_raw_format() -> Result<Option<(RawFormat, u32)>, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let value_of = |name: &str| -> Option<String> {
+        let prefix = format!("{}=", name);
+        args.iter().enumerate().find_map(|(i, arg)| {
+            if let Some(value) = arg.strip_prefix(&prefix) {
+                Some(value.to_string())
+            } else if arg == name {
+                args.get(i + 1).cloned()
+            } else {
+                None
+            }
+        })
+    };
+    let Some(format) = value_of("--raw") else {
+        return Ok(None);
+    };
+    let format: RawFormat = format.parse().map_err(DspError::Argument)?;
+    let fs = value_of("--fs").ok_or_else(|| DspError::Argument("--raw 需要同时用 --fs 指定采样率".to_string()))?;
+    let fs: u32 = fs.parse().map_err(|_| DspError::Argument(format!("无效的采样率: {}", fs)))?;
+    Ok(Some((format, fs)))
+}
+
+/// 解析命令行参数 `--channels <left|right|mix|both>`（也接受 `--channels=left`），默认 mix
+fn parse_channel_mode() -> Result<ChannelMode, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(ChannelMode::from_args(&args)?)
+}
+
+/// 解析命令行参数 `--plot-format <png|svg|pdf>`、`--plot-size <宽>x<高>` 与 `--plot-dpi <n>`，默认 PNG
+fn parse_plot_output() -> Result<PlotOutput, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(PlotOutput::from_args(&args)?)
+}
+
+/// 解析命令行参数 `--batch <目录>`
+fn parse_batch_dir() -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(batch::dir_from_args(&args)?)
+}
+
+/// 批处理：对目录中每个 WAV 文件估计 f_d
+///
+/// 每个文件的 Q1_results.txt / Q1_results.json 与标注 f_d 的低频段频谱写入 output/batch/<文件名>/，
+/// 格式与单文件运行相同，Q3/Q4 的 --batch 按同样的目录读取各文件的 f_d；
+/// 汇总表写入 output/Q1_batch_summary.csv，某个文件出错时记录错误并继续处理其余文件
+fn run_batch(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let files = batch::wav_files(dir)?;
+    let plots = parse_plot_output()?;
+    let reproducibility = parse_reproducibility()?;
+    // 与单文件运行相同，--channels both 时按混合声道估计
+    let mode = match parse_channel_mode()? {
+        ChannelMode::Both => ChannelMode::Mix,
+        mode => mode,
+    };
+    info!("批处理: {} 中的 {} 个 WAV 文件\n", dir.display(), files.len());
+
+    let columns = ["f_s_hz", "samples", "f_d_hz", "f_d_std_hz", "f_d_peak_hz", "symmetric_pair"];
+    let summary = BatchSummary::run(&files, &columns, |file| -> Result<Vec<String>, Box<dyn Error>> {
+        let audio = AudioData::open(file)?;
+        let samples = Preprocessor::apply(&audio.channels(mode).remove(0), &PreprocessConfig::default());
+        let sample_rate = audio.sample_rate as f64;
+        let fft = FftResult::compute_quiet(&samples, sample_rate);
+        // 与单文件运行相同，传入双边谱：frequency_uncertainty 按 N 点双边谱取噪声底与 CRLB 的 N
+        let (frequencies, magnitude) = (&fft.frequencies, &fft.magnitude);
+
+        // 与单文件运行相同：峰值搜索 + 抛物线插值，再以对称峰值对的对称轴作为 f_d
+        let (f_d, uncertainty, f_peak, best_pair) = symmetric_axis_estimate(frequencies, magnitude, sample_rate, |i| {
+            FrequencyEstimator::frequency_uncertainty(frequencies, magnitude, i)
+        });
+        info!("  f_d = {:.4} Hz (σ = {:.4} Hz){}", f_d, uncertainty.std_dev, if best_pair.is_none() { "，未找到对称峰值对" } else { "" });
+
+        let out_dir = batch::file_dir("output", file);
+        let out = |name: &str| out_dir.join(name).to_string_lossy().into_owned();
+        save_results_for_q2(&out("Q1_results.txt"), f_d, &uncertainty, sample_rate)?;
+        StageResults::new("Q1")
+            .parameter("input", file.to_string_lossy().into_owned())
+            .parameter("f_s_hz", sample_rate)
+            .parameter("samples", samples.len())
+            .reproducibility(&reproducibility)
+            .metric("f_d_hz", f_d)
+            .metric("f_d_std_hz", uncertainty.std_dev)
+            .metric("f_d_peak_hz", f_peak)
+            .metric("symmetric_pair_found", best_pair.is_some())
+            .save_with(out("Q1_results.json"), &reproducibility)?;
+        let axis_label = format!("f_d = {:.0} Hz", f_d);
+        SpectrumVisualizer::plot_spectrum(
+            frequencies,
+            magnitude,
+            plots.target(&out("Q1_spectrum_lowfreq.png")),
+            &format!("Spectrum of {} (0-5 kHz)", batch::stem(file)),
+            Some(5000.0f64.min(sample_rate / 2.0)),
+            &[(f_d, &axis_label)],
+        )?;
+
+        Ok(vec![
+            format!("{}", sample_rate),
+            samples.len().to_string(),
+            format!("{:.4}", f_d),
+            format!("{:.4}", uncertainty.std_dev),
+            format!("{:.4}", f_peak),
+            best_pair.is_some().to_string(),
+        ])
+    });
+
+    std::fs::create_dir_all("output")?;
+    summary.save("output/Q1_batch_summary.csv")?;
+    info!("\n批处理完成: {} 个文件成功，{} 个失败", files.len() - summary.failures(), summary.failures());
+    info!("每个文件的结果: output/batch/<文件名>/，汇总: output/Q1_batch_summary.csv");
+    Ok(())
+}
+
+/// 解析命令行参数 `--streaming`：只做分段平均频谱，不把整段录音读入内存
+fn parse_streaming() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--streaming")
+}
+
+/// 流式分析：内存映射读取 WAV，按块送入分段平均频谱，由平均频谱估计 f_d
+///
+/// 不调用 `AudioData::open`，也不做整段 FFT，内存只与段长有关，适用于无法整体读入的长录音。
+/// 分辨率为 f_s / 8192（44.1 kHz 时约 5.4 Hz），比整段 FFT 粗，f_d 的不确定度相应更大；
+/// 结果写入与单文件运行相同的 Q1_results.txt / Q1_results.json，供 Q2-Q4 读取
+fn run_streaming() -> Result<(), Box<dyn Error>> {
+    const SEGMENT_LEN: usize = 8192;
+    const CHUNK_LEN: usize = 4096;
+
+    let audio_path = parse_input_path();
+    if parse_raw_format()?.is_some() || !audio_path.to_ascii_lowercase().ends_with(".wav") {
+        return Err(DspError::Argument("--streaming 只支持 WAV 文件（需要内存映射读取）".to_string()).into());
+    }
+    let plots = parse_plot_output()?;
+    let reproducibility = parse_reproducibility()?;
+    let channel_mode = parse_channel_mode()?;
+
+    info!("步骤 1: 内存映射读取 {}（流式分析，不整体读入）...", audio_path);
+    let mapped = AudioData::open_mmap(&audio_path)?;
+    let sample_rate = mapped.spec.sample_rate as f64;
+    let num_frames = mapped.num_frames();
+    info!("  {} 个声道，{} 帧，{:.2} 秒", mapped.spec.channels, num_frames, num_frames as f64 / sample_rate);
+
+    // 与单文件运行相同，--channels both 时按混合声道估计
+    let pick = |frame: Vec<f64>| match channel_mode {
+        ChannelMode::Left => frame[0],
+        ChannelMode::Right => frame[frame.len() - 1],
+        ChannelMode::Mix | ChannelMode::Both => frame.iter().sum::<f64>() / frame.len() as f64,
+    };
+
+    info!("步骤 2: 分段平均频谱 (段长 {}, Hann 窗, 50% 重叠)...", SEGMENT_LEN);
+    let mut chunked = ChunkedSpectrum::new(SEGMENT_LEN, sample_rate);
+    let mut chunk = Vec::with_capacity(CHUNK_LEN);
+    for frame in mapped.frames() {
+        chunk.push(pick(frame));
+        if chunk.len() == CHUNK_LEN {
+            chunked.push(&chunk);
+            chunk.clear();
+        }
+    }
+    chunked.push(&chunk);
+    let averaged = chunked.finish();
+    if averaged.num_segments == 0 {
+        return Err(DspError::Data(format!("{} 中没有采样点，无法流式分析", audio_path)).into());
+    }
+    info!("  {} 段，分辨率 {:.2} Hz\n", averaged.num_segments, sample_rate / SEGMENT_LEN as f64);
+
+    info!("步骤 3: 由平均频谱估计频率偏差 f_d...");
+    let (frequencies, magnitude) = (&averaged.frequencies, &averaged.magnitude);
+    // 平均谱是单边、按窗归一化的 K 段平均，不确定度按段长与段数换算
+    let (f_d, uncertainty, f_peak, best_pair) = symmetric_axis_estimate(frequencies, magnitude, sample_rate, |i| {
+        FrequencyEstimator::averaged_frequency_uncertainty(&averaged, i)
+    });
+    match best_pair {
+        Some(pair) => info!(
+            "  对称峰值对 {:.2} Hz / {:.2} Hz，对称轴 f_d = {:.4} Hz (σ = {:.4} Hz)",
+            pair.lower_freq, pair.upper_freq, f_d, uncertainty.std_dev
+        ),
+        None => info!("  未找到对称峰值对，取最强峰 f_d = {:.4} Hz (σ = {:.4} Hz)", f_d, uncertainty.std_dev),
+    }
+
+    std::fs::create_dir_all("output")?;
+    let axis_label = format!("f_d = {:.0} Hz", f_d);
+    SpectrumVisualizer::plot_spectrum(
+        frequencies,
+        magnitude,
+        plots.target("output/Q1_spectrum_streaming.png"),
+        "Averaged Spectrum (streaming, 0-5 kHz)",
+        Some(5000.0f64.min(sample_rate / 2.0)),
+        &[(f_d, &axis_label)],
+    )?;
+    save_results_for_q2("output/Q1_results.txt", f_d, &uncertainty, sample_rate)?;
+    StageResults::new("Q1")
+        .parameter("input", audio_path.clone())
+        .parameter("f_s_hz", sample_rate)
+        .parameter("samples", num_frames)
+        .parameter("streaming", true)
+        .parameter("segment_length", SEGMENT_LEN)
+        .reproducibility(&reproducibility)
+        .metric("num_segments", averaged.num_segments)
+        .metric("f_d_hz", f_d)
+        .metric("f_d_std_hz", uncertainty.std_dev)
+        .metric("f_d_peak_hz", f_peak)
+        .metric("symmetric_pair_found", best_pair.is_some())
+        .save_with("output/Q1_results.json", &reproducibility)?;
+    info!("结果已保存到 output/Q1_results.json");
+    Ok(())
+}
+
+/// 峰值搜索 + 抛物线插值，再以最佳对称峰值对的对称轴作为 f_d（批处理与流式分析共用）
+///
+/// 返回 (f_d, f_d 的不确定度, 最强峰频率, 对称峰值对)；未找到对称峰值对时 f_d 取最强峰
+///
+/// uncertainty_at 给出某个峰值 bin 的频率不确定度，随频谱的构成（双边整段谱或分段平均谱）而不同
+fn symmetric_axis_estimate(
+    frequencies: &[f64],
+    magnitude: &[f64],
+    sample_rate: f64,
+    uncertainty_at: impl Fn(usize) -> FrequencyUncertainty,
+) -> (f64, FrequencyUncertainty, f64, Option<SymmetricPair>) {
+    let search = (10.0, 10000.0f64.min(sample_rate / 2.0));
+    let (_, _, peak_idx) = FrequencyEstimator::estimate_frequency_offset(frequencies, magnitude, search);
+    let f_peak = FrequencyEstimator::refined_frequency_estimate(frequencies, magnitude, peak_idx);
+    let peaks = FrequencyEstimator::find_multiple_peaks(frequencies, magnitude, 5, 20, magnitude[peak_idx] * 0.1);
+    let best_pair = FrequencyEstimator::best_symmetric_pair(&FrequencyEstimator::find_symmetric_pairs(&peaks, 5000.0, 0.9));
+    let (f_d, uncertainty) = match best_pair {
+        Some(pair) => (
+            pair.axis,
+            FrequencyUncertainty::symmetric_axis(&uncertainty_at(pair.lower_index), &uncertainty_at(pair.upper_index)),
+        ),
+        None => (f_peak, uncertainty_at(peak_idx)),
+    };
+    (f_d, uncertainty, f_peak, best_pair)
+}
+
+/// 解析命令行参数 `--run-dir` 与 `--run-tag <标签>`，未给出时不归档
+fn parse_run_directory() -> Result<Option<RunDirectory>, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(RunDirectory::from_args(&args, "output")?)
+}
+
+/// 解析命令行参数 `--seed <n>` 与 `--deterministic`
+fn parse_reproducibility() -> Result<Reproducibility, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(Reproducibility::from_args(&args)?)
+}
+
+/// 解析命令行参数 `--active-only` 与 `--activity-threshold <dB>`，未给出时分析整段录音
+fn parse_activity_detector() -> Result<Option<ActivityDetector>, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    Ok(ActivityDetector::from_args(&args)?)
+}
+
+/// 解析命令行参数 `--fft-length <keep|pad|truncate>`（也接受 `--fft-length=pad`），默认 keep
+fn parse_fft_length_policy() -> Result<FftLengthPolicy, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut policy = FftLengthPolicy::Keep;
+    let mut i = 0;
+    while i < args.len() {
+        if let Some(value) = args[i].strip_prefix("--fft-length=") {
+            policy = value.parse().map_err(DspError::Argument)?;
+        } else if args[i] == "--fft-length" {
+            let value = args.get(i + 1).ok_or_else(|| DspError::Argument("--fft-length 缺少参数".to_string()))?;
+            policy = value.parse().map_err(DspError::Argument)?;
+            i += 1;
+        }
+        i += 1;
+    }
+    Ok(policy)
+}
+
+/// 单次复数 FFT 的耗时（不含规划），用于比较不同长度
+fn time_fft(len: usize) -> Duration {
+    let fft = FftPlanner::<f64>::new().plan_fft_forward(len);
+    let mut buffer = vec![Complex::new(1.0, 0.0); len];
+    let start = Instant::now();
+    fft.process(&mut buffer);
+    start.elapsed()
+}
+
 /// 保存结果供 Q2 使用
-fn save_results_for_q2(f_d: f64, sample_rate: f64) -> Result<(), Box<dyn Error>> {
+fn save_results_for_q2(
+    path: &str,
+    f_d: f64,
+    uncertainty: &FrequencyUncertainty,
+    sample_rate: f64,
+) -> Result<(), Box<dyn Error>> {
     use std::fs;
     use std::io::Write;
 
-    fs::create_dir_all("output")?;
-    let mut file = fs::File::create("output/Q1_results.txt")?;
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::File::create(path)?;
     
     writeln!(file, "Q1 分析结果")?;
     writeln!(file, "===========")?;
     writeln!(file, "频率偏差 f_d = {:.4} Hz", f_d)?;
+    // 以下两行不含 f_d 字样，避免被 Q3/Q4 的 f_d 解析误读
+    writeln!(file, "估计标准不确定度 σ = {:.4} Hz", uncertainty.std_dev)?;
+    let (low, high) = uncertainty.confidence_interval(f_d, 1.96);
+    writeln!(file, "95% 置信区间 = [{:.4}, {:.4}] Hz", low, high)?;
     writeln!(file, "采样率 f_s = {:.2} Hz", sample_rate)?;
     writeln!(file, "基带带宽 f_B = 4000 Hz")?;
     
-    println!("结果已保存到 output/Q1_results.txt");
+    info!("结果已保存到 {}", path);
     
     Ok(())
 }
@@ -0,0 +1,142 @@
+// 5. 时变频率偏差跟踪模块
+// 在滑动窗口（STFT 帧）上重复对称峰值分析，得到 f_d(t)
+
+use crate::frequency_estimator::{FrequencyEstimator, InterpolationScale};
+use dsp_core::stft::{Stft, Window};
+
+/// 频率偏差随时间的变化轨迹
+#[derive(Debug, Clone)]
+pub struct OffsetTrack {
+    /// 各帧中心时刻 (s)
+    pub times: Vec<f64>,
+    /// 各帧估计的频率偏差 (Hz)，未找到对称峰值对的帧为 None
+    pub offsets: Vec<Option<f64>>,
+    /// 单帧频率分辨率 (Hz)
+    pub bin_width: f64,
+}
+
+impl OffsetTrack {
+    /// 有效估计值
+    pub fn valid_offsets(&self) -> Vec<f64> {
+        self.offsets.iter().flatten().copied().collect()
+    }
+
+    /// 有效估计值的平均
+    pub fn mean(&self) -> Option<f64> {
+        let valid = self.valid_offsets();
+        if valid.is_empty() {
+            None
+        } else {
+            Some(valid.iter().sum::<f64>() / valid.len() as f64)
+        }
+    }
+
+    /// 漂移量：有效估计值的最大值与最小值之差 (Hz)
+    pub fn drift(&self) -> f64 {
+        let valid = self.valid_offsets();
+        let max = valid.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = valid.iter().copied().fold(f64::INFINITY, f64::min);
+        if valid.is_empty() { 0.0 } else { max - min }
+    }
+}
+
+/// 逐帧跟踪频率偏差
+///
+/// # 参数
+/// - samples: 时域信号
+/// - sample_rate: 采样率 (Hz)
+/// - window_len: 帧长（采样点），决定单帧频率分辨率 fs / window_len
+/// - hop: 帧移（采样点）
+/// - max_freq: 对称峰值搜索上限 (Hz)
+/// - initial: 初始频率偏差 (Hz)，通常取全局对称峰值法的结果
+/// - max_step: 相邻有效帧之间允许的最大变化 (Hz)
+///
+/// 短帧内语音成分变化大，可能出现多个偶然对称的峰值对。每帧在距上一有效估计
+/// max_step 以内的峰值对中选取对称轴最接近的一对，没有候选时该帧记为 None。
+pub fn track_frequency_offset(
+    samples: &[f64],
+    sample_rate: f64,
+    window_len: usize,
+    hop: usize,
+    max_freq: f64,
+    initial: f64,
+    max_step: f64,
+) -> OffsetTrack {
+    let stft = Stft::compute(samples, sample_rate, window_len, hop, Window::Hann);
+
+    let mut previous = initial;
+    let offsets = stft
+        .magnitude()
+        .iter()
+        .map(|frame| {
+            let peak = frame.iter().copied().fold(0.0f64, f64::max);
+            // 与全局分析一致：阈值取主峰的 10%，最小间隔折算为 ~15 Hz
+            let min_distance = (15.0 / stft.bin_width()).ceil().max(1.0) as usize;
+            let peaks = FrequencyEstimator::detect_peaks(
+                &stft.frequencies,
+                frame,
+                5,
+                min_distance,
+                peak * 0.1,
+            );
+            // 帧长较短时 bin 宽度较大，先对每个峰值做 dB 抛物线插值再配对
+            let refined: Vec<(f64, f64, usize)> = peaks
+                .iter()
+                .map(|&(_, mag, idx)| {
+                    let fit = FrequencyEstimator::interpolate_peak(
+                        &stft.frequencies,
+                        frame,
+                        idx,
+                        InterpolationScale::Decibel,
+                        1,
+                    );
+                    (fit.frequency, mag, idx)
+                })
+                .collect();
+            let axis = FrequencyEstimator::find_symmetric_pairs(&refined, max_freq, 0.8)
+                .into_iter()
+                .map(|pair| pair.axis)
+                .filter(|axis| (axis - previous).abs() <= max_step)
+                .min_by(|a, b| (a - previous).abs().total_cmp(&(b - previous).abs()));
+            if let Some(axis) = axis {
+                previous = axis;
+            }
+            axis
+        })
+        .collect();
+
+    OffsetTrack {
+        times: stft.times.clone(),
+        offsets,
+        bin_width: stft.bin_width(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_track_linear_drift() {
+        // 基带 200 Hz 单音调制在线性漂移的载波上：f_d 从 1000 Hz 漂移到 1020 Hz
+        let sample_rate = 8000.0;
+        let n = 8000 * 4;
+        let duration = n as f64 / sample_rate;
+        let mut phase = 0.0;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| {
+                let t = i as f64 / sample_rate;
+                let f_d = 1000.0 + 20.0 * t / duration;
+                phase += 2.0 * PI * f_d / sample_rate;
+                (2.0 * PI * 200.0 * t).cos() * phase.cos()
+            })
+            .collect();
+
+        let track = track_frequency_offset(&samples, sample_rate, 4096, 2048, 3000.0, 1000.0, 20.0);
+        let valid = track.valid_offsets();
+        assert!(valid.len() >= track.offsets.len() / 2);
+        assert!(valid.first().unwrap() < valid.last().unwrap());
+        assert!(track.drift() > 10.0);
+    }
+}
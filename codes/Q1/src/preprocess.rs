@@ -0,0 +1,121 @@
+// 预处理模块
+// FFT 之前去除直流偏移、线性趋势，并可选预加重，避免直流 bin 主导幅度刻度
+
+/// 预处理选项
+#[derive(Debug, Clone, Copy)]
+pub struct PreprocessConfig {
+    /// 去除均值（直流偏移）
+    pub remove_mean: bool,
+    /// 去除线性趋势（同时去除均值）
+    pub detrend: bool,
+    /// 预加重系数 α：y[n] = x[n] − α·x[n−1]，None 表示不做预加重
+    pub pre_emphasis: Option<f64>,
+}
+
+impl Default for PreprocessConfig {
+    /// 默认：去均值并去线性趋势，不做预加重（预加重会改变各频率的相对幅度）
+    fn default() -> Self {
+        PreprocessConfig {
+            remove_mean: true,
+            detrend: true,
+            pre_emphasis: None,
+        }
+    }
+}
+
+/// 信号预处理器
+pub struct Preprocessor;
+
+impl Preprocessor {
+    /// 按配置依次执行去趋势 / 去均值、预加重
+    pub fn apply(samples: &[f64], config: &PreprocessConfig) -> Vec<f64> {
+        let mut output = if config.detrend {
+            Self::remove_linear_trend(samples)
+        } else if config.remove_mean {
+            Self::remove_mean(samples)
+        } else {
+            samples.to_vec()
+        };
+
+        if let Some(alpha) = config.pre_emphasis {
+            output = Self::pre_emphasis(&output, alpha);
+        }
+
+        output
+    }
+
+    /// 去除均值
+    pub fn remove_mean(samples: &[f64]) -> Vec<f64> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        samples.iter().map(|&x| x - mean).collect()
+    }
+
+    /// 去除最小二乘拟合的直线 a + b·n
+    pub fn remove_linear_trend(samples: &[f64]) -> Vec<f64> {
+        let n = samples.len();
+        if n < 2 {
+            return Self::remove_mean(samples);
+        }
+
+        let n_f = n as f64;
+        let mean_x = (n_f - 1.0) / 2.0;
+        let mean_y = samples.iter().sum::<f64>() / n_f;
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (i, &y) in samples.iter().enumerate() {
+            let dx = i as f64 - mean_x;
+            sxy += dx * (y - mean_y);
+            sxx += dx * dx;
+        }
+        let slope = sxy / sxx;
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| y - mean_y - slope * (i as f64 - mean_x))
+            .collect()
+    }
+
+    /// 一阶预加重滤波 y[n] = x[n] − α·x[n−1]（y[0] = x[0]）
+    pub fn pre_emphasis(samples: &[f64], alpha: f64) -> Vec<f64> {
+        let mut previous = 0.0;
+        samples
+            .iter()
+            .map(|&x| {
+                let y = x - alpha * previous;
+                previous = x;
+                y
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detrend_removes_offset_and_ramp() {
+        let samples: Vec<f64> = (0..100)
+            .map(|i| 0.5 + 0.01 * i as f64 + (i as f64 * 0.7).sin())
+            .collect();
+        let detrended = Preprocessor::apply(&samples, &PreprocessConfig::default());
+
+        let mean = detrended.iter().sum::<f64>() / detrended.len() as f64;
+        assert!(mean.abs() < 1e-12);
+        // 去趋势后残差应只剩正弦分量，不再随 n 线性增长
+        let first_half: f64 = detrended[..50].iter().sum();
+        let second_half: f64 = detrended[50..].iter().sum();
+        assert!((first_half - second_half).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_pre_emphasis() {
+        let output = Preprocessor::pre_emphasis(&[1.0, 1.0, 1.0], 0.9);
+        assert_eq!(output[0], 1.0);
+        assert!((output[1] - 0.1).abs() < 1e-12);
+        assert!((output[2] - 0.1).abs() < 1e-12);
+    }
+}
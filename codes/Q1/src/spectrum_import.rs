@@ -0,0 +1,142 @@
+// 频谱导入模块
+// 读取其他工具（MATLAB、Python、频谱仪等）导出的频率/幅度 CSV，
+// 使频率估计与绘图模块可以脱离音频文件单独使用
+
+use dsp_core::error::DspError;
+use std::path::Path;
+
+/// 幅度列的刻度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MagnitudeScale {
+    /// 线性幅度
+    Linear,
+    /// 20·log10 幅度 (dB)，读入后换算为线性幅度
+    Decibel,
+}
+
+/// 从 CSV 导入的单边幅度谱
+#[derive(Debug, Clone)]
+pub struct ImportedSpectrum {
+    /// 频率轴（Hz），严格递增
+    pub frequencies: Vec<f64>,
+    /// 线性幅度谱
+    pub magnitude: Vec<f64>,
+    /// 幅度取自的列名（无表头时为 "column 2"）
+    pub column: String,
+    /// 原文件的幅度刻度
+    pub scale: MagnitudeScale,
+}
+
+impl ImportedSpectrum {
+    /// 读取频率/幅度 CSV
+    ///
+    /// 第一列为频率 (Hz)。有表头时取第一个名称含 "mag"（不区分大小写）且不含 "dB" 的列，
+    /// 没有则取第一个含 "dB" 的列并按 dB 换算；无表头时取第二列，`scale` 决定其刻度。
+    /// 分隔符可为逗号、分号或制表符，空行与以 `#` 开头的注释行被跳过。
+    /// `export_csv` 生成的 `Q1_spectrum_*.csv` 可以直接读回。
+    pub fn read_csv<P: AsRef<Path>>(path: P, scale: MagnitudeScale) -> Result<Self, DspError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
+        let invalid = |message: String| DspError::Data(format!("{}: {}", path.display(), message));
+        let split = |line: &str| -> Vec<String> {
+            line.split([',', ';', '\t']).map(|cell| cell.trim().to_string()).collect()
+        };
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .peekable();
+
+        // 第一行的第一格不是数字时视为表头
+        let header = match lines.peek() {
+            Some((_, line)) if split(line)[0].parse::<f64>().is_err() => lines.next().map(|(_, line)| split(line)),
+            _ => None,
+        };
+        let (index, column, scale) = match &header {
+            Some(names) => {
+                let lower: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+                let linear = (1..names.len()).find(|&i| lower[i].contains("mag") && !lower[i].contains("db"));
+                let decibel = (1..names.len()).find(|&i| lower[i].contains("db"));
+                match (linear, decibel) {
+                    (Some(i), _) => (i, names[i].clone(), MagnitudeScale::Linear),
+                    (None, Some(i)) => (i, names[i].clone(), MagnitudeScale::Decibel),
+                    (None, None) if names.len() > 1 => (1, names[1].clone(), scale),
+                    (None, None) => return Err(invalid("只有一列，缺少幅度列".to_string())),
+                }
+            }
+            None => (1, "column 2".to_string(), scale),
+        };
+
+        let mut frequencies = Vec::new();
+        let mut magnitude = Vec::new();
+        for (line_no, line) in lines {
+            let cells = split(line);
+            let value = |i: usize| cells.get(i).and_then(|cell| cell.parse::<f64>().ok());
+            let (Some(freq), Some(mag)) = (value(0), value(index)) else {
+                return Err(invalid(format!("第 {} 行无法解析: {}", line_no + 1, line)));
+            };
+            if frequencies.last().is_some_and(|&last| freq <= last) {
+                return Err(invalid(format!("第 {} 行频率不是严格递增", line_no + 1)));
+            }
+            frequencies.push(freq);
+            magnitude.push(match scale {
+                MagnitudeScale::Linear => mag,
+                MagnitudeScale::Decibel => 10f64.powf(mag / 20.0),
+            });
+        }
+        if frequencies.len() < 3 {
+            return Err(invalid("至少需要 3 个频点".to_string()));
+        }
+
+        Ok(ImportedSpectrum { frequencies, magnitude, column, scale })
+    }
+
+    /// 平均频率间隔 (Hz)
+    pub fn resolution(&self) -> f64 {
+        let n = self.frequencies.len();
+        (self.frequencies[n - 1] - self.frequencies[0]) / (n - 1) as f64
+    }
+
+    /// 频率间隔相对平均值的最大偏差；峰值插值假定等间隔，偏差大时插值结果不可靠
+    pub fn spacing_deviation(&self) -> f64 {
+        let resolution = self.resolution();
+        self.frequencies
+            .windows(2)
+            .map(|w| ((w[1] - w[0]) / resolution - 1.0).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_export_csv_and_headerless_db() {
+        let dir = std::env::temp_dir();
+        let exported = dir.join(format!("q1_spectrum_import_{}.csv", std::process::id()));
+        std::fs::write(
+            &exported,
+            "Frequency (Hz),Magnitude,Magnitude (dB)\n0.0000,0.001000,-60.0\n0.5000,0.500000,-6.02\n1.0000,0.002000,-54.0\n",
+        )
+        .unwrap();
+        let spectrum = ImportedSpectrum::read_csv(&exported, MagnitudeScale::Decibel).unwrap();
+        assert_eq!(spectrum.column, "Magnitude");
+        assert_eq!(spectrum.scale, MagnitudeScale::Linear);
+        assert_eq!(spectrum.magnitude, vec![0.001, 0.5, 0.002]);
+        assert!((spectrum.resolution() - 0.5).abs() < 1e-12);
+
+        // 无表头、分号分隔、dB 刻度，带注释行
+        let headerless = dir.join(format!("q1_spectrum_import_db_{}.csv", std::process::id()));
+        std::fs::write(&headerless, "# analyzer trace\n100;-20\n200;0\n400;-40\n").unwrap();
+        let spectrum = ImportedSpectrum::read_csv(&headerless, MagnitudeScale::Decibel).unwrap();
+        assert!((spectrum.magnitude[0] - 0.1).abs() < 1e-12);
+        assert!((spectrum.magnitude[1] - 1.0).abs() < 1e-12);
+        assert!((spectrum.spacing_deviation() - 1.0 / 3.0).abs() < 1e-12);
+
+        std::fs::write(&headerless, "100,1\n100,2\n200,3\n").unwrap();
+        assert!(ImportedSpectrum::read_csv(&headerless, MagnitudeScale::Linear).is_err());
+        std::fs::remove_file(&exported).unwrap();
+        std::fs::remove_file(&headerless).unwrap();
+    }
+}
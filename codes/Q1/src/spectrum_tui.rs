@@ -0,0 +1,323 @@
+// 终端频谱查看器（--tui）
+// 用 ratatui 在终端里以柱状图显示幅度谱、检测到的峰值与 f_d，
+// 通过 SSH 运行时不必打开 PNG 也能核对估计结果
+
+use crate::frequency_estimator::SymmetricPair;
+
+/// 柱状图显示的动态范围：低于全局峰值这么多 dB 的列画成空白
+pub const DISPLAY_RANGE_DB: f64 = 80.0;
+
+/// 命令行中是否给出了 `--tui`
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--tui")
+}
+
+/// 查看器显示的内容与当前频率范围
+#[derive(Debug, Clone)]
+pub struct SpectrumView<'a> {
+    pub frequencies: &'a [f64],
+    pub magnitude: &'a [f64],
+    /// 检测到的峰值 (频率, 幅度, 索引)
+    pub peaks: &'a [(f64, f64, usize)],
+    /// 估计的频率偏差 (Hz)
+    pub f_d: f64,
+    /// 确定 f_d 的对称峰值对
+    pub pair: Option<SymmetricPair>,
+    /// 当前显示的频率范围 (Hz)
+    pub range: (f64, f64),
+    /// 按 h 回到的初始范围
+    home: (f64, f64),
+}
+
+impl<'a> SpectrumView<'a> {
+    pub fn new(
+        frequencies: &'a [f64],
+        magnitude: &'a [f64],
+        peaks: &'a [(f64, f64, usize)],
+        f_d: f64,
+        pair: Option<SymmetricPair>,
+        range: (f64, f64),
+    ) -> Self {
+        let mut view = SpectrumView { frequencies, magnitude, peaks, f_d, pair, range, home: range };
+        view.set_range(range.0, range.1);
+        view.home = view.range;
+        view
+    }
+
+    /// 频率轴的上限（最后一个频点）
+    fn max_freq(&self) -> f64 {
+        self.frequencies.last().copied().unwrap_or(0.0)
+    }
+
+    /// 设置显示范围，限制在 [0, 最高频率] 内且至少包含两个频点
+    pub fn set_range(&mut self, low: f64, high: f64) {
+        let max = self.max_freq();
+        let bin = if self.frequencies.len() > 1 { self.frequencies[1] - self.frequencies[0] } else { 1.0 };
+        let span = (high - low).max(2.0 * bin).min(max.max(2.0 * bin));
+        let low = low.clamp(0.0, (max - span).max(0.0));
+        self.range = (low, low + span);
+    }
+
+    /// 以当前中心缩放显示范围，factor < 1 放大
+    pub fn zoom(&mut self, factor: f64) {
+        let center = 0.5 * (self.range.0 + self.range.1);
+        let half = 0.5 * (self.range.1 - self.range.0) * factor;
+        self.set_range(center - half, center + half);
+    }
+
+    /// 平移显示范围，fraction 以当前宽度为单位，正数向高频
+    pub fn pan(&mut self, fraction: f64) {
+        let shift = (self.range.1 - self.range.0) * fraction;
+        self.set_range(self.range.0 + shift, self.range.1 + shift);
+    }
+
+    /// 保持宽度，把 f_d 移到中央
+    pub fn center_on_f_d(&mut self) {
+        let half = 0.5 * (self.range.1 - self.range.0);
+        self.set_range(self.f_d - half, self.f_d + half);
+    }
+
+    /// 回到初始范围
+    pub fn reset(&mut self) {
+        self.range = self.home;
+    }
+
+    /// 频率 f 落在 width 列中的哪一列（范围外为 None）
+    pub fn column_of(&self, f: f64, width: usize) -> Option<usize> {
+        let (low, high) = self.range;
+        if width == 0 || f < low || f > high {
+            return None;
+        }
+        Some((((f - low) / (high - low)) * width as f64).min(width as f64 - 1.0) as usize)
+    }
+
+    /// 把显示范围内的频谱分成 width 列，每列取最大幅度，
+    /// 以全局峰值为 0 dB，截断在 -DISPLAY_RANGE_DB；没有频点的列为 None
+    pub fn columns_db(&self, width: usize) -> Vec<Option<f64>> {
+        let mut columns = vec![None::<f64>; width];
+        let top = self.magnitude.iter().fold(0.0f64, |m, &x| m.max(x));
+        for (&f, &mag) in self.frequencies.iter().zip(self.magnitude) {
+            if let Some(column) = self.column_of(f, width) {
+                let current = columns[column].get_or_insert(0.0);
+                *current = current.max(mag);
+            }
+        }
+        columns
+            .into_iter()
+            .map(|column| {
+                column.map(|mag| {
+                    if top <= 0.0 || mag <= 0.0 {
+                        -DISPLAY_RANGE_DB
+                    } else {
+                        (20.0 * (mag / top).log10()).max(-DISPLAY_RANGE_DB)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "tui")]
+mod render {
+    use super::{SpectrumView, DISPLAY_RANGE_DB};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Layout, Rect};
+    use ratatui::style::{Color, Style};
+    use ratatui::text::Line;
+    use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Borders, Paragraph};
+    use ratatui::Frame;
+
+    /// 右侧峰值列表的宽度（列）
+    const SIDEBAR_WIDTH: u16 = 34;
+
+    impl SpectrumView<'_> {
+        /// 在 frame 中画出标题、柱状图、频率轴与峰值列表
+        pub fn render(&self, frame: &mut Frame) {
+            let [main, sidebar] =
+                Layout::horizontal([Constraint::Min(20), Constraint::Length(SIDEBAR_WIDTH)]).areas(frame.area());
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Q1 幅度谱  f_d = {:.2} Hz ", self.f_d))
+                .title_bottom(" +/- 缩放  ←/→ 平移  d 居中 f_d  h 复位  q 退出 ");
+            let inner = block.inner(main);
+            frame.render_widget(block, main);
+            let [chart_area, axis_area] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+            self.render_bars(frame, chart_area);
+            self.render_axis(frame, axis_area);
+            frame.render_widget(Paragraph::new(self.sidebar_lines()).block(Block::default().borders(Borders::ALL).title(" 峰值 ")), sidebar);
+        }
+
+        /// 每列一根柱子：峰值所在列标红，f_d 所在列标黄
+        fn render_bars(&self, frame: &mut Frame, area: Rect) {
+            let width = area.width as usize;
+            let peak_columns: Vec<usize> = self.peaks.iter().filter_map(|&(f, _, _)| self.column_of(f, width)).collect();
+            let f_d_column = self.column_of(self.f_d, width);
+            let bars: Vec<Bar> = self
+                .columns_db(width)
+                .iter()
+                .enumerate()
+                .map(|(i, db)| {
+                    let height = db.map_or(0, |db| ((db + DISPLAY_RANGE_DB) * 10.0).round() as u64);
+                    let color = if Some(i) == f_d_column {
+                        Color::Yellow
+                    } else if peak_columns.contains(&i) {
+                        Color::Red
+                    } else {
+                        Color::Cyan
+                    };
+                    Bar::default().value(height).text_value(String::new()).style(Style::default().fg(color))
+                })
+                .collect();
+            let chart = BarChart::default()
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(1)
+                .bar_gap(0)
+                .max((DISPLAY_RANGE_DB * 10.0) as u64);
+            frame.render_widget(chart, area);
+        }
+
+        /// 频率轴：左端、中点与右端的频率
+        fn render_axis(&self, frame: &mut Frame, area: Rect) {
+            let (low, high) = self.range;
+            let labels = [format!("{:.0} Hz", low), format!("{:.0} Hz", 0.5 * (low + high)), format!("{:.0} Hz", high)];
+            let width = area.width as usize;
+            let mut axis = vec![' '; width];
+            let starts = [0, (width / 2).saturating_sub(labels[1].len() / 2), width.saturating_sub(labels[2].len())];
+            for (label, start) in labels.iter().zip(starts) {
+                for (offset, c) in label.chars().enumerate() {
+                    if let Some(slot) = axis.get_mut(start + offset) {
+                        *slot = c;
+                    }
+                }
+            }
+            frame.render_widget(Paragraph::new(axis.into_iter().collect::<String>()), area);
+        }
+
+        fn sidebar_lines(&self) -> Vec<Line<'static>> {
+            let top = self.magnitude.iter().fold(0.0f64, |m, &x| m.max(x));
+            let db = |mag: f64| if top > 0.0 && mag > 0.0 { 20.0 * (mag / top).log10() } else { -DISPLAY_RANGE_DB };
+            let mut lines: Vec<Line> = self
+                .peaks
+                .iter()
+                .enumerate()
+                .map(|(i, &(f, mag, _))| Line::from(format!("{}. {:9.2} Hz {:6.1} dB", i + 1, f, db(mag))))
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("f_d = {:.2} Hz", self.f_d)));
+            match &self.pair {
+                Some(pair) => {
+                    lines.push(Line::from(format!("下边带 {:.2} Hz", pair.lower_freq)));
+                    lines.push(Line::from(format!("上边带 {:.2} Hz", pair.upper_freq)));
+                    lines.push(Line::from(format!("基带   {:.2} Hz", pair.baseband)));
+                }
+                None => lines.push(Line::from("未找到对称峰值对")),
+            }
+            lines
+        }
+    }
+
+    /// 打开全屏查看器，按 q 或 Esc 退出
+    pub fn show(view: &mut SpectrumView) -> std::io::Result<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = (|| loop {
+            terminal.draw(|frame| view.render(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('+') | KeyCode::Char('=') => view.zoom(0.5),
+                    KeyCode::Char('-') => view.zoom(2.0),
+                    KeyCode::Left => view.pan(-0.25),
+                    KeyCode::Right => view.pan(0.25),
+                    KeyCode::Char('d') => view.center_on_f_d(),
+                    KeyCode::Char('h') => view.reset(),
+                    _ => {}
+                }
+            }
+        })();
+        ratatui::restore();
+        result
+    }
+}
+
+/// 打开终端频谱查看器，直到用户退出
+///
+/// 需要 `tui` 特性；未启用时返回说明如何启用的错误。
+pub fn show(view: &mut SpectrumView) -> dsp_core::error::Result<()> {
+    #[cfg(feature = "tui")]
+    {
+        render::show(view).map_err(|e| dsp_core::error::DspError::Other(format!("终端查看器: {}", e)))
+    }
+    #[cfg(not(feature = "tui"))]
+    {
+        let _ = view;
+        Err(dsp_core::error::DspError::Unsupported(
+            "built without the terminal viewer; rebuild with `--features tui`".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1 Hz 间隔、0-1000 Hz 的频谱，在 200 Hz 和 600 Hz 处各有一个峰
+    fn spectrum() -> (Vec<f64>, Vec<f64>) {
+        let frequencies: Vec<f64> = (0..=1000).map(|k| k as f64).collect();
+        let magnitude = frequencies
+            .iter()
+            .map(|&f| if f == 200.0 { 1.0 } else if f == 600.0 { 0.1 } else { 1e-3 })
+            .collect();
+        (frequencies, magnitude)
+    }
+
+    #[test]
+    fn test_columns_and_navigation() {
+        let (frequencies, magnitude) = spectrum();
+        let peaks = [(200.0, 1.0, 200), (600.0, 0.1, 600)];
+        let mut view = SpectrumView::new(&frequencies, &magnitude, &peaks, 400.0, None, (0.0, 2000.0));
+        // 范围被限制在频率轴之内
+        assert_eq!(view.range, (0.0, 1000.0));
+
+        let columns = view.columns_db(10);
+        assert_eq!(columns[2], Some(0.0));
+        assert!((columns[6].unwrap() + 20.0).abs() < 1e-9);
+        assert!((columns[0].unwrap() + 60.0).abs() < 1e-9);
+        assert_eq!(view.column_of(1000.0, 10), Some(9));
+
+        view.zoom(0.5);
+        assert_eq!(view.range, (250.0, 750.0));
+        view.pan(-1.0);
+        assert_eq!(view.range, (0.0, 500.0));
+        view.f_d = 900.0;
+        view.center_on_f_d();
+        assert_eq!(view.range, (500.0, 1000.0));
+        assert_eq!(view.column_of(200.0, 10), None);
+        view.reset();
+        assert_eq!(view.range, (0.0, 1000.0));
+
+        assert!(requested(&["--tui".to_string()]));
+        #[cfg(not(feature = "tui"))]
+        assert!(show(&mut view).is_err());
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_render_shows_peaks_and_f_d() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let (frequencies, magnitude) = spectrum();
+        let peaks = [(200.0, 1.0, 200), (600.0, 0.1, 600)];
+        let view = SpectrumView::new(&frequencies, &magnitude, &peaks, 400.0, None, (0.0, 1000.0));
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| view.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("f_d = 400.00 Hz"));
+        assert!(text.contains("600.00 Hz"));
+        assert!(text.contains("1000 Hz"));
+    }
+}
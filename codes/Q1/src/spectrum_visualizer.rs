@@ -1,245 +1,705 @@
 // 3. 频谱可视化模块
 // 使用 plotters 库绘制频谱图
 
+use crate::frequency_estimator::BandEnergy;
+use dsp_core::error::{DspError, Result};
+use dsp_core::min_max::{band_outline, min_max_columns};
+use dsp_core::plot_output::PlotTarget;
+use dsp_core::plot_style::{scientific_label, LegendPosition, PlotStyle, Rgb};
+use dsp_core::stft::Stft;
+use dsp_core::with_drawing_area;
+use log::info;
+use plotters::chart::MeshStyle;
+use plotters::coord::Shift;
 use plotters::prelude::*;
-use std::path::Path;
+use std::ops::Range;
 
 /// 频谱可视化器
 pub struct SpectrumVisualizer;
 
 impl SpectrumVisualizer {
     /// 绘制频谱图（幅度谱）
-    pub fn plot_spectrum<P: AsRef<Path>>(
+    ///
+    /// annotations: 需要标注的 (频率, 文字)，例如 `(3225.0, "f_d = 3225 Hz")`；
+    /// 每个标注画一条竖线、在曲线上对应频点画圆点，并在顶部写出文字
+    pub fn plot_spectrum(
         frequencies: &[f64],
         magnitude: &[f64],
-        output_path: P,
+        target: impl Into<PlotTarget>,
         title: &str,
         max_freq: Option<f64>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        annotations: &[(f64, &str)],
+    ) -> Result<()> {
+        let target = target.into();
         // 只显示到指定频率或 Nyquist 频率
         let nyquist = frequencies.last().copied().unwrap_or(0.0) / 2.0;
-        let max_f = max_freq.unwrap_or(nyquist);
+        // --freq-range 优先于 max_freq
+        let axes = target.style.axes;
+        let x_range = axes.freq(0.0..max_freq.unwrap_or(nyquist));
+        let in_view = |f: f64| x_range.start <= f && f <= x_range.end;
         
         // 过滤数据点
         let data: Vec<(f64, f64)> = frequencies
             .iter()
             .zip(magnitude.iter())
-            .filter(|(&f, _)| f <= max_f)
+            .filter(|(&f, _)| in_view(f))
+            .map(|(&f, &m)| (f, m))
+            .collect();
+
+        if data.is_empty() {
+            return Err(no_data());
+        }
+
+        // 找出幅度范围；有标注时在顶部为文字留出空间
+        let max_magnitude = data.iter().map(|(_, m)| m).fold(0.0f64, |a, &b| a.max(b));
+        let y_range = axes.magnitude(0.0..max_magnitude * if annotations.is_empty() { 1.1 } else { 1.3 });
+        let annotations: Vec<(f64, &str)> = annotations
+            .iter()
+            .copied()
+            .filter(|&(f, _)| in_view(f))
+            .collect();
+
+        // 创建绘图区域 - 按文件扩展名选择 PNG / SVG / PDF 后端
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(x_range.clone(), y_range.clone()).map_err(DspError::plot)?;
+
+            axis_mesh(style_mesh(&mut chart.configure_mesh(), &style), &style, &x_range, &y_range)
+                .x_desc("Frequency (Hz)")
+                .y_desc("Magnitude")
+                .draw().map_err(DspError::plot)?;
+
+            // 标注竖线画在曲线之下
+            let marker = rgb(style.marker);
+            for &(f, _) in &annotations {
+                chart.draw_series(LineSeries::new(
+                    vec![(f, y_range.start), (f, y_range.end)],
+                    marker.mix(0.5).stroke_width(style.line_width),
+                )).map_err(DspError::plot)?;
+            }
+
+            // 绘制频谱曲线
+            chart.draw_series(LineSeries::new(
+                data.iter().map(|&(f, m)| (f, m)),
+                rgb(style.color(0)).stroke_width(style.line_width),
+            )).map_err(DspError::plot)?;
+
+            // 曲线上最近频点处的圆点，文字在竖线右侧、分三层错开以免相邻标注重叠
+            for (i, &(f, label)) in annotations.iter().enumerate() {
+                let nearest = data
+                    .iter()
+                    .min_by(|a, b| (a.0 - f).abs().total_cmp(&(b.0 - f).abs()))
+                    .map_or(0.0, |&(_, m)| m);
+                chart.draw_series(std::iter::once(Circle::new((f, nearest), 4, marker.filled()))).map_err(DspError::plot)?;
+                let y = y_range.start + (y_range.end - y_range.start) * (0.97 - 0.06 * (i % 3) as f64);
+                chart.draw_series(std::iter::once(
+                    Text::new(label.to_string(), (f, y), (style.font, style.label_font_size).into_font().color(&marker))
+                )).map_err(DspError::plot)?;
+            }
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("频谱图已保存到: {:?}", target.path);
+        Ok(())
+    }
+
+    /// 绘制以零频为中心的双边频谱图
+    ///
+    /// # 参数
+    /// - max_freq: 显示范围 ±max_freq，默认 ±f_s/2
+    /// - markers: 需要标注的频率（红色竖线），例如 ±f_d
+    pub fn plot_spectrum_centered(
+        frequencies: &[f64],
+        magnitude: &[f64],
+        target: impl Into<PlotTarget>,
+        title: &str,
+        max_freq: Option<f64>,
+        markers: &[f64],
+    ) -> Result<()> {
+        let target = target.into();
+        let nyquist = frequencies.last().copied().unwrap_or(0.0).max(0.0);
+        let max_f = max_freq.unwrap_or(nyquist);
+        let axes = target.style.axes;
+        let x_range = axes.freq(-max_f..max_f);
+        let in_view = |f: f64| x_range.start <= f && f <= x_range.end;
+
+        let data: Vec<(f64, f64)> = frequencies
+            .iter()
+            .zip(magnitude.iter())
+            .filter(|(&f, _)| in_view(f))
             .map(|(&f, &m)| (f, m))
             .collect();
 
         if data.is_empty() {
-            return Err("没有数据可以绘制".into());
+            return Err(no_data());
         }
 
-        // 找出幅度范围
         let max_magnitude = data.iter().map(|(_, m)| m).fold(0.0f64, |a, &b| a.max(b));
-        let y_max = max_magnitude * 1.1;
-
-        // 创建绘图区域 - 使用文件路径
-        let root = BitMapBackend::new(output_path.as_ref(), (1200, 600))
-            .into_drawing_area();
-        root.fill(&WHITE)?;
-
-        let mut chart = ChartBuilder::on(&root)
-            .caption(title, ("Arial", 30).into_font())
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0..max_f, 0.0..y_max)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Frequency (Hz)")
-            .y_desc("Magnitude")
-            .draw()?;
-
-        // 绘制频谱曲线
-        chart.draw_series(LineSeries::new(
-            data.iter().map(|&(f, m)| (f, m)),
-            &BLUE,
-        ))?;
-
-        root.present()?;
-        println!("频谱图已保存到: {:?}", output_path.as_ref());
+        let y_range = axes.magnitude(0.0..max_magnitude * 1.1);
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(x_range.clone(), y_range.clone()).map_err(DspError::plot)?;
+
+            axis_mesh(style_mesh(&mut chart.configure_mesh(), &style), &style, &x_range, &y_range)
+                .x_desc("Frequency (Hz)")
+                .y_desc("Magnitude")
+                .draw().map_err(DspError::plot)?;
+
+            for &marker in markers.iter().filter(|&&f| in_view(f)) {
+                chart.draw_series(LineSeries::new(
+                    vec![(marker, y_range.start), (marker, y_range.end)],
+                    rgb(style.marker).stroke_width(style.line_width + 1),
+                )).map_err(DspError::plot)?;
+            }
+
+            chart.draw_series(LineSeries::new(
+                data.iter().map(|&(f, m)| (f, m)),
+                rgb(style.color(0)).stroke_width(style.line_width),
+            )).map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("双边频谱图已保存到: {:?}", target.path);
         Ok(())
     }
 
     /// 绘制频谱图（dB 刻度）
-    pub fn plot_spectrum_db<P: AsRef<Path>>(
+    pub fn plot_spectrum_db(
         frequencies: &[f64],
         magnitude_db: &[f64],
-        output_path: P,
+        target: impl Into<PlotTarget>,
         title: &str,
         max_freq: Option<f64>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
+        let target = target.into();
         let nyquist = frequencies.last().copied().unwrap_or(0.0) / 2.0;
-        let max_f = max_freq.unwrap_or(nyquist);
+        let axes = target.style.axes;
+        let x_range = axes.freq(0.0..max_freq.unwrap_or(nyquist));
         
         let data: Vec<(f64, f64)> = frequencies
             .iter()
             .zip(magnitude_db.iter())
-            .filter(|(&f, _)| f <= max_f)
+            .filter(|(&f, _)| x_range.start <= f && f <= x_range.end)
             .map(|(&f, &m)| (f, m))
             .collect();
 
         if data.is_empty() {
-            return Err("没有数据可以绘制".into());
+            return Err(no_data());
         }
 
         let max_db = data.iter().map(|(_, m)| m).fold(-200.0f64, |a, &b| a.max(b));
         let min_db = -100.0;
+        let y_range = axes.magnitude_db(min_db..max_db);
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(x_range.clone(), y_range.clone()).map_err(DspError::plot)?;
 
-        let root = BitMapBackend::new(output_path.as_ref(), (1200, 600))
-            .into_drawing_area();
-        root.fill(&WHITE)?;
-
-        let mut chart = ChartBuilder::on(&root)
-            .caption(title, ("Arial", 30).into_font())
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0..max_f, min_db..max_db)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Frequency (Hz)")
-            .y_desc("Magnitude (dB)")
-            .draw()?;
-
-        chart.draw_series(LineSeries::new(
-            data.iter().map(|&(f, m)| (f, m)),
-            &RED,
-        ))?;
-
-        root.present()?;
-        println!("频谱图（dB）已保存到: {:?}", output_path.as_ref());
+            axis_mesh(style_mesh(&mut chart.configure_mesh(), &style), &style, &x_range, &y_range)
+                .x_desc("Frequency (Hz)")
+                .y_desc("Magnitude (dB)")
+                .draw().map_err(DspError::plot)?;
+
+            chart.draw_series(LineSeries::new(
+                data.iter().map(|&(f, m)| (f, m)),
+                rgb(style.color(1)).stroke_width(style.line_width),
+            )).map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("频谱图（dB）已保存到: {:?}", target.path);
         Ok(())
     }
 
     /// 绘制时域波形
-    pub fn plot_waveform<P: AsRef<Path>>(
+    ///
+    /// 采样点多于绘图区宽度（像素）时，按像素列取每列的最小值与最大值，画出填充的包络带，
+    /// 既不丢失峰值，也避免数万点折线的混叠与绘制开销
+    pub fn plot_waveform(
         samples: &[f64],
         sample_rate: f64,
-        output_path: P,
+        target: impl Into<PlotTarget>,
         title: &str,
         max_samples: Option<usize>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
+        let target = target.into();
         // 限制显示的采样点数量
         let n = max_samples.unwrap_or(samples.len()).min(samples.len());
-        let time: Vec<f64> = (0..n)
-            .map(|i| i as f64 / sample_rate)
+        if n == 0 {
+            return Err(no_data());
+        }
+        let t_max = (n - 1) as f64 / sample_rate;
+
+        let max_amplitude = samples.iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
+        let y_range = max_amplitude * 1.2;
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(0.0..t_max, -y_range..y_range).map_err(DspError::plot)?;
+
+            style_mesh(&mut chart.configure_mesh(), &style)
+                .x_desc("Time (seconds)")
+                .y_desc("Amplitude")
+                .draw().map_err(DspError::plot)?;
+
+            let color = rgb(style.color(2));
+            let width = chart.plotting_area().dim_in_pixel().0 as usize;
+            if n > width {
+                let columns = min_max_columns(&samples[..n], width);
+                let outline = band_outline(&columns, |i| i / sample_rate);
+                // 轮廓线保证安静段（最小值与最大值几乎相同）仍至少有一个像素宽
+                chart.draw_series(std::iter::once(Polygon::new(outline.clone(), color.filled()))).map_err(DspError::plot)?;
+                chart.draw_series(std::iter::once(PathElement::new(outline, color.stroke_width(style.line_width)))).map_err(DspError::plot)?;
+            } else {
+                chart.draw_series(LineSeries::new(
+                    samples[..n].iter().enumerate().map(|(i, &s)| (i as f64 / sample_rate, s)),
+                    color.stroke_width(style.line_width),
+                )).map_err(DspError::plot)?;
+            }
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("时域波形图已保存到: {:?}", target.path);
+        Ok(())
+    }
+
+    /// 绘制频率偏差随时间的漂移曲线 f_d(t)
+    ///
+    /// 未找到对称峰值对的帧不绘制；红线为全局估计值作为参考
+    pub fn plot_offset_track(
+        times: &[f64],
+        offsets: &[Option<f64>],
+        reference: f64,
+        target: impl Into<PlotTarget>,
+        title: &str,
+    ) -> Result<()> {
+        let target = target.into();
+        let data: Vec<(f64, f64)> = times
+            .iter()
+            .zip(offsets.iter())
+            .filter_map(|(&t, f)| f.map(|f| (t, f)))
             .collect();
-        
-        let data: Vec<(f64, f64)> = time
+
+        let t_max = times.last().copied().unwrap_or(1.0).max(1e-3);
+        let f_min = data.iter().map(|&(_, f)| f).fold(reference, f64::min);
+        let f_max = data.iter().map(|&(_, f)| f).fold(reference, f64::max);
+        let margin = ((f_max - f_min) * 0.2).max(5.0);
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(0.0..t_max, (f_min - margin)..(f_max + margin)).map_err(DspError::plot)?;
+
+            style_mesh(&mut chart.configure_mesh(), &style)
+                .x_desc("Time (seconds)")
+                .y_desc("f_d (Hz)")
+                .draw().map_err(DspError::plot)?;
+
+            chart.draw_series(LineSeries::new(
+                vec![(0.0, reference), (t_max, reference)],
+                rgb(style.marker).mix(0.6).stroke_width(style.line_width),
+            )).map_err(DspError::plot)?;
+
+            chart.draw_series(LineSeries::new(
+                data.iter().copied(),
+                rgb(style.color(0)).stroke_width(style.line_width),
+            )).map_err(DspError::plot)?;
+            chart.draw_series(
+                data.iter()
+                    .map(|&(t, f)| Circle::new((t, f), 3, rgb(style.color(0)).filled())),
+            ).map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("频率偏差漂移图已保存到: {:?}", target.path);
+        Ok(())
+    }
+
+    /// 绘制瀑布图：逐帧短时频谱沿时间轴依次排开
+    ///
+    /// 从 STFT 中均匀选取至多 max_traces 帧，幅度按全部帧的最大值归一化；从后往前绘制，
+    /// 每条曲线下方用背景色填充，遮住其后的帧。references 中的频率（如 f_d）沿时间轴画成
+    /// 红线，峰值相对红线的横向移动即为单次 FFT 看不出的载波漂移。
+    ///
+    /// 样式设置了 dB 范围（`--db-range`）时高度改为相对最大值的 dB 数并截断在该范围内，
+    /// 载波峰值不再压扁弱小的基带成分
+    pub fn plot_waterfall(
+        stft: &Stft,
+        max_freq: f64,
+        max_traces: usize,
+        references: &[f64],
+        target: impl Into<PlotTarget>,
+        title: &str,
+    ) -> Result<()> {
+        let target = target.into();
+        let magnitude = stft.magnitude();
+        let bins = stft.frequencies.iter().take_while(|&&f| f <= max_freq).count();
+        let step = magnitude.len().div_ceil(max_traces.max(1)).max(1);
+        let frames: Vec<(f64, &[f64])> = stft
+            .times
             .iter()
-            .zip(samples.iter())
-            .take(n)
-            .map(|(&t, &s)| (t, s))
+            .zip(magnitude.iter())
+            .step_by(step)
+            .map(|(&t, frame)| (t, &frame[..bins]))
             .collect();
+        let peak = frames
+            .iter()
+            .flat_map(|(_, frame)| frame.iter())
+            .fold(0.0f64, |a, &b| a.max(b))
+            .max(1e-12);
+
+        let db_range = target.style.db_range;
+        let level = |m: f64| match db_range {
+            Some(range) => range.level(20.0 * (m / peak).max(1e-12).log10()),
+            None => m / peak,
+        };
+        // dB 高度在范围顶端截断，无需为峰值留余量
+        let y_top = if db_range.is_some() { 1.0 } else { 1.1 };
+
+        let t_min = frames.first().map(|f| f.0).unwrap_or(0.0);
+        let t_max = frames.last().map(|f| f.0).unwrap_or(1.0).max(t_min + 1e-3);
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style)
+                .build_cartesian_3d(0.0..max_freq, 0.0..y_top, t_min..t_max).map_err(DspError::plot)?;
+            // 压低幅度轴、加深时间轴，让各帧曲线错开而不互相遮挡
+            let (width, height) = root.dim_in_pixel();
+            chart.set_3d_pixel_range((width as i32, height as i32 * 2 / 3, width as i32 * 2 / 3));
+            chart.with_projection(|mut projection| {
+                projection.yaw = 0.5;
+                projection.pitch = 0.3;
+                projection.scale = 0.65;
+                projection.into_matrix()
+            });
+            chart
+                .configure_axes()
+                .label_style((style.font, style.label_font_size))
+                .light_grid_style(BLACK.mix(if style.grid { 0.1 } else { 0.0 }))
+                .max_light_lines(3)
+                .x_formatter(&|f| format!("{:.0} Hz", f))
+                .y_formatter(&|m| match db_range {
+                    Some(range) => format!("{:.0} dB", range.db_at(*m)),
+                    None => format!("{:.1}", m),
+                })
+                .z_formatter(&|t| format!("{:.1} s", t))
+                .draw().map_err(DspError::plot)?;
+
+            for &reference in references.iter().filter(|&&f| f <= max_freq) {
+                chart.draw_series(LineSeries::new(
+                    vec![(reference, 0.0, t_min), (reference, 0.0, t_max)],
+                    rgb(style.marker).stroke_width(style.line_width + 1),
+                )).map_err(DspError::plot)?;
+            }
+
+            // 时间轴朝向观察者，最晚的帧在最前面，故从最早的帧开始绘制
+            for (t, frame) in &frames {
+                let curve: Vec<(f64, f64, f64)> = stft.frequencies[..bins]
+                    .iter()
+                    .zip(frame.iter())
+                    .map(|(&f, &m)| (f, level(m), *t))
+                    .collect();
+                let mut outline = curve.clone();
+                outline.push((max_freq, 0.0, *t));
+                outline.push((0.0, 0.0, *t));
+                chart.draw_series(std::iter::once(Polygon::new(outline, rgb(style.background).filled()))).map_err(DspError::plot)?;
+                chart.draw_series(LineSeries::new(curve, rgb(style.color(0)).stroke_width(style.line_width))).map_err(DspError::plot)?;
+            }
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("瀑布图已保存到: {:?}", target.path);
+        Ok(())
+    }
 
-        let max_amplitude = samples.iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
-        let y_range = max_amplitude * 1.2;
+    /// 绘制频带能量分布堆叠柱状图
+    ///
+    /// 每组数据一根柱，柱内按频带从低到高堆叠各频带的能量百分比
+    pub fn plot_energy_distribution(
+        datasets: &[(&str, &[BandEnergy])], // (label, bands)
+        target: impl Into<PlotTarget>,
+        title: &str,
+    ) -> Result<()> {
+        let target = target.into();
+        if datasets.is_empty() {
+            return Err(no_data());
+        }
+
+        let max_total = datasets
+            .iter()
+            .map(|(_, bands)| bands.iter().map(|b| b.percentage).sum::<f64>())
+            .fold(0.0f64, f64::max);
+        let y_max = (max_total * 1.1).max(1.0);
+        let num_groups = datasets.len();
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(-0.5..(num_groups as f64 - 0.5), 0.0..y_max).map_err(DspError::plot)?;
+
+            style_mesh(&mut chart.configure_mesh(), &style)
+                .disable_x_mesh()
+                .x_labels(num_groups)
+                .x_label_formatter(&|x| {
+                    let idx = x.round();
+                    if (x - idx).abs() < 1e-6 && idx >= 0.0 && (idx as usize) < num_groups {
+                        datasets[idx as usize].0.to_string()
+                    } else {
+                        String::new()
+                    }
+                })
+                .y_desc("Energy (%)")
+                .draw().map_err(DspError::plot)?;
+
+            let num_bands = datasets.iter().map(|(_, bands)| bands.len()).max().unwrap_or(0);
+
+            for band_idx in 0..num_bands {
+                let color = rgb(style.color(band_idx));
+                let mut label = None;
+                let bars: Vec<Rectangle<(f64, f64)>> = datasets
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(group, (_, bands))| {
+                        let band = bands.get(band_idx)?;
+                        label.get_or_insert_with(|| band.label());
+                        let base: f64 = bands[..band_idx].iter().map(|b| b.percentage).sum();
+                        let x = group as f64;
+                        Some(Rectangle::new(
+                            [(x - 0.3, base), (x + 0.3, base + band.percentage)],
+                            color.filled(),
+                        ))
+                    })
+                    .collect();
+
+                chart
+                    .draw_series(bars).map_err(DspError::plot)?
+                    .label(label.unwrap_or_default())
+                    .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 15, y + 5)], color.filled()));
+            }
 
-        let root = BitMapBackend::new(output_path.as_ref(), (1200, 600))
-            .into_drawing_area();
-        root.fill(&WHITE)?;
-
-        let mut chart = ChartBuilder::on(&root)
-            .caption(title, ("Arial", 30).into_font())
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0..time[n-1], -y_range..y_range)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Time (seconds)")
-            .y_desc("Amplitude")
-            .draw()?;
-
-        chart.draw_series(LineSeries::new(
-            data.iter().map(|&(t, s)| (t, s)),
-            &GREEN,
-        ))?;
-
-        root.present()?;
-        println!("时域波形图已保存到: {:?}", output_path.as_ref());
+            chart
+                .configure_series_labels()
+                .position(legend_position(style.legend))
+                .label_font((style.font, style.label_font_size))
+                .background_style(rgb(style.background).mix(0.8))
+                .border_style(BLACK)
+                .draw().map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("能量分布图已保存到: {:?}", target.path);
+        Ok(())
+    }
+
+    /// 绘制估计误差 (RMSE) 随 SNR 变化的曲线，纵轴为对数刻度
+    pub fn plot_rmse_vs_snr(
+        snrs: &[f64],
+        series: &[(&str, Vec<f64>)], // (label, 各 SNR 下的 RMSE)
+        target: impl Into<PlotTarget>,
+        title: &str,
+    ) -> Result<()> {
+        let target = target.into();
+        let values = || series.iter().flat_map(|(_, v)| v.iter().copied()).filter(|&v| v > 0.0);
+        if snrs.is_empty() || values().next().is_none() {
+            return Err(no_data());
+        }
+        let x_min = snrs.iter().copied().fold(f64::INFINITY, f64::min);
+        let x_max = snrs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let y_min = values().fold(f64::INFINITY, f64::min) * 0.5;
+        let y_max = values().fold(0.0f64, f64::max) * 2.0;
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(x_min..x_max, (y_min..y_max).log_scale()).map_err(DspError::plot)?;
+
+            style_mesh(&mut chart.configure_mesh(), &style)
+                .x_desc("SNR (dB)")
+                .y_desc("RMSE of f_d (Hz)")
+                .draw().map_err(DspError::plot)?;
+
+            for (idx, (label, rmse)) in series.iter().enumerate() {
+                let data: Vec<(f64, f64)> = snrs
+                    .iter()
+                    .zip(rmse.iter())
+                    .filter(|(_, &v)| v > 0.0)
+                    .map(|(&s, &v)| (s, v))
+                    .collect();
+
+                let color = rgb(style.color(idx));
+                let line = color.stroke_width(style.line_width);
+                chart
+                    .draw_series(LineSeries::new(data.iter().copied(), line)).map_err(DspError::plot)?
+                    .label(*label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], line));
+                chart.draw_series(data.iter().map(|&(s, v)| Circle::new((s, v), 3, color.filled()))).map_err(DspError::plot)?;
+            }
+
+            chart
+                .configure_series_labels()
+                .position(legend_position(style.legend))
+                .label_font((style.font, style.label_font_size))
+                .background_style(rgb(style.background).mix(0.8))
+                .border_style(BLACK)
+                .draw().map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("RMSE 曲线已保存到: {:?}", target.path);
         Ok(())
     }
 
     /// 绘制多个频谱对比图
-    pub fn plot_spectrum_comparison<P: AsRef<Path>>(
+    pub fn plot_spectrum_comparison(
         datasets: Vec<(&[f64], &[f64], &str)>, // (frequencies, magnitude, label)
-        output_path: P,
+        target: impl Into<PlotTarget>,
         title: &str,
         max_freq: Option<f64>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
+        let target = target.into();
         if datasets.is_empty() {
-            return Err("没有数据可以绘制".into());
+            return Err(no_data());
         }
 
         let nyquist = datasets[0].0.last().copied().unwrap_or(0.0) / 2.0;
-        let max_f = max_freq.unwrap_or(nyquist);
+        let axes = target.style.axes;
+        let x_range = axes.freq(0.0..max_freq.unwrap_or(nyquist));
+        let in_view = |f: f64| x_range.start <= f && f <= x_range.end;
 
-        // 找出最大幅度
+        // 找出显示范围内的最大幅度
         let max_magnitude = datasets
             .iter()
-            .flat_map(|(_, mag, _)| mag.iter())
-            .copied()
+            .flat_map(|(freqs, mags, _)| freqs.iter().zip(mags.iter()))
+            .filter(|(&f, _)| in_view(f))
+            .map(|(_, &m)| m)
             .fold(0.0f64, f64::max);
-        let y_max = max_magnitude * 1.1;
-
-        let root = BitMapBackend::new(output_path.as_ref(), (1200, 600))
-            .into_drawing_area();
-        root.fill(&WHITE)?;
-
-        let mut chart = ChartBuilder::on(&root)
-            .caption(title, ("Arial", 30).into_font())
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0..max_f, 0.0..y_max)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Frequency (Hz)")
-            .y_desc("Magnitude")
-            .draw()?;
-
-        let colors = [&BLUE, &RED, &GREEN, &CYAN, &MAGENTA];
-
-        for (idx, (freqs, mags, label)) in datasets.iter().enumerate() {
-            let data: Vec<(f64, f64)> = freqs
-                .iter()
-                .zip(mags.iter())
-                .filter(|(&f, _)| f <= max_f)
-                .map(|(&f, &m)| (f, m))
-                .collect();
-
-            let color = colors[idx % colors.len()];
+        let y_range = axes.magnitude(0.0..max_magnitude * 1.1);
+
+        let style = target.style;
+        with_drawing_area!(target, style.size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+            let mut chart = chart_builder(&root, title, &style).build_cartesian_2d(x_range.clone(), y_range.clone()).map_err(DspError::plot)?;
+
+            axis_mesh(style_mesh(&mut chart.configure_mesh(), &style), &style, &x_range, &y_range)
+                .x_desc("Frequency (Hz)")
+                .y_desc("Magnitude")
+                .draw().map_err(DspError::plot)?;
+
+            for (idx, (freqs, mags, label)) in datasets.iter().enumerate() {
+                let data: Vec<(f64, f64)> = freqs
+                    .iter()
+                    .zip(mags.iter())
+                    .filter(|(&f, _)| in_view(f))
+                    .map(|(&f, &m)| (f, m))
+                    .collect();
+
+                let color = rgb(style.color(idx));
+                let line = color.stroke_width(style.line_width);
+                chart
+                    .draw_series(LineSeries::new(data.iter().map(|&(f, m)| (f, m)), line)).map_err(DspError::plot)?
+                    .label(*label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], line));
+            }
+
             chart
-                .draw_series(LineSeries::new(data.iter().map(|&(f, m)| (f, m)), color))?
-                .label(*label)
-                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
-        }
+                .configure_series_labels()
+                .position(legend_position(style.legend))
+                .label_font((style.font, style.label_font_size))
+                .background_style(rgb(style.background).mix(0.8))
+                .border_style(BLACK)
+                .draw().map_err(DspError::plot)?;
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("对比频谱图已保存到: {:?}", target.path);
+        Ok(())
+    }
+}
 
-        chart
-            .configure_series_labels()
-            .background_style(&WHITE.mix(0.8))
-            .border_style(&BLACK)
-            .draw()?;
+/// 数据为空时的错误
+fn no_data() -> DspError {
+    DspError::Data("没有数据可以绘制".to_string())
+}
 
-        root.present()?;
-        println!("对比频谱图已保存到: {:?}", output_path.as_ref());
-        Ok(())
+/// plotters 颜色
+fn rgb(Rgb(r, g, b): Rgb) -> RGBColor {
+    RGBColor(r, g, b)
+}
+
+fn legend_position(position: LegendPosition) -> SeriesLabelPosition {
+    match position {
+        LegendPosition::UpperLeft => SeriesLabelPosition::UpperLeft,
+        LegendPosition::UpperRight => SeriesLabelPosition::UpperRight,
+        LegendPosition::MiddleLeft => SeriesLabelPosition::MiddleLeft,
+        LegendPosition::MiddleRight => SeriesLabelPosition::MiddleRight,
+        LegendPosition::LowerLeft => SeriesLabelPosition::LowerLeft,
+        LegendPosition::LowerRight => SeriesLabelPosition::LowerRight,
+    }
+}
+
+/// 按样式设置标题字体、边距与坐标轴标签区
+fn chart_builder<'a, 'b, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    style: &PlotStyle,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .caption(title, (style.font, style.title_font_size).into_font())
+        .margin(style.margin)
+        .x_label_area_size(style.x_label_area)
+        .y_label_area_size(style.y_label_area);
+    builder
+}
+
+/// 按样式设置刻度字体与网格线
+fn style_mesh<'m, 'a, 'b, X: Ranged, Y: Ranged, DB: DrawingBackend>(
+    mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>,
+    style: &PlotStyle,
+) -> &'m mut MeshStyle<'a, 'b, X, Y, DB> {
+    mesh.label_style((style.font, style.label_font_size))
+        .axis_desc_style((style.font, style.label_font_size));
+    if !style.grid {
+        mesh.disable_mesh();
     }
+    mesh
+}
+
+/// 频谱与频率响应图的刻度：按 `--freq-tick` / `--mag-tick` 设刻度数，
+/// `--sci-ticks` 时幅度轴用科学计数法
+fn axis_mesh<'m, 'a, 'b, X: Ranged, Y: Ranged<ValueType = f64>, DB: DrawingBackend>(
+    mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>,
+    style: &PlotStyle,
+    x_range: &Range<f64>,
+    y_range: &Range<f64>,
+) -> &'m mut MeshStyle<'a, 'b, X, Y, DB> {
+    let axes = style.axes;
+    mesh.x_labels(axes.freq_labels(x_range))
+        .y_labels(axes.magnitude_labels(y_range));
+    if axes.scientific {
+        mesh.y_label_formatter(&scientific_label);
+    }
+    mesh
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::f64::consts::PI;
 
     #[test]
     fn test_plot_spectrum() {
@@ -266,8 +726,33 @@ mod tests {
             "/tmp/test_spectrum.png",
             "测试频谱",
             Some(500.0),
+            &[(100.0, "f = 100 Hz"), (800.0, "超出范围，不绘制")],
         );
         
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_plot_waterfall() {
+        // 频率缓慢漂移的正弦信号
+        let sample_rate = 8000.0;
+        let signal: Vec<f64> = (0..16000)
+            .map(|i| {
+                let t = i as f64 / sample_rate;
+                (2.0 * std::f64::consts::PI * (1000.0 + 50.0 * t) * t).sin()
+            })
+            .collect();
+        let stft = Stft::compute(&signal, sample_rate, 512, 256, dsp_core::stft::Window::Hann);
+
+        let result = SpectrumVisualizer::plot_waterfall(
+            &stft,
+            2000.0,
+            20,
+            &[1000.0],
+            "/tmp/test_waterfall.png",
+            "测试瀑布图",
+        );
+
+        assert!(result.is_ok());
+    }
 }
@@ -0,0 +1,132 @@
+//! FFT 金标准回归测试
+//!
+//! 参考值由 NumPy 计算（`numpy.fft.fft`），存为常量：`fft_processor` 重构后若结果
+//! 超出舍入误差范围，测试即失败。
+
+use am_demodulation_q1::fft_processor::FftResult;
+use std::f64::consts::PI;
+
+/// 测试信号长度与采样率
+const N: usize = 64;
+const SAMPLE_RATE: f64 = 64.0;
+
+/// 与 NumPy 结果的绝对容差
+const TOLERANCE: f64 = 1e-9;
+
+/// x[n] = cos(2π·5.5n/64) + 0.5·sin(2π·12n/64)：一个落在两个频点之间的余弦（有泄漏）
+/// 加一个正好落在第 12 个频点上的正弦
+fn test_tone() -> Vec<f64> {
+    (0..N)
+        .map(|n| {
+            let t = n as f64 / N as f64;
+            (2.0 * PI * 5.5 * t).cos() + 0.5 * (2.0 * PI * 12.0 * t).sin()
+        })
+        .collect()
+}
+
+/// numpy.fft.fft(x)[:33].imag（实部在各频点均为 1）
+const FFT_IMAG: [f64; 33] = [
+    0.0,
+    0.7130795100483,
+    1.58536974369,
+    2.925910975874,
+    5.785005225005,
+    19.48511850099,
+    -21.15732822002,
+    -7.488280072923,
+    -4.694583691857,
+    -3.461209956226,
+    -2.751767535327,
+    -2.282807414004,
+    -17.94482456829,
+    -1.686405088696,
+    -1.480121656397,
+    -1.30995090426,
+    -1.165869936412,
+    -1.04126513388,
+    -0.9315802086925,
+    -0.8335620731538,
+    -0.7448166423389,
+    -0.6635350581902,
+    -0.588318328009,
+    -0.5180611961973,
+    -0.4518729478953,
+    -0.3890219461937,
+    -0.3288958191432,
+    -0.2709721903107,
+    -0.2147966341384,
+    -0.159965638877,
+    -0.1061130530666,
+    -0.05289893401357,
+    0.0,
+];
+
+/// abs(numpy.fft.fft(x)[:33]) / 64，即 `FftResult::magnitude` 的归一化
+const FFT_MAGNITUDE: [f64; 33] = [
+    0.015625,
+    0.01919067046569,
+    0.02928759112989,
+    0.0483137406827,
+    0.09173124043642,
+    0.3048556599247,
+    0.3309523048395,
+    0.1180430627389,
+    0.07499856124897,
+    0.05629333044862,
+    0.04574743996979,
+    0.03894109160751,
+    0.2808229087012,
+    0.0306344139038,
+    0.02791046702169,
+    0.02575031937934,
+    0.02399977980769,
+    0.02255761438098,
+    0.02135453198814,
+    0.0203414801069,
+    0.01948276375847,
+    0.01875181484347,
+    0.01812849141394,
+    0.01759729789803,
+    0.01714618016919,
+    0.01676569107207,
+    0.01644840165352,
+    0.01618847975982,
+    0.0159813856109,
+    0.01582365131142,
+    0.01571272234727,
+    0.01564684642451,
+    0.015625,
+];
+
+#[test]
+fn fft_of_known_tone_matches_numpy() {
+    let result = FftResult::compute_quiet(&test_tone(), SAMPLE_RATE);
+    assert_eq!(result.spectrum.len(), N);
+
+    for k in 0..=N / 2 {
+        let bin = result.spectrum[k];
+        assert!((bin.re - 1.0).abs() < TOLERANCE, "X[{}].re = {}", k, bin.re);
+        assert!(
+            (bin.im - FFT_IMAG[k]).abs() < TOLERANCE,
+            "X[{}].im = {}，NumPy 为 {}",
+            k,
+            bin.im,
+            FFT_IMAG[k]
+        );
+        assert!(
+            (result.magnitude[k] - FFT_MAGNITUDE[k]).abs() < TOLERANCE,
+            "|X[{}]|/N = {}，NumPy 为 {}",
+            k,
+            result.magnitude[k],
+            FFT_MAGNITUDE[k]
+        );
+        // 采样率取 N，频率轴即频点序号
+        assert!((result.frequencies[k] - k as f64).abs() < 1e-12);
+    }
+
+    // 实信号的频谱共轭对称
+    for k in 1..N / 2 {
+        let (a, b) = (result.spectrum[k], result.spectrum[N - k]);
+        assert!((a - b.conj()).norm() < TOLERANCE);
+    }
+}
@@ -0,0 +1,33 @@
+//! FFT 性质测试：对随机信号验证 FFT→IFFT 往返与 Parseval 定理
+
+use am_demodulation_q1::fft_processor::FftResult;
+use proptest::prelude::*;
+
+/// 长度 1..=600 的随机信号，包含 2 的幂、质数等各种 FFT 长度
+fn signal() -> impl Strategy<Value = Vec<f64>> {
+    prop::collection::vec(-1.0f64..1.0, 1..=600)
+}
+
+proptest! {
+    #[test]
+    fn fft_ifft_round_trip(samples in signal(), sample_rate in 100.0f64..50000.0) {
+        let result = FftResult::compute_quiet(&samples, sample_rate);
+        let restored = FftResult::ifft(&result.spectrum);
+        prop_assert_eq!(restored.len(), samples.len());
+        for (x, y) in samples.iter().zip(&restored) {
+            prop_assert!((x - y).abs() < 1e-12, "{} 还原为 {}", x, y);
+        }
+    }
+
+    #[test]
+    fn parseval_holds(samples in signal()) {
+        // Σ|x[n]|² = (1/N)·Σ|X[k]|²；`magnitude` 已除以 N，故右边为 N·Σ magnitude²
+        let result = FftResult::compute_quiet(&samples, 1000.0);
+        let n = samples.len() as f64;
+        let time_energy: f64 = samples.iter().map(|x| x * x).sum();
+        let spectrum_energy: f64 = result.spectrum.iter().map(|c| c.norm_sqr()).sum::<f64>() / n;
+        let magnitude_energy: f64 = n * result.magnitude.iter().map(|m| m * m).sum::<f64>();
+        prop_assert!((time_energy - spectrum_energy).abs() <= 1e-10 * (1.0 + time_energy));
+        prop_assert!((time_energy - magnitude_energy).abs() <= 1e-10 * (1.0 + time_energy));
+    }
+}
@@ -13,16 +13,21 @@ pub struct FilterResponse {
 impl FilterResponse {
     /// Compute frequency response of a filter at specified frequency points
     pub fn compute(filter: &ButterworthFilter, sample_rate: f64, num_points: usize) -> Self {
-        let mut frequencies = Vec::with_capacity(num_points / 2 + 1);
-        let mut magnitude = Vec::with_capacity(num_points / 2 + 1);
-        let mut phase = Vec::with_capacity(num_points / 2 + 1);
-        let mut complex_response = Vec::with_capacity(num_points / 2 + 1);
-
         // Generate frequency points from 0 to Nyquist frequency
-        for k in 0..=(num_points / 2) {
-            let freq = k as f64 * sample_rate / num_points as f64;
-            frequencies.push(freq);
+        let frequencies: Vec<f64> = (0..=(num_points / 2))
+            .map(|k| k as f64 * sample_rate / num_points as f64)
+            .collect();
+        Self::at_frequencies(filter, &frequencies, sample_rate)
+    }
+
+    /// Compute the frequency response exactly at each of `frequencies` (Hz), e.g. at
+    /// detected peaks or at f_d ± f_B, instead of at the nearest point of a uniform grid
+    pub fn at_frequencies(filter: &ButterworthFilter, frequencies: &[f64], sample_rate: f64) -> Self {
+        let mut magnitude = Vec::with_capacity(frequencies.len());
+        let mut phase = Vec::with_capacity(frequencies.len());
+        let mut complex_response = Vec::with_capacity(frequencies.len());
 
+        for &freq in frequencies {
             // Compute H(e^(jω)) at this frequency
             let omega = 2.0 * PI * freq / sample_rate;
             let h = frequency_response_at_omega(filter, omega);
@@ -33,7 +38,7 @@ impl FilterResponse {
         }
 
         Self {
-            frequencies,
+            frequencies: frequencies.to_vec(),
             magnitude,
             phase,
             complex_response,
@@ -0,0 +1,3 @@
+pub mod butterworth_filter;
+pub mod filter_response;
+pub mod response_visualizer;
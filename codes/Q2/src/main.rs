@@ -1,144 +1,262 @@
-mod butterworth_filter;
-mod filter_response;
-mod response_visualizer;
-
+use q2_filter_design::response_visualizer::{self, FrequencyScale};
+use q2_filter_design::{butterworth_filter, filter_response};
+use dsp_core::demodulator;
+use dsp_core::error::{self, DspError};
+use dsp_core::logging;
+use dsp_core::mat;
+use dsp_core::npy::{self, Npz};
+use dsp_core::parallel::{self, Task};
+use dsp_core::plot_output::PlotOutput;
+use dsp_core::reproducible::Reproducibility;
+use dsp_core::results::StageResults;
+use dsp_core::run_dir::{self, RunDirectory};
+use dsp_core::row;
+use dsp_core::watch::Watch;
+use log::{info, warn};
+use std::error::Error;
 use std::fs;
-use std::path::Path;
 
 fn main() {
-    println!("=== Q2: Butterworth Filter Design ===\n");
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run_or_watch() {
+        error::exit(e.as_ref(), "Error");
+    }
+}
 
-    // Read parameters from Q1 results
-    let q1_results_path = "../Q1/output/Q1_results.txt";
-    let (sample_rate, f_d, f_b) = read_q1_results(q1_results_path);
+/// Run once, or with `--watch` again whenever Q1's results change
+fn run_or_watch() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match Watch::from_args(&args)? {
+        Some(watch) => {
+            let q1_results_path = run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?;
+            Ok(watch.run(&[q1_results_path.into()], run)?)
+        }
+        None => run(),
+    }
+}
 
-    println!("Parameters from Q1:");
-    println!("  Sample Rate: {} Hz", sample_rate);
-    println!("  Frequency Offset (f_d): {:.4} Hz", f_d);
-    println!("  Signal Bandwidth (f_B): {} Hz", f_b);
-    println!();
+fn run() -> Result<(), Box<dyn Error>> {
+    info!("=== Q2: Butterworth Filter Design ===\n");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let plots = PlotOutput::from_args(&args)?;
+    let reproducibility = Reproducibility::from_args(&args)?;
+    let run_directory = RunDirectory::from_args(&args, "output")?;
+
+    // Read parameters from Q1 results (`--from-run <tag>` reads an archived Q1 run)
+    let q1_results_path = run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?;
+    let (sample_rate, f_d, f_b, f_d_sigma) = read_q1_results(&q1_results_path)?;
+
+    info!("Parameters from Q1:");
+    info!("  Sample Rate: {} Hz", sample_rate);
+    info!("  Frequency Offset (f_d): {:.4} Hz (σ = {:.4} Hz)", f_d, f_d_sigma);
+    info!("  Signal Bandwidth (f_B): {} Hz", f_b);
+    info!("");
+
+    // Widen both passbands by the 95% confidence half-width of f_d, so that an
+    // f_d error inside the interval does not push signal into the transition bands
+    let margin = 1.96 * f_d_sigma;
+    let hp_cutoff = f_d - margin;
+    let lp_cutoff = f_b + margin;
+    if margin > 0.0 {
+        info!("Widening transition bands by the f_d uncertainty margin (±{:.4} Hz)", margin);
+        info!("  High-pass cutoff: {:.4} Hz -> {:.4} Hz", f_d, hp_cutoff);
+        info!("  Low-pass cutoff:  {:.4} Hz -> {:.4} Hz", f_b, lp_cutoff);
+        info!("");
+    }
+
+    // `--guard-band <Hz>` moves the high-pass further down, out of the message's lowest frequencies
+    let guard_band = demodulator::guard_band_from_args(&args)?;
+    if guard_band >= hp_cutoff {
+        return Err(DspError::invalid_value("--guard-band", &guard_band.to_string()).into());
+    }
+    let hp_cutoff = hp_cutoff - guard_band;
+    if guard_band > 0.0 {
+        info!("Guard band below f_d: {:.1} Hz", guard_band);
+        info!("  High-pass cutoff: {:.4} Hz -> {:.4} Hz", hp_cutoff + guard_band, hp_cutoff);
+        info!("");
+    }
 
     // Design 8th-order Butterworth filters
     let order = 8;
-    println!("Designing 8th-order Butterworth filters...");
+    info!("Designing 8th-order Butterworth filters...");
 
-    // High-pass filter with cutoff frequency f_d
-    println!("  - High-pass filter (cutoff = {:.4} Hz)", f_d);
-    let highpass = butterworth_filter::ButterworthFilter::highpass(order, f_d, sample_rate);
+    // High-pass filter with cutoff frequency f_d (minus the uncertainty margin and guard band)
+    info!("  - High-pass filter (cutoff = {:.4} Hz)", hp_cutoff);
+    let highpass = butterworth_filter::ButterworthFilter::highpass(order, hp_cutoff, sample_rate);
 
-    // Low-pass filter with cutoff frequency f_B
-    println!("  - Low-pass filter (cutoff = {} Hz)", f_b);
-    let lowpass = butterworth_filter::ButterworthFilter::lowpass(order, f_b, sample_rate);
+    // Low-pass filter with cutoff frequency f_B (plus the uncertainty margin)
+    info!("  - Low-pass filter (cutoff = {:.4} Hz)", lp_cutoff);
+    let lowpass = butterworth_filter::ButterworthFilter::lowpass(order, lp_cutoff, sample_rate);
 
-    println!("\nHigh-pass filter coefficients:");
-    println!("  b (numerator): {:?}", &highpass.b[..5.min(highpass.b.len())]);
-    println!("  a (denominator): {:?}", &highpass.a[..5.min(highpass.a.len())]);
+    info!("\nHigh-pass filter coefficients:");
+    info!("  b (numerator): {:?}", &highpass.b[..5.min(highpass.b.len())]);
+    info!("  a (denominator): {:?}", &highpass.a[..5.min(highpass.a.len())]);
 
-    println!("\nLow-pass filter coefficients:");
-    println!("  b (numerator): {:?}", &lowpass.b[..5.min(lowpass.b.len())]);
-    println!("  a (denominator): {:?}", &lowpass.a[..5.min(lowpass.a.len())]);
+    info!("\nLow-pass filter coefficients:");
+    info!("  b (numerator): {:?}", &lowpass.b[..5.min(lowpass.b.len())]);
+    info!("  a (denominator): {:?}", &lowpass.a[..5.min(lowpass.a.len())]);
 
     // Calculate frequency response at the same frequency points as Q1
     let num_points = 31265; // Same as Q1 audio samples
-    println!("\nCalculating frequency responses ({} points)...", num_points);
+    info!("\nCalculating frequency responses ({} points)...", num_points);
 
-    let hp_response = filter_response::FilterResponse::compute(&highpass, sample_rate, num_points);
-    let lp_response = filter_response::FilterResponse::compute(&lowpass, sample_rate, num_points);
+    let (hp_response, lp_response) = rayon::join(
+        || filter_response::FilterResponse::compute(&highpass, sample_rate, num_points),
+        || filter_response::FilterResponse::compute(&lowpass, sample_rate, num_points),
+    );
 
     // Create output directory
     let output_dir = "output";
-    fs::create_dir_all(output_dir).expect("Failed to create output directory");
+    fs::create_dir_all(output_dir).map_err(|e| DspError::io(output_dir, e))?;
 
     // Plot frequency responses
-    println!("\nGenerating plots...");
-
-    // High-pass filter magnitude response
-    response_visualizer::plot_magnitude_response(
-        &hp_response.frequencies,
-        &hp_response.magnitude,
-        &format!("{}/Q2_highpass_magnitude.png", output_dir),
-        "High-pass Filter Magnitude Response",
-        Some(10000.0),
-    ).expect("Failed to plot high-pass magnitude");
-
-    // High-pass filter magnitude response in dB
-    response_visualizer::plot_magnitude_response_db(
-        &hp_response.frequencies,
-        &hp_response.magnitude,
-        &format!("{}/Q2_highpass_magnitude_db.png", output_dir),
-        "High-pass Filter Magnitude Response (dB)",
-        Some(10000.0),
-    ).expect("Failed to plot high-pass magnitude dB");
-
-    // High-pass filter phase response
-    response_visualizer::plot_phase_response(
-        &hp_response.frequencies,
-        &hp_response.phase,
-        &format!("{}/Q2_highpass_phase.png", output_dir),
-        "High-pass Filter Phase Response",
-        Some(10000.0),
-    ).expect("Failed to plot high-pass phase");
-
-    // Low-pass filter magnitude response
-    response_visualizer::plot_magnitude_response(
-        &lp_response.frequencies,
-        &lp_response.magnitude,
-        &format!("{}/Q2_lowpass_magnitude.png", output_dir),
-        "Low-pass Filter Magnitude Response",
-        Some(10000.0),
-    ).expect("Failed to plot low-pass magnitude");
-
-    // Low-pass filter magnitude response in dB
-    response_visualizer::plot_magnitude_response_db(
-        &lp_response.frequencies,
-        &lp_response.magnitude,
-        &format!("{}/Q2_lowpass_magnitude_db.png", output_dir),
-        "Low-pass Filter Magnitude Response (dB)",
-        Some(10000.0),
-    ).expect("Failed to plot low-pass magnitude dB");
-
-    // Low-pass filter phase response
-    response_visualizer::plot_phase_response(
-        &lp_response.frequencies,
-        &lp_response.phase,
-        &format!("{}/Q2_lowpass_phase.png", output_dir),
-        "Low-pass Filter Phase Response",
-        Some(10000.0),
-    ).expect("Failed to plot low-pass phase");
+    info!("\nGenerating plots...");
+
+    // Bode plots: magnitude (dB) over phase, on a linear frequency axis and on a log axis
+    // whose decade gridlines show the roll-off in dB/decade. The figures are independent,
+    // so they are rendered in parallel
+    let mut figures: Vec<Task> = Vec::new();
+    for (name, label, response) in [("highpass", "High-pass", &hp_response), ("lowpass", "Low-pass", &lp_response)] {
+        for (suffix, title_suffix, max_freq, scale) in [
+            ("", "", Some(10000.0), FrequencyScale::Linear),
+            ("_log", " (log frequency)", None, FrequencyScale::Log),
+        ] {
+            let target = plots.target(&format!("{}/Q2_{}_bode{}.png", output_dir, name, suffix));
+            let title = format!("{} Filter Frequency Response{}", label, title_suffix);
+            figures.push(Box::new(move || {
+                response_visualizer::plot_bode(
+                    &response.frequencies,
+                    &response.magnitude,
+                    &response.phase,
+                    target,
+                    &title,
+                    max_freq,
+                    scale,
+                )
+            }));
+        }
+    }
 
     // Combined magnitude plot
-    response_visualizer::plot_combined_magnitude(
-        &hp_response.frequencies,
-        &hp_response.magnitude,
-        &lp_response.magnitude,
-        &format!("{}/Q2_combined_magnitude.png", output_dir),
-        "Combined Filter Magnitude Responses",
-        Some(10000.0),
-    ).expect("Failed to plot combined magnitude");
+    let combined_target = plots.target(&format!("{}/Q2_combined_magnitude.png", output_dir));
+    figures.push(Box::new(|| {
+        response_visualizer::plot_combined_magnitude(
+            &hp_response.frequencies,
+            &hp_response.magnitude,
+            &lp_response.magnitude,
+            combined_target,
+            "Combined Filter Magnitude Responses",
+            Some(10000.0),
+            FrequencyScale::Linear,
+        )
+    }));
+    parallel::run_all(figures)?;
 
     // Save filter coefficients
-    save_filter_coefficients(&highpass, &lowpass, &format!("{}/Q2_filter_coefficients.txt", output_dir));
+    save_filter_coefficients(&highpass, &lowpass, &format!("{}/Q2_filter_coefficients.txt", output_dir))?;
 
     // Save frequency response data
-    save_frequency_response(&hp_response, &lp_response, &format!("{}/Q2_frequency_response.txt", output_dir));
+    save_frequency_response(&hp_response, &lp_response, &format!("{}/Q2_frequency_response.txt", output_dir))?;
+
+    // Attenuation table: the exact response at f_d ± f_B and at Q1's detected peaks
+    let q1_peaks_path = run_dir::upstream_path(&args, "../Q1/output", "Q1_peaks.csv")?;
+    let attenuation = attenuation_table(&highpass, &lowpass, f_d, f_b, &read_q1_peaks(&q1_peaks_path, sample_rate)?);
+    save_attenuation_table(&attenuation, &format!("{}/Q2_attenuation.csv", output_dir))?;
+
+    // Coefficients and key gains in the shared results format
+    StageResults::new("Q2")
+        .parameter("f_s_hz", sample_rate)
+        .parameter("f_d_hz", f_d)
+        .parameter("f_d_std_hz", f_d_sigma)
+        .parameter("f_b_hz", f_b)
+        .parameter("order", order)
+        .parameter("guard_band_hz", guard_band)
+        .metric("highpass_cutoff_hz", hp_cutoff)
+        .metric("lowpass_cutoff_hz", lp_cutoff)
+        .table(
+            "filters",
+            [("highpass", &highpass), ("lowpass", &lowpass)]
+                .iter()
+                .map(|(name, filter)| {
+                    row![
+                        ("name", *name),
+                        ("cutoff_hz", filter.cutoff),
+                        ("b", filter.b.as_slice()),
+                        ("a", filter.a.as_slice())
+                    ]
+                })
+                .collect(),
+        )
+        .table(
+            "gains",
+            gain_rows(&highpass, &lowpass, &[1000.0, f_b, f_d, f_d + f_b, 2.0 * f_d], sample_rate)
+                .into_iter()
+                .map(|(f, hp_db, lp_db)| row![("frequency_hz", f), ("highpass_db", hp_db), ("lowpass_db", lp_db)])
+                .collect(),
+        )
+        .table(
+            "attenuation",
+            attenuation
+                .iter()
+                .map(|(label, (f, hp_db, lp_db))| {
+                    row![
+                        ("point", label.as_str()),
+                        ("frequency_hz", *f),
+                        ("highpass_db", *hp_db),
+                        ("lowpass_db", *lp_db),
+                        ("chain_db", hp_db + lp_db)
+                    ]
+                })
+                .collect(),
+        )
+        .reproducibility(&reproducibility)
+        .save_with(format!("{}/Q2_results.json", output_dir), &reproducibility)?;
+
+    // Coefficients and complex responses for MATLAB's filter/freqz or NumPy (--mat / --npz)
+    if npy::requested(&args) || mat::requested(&args) {
+        let arrays = Npz::new()
+            .array("f_s", vec![sample_rate])
+            .array("hp_cutoff", vec![hp_cutoff])
+            .array("lp_cutoff", vec![lp_cutoff])
+            .array("hp_b", highpass.b.as_slice())
+            .array("hp_a", highpass.a.as_slice())
+            .array("lp_b", lowpass.b.as_slice())
+            .array("lp_a", lowpass.a.as_slice())
+            .array("frequencies", hp_response.frequencies.as_slice())
+            .array("H_hp", hp_response.complex_response.as_slice())
+            .array("H_lp", lp_response.complex_response.as_slice());
+        if npy::requested(&args) {
+            arrays.save(format!("{}/Q2_filters.npz", output_dir))?;
+        }
+        if mat::requested(&args) {
+            mat::save(format!("{}/Q2_filters.mat", output_dir), &arrays.arrays)?;
+        }
+    }
 
-    println!("\nAll results saved to '{}/' directory", output_dir);
-    println!("\nQ2 completed successfully!");
+    info!("\nAll results saved to '{}/' directory", output_dir);
+    if let Some(run_directory) = run_directory {
+        let dir = run_directory.finish("Q2", &[&q1_results_path], &args)?;
+        info!("Run archived to '{}/' ({}/latest points to it)", dir.display(), output_dir);
+    }
+    info!("\nQ2 completed successfully!");
+    Ok(())
 }
 
-fn read_q1_results(path: &str) -> (f64, f64, f64) {
-    let content = fs::read_to_string(path)
-        .expect("Failed to read Q1 results file");
+fn read_q1_results(path: &str) -> Result<(f64, f64, f64, f64), DspError> {
+    let content = fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
 
     let mut sample_rate = 22050.0;
     let mut f_d = 3225.0;
     let mut f_b = 4000.0;
+    // Older Q1 results carry no uncertainty; treat f_d as exact
+    let mut f_d_sigma = 0.0;
 
     for line in content.lines() {
         // Parse: "频率偏差 f_d = 3225.1032 Hz"
         if line.contains("频率偏差") && line.contains("f_d") {
             if let Some(value_str) = line.split('=').nth(1) {
-                if let Some(num_str) = value_str.trim().split_whitespace().next() {
+                if let Some(num_str) = value_str.split_whitespace().next() {
                     f_d = num_str.parse().unwrap_or(3225.0);
                 }
             }
@@ -146,7 +264,7 @@ fn read_q1_results(path: &str) -> (f64, f64, f64) {
         // Parse: "采样率 f_s = 22050.00 Hz"
         else if line.contains("采样率") && line.contains("f_s") {
             if let Some(value_str) = line.split('=').nth(1) {
-                if let Some(num_str) = value_str.trim().split_whitespace().next() {
+                if let Some(num_str) = value_str.split_whitespace().next() {
                     sample_rate = num_str.parse().unwrap_or(22050.0);
                 }
             }
@@ -154,19 +272,97 @@ fn read_q1_results(path: &str) -> (f64, f64, f64) {
         // Parse: "基带带宽 f_B = 4000 Hz"
         else if line.contains("基带带宽") && line.contains("f_B") {
             if let Some(value_str) = line.split('=').nth(1) {
-                if let Some(num_str) = value_str.trim().split_whitespace().next() {
+                if let Some(num_str) = value_str.split_whitespace().next() {
                     f_b = num_str.parse().unwrap_or(4000.0);
                 }
             }
         }
+        // Parse: "估计标准不确定度 σ = 0.2879 Hz"
+        else if line.contains("不确定度") && line.contains('σ') {
+            if let Some(value_str) = line.split('=').nth(1) {
+                if let Some(num_str) = value_str.split_whitespace().next() {
+                    f_d_sigma = num_str.parse().unwrap_or(0.0);
+                }
+            }
+        }
+    }
+
+    Ok((sample_rate, f_d, f_b, f_d_sigma))
+}
+
+/// Peak frequencies from Q1's `Q1_peaks.csv`, folded into 0..f_s/2 with the mirror images
+/// of the two-sided spectrum dropped; none when Q1 did not write the file
+fn read_q1_peaks(path: &str, sample_rate: f64) -> Result<Vec<f64>, DspError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("{} not found; the attenuation table lists f_d ± f_B only", path);
+            return Ok(Vec::new());
+        }
+        Err(e) => return Err(DspError::io(path, e)),
+    };
+
+    let mut peaks: Vec<f64> = Vec::new();
+    // Parse: "1,3225.1607,0.003444,4573" (peak number, frequency, magnitude, bin)
+    for (i, line) in content.lines().enumerate().skip(1).filter(|(_, line)| !line.trim().is_empty()) {
+        let freq: f64 = line
+            .split(',')
+            .nth(1)
+            .and_then(|field| field.trim().parse().ok())
+            .ok_or_else(|| DspError::Data(format!("{}: line {}: no peak frequency in '{}'", path, i + 1, line)))?;
+        let freq = if freq > sample_rate / 2.0 { sample_rate - freq } else { freq };
+        if !peaks.iter().any(|&p| (p - freq).abs() < 0.01) {
+            peaks.push(freq);
+        }
+    }
+    Ok(peaks)
+}
+
+/// `(frequency, high-pass dB, low-pass dB)` at each of `frequencies`, evaluated exactly
+fn gain_rows(
+    highpass: &butterworth_filter::ButterworthFilter,
+    lowpass: &butterworth_filter::ButterworthFilter,
+    frequencies: &[f64],
+    sample_rate: f64,
+) -> Vec<(f64, f64, f64)> {
+    let hp = filter_response::FilterResponse::at_frequencies(highpass, frequencies, sample_rate);
+    let lp = filter_response::FilterResponse::at_frequencies(lowpass, frequencies, sample_rate);
+    frequencies
+        .iter()
+        .zip(hp.magnitude.iter().zip(&lp.magnitude))
+        .map(|(&f, (&h, &l))| (f, filter_response::magnitude_to_db(h), filter_response::magnitude_to_db(l)))
+        .collect()
+}
+
+/// Labelled gains at |f_d - f_B| (the lower sideband edge, mirrored to positive frequency),
+/// f_d, f_d + f_B and every detected peak
+fn attenuation_table(
+    highpass: &butterworth_filter::ButterworthFilter,
+    lowpass: &butterworth_filter::ButterworthFilter,
+    f_d: f64,
+    f_b: f64,
+    peaks: &[f64],
+) -> Vec<(String, (f64, f64, f64))> {
+    let mut labels = vec!["|f_d - f_B|".to_string(), "f_d".to_string(), "f_d + f_B".to_string()];
+    let mut frequencies = vec![(f_d - f_b).abs(), f_d, f_d + f_b];
+    for (i, &peak) in peaks.iter().enumerate() {
+        labels.push(format!("peak {}", i + 1));
+        frequencies.push(peak);
     }
+    labels.into_iter().zip(gain_rows(highpass, lowpass, &frequencies, highpass.sample_rate)).collect()
+}
 
-    (sample_rate, f_d, f_b)
+fn save_attenuation_table(rows: &[(String, (f64, f64, f64))], path: &str) -> Result<(), DspError> {
+    let mut content = String::from("Point,Frequency (Hz),High-pass (dB),Low-pass (dB),High-pass + low-pass (dB)\n");
+    for (label, (f, hp_db, lp_db)) in rows {
+        content.push_str(&format!("{},{:.4},{:.2},{:.2},{:.2}\n", label, f, hp_db, lp_db, hp_db + lp_db));
+    }
+    fs::write(path, content).map_err(|e| DspError::io(path, e))
 }
 
 fn save_filter_coefficients(highpass: &butterworth_filter::ButterworthFilter, 
                             lowpass: &butterworth_filter::ButterworthFilter,
-                            path: &str) {
+                            path: &str) -> Result<(), DspError> {
     let mut content = String::new();
     content.push_str("=== Q2: Filter Coefficients ===\n\n");
 
@@ -194,26 +390,26 @@ fn save_filter_coefficients(highpass: &butterworth_filter::ButterworthFilter,
         content.push_str(&format!("  a[{}] = {:.15e}\n", i, coef));
     }
 
-    fs::write(path, content).expect("Failed to write filter coefficients");
+    fs::write(path, content).map_err(|e| DspError::io(path, e))
 }
 
 fn save_frequency_response(hp_response: &filter_response::FilterResponse,
                           lp_response: &filter_response::FilterResponse,
-                          path: &str) {
+                          path: &str) -> Result<(), DspError> {
     let mut content = String::new();
     content.push_str("=== Q2: Frequency Response Data ===\n\n");
 
     content.push_str("High-pass Filter:\n");
     content.push_str(&format!("Number of frequency points: {}\n", hp_response.frequencies.len()));
     content.push_str(&format!("Frequency range: 0 - {:.2} Hz\n", hp_response.frequencies.last().unwrap_or(&0.0)));
-    content.push_str(&format!("Maximum magnitude: {:.6}\n", hp_response.magnitude.iter().cloned().fold(0./0., f64::max)));
+    content.push_str(&format!("Maximum magnitude: {:.6}\n", hp_response.magnitude.iter().cloned().fold(f64::NAN, f64::max)));
     content.push_str(&format!("Minimum magnitude: {:.6}\n", hp_response.magnitude.iter().cloned().fold(f64::INFINITY, f64::min)));
 
     content.push_str("\nLow-pass Filter:\n");
     content.push_str(&format!("Number of frequency points: {}\n", lp_response.frequencies.len()));
     content.push_str(&format!("Frequency range: 0 - {:.2} Hz\n", lp_response.frequencies.last().unwrap_or(&0.0)));
-    content.push_str(&format!("Maximum magnitude: {:.6}\n", lp_response.magnitude.iter().cloned().fold(0./0., f64::max)));
+    content.push_str(&format!("Maximum magnitude: {:.6}\n", lp_response.magnitude.iter().cloned().fold(f64::NAN, f64::max)));
     content.push_str(&format!("Minimum magnitude: {:.6}\n", lp_response.magnitude.iter().cloned().fold(f64::INFINITY, f64::min)));
 
-    fs::write(path, content).expect("Failed to write frequency response data");
+    fs::write(path, content).map_err(|e| DspError::io(path, e))
 }
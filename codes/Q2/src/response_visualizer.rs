@@ -1,20 +1,36 @@
+use plotters::coord::ranged1d::{AsRangedCoord, ValueFormatter};
+use dsp_core::error::{DspError, Result};
+use dsp_core::plot_output::PlotTarget;
+use dsp_core::plot_style::{scientific_label, LegendPosition, PlotStyle, Rgb};
+use dsp_core::with_drawing_area;
+use plotters::chart::MeshStyle;
+use plotters::coord::Shift;
 use plotters::prelude::*;
+use std::ops::Range;
 use crate::filter_response;
 
-const PLOT_WIDTH: u32 = 1200;
-const PLOT_HEIGHT: u32 = 600;
+/// Lowest frequency on a logarithmic axis (Hz), since 0 Hz cannot be shown
+const LOG_MIN_FREQ: f64 = 10.0;
+
+/// Frequency axis of a response plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrequencyScale {
+    /// 0 Hz to the maximum frequency
+    #[default]
+    Linear,
+    /// Logarithmic from 10 Hz with a gridline at every decade, so roll-off reads as dB/decade
+    Log,
+}
 
 /// Plot magnitude response (linear scale)
 pub fn plot_magnitude_response(
     frequencies: &[f64],
     magnitude: &[f64],
-    output_path: &str,
+    target: impl Into<PlotTarget>,
     title: &str,
     max_freq: Option<f64>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new(output_path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
-    root.fill(&WHITE)?;
-
+    scale: FrequencyScale,
+) -> Result<()> {
     let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
     let max_mag = magnitude.iter()
         .zip(frequencies.iter())
@@ -23,192 +39,322 @@ pub fn plot_magnitude_response(
         .fold(0.0, f64::max)
         .max(1.1);
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 30).into_font())
-        .margin(15)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq_val, 0.0..max_mag)?;
-
-    chart.configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude")
-        .draw()?;
-
-    chart.draw_series(LineSeries::new(
-        frequencies.iter()
-            .zip(magnitude.iter())
-            .filter(|(&f, _)| f <= max_freq_val)
-            .map(|(&f, &m)| (f, m)),
-        &BLUE,
-    ))?;
-
-    root.present()?;
-    Ok(())
+    let panel = Panel {
+        y_range: 0.0..max_mag,
+        y_desc: "Magnitude",
+        axis: ValueAxis::Magnitude,
+        curves: vec![(None, points(frequencies, magnitude, max_freq_val, scale), 0)],
+    };
+    plot_panels(&target.into(), title, scale, max_freq_val, vec![panel])
 }
 
 /// Plot magnitude response in dB scale
 pub fn plot_magnitude_response_db(
     frequencies: &[f64],
     magnitude: &[f64],
-    output_path: &str,
+    target: impl Into<PlotTarget>,
+    title: &str,
+    max_freq: Option<f64>,
+    scale: FrequencyScale,
+) -> Result<()> {
+    let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
+    let panel = magnitude_db_panel(frequencies, magnitude, max_freq_val, scale);
+    plot_panels(&target.into(), title, scale, max_freq_val, vec![panel])
+}
+
+/// Plot phase response
+pub fn plot_phase_response(
+    frequencies: &[f64],
+    phase: &[f64],
+    target: impl Into<PlotTarget>,
     title: &str,
     max_freq: Option<f64>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new(output_path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
-    root.fill(&WHITE)?;
+    scale: FrequencyScale,
+) -> Result<()> {
+    let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
+    let panel = phase_panel(frequencies, phase, max_freq_val, scale);
+    plot_panels(&target.into(), title, scale, max_freq_val, vec![panel])
+}
 
+/// Bode plot: magnitude (dB) above phase (degrees) on a shared frequency axis, in one figure
+///
+/// The canvas is 1.5 times the style's height so each panel keeps a readable aspect ratio.
+pub fn plot_bode(
+    frequencies: &[f64],
+    magnitude: &[f64],
+    phase: &[f64],
+    target: impl Into<PlotTarget>,
+    title: &str,
+    max_freq: Option<f64>,
+    scale: FrequencyScale,
+) -> Result<()> {
     let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
-    
+    let panels = vec![
+        magnitude_db_panel(frequencies, magnitude, max_freq_val, scale),
+        phase_panel(frequencies, phase, max_freq_val, scale),
+    ];
+    plot_panels(&target.into(), title, scale, max_freq_val, panels)
+}
+
+/// Plot combined magnitude responses of high-pass and low-pass filters
+pub fn plot_combined_magnitude(
+    frequencies: &[f64],
+    hp_magnitude: &[f64],
+    lp_magnitude: &[f64],
+    target: impl Into<PlotTarget>,
+    title: &str,
+    max_freq: Option<f64>,
+    scale: FrequencyScale,
+) -> Result<()> {
+    let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
+
+    let max_mag = hp_magnitude.iter()
+        .chain(lp_magnitude.iter())
+        .zip(frequencies.iter().cycle())
+        .filter(|(_, &f)| f <= max_freq_val)
+        .map(|(&m, _)| m)
+        .fold(0.0, f64::max)
+        .max(1.1);
+
+    let panel = Panel {
+        y_range: 0.0..max_mag,
+        y_desc: "Magnitude",
+        axis: ValueAxis::Magnitude,
+        curves: vec![
+            (Some("High-pass"), points(frequencies, hp_magnitude, max_freq_val, scale), 0),
+            (Some("Low-pass"), points(frequencies, lp_magnitude, max_freq_val, scale), 1),
+        ],
+    };
+    plot_panels(&target.into(), title, scale, max_freq_val, vec![panel])
+}
+
+/// Magnitude in dB, floored at -80 dB
+fn magnitude_db_panel(frequencies: &[f64], magnitude: &[f64], max_freq: f64, scale: FrequencyScale) -> Panel<'static> {
     let magnitude_db: Vec<f64> = magnitude.iter()
         .map(|&m| filter_response::magnitude_to_db(m))
         .collect();
 
     let min_db = magnitude_db.iter()
         .zip(frequencies.iter())
-        .filter(|(_, &f)| f <= max_freq_val)
+        .filter(|(_, &f)| f <= max_freq)
         .map(|(&db, _)| db)
         .fold(f64::INFINITY, f64::min)
         .max(-80.0);
-    
-    let max_db = 10.0;
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 30).into_font())
-        .margin(15)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq_val, min_db..max_db)?;
+    let max_db = 10.0;
 
-    chart.configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude (dB)")
-        .draw()?;
-
-    chart.draw_series(LineSeries::new(
-        frequencies.iter()
-            .zip(magnitude_db.iter())
-            .filter(|(&f, _)| f <= max_freq_val)
-            .map(|(&f, &db)| (f, db)),
-        &BLUE,
-    ))?;
-
-    root.present()?;
-    Ok(())
+    Panel {
+        y_range: min_db..max_db,
+        y_desc: "Magnitude (dB)",
+        axis: ValueAxis::MagnitudeDb,
+        curves: vec![(None, points(frequencies, &magnitude_db, max_freq, scale), 0)],
+    }
 }
 
-/// Plot phase response
-pub fn plot_phase_response(
-    frequencies: &[f64],
-    phase: &[f64],
-    output_path: &str,
-    title: &str,
-    max_freq: Option<f64>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new(output_path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
-    root.fill(&WHITE)?;
-
-    let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
-
+/// Phase in degrees, with 10° of headroom either side
+fn phase_panel(frequencies: &[f64], phase: &[f64], max_freq: f64, scale: FrequencyScale) -> Panel<'static> {
     let phase_deg: Vec<f64> = phase.iter()
         .map(|&p| filter_response::phase_to_degrees(p))
         .collect();
 
     let min_phase = phase_deg.iter()
         .zip(frequencies.iter())
-        .filter(|(_, &f)| f <= max_freq_val)
+        .filter(|(_, &f)| f <= max_freq)
         .map(|(&p, _)| p)
         .fold(f64::INFINITY, f64::min) - 10.0;
-    
+
     let max_phase = phase_deg.iter()
         .zip(frequencies.iter())
-        .filter(|(_, &f)| f <= max_freq_val)
+        .filter(|(_, &f)| f <= max_freq)
         .map(|(&p, _)| p)
         .fold(f64::NEG_INFINITY, f64::max) + 10.0;
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 30).into_font())
-        .margin(15)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq_val, min_phase..max_phase)?;
+    Panel {
+        y_range: min_phase..max_phase,
+        y_desc: "Phase (degrees)",
+        axis: ValueAxis::Phase,
+        curves: vec![(None, points(frequencies, &phase_deg, max_freq, scale), 1)],
+    }
+}
 
-    chart.configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Phase (degrees)")
-        .draw()?;
-
-    chart.draw_series(LineSeries::new(
-        frequencies.iter()
-            .zip(phase_deg.iter())
-            .filter(|(&f, _)| f <= max_freq_val)
-            .map(|(&f, &p)| (f, p)),
-        &RED,
-    ))?;
-
-    root.present()?;
-    Ok(())
+/// (frequency, value) pairs inside the plotted frequency range
+fn points(frequencies: &[f64], values: &[f64], max_freq: f64, scale: FrequencyScale) -> Vec<(f64, f64)> {
+    let min_freq = match scale {
+        FrequencyScale::Linear => 0.0,
+        FrequencyScale::Log => LOG_MIN_FREQ,
+    };
+    frequencies.iter()
+        .zip(values.iter())
+        .filter(|(&f, _)| f >= min_freq && f <= max_freq)
+        .map(|(&f, &v)| (f, v))
+        .collect()
 }
 
-/// Plot combined magnitude responses of high-pass and low-pass filters
-pub fn plot_combined_magnitude(
-    frequencies: &[f64],
-    hp_magnitude: &[f64],
-    lp_magnitude: &[f64],
-    output_path: &str,
+/// Legend label, points and palette index of one curve
+type Curve<'a> = (Option<&'a str>, Vec<(f64, f64)>, usize);
+
+/// What a panel's y axis shows, which decides the axis options that apply to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueAxis {
+    Magnitude,
+    MagnitudeDb,
+    Phase,
+}
+
+/// One chart of a figure, with its own y axis
+struct Panel<'a> {
+    y_range: Range<f64>,
+    y_desc: &'a str,
+    axis: ValueAxis,
+    curves: Vec<Curve<'a>>,
+}
+
+/// Draw `panels` stacked top to bottom under one title, on a linear or logarithmic
+/// frequency axis, and save the plot
+///
+/// `--freq-range` replaces the frequency axis and drops the points outside it. On a
+/// logarithmic axis a lower limit at or below 0 Hz keeps the usual 10 Hz, and a range
+/// narrower than a decade is drawn linearly, since it would get no tick labels.
+fn plot_panels(
+    target: &PlotTarget,
     title: &str,
-    max_freq: Option<f64>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new(output_path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
-    root.fill(&WHITE)?;
+    scale: FrequencyScale,
+    max_freq: f64,
+    mut panels: Vec<Panel>,
+) -> Result<()> {
+    let style = &target.style;
+    let x_range = match scale {
+        FrequencyScale::Linear => style.axes.freq(0.0..max_freq),
+        FrequencyScale::Log => {
+            let range = style.axes.freq(LOG_MIN_FREQ..max_freq);
+            if range.start > 0.0 { range } else { LOG_MIN_FREQ..range.end }
+        }
+    };
+    let scale = if x_range.end < 10.0 * x_range.start { FrequencyScale::Linear } else { scale };
+    for (_, curve, _) in panels.iter_mut().flat_map(|panel| panel.curves.iter_mut()) {
+        curve.retain(|&(f, _)| x_range.start <= f && f <= x_range.end);
+    }
+    let x_labels = style.axes.freq_labels(&x_range);
+    let decades: Vec<f64> = (x_range.start.log10().ceil() as i32..=x_range.end.log10().floor() as i32)
+        .map(|k| 10f64.powi(k))
+        .collect();
 
-    let max_freq_val = max_freq.unwrap_or(*frequencies.last().unwrap_or(&10000.0));
-    
-    let max_mag = hp_magnitude.iter()
-        .chain(lp_magnitude.iter())
-        .zip(frequencies.iter().cycle())
-        .filter(|(_, &f)| f <= max_freq_val)
-        .map(|(&m, _)| m)
-        .fold(0.0, f64::max)
-        .max(1.1);
+    let (width, height) = style.size;
+    let default_size = (width, height * (panels.len() as u32 + 1) / 2);
+    with_drawing_area!(*target, default_size, |root| {
+        root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+        let titled = root.titled(title, (style.font, style.title_font_size)).map_err(DspError::plot)?;
+        let areas = titled.split_evenly((panels.len(), 1));
+        for (area, panel) in areas.iter().zip(panels) {
+            match scale {
+                FrequencyScale::Linear => draw_chart(area, style, x_range.clone(), x_labels, panel, &[])?,
+                FrequencyScale::Log => {
+                    draw_chart(area, style, x_range.clone().log_scale(), x_labels, panel, &decades)?
+                }
+            }
+        }
+        root.present().map_err(DspError::plot)?;
+    })?;
+    Ok(())
+}
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 30).into_font())
-        .margin(15)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq_val, 0.0..max_mag)?;
+/// Chart body shared by both frequency scales; `gridlines` are extra vertical lines
+fn draw_chart<DB, X>(
+    root: &DrawingArea<DB, Shift>,
+    style: &PlotStyle,
+    x_range: X,
+    x_labels: usize,
+    panel: Panel,
+    gridlines: &[f64],
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    X: AsRangedCoord<Value = f64>,
+    X::CoordDescType: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let Panel { y_range, y_desc, axis, curves } = panel;
+    let axes = style.axes;
+    let y_range = match axis {
+        ValueAxis::Magnitude => axes.magnitude(y_range),
+        ValueAxis::MagnitudeDb => axes.magnitude_db(y_range),
+        ValueAxis::Phase => y_range,
+    };
+    let (y_min, y_max) = (y_range.start, y_range.end);
+    // Phase keeps the plotting library's ten labels
+    let y_labels = match axis {
+        ValueAxis::Phase => 10,
+        _ => axes.magnitude_labels(&y_range),
+    };
+    let mut chart = ChartBuilder::on(root)
+        .margin(style.margin)
+        .x_label_area_size(style.x_label_area)
+        .y_label_area_size(style.y_label_area)
+        .build_cartesian_2d(x_range, y_range).map_err(DspError::plot)?;
 
-    chart.configure_mesh()
+    let mut mesh = chart.configure_mesh();
+    style_mesh(&mut mesh, style)
+        .x_labels(x_labels)
+        .y_labels(y_labels)
         .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude")
-        .draw()?;
-
-    // Draw high-pass filter
-    chart.draw_series(LineSeries::new(
-        frequencies.iter()
-            .zip(hp_magnitude.iter())
-            .filter(|(&f, _)| f <= max_freq_val)
-            .map(|(&f, &m)| (f, m)),
-        &BLUE,
-    ))?.label("High-pass")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
-
-    // Draw low-pass filter
-    chart.draw_series(LineSeries::new(
-        frequencies.iter()
-            .zip(lp_magnitude.iter())
-            .filter(|(&f, _)| f <= max_freq_val)
-            .map(|(&f, &m)| (f, m)),
-        &RED,
-    ))?.label("Low-pass")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
-
-    chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw()?;
-
-    root.present()?;
+        .y_desc(y_desc)
+        .x_label_formatter(&|f| format!("{:.0}", f));
+    if axes.scientific && axis != ValueAxis::Phase {
+        mesh.y_label_formatter(&scientific_label);
+    }
+    mesh.draw().map_err(DspError::plot)?;
+
+    for &f in gridlines {
+        chart.draw_series(LineSeries::new(vec![(f, y_min), (f, y_max)], BLACK.mix(0.4).stroke_width(style.line_width))).map_err(DspError::plot)?;
+    }
+
+    let mut labelled = false;
+    for (label, curve, index) in curves {
+        let line = rgb(style.color(index)).stroke_width(style.line_width);
+        let series = chart.draw_series(LineSeries::new(curve, line)).map_err(DspError::plot)?;
+        if let Some(label) = label {
+            series.label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], line));
+            labelled = true;
+        }
+    }
+
+    if labelled {
+        chart.configure_series_labels()
+            .position(legend_position(style.legend))
+            .label_font((style.font, style.label_font_size))
+            .background_style(rgb(style.background).mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+    }
     Ok(())
 }
+
+fn rgb(Rgb(r, g, b): Rgb) -> RGBColor {
+    RGBColor(r, g, b)
+}
+
+fn legend_position(position: LegendPosition) -> SeriesLabelPosition {
+    match position {
+        LegendPosition::UpperLeft => SeriesLabelPosition::UpperLeft,
+        LegendPosition::UpperRight => SeriesLabelPosition::UpperRight,
+        LegendPosition::MiddleLeft => SeriesLabelPosition::MiddleLeft,
+        LegendPosition::MiddleRight => SeriesLabelPosition::MiddleRight,
+        LegendPosition::LowerLeft => SeriesLabelPosition::LowerLeft,
+        LegendPosition::LowerRight => SeriesLabelPosition::LowerRight,
+    }
+}
+
+/// Tick label fonts and gridlines from the style
+fn style_mesh<'m, 'a, 'b, X: Ranged, Y: Ranged, DB: DrawingBackend>(
+    mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>,
+    style: &PlotStyle,
+) -> &'m mut MeshStyle<'a, 'b, X, Y, DB> {
+    mesh.label_style((style.font, style.label_font_size))
+        .axis_desc_style((style.font, style.label_font_size));
+    if !style.grid {
+        mesh.disable_mesh();
+    }
+    mesh
+}
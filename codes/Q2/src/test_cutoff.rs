@@ -1,38 +1,38 @@
 // 测试滤波器的实际截止频率
+use log::info;
 use std::f64::consts::PI;
 
-mod butterworth_filter;
-mod filter_response;
-
-use butterworth_filter::ButterworthFilter;
+use q2_filter_design::butterworth_filter::ButterworthFilter;
+use dsp_core::logging;
 
 fn main() {
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
     let sample_rate = 22050.0;
     let designed_cutoff_hp = 3000.0;  // 使用对称峰值法确定的频率偏差
     let designed_cutoff_lp = 4000.0;
     
-    println!("=== Testing Filter Cutoff Frequencies ===\n");
+    info!("=== Testing Filter Cutoff Frequencies ===\n");
     
     // Test high-pass filter
-    println!("High-pass filter (designed cutoff = {:.4} Hz)", designed_cutoff_hp);
+    info!("High-pass filter (designed cutoff = {:.4} Hz)", designed_cutoff_hp);
     let hp_filter = ButterworthFilter::highpass(8, designed_cutoff_hp, sample_rate);
     let actual_cutoff_hp = find_3db_cutoff(&hp_filter, sample_rate, true);
-    println!("  Actual -3dB cutoff: {:.4} Hz", actual_cutoff_hp);
-    println!("  Error: {:.4} Hz ({:.2}%)\n", 
+    info!("  Actual -3dB cutoff: {:.4} Hz", actual_cutoff_hp);
+    info!("  Error: {:.4} Hz ({:.2}%)\n", 
         actual_cutoff_hp - designed_cutoff_hp,
         (actual_cutoff_hp - designed_cutoff_hp) / designed_cutoff_hp * 100.0);
     
     // Test low-pass filter
-    println!("Low-pass filter (designed cutoff = {:.4} Hz)", designed_cutoff_lp);
+    info!("Low-pass filter (designed cutoff = {:.4} Hz)", designed_cutoff_lp);
     let lp_filter = ButterworthFilter::lowpass(8, designed_cutoff_lp, sample_rate);
     let actual_cutoff_lp = find_3db_cutoff(&lp_filter, sample_rate, false);
-    println!("  Actual -3dB cutoff: {:.4} Hz", actual_cutoff_lp);
-    println!("  Error: {:.4} Hz ({:.2}%)\n", 
+    info!("  Actual -3dB cutoff: {:.4} Hz", actual_cutoff_lp);
+    info!("  Error: {:.4} Hz ({:.2}%)\n", 
         actual_cutoff_lp - designed_cutoff_lp,
         (actual_cutoff_lp - designed_cutoff_lp) / designed_cutoff_lp * 100.0);
 }
 
-fn find_3db_cutoff(filter: &ButterworthFilter, sample_rate: f64, is_highpass: bool) -> f64 {
+fn find_3db_cutoff(filter: &ButterworthFilter, sample_rate: f64, _is_highpass: bool) -> f64 {
     // 寻找-3dB点 (幅度为1/sqrt(2) ≈ 0.7071)
     let target_magnitude = 1.0 / 2.0_f64.sqrt();
     
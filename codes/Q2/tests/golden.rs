@@ -0,0 +1,298 @@
+//! Golden-reference tests against scipy.signal
+//!
+//! The expected vectors were produced with SciPy (`scipy.signal.butter(..., output="ba")`
+//! and `scipy.signal.freqz`) and are stored here, so a change to the Butterworth designer or
+//! the frequency response code that moves a coefficient beyond rounding fails the build.
+//! Cutoffs are given as in SciPy, as a fraction of Nyquist; the tests use `fs = 2`.
+
+use q2_filter_design::butterworth_filter::ButterworthFilter;
+use q2_filter_design::filter_response::FilterResponse;
+
+/// Coefficients agree with SciPy to this relative tolerance
+const COEFFICIENT_TOLERANCE: f64 = 1e-10;
+
+/// `|actual - expected| <= tolerance * max(1, |expected|)` elementwise, with a readable failure
+fn assert_close(what: &str, actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len(), "{}: length", what);
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            (a - e).abs() <= tolerance * e.abs().max(1.0),
+            "{}[{}]: got {:e}, SciPy gives {:e}",
+            what,
+            i,
+            a,
+            e
+        );
+    }
+}
+
+/// One `scipy.signal.butter(order, wn, btype)` call and its result
+struct ButterCase {
+    btype: &'static str,
+    order: usize,
+    wn: f64,
+    b: &'static [f64],
+    a: &'static [f64],
+}
+
+const BUTTER_CASES: &[ButterCase] = &[
+    // butter(2, 0.5, "low")
+    ButterCase {
+        btype: "lowpass",
+        order: 2,
+        wn: 0.5,
+        b: &[0.2928932188134524, 0.5857864376269049, 0.2928932188134524],
+        a: &[1.0, -1.665334536937735e-16, 0.1715728752538099],
+    },
+    // butter(3, 0.3, "low")
+    ButterCase {
+        btype: "lowpass",
+        order: 3,
+        wn: 0.3,
+        b: &[
+            0.04953299635725319,
+            0.1485989890717596,
+            0.1485989890717596,
+            0.04953299635725319,
+        ],
+        a: &[
+            1.0,
+            -1.161917483671733,
+            0.6959427557896508,
+            -0.1377613012598928,
+        ],
+    },
+    // butter(4, 0.2, "low")
+    ButterCase {
+        btype: "lowpass",
+        order: 4,
+        wn: 0.2,
+        b: &[
+            0.004824343357716228,
+            0.01929737343086491,
+            0.02894606014629737,
+            0.01929737343086491,
+            0.004824343357716228,
+        ],
+        a: &[
+            1.0,
+            -2.369513007182038,
+            2.31398841441588,
+            -1.054665405878568,
+            0.1873794923681849,
+        ],
+    },
+    // butter(4, 0.2, "high")
+    ButterCase {
+        btype: "highpass",
+        order: 4,
+        wn: 0.2,
+        b: &[
+            0.4328466449902919,
+            -1.731386579961168,
+            2.597079869941751,
+            -1.731386579961168,
+            0.4328466449902919,
+        ],
+        a: &[
+            1.0,
+            -2.369513007182038,
+            2.31398841441588,
+            -1.054665405878568,
+            0.1873794923681849,
+        ],
+    },
+    // butter(5, 0.4, "high")
+    ButterCase {
+        btype: "highpass",
+        order: 5,
+        wn: 0.4,
+        b: &[
+            0.10837370258748,
+            -0.5418685129373998,
+            1.0837370258748,
+            -1.0837370258748,
+            0.5418685129373998,
+            -0.10837370258748,
+        ],
+        a: &[
+            1.0,
+            -0.9853252392792378,
+            0.9738493318367639,
+            -0.3863565586484487,
+            0.111163840578342,
+            -0.01126351245656588,
+        ],
+    },
+];
+
+/// butter(8, 3000.1823, "high", fs=22050): the Q2 high-pass for project.wav
+const PROJECT_HIGHPASS_B: [f64; 9] = [
+    0.10202663063464,
+    -0.8162130450771199,
+    2.85674565776992,
+    -5.713491315539839,
+    7.141864144424799,
+    -5.713491315539839,
+    2.85674565776992,
+    -0.8162130450771199,
+    0.10202663063464,
+];
+const PROJECT_HIGHPASS_A: [f64; 9] = [
+    1.0,
+    -3.629963587875276,
+    6.427310293207759,
+    -6.94184447738788,
+    4.931086451100367,
+    -2.334617349294002,
+    0.7148214475316049,
+    -0.1287643669116861,
+    0.01040946915925804,
+];
+
+/// butter(8, 4000, "low", fs=22050): the Q2 low-pass for project.wav
+const PROJECT_LOWPASS_B: [f64; 9] = [
+    0.00122130219055038,
+    0.009770417524403037,
+    0.03419646133541063,
+    0.06839292267082125,
+    0.08549115333852657,
+    0.06839292267082125,
+    0.03419646133541063,
+    0.009770417524403037,
+    0.00122130219055038,
+];
+const PROJECT_LOWPASS_A: [f64; 9] = [
+    1.0,
+    -2.182803717235219,
+    2.990304913083171,
+    -2.530910169286827,
+    1.500195796490401,
+    -0.6009034249791095,
+    0.1603748944005234,
+    -0.02545031672786034,
+    0.001845385035817037,
+];
+
+/// abs(freqz(*butter(4, 0.2), worN=16)[1]), at w = k·π/16
+const FREQZ_LOWPASS: [f64; 16] = [
+    0.9999999999999997,
+    0.999964361034989,
+    0.9902795246029347,
+    0.7962700645378062,
+    0.354092141622511,
+    0.1352900225045407,
+    0.05582849759252414,
+    0.02456293516486005,
+    0.01114492578357358,
+    0.005055818132295668,
+    0.002221638688809062,
+    0.0009097687294296469,
+    0.0003280963427893324,
+    9.437591112782072e-05,
+    1.744822685040042e-05,
+    1.048811537919298e-06,
+];
+
+/// abs(freqz(*butter(4, 0.2, "high"), worN=16)[1])
+const FREQZ_HIGHPASS: [f64; 16] = [
+    0.0,
+    0.008442550555511174,
+    0.1390915639144972,
+    0.6049413065091291,
+    0.9352105405956413,
+    0.9908060404593412,
+    0.9984403732104197,
+    0.9996982855922513,
+    0.9999378933860235,
+    0.999987219269833,
+    0.9999975321577231,
+    0.9999995861603439,
+    0.9999999461763937,
+    0.9999999955465938,
+    0.9999999998477799,
+    0.9999999999994501,
+];
+
+#[test]
+fn butterworth_matches_scipy_butter() {
+    for case in BUTTER_CASES {
+        // SciPy's wn is relative to Nyquist, so fs = 2 makes it the cutoff in Hz
+        let filter = match case.btype {
+            "lowpass" => ButterworthFilter::lowpass(case.order, case.wn, 2.0),
+            _ => ButterworthFilter::highpass(case.order, case.wn, 2.0),
+        };
+        let what = format!("butter({}, {}, {})", case.order, case.wn, case.btype);
+        assert_close(
+            &format!("{} b", what),
+            &filter.b,
+            case.b,
+            COEFFICIENT_TOLERANCE,
+        );
+        assert_close(
+            &format!("{} a", what),
+            &filter.a,
+            case.a,
+            COEFFICIENT_TOLERANCE,
+        );
+    }
+}
+
+#[test]
+fn project_filters_match_scipy_butter() {
+    let highpass = ButterworthFilter::highpass(8, 3000.1823, 22050.0);
+    assert_close(
+        "high-pass b",
+        &highpass.b,
+        &PROJECT_HIGHPASS_B,
+        COEFFICIENT_TOLERANCE,
+    );
+    assert_close(
+        "high-pass a",
+        &highpass.a,
+        &PROJECT_HIGHPASS_A,
+        COEFFICIENT_TOLERANCE,
+    );
+
+    let lowpass = ButterworthFilter::lowpass(8, 4000.0, 22050.0);
+    assert_close(
+        "low-pass b",
+        &lowpass.b,
+        &PROJECT_LOWPASS_B,
+        COEFFICIENT_TOLERANCE,
+    );
+    assert_close(
+        "low-pass a",
+        &lowpass.a,
+        &PROJECT_LOWPASS_A,
+        COEFFICIENT_TOLERANCE,
+    );
+}
+
+#[test]
+fn frequency_response_matches_scipy_freqz() {
+    // 32 points from 0 to fs cover freqz's 16 points from 0 to just below Nyquist
+    for (filter, expected) in [
+        (ButterworthFilter::lowpass(4, 0.2, 2.0), &FREQZ_LOWPASS),
+        (ButterworthFilter::highpass(4, 0.2, 2.0), &FREQZ_HIGHPASS),
+    ] {
+        let response = FilterResponse::compute(&filter, 2.0, 32);
+        assert_close("|H|", &response.magnitude[..16], expected, 1e-9);
+    }
+}
+
+#[test]
+fn response_at_arbitrary_frequencies() {
+    // freqz's grid, given explicitly, reproduces the grid results
+    let frequencies: Vec<f64> = (0..16).map(|k| k as f64 / 16.0).collect();
+    let filter = ButterworthFilter::lowpass(4, 0.2, 2.0);
+    let response = FilterResponse::at_frequencies(&filter, &frequencies, 2.0);
+    assert_eq!(response.frequencies, frequencies);
+    assert_close("|H|", &response.magnitude, &FREQZ_LOWPASS, 1e-9);
+
+    // Off the grid: a Butterworth is 3.01 dB down exactly at its cutoff
+    for filter in [ButterworthFilter::lowpass(8, 0.2317, 2.0), ButterworthFilter::highpass(8, 0.5441, 2.0)] {
+        let response = FilterResponse::at_frequencies(&filter, &[filter.cutoff], 2.0);
+        assert_close("|H(fc)|", &response.magnitude, &[std::f64::consts::FRAC_1_SQRT_2], 1e-9);
+    }
+}
@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use q3_time_domain_demodulation::{audio_reader, iir_filter};
+
+const INPUT: &str = "../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav";
+const FILTERS: &str = "../Q2/output/Q2_filter_coefficients.txt";
+
+/// The Q2 filters over the whole recording, as in one run of the Q3 chain
+fn iir_filtering(c: &mut Criterion) {
+    let signal = audio_reader::read_wav(INPUT).expect("read project.wav");
+    let (hp_b, hp_a, lp_b, lp_a) = iir_filter::read_q2_filters(FILTERS).expect("read Q2 filter coefficients");
+
+    let mut group = c.benchmark_group("iir_filter");
+    group.sample_size(30);
+    group.bench_function("highpass", |b| b.iter(|| iir_filter::apply_filter(black_box(&signal), &hp_b, &hp_a)));
+    group.bench_function("lowpass", |b| b.iter(|| iir_filter::apply_filter(black_box(&signal), &lp_b, &lp_a)));
+    group.bench_function("lowpass_zero_phase", |b| {
+        b.iter(|| iir_filter::apply_filter_zero_phase(black_box(&signal), &lp_b, &lp_a))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, iir_filtering);
+criterion_main!(benches);
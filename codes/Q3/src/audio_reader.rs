@@ -1,29 +1,23 @@
-use hound;
+use dsp_core::channels::ChannelMode;
+use dsp_core::error::Result;
+use dsp_core::wav_io::{self, WavSpec};
+use log::info;
 
-pub fn read_wav(filename: &str) -> Result<Vec<f64>, String> {
-    let reader = hound::WavReader::open(filename)
-        .map_err(|e| format!("Failed to open WAV file: {}", e))?;
-
-    let spec = reader.spec();
-    println!("  Sample rate: {} Hz", spec.sample_rate);
-    println!("  Channels: {}", spec.channels);
-    println!("  Bits per sample: {}", spec.bits_per_sample);
+/// Read a WAV file as a single mono signal (channels averaged)
+pub fn read_wav(filename: &str) -> Result<Vec<f64>> {
+    let (mut channels, _) = read_wav_channels(filename, ChannelMode::Mix)?;
+    Ok(channels.remove(0))
+}
 
-    // Read all samples and normalize to [-1.0, 1.0]
-    let samples: Vec<f64> = match spec.bits_per_sample {
-        16 => {
-            reader
-                .into_samples::<i16>()
-                .map(|s| s.unwrap() as f64 / 32768.0)
-                .collect()
-        }
-        _ => {
-            return Err(format!(
-                "Unsupported bits per sample: {}",
-                spec.bits_per_sample
-            ))
-        }
-    };
+/// Read a WAV file and split it into the channels selected by `mode`
+///
+/// Returns the selected signals and the input spec, so the output can keep its format.
+pub fn read_wav_channels(filename: &str, mode: ChannelMode) -> Result<(Vec<Vec<f64>>, WavSpec)> {
+    // 16/24/32-bit integer and 32-bit float input, normalized to [-1.0, 1.0]
+    let (samples, spec) = wav_io::read_interleaved(filename)?;
+    info!("  Sample rate: {} Hz", spec.sample_rate);
+    info!("  Channels: {}", spec.channels);
+    info!("  Bits per sample: {}", spec.bits_per_sample);
 
-    Ok(samples)
+    Ok((mode.select(&samples, spec.channels as usize), spec))
 }
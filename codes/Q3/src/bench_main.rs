@@ -0,0 +1,112 @@
+use q3_time_domain_demodulation::{audio_reader, iir_filter};
+
+use dsp_core::error::{DspError, Result};
+use dsp_core::{logging, simd};
+use log::info;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const INPUT: &str = "../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav";
+const USAGE: &str = "Usage: q3_bench_kernels [--repeat N] [-q|-v]";
+
+fn main() {
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run() {
+        e.exit("Error");
+    }
+}
+
+fn run() -> Result<()> {
+    let mut repeat = 5usize;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--repeat" => {
+                let value = args.next().ok_or_else(|| DspError::missing_value("--repeat"))?;
+                repeat = value
+                    .parse()
+                    .ok()
+                    .filter(|&n: &usize| n > 0)
+                    .ok_or_else(|| DspError::invalid_value("--repeat", &value))?;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            flag if logging::is_verbosity_flag(flag) => {}
+            other => return Err(DspError::Argument(format!("unknown option '{}'\n{}", other, USAGE))),
+        }
+    }
+
+    let signal = audio_reader::read_wav(INPUT)?;
+    let (hp_b, hp_a, lp_b, lp_a) = iir_filter::read_q2_filters("../Q2/output/Q2_filter_coefficients.txt")?;
+    info!("Kernel timings on project.wav ({} samples, best of {})", signal.len(), repeat);
+    info!("{:<24} {:>12} {:>12} {:>8}", "kernel", "scalar", "vectorized", "speedup");
+
+    let highpassed = iir_filter::apply_filter(&signal, &hp_b, &hp_a);
+    let lowpassed = iir_filter::apply_filter(&signal, &lp_b, &lp_a);
+
+    compare("IIR high-pass", repeat, || direct_form_ii(&signal, &hp_b, &hp_a), || {
+        iir_filter::apply_filter(&signal, &hp_b, &hp_a)
+    });
+    compare("IIR low-pass", repeat, || direct_form_ii(&signal, &lp_b, &lp_a), || {
+        iir_filter::apply_filter(&signal, &lp_b, &lp_a)
+    });
+    compare("dot product", repeat, || signal.iter().zip(&highpassed).map(|(x, y)| x * y).sum::<f64>(), || {
+        simd::dot(&signal, &highpassed)
+    });
+    compare("energy", repeat, || signal.iter().map(|x| x * x).sum::<f64>(), || simd::energy(&signal));
+    compare("squared error", repeat, || {
+        highpassed.iter().zip(&lowpassed).map(|(x, y)| (x - y).powi(2)).sum::<f64>()
+    }, || simd::squared_error(&highpassed, &lowpassed));
+    Ok(())
+}
+
+/// Time `scalar` against `vectorized`, best of `repeat` runs each, and log the speedup
+fn compare<T>(name: &str, repeat: usize, scalar: impl Fn() -> T, vectorized: impl Fn() -> T) {
+    let best = |f: &dyn Fn() -> T| {
+        (0..repeat)
+            .map(|_| {
+                let start = Instant::now();
+                black_box(f());
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or(Duration::ZERO)
+    };
+    let (slow, fast) = (best(&scalar), best(&vectorized));
+    info!(
+        "{:<24} {:>10.3}ms {:>10.3}ms {:>7.2}x",
+        name,
+        slow.as_secs_f64() * 1e3,
+        fast.as_secs_f64() * 1e3,
+        slow.as_secs_f64() / fast.as_secs_f64().max(1e-12)
+    );
+}
+
+/// The previous Direct Form II loop with shifted input/output histories, as the baseline
+fn direct_form_ii(input: &[f64], b: &[f64], a: &[f64]) -> Vec<f64> {
+    let order = b.len().max(a.len());
+    let mut x_history = vec![0.0; order];
+    let mut y_history = vec![0.0; order];
+    input
+        .iter()
+        .map(|&x| {
+            for j in (1..order).rev() {
+                x_history[j] = x_history[j - 1];
+                y_history[j] = y_history[j - 1];
+            }
+            x_history[0] = x;
+            let mut y = 0.0;
+            for k in 0..b.len() {
+                y += b[k] * x_history[k];
+            }
+            for k in 1..a.len() {
+                y -= a[k] * y_history[k];
+            }
+            y /= a[0];
+            y_history[0] = y;
+            y
+        })
+        .collect()
+}
@@ -1,23 +1,257 @@
-use std::f64::consts::PI;
+use crate::fm::{self, FmStages};
+use crate::iir_filter::FilterCoefficients;
+use crate::pll::{self, CarrierRecovery, PllTrack};
+use crate::square_law;
+use crate::ssb::{self, Sideband, SsbStages};
+use dsp_core::demodulator::TimeDomainDemodulator;
+use dsp_core::error::{DspError, Result};
 
-/// Multiply signal with carrier cos(2*pi*f_d*t)
+pub use dsp_core::demodulator::{advance, guard_band_from_args, mix_with_carrier};
+
+/// Multiply signal with carrier 2*cos(2*pi*f_d*t)
+///
+/// The factor 2 compensates for the 1/2 of cos^2, so the baseband term comes out at unit gain.
 pub fn multiply_with_carrier(signal: &[f64], f_d: f64, f_s: f64) -> Vec<f64> {
-    let n = signal.len();
-    let mut output = vec![0.0; n];
-    
-    for i in 0..n {
-        let t = i as f64 / f_s;
-        let carrier = (2.0 * PI * f_d * t).cos();
-        // Multiply by 2 to compensate for the 1/2 factor from cos²(x) = (1 + cos(2x))/2
-        output[i] = signal[i] * carrier * 2.0;
+    mix_with_carrier(signal, f_d, 0.0, f_s)
+}
+
+/// Read `--lo-phase <degrees>` / `--lo-phase=<degrees>` from command-line arguments, in radians
+pub fn lo_phase_from_args(args: &[String]) -> Result<f64> {
+    let mut phase = 0.0;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if let Some(value) = arg.strip_prefix("--lo-phase=") {
+            value
+        } else if arg == "--lo-phase" {
+            iter.next().ok_or_else(|| DspError::missing_value("--lo-phase"))?
+        } else {
+            continue;
+        };
+        let degrees: f64 = value
+            .parse()
+            .map_err(|_| DspError::Argument(format!("Invalid LO phase '{}'", value)))?;
+        phase = degrees.to_radians();
+    }
+    Ok(phase)
+}
+
+/// Demodulation scheme
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DemodMode {
+    /// Product detector: high-pass, mixing, low-pass (AM and DSB-SC)
+    #[default]
+    Coherent,
+    /// Phasing-method single-sideband demodulator; no high-pass, the image sideband is
+    /// rejected by the I/Q combination instead
+    Ssb(Sideband),
+    /// Frequency discriminator on the analytic signal, with an optional de-emphasis time
+    /// constant (s)
+    Fm { de_emphasis: Option<f64> },
+    /// Non-coherent square-law detector, with or without the final square root
+    SquareLaw { sqrt: bool },
+}
+
+impl DemodMode {
+    /// Read `--mode <coherent|usb|lsb|fm|square-law|square-law-raw>` and `--deemphasis <µs|none>` (FM only, default
+    /// 50 µs) from command-line arguments
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut mode = DemodMode::Coherent;
+        let mut de_emphasis = Some(fm::DEFAULT_DE_EMPHASIS);
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if !matches!(name, "--mode" | "--deemphasis") {
+                continue;
+            }
+            let value = iter.next().ok_or_else(|| DspError::missing_value(name))?;
+            if name == "--deemphasis" {
+                de_emphasis = match value.as_str() {
+                    "none" | "off" => None,
+                    micros => Some(
+                        micros
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|&us| us > 0.0)
+                            .ok_or_else(|| DspError::Argument(format!("Invalid de-emphasis '{}' (µs or none)", value)))?
+                            / 1e6,
+                    ),
+                };
+                continue;
+            }
+            mode = match value.to_ascii_lowercase().as_str() {
+                "coherent" | "am" => DemodMode::Coherent,
+                "fm" => DemodMode::Fm { de_emphasis: None },
+                "square-law" => DemodMode::SquareLaw { sqrt: true },
+                "square-law-raw" => DemodMode::SquareLaw { sqrt: false },
+                other => DemodMode::Ssb(
+                    other
+                        .parse()
+                        .map_err(|_| {
+                            DspError::Argument(format!(
+                                "Unknown mode '{}' (expected coherent, usb, lsb, fm, square-law or square-law-raw)",
+                                value
+                            ))
+                        })?,
+                ),
+            };
+        }
+        if let DemodMode::Fm { de_emphasis: tau } = &mut mode {
+            *tau = de_emphasis;
+        }
+        Ok(mode)
+    }
+}
+
+impl std::fmt::Display for DemodMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DemodMode::Coherent => write!(f, "coherent"),
+            DemodMode::Ssb(sideband) => write!(f, "SSB ({})", sideband),
+            DemodMode::Fm { de_emphasis: Some(tau) } => write!(f, "FM ({:.0} µs de-emphasis)", tau * 1e6),
+            DemodMode::Fm { de_emphasis: None } => write!(f, "FM (no de-emphasis)"),
+            DemodMode::SquareLaw { sqrt: true } => write!(f, "square-law (with square root)"),
+            DemodMode::SquareLaw { sqrt: false } => write!(f, "square-law (no square root)"),
+        }
+    }
+}
+
+/// Intermediate and final signals of one demodulation pass
+pub struct DemodulationStages {
+    /// After the high-pass, x_h(t)
+    pub highpassed: Vec<f64>,
+    /// After local-oscillator mixing, x_b(t)
+    pub mixed: Vec<f64>,
+    /// After the low-pass, x_l(t)
+    pub output: Vec<f64>,
+}
+
+/// Coherent time-domain demodulator: high-pass, local-oscillator mixing, low-pass
+///
+/// Wraps the shared [`TimeDomainDemodulator`] chain with carrier recovery and the other
+/// demodulation schemes.
+#[derive(Debug, Clone)]
+pub struct Demodulator {
+    /// Filters, local oscillator frequency, phase and sample rate
+    pub chain: TimeDomainDemodulator,
+    /// Fixed oscillator or PLL carrier recovery
+    pub recovery: CarrierRecovery,
+    pub mode: DemodMode,
+}
+
+impl Demodulator {
+    /// Demodulator with a zero-phase local oscillator and Q2's filters
+    pub fn new(f_d: f64, f_s: f64, filters: FilterCoefficients) -> Self {
+        let (hp_b, hp_a, lp_b, lp_a) = filters;
+        Self::from_chain(TimeDomainDemodulator::new(f_d, f_s, (hp_b, hp_a), (lp_b, lp_a)))
+    }
+
+    /// Coherent demodulator with a fixed oscillator around an existing chain
+    pub fn from_chain(chain: TimeDomainDemodulator) -> Self {
+        Demodulator {
+            chain,
+            recovery: CarrierRecovery::Fixed,
+            mode: DemodMode::Coherent,
+        }
+    }
+
+    /// Same demodulator with the local oscillator at `phase` radians
+    pub fn with_phase(mut self, phase: f64) -> Self {
+        self.chain.phase = phase;
+        self
+    }
+
+    /// Same demodulator with the given local-oscillator source
+    pub fn with_recovery(self, recovery: CarrierRecovery) -> Self {
+        Demodulator { recovery, ..self }
+    }
+
+    /// Same demodulator using the given scheme
+    pub fn with_mode(self, mode: DemodMode) -> Self {
+        Demodulator { mode, ..self }
+    }
+
+    pub fn highpass(&self, signal: &[f64]) -> Vec<f64> {
+        self.chain.highpass(signal)
+    }
+
+    /// Mix with the local oscillator; with PLL recovery the oscillator follows the input carrier
+    pub fn mix(&self, signal: &[f64]) -> Vec<f64> {
+        match self.track_carrier(signal) {
+            Some(track) => track.mixed,
+            None => self.chain.mix(signal),
+        }
+    }
+
+    /// PLL record for a high-passed signal, or `None` with a fixed oscillator
+    pub fn track_carrier(&self, signal: &[f64]) -> Option<PllTrack> {
+        let chain = &self.chain;
+        match &self.recovery {
+            CarrierRecovery::Fixed => None,
+            CarrierRecovery::Pll(config) => Some(pll::track(signal, chain.f_d, chain.phase, chain.f_s, config)),
+        }
+    }
+
+    pub fn lowpass(&self, signal: &[f64]) -> Vec<f64> {
+        self.chain.lowpass(signal)
+    }
+
+    /// Run the coherent chain's three stages, keeping the intermediate signals
+    pub fn run(&self, signal: &[f64]) -> DemodulationStages {
+        let highpassed = self.highpass(signal);
+        let mixed = self.mix(&highpassed);
+        let output = self.lowpass(&mixed);
+        DemodulationStages {
+            highpassed,
+            mixed,
+            output,
+        }
+    }
+
+    /// SSB demodulation of the unfiltered signal with the fixed oscillator and Q2's low-pass
+    pub fn ssb(&self, signal: &[f64], sideband: Sideband) -> SsbStages {
+        ssb::demodulate(signal, self.chain.f_d, self.chain.phase, self.chain.f_s, sideband, |x| self.lowpass(x))
+    }
+
+    /// FM discrimination of the unfiltered signal against f_d, with Q2's low-pass
+    pub fn fm(&self, signal: &[f64], de_emphasis: Option<f64>) -> FmStages {
+        fm::demodulate(signal, self.chain.f_d, self.chain.f_s, de_emphasis, |x| self.lowpass(x))
+    }
+
+    /// Square-law detection of the unfiltered signal with Q2's low-pass
+    pub fn square_law(&self, signal: &[f64], sqrt: bool) -> Vec<f64> {
+        square_law::detect(signal, sqrt, |x| self.lowpass(x))
+    }
+
+    /// Demodulated output only, using the selected mode
+    pub fn demodulate(&self, signal: &[f64]) -> Vec<f64> {
+        match self.mode {
+            DemodMode::Coherent => self.run(signal).output,
+            DemodMode::Ssb(sideband) => self.ssb(signal, sideband).output,
+            DemodMode::Fm { de_emphasis } => self.fm(signal, de_emphasis).output,
+            DemodMode::SquareLaw { sqrt } => self.square_law(signal, sqrt),
+        }
+    }
+
+    /// Output RMS for each local-oscillator phase in `phases` (rad)
+    ///
+    /// The high-pass runs once; only mixing and the low-pass are repeated per phase.
+    pub fn phase_sweep(&self, signal: &[f64], phases: &[f64]) -> Vec<(f64, f64)> {
+        let highpassed = self.highpass(signal);
+        phases
+            .iter()
+            .map(|&phase| {
+                let output = self.lowpass(&mix_with_carrier(&highpassed, self.chain.f_d, phase, self.chain.f_s));
+                let rms = (output.iter().map(|x| x * x).sum::<f64>() / output.len().max(1) as f64).sqrt();
+                (phase, rms)
+            })
+            .collect()
     }
-    
-    output
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::PI;
 
     #[test]
     fn test_carrier_multiplication() {
@@ -29,7 +263,32 @@ mod tests {
         
         // Output should oscillate with carrier frequency
         assert_eq!(output.len(), 100);
-        // At t=0, cos(0) = 1.0
-        assert!((output[0] - 1.0).abs() < 1e-10);
+        // The mixer multiplies by 2*cos (compensating the 1/2 of cos^2), as it always has;
+        // the earlier expectation of 1.0 at t=0 predates that factor and never held
+        assert!((output[0] - 2.0).abs() < 1e-10);
+        // Mixing the carrier itself averages to unit gain: 2*cos^2 = 1 + cos(2*w*t)
+        let carrier: Vec<f64> = (0..100).map(|i| (2.0 * PI * f_d * i as f64 / f_s).cos()).collect();
+        let mixed = multiply_with_carrier(&carrier, f_d, f_s);
+        let mean = mixed.iter().sum::<f64>() / mixed.len() as f64;
+        assert!((mean - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_lo_phase_error_scales_output() {
+        // Unit baseband on the carrier: x(t) = cos(2 pi f_d t)
+        let (f_d, f_s) = (1000.0, 8000.0);
+        let signal: Vec<f64> = (0..8000).map(|i| (2.0 * PI * f_d * i as f64 / f_s).cos()).collect();
+
+        // Pass-through filters; averaging over whole carrier periods removes the 2 f_d term
+        let identity = (vec![1.0], vec![1.0], vec![1.0], vec![1.0]);
+        let demodulator = Demodulator::new(f_d, f_s, identity);
+        for phase in [0.0, PI / 3.0, PI / 2.0, PI] {
+            let output = demodulator.clone().with_phase(phase).demodulate(&signal);
+            let mean = output.iter().sum::<f64>() / output.len() as f64;
+            assert!((mean - phase.cos()).abs() < 1e-9, "phase {}: {}", phase, mean);
+        }
+
+        let sweep = demodulator.phase_sweep(&signal, &[0.0, PI / 2.0]);
+        assert!(sweep[0].1 > sweep[1].1);
     }
 }
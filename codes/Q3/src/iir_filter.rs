@@ -1,60 +1,77 @@
-/// Apply IIR filter using Direct Form II structure
-/// y[n] = sum(b[i] * x[n-i]) - sum(a[j] * y[n-j]) for j > 0
-pub fn apply_filter(input: &[f64], b: &[f64], a: &[f64]) -> Vec<f64> {
-    let n = input.len();
-    let mut output = vec![0.0; n];
+use dsp_core::error::{DspError, Result};
+use dsp_core::iir;
+
+// The filtering itself is shared with the other crates
+pub use dsp_core::iir::{apply_filter, apply_filter_zero_phase, lfilter, lfilter_with_state, lfilter_zi};
+
+/// (hp_b, hp_a, lp_b, lp_a) as written by Q2
+pub type FilterCoefficients = (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>);
+
+/// Parse the high-pass and low-pass coefficients from Q2's `Q2_filter_coefficients.txt`
+pub fn read_q2_filters(path: &str) -> Result<FilterCoefficients> {
+    let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
+
+    let mut hp_b = Vec::new();
+    let mut hp_a = Vec::new();
+    let mut lp_b = Vec::new();
+    let mut lp_a = Vec::new();
     
-    let order = b.len().max(a.len());
-    let mut x_history = vec![0.0; order]; // Input history
-    let mut y_history = vec![0.0; order]; // Output history
+    let mut current_section = "";
     
-    for i in 0..n {
-        // Shift histories
-        for j in (1..order).rev() {
-            x_history[j] = x_history[j - 1];
-            y_history[j] = y_history[j - 1];
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
         }
-        x_history[0] = input[i];
-        
-        // Calculate output using difference equation
-        let mut y = 0.0;
         
-        // Feedforward part: sum(b[k] * x[n-k])
-        for k in 0..b.len() {
-            y += b[k] * x_history[k];
+        if line.contains("High-pass Filter") {
+            current_section = "hp";
+        } else if line.contains("Low-pass Filter") {
+            current_section = "lp";
+        } else if line.starts_with("b[") {
+            if let Some(value_str) = line.split('=').nth(1) {
+                if let Ok(value) = value_str.trim().parse::<f64>() {
+                    match current_section {
+                        "hp" => hp_b.push(value),
+                        "lp" => lp_b.push(value),
+                        _ => {}
+                    }
+                }
+            }
+        } else if line.starts_with("a[") {
+            if let Some(value_str) = line.split('=').nth(1) {
+                if let Ok(value) = value_str.trim().parse::<f64>() {
+                    match current_section {
+                        "hp" => hp_a.push(value),
+                        "lp" => lp_a.push(value),
+                        _ => {}
+                    }
+                }
+            }
         }
-        
-        // Feedback part: -sum(a[k] * y[n-k]) for k > 0
-        for k in 1..a.len() {
-            y -= a[k] * y_history[k];
-        }
-        
-        // Normalize by a[0] (usually 1.0)
-        y /= a[0];
-        
-        y_history[0] = y;
-        output[i] = y;
     }
-    
-    output
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    if hp_b.is_empty() || hp_a.is_empty() || lp_b.is_empty() || lp_a.is_empty() {
+        return Err(DspError::Data(format!("{}: failed to parse filter coefficients", path)));
+    }
+    for (name, b, a) in [("high-pass", &hp_b, &hp_a), ("low-pass", &lp_b, &lp_a)] {
+        iir::validate(b, a).map_err(|e| e.context(format!("{}: {} filter", path, name)))?;
+    }
+
+    Ok((hp_b, hp_a, lp_b, lp_a))
+}
 
-    #[test]
-    fn test_simple_filter() {
-        // Simple moving average filter: y[n] = 0.5*x[n] + 0.5*x[n-1]
-        let b = vec![0.5, 0.5];
-        let a = vec![1.0];
-        let input = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        
-        let output = apply_filter(&input, &b, &a);
-        
-        // Expected: [0.5, 1.5, 2.5, 3.5, 4.5]
-        assert!((output[0] - 0.5).abs() < 1e-10);
-        assert!((output[1] - 1.5).abs() < 1e-10);
-        assert!((output[2] - 2.5).abs() < 1e-10);
+/// Parse the high-pass and low-pass cutoffs (Hz) from Q2's `Q2_filter_coefficients.txt`
+pub fn read_q2_cutoffs(path: &str) -> Result<(f64, f64)> {
+    let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
+    // Parse: "Cutoff Frequency: 3000.1823 Hz", the high-pass first
+    let cutoffs: Vec<f64> = content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Cutoff Frequency:"))
+        .filter_map(|value| value.split_whitespace().next()?.parse().ok())
+        .collect();
+    match cutoffs[..] {
+        [hp, lp] => Ok((hp, lp)),
+        _ => Err(DspError::Data(format!("{}: expected two cutoff frequencies, found {}", path, cutoffs.len()))),
     }
 }
@@ -0,0 +1,12 @@
+// Q3 time-domain demodulation modules
+
+pub mod audio_reader;
+pub mod audio_writer;
+pub mod demodulator;
+pub mod fm;
+pub mod iir_filter;
+pub mod live;
+pub mod pll;
+pub mod spectrum_analyzer;
+pub mod square_law;
+pub mod ssb;
@@ -0,0 +1,343 @@
+use crate::{audio_reader, spectrum_analyzer};
+use dsp_core::capture::{self, LiveSource};
+use dsp_core::channels::ChannelMode;
+use dsp_core::demodulator::TimeDomainDemodulator;
+use dsp_core::error::{DspError, Result};
+use log::info;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Most recent samples the live spectrum is computed from
+pub const SPECTRUM_SIZE: usize = 4096;
+
+/// Time between display updates when `--live-refresh` is not given
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(500);
+
+/// Capture length from an input device when `--live-seconds` is not given
+pub const DEFAULT_DEVICE_SECONDS: f64 = 30.0;
+
+/// Columns of the terminal spectrum
+const BARS: usize = 64;
+
+/// Range below the peak that the terminal spectrum shows (dB)
+const DISPLAY_RANGE_DB: f64 = 60.0;
+
+/// Where live audio comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveInput {
+    /// Input device whose name contains the given text, or the default one
+    Device(Option<String>),
+    /// WAV file replayed in real time, block by block
+    Replay(PathBuf),
+}
+
+/// Live demodulation of streaming audio (`--live`)
+///
+/// The input arrives in blocks and goes through the same high-pass, mixing and low-pass
+/// chain as a recording, with the filter states carried from block to block
+/// ([`dsp_core::demodulator::StreamingDemodulator`]). Blocks wait in a queue of `queue`
+/// entries; when processing falls behind, new blocks are dropped rather than queued, so the
+/// latency stays below about (`queue` + 1) block durations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveConfig {
+    pub input: LiveInput,
+    /// Samples per block
+    pub block: usize,
+    /// Blocks that may wait for processing
+    pub queue: usize,
+    pub refresh: Duration,
+    /// Stop after this much input (s); a replay otherwise stops at the end of its file
+    pub seconds: Option<f64>,
+    /// Refresh a PNG of the output spectrum instead of drawing it in the terminal
+    pub png: Option<PathBuf>,
+}
+
+/// What a live run produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveReport {
+    /// Demodulated signal of every processed block, in order
+    pub output: Vec<f64>,
+    pub sample_rate: f64,
+    pub blocks: usize,
+    /// Blocks lost because processing fell behind
+    pub dropped: usize,
+    /// Time from a block's arrival to the end of its processing
+    pub mean_latency: Duration,
+    pub max_latency: Duration,
+    /// Duration of one block: the wait for its samples, before it arrives
+    pub block_duration: Duration,
+}
+
+impl LiveConfig {
+    /// Read `--live`, `--live-device <name>`, `--live-input <wav>`, `--live-block <samples>`,
+    /// `--live-queue <blocks>`, `--live-seconds <s>`, `--live-refresh <ms>` and
+    /// `--live-png <path>` from command-line arguments
+    ///
+    /// Returns `None` unless one of them is given.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut live: Option<LiveConfig> = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let flag = arg.as_str();
+            if !flag.starts_with("--live") {
+                continue;
+            }
+            let config = live.get_or_insert_with(LiveConfig::default);
+            if flag == "--live" {
+                continue;
+            }
+            let value = iter.next().ok_or_else(|| DspError::missing_value(flag))?;
+            let count = || {
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| DspError::invalid_value(flag, value))
+            };
+            match flag {
+                "--live-device" => config.input = LiveInput::Device(Some(value.clone())),
+                "--live-input" => config.input = LiveInput::Replay(PathBuf::from(value)),
+                "--live-block" => config.block = count()?,
+                "--live-queue" => config.queue = count()?,
+                "--live-refresh" => config.refresh = Duration::from_millis(count()? as u64),
+                "--live-png" => config.png = Some(PathBuf::from(value)),
+                "--live-seconds" => {
+                    let seconds = value
+                        .parse()
+                        .ok()
+                        .filter(|&s: &f64| s > 0.0 && s.is_finite())
+                        .ok_or_else(|| DspError::invalid_value(flag, value))?;
+                    config.seconds = Some(seconds);
+                }
+                _ => return Err(DspError::Argument(format!("Unknown option '{}'", flag))),
+            }
+        }
+        Ok(live)
+    }
+
+    /// Open the input: the device capture starts right away, the replay reads its file first
+    pub fn open(&self) -> Result<LiveSource> {
+        match &self.input {
+            LiveInput::Device(name) => LiveSource::device(name.as_deref(), self.block, self.queue),
+            LiveInput::Replay(path) => {
+                let (mut channels, spec) = audio_reader::read_wav_channels(&path.to_string_lossy(), ChannelMode::Mix)?;
+                let name = path.display().to_string();
+                let samples = channels.remove(0);
+                Ok(LiveSource::replay(&name, samples, spec.sample_rate as f64, self.block, self.queue, true))
+            }
+        }
+    }
+
+    /// Input length after which [`run`] stops, if any (s)
+    pub fn limit(&self) -> Option<f64> {
+        match (&self.input, self.seconds) {
+            (_, Some(seconds)) => Some(seconds),
+            (LiveInput::Device(_), None) => Some(DEFAULT_DEVICE_SECONDS),
+            (LiveInput::Replay(_), None) => None,
+        }
+    }
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        LiveConfig {
+            input: LiveInput::Device(None),
+            block: capture::DEFAULT_BLOCK,
+            queue: capture::DEFAULT_QUEUE,
+            refresh: DEFAULT_REFRESH,
+            seconds: None,
+            png: None,
+        }
+    }
+}
+
+impl std::fmt::Display for LiveInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiveInput::Device(Some(name)) => write!(f, "input device matching '{}'", name),
+            LiveInput::Device(None) => write!(f, "default input device"),
+            LiveInput::Replay(path) => write!(f, "{} replayed in real time", path.display()),
+        }
+    }
+}
+
+/// Demodulate blocks from `source` with `chain` until the source ends or `config`'s limit
+/// is reached, refreshing the spectrum display every `config.refresh`
+pub fn run(config: &LiveConfig, source: &LiveSource, chain: &TimeDomainDemodulator) -> Result<LiveReport> {
+    let f_s = source.sample_rate;
+    let limit = config.limit().map(|seconds| (seconds * f_s) as usize);
+    let mut streaming = chain.streaming();
+    let mut display = Display::new(config.png.clone());
+
+    let (mut input, mut output): (Vec<f64>, Vec<f64>) = (Vec::new(), Vec::new());
+    let (mut blocks, mut total_latency, mut max_latency) = (0usize, Duration::ZERO, Duration::ZERO);
+    let mut next_refresh = Instant::now() + config.refresh;
+    while let Some(block) = source.next_block() {
+        output.extend(streaming.process(&block.samples));
+        input.extend(block.samples);
+        // Only the spectrum needs the input, and only its tail
+        if input.len() > 2 * SPECTRUM_SIZE {
+            input.drain(..input.len() - SPECTRUM_SIZE);
+        }
+        let latency = block.captured.elapsed();
+        blocks += 1;
+        total_latency += latency;
+        max_latency = max_latency.max(latency);
+
+        let done = limit.is_some_and(|limit| output.len() >= limit);
+        if done || Instant::now() >= next_refresh {
+            let status = format!(
+                "{:6.1} s  output {:6.1} dBFS  latency {:5.1} ms (max {:.1})  dropped {}",
+                output.len() as f64 / f_s,
+                level_db(tail(&output)),
+                latency.as_secs_f64() * 1e3,
+                max_latency.as_secs_f64() * 1e3,
+                source.dropped()
+            );
+            display.update(&status, tail(&input), tail(&output), f_s)?;
+            next_refresh = Instant::now() + config.refresh;
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok(LiveReport {
+        output,
+        sample_rate: f_s,
+        blocks,
+        dropped: source.dropped(),
+        mean_latency: total_latency / blocks.max(1) as u32,
+        max_latency,
+        block_duration: Duration::from_secs_f64(config.block as f64 / f_s),
+    })
+}
+
+/// The last [`SPECTRUM_SIZE`] samples of `signal`
+fn tail(signal: &[f64]) -> &[f64] {
+    &signal[signal.len().saturating_sub(SPECTRUM_SIZE)..]
+}
+
+/// RMS level relative to full scale (dB)
+fn level_db(signal: &[f64]) -> f64 {
+    let power = signal.iter().map(|x| x * x).sum::<f64>() / signal.len().max(1) as f64;
+    10.0 * power.max(1e-20).log10()
+}
+
+/// Magnitude spectrum of `signal` under a Hann window
+pub fn windowed_spectrum(signal: &[f64], f_s: f64) -> Vec<(f64, f64)> {
+    let n = signal.len();
+    let windowed: Vec<f64> = signal
+        .iter()
+        .enumerate()
+        .map(|(i, x)| x * (0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos()))
+        .collect();
+    spectrum_analyzer::compute_spectrum(&windowed, f_s)
+}
+
+/// One line of `bars` characters: the peak of the spectrum in each of `bars` equal
+/// frequency ranges up to `max_freq`, over the [`DISPLAY_RANGE_DB`] below the overall peak
+pub fn spectrum_bars(spectrum: &[(f64, f64)], max_freq: f64, bars: usize) -> String {
+    const LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let mut peaks = vec![0.0f64; bars];
+    for &(f, magnitude) in spectrum.iter().filter(|(f, _)| *f < max_freq) {
+        let column = ((f / max_freq) * bars as f64) as usize;
+        peaks[column.min(bars - 1)] = peaks[column.min(bars - 1)].max(magnitude);
+    }
+    let top = peaks.iter().fold(0.0f64, |m, &p| m.max(p));
+    peaks
+        .iter()
+        .map(|&p| {
+            if top <= 0.0 || p <= 0.0 {
+                return LEVELS[0];
+            }
+            let fraction = (1.0 + 20.0 * (p / top).log10() / DISPLAY_RANGE_DB).clamp(0.0, 1.0);
+            LEVELS[(fraction * (LEVELS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+/// Live spectrum: redrawn in place in the terminal, or as a PNG refreshed on disk
+struct Display {
+    png: Option<PathBuf>,
+    terminal: bool,
+    /// Lines drawn by the previous terminal update, to move back over
+    drawn: usize,
+}
+
+impl Display {
+    fn new(png: Option<PathBuf>) -> Self {
+        Display { terminal: png.is_none() && std::io::stderr().is_terminal(), png, drawn: 0 }
+    }
+
+    fn update(&mut self, status: &str, input: &[f64], output: &[f64], f_s: f64) -> Result<()> {
+        if let Some(png) = &self.png {
+            info!("{}", status);
+            let title = format!("Q3 Live Output Spectrum (last {} samples)", output.len());
+            return spectrum_analyzer::plot_spectrum_db(&windowed_spectrum(output, f_s), png, &title);
+        }
+        if !self.terminal {
+            info!("{}", status);
+            return Ok(());
+        }
+        // One frequency axis for both, so the carrier band moves down to baseband on screen
+        let nyquist = f_s / 2.0;
+        let lines = [
+            status.to_string(),
+            format!("  input  |{}|", spectrum_bars(&windowed_spectrum(input, f_s), nyquist, BARS)),
+            format!("  output |{}| 0-{:.0} Hz", spectrum_bars(&windowed_spectrum(output, f_s), nyquist, BARS), nyquist),
+        ];
+        let mut stderr = std::io::stderr().lock();
+        if self.drawn > 0 {
+            let _ = write!(stderr, "\x1b[{}A", self.drawn);
+        }
+        for line in &lines {
+            let _ = writeln!(stderr, "\x1b[2K{}", line);
+        }
+        self.drawn = lines.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_live_run_matches_offline_demodulation() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(LiveConfig::from_args(&args("--play")).unwrap(), None);
+        let config = LiveConfig::from_args(&args("--live --live-block 500 --live-seconds 0.5")).unwrap().unwrap();
+        assert_eq!((config.block, config.seconds), (500, Some(0.5)));
+        assert_eq!(config.input, LiveInput::Device(None));
+        assert!(LiveConfig::from_args(&args("--live-block 0")).is_err());
+        assert!(LiveConfig::from_args(&args("--live-typo 1")).is_err());
+
+        // A 300 Hz tone on a 2 kHz carrier, fed in blocks without real-time pacing
+        let (f_d, f_b, f_s) = (2000.0, 800.0, 8000.0);
+        let x: Vec<f64> = (0..8000)
+            .map(|n| {
+                let t = n as f64 / f_s;
+                (2.0 * PI * 300.0 * t).cos() * (2.0 * PI * f_d * t).cos()
+            })
+            .collect();
+        let chain = TimeDomainDemodulator::butterworth(6, f_d, f_b, f_s);
+        let source = LiveSource::replay("tone", x.clone(), f_s, config.block, config.queue, false);
+        let png = std::env::temp_dir().join(format!("q3_live_{}.png", std::process::id()));
+        let config = LiveConfig { png: Some(png.clone()), refresh: Duration::from_secs(60), ..config };
+        let report = run(&config, &source, &chain).unwrap();
+        std::fs::remove_file(&png).unwrap();
+        assert_eq!((report.blocks, report.dropped), (8, 0));
+        let offline = chain.demodulate(&x[..4000]);
+        for (live, offline) in report.output.iter().zip(&offline) {
+            assert!((live - offline).abs() < 1e-12);
+        }
+
+        // The 300 Hz output fills the bar that covers 300 Hz
+        let bars = spectrum_bars(&windowed_spectrum(&report.output[2000..], f_s), 1000.0, 10);
+        assert_eq!(bars.chars().count(), 10);
+        assert_eq!(bars.chars().nth(3), Some('█'));
+    }
+}
@@ -1,112 +1,832 @@
-mod audio_reader;
-mod iir_filter;
-mod demodulator;
-mod spectrum_analyzer;
-mod audio_writer;
+use dsp_core::agc::{self, AgcConfig};
+use dsp_core::butterworth::ButterworthFilter;
+use dsp_core::batch::{self, BatchSummary};
+use dsp_core::channels::ChannelMode;
+use dsp_core::clipping::{ClippingReport, Headroom};
+use dsp_core::demodulator::{ProbeTone, TimeDomainDemodulator, BATCH_FILTER_ORDER};
+use dsp_core::error::{DspError, Result};
+use dsp_core::fixed_point::{self, FixedPointComparison, QFormat};
+use dsp_core::logging;
+use dsp_core::loudness::Levels;
+use dsp_core::html_plot::{self, InteractiveChart, InteractivePage};
+use dsp_core::mat;
+use dsp_core::npy::{self, Npz};
+use dsp_core::parallel::{self, Task};
+use dsp_core::playback;
+use dsp_core::plot_output::{PlotOutput, PlotTarget};
+use dsp_core::resample::{self, decimate, resample};
+use dsp_core::reproducible::Reproducibility;
+use dsp_core::results::StageResults;
+use dsp_core::run_dir::{self, RunDirectory};
+use dsp_core::row;
+use dsp_core::simd;
+use dsp_core::watch::Watch;
+use dsp_core::wav_io::WavWriteOptions;
+use log::{error, info, warn};
+use q3_time_domain_demodulation::demodulator::{self, DemodMode, Demodulator};
+use q3_time_domain_demodulation::live::{self, LiveConfig};
+use q3_time_domain_demodulation::pll::{CarrierRecovery, PllTrack};
+use q3_time_domain_demodulation::{audio_reader, audio_writer, iir_filter, spectrum_analyzer};
+use std::path::Path;
 
-use std::f64::consts::PI;
+/// The recording to demodulate
+const INPUT: &str = "../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav";
 
 fn main() {
-    println!("Q3: Time-Domain Demodulation");
-    println!("================================");
-
-    // Step 1: Read Q1 results to get f_d, f_s, f_B
-    println!("\n[Step 1] Reading Q1 results...");
-    let (f_d, f_s, f_b) = match read_q1_results() {
-        Ok(params) => params,
-        Err(e) => {
-            eprintln!("Error reading Q1 results: {}", e);
-            return;
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run_or_watch() {
+        e.exit("Error");
+    }
+}
+
+/// Run once, or with `--watch` again whenever the recording, Q1's results or Q2's
+/// coefficients change (every WAV of the folder and its Q1 results with `--batch`)
+fn run_or_watch() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(watch) = Watch::from_args(&args)? else {
+        return run();
+    };
+    let inputs = match batch::dir_from_args(&args)? {
+        Some(dir) => {
+            let files = batch::wav_files(&dir)?;
+            let results = files.iter().map(|file| batch::file_dir("../Q1/output", file).join("Q1_results.txt"));
+            results.chain(files.iter().cloned()).collect()
         }
+        None => vec![
+            run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?.into(),
+            run_dir::upstream_path(&args, "../Q2/output", "Q2_filter_coefficients.txt")?.into(),
+            INPUT.into(),
+        ],
     };
-    println!("  f_d = {:.4} Hz", f_d);
-    println!("  f_s = {:.4} Hz", f_s);
-    println!("  f_B = {:.4} Hz", f_b);
+    watch.run(&inputs, run)
+}
+
+fn run() -> Result<()> {
+    info!("Q3: Time-Domain Demodulation");
+    info!("================================");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let channel_mode = ChannelMode::from_args(&args)?;
+    if let Some(dir) = batch::dir_from_args(&args)? {
+        return run_batch(&dir, &args, channel_mode);
+    }
+    if let Some(live) = LiveConfig::from_args(&args)? {
+        return run_live(&live, &args);
+    }
+    // `replot` subcommand: figures only, from the arrays of an earlier `--npz` run
+    if args.first().map(String::as_str) == Some("replot") {
+        return run_replot(&args);
+    }
+    let run_directory = RunDirectory::from_args(&args, "output")?;
+
+    // Step 1: Read Q1 results to get f_d, f_s, f_B (`--from-run <tag>` reads archived runs)
+    info!("\n[Step 1] Reading Q1 results...");
+    let q1_results_path = run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?;
+    let (f_d, f_s, f_b) = read_q1_results(&q1_results_path)?;
+    info!("  f_d = {:.4} Hz", f_d);
+    info!("  f_s = {:.4} Hz", f_s);
+    info!("  f_B = {:.4} Hz", f_b);
 
     // Step 2: Read Q2 filter coefficients
-    println!("\n[Step 2] Reading Q2 filter coefficients...");
-    let (hp_b, hp_a, lp_b, lp_a) = match read_q2_filters() {
-        Ok(filters) => filters,
-        Err(e) => {
-            eprintln!("Error reading Q2 filters: {}", e);
-            return;
-        }
-    };
-    println!("  High-pass filter: {} b coefficients, {} a coefficients", hp_b.len(), hp_a.len());
-    println!("  Low-pass filter: {} b coefficients, {} a coefficients", lp_b.len(), lp_a.len());
+    info!("\n[Step 2] Reading Q2 filter coefficients...");
+    let q2_filters_path = run_dir::upstream_path(&args, "../Q2/output", "Q2_filter_coefficients.txt")?;
+    let filters = iir_filter::read_q2_filters(&q2_filters_path)?;
+    info!("  High-pass filter: {} b coefficients, {} a coefficients", filters.0.len(), filters.1.len());
+    info!("  Low-pass filter: {} b coefficients, {} a coefficients", filters.2.len(), filters.3.len());
+    let lo_phase = demodulator::lo_phase_from_args(&args)?;
+    let recovery = CarrierRecovery::from_args(&args)?;
+    let mode = DemodMode::from_args(&args)?;
+    let demodulator = Demodulator::new(f_d, f_s, filters)
+        .with_phase(lo_phase)
+        .with_recovery(recovery)
+        .with_mode(mode);
 
     // Step 3: Read audio signal
-    println!("\n[Step 3] Reading audio signal...");
-    let audio_samples = match audio_reader::read_wav("../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav") {
-        Ok(samples) => samples,
-        Err(e) => {
-            eprintln!("Error reading audio: {}", e);
-            return;
-        }
-    };
-    println!("  Number of samples: {}", audio_samples.len());
+    info!("\n[Step 3] Reading audio signal...");
+    let input_path = INPUT;
+    let (channels, source_spec) = audio_reader::read_wav_channels(input_path, channel_mode)?;
+    info!("  Channel mode: {} ({} channel(s) processed)", channel_mode, channels.len());
+    let input_clipping = ClippingReport::detect(&channels);
+    if input_clipping.runs > 0 {
+        warn!(
+            "  Warning: the input looks clipped: {} sample(s) in {} flat top(s) at {:.2} dBFS, longest {} samples",
+            input_clipping.clipped_samples, input_clipping.runs, input_clipping.peak_dbfs, input_clipping.longest_run
+        );
+    }
+    let output_options = WavWriteOptions::from_args(&args, &source_spec)?;
+    let output_rate = resample::output_rate_from_args(&args)?;
+    let decimation = resample::decimation_from_args(&args)?;
+    let agc = AgcConfig::from_args(&args)?;
+    let plots = PlotOutput::from_args(&args)?;
+    // The first selected channel drives the step-by-step report and spectra
+    let audio_samples = &channels[0];
+    info!("  Number of samples: {}", audio_samples.len());
     let max_orig = audio_samples.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
-    println!("  Signal max: {:.6}", max_orig);
+    info!("  Signal max: {:.6}", max_orig);
 
     // Step 4: Apply high-pass filter
-    println!("\n[Step 4] Applying high-pass filter...");
-    let x_h = iir_filter::apply_filter(&audio_samples, &hp_b, &hp_a);
-    println!("  Output samples: {}", x_h.len());
+    info!("\n[Step 4] Applying high-pass filter...");
+    let x_h = match mode {
+        DemodMode::Coherent => demodulator.highpass(audio_samples),
+        DemodMode::Ssb(_) => {
+            info!("  Skipped in {} mode: the I/Q combination rejects the other sideband", mode);
+            audio_samples.clone()
+        }
+        DemodMode::Fm { .. } => {
+            info!("  Skipped in {} mode: the discriminator needs both sides of the carrier", mode);
+            audio_samples.clone()
+        }
+        DemodMode::SquareLaw { .. } => {
+            info!("  Skipped in {} mode: the envelope needs both sidebands", mode);
+            audio_samples.clone()
+        }
+    };
+    info!("  Output samples: {}", x_h.len());
     let max_xh = x_h.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
-    println!("  Signal max: {:.6}", max_xh);
+    info!("  Signal max: {:.6}", max_xh);
 
     // Step 5: Generate carrier and multiply
-    println!("\n[Step 5] Multiplying with carrier signal (f_d = {:.4} Hz)...", f_d);
-    let x_b = demodulator::multiply_with_carrier(&x_h, f_d, f_s);
-    println!("  Output samples: {}", x_b.len());
+    info!(
+        "\n[Step 5] Multiplying with carrier signal (f_d = {:.4} Hz, phase = {:.1} deg, {})...",
+        f_d,
+        lo_phase.to_degrees(),
+        recovery
+    );
+    let x_b = match demodulator.track_carrier(&x_h) {
+        Some(track) => {
+            // Judge the loop after one second of settling
+            let settle = (f_s as usize).min(x_h.len() / 2);
+            info!(
+                "  PLL: mean frequency {:.4} Hz (drift {:+.4} Hz from f_d), locked {:.1}% of the time",
+                track.mean_frequency(settle),
+                track.mean_frequency(settle) - f_d,
+                100.0 * track.locked_fraction(settle)
+            );
+            std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+            save_pll_track(&track, f_s, "output/Q3_pll_track.txt")?;
+            track.mixed
+        }
+        None => demodulator.mix(&x_h),
+    };
+    info!("  Output samples: {}", x_b.len());
     let max_xb = x_b.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
-    println!("  Signal max: {:.6}", max_xb);
+    info!("  Signal max: {:.6}", max_xb);
 
     // Step 6: Apply low-pass filter
-    println!("\n[Step 6] Applying low-pass filter...");
-    let x_l = iir_filter::apply_filter(&x_b, &lp_b, &lp_a);
-    println!("  Output samples: {}", x_l.len());
+    info!("\n[Step 6] Applying low-pass filter...");
+    let (x_l, ssb_image) = match mode {
+        DemodMode::Coherent => (demodulator.lowpass(&x_b), None),
+        DemodMode::Ssb(sideband) => {
+            let stages = demodulator.ssb(&x_h, sideband);
+            info!(
+                "  {} output, {} image rejected: output/image power = {:.2} dB",
+                sideband,
+                sideband.image(),
+                stages.image_rejection_db()
+            );
+            (stages.output, Some((sideband, stages.image)))
+        }
+        DemodMode::Fm { de_emphasis } => {
+            let stages = demodulator.fm(&x_h, de_emphasis);
+            info!(
+                "  {}: mean instantaneous frequency {:.4} Hz ({:+.4} Hz from f_d), peak deviation {:.1} Hz",
+                mode,
+                stages.mean_frequency(),
+                stages.mean_frequency() - f_d,
+                stages.peak_deviation()
+            );
+            (stages.output, None)
+        }
+        DemodMode::SquareLaw { sqrt } => {
+            info!("  {}: no local oscillator, carrier multiplication output unused", mode);
+            (demodulator.square_law(&x_h, sqrt), None)
+        }
+    };
+    info!("  Output samples: {}", x_l.len());
+
+    // `--compensate-delay`: advance the output by the chain's mean group delay in the
+    // baseband, so it lines up with the input and with Q4's zero-delay output
+    let delay = match (args.iter().any(|a| a == "--compensate-delay"), mode) {
+        (false, _) => None,
+        (true, DemodMode::Coherent) => {
+            let delay = demodulator.chain.passband_group_delay(f_b);
+            info!("  Advancing the output by the passband group delay: {:.2} samples ({:.3} ms)", delay, delay / f_s * 1e3);
+            Some(delay.round() as usize)
+        }
+        (true, _) => {
+            warn!("  --compensate-delay applies to the coherent chain only; {} output left as is", mode);
+            None
+        }
+    };
+    let x_l = match delay {
+        Some(samples) => demodulator::advance(&x_l, samples),
+        None => x_l,
+    };
     
     // Debug: Check signal statistics
     let max_val = x_l.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
     let mean_val = x_l.iter().sum::<f64>() / x_l.len() as f64;
-    println!("  Signal range: max = {:.6}, mean = {:.6}", max_val, mean_val);
+    info!("  Signal range: max = {:.6}, mean = {:.6}", max_val, mean_val);
+
+    // Audition input, band-shifted intermediate and output with --play
+    if playback::requested(&args) {
+        info!("\n[Playback]");
+        for (label, signal) in [
+            ("misdemodulated input x(t)", audio_samples.as_slice()),
+            ("after carrier multiplication x_b(t)", x_b.as_slice()),
+            ("demodulated output x_l(t)", x_l.as_slice()),
+        ] {
+            if let Err(e) = playback::play(label, signal, f_s) {
+                warn!("  Playback unavailable: {}", e);
+                break;
+            }
+        }
+    }
 
     // Step 7: Spectrum analysis
-    println!("\n[Step 7] Performing spectrum analysis...");
-    let original_spectrum = spectrum_analyzer::compute_spectrum(&audio_samples, f_s);
-    let xh_spectrum = spectrum_analyzer::compute_spectrum(&x_h, f_s);
-    let xb_spectrum = spectrum_analyzer::compute_spectrum(&x_b, f_s);
-    let xl_spectrum = spectrum_analyzer::compute_spectrum(&x_l, f_s);
+    info!("\n[Step 7] Performing spectrum analysis...");
+    let spectrum = |signal: &[f64]| spectrum_analyzer::compute_spectrum(signal, f_s);
+    let ((original_spectrum, xh_spectrum), (xb_spectrum, xl_spectrum)) = rayon::join(
+        || rayon::join(|| spectrum(audio_samples), || spectrum(&x_h)),
+        || rayon::join(|| spectrum(&x_b), || spectrum(&x_l)),
+    );
 
     // Step 8: Create output directory
-    std::fs::create_dir_all("output").expect("Failed to create output directory");
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
 
     // Step 9: Plot spectra
-    println!("\n[Step 8] Plotting spectra...");
-    spectrum_analyzer::plot_spectrum(&original_spectrum, "output/Q3_original_spectrum.png", "Original Signal X(f)");
-    spectrum_analyzer::plot_spectrum(&xh_spectrum, "output/Q3_xh_spectrum.png", "After High-Pass X_h(f)");
-    spectrum_analyzer::plot_spectrum(&xb_spectrum, "output/Q3_xb_spectrum.png", "After Multiplication X_b(f)");
-    spectrum_analyzer::plot_spectrum(&xl_spectrum, "output/Q3_xl_spectrum.png", "After Low-Pass X_l(f) - Demodulated");
+    info!("\n[Step 8] Plotting spectra...");
+    // The wanted sideband is X_l(f); the same baseband from the opposite side shows what was rejected
+    let image_spectrum = ssb_image.as_ref().map(|(_, image)| spectrum(image));
+    // The figures are independent, so they are rendered in parallel
+    let mut figures = stage_figures(
+        [audio_samples, &x_h, &x_b, &x_l],
+        [&original_spectrum, &xh_spectrum, &xb_spectrum, &xl_spectrum],
+        f_s,
+        (f_d, f_b),
+        &plots,
+    );
+    if let (Some((sideband, _)), Some(image_spectrum)) = (&ssb_image, &image_spectrum) {
+        let output_spectrum = &xl_spectrum;
+        let output_target = plots.target("output/Q3_ssb_output_spectrum_db.png");
+        let output_title = format!("SSB Output, {} Selected (dB)", sideband);
+        figures.push(Box::new(move || spectrum_analyzer::plot_spectrum_db(output_spectrum, output_target, &output_title)));
+        let image_target = plots.target("output/Q3_ssb_image_spectrum_db.png");
+        let image_title = format!("Rejected {} Image (dB)", sideband.image());
+        figures.push(Box::new(move || spectrum_analyzer::plot_spectrum_db(image_spectrum, image_target, &image_title)));
+    }
+    parallel::run_all(figures)?;
+    // Optional zoomable copies for inspecting the sideband peaks
+    if html_plot::requested(&args) {
+        save_interactive_plots(
+            "Q3: Time-Domain Demodulation",
+            &[
+                ("Original Signal X(f)", &original_spectrum),
+                ("After High-Pass X_h(f)", &xh_spectrum),
+                ("After Multiplication X_b(f)", &xb_spectrum),
+                ("After Low-Pass X_l(f)", &xl_spectrum),
+            ],
+            &[("input x(t)", audio_samples), ("demodulated x_l(t)", &x_l)],
+            f_s,
+            (f_d, f_b),
+            "output/Q3_interactive.html",
+        );
+    }
+    // Optional raw arrays for cross-checking each stage in NumPy or MATLAB
+    if npy::requested(&args) || mat::requested(&args) {
+        let n = audio_samples.len();
+        let frequencies: Vec<f64> = (0..n).map(|k| k as f64 * f_s / n as f64).collect();
+        let arrays = Npz::new()
+            .array("f_s", vec![f_s])
+            .array("f_d", vec![f_d])
+            .array("f_b", vec![f_b])
+            .array("x", audio_samples.as_slice())
+            .array("x_h", x_h.as_slice())
+            .array("x_b", x_b.as_slice())
+            .array("x_l", x_l.as_slice())
+            .array("frequencies", frequencies)
+            .array("X", spectrum_analyzer::fft(audio_samples))
+            .array("X_h", spectrum_analyzer::fft(&x_h))
+            .array("X_b", spectrum_analyzer::fft(&x_b))
+            .array("X_l", spectrum_analyzer::fft(&x_l));
+        let mut saved = Vec::new();
+        if npy::requested(&args) {
+            saved.push(("output/Q3_signals.npz", arrays.save("output/Q3_signals.npz")));
+        }
+        if mat::requested(&args) {
+            saved.push(("output/Q3_signals.mat", mat::save("output/Q3_signals.mat", &arrays.arrays)));
+        }
+        for (filename, result) in saved {
+            match result {
+                Ok(()) => info!("  Saved to: {}", filename),
+                Err(e) => error!("  Error saving arrays: {}", e),
+            }
+        }
+    }
 
     // Step 10: Save demodulated audio
-    println!("\n[Step 9] Saving demodulated audio...");
-    match audio_writer::write_wav("output/Q3_demodulated.wav", &x_l, f_s as u32) {
-        Ok(_) => println!("  Saved to: output/Q3_demodulated.wav"),
-        Err(e) => eprintln!("  Error saving audio: {}", e),
+    info!("\n[Step 9] Saving demodulated audio...");
+    // Remaining channels go through the same chain (high-pass -> carrier -> low-pass, or SSB)
+    let mut demodulated = vec![x_l.clone()];
+    demodulated.extend(parallel::map_signals(&channels[1..], |samples| {
+        let output = demodulator.demodulate(samples);
+        match delay {
+            Some(delay) => demodulator::advance(&output, delay),
+            None => output,
+        }
+    }));
+    // The baseband only occupies 0-f_B, so --decimate / --output-rate can shrink the file
+    // Optional AGC evens out fades in the written audio; the analysis uses the raw output
+    let demodulated = match agc {
+        Some(config) => apply_agc(demodulated, f_s, &config, plots.target("output/Q3_agc_envelope.png"))?,
+        None => demodulated,
+    };
+    let (demodulated, decimated_fs) = decimate_output(demodulated, decimation, f_s, f_b);
+    let output_fs = output_rate.unwrap_or(decimated_fs);
+    let demodulated: Vec<Vec<f64>> = demodulated.iter().map(|c| resample(c, decimated_fs, output_fs)).collect();
+    // Mixing with 2 cos(2π f_d t) doubles the baseband, so a loud input can end up over full scale
+    let headroom = Headroom::measure(&demodulated, &output_options);
+    if headroom.over_full_scale > 0 {
+        warn!(
+            "  Warning: {} output sample(s) are beyond full scale (peak {:+.2} dBFS)",
+            headroom.over_full_scale, headroom.peak_dbfs
+        );
+    }
+    if headroom.clipped_on_write > 0 {
+        warn!(
+            "  Warning: {} sample(s) will be clipped in the WAV file; --normalize peak:<dB> scales the output instead",
+            headroom.clipped_on_write
+        );
+    } else {
+        info!("  Output peak {:+.2} dBFS, {:.2} dB headroom in the file", headroom.peak_dbfs, headroom.headroom_db());
+    }
+    match audio_writer::write_wav_channels("output/Q3_demodulated.wav", &demodulated, output_fs as u32, &output_options) {
+        Ok(_) => info!("  Saved to: output/Q3_demodulated.wav ({} Hz, {})", output_fs, output_options),
+        Err(e) => error!("  Error saving audio: {}", e),
+    }
+
+    // Optional: output amplitude against local-oscillator phase error
+    if args.iter().any(|a| a == "--phase-sweep") {
+        info!("\n[Phase sweep] Demodulating with LO phase 0-180 deg...");
+        let phases: Vec<f64> = (0..=12).map(|k| (15.0 * k as f64).to_radians()).collect();
+        let sweep = demodulator.phase_sweep(audio_samples, &phases);
+        save_phase_sweep(&sweep, "output/Q3_phase_sweep.txt")?;
+    }
+
+    // Optional: the Q2 filters as Q15/Q31 biquad cascades, measured against f64
+    if args.iter().any(|a| a == "--fixed-point") {
+        info!("\n[Fixed point] Q2 filters as Q15/Q31 biquad cascades...");
+        let (hp_cutoff, lp_cutoff) = iir_filter::read_q2_cutoffs(&q2_filters_path)?;
+        let (hp, lp) = (&demodulator.chain.hp, &demodulator.chain.lp);
+        // The same designs as Q2, in second-order sections
+        let hp_sos = ButterworthFilter::highpass(hp.0.len() - 1, hp_cutoff, f_s).sos();
+        let lp_sos = ButterworthFilter::lowpass(lp.0.len() - 1, lp_cutoff, f_s).sos();
+        let mut rows = Vec::new();
+        for (name, sos, input) in [("high-pass", &hp_sos, audio_samples.as_slice()), ("low-pass", &lp_sos, &x_b[..])] {
+            for format in [QFormat::Q15, QFormat::Q31] {
+                rows.push((name, fixed_point::compare(sos, input, format)?));
+            }
+        }
+        save_fixed_point(&rows, "output/Q3_fixed_point.txt")?;
+    }
+
+    // Optional: end-to-end gain of the chain for probe tones at the key frequencies
+    if args.iter().any(|a| a == "--verify-chain") {
+        info!("\n[Chain verification] Probe tones through high-pass, mixing and low-pass...");
+        let points = [
+            ("|f_d - f_B|", (f_d - f_b).abs()),
+            ("f_d", f_d),
+            ("f_d + f_B/2", f_d + f_b / 2.0),
+            ("f_d + f_B", f_d + f_b),
+            ("2 f_d", 2.0 * f_d),
+        ];
+        let probes: Vec<(&str, ProbeTone)> =
+            points.iter().map(|&(label, f)| (label, demodulator.chain.probe(f, f_s as usize))).collect();
+        save_chain_verification(&probes, f_d, "output/Q3_chain_verification.txt")?;
     }
 
     // Step 11: Save analysis results
-    println!("\n[Step 10] Saving analysis results...");
-    save_results(&original_spectrum, &xh_spectrum, &xb_spectrum, &xl_spectrum, f_d, f_s);
+    info!("\n[Step 10] Saving analysis results...");
+    let reproducibility = Reproducibility::from_args(&args)?;
+    // Loudness of what went in and what was written, e.g. to match levels before listening
+    let levels = [
+        ("input", Levels::measure(&channels, f_s)?),
+        ("demodulated", Levels::measure(&demodulated, output_fs)?),
+    ];
+    for (signal, level) in &levels {
+        info!("  Level of the {}: {}", signal, level);
+    }
+    save_results(
+        &original_spectrum,
+        &xh_spectrum,
+        &xb_spectrum,
+        &xl_spectrum,
+        &levels,
+        (&input_clipping, &headroom),
+        f_d,
+        f_s,
+        f_b,
+        &reproducibility,
+    )?;
+
+    info!("\nQ3 Time-Domain Demodulation completed successfully!");
+    info!("Output files saved in: codes/Q3/output/");
+    if let Some(run_directory) = run_directory {
+        let dir = run_directory.finish("Q3", &[&q1_results_path, &q2_filters_path, input_path], &args)?;
+        info!("Run archived to: codes/Q3/{} (output/latest points to it)", dir.display());
+    }
+    Ok(())
+}
+
+/// The stage figures, to render with [`parallel::run_all`]: the spectrum of each stage, the
+/// spectrogram before and after, and the stage waveforms
+///
+/// `signals` are x(t), x_h(t), x_b(t) and x_l(t), `spectra` their magnitude spectra. The
+/// step-by-step run adds the SSB figures to these; `replot` draws them alone.
+fn stage_figures<'a>(
+    [x, x_h, x_b, x_l]: [&'a [f64]; 4],
+    spectra: [&'a [(f64, f64)]; 4],
+    f_s: f64,
+    (f_d, f_b): (f64, f64),
+    plots: &PlotOutput,
+) -> Vec<Task<'a>> {
+    let mut figures: Vec<Task> = Vec::new();
+    for (spectrum, (filename, title)) in spectra.into_iter().zip([
+        ("output/Q3_original_spectrum.png", "Original Signal X(f)"),
+        ("output/Q3_xh_spectrum.png", "After High-Pass X_h(f)"),
+        ("output/Q3_xb_spectrum.png", "After Multiplication X_b(f)"),
+        ("output/Q3_xl_spectrum.png", "After Low-Pass X_l(f) - Demodulated"),
+    ]) {
+        let target = plots.target(filename);
+        figures.push(Box::new(move || spectrum_analyzer::plot_spectrum(spectrum, target, title)));
+    }
+    // Time-frequency view: the image band around f_d disappears, the 0-f_B baseband stays
+    let spectrogram_target = plots.target("output/Q3_spectrogram_comparison.png");
+    figures.push(Box::new(move || {
+        spectrum_analyzer::plot_spectrogram_comparison(
+            x,
+            x_l,
+            f_s,
+            &[f_d, f_b],
+            spectrogram_target,
+            "Spectrogram Before/After Demodulation (dB)",
+        )
+    }));
+    // The same stages in time: carrier cycles, the double-frequency term after multiplication
+    // and the recovered baseband, zoomed in around the output's peak
+    let waveforms_target = plots.target("output/Q3_stage_waveforms.png");
+    let zoom = spectrum_analyzer::peak_window(x_l, f_s, spectrum_analyzer::WAVEFORM_ZOOM_SECONDS);
+    figures.push(Box::new(move || {
+        spectrum_analyzer::plot_stage_waveforms(
+            &[
+                ("Original Signal x(t)", x),
+                ("After High-Pass x_h(t)", x_h),
+                ("After Multiplication x_b(t)", x_b),
+                ("After Low-Pass x_l(t) - Demodulated", x_l),
+            ],
+            f_s,
+            zoom,
+            waveforms_target,
+            "Waveform at Each Demodulation Stage",
+        )
+    }));
+    figures
+}
+
+/// Arrays `replot` reads unless a file follows the subcommand
+const REPLOT_SOURCE: &str = "output/Q3_signals.npz";
+
+/// `replot` subcommand: redraw the stage figures from the arrays an earlier run saved with
+/// `--npz` (or `--mat`), under the current `--plot-*` and `--db-range` options, without
+/// reading the inputs or filtering again
+fn run_replot(args: &[String]) -> Result<()> {
+    let source = match args.get(1) {
+        Some(path) if !path.starts_with('-') => path.as_str(),
+        _ => REPLOT_SOURCE,
+    };
+    info!("\n[Replot] Redrawing the stage figures from {}...", source);
+    let arrays = Npz::load_any(source)?;
+    let (f_s, f_d, f_b) = (arrays.scalar("f_s")?, arrays.scalar("f_d")?, arrays.scalar("f_b")?);
+    info!("  f_d = {:.4} Hz, f_s = {:.4} Hz, f_B = {:.4} Hz", f_d, f_s, f_b);
+    let signals = [arrays.real("x")?, arrays.real("x_h")?, arrays.real("x_b")?, arrays.real("x_l")?];
+    let spectra: Vec<Vec<(f64, f64)>> = signals.iter().map(|x| spectrum_analyzer::compute_spectrum(x, f_s)).collect();
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    parallel::run_all(stage_figures(
+        signals,
+        [&spectra[0], &spectra[1], &spectra[2], &spectra[3]],
+        f_s,
+        (f_d, f_b),
+        &PlotOutput::from_args(args)?,
+    ))
+}
+
+/// Write zoomable copies of the stage spectra (f_d and f_B marked) and of the input and
+/// output waveforms to one self-contained HTML page
+/// `--batch <dir>`: demodulate every WAV file in `dir` with the f_d Q1 estimated for it
+///
+/// Each file's f_d and f_s come from `../Q1/output/batch/<name>/Q1_results.txt` (run
+/// `q1 --batch <dir>` first). The high-pass and low-pass are 8th-order Butterworth filters
+/// at f_d (less `--guard-band`) and f_B, Q2's design without the uncertainty margin. The
+/// demodulated audio goes to `output/batch/<name>/Q3_demodulated.wav` and one summary row
+/// per file to `output/Q3_batch_summary.csv`; a file that fails is recorded there and skipped.
+fn run_batch(dir: &Path, args: &[String], channel_mode: ChannelMode) -> Result<()> {
+    let files = batch::wav_files(dir)?;
+    let lo_phase = demodulator::lo_phase_from_args(args)?;
+    let recovery = CarrierRecovery::from_args(args)?;
+    let mode = DemodMode::from_args(args)?;
+    let guard_band = demodulator::guard_band_from_args(args)?;
+    let reproducibility = Reproducibility::from_args(args)?;
+    info!("\n[Batch] {} WAV file(s) in {}", files.len(), dir.display());
 
-    println!("\nQ3 Time-Domain Demodulation completed successfully!");
-    println!("Output files saved in: codes/Q3/output/");
+    let columns = ["f_d_hz", "f_s_hz", "samples", "output_rms", "output_peak"];
+    let summary = BatchSummary::run(&files, &columns, |file| -> Result<Vec<String>> {
+        let q1_results = batch::file_dir("../Q1/output", file).join("Q1_results.txt");
+        let (f_d, f_s, f_b) = read_q1_results(&q1_results.to_string_lossy())?;
+        let (channels, source_spec) = audio_reader::read_wav_channels(&file.to_string_lossy(), channel_mode)?;
+        if (source_spec.sample_rate as f64 - f_s).abs() > 0.5 {
+            return Err(DspError::Data(format!(
+                "{}: sample rate {} Hz differs from f_s = {} Hz in {}",
+                file.display(),
+                source_spec.sample_rate,
+                f_s,
+                q1_results.display()
+            )));
+        }
+
+        let chain = TimeDomainDemodulator::butterworth_guarded(BATCH_FILTER_ORDER, f_d, f_b, guard_band, f_s);
+        let demodulator = Demodulator::from_chain(chain)
+            .with_phase(lo_phase)
+            .with_recovery(recovery)
+            .with_mode(mode);
+        let demodulated: Vec<Vec<f64>> = channels.iter().map(|channel| demodulator.demodulate(channel)).collect();
+
+        let out_dir = batch::file_dir("output", file);
+        std::fs::create_dir_all(&out_dir).map_err(|e| DspError::io(&out_dir, e))?;
+        let wav_path = out_dir.join("Q3_demodulated.wav");
+        let output_options = WavWriteOptions::from_args(args, &source_spec)?;
+        audio_writer::write_wav_channels(&wav_path.to_string_lossy(), &demodulated, f_s as u32, &output_options)?;
+        info!("  f_d = {:.4} Hz, saved to {}", f_d, wav_path.display());
+
+        let first = &demodulated[0];
+        let rms = (simd::energy(first) / first.len().max(1) as f64).sqrt();
+        let peak = first.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
+        let loudness = Levels::measure(&demodulated, f_s)?.integrated_lufs;
+        StageResults::new("Q3")
+            .parameter("input", file.to_string_lossy().into_owned())
+            .parameter("f_d_hz", f_d)
+            .parameter("f_s_hz", f_s)
+            .parameter("f_b_hz", f_b)
+            .parameter("mode", mode.to_string())
+            .reproducibility(&reproducibility)
+            .metric("output_rms", rms)
+            .metric("output_peak", peak)
+            .metric("output_lufs", loudness)
+            .save_with(out_dir.join("Q3_results.json"), &reproducibility)?;
+        Ok(vec![
+            format!("{:.4}", f_d),
+            format!("{}", f_s),
+            first.len().to_string(),
+            format!("{:.6e}", rms),
+            format!("{:.6e}", peak),
+        ])
+    });
+
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    summary.save("output/Q3_batch_summary.csv")?;
+    info!(
+        "\n[Batch] {} succeeded, {} failed; summary saved to output/Q3_batch_summary.csv",
+        files.len() - summary.failures(),
+        summary.failures()
+    );
+    Ok(())
+}
+
+/// Demodulate live input (`--live`) with Butterworth filters designed at the input's rate
+///
+/// f_d and f_B come from Q1's results, as in a normal run; Q2's coefficients are designed
+/// for the recording's sample rate, which a capture device need not share.
+fn run_live(config: &LiveConfig, args: &[String]) -> Result<()> {
+    info!("\n[Live] Reading Q1 results...");
+    let q1_results_path = run_dir::upstream_path(args, "../Q1/output", "Q1_results.txt")?;
+    let (f_d, _, f_b) = read_q1_results(&q1_results_path)?;
+    let guard_band = demodulator::guard_band_from_args(args)?;
+    if guard_band >= f_d {
+        return Err(DspError::invalid_value("--guard-band", &guard_band.to_string()));
+    }
+
+    info!("[Live] Opening {}...", config.input);
+    let source = config.open()?;
+    let f_s = source.sample_rate;
+    if f_d + f_b >= f_s / 2.0 {
+        return Err(DspError::Data(format!(
+            "{}: sample rate {} Hz is too low for f_d = {:.1} Hz and f_B = {:.1} Hz",
+            source.name, f_s, f_d, f_b
+        )));
+    }
+    let mut chain = TimeDomainDemodulator::butterworth_guarded(BATCH_FILTER_ORDER, f_d, f_b, guard_band, f_s);
+    chain.phase = demodulator::lo_phase_from_args(args)?;
+    info!("  Source: {} at {} Hz", source.name, f_s);
+    info!(
+        "  Filters: order-{} Butterworth, high-pass {:.1} Hz, low-pass {:.1} Hz; f_d = {:.4} Hz",
+        BATCH_FILTER_ORDER,
+        f_d - guard_band,
+        f_b,
+        f_d
+    );
+    info!(
+        "  Blocks of {} samples ({:.1} ms), up to {} queued",
+        config.block,
+        config.block as f64 / f_s * 1e3,
+        config.queue
+    );
+    match config.limit() {
+        Some(seconds) => info!("  Running for {} s...\n", seconds),
+        None => info!("  Running to the end of the input...\n"),
+    }
+
+    let report = live::run(config, &source, &chain)?;
+    info!("\n[Live] {} blocks processed, {} dropped", report.blocks, report.dropped);
+    info!(
+        "  Latency: {:.1} ms block + {:.2} ms mean / {:.2} ms max processing",
+        report.block_duration.as_secs_f64() * 1e3,
+        report.mean_latency.as_secs_f64() * 1e3,
+        report.max_latency.as_secs_f64() * 1e3
+    );
+    if report.output.is_empty() {
+        return Err(DspError::Data(format!("{}: no complete block was received", source.name)));
+    }
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    audio_writer::write_wav("output/Q3_live_demodulated.wav", &report.output, f_s.round() as u32)?;
+    info!("  Saved: output/Q3_live_demodulated.wav");
+    Ok(())
+}
+
+fn save_interactive_plots(
+    title: &str,
+    spectra: &[(&str, &[(f64, f64)])],
+    waveforms: &[(&str, &[f64])],
+    f_s: f64,
+    (f_d, f_b): (f64, f64),
+    filename: &str,
+) {
+    let mut page = InteractivePage::new(title);
+    for &(name, spectrum) in spectra {
+        page = page.chart(
+            InteractiveChart::new(name, "Frequency (Hz)", "Magnitude")
+                .series(name, spectrum.iter().copied())
+                .marker(f_d, "f_d")
+                .marker(f_b, "f_B"),
+        );
+    }
+    let mut waveform_chart = InteractiveChart::new("Waveforms", "Time (s)", "Amplitude");
+    for &(name, samples) in waveforms {
+        waveform_chart = waveform_chart.waveform(name, samples, f_s);
+    }
+    match page.chart(waveform_chart).save(filename) {
+        Ok(()) => info!("  Saved: {}", filename),
+        Err(e) => warn!("  Warning: {}", e),
+    }
 }
 
-fn read_q1_results() -> Result<(f64, f64, f64), String> {
-    let content = std::fs::read_to_string("../Q1/output/Q1_results.txt")
-        .map_err(|e| format!("Failed to read Q1 results: {}", e))?;
+/// Run AGC over every channel, reporting and plotting the first one
+fn apply_agc(channels: Vec<Vec<f64>>, f_s: f64, config: &AgcConfig, plot: PlotTarget) -> Result<Vec<Vec<f64>>> {
+    info!("  AGC: {}", config);
+    let results: Vec<_> = channels.iter().map(|c| agc::apply(c, f_s, config)).collect();
+    let (min, max) = results[0].gain_range_db();
+    info!("  AGC gain ranged from {:+.1} dB to {:+.1} dB", min, max);
+    spectrum_analyzer::plot_envelopes(&channels[0], &results[0].output, f_s, plot, "Output Envelope Before/After AGC")?;
+    Ok(results.into_iter().map(|r| r.output).collect())
+}
+
+/// Decimate every demodulated channel by `factor`, returning the signals and their new rate
+fn decimate_output(channels: Vec<Vec<f64>>, factor: usize, f_s: f64, f_b: f64) -> (Vec<Vec<f64>>, f64) {
+    if factor <= 1 {
+        return (channels, f_s);
+    }
+    let decimated_fs = f_s / factor as f64;
+    let decimated: Vec<Vec<f64>> = channels.iter().map(|c| decimate(c, factor)).collect();
+    info!(
+        "  Decimated by {}: {:.0} Hz -> {:.0} Hz, {} -> {} samples per channel",
+        factor,
+        f_s,
+        decimated_fs,
+        channels[0].len(),
+        decimated[0].len()
+    );
+    if decimated_fs / 2.0 < f_b {
+        info!(
+            "  Warning: new Nyquist frequency {:.0} Hz is below f_B = {:.0} Hz, part of the baseband is removed",
+            decimated_fs / 2.0,
+            f_b
+        );
+    }
+    (decimated, decimated_fs)
+}
+
+/// Write the PLL frequency, phase error and lock indicator every 10 ms
+fn save_pll_track(track: &PllTrack, f_s: f64, filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("Q3 PLL Carrier Tracking\n");
+    content.push_str("=======================\n\n");
+    content.push_str("time_s  frequency_hz  phase_error  lock\n");
+    let hop = ((f_s * 0.01) as usize).max(1);
+    for k in (0..track.frequency.len()).step_by(hop) {
+        content.push_str(&format!(
+            "{:.3}  {:.4}  {:+.5}  {:+.3}\n",
+            k as f64 / f_s,
+            track.frequency[k],
+            track.phase_error[k],
+            track.lock[k]
+        ));
+    }
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
+}
+
+/// Write output RMS against LO phase, next to the cos(phase) law for a DSB-SC signal
+fn save_phase_sweep(sweep: &[(f64, f64)], filename: &str) -> Result<()> {
+    let reference = sweep.first().map(|&(_, rms)| rms).unwrap_or(0.0);
+    let mut content = String::new();
+    content.push_str("Q3 Local-Oscillator Phase Sweep\n");
+    content.push_str("===============================\n\n");
+    let header = "phase_deg  output_rms  relative  |cos(phase)|\n";
+    info!("  {}", header.trim_end());
+    content.push_str(header);
+    for &(phase, rms) in sweep {
+        let relative = if reference > 0.0 { rms / reference } else { 0.0 };
+        let line = format!(
+            "{:>9.1}  {:>10.6}  {:>8.4}  {:>12.4}\n",
+            phase.to_degrees(),
+            rms,
+            relative,
+            phase.cos().abs()
+        );
+        info!("  {}", line.trim_end());
+        content.push_str(&line);
+    }
+    content.push_str("\nRelative amplitude is measured against the 0 deg run. The phase is\n");
+    content.push_str("referenced to the estimated f_d, so any residual frequency error makes the\n");
+    content.push_str("true phase drift over the recording and flattens the curve.\n");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
+}
+
+fn save_fixed_point(rows: &[(&str, FixedPointComparison)], filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("Q3 Fixed-Point Filtering\n");
+    content.push_str("========================\n\n");
+    let header = "filter     format  snr_db  max_error     saturated\n";
+    info!("  {}", header.trim_end());
+    content.push_str(header);
+    for (name, comparison) in rows {
+        let line = format!(
+            "{:<9}  {:<6}  {:>6.1}  {:>12.4e}  {:>9}\n",
+            name, comparison.format, comparison.snr_db, comparison.max_error, comparison.saturations
+        );
+        info!("  {}", line.trim_end());
+        content.push_str(&line);
+    }
+    content.push_str("\nEach filter runs as a Direct Form I biquad cascade in integer arithmetic:\n");
+    content.push_str("samples in Q15/Q31, coefficients in Q2.14/Q2.30, a wide accumulator and\n");
+    content.push_str("saturation at full scale. SNR is the f64 cascade's output power over the power\n");
+    content.push_str("of the difference. The high-pass filters the recording and the low-pass the\n");
+    content.push_str("mixer output, which the factor 2 of the local oscillator can push past full\n");
+    content.push_str("scale; those samples are counted as saturated.\n");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
+}
+
+fn save_chain_verification(probes: &[(&str, ProbeTone)], f_d: f64, filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("Q3 End-to-End Chain Verification\n");
+    content.push_str("================================\n\n");
+    let header = "point         probe_hz   products_hz          measured_db  predicted_db\n";
+    info!("  {}", header.trim_end());
+    content.push_str(header);
+    for (label, probe) in probes {
+        let products = format!("{:.1}, {:.1}", (probe.frequency - f_d).abs(), probe.frequency + f_d);
+        let line = format!(
+            "{:<12}  {:>9.2}  {:<19}  {:>11.2}  {:>12.2}\n",
+            label, probe.frequency, products, probe.measured_db, probe.predicted_db
+        );
+        info!("  {}", line.trim_end());
+        content.push_str(&line);
+    }
+    content.push_str("\nEach probe is a unit cosine, one second long, through the same filters and\n");
+    content.push_str("local oscillator as the recording; the level is output RMS over input RMS,\n");
+    content.push_str("measured over the last half second. Mixing moves a probe to |f - f_d| and\n");
+    content.push_str("f + f_d; the prediction weights both by the Q2 filter responses. A probe at\n");
+    content.push_str("f_d lands on DC, where the level also depends on the LO phase (--lo-phase).\n");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
+}
+
+fn read_q1_results(path: &str) -> Result<(f64, f64, f64)> {
+    let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
 
     let mut f_d = None;
     let mut f_s = None;
@@ -114,85 +834,39 @@ fn read_q1_results() -> Result<(f64, f64, f64), String> {
     for line in content.lines() {
         if line.contains("频率偏差") || line.contains("f_d") {
             if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().split_whitespace().next().unwrap_or("0").parse::<f64>() {
+                if let Ok(value) = value_str.split_whitespace().next().unwrap_or("0").parse::<f64>() {
                     f_d = Some(value);
                 }
             }
         } else if line.contains("采样频率") || line.contains("f_s") {
             if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().split_whitespace().next().unwrap_or("0").parse::<f64>() {
+                if let Ok(value) = value_str.split_whitespace().next().unwrap_or("0").parse::<f64>() {
                     f_s = Some(value);
                 }
             }
         }
     }
 
-    let f_d = f_d.ok_or_else(|| "Could not find f_d in Q1 results".to_string())?;
-    let f_s = f_s.ok_or_else(|| "Could not find f_s in Q1 results".to_string())?;
+    let f_d = f_d.ok_or_else(|| DspError::Data(format!("{}: could not find f_d", path)))?;
+    let f_s = f_s.ok_or_else(|| DspError::Data(format!("{}: could not find f_s", path)))?;
     let f_b = 4000.0; // Given in problem statement
 
     Ok((f_d, f_s, f_b))
 }
 
-fn read_q2_filters() -> Result<(Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>), String> {
-    let content = std::fs::read_to_string("../Q2/output/Q2_filter_coefficients.txt")
-        .map_err(|e| format!("Failed to read Q2 filters: {}", e))?;
-
-    let mut hp_b = Vec::new();
-    let mut hp_a = Vec::new();
-    let mut lp_b = Vec::new();
-    let mut lp_a = Vec::new();
-    
-    let mut current_section = "";
-    
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        
-        if line.contains("High-pass Filter") {
-            current_section = "hp";
-        } else if line.contains("Low-pass Filter") {
-            current_section = "lp";
-        } else if line.starts_with("b[") {
-            if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().parse::<f64>() {
-                    match current_section {
-                        "hp" => hp_b.push(value),
-                        "lp" => lp_b.push(value),
-                        _ => {}
-                    }
-                }
-            }
-        } else if line.starts_with("a[") {
-            if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().parse::<f64>() {
-                    match current_section {
-                        "hp" => hp_a.push(value),
-                        "lp" => lp_a.push(value),
-                        _ => {}
-                    }
-                }
-            }
-        }
-    }
-
-    if hp_b.is_empty() || hp_a.is_empty() || lp_b.is_empty() || lp_a.is_empty() {
-        return Err("Failed to parse filter coefficients".to_string());
-    }
-
-    Ok((hp_b, hp_a, lp_b, lp_a))
-}
-
+#[allow(clippy::too_many_arguments)]
 fn save_results(
     original: &[(f64, f64)],
     xh: &[(f64, f64)],
     xb: &[(f64, f64)],
     xl: &[(f64, f64)],
+    levels: &[(&str, Levels)],
+    (clipping, headroom): (&ClippingReport, &Headroom),
     f_d: f64,
     f_s: f64,
-) {
+    f_b: f64,
+    reproducibility: &Reproducibility,
+) -> Result<()> {
     let mut content = String::new();
     content.push_str("Q3 Time-Domain Demodulation Results\n");
     content.push_str("=====================================\n\n");
@@ -204,34 +878,22 @@ fn save_results(
     content.push_str("------------------\n");
     
     // Original signal peak
-    let orig_peak = original.iter()
-        .filter(|(f, _)| *f > 1000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let orig_peak = stage_peak(original, (1000.0, f64::INFINITY), "X(f)")?;
     content.push_str(&format!("Original signal X(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         orig_peak.0, orig_peak.1));
     
     // After high-pass
-    let xh_peak = xh.iter()
-        .filter(|(f, _)| *f > 1000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xh_peak = stage_peak(xh, (1000.0, f64::INFINITY), "X_h(f)")?;
     content.push_str(&format!("After high-pass X_h(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         xh_peak.0, xh_peak.1));
     
     // After multiplication
-    let xb_low_peak = xb.iter()
-        .filter(|(f, _)| *f > 10.0 && *f < 5000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xb_low_peak = stage_peak(xb, (10.0, 5000.0), "X_b(f)")?;
     content.push_str(&format!("After multiplication X_b(f) peak (baseband): f = {:.2} Hz, magnitude = {:.6}\n", 
         xb_low_peak.0, xb_low_peak.1));
     
     // Demodulated signal peak
-    let xl_peak = xl.iter()
-        .filter(|(f, _)| *f > 10.0 && *f < 4000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xl_peak = stage_peak(xl, (10.0, 4000.0), "X_l(f)")?;
     content.push_str(&format!("Demodulated signal X_l(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         xl_peak.0, xl_peak.1));
     
@@ -245,17 +907,78 @@ fn save_results(
         .map(|(_, m)| m * m)
         .sum();
     
-    content.push_str(&format!("\nEnergy analysis (0-4000 Hz band):\n"));
+    content.push_str("\nEnergy analysis (0-4000 Hz band):\n");
     content.push_str(&format!("  Original signal energy: {:.6e}\n", energy_orig_baseband));
     content.push_str(&format!("  Demodulated signal energy: {:.6e}\n", energy_demod_baseband));
     
     // Frequency shift verification
-    content.push_str(&format!("\nFrequency shift verification:\n"));
+    content.push_str("\nFrequency shift verification:\n");
     content.push_str(&format!("  Original peak at: {:.2} Hz\n", orig_peak.0));
     content.push_str(&format!("  Expected shift: {:.2} Hz (should be near f_d = {:.2} Hz)\n", 
         orig_peak.0 - f_d, f_d));
     content.push_str(&format!("  Demodulated peak at: {:.2} Hz (should be in baseband)\n", xl_peak.0));
 
-    std::fs::write("output/Q3_results.txt", content).expect("Failed to save results");
-    println!("  Saved to: output/Q3_results.txt");
+    content.push_str("\nLevels (integrated loudness per ITU-R BS.1770, RMS and sample peak):\n");
+    for (signal, level) in levels {
+        content.push_str(&format!("  {:<12} {}\n", signal, level));
+    }
+
+    content.push_str("\nClipping and headroom:\n");
+    content.push_str(&format!(
+        "  Input: {} clipped sample(s) in {} flat top(s), peak {:.2} dBFS\n",
+        clipping.clipped_samples, clipping.runs, clipping.peak_dbfs
+    ));
+    content.push_str(&format!(
+        "  Output before writing: peak {:+.2} dBFS, {} sample(s) beyond full scale\n",
+        headroom.peak_dbfs, headroom.over_full_scale
+    ));
+    content.push_str(&format!(
+        "  Written file: gain x{:.4}, headroom {:.2} dB, {} sample(s) clipped\n",
+        headroom.write_gain,
+        headroom.headroom_db(),
+        headroom.clipped_on_write
+    ));
+
+    std::fs::write("output/Q3_results.txt", content).map_err(|e| DspError::io("output/Q3_results.txt", e))?;
+    info!("  Saved to: output/Q3_results.txt");
+
+    let peak_row = |stage: &str, peak: &(f64, f64)| row![("stage", stage), ("frequency_hz", peak.0), ("magnitude", peak.1)];
+    StageResults::new("Q3")
+        .parameter("f_d_hz", f_d)
+        .parameter("f_s_hz", f_s)
+        .parameter("f_b_hz", f_b)
+        .metric("baseband_energy_original", energy_orig_baseband)
+        .metric("baseband_energy_demodulated", energy_demod_baseband)
+        .metric("demodulated_peak_hz", xl_peak.0)
+        .metric("input_clipped_samples", clipping.clipped_samples)
+        .metric("input_clipped_runs", clipping.runs)
+        .metric("output_peak_dbfs", headroom.peak_dbfs)
+        .metric("output_over_full_scale", headroom.over_full_scale)
+        .metric("output_headroom_db", headroom.headroom_db())
+        .metric("output_clipped_on_write", headroom.clipped_on_write)
+        .table(
+            "peaks",
+            vec![
+                peak_row("X", orig_peak),
+                peak_row("X_h", xh_peak),
+                peak_row("X_b", xb_low_peak),
+                peak_row("X_l", xl_peak),
+            ],
+        )
+        .table("levels", levels.iter().map(|(signal, level)| level.row(signal)).collect())
+        .reproducibility(reproducibility)
+        .save_with("output/Q3_results.json", reproducibility)?;
+    info!("  Saved to: output/Q3_results.json");
+    Ok(())
+}
+
+/// Largest spectral line strictly inside `(low, high)` Hz
+///
+/// An error names the stage when no bin falls in the band, e.g. for a signal too short to resolve it.
+fn stage_peak<'a>(spectrum: &'a [(f64, f64)], (low, high): (f64, f64), stage: &str) -> Result<&'a (f64, f64)> {
+    spectrum
+        .iter()
+        .filter(|(f, _)| *f > low && *f < high)
+        .max_by(|(_, mag1), (_, mag2)| mag1.total_cmp(mag2))
+        .ok_or_else(|| DspError::Data(format!("{} has no spectral line between {} and {} Hz", stage, low, high)))
 }
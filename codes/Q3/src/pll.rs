@@ -0,0 +1,294 @@
+use dsp_core::error::{DspError, Result};
+use std::f64::consts::PI;
+
+/// How the phase detector measures the error between the input carrier and the NCO
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PhaseDetector {
+    /// Error from the quadrature arm alone; needs a residual carrier (AM with carrier)
+    #[default]
+    Carrier,
+    /// Costas loop, error from I·Q; works without a carrier line (DSB-SC), 180° ambiguous
+    Costas,
+}
+
+/// Loop parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PllConfig {
+    /// Loop noise bandwidth (Hz): wider locks and tracks faster, narrower rejects more noise
+    pub bandwidth: f64,
+    /// Damping factor of the second-order loop
+    pub damping: f64,
+    pub detector: PhaseDetector,
+}
+
+impl Default for PllConfig {
+    fn default() -> Self {
+        PllConfig {
+            bandwidth: 20.0,
+            damping: std::f64::consts::FRAC_1_SQRT_2,
+            detector: PhaseDetector::Carrier,
+        }
+    }
+}
+
+impl PllConfig {
+    /// Default loop with the Costas detector, for suppressed-carrier (DSB-SC) signals
+    pub fn costas() -> Self {
+        PllConfig {
+            detector: PhaseDetector::Costas,
+            ..PllConfig::default()
+        }
+    }
+}
+
+/// Local-oscillator source of the demodulator
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CarrierRecovery {
+    /// Fixed-frequency oscillator at the estimated f_d
+    #[default]
+    Fixed,
+    /// Oscillator steered by a phase-locked loop (or Costas loop) started at f_d
+    Pll(PllConfig),
+}
+
+impl CarrierRecovery {
+    /// Read `--carrier-recovery <fixed|pll|costas>` and `--pll-bandwidth <Hz>` from
+    /// command-line arguments
+    ///
+    /// `pll` locks onto a residual carrier line; `costas` needs none and suits DSB-SC.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut config = None;
+        let mut bandwidth = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if !matches!(name, "--carrier-recovery" | "--pll-bandwidth") {
+                continue;
+            }
+            let value = iter.next().ok_or_else(|| DspError::missing_value(name))?;
+            if name == "--pll-bandwidth" {
+                bandwidth = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&b: &f64| b > 0.0)
+                        .ok_or_else(|| DspError::Argument(format!("Invalid PLL bandwidth '{}'", value)))?,
+                );
+                continue;
+            }
+            config = match value.to_ascii_lowercase().as_str() {
+                "fixed" => None,
+                "pll" => Some(PllConfig::default()),
+                "costas" => Some(PllConfig::costas()),
+                other => {
+                    return Err(DspError::Argument(format!(
+                        "Unknown carrier recovery '{}' (expected fixed, pll or costas)",
+                        other
+                    )))
+                }
+            };
+        }
+        Ok(match config {
+            Some(mut config) => {
+                config.bandwidth = bandwidth.unwrap_or(config.bandwidth);
+                CarrierRecovery::Pll(config)
+            }
+            None => CarrierRecovery::Fixed,
+        })
+    }
+}
+
+impl std::fmt::Display for CarrierRecovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CarrierRecovery::Fixed => write!(f, "fixed oscillator"),
+            CarrierRecovery::Pll(config) => match config.detector {
+                PhaseDetector::Carrier => write!(f, "PLL ({} Hz loop bandwidth)", config.bandwidth),
+                PhaseDetector::Costas => write!(f, "Costas loop ({} Hz loop bandwidth)", config.bandwidth),
+            },
+        }
+    }
+}
+
+/// Per-sample record of a PLL run
+pub struct PllTrack {
+    /// Input multiplied by 2 cos(NCO phase), ready for the low-pass
+    pub mixed: Vec<f64>,
+    /// Loop frequency estimate (Hz)
+    pub frequency: Vec<f64>,
+    /// Phase detector output (rad, approximately)
+    pub phase_error: Vec<f64>,
+    /// Lock indicator in [-1, 1]; near 1 when locked
+    pub lock: Vec<f64>,
+}
+
+impl PllTrack {
+    /// Fraction of samples after `skip` with the lock indicator above 0.5
+    pub fn locked_fraction(&self, skip: usize) -> f64 {
+        let tail = &self.lock[skip.min(self.lock.len())..];
+        tail.iter().filter(|&&l| l > 0.5).count() as f64 / tail.len().max(1) as f64
+    }
+
+    /// Mean NCO frequency after `skip` samples
+    pub fn mean_frequency(&self, skip: usize) -> f64 {
+        let tail = &self.frequency[skip.min(self.frequency.len())..];
+        tail.iter().sum::<f64>() / tail.len().max(1) as f64
+    }
+}
+
+/// One-pole low-pass, y += alpha (x - y)
+struct OnePole {
+    alpha: f64,
+    y: f64,
+}
+
+impl OnePole {
+    fn new(cutoff: f64, f_s: f64) -> Self {
+        OnePole {
+            alpha: 1.0 - (-2.0 * PI * cutoff / f_s).exp(),
+            y: 0.0,
+        }
+    }
+
+    fn step(&mut self, x: f64) -> f64 {
+        self.y += self.alpha * (x - self.y);
+        self.y
+    }
+}
+
+/// Run a second-order PLL over `signal`, starting the NCO at `f_d` Hz and `phase` rad
+///
+/// The I and Q arms mix with cos/sin of the NCO phase and pass two one-pole sections at
+/// f_d / 2 to suppress the 2 f_d product. The detector output is normalized by the slowly
+/// averaged arm power, so the loop gain does not depend on the signal level, and a PI
+/// loop filter designed from `bandwidth` and `damping` steers the NCO.
+pub fn track(signal: &[f64], f_d: f64, phase: f64, f_s: f64, config: &PllConfig) -> PllTrack {
+    let t = 1.0 / f_s;
+    // Standard digital PI gains for a loop with unit detector and NCO gain
+    let theta = config.bandwidth * t / (config.damping + 0.25 / config.damping);
+    let denom = 1.0 + 2.0 * config.damping * theta + theta * theta;
+    let k_p = 4.0 * config.damping * theta / denom;
+    let k_i = 4.0 * theta * theta / denom;
+
+    let arm_cutoff = f_d / 2.0;
+    let mut i_arm = [OnePole::new(arm_cutoff, f_s), OnePole::new(arm_cutoff, f_s)];
+    let mut q_arm = [OnePole::new(arm_cutoff, f_s), OnePole::new(arm_cutoff, f_s)];
+    let mut power = OnePole::new(config.bandwidth / 4.0, f_s);
+    let mut lock_filter = OnePole::new(config.bandwidth / 4.0, f_s);
+
+    let nominal_step = 2.0 * PI * f_d * t;
+    let mut nco_phase = phase;
+    let mut integrator = 0.0;
+
+    let mut result = PllTrack {
+        mixed: Vec::with_capacity(signal.len()),
+        frequency: Vec::with_capacity(signal.len()),
+        phase_error: Vec::with_capacity(signal.len()),
+        lock: Vec::with_capacity(signal.len()),
+    };
+
+    for &x in signal {
+        let (sin, cos) = nco_phase.sin_cos();
+        result.mixed.push(2.0 * x * cos);
+
+        let i = i_arm[0].step(2.0 * x * cos);
+        let i = i_arm[1].step(i);
+        let q = q_arm[0].step(-2.0 * x * sin);
+        let q = q_arm[1].step(q);
+        let p = power.step(i * i + q * q).max(1e-12);
+
+        let error = match config.detector {
+            // I Q / (I² + Q²) = sin(2Δ) / 2 ≈ Δ
+            PhaseDetector::Costas => i * q / p,
+            // Q / |A| = sin(Δ) ≈ Δ
+            PhaseDetector::Carrier => q / p.sqrt(),
+        };
+        // Ratio of averages with the same smoothing as the power, so it stays within [-1, 1]
+        let lock = match config.detector {
+            PhaseDetector::Costas => lock_filter.step(i * i - q * q) / p,
+            PhaseDetector::Carrier => lock_filter.step(i) / p.sqrt(),
+        };
+
+        integrator += k_i * error;
+        let step = nominal_step + k_p * error + integrator;
+        nco_phase = (nco_phase + step) % (2.0 * PI);
+
+        // The integrator holds the frequency correction; the proportional path only fixes phase
+        result.frequency.push((nominal_step + integrator) / (2.0 * PI * t));
+        result.phase_error.push(error);
+        result.lock.push(lock);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costas_tracks_drifting_dsb_sc_carrier() {
+        // 200 Hz tone on a DSB-SC carrier drifting from 1003 to 1008 Hz; the loop starts at 1000 Hz
+        let f_s = 8000.0;
+        let n = 8000 * 4;
+        let mut carrier_phase = 0.3;
+        let mut true_frequency = Vec::with_capacity(n);
+        let signal: Vec<f64> = (0..n)
+            .map(|k| {
+                let t = k as f64 / f_s;
+                let f = 1003.0 + 5.0 * k as f64 / n as f64;
+                true_frequency.push(f);
+                carrier_phase += 2.0 * PI * f / f_s;
+                (2.0 * PI * 200.0 * t).cos() * carrier_phase.cos()
+            })
+            .collect();
+
+        let pll = track(&signal, 1000.0, 0.0, f_s, &PllConfig::costas());
+        let settle = n / 4;
+        assert!(pll.locked_fraction(settle) > 0.9, "locked {}", pll.locked_fraction(settle));
+        let worst = pll.frequency[settle..]
+            .iter()
+            .zip(&true_frequency[settle..])
+            .map(|(f, t)| (f - t).abs())
+            .fold(0.0f64, f64::max);
+        assert!(worst < 1.0, "frequency error {} Hz", worst);
+
+        let args: Vec<String> = ["--carrier-recovery", "costas", "--pll-bandwidth", "10"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let expected = PllConfig { bandwidth: 10.0, ..PllConfig::costas() };
+        assert_eq!(CarrierRecovery::from_args(&args).unwrap(), CarrierRecovery::Pll(expected));
+    }
+
+    #[test]
+    fn test_costas_recovers_drifting_dsb_sc_where_fixed_mixing_fails() {
+        use dsp_core::resample::decimate;
+        use dsp_core::signal_gen::{self, AmConfig, Baseband};
+
+        // Synthetic DSB-SC: no carrier line, and f_d drifts 3000 -> 3004 Hz
+        let config = AmConfig {
+            offset_drift: 4.0,
+            baseband: Baseband::Tones(vec![225.0, 610.0]),
+            snr_db: Some(20.0),
+            ..AmConfig::default()
+        };
+        let signal = signal_gen::generate(&config).unwrap();
+
+        // Decimating by 4 is the low-pass (cutoff ~2.6 kHz rejects the 2 f_d product)
+        let reference = decimate(&signal.baseband, 4);
+        let correlation = |mixed: &[f64]| {
+            let output = decimate(mixed, 4);
+            let skip = output.len() / 3;
+            let (x, y) = (&output[skip..], &reference[skip..]);
+            let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(p, q)| p * q).sum::<f64>();
+            dot(x, y) / (dot(x, x) * dot(y, y)).sqrt()
+        };
+
+        // The fixed oscillator stays at the starting f_d and drifts out of phase
+        let fixed = crate::demodulator::mix_with_carrier(&signal.samples, config.offset, 0.0, config.sample_rate);
+        let costas = track(&signal.samples, config.offset, 0.0, config.sample_rate, &PllConfig::costas());
+        assert!(correlation(&fixed).abs() < 0.5, "fixed: {}", correlation(&fixed));
+        // Costas locks with a sign ambiguity
+        assert!(correlation(&costas.mixed).abs() > 0.95, "costas: {}", correlation(&costas.mixed));
+    }
+}
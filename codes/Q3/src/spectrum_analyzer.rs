@@ -1,118 +1,375 @@
 use rustfft::{FftPlanner, num_complex::Complex};
+use dsp_core::error::{DspError, Result};
+use dsp_core::agc::envelope;
+use dsp_core::colormap::DbRange;
+use dsp_core::min_max::{band_outline, min_max_columns};
+use dsp_core::plot_style::Rgb;
+use dsp_core::stft::{Stft, Window};
+use dsp_core::plot_output::PlotTarget;
+use dsp_core::with_drawing_area;
+use log::info;
 use plotters::prelude::*;
 
 /// Compute magnitude spectrum of a signal
 pub fn compute_spectrum(signal: &[f64], f_s: f64) -> Vec<(f64, f64)> {
     let n = signal.len();
-    
-    // Prepare FFT input
-    let mut buffer: Vec<Complex<f64>> = signal
-        .iter()
-        .map(|&x| Complex::new(x, 0.0))
-        .collect();
-    
-    // Perform FFT
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(n);
-    fft.process(&mut buffer);
+    let buffer = fft(signal);
     
     // Compute magnitude spectrum and frequency axis
     let mut spectrum = Vec::with_capacity(n / 2);
     let df = f_s / n as f64;
     
-    for i in 0..n / 2 {
+    for (i, bin) in buffer.iter().take(n / 2).enumerate() {
         let freq = i as f64 * df;
-        let magnitude = buffer[i].norm() / n as f64;
+        let magnitude = bin.norm() / n as f64;
         spectrum.push((freq, magnitude));
     }
     
     spectrum
 }
 
+/// Full complex FFT of a real signal (unnormalized, all N bins)
+pub fn fft(signal: &[f64]) -> Vec<Complex<f64>> {
+    let mut buffer: Vec<Complex<f64>> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    FftPlanner::new().plan_fft_forward(signal.len()).process(&mut buffer);
+    buffer
+}
+
 /// Plot spectrum
-pub fn plot_spectrum(spectrum: &[(f64, f64)], filename: &str, title: &str) {
-    let root = BitMapBackend::new(filename, (1200, 800)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
+pub fn plot_spectrum(spectrum: &[(f64, f64)], target: impl Into<PlotTarget>, title: &str) -> Result<()> {
+    let target = target.into();
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let axes = target.style.axes;
     
-    // Find max magnitude for y-axis
-    let max_mag = spectrum.iter()
-        .map(|(_, m)| *m)
-        .fold(0.0f64, f64::max);
+        let max_freq = spectrum.last().ok_or_else(|| empty_spectrum(title))?.0;
+        let x_range = axes.freq(0.0..max_freq);
+        let visible = || spectrum.iter().filter(|(f, _)| x_range.start <= *f && *f <= x_range.end);
     
-    let max_freq = spectrum.last().unwrap().0;
+        // Find max magnitude in view for y-axis
+        let max_mag = visible()
+            .map(|(_, m)| *m)
+            .fold(0.0f64, f64::max);
+        let y_range = axes.magnitude(0.0..max_mag * 1.1);
     
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq, 0.0..max_mag * 1.1)
-        .unwrap();
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .map_err(DspError::plot)?;
     
-    chart
-        .configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude")
-        .x_label_formatter(&|x| format!("{:.0}", x))
-        .y_label_formatter(&|y| format!("{:.3}", y))
-        .draw()
-        .unwrap();
+        chart
+            .configure_mesh()
+            .x_labels(axes.freq_labels(&x_range))
+            .y_labels(axes.magnitude_labels(&y_range))
+            .x_desc("Frequency (Hz)")
+            .y_desc("Magnitude")
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| axes.magnitude_label(*y, 3))
+            .draw()
+            .map_err(DspError::plot)?;
     
-    chart
-        .draw_series(LineSeries::new(
-            spectrum.iter().map(|(f, m)| (*f, *m)),
-            &BLUE,
-        ))
-        .unwrap();
+        chart
+            .draw_series(LineSeries::new(
+                visible().copied(),
+                &BLUE,
+            ))
+            .map_err(DspError::plot)?;
     
-    root.present().unwrap();
-    println!("  Saved: {}", filename);
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
 }
 
 /// Plot spectrum in dB scale
-pub fn plot_spectrum_db(spectrum: &[(f64, f64)], filename: &str, title: &str) {
-    let root = BitMapBackend::new(filename, (1200, 800)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
+pub fn plot_spectrum_db(spectrum: &[(f64, f64)], target: impl Into<PlotTarget>, title: &str) -> Result<()> {
+    let target = target.into();
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
     
-    // Convert to dB
-    let spectrum_db: Vec<(f64, f64)> = spectrum
-        .iter()
-        .map(|(f, m)| {
-            let db = if *m > 1e-10 {
-                20.0 * m.log10()
-            } else {
-                -200.0
-            };
-            (*f, db)
-        })
-        .collect();
+        // Convert to dB
+        let spectrum_db: Vec<(f64, f64)> = spectrum
+            .iter()
+            .map(|(f, m)| {
+                let db = if *m > 1e-10 {
+                    20.0 * m.log10()
+                } else {
+                    -200.0
+                };
+                (*f, db)
+            })
+            .collect();
     
-    let max_freq = spectrum_db.last().unwrap().0;
+        let axes = target.style.axes;
+        let max_freq = spectrum_db.last().ok_or_else(|| empty_spectrum(title))?.0;
+        let x_range = axes.freq(0.0..max_freq);
+        let y_range = axes.magnitude_db(-100.0..0.0);
     
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq, -100.0..0.0)
-        .unwrap();
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .map_err(DspError::plot)?;
     
-    chart
-        .configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude (dB)")
-        .x_label_formatter(&|x| format!("{:.0}", x))
-        .y_label_formatter(&|y| format!("{:.0}", y))
-        .draw()
-        .unwrap();
+        chart
+            .configure_mesh()
+            .x_labels(axes.freq_labels(&x_range))
+            .y_labels(axes.magnitude_labels(&y_range))
+            .x_desc("Frequency (Hz)")
+            .y_desc("Magnitude (dB)")
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.0}", y))
+            .draw()
+            .map_err(DspError::plot)?;
     
-    chart
-        .draw_series(LineSeries::new(
-            spectrum_db.iter().map(|(f, m)| (*f, *m)),
-            &RED,
-        ))
-        .unwrap();
+        chart
+            .draw_series(LineSeries::new(
+                spectrum_db.iter().copied().filter(|(f, _)| x_range.start <= *f && *f <= x_range.end),
+                &RED,
+            ))
+            .map_err(DspError::plot)?;
     
-    root.present().unwrap();
-    println!("  Saved: {}", filename);
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Plot the peak envelopes (dB) of a signal before and after a gain stage such as AGC
+pub fn plot_envelopes(before: &[f64], after: &[f64], f_s: f64, target: impl Into<PlotTarget>, title: &str) -> Result<()> {
+    let target = target.into();
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+
+        // 5 ms attack / 50 ms release follows syllables without drawing every carrier cycle;
+        // about 2000 points per curve are enough for the plot
+        let step = (before.len() / 2000).max(1);
+        let to_db = |signal: &[f64]| -> Vec<(f64, f64)> {
+            envelope(signal, f_s, 0.005, 0.05)
+                .iter()
+                .enumerate()
+                .step_by(step)
+                .map(|(i, e)| (i as f64 / f_s, (20.0 * e.max(1e-6).log10()).max(-80.0)))
+                .collect()
+        };
+        let curves = [("Before", to_db(before), BLUE), ("After", to_db(after), RED)];
+
+        let duration = before.len().max(after.len()) as f64 / f_s;
+        let levels = || curves.iter().flat_map(|(_, c, _)| c.iter().map(|p| p.1));
+        let y_min = levels().fold(0.0f64, f64::min);
+        let y_max = levels().fold(f64::NEG_INFINITY, f64::max);
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0.0..duration, (y_min - 3.0)..(y_max + 3.0))
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc("Envelope (dB)")
+            .x_label_formatter(&|x| format!("{:.2}", x))
+            .y_label_formatter(&|y| format!("{:.0}", y))
+            .draw()
+            .map_err(DspError::plot)?;
+
+        for (label, curve, color) in curves {
+            chart
+                .draw_series(LineSeries::new(curve, &color))
+                .map_err(DspError::plot)?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Length of the zoomed waveform window (s): about 30 carrier cycles at f_d = 3 kHz
+pub const WAVEFORM_ZOOM_SECONDS: f64 = 0.01;
+
+/// Window of `seconds` centred on the largest sample of `signal`, moved inside the signal
+/// where the peak is near an end
+pub fn peak_window(signal: &[f64], f_s: f64, seconds: f64) -> (f64, f64) {
+    let peak = signal
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map_or(0, |(i, _)| i);
+    let duration = signal.len() as f64 / f_s;
+    let start = (peak as f64 / f_s - seconds / 2.0).clamp(0.0, (duration - seconds).max(0.0));
+    (start, (start + seconds).min(duration))
+}
+
+/// Waveforms of the demodulation stages, one row per stage: the whole signal on the left and
+/// the `zoom` window (s) on the right
+///
+/// The time-domain counterpart of the stage spectra. In the zoom the carrier cycles of the
+/// input and the double-frequency term riding on the baseband after multiplication are
+/// visible, and the low-pass output follows the envelope. Every panel scales to its own peak,
+/// since multiplication halves the level. Windows longer than the plot is wide are drawn as
+/// min/max bands, so no peak is lost.
+pub fn plot_stage_waveforms(
+    stages: &[(&str, &[f64])],
+    f_s: f64,
+    zoom: (f64, f64),
+    target: impl Into<PlotTarget>,
+    title: &str,
+) -> Result<()> {
+    let target = target.into();
+    if let Some((label, _)) = stages.iter().find(|(_, signal)| signal.is_empty()) {
+        return Err(DspError::Data(format!("{}: {} is empty", title, label)));
+    }
+    let rows = stages.len().max(1);
+    with_drawing_area!(target, (1600, 250 * rows as u32 + 60), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let root = root.titled(title, ("sans-serif", 40)).map_err(DspError::plot)?;
+        let areas = root.split_evenly((rows, 2));
+
+        for (row, (label, signal)) in stages.iter().enumerate() {
+            let n = signal.len();
+            let first = ((zoom.0 * f_s).floor() as usize).min(n - 1);
+            let last = ((zoom.1 * f_s).ceil() as usize).clamp(first + 1, n);
+            let zoom_caption = format!("{} ({:.1}-{:.1} ms)", label, first as f64 / f_s * 1e3, last as f64 / f_s * 1e3);
+            for (area, (caption, (first, last))) in areas[2 * row..2 * row + 2]
+                .iter()
+                .zip([(label.to_string(), (0, n)), (zoom_caption, (first, last))])
+            {
+                let time = |i: f64| i / f_s;
+                let amplitude = signal[first..last].iter().fold(0.0f64, |max, x| max.max(x.abs())).max(1e-12) * 1.1;
+                let mut chart = ChartBuilder::on(area)
+                    .caption(caption, ("sans-serif", 24))
+                    .margin(10)
+                    .x_label_area_size(if row == rows - 1 { 40 } else { 25 })
+                    .y_label_area_size(70)
+                    .build_cartesian_2d(time(first as f64)..time(last as f64), -amplitude..amplitude)
+                    .map_err(DspError::plot)?;
+                chart
+                    .configure_mesh()
+                    .x_desc(if row == rows - 1 { "Time (s)" } else { "" })
+                    .x_label_formatter(&|x| format!("{:.3}", x))
+                    .y_label_formatter(&|y| format!("{:.3}", y))
+                    .draw()
+                    .map_err(DspError::plot)?;
+
+                let width = chart.plotting_area().dim_in_pixel().0 as usize;
+                if last - first > width {
+                    let columns = min_max_columns(&signal[first..last], width);
+                    let outline = band_outline(&columns, |i| time(first as f64 + i));
+                    chart
+                        .draw_series(std::iter::once(Polygon::new(outline, BLUE.mix(0.6).filled())))
+                        .map_err(DspError::plot)?;
+                } else {
+                    chart
+                        .draw_series(LineSeries::new((first..last).map(|i| (time(i as f64), signal[i])), &BLUE))
+                        .map_err(DspError::plot)?;
+                }
+            }
+        }
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Stacked spectrograms (dB) of a signal before and after demodulation on one colour scale
+///
+/// Both panels share the same 0 dB reference (the loudest cell of either), so removed bands
+/// turn dark instead of being renormalized. `markers` draws horizontal lines, e.g. at f_d and f_B.
+/// Colours follow the target style's colormap over its dB range, -80..0 dB when it sets none.
+pub fn plot_spectrogram_comparison(
+    before: &[f64],
+    after: &[f64],
+    f_s: f64,
+    markers: &[f64],
+    target: impl Into<PlotTarget>,
+    title: &str,
+) -> Result<()> {
+    let target = target.into();
+    // 1024-point Hann frames; the hop keeps the plot to about 400 columns
+    let window_len = 1024;
+    let hop = (before.len().max(after.len()) / 400).max(window_len / 4);
+    let panels: Vec<(&str, Stft)> = [("Before (input)", before), ("After (demodulated)", after)]
+        .into_iter()
+        .map(|(label, signal)| (label, Stft::compute(signal, f_s, window_len, hop, Window::Hann)))
+        .collect();
+    let to_db = |m: f64| 20.0 * m.max(1e-12).log10();
+    let reference = panels
+        .iter()
+        .flat_map(|(_, stft)| stft.magnitude().into_iter().flatten())
+        .fold(1e-12f64, f64::max);
+    let reference_db = to_db(reference);
+    let colormap = target.style.colormap;
+    let range = target.style.db_range.unwrap_or(DbRange::new(-80.0, 0.0));
+
+    with_drawing_area!(target, (1200, 1000), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let root = root.titled(title, ("sans-serif", 40)).map_err(DspError::plot)?;
+        let duration = before.len().max(after.len()) as f64 / f_s;
+
+        for (area, (label, stft)) in root.split_evenly((2, 1)).iter().zip(&panels) {
+            let mut chart = ChartBuilder::on(area)
+                .caption(*label, ("sans-serif", 28))
+                .margin(15)
+                .x_label_area_size(45)
+                .y_label_area_size(70)
+                .build_cartesian_2d(0.0..duration, 0.0..f_s / 2.0)
+                .map_err(DspError::plot)?;
+            chart
+                .configure_mesh()
+                .disable_mesh()
+                .x_desc("Time (s)")
+                .y_desc("Frequency (Hz)")
+                .x_label_formatter(&|x| format!("{:.2}", x))
+                .y_label_formatter(&|y| format!("{:.0}", y))
+                .draw()
+                .map_err(DspError::plot)?;
+
+            let (dt, df) = (stft.hop as f64 / f_s, stft.bin_width());
+            let cells = stft.magnitude().into_iter().zip(&stft.times).flat_map(|(frame, &t)| {
+                frame.into_iter().enumerate().map(move |(k, m)| {
+                    let level = range.level(to_db(m) - reference_db);
+                    let (x0, y0) = (t - dt / 2.0, k as f64 * df - df / 2.0);
+                    Rectangle::new([(x0, y0.max(0.0)), (x0 + dt, y0 + df)], rgb(colormap.color(level)).filled())
+                })
+            });
+            chart.draw_series(cells).map_err(DspError::plot)?;
+            for &f in markers {
+                chart
+                    .draw_series(LineSeries::new(vec![(0.0, f), (duration, f)], WHITE.mix(0.8).stroke_width(1)))
+                    .map_err(DspError::plot)?;
+            }
+        }
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+fn rgb(Rgb(r, g, b): Rgb) -> RGBColor {
+    RGBColor(r, g, b)
+}
+
+/// Error for a spectrum with nothing to plot
+fn empty_spectrum(title: &str) -> DspError {
+    DspError::Data(format!("{}: empty spectrum", title))
 }
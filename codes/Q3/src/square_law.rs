@@ -0,0 +1,59 @@
+/// Square-law detector, the classic non-coherent AM demodulator
+///
+/// For x(t) = a(t) cos(2π f_d t), 2 x² = a² + a² cos(4π f_d t), so the low-pass leaves a²(t)
+/// without any local oscillator. With `sqrt` the square root gives the envelope |a(t)|;
+/// without it the output keeps the a² term, which for a(t) = 1 + μ m(t) adds μ² m² and with
+/// it the second-harmonic distortion the detector is known for. The mean is removed last.
+/// Like an envelope detector, it needs AM with carrier: DSB-SC comes out as |m(t)|.
+pub fn detect(signal: &[f64], sqrt: bool, lowpass: impl Fn(&[f64]) -> Vec<f64>) -> Vec<f64> {
+    let squared: Vec<f64> = signal.iter().map(|x| 2.0 * x * x).collect();
+    let mut output = lowpass(&squared);
+    if sqrt {
+        // The low-pass can ring slightly below zero
+        output.iter_mut().for_each(|y| *y = y.max(0.0).sqrt());
+    }
+    let mean = output.iter().sum::<f64>() / output.len().max(1) as f64;
+    output.iter_mut().for_each(|y| *y -= mean);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demodulator::DemodMode;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_square_law_recovers_envelope_and_distorts_without_root() {
+        // AM with mu = 0.5 on a 2 kHz carrier; averaging over whole carrier periods is the low-pass
+        let (f_d, f_m, f_s) = (2000.0, 50.0, 16000.0);
+        let message = |k: usize| (2.0 * PI * f_m * k as f64 / f_s).cos();
+        let signal: Vec<f64> = (0..16000)
+            .map(|k| (1.0 + 0.5 * message(k)) * (2.0 * PI * f_d * k as f64 / f_s).cos())
+            .collect();
+        let lowpass = |x: &[f64]| -> Vec<f64> {
+            (0..x.len()).map(|k| x[k.saturating_sub(7)..=k].iter().sum::<f64>() / 8.0).collect()
+        };
+
+        // Trailing 8-sample average delays by 3.5 samples and scales a 50 Hz tone by ~0.997
+        let rooted = detect(&signal, true, lowpass);
+        let error = (100..16000)
+            .map(|k| (rooted[k] - 0.5 * (2.0 * PI * f_m * (k as f64 - 3.5) / f_s).cos()).abs())
+            .fold(0.0f64, f64::max);
+        assert!(error < 0.01, "envelope error {}", error);
+
+        // Without the root: (1 + 0.5 m)² = 1 + m + 0.25 m², a 2 f_m component of 0.125
+        let raw = detect(&signal, false, lowpass);
+        let second: f64 = raw
+            .iter()
+            .enumerate()
+            .map(|(k, y)| y * (2.0 * PI * 2.0 * f_m * (k as f64 - 3.5) / f_s).cos())
+            .sum::<f64>()
+            * 2.0
+            / raw.len() as f64;
+        assert!((second - 0.125).abs() < 0.01, "second harmonic {}", second);
+
+        let args = vec!["--mode".to_string(), "square-law".to_string()];
+        assert_eq!(DemodMode::from_args(&args).unwrap(), DemodMode::SquareLaw { sqrt: true });
+    }
+}
@@ -0,0 +1,76 @@
+//! End-to-end check of the estimate → design → demodulate chain on a synthetic recording
+//!
+//! A short AM signal with known message and carrier offset is generated in memory, f_d is
+//! estimated from its spectrum, Q2's Butterworth pair is designed for it, and the Q3
+//! demodulator runs on it. The recovered baseband has to follow the message.
+//!
+//! The message is a single tone. The 8th-order filters do not have linear phase, so the
+//! tones of a multi-tone message come out with different delays, which alone holds the
+//! correlation near 0.98 however well the chain works.
+
+use dsp_core::butterworth::ButterworthFilter;
+use dsp_core::signal_gen::{self, AmConfig, Baseband};
+use q3_time_domain_demodulation::demodulator::Demodulator;
+use q3_time_domain_demodulation::spectrum_analyzer;
+
+const SAMPLE_RATE: f64 = 8000.0;
+const OFFSET: f64 = 1500.0;
+const BANDWIDTH: f64 = 1000.0;
+/// Filter start-up samples left out of the comparison
+const SETTLE: usize = 1000;
+/// Largest filter delay (samples) searched when lining the output up with the message
+const MAX_LAG: usize = 100;
+
+/// The carrier line of an AM recording: strongest bin above DC, refined by a parabola
+/// through it and its neighbours, as Q1 does
+fn estimate_offset(samples: &[f64]) -> f64 {
+    let spectrum = spectrum_analyzer::compute_spectrum(samples, SAMPLE_RATE);
+    let (peak, _) = spectrum
+        .iter()
+        .enumerate()
+        .filter(|(_, &(f, _))| f > 10.0)
+        .fold((0, 0.0), |best, (i, &(_, m))| if m > best.1 { (i, m) } else { best });
+    let (left, centre, right) = (spectrum[peak - 1].1, spectrum[peak].1, spectrum[peak + 1].1);
+    let shift = 0.5 * (left - right) / (left - 2.0 * centre + right);
+    spectrum[peak].0 + shift * SAMPLE_RATE / samples.len() as f64
+}
+
+/// Pearson correlation of `output` delayed by `lag` against `message`, past the settling time
+fn correlation_at(message: &[f64], output: &[f64], lag: usize) -> f64 {
+    let n = message.len().min(output.len() - lag);
+    let (x, y) = (&message[SETTLE..n], &output[SETTLE + lag..n + lag]);
+    let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+    let (mx, my) = (mean(x), mean(y));
+    let cov: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let vx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+    let vy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+    cov / (vx * vy).sqrt()
+}
+
+#[test]
+fn synthetic_am_is_recovered() {
+    let config = AmConfig {
+        sample_rate: SAMPLE_RATE,
+        duration: 1.0,
+        offset: OFFSET,
+        modulation_index: Some(0.5),
+        bandwidth: BANDWIDTH,
+        baseband: Baseband::Tones(vec![400.0]),
+        ..AmConfig::default()
+    };
+    let signal = signal_gen::generate(&config).unwrap();
+
+    let f_d = estimate_offset(&signal.samples);
+    assert!((f_d - OFFSET).abs() < 0.5, "estimated f_d = {} Hz", f_d);
+
+    let highpass = ButterworthFilter::highpass(8, f_d, SAMPLE_RATE);
+    let lowpass = ButterworthFilter::lowpass(8, BANDWIDTH, SAMPLE_RATE);
+    let demodulator = Demodulator::new(f_d, SAMPLE_RATE, (highpass.b, highpass.a, lowpass.b, lowpass.a));
+    let output = demodulator.run(&signal.samples).output;
+    assert_eq!(output.len(), signal.samples.len());
+
+    let (lag, correlation) = (0..=MAX_LAG)
+        .map(|lag| (lag, correlation_at(&signal.baseband, &output, lag)))
+        .fold((0, f64::MIN), |best, c| if c.1 > best.1 { c } else { best });
+    assert!(correlation > 0.99, "correlation {:.4} at lag {}", correlation, lag);
+}
@@ -0,0 +1,37 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dsp_core::channels::ChannelMode;
+use q3_time_domain_demodulation::iir_filter;
+use q4_frequency_domain_demodulation::{audio_reader, comparator};
+
+const INPUT: &str = "../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav";
+const FILTERS: &str = "../Q2/output/Q2_filter_coefficients.txt";
+
+/// Comparator metrics between project.wav and its Q2 low-passed version
+///
+/// The two differ by filter ringing and a band limit, like the Q3 and Q4 outputs do, and
+/// have the full recording's length.
+fn comparator_metrics(c: &mut Criterion) {
+    let (mut channels, spec) = audio_reader::read_wav_channels(INPUT, ChannelMode::Mix).expect("read project.wav");
+    let reference = channels.remove(0);
+    let sample_rate = spec.sample_rate as f64;
+    let (_, _, lp_b, lp_a) = iir_filter::read_q2_filters(FILTERS).expect("read Q2 filter coefficients");
+    let degraded = iir_filter::apply_filter(&reference, &lp_b, &lp_a);
+
+    let mut group = c.benchmark_group("comparator");
+    group.sample_size(20);
+    group.bench_function("compare_signals", |b| {
+        b.iter(|| comparator::compare_signals(black_box(&reference), &degraded, sample_rate))
+    });
+    group.bench_function("align", |b| b.iter(|| comparator::align(black_box(&reference), &degraded, sample_rate)));
+    group.bench_function("band_metrics", |b| {
+        b.iter(|| comparator::band_metrics(black_box(&reference), &degraded, sample_rate, &comparator::REPORT_BANDS))
+    });
+    group.bench_function("segmental_metrics", |b| {
+        b.iter(|| comparator::segmental_metrics(black_box(&reference), &degraded, sample_rate, 0.02))
+    });
+    group.bench_function("stoi", |b| b.iter(|| comparator::stoi(black_box(&reference), &degraded, sample_rate)));
+    group.finish();
+}
+
+criterion_group!(benches, comparator_metrics);
+criterion_main!(benches);
@@ -1,35 +1,19 @@
-use hound;
+use dsp_core::error::Result;
+use dsp_core::wav_io::{self, WavWriteOptions};
 
-pub fn write_wav(filename: &str, samples: &[f64], sample_rate: u32) -> Result<(), String> {
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-
-    let mut writer = hound::WavWriter::create(filename, spec)
-        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
-
-    // Normalize samples to prevent clipping
-    let max_val = samples.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
-    let scale = if max_val > 0.0 {
-        0.95 / max_val // Leave some headroom
-    } else {
-        1.0
-    };
-
-    for &sample in samples {
-        let normalized = sample * scale;
-        let sample_i16 = (normalized * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        writer
-            .write_sample(sample_i16)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
-    }
-
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
+/// Write a mono signal with the default options (16-bit PCM, peak at 0.95, TPDF dither)
+pub fn write_wav(filename: &str, samples: &[f64], sample_rate: u32) -> Result<()> {
+    write_wav_channels(filename, &[samples.to_vec()], sample_rate, &WavWriteOptions::default())
+}
 
-    Ok(())
+/// Write one or more channels as an interleaved WAV file
+///
+/// All channels share one normalization gain so their relative levels are kept.
+pub fn write_wav_channels(
+    filename: &str,
+    channels: &[Vec<f64>],
+    sample_rate: u32,
+    options: &WavWriteOptions,
+) -> Result<()> {
+    wav_io::write_channels(filename, channels, sample_rate, options)
 }
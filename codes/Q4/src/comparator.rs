@@ -1,4 +1,21 @@
+use dsp_core::error::{DspError, Result};
+use dsp_core::activity;
+use dsp_core::agc::envelope;
+use dsp_core::carrier_search::CarrierSearchResult;
+use dsp_core::channel::ChannelImpairments;
+use dsp_core::min_max::{band_outline, min_max_columns};
+use dsp_core::noise::NoiseKind;
+use dsp_core::plot_output::PlotTarget;
+use dsp_core::progress::Progress;
+use dsp_core::resample::resample;
+use dsp_core::simd;
+use dsp_core::stft::{Stft, Window};
+use dsp_core::with_drawing_area;
+use log::info;
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::ops::Range;
 
 pub struct ComparisonResult {
     pub mse: f64,
@@ -6,82 +23,52 @@ pub struct ComparisonResult {
     pub correlation: f64,
     pub correlation_normalized: f64,
     pub snr_db: f64,
+    /// RMS difference of the two log power spectra over [`SPECTRAL_BAND`] (dB)
+    pub log_spectral_distance: f64,
+    /// Mean magnitude-squared coherence over [`SPECTRAL_BAND`], 0..=1
+    pub coherence: f64,
+    /// SNR counting only the difference inside [`SPECTRAL_BAND`] (dB)
+    pub band_snr_db: f64,
 }
 
-/// Compare two signals
-pub fn compare_signals(signal1: &[f64], signal2: &[f64]) -> ComparisonResult {
+/// Band the spectral metrics cover (Hz): the baseband the low-pass keeps
+///
+/// Broadband MSE is dominated by the filters' out-of-band ringing, which differs between
+/// Q3's Butterworth and Q4's brick-wall filters but is not part of the demodulated message.
+pub const SPECTRAL_BAND: (f64, f64) = (0.0, 4000.0);
+
+/// Frame length (samples) of the Welch averages behind the spectral metrics
+const WELCH_FRAME: usize = 1024;
+
+/// Compare two signals sampled at `sample_rate`
+pub fn compare_signals(signal1: &[f64], signal2: &[f64], sample_rate: f64) -> ComparisonResult {
     let n = signal1.len().min(signal2.len());
+    let (signal1, signal2) = (&signal1[..n], &signal2[..n]);
     
     // Mean Squared Error
-    let mse: f64 = (0..n)
-        .map(|i| {
-            let diff = signal1[i] - signal2[i];
-            diff * diff
-        })
-        .sum::<f64>() / n as f64;
+    let mse = simd::squared_error(signal1, signal2) / n as f64;
     
     // Maximum absolute difference
-    let max_diff = (0..n)
-        .map(|i| (signal1[i] - signal2[i]).abs())
-        .fold(0.0f64, f64::max);
+    let max_diff = simd::max_abs_diff(signal1, signal2);
     
     // Correlation coefficient (original)
-    let mean1 = signal1[..n].iter().sum::<f64>() / n as f64;
-    let mean2 = signal2[..n].iter().sum::<f64>() / n as f64;
-    
-    let cov: f64 = (0..n)
-        .map(|i| (signal1[i] - mean1) * (signal2[i] - mean2))
-        .sum::<f64>() / n as f64;
-    
-    let var1: f64 = (0..n)
-        .map(|i| (signal1[i] - mean1).powi(2))
-        .sum::<f64>() / n as f64;
-    
-    let var2: f64 = (0..n)
-        .map(|i| (signal2[i] - mean2).powi(2))
-        .sum::<f64>() / n as f64;
-    
-    let correlation = if var1 > 0.0 && var2 > 0.0 {
-        cov / (var1.sqrt() * var2.sqrt())
-    } else {
-        0.0
-    };
+    let correlation = pearson(signal1, signal2);
     
     // Correlation coefficient with amplitude normalization
     // Normalize both signals to [-1, 1] range based on their max absolute value
-    let max_abs1 = signal1[..n].iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
-    let max_abs2 = signal2[..n].iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
+    let max_abs1 = signal1.iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
+    let max_abs2 = signal2.iter().map(|&x| x.abs()).fold(0.0f64, f64::max);
     
     let correlation_normalized = if max_abs1 > 0.0 && max_abs2 > 0.0 {
-        let norm1: Vec<f64> = signal1[..n].iter().map(|&x| x / max_abs1).collect();
-        let norm2: Vec<f64> = signal2[..n].iter().map(|&x| x / max_abs2).collect();
-        
-        let mean_norm1 = norm1.iter().sum::<f64>() / n as f64;
-        let mean_norm2 = norm2.iter().sum::<f64>() / n as f64;
-        
-        let cov_norm: f64 = (0..n)
-            .map(|i| (norm1[i] - mean_norm1) * (norm2[i] - mean_norm2))
-            .sum::<f64>() / n as f64;
-        
-        let var_norm1: f64 = (0..n)
-            .map(|i| (norm1[i] - mean_norm1).powi(2))
-            .sum::<f64>() / n as f64;
-        
-        let var_norm2: f64 = (0..n)
-            .map(|i| (norm2[i] - mean_norm2).powi(2))
-            .sum::<f64>() / n as f64;
-        
-        if var_norm1 > 0.0 && var_norm2 > 0.0 {
-            cov_norm / (var_norm1.sqrt() * var_norm2.sqrt())
-        } else {
-            0.0
-        }
+        let norm1: Vec<f64> = signal1.iter().map(|&x| x / max_abs1).collect();
+        let norm2: Vec<f64> = signal2.iter().map(|&x| x / max_abs2).collect();
+        pearson(&norm1, &norm2)
     } else {
         0.0
     };
     
     // Signal-to-Noise Ratio (treating difference as noise)
-    let signal_power: f64 = signal1[..n].iter().map(|&x| x * x).sum::<f64>() / n as f64;
+    let signal_power = simd::energy(signal1) / n as f64;
     let noise_power = mse;
     
     let snr_db = if noise_power > 0.0 {
@@ -89,6 +76,8 @@ pub fn compare_signals(signal1: &[f64], signal2: &[f64]) -> ComparisonResult {
     } else {
         f64::INFINITY
     };
+
+    let (log_spectral_distance, coherence, band_snr_db) = spectral_metrics(signal1, signal2, sample_rate);
     
     ComparisonResult {
         mse,
@@ -96,38 +85,253 @@ pub fn compare_signals(signal1: &[f64], signal2: &[f64]) -> ComparisonResult {
         correlation,
         correlation_normalized,
         snr_db,
+        log_spectral_distance,
+        coherence,
+        band_snr_db,
+    }
+}
+
+/// Pearson correlation coefficient of two equal-length signals, 0 if either is constant
+fn pearson(signal1: &[f64], signal2: &[f64]) -> f64 {
+    let n = signal1.len() as f64;
+    let mean1 = simd::sum(signal1) / n;
+    let mean2 = simd::sum(signal2) / n;
+    let cov = simd::centered_dot(signal1, mean1, signal2, mean2);
+    let var1 = simd::centered_dot(signal1, mean1, signal1, mean1);
+    let var2 = simd::centered_dot(signal2, mean2, signal2, mean2);
+    if var1 > 0.0 && var2 > 0.0 {
+        cov / (var1.sqrt() * var2.sqrt())
+    } else {
+        0.0
+    }
+}
+
+/// Log-spectral distance (dB), mean coherence and band-limited SNR (dB) over [`SPECTRAL_BAND`]
+fn spectral_metrics(signal1: &[f64], signal2: &[f64], sample_rate: f64) -> (f64, f64, f64) {
+    let spectra = WelchSpectra::compute(signal1, signal2, sample_rate);
+    spectra.band(SPECTRAL_BAND.0, SPECTRAL_BAND.1.min(sample_rate / 2.0))
+}
+
+/// Sub-bands of [`SPECTRAL_BAND`] reported by [`band_metrics`] (Hz), roughly octaves
+pub const REPORT_BANDS: [(f64, f64); 5] =
+    [(0.0, 250.0), (250.0, 500.0), (500.0, 1000.0), (1000.0, 2000.0), (2000.0, 4000.0)];
+
+/// Spectral agreement of two signals within one frequency band
+pub struct BandMetrics {
+    pub low: f64,
+    pub high: f64,
+    /// RMS difference of the two log power spectra in the band (dB)
+    pub log_spectral_distance: f64,
+    /// Mean magnitude-squared coherence in the band, 0..=1
+    pub coherence: f64,
+    /// Band power of `signal1` over the band power of the difference (dB)
+    pub snr_db: f64,
+}
+
+/// The metrics of [`spectral_metrics`] for each band in `bands`, e.g. [`REPORT_BANDS`]
+///
+/// Shows where in the baseband two outputs disagree: a low-pass with a different cutoff
+/// only lowers the top band, a DC blocker only the bottom one.
+pub fn band_metrics(signal1: &[f64], signal2: &[f64], sample_rate: f64, bands: &[(f64, f64)]) -> Vec<BandMetrics> {
+    let n = signal1.len().min(signal2.len());
+    let spectra = WelchSpectra::compute(&signal1[..n], &signal2[..n], sample_rate);
+    bands
+        .iter()
+        .map(|&(low, high)| {
+            let (log_spectral_distance, coherence, snr_db) = spectra.band(low, high.min(sample_rate / 2.0));
+            BandMetrics {
+                low,
+                high,
+                log_spectral_distance,
+                coherence,
+                snr_db,
+            }
+        })
+        .collect()
+}
+
+/// Auto- and cross-spectra of two signals, as Welch averages of Hann frames with 50 % overlap
+struct WelchSpectra {
+    frequencies: Vec<f64>,
+    pxx: Vec<f64>,
+    pyy: Vec<f64>,
+    pxy: Vec<Complex<f64>>,
+}
+
+impl WelchSpectra {
+    fn compute(signal1: &[f64], signal2: &[f64], sample_rate: f64) -> Self {
+        let stft1 = Stft::compute(signal1, sample_rate, WELCH_FRAME, WELCH_FRAME / 2, Window::Hann);
+        let stft2 = Stft::compute(signal2, sample_rate, WELCH_FRAME, WELCH_FRAME / 2, Window::Hann);
+        let average = |f: &dyn Fn(Complex<f64>, Complex<f64>) -> Complex<f64>| -> Vec<Complex<f64>> {
+            (0..stft1.frequencies.len())
+                .map(|k| {
+                    let frames = stft1.spectra.iter().zip(&stft2.spectra);
+                    frames.map(|(a, b)| f(a[k], b[k])).sum::<Complex<f64>>() / stft1.spectra.len().max(1) as f64
+                })
+                .collect()
+        };
+        WelchSpectra {
+            pxx: average(&|a, _| Complex::new(a.norm_sqr(), 0.0)).iter().map(|c| c.re).collect(),
+            pyy: average(&|_, b| Complex::new(b.norm_sqr(), 0.0)).iter().map(|c| c.re).collect(),
+            pxy: average(&|a, b| a * b.conj()),
+            frequencies: stft1.frequencies,
+        }
+    }
+
+    /// Log-spectral distance (dB), mean coherence and SNR (dB) over `low..=high` Hz
+    ///
+    /// The difference spectrum follows from the averages as Pxx + Pyy − 2·Re Pxy.
+    fn band(&self, low: f64, high: f64) -> (f64, f64, f64) {
+        let (pxx, pyy, pxy) = (&self.pxx, &self.pyy, &self.pxy);
+        let bins: Vec<usize> = (0..self.frequencies.len())
+            .filter(|&k| self.frequencies[k] >= low && self.frequencies[k] <= high)
+            .collect();
+
+        // Relative floor so empty bins neither divide by zero nor dominate the log distance
+        let floor = pxx.iter().chain(pyy).fold(0.0f64, |a, &b| a.max(b)) * 1e-12 + f64::MIN_POSITIVE;
+        let log_ratio = |k: usize| 10.0 * ((pxx[k] + floor) / (pyy[k] + floor)).log10();
+        let lsd = (bins.iter().map(|&k| log_ratio(k).powi(2)).sum::<f64>() / bins.len().max(1) as f64).sqrt();
+
+        let coherent: Vec<f64> = bins
+            .iter()
+            .filter(|&&k| pxx[k] > floor && pyy[k] > floor)
+            .map(|&k| pxy[k].norm_sqr() / (pxx[k] * pyy[k]))
+            .collect();
+        let coherence = if coherent.is_empty() { 0.0 } else { coherent.iter().sum::<f64>() / coherent.len() as f64 };
+
+        let signal_power: f64 = bins.iter().map(|&k| pxx[k]).sum();
+        let noise_power: f64 = bins.iter().map(|&k| (pxx[k] + pyy[k] - 2.0 * pxy[k].re).max(0.0)).sum();
+        let snr_db = if noise_power > 0.0 {
+            10.0 * (signal_power / noise_power).log10()
+        } else {
+            f64::INFINITY
+        };
+        (lsd, coherence, snr_db)
+    }
+}
+
+/// Best lag between two signals and the metrics once it is removed
+pub struct Alignment {
+    /// Samples `signal2` lags `signal1` by; negative when it leads
+    pub lag: isize,
+    /// Normalized cross-correlation at `lag`
+    pub correlation: f64,
+    /// Samples the shifted signals have in common
+    pub overlap: usize,
+    /// [`compare_signals`] over the overlapping, lag-aligned samples
+    pub aligned: ComparisonResult,
+}
+
+/// Find the lag that maximizes the cross-correlation of two signals and compare them there
+///
+/// Q3's IIR filters delay the output by their group delay while Q4's ideal filters add none,
+/// so sample-wise metrics of the raw signals mostly measure that delay. The cross-correlation
+/// is computed by FFT, zero-padded so the lags do not wrap around.
+pub fn align(signal1: &[f64], signal2: &[f64], sample_rate: f64) -> Alignment {
+    let (n1, n2) = (signal1.len(), signal2.len());
+    if n1 == 0 || n2 == 0 {
+        return Alignment {
+            lag: 0,
+            correlation: 0.0,
+            overlap: 0,
+            aligned: compare_signals(signal1, signal2, sample_rate),
+        };
+    }
+
+    let fft_len = (n1 + n2).next_power_of_two();
+    let spectrum = |signal: &[f64]| {
+        let mut buffer: Vec<Complex<f64>> = signal
+            .iter()
+            .map(|&x| Complex::new(x, 0.0))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(fft_len)
+            .collect();
+        FftPlanner::new().plan_fft_forward(fft_len).process(&mut buffer);
+        buffer
+    };
+    // r[d] = Σ signal2[i]·signal1[i - d], with negative lags wrapped to the end of the buffer
+    let mut cross: Vec<Complex<f64>> = spectrum(signal2)
+        .iter()
+        .zip(spectrum(signal1).iter())
+        .map(|(a, b)| a * b.conj())
+        .collect();
+    FftPlanner::new().plan_fft_inverse(fft_len).process(&mut cross);
+
+    let lag_of = |k: usize| if k < n2 { k as isize } else { k as isize - fft_len as isize };
+    let (lag, peak) = (0..fft_len)
+        .filter(|&k| k < n2 || fft_len - k < n1)
+        .map(|k| (lag_of(k), cross[k].re))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    let norm = (simd::energy(signal1) * simd::energy(signal2)).sqrt();
+    // FFT round trip scales by the transform length
+    let correlation = if norm > 0.0 { peak / fft_len as f64 / norm } else { 0.0 };
+
+    let (shifted1, shifted2) = shift_by_lag(lag, signal1, signal2);
+    Alignment {
+        lag,
+        correlation,
+        overlap: shifted1.len().min(shifted2.len()),
+        aligned: compare_signals(shifted1, shifted2, sample_rate),
     }
 }
 
-/// Save comparison results to file
-pub fn save_comparison(result: &ComparisonResult, filename: &str) {
+impl Alignment {
+    /// The two signals with the lag removed, starting at their first common sample
+    pub fn apply<'a>(&self, signal1: &'a [f64], signal2: &'a [f64]) -> (&'a [f64], &'a [f64]) {
+        shift_by_lag(self.lag, signal1, signal2)
+    }
+}
+
+fn shift_by_lag<'a>(lag: isize, signal1: &'a [f64], signal2: &'a [f64]) -> (&'a [f64], &'a [f64]) {
+    let skip = |signal: &'a [f64], k: usize| &signal[k.min(signal.len())..];
+    if lag >= 0 {
+        (signal1, skip(signal2, lag as usize))
+    } else {
+        (skip(signal1, lag.unsigned_abs()), signal2)
+    }
+}
+
+/// Save comparison results to file, raw and after removing the lag found by [`align`]
+///
+/// `intelligibility` is the [`stoi`] of Q3's output against Q4's.
+pub fn save_comparison(result: &ComparisonResult, alignment: &Alignment, intelligibility: f64, filename: &str) -> Result<()> {
     let mut content = String::new();
     content.push_str("Q4 vs Q3 Comparison Results\n");
     content.push_str("============================\n\n");
-    content.push_str(&format!("Mean Squared Error (MSE): {:.6e}\n", result.mse));
-    content.push_str(&format!("Root Mean Squared Error (RMSE): {:.6e}\n", result.mse.sqrt()));
-    content.push_str(&format!("Maximum absolute difference: {:.6}\n", result.max_diff));
-    content.push_str(&format!("Correlation coefficient (original): {:.6}\n", result.correlation));
-    content.push_str(&format!("Correlation coefficient (normalized): {:.6}\n", result.correlation_normalized));
-    content.push_str(&format!("Signal-to-Noise Ratio: {:.2} dB\n\n", result.snr_db));
+    push_metrics(&mut content, result);
+
+    let aligned = &alignment.aligned;
+    content.push_str("Lag-Aligned Comparison\n");
+    content.push_str("----------------------\n");
+    content.push_str(&format!(
+        "Q3 lags Q4 by {} samples (cross-correlation peak {:.6}); metrics over the {} overlapping samples:\n",
+        alignment.lag, alignment.correlation, alignment.overlap
+    ));
+    push_metrics(&mut content, aligned);
+    content.push_str(&format!("Intelligibility (STOI of Q3 against Q4): {:.4}\n\n", intelligibility));
     
     content.push_str("Interpretation:\n");
     content.push_str("---------------\n");
     
-    // Use normalized correlation for interpretation (more accurate for waveform similarity)
-    if result.correlation_normalized > 0.99 {
-        content.push_str("✓ Excellent correlation (normalized) - waveforms are nearly identical\n");
-    } else if result.correlation_normalized > 0.95 {
-        content.push_str("✓ Good correlation (normalized) - waveforms are very similar\n");
-    } else if result.correlation_normalized > 0.8 {
-        content.push_str("~ Moderate correlation (normalized) - some waveform differences\n");
+    // Use normalized correlation for interpretation (more accurate for waveform similarity),
+    // after alignment so the filter delay is not counted as a shape difference
+    if aligned.correlation_normalized > 0.99 {
+        content.push_str("✓ Excellent correlation (normalized, lag-aligned) - waveforms are nearly identical\n");
+    } else if aligned.correlation_normalized > 0.95 {
+        content.push_str("✓ Good correlation (normalized, lag-aligned) - waveforms are very similar\n");
+    } else if aligned.correlation_normalized > 0.8 {
+        content.push_str("~ Moderate correlation (normalized, lag-aligned) - some waveform differences\n");
     } else {
-        content.push_str("✗ Low correlation (normalized) - significant waveform differences\n");
+        content.push_str("✗ Low correlation (normalized, lag-aligned) - significant waveform differences\n");
     }
     
     content.push_str(&format!("\nNote: Normalized correlation ({:.3}) adjusts for amplitude differences,\n", result.correlation_normalized));
     content.push_str("      providing a better measure of waveform shape similarity.\n");
     content.push_str(&format!("      Original correlation ({:.3}) is affected by both amplitude and shape.\n", result.correlation));
+    content.push_str("      The raw metrics include the delay of Q3's IIR filters; the lag-aligned ones\n");
+    content.push_str("      compare the waveforms themselves.\n");
     
     if result.snr_db > 40.0 {
         content.push_str("✓ Excellent SNR - minimal difference\n");
@@ -151,119 +355,1678 @@ pub fn save_comparison(result: &ComparisonResult, filename: &str) {
     content.push_str("  - No phase distortion from filters\n");
     content.push_str("  - Sharp cutoff\n");
     
-    std::fs::write(filename, content).expect("Failed to save comparison");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))
+}
+
+/// Time- and frequency-domain metrics of one comparison, one per line
+/// Save the same comparison as JSON for scripts and the report generator
+///
+/// Holds the raw and lag-aligned [`ComparisonResult`]s, the alignment, the STOI score and
+/// the per-band metrics of the aligned signals. Metrics that are infinite (identical
+/// signals) are written as `null`, since JSON has no infinity.
+pub fn save_comparison_json(
+    result: &ComparisonResult,
+    alignment: &Alignment,
+    bands: &[BandMetrics],
+    intelligibility: f64,
+    sample_rate: f64,
+    filename: &str,
+) -> Result<()> {
+    let mut json = String::from("{\n");
+    json.push_str(&format!("  \"sample_rate\": {},\n", json_number(sample_rate)));
+    json.push_str(&format!("  \"raw\": {},\n", comparison_json(result)));
+    json.push_str(&format!(
+        "  \"alignment\": {{\"lag_samples\": {}, \"lag_ms\": {}, \"correlation\": {}, \"overlap\": {}}},\n",
+        alignment.lag,
+        json_number(alignment.lag as f64 / sample_rate * 1000.0),
+        json_number(alignment.correlation),
+        alignment.overlap
+    ));
+    json.push_str(&format!("  \"aligned\": {},\n", comparison_json(&alignment.aligned)));
+    json.push_str(&format!("  \"stoi\": {},\n", json_number(intelligibility)));
+    json.push_str("  \"bands\": [");
+    for (i, band) in bands.iter().enumerate() {
+        json.push_str(if i > 0 { ",\n    " } else { "\n    " });
+        json.push_str(&format!(
+            "{{\"low_hz\": {}, \"high_hz\": {}, \"log_spectral_distance_db\": {}, \"coherence\": {}, \"snr_db\": {}}}",
+            json_number(band.low),
+            json_number(band.high),
+            json_number(band.log_spectral_distance),
+            json_number(band.coherence),
+            json_number(band.snr_db)
+        ));
+    }
+    json.push_str(if bands.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+
+    std::fs::write(filename, json).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// One [`ComparisonResult`] as a JSON object
+fn comparison_json(result: &ComparisonResult) -> String {
+    format!(
+        "{{\"mse\": {}, \"max_diff\": {}, \"correlation\": {}, \"correlation_normalized\": {}, \"snr_db\": {}, \"log_spectral_distance_db\": {}, \"coherence\": {}, \"band_snr_db\": {}}}",
+        json_number(result.mse),
+        json_number(result.max_diff),
+        json_number(result.correlation),
+        json_number(result.correlation_normalized),
+        json_number(result.snr_db),
+        json_number(result.log_spectral_distance),
+        json_number(result.coherence),
+        json_number(result.band_snr_db)
+    )
+}
+
+/// A finite number as written by `Display` (never exponent notation), anything else as `null`
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
 }
 
-/// Plot full-time comparison of two signals (all samples)
-pub fn plot_full_comparison(signal1: &[f64], signal2: &[f64], filename: &str) {
+fn push_metrics(content: &mut String, result: &ComparisonResult) {
+    let (low, high) = SPECTRAL_BAND;
+    content.push_str(&format!("Mean Squared Error (MSE): {:.6e}\n", result.mse));
+    content.push_str(&format!("Root Mean Squared Error (RMSE): {:.6e}\n", result.mse.sqrt()));
+    content.push_str(&format!("Maximum absolute difference: {:.6}\n", result.max_diff));
+    content.push_str(&format!("Correlation coefficient (original): {:.6}\n", result.correlation));
+    content.push_str(&format!("Correlation coefficient (normalized): {:.6}\n", result.correlation_normalized));
+    content.push_str(&format!("Signal-to-Noise Ratio: {:.2} dB\n", result.snr_db));
+    content.push_str(&format!("Log-spectral distance ({:.0}-{:.0} Hz): {:.2} dB\n", low, high, result.log_spectral_distance));
+    content.push_str(&format!("Mean coherence ({:.0}-{:.0} Hz): {:.6}\n", low, high, result.coherence));
+    content.push_str(&format!("Band-limited SNR ({:.0}-{:.0} Hz): {:.2} dB\n\n", low, high, result.band_snr_db));
+}
+
+/// Visible samples above which comparison plots draw each signal's envelope instead of
+/// every sample, about three samples per horizontal pixel
+const ENVELOPE_THRESHOLD: usize = 4000;
+
+/// Plot full-time comparison of two signals (all samples), with time in seconds
+pub fn plot_full_comparison(signal1: &[f64], signal2: &[f64], sample_rate: f64, target: impl Into<PlotTarget>) -> Result<()> {
+    let duration = signal1.len().min(signal2.len()) as f64 / sample_rate;
+    plot_waveforms(
+        signal1,
+        signal2,
+        sample_rate,
+        (0.0, duration),
+        target.into(),
+        "Q4 vs Q3 Signal Comparison (Full Waveform)",
+        (1600, 600),
+    )
+}
+
+/// Plot comparison of two signals over `time_range` seconds, by default the first 2000
+/// samples for detail
+pub fn plot_comparison(
+    signal1: &[f64],
+    signal2: &[f64],
+    sample_rate: f64,
+    time_range: Option<(f64, f64)>,
+    target: impl Into<PlotTarget>,
+) -> Result<()> {
+    let time_range = time_range.unwrap_or((0.0, 2000.0 / sample_rate));
+    plot_waveforms(
+        signal1,
+        signal2,
+        sample_rate,
+        time_range,
+        target.into(),
+        "Q4 vs Q3 Signal Comparison (Detail View)",
+        (1200, 800),
+    )
+}
+
+/// Plot the first and last `seconds` of the output without and with the edge guard, side by
+/// side, so the wrap-around clicks it removes are visible
+pub fn plot_edges(plain: &[f64], guarded: &[f64], sample_rate: f64, seconds: f64, target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let n = plain.len().min(guarded.len());
+    let len = ((seconds * sample_rate) as usize).clamp(1, n.max(1));
+    if n < 2 {
+        return Ok(());
+    }
+    let time = |i: usize| i as f64 / sample_rate;
+
+    with_drawing_area!(target, (1600, 600), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let root = root.titled("Output Edges Without and With the Edge Guard", ("sans-serif", 40)).map_err(DspError::plot)?;
+        let panels = root.split_evenly((1, 2));
+        for (area, (first, last), title) in [
+            (&panels[0], (0, len), "Start of file"),
+            (&panels[1], (n - len, n), "End of file"),
+        ] {
+            let max_val = plain[first..last]
+                .iter()
+                .chain(&guarded[first..last])
+                .fold(0.0f64, |max, &x| max.max(x.abs()))
+                .max(1e-12);
+            let mut chart = ChartBuilder::on(area)
+                .caption(title, ("sans-serif", 28))
+                .margin(20)
+                .x_label_area_size(50)
+                .y_label_area_size(70)
+                .build_cartesian_2d(time(first)..time(last - 1), -max_val * 1.1..max_val * 1.1)
+                .map_err(DspError::plot)?;
+            chart
+                .configure_mesh()
+                .x_desc("Time (s)")
+                .y_desc("Amplitude")
+                .draw()
+                .map_err(DspError::plot)?;
+            for (signal, label, color) in [(plain, "Plain FFT block", RED), (guarded, "With edge guard", BLUE)] {
+                chart
+                    .draw_series(LineSeries::new((first..last).map(|i| (time(i), signal[i])), &color))
+                    .map_err(DspError::plot)?
+                    .label(label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            }
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()
+                .map_err(DspError::plot)?;
+        }
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// One line per candidate carrier of a `--carrier-search`
+pub fn save_carrier_search_csv(search: &CarrierSearchResult, filename: &str) -> Result<()> {
+    let mut content = String::from("frequency_hz,offset_hz,concentration\n");
+    for c in &search.candidates {
+        content.push_str(&format!(
+            "{:.4},{:+.4},{:.6}\n",
+            c.frequency,
+            c.frequency - search.estimate,
+            c.concentration
+        ));
+    }
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Baseband concentration over the candidate carriers, with Q1's estimate (grey) and the
+/// chosen carrier (red) marked
+pub fn plot_carrier_search(search: &CarrierSearchResult, target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let (Some(first), Some(last)) = (search.candidates.first(), search.candidates.last()) else {
+        return Ok(());
+    };
+    if first.frequency >= last.frequency {
+        return Ok(());
+    }
+    let chosen = search.peak();
+    let low = search.candidates.iter().fold(1.0f64, |min, c| min.min(c.concentration));
+    let y_range = (low - 0.02).max(0.0)..1.01;
+
+    with_drawing_area!(target, (1200, 700), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Carrier Fine Search: Baseband Energy Concentration", ("sans-serif", 36))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(70)
+            .build_cartesian_2d(first.frequency..last.frequency, y_range.clone())
+            .map_err(DspError::plot)?;
+        chart
+            .configure_mesh()
+            .x_desc("Candidate carrier (Hz)")
+            .y_desc("Energy on the strongest axis")
+            .draw()
+            .map_err(DspError::plot)?;
+        for (f, label, color) in [
+            (search.estimate, format!("Q1 estimate {:.4} Hz", search.estimate), RGBColor(128, 128, 128)),
+            (chosen, format!("Chosen {:.4} Hz", chosen), RED),
+        ] {
+            chart
+                .draw_series(LineSeries::new(vec![(f, y_range.start), (f, y_range.end)], color.stroke_width(2)))
+                .map_err(DspError::plot)?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+        chart
+            .draw_series(LineSeries::new(
+                search.candidates.iter().map(|c| (c.frequency, c.concentration)),
+                BLUE.stroke_width(2),
+            ))
+            .map_err(DspError::plot)?;
+        chart
+            .draw_series(search.candidates.iter().map(|c| Circle::new((c.frequency, c.concentration), 3, BLUE.filled())))
+            .map_err(DspError::plot)?;
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Read `--compare-range <start>:<end>` (seconds), the window of the detailed comparison plot
+pub fn time_range_from_args(args: &[String]) -> Result<Option<(f64, f64)>> {
+    let mut range = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg != "--compare-range" {
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| DspError::Argument("--compare-range requires <start>:<end> in seconds".to_string()))?;
+        let invalid = || DspError::Argument(format!("Invalid time range '{}' (expected <start>:<end> in seconds)", value));
+        let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+        let start: f64 = start.trim().parse().map_err(|_| invalid())?;
+        let end: f64 = end.trim().parse().map_err(|_| invalid())?;
+        if start < 0.0 || end <= start {
+            return Err(invalid());
+        }
+        range = Some((start, end));
+    }
+    Ok(range)
+}
+
+/// Both signals between `start` and `end` seconds
+///
+/// Windows with more samples than the plot is wide are drawn as a min/max band per pixel
+/// column, and long windows get a ±envelope (5 ms attack, 50 ms release) on top so the
+/// loudness of the two outputs can be compared at a glance.
+fn plot_waveforms(
+    signal1: &[f64],
+    signal2: &[f64],
+    sample_rate: f64,
+    (start, end): (f64, f64),
+    target: PlotTarget,
+    caption: &str,
+    default_size: (u32, u32),
+) -> Result<()> {
     let n = signal1.len().min(signal2.len());
-    
-    let root = BitMapBackend::new(filename, (1600, 600)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    
-    let max_val = signal1[..n].iter()
-        .chain(signal2[..n].iter())
-        .fold(0.0f64, |max, &x| max.max(x.abs()));
-    
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Q4 vs Q3 Signal Comparison (Full Waveform)", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0..n, -max_val*1.1..max_val*1.1)
-        .unwrap();
-    
-    chart
-        .configure_mesh()
-        .x_desc("Sample")
-        .y_desc("Amplitude")
-        .draw()
-        .unwrap();
-    
-    // Plot Q4 signal
-    chart
-        .draw_series(LineSeries::new(
-            (0..n).map(|i| (i, signal1[i])),
-            &BLUE,
-        ))
-        .unwrap()
-        .label("Q4 (Frequency-domain)")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
-    
-    // Plot Q3 signal  
-    chart
-        .draw_series(LineSeries::new(
-            (0..n).map(|i| (i, signal2[i])),
-            &RED,
-        ))
-        .unwrap()
-        .label("Q3 (Time-domain)")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
-    
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw()
-        .unwrap();
-    
-    root.present().unwrap();
-    println!("  Saved: {}", filename);
+    let first = ((start * sample_rate).floor() as usize).min(n.saturating_sub(1));
+    let last = ((end * sample_rate).ceil() as usize).clamp(first + 1, n);
+    let (start, end) = (first as f64 / sample_rate, last as f64 / sample_rate);
+    let use_envelope = last - first > ENVELOPE_THRESHOLD;
+
+    with_drawing_area!(target, default_size, |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+
+        let max_val = signal1[first..last]
+            .iter()
+            .chain(signal2[first..last].iter())
+            .fold(0.0f64, |max, &x| max.max(x.abs()))
+            .max(1e-12);
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(caption, ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(start..end, -max_val * 1.1..max_val * 1.1)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc(if use_envelope { "Amplitude (envelope)" } else { "Amplitude" })
+            .draw()
+            .map_err(DspError::plot)?;
+
+        let time = |i: usize| i as f64 / sample_rate;
+        let width = chart.plotting_area().dim_in_pixel().0 as usize;
+        for (signal, label, color) in [
+            (signal1, "Q4 (Frequency-domain)", BLUE),
+            (signal2, "Q3 (Time-domain)", RED),
+        ] {
+            if last - first > width {
+                let columns = min_max_columns(&signal[first..last], width);
+                let outline = band_outline(&columns, |i| time(first) + i / sample_rate);
+                chart
+                    .draw_series(std::iter::once(Polygon::new(outline.clone(), color.mix(0.3).filled())))
+                    .map_err(DspError::plot)?;
+                chart
+                    .draw_series(std::iter::once(PathElement::new(outline, color.mix(0.5))))
+                    .map_err(DspError::plot)?
+                    .label(label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            } else {
+                chart
+                    .draw_series(LineSeries::new((first..last).map(|i| (time(i), signal[i])), &color))
+                    .map_err(DspError::plot)?
+                    .label(label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            }
+            if use_envelope {
+                // The follower runs over the whole signal so the window starts settled
+                let envelope = envelope(&signal[..last], sample_rate, 0.005, 0.05);
+                let step = ((last - first) / width.max(1)).max(1);
+                let upper: Vec<(f64, f64)> = (first..last).step_by(step).map(|i| (time(i), envelope[i])).collect();
+                chart
+                    .draw_series(LineSeries::new(upper.iter().copied(), color.stroke_width(2)))
+                    .map_err(DspError::plot)?;
+                chart
+                    .draw_series(LineSeries::new(upper.iter().map(|&(t, e)| (t, -e)), color.stroke_width(2)))
+                    .map_err(DspError::plot)?;
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
 }
 
-/// Plot comparison of two signals (first 2000 samples for detail)
-pub fn plot_comparison(signal1: &[f64], signal2: &[f64], filename: &str) {
-    let n = signal1.len().min(signal2.len()).min(2000); // Plot first 2000 samples
-    
-    let root = BitMapBackend::new(filename, (1200, 800)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    
-    let max_val = signal1[..n].iter()
-        .chain(signal2[..n].iter())
-        .fold(0.0f64, |max, &x| max.max(x.abs()));
-    
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Q4 vs Q3 Signal Comparison (Detail View)", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0..n, -max_val*1.1..max_val*1.1)
-        .unwrap();
-    
-    chart
-        .configure_mesh()
-        .x_desc("Sample")
-        .y_desc("Amplitude")
-        .draw()
-        .unwrap();
-    
-    // Plot Q4 signal
-    chart
-        .draw_series(LineSeries::new(
-            (0..n).map(|i| (i, signal1[i])),
-            &BLUE,
-        ))
-        .unwrap()
-        .label("Q4 (Frequency-domain)")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
-    
-    // Plot Q3 signal
-    chart
-        .draw_series(LineSeries::new(
-            (0..n).map(|i| (i, signal2[i])),
-            &RED,
-        ))
-        .unwrap()
-        .label("Q3 (Time-domain)")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
-    
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw()
-        .unwrap();
-    
-    root.present().unwrap();
-    println!("  Saved: {}", filename);
+/// Plot where two signals disagree: the difference signal1 - signal2 over time, and below it
+/// the spectrum of the difference against the spectrum of signal1
+pub fn plot_error(signal1: &[f64], signal2: &[f64], sample_rate: f64, target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let n = signal1.len().min(signal2.len());
+    let error: Vec<f64> = (0..n).map(|i| signal1[i] - signal2[i]).collect();
+    let error_db = magnitude_spectrum_db(&error, sample_rate);
+    let reference_db = magnitude_spectrum_db(&signal1[..n], sample_rate);
+
+    with_drawing_area!(target, (1200, 900), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let (upper, lower) = root.split_vertically(450);
+
+        let max_error = error.iter().fold(0.0f64, |max, &x| max.max(x.abs())).max(1e-12);
+        let duration = n as f64 / sample_rate;
+        let mut chart = ChartBuilder::on(&upper)
+            .caption("Q4 - Q3 Difference Waveform", ("sans-serif", 30))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(80)
+            .build_cartesian_2d(0.0..duration, -max_error * 1.1..max_error * 1.1)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc("Difference")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        let width = chart.plotting_area().dim_in_pixel().0 as usize;
+        if n > width {
+            let outline = band_outline(&min_max_columns(&error, width), |i| i / sample_rate);
+            chart
+                .draw_series(std::iter::once(Polygon::new(outline.clone(), RED.filled())))
+                .map_err(DspError::plot)?;
+            chart.draw_series(std::iter::once(PathElement::new(outline, RED))).map_err(DspError::plot)?;
+        } else {
+            chart
+                .draw_series(LineSeries::new(
+                    error.iter().enumerate().map(|(i, &e)| (i as f64 / sample_rate, e)),
+                    &RED,
+                ))
+                .map_err(DspError::plot)?;
+        }
+
+        // dB range from the louder of the two spectra down 100 dB
+        let top = error_db
+            .iter()
+            .chain(reference_db.iter())
+            .fold(f64::NEG_INFINITY, |max, &(_, db)| max.max(db));
+        let bottom = top - 100.0;
+        let mut chart = ChartBuilder::on(&lower)
+            .caption("Difference Spectrum", ("sans-serif", 30))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(80)
+            .build_cartesian_2d(0.0..sample_rate / 2.0, bottom..top + 5.0)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Frequency (Hz)")
+            .y_desc("Magnitude (dB)")
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .draw()
+            .map_err(DspError::plot)?;
+
+        chart
+            .draw_series(LineSeries::new(
+                reference_db.iter().map(|&(f, db)| (f, db.max(bottom))),
+                BLUE.mix(0.5),
+            ))
+            .map_err(DspError::plot)?
+            .label("Q4 output")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.mix(0.5)));
+
+        chart
+            .draw_series(LineSeries::new(
+                error_db.iter().map(|&(f, db)| (f, db.max(bottom))),
+                &RED,
+            ))
+            .map_err(DspError::plot)?
+            .label("Q4 - Q3")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// One-sided spectrum (Hz, dB) of signal1 - signal2 over their common length
+pub fn error_spectrum(signal1: &[f64], signal2: &[f64], sample_rate: f64) -> Vec<(f64, f64)> {
+    let n = signal1.len().min(signal2.len());
+    let error: Vec<f64> = (0..n).map(|i| signal1[i] - signal2[i]).collect();
+    magnitude_spectrum_db(&error, sample_rate)
+}
+
+/// Hann-windowed one-sided spectrum, scaled so a sinusoid of amplitude A reads 20·log10(A) dB
+fn magnitude_spectrum_db(signal: &[f64], sample_rate: f64) -> Vec<(f64, f64)> {
+    let n = signal.len();
+    let mut spectrum: Vec<Complex<f64>> = signal
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+            Complex::new(x * window, 0.0)
+        })
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+
+    // The Hann window halves a sinusoid's peak, and the one-sided spectrum halves it again
+    let scale = 4.0 / n as f64;
+    (0..=n / 2)
+        .map(|k| (k as f64 * sample_rate / n as f64, 20.0 * (spectrum[k].norm() * scale).max(1e-12).log10()))
+        .collect()
+}
+
+/// Frame length (s) of the segmental metrics, short enough to isolate a transient
+pub const SEGMENT_SECONDS: f64 = 0.02;
+
+/// Per-frame SNRs are clamped to this range (dB) before averaging, as is usual for
+/// segmental SNR, so silent or identical frames do not dominate the mean
+const SEGMENT_SNR_RANGE: (f64, f64) = (-10.0, 35.0);
+
+/// Agreement of two signals within one frame
+#[derive(Debug, Clone, Copy)]
+pub struct FrameMetrics {
+    /// Centre of the frame (s)
+    pub time: f64,
+    /// `signal1` power over difference power in the frame (dB); infinite if they match
+    pub snr_db: f64,
+    /// Correlation coefficient within the frame; 0 when either is constant
+    pub correlation: f64,
+}
+
+/// SNR and correlation of consecutive, non-overlapping frames of `frame_seconds`
+///
+/// Whole-signal metrics average a filter transient at the start or clipping at a few peaks
+/// away; per frame they stand out. A trailing partial frame is dropped.
+pub fn segmental_metrics(signal1: &[f64], signal2: &[f64], sample_rate: f64, frame_seconds: f64) -> Vec<FrameMetrics> {
+    let n = signal1.len().min(signal2.len());
+    let frame = ((frame_seconds * sample_rate).round() as usize).max(2);
+    (0..n / frame)
+        .map(|k| {
+            let (a, b) = (&signal1[k * frame..(k + 1) * frame], &signal2[k * frame..(k + 1) * frame]);
+            let signal_power = simd::energy(a);
+            let noise_power = simd::squared_error(a, b);
+            let snr_db = if noise_power > 0.0 {
+                10.0 * (signal_power / noise_power).log10()
+            } else {
+                f64::INFINITY
+            };
+
+            let correlation = pearson(a, b);
+
+            FrameMetrics {
+                time: (k as f64 + 0.5) * frame as f64 / sample_rate,
+                snr_db,
+                correlation,
+            }
+        })
+        .collect()
+}
+
+/// Segmental SNR (dB): the mean per-frame SNR, each clamped to -10..35 dB
+pub fn segmental_snr(frames: &[FrameMetrics]) -> f64 {
+    if frames.is_empty() {
+        return f64::NAN;
+    }
+    let (low, high) = SEGMENT_SNR_RANGE;
+    frames.iter().map(|f| f.snr_db.clamp(low, high)).sum::<f64>() / frames.len() as f64
+}
+
+/// [`compare_signals`] over the active `segments` only (sample ranges of both signals,
+/// e.g. from [`ActivityDetector::detect`](dsp_core::activity::ActivityDetector::detect)),
+/// joined end to end
+///
+/// Silent stretches in both outputs agree trivially and inflate the correlation; their
+/// residual noise, on the other hand, pulls the SNR down. Leaving them out gives the
+/// agreement on the programme material itself. The joins add a little broadband content
+/// to the spectral metrics, one discontinuity per segment.
+pub fn compare_active(signal1: &[f64], signal2: &[f64], sample_rate: f64, segments: &[Range<usize>]) -> ComparisonResult {
+    compare_signals(&activity::concatenate(signal1, segments), &activity::concatenate(signal2, segments), sample_rate)
+}
+
+/// Save the active-segment comparison next to the comparison over the whole overlap
+///
+/// `length` is the number of samples `segments` were found in.
+pub fn save_active_comparison(
+    full: &ComparisonResult,
+    active: &ComparisonResult,
+    segments: &[Range<usize>],
+    length: usize,
+    sample_rate: f64,
+    filename: &str,
+) -> Result<()> {
+    let active_samples = activity::active_samples(segments);
+    let mut content = String::new();
+    content.push_str("Q4 vs Q3 Comparison over Active Segments\n");
+    content.push_str("========================================\n\n");
+    content.push_str(&format!(
+        "{} active segments, {:.3} s of {:.3} s ({:.1} %):\n",
+        segments.len(),
+        active_samples as f64 / sample_rate,
+        length as f64 / sample_rate,
+        100.0 * active_samples as f64 / length.max(1) as f64
+    ));
+    for segment in segments {
+        content.push_str(&format!(
+            "  {:.3}-{:.3} s\n",
+            segment.start as f64 / sample_rate,
+            segment.end as f64 / sample_rate
+        ));
+    }
+    content.push_str("\nActive segments only\n");
+    content.push_str("--------------------\n");
+    push_metrics(&mut content, active);
+    content.push_str("Whole lag-aligned overlap\n");
+    content.push_str("-------------------------\n");
+    push_metrics(&mut content, full);
+    content.push_str("Segments are found on Q4's lag-aligned output: 20 ms frames more than the\n");
+    content.push_str("threshold above the noise floor (10th percentile of the frame levels), widened\n");
+    content.push_str("by 0.1 s on each side. When the output has no clear pauses all of it is active\n");
+    content.push_str("and the two sets of metrics coincide.\n");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))
+}
+
+/// Per-frame SNR (top) and correlation (bottom) against time, with the segmental SNR marked
+///
+/// The SNR trace is not clamped, so a gain difference between the signals shows as an
+/// offset; frames where they match exactly are drawn at the top of the axis.
+pub fn plot_segmental(frames: &[FrameMetrics], target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let finite = || frames.iter().map(|f| f.snr_db).filter(|v| v.is_finite());
+    let mean_snr = segmental_snr(frames);
+    let low = finite().fold(mean_snr, f64::min).min(0.0) - 5.0;
+    let high = finite().fold(mean_snr, f64::max).max(0.0) + 5.0;
+    // The first frame's centre is half a frame in, so this reaches the last frame's end
+    let duration = frames.last().map(|f| f.time + frames[0].time).unwrap_or(1.0).max(1e-3);
+
+    with_drawing_area!(target, (1200, 900), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let (upper, lower) = root.split_vertically(450);
+
+        let mut chart = ChartBuilder::on(&upper)
+            .caption("Q4 vs Q3 Segmental SNR", ("sans-serif", 30))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(80)
+            .build_cartesian_2d(0.0..duration, low..high)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc("SNR per frame (dB)")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        chart
+            .draw_series(LineSeries::new(
+                frames.iter().map(|f| (f.time, f.snr_db.min(high))),
+                &BLUE,
+            ))
+            .map_err(DspError::plot)?
+            .label(format!("{:.0} ms frames", SEGMENT_SECONDS * 1000.0))
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+
+        if mean_snr.is_finite() {
+            chart
+                .draw_series(LineSeries::new(vec![(0.0, mean_snr), (duration, mean_snr)], RED.stroke_width(2)))
+                .map_err(DspError::plot)?
+                .label(format!("Segmental SNR {:.2} dB", mean_snr))
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        let mut chart = ChartBuilder::on(&lower)
+            .caption("Correlation per Frame", ("sans-serif", 30))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(80)
+            .build_cartesian_2d(0.0..duration, -1.05..1.05)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc("Correlation")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        chart
+            .draw_series(LineSeries::new(frames.iter().map(|f| (f.time, f.correlation)), &BLUE))
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Sample rate (Hz) both signals are resampled to for [`stoi`]
+const STOI_SAMPLE_RATE: f64 = 10000.0;
+/// Hann frame length of the STOI analysis, with 50 % overlap
+const STOI_FRAME: usize = 256;
+/// One-third-octave bands, centred from 150 Hz up to about 3.8 kHz
+const STOI_BANDS: usize = 15;
+const STOI_LOWEST_CENTRE: f64 = 150.0;
+/// Frames per intermediate-intelligibility segment (384 ms)
+const STOI_SEGMENT: usize = 30;
+/// Lower signal-to-distortion bound (dB) of the envelope clipping
+const STOI_BETA_DB: f64 = -15.0;
+/// Frames this far (dB) below the loudest frame of the reference count as silence
+const STOI_DYNAMIC_RANGE_DB: f64 = 40.0;
+
+/// Short-time objective intelligibility (STOI) of `degraded` against `reference`, 0..=1
+///
+/// A simplified form of Taal et al. (2011): after dropping silent frames, the one-third-octave
+/// band envelopes of both signals are compared over 384 ms segments by correlation, with the
+/// degraded envelope scaled to the reference and clipped at -15 dB signal-to-distortion. The
+/// score ignores overall gain and small delays and tracks speech intelligibility far better
+/// than MSE; scores above about 0.9 mean the two are practically equally intelligible. The
+/// spectra use 256-point FFTs without zero-padding, so absolute values differ slightly from
+/// the reference implementation. Returns NaN for signals shorter than one segment.
+pub fn stoi(reference: &[f64], degraded: &[f64], sample_rate: f64) -> f64 {
+    let n = reference.len().min(degraded.len());
+    let reference = resample(&reference[..n], sample_rate, STOI_SAMPLE_RATE);
+    let degraded = resample(&degraded[..n], sample_rate, STOI_SAMPLE_RATE);
+    let (reference, degraded) = remove_silent_frames(&reference, &degraded);
+
+    let hop = STOI_FRAME / 2;
+    let envelopes = |signal: &[f64]| {
+        let stft = Stft::compute(signal, STOI_SAMPLE_RATE, STOI_FRAME, hop, Window::Hann);
+        let bands: Vec<Vec<usize>> = (0..STOI_BANDS)
+            .map(|j| {
+                let centre = STOI_LOWEST_CENTRE * 2f64.powf(j as f64 / 3.0);
+                let (low, high) = (centre * 2f64.powf(-1.0 / 6.0), centre * 2f64.powf(1.0 / 6.0));
+                (0..stft.frequencies.len())
+                    .filter(|&k| stft.frequencies[k] >= low && stft.frequencies[k] < high)
+                    .collect()
+            })
+            .collect();
+        // envelopes[band][frame]
+        bands
+            .iter()
+            .map(|bins| {
+                let frames = stft.spectra.iter();
+                frames.map(|frame| bins.iter().map(|&k| frame[k].norm_sqr()).sum::<f64>().sqrt()).collect()
+            })
+            .collect::<Vec<Vec<f64>>>()
+    };
+    let (x, y) = (envelopes(&reference), envelopes(&degraded));
+    let frames = x[0].len().min(y[0].len());
+    if frames < STOI_SEGMENT {
+        return f64::NAN;
+    }
+
+    let clip = 1.0 + 10f64.powf(-STOI_BETA_DB / 20.0);
+    let mut total = 0.0;
+    let mut count = 0;
+    for end in STOI_SEGMENT..=frames {
+        for (x_band, y_band) in x.iter().zip(&y) {
+            let xs = &x_band[end - STOI_SEGMENT..end];
+            let ys = &y_band[end - STOI_SEGMENT..end];
+            let norm = |v: &[f64]| v.iter().map(|a| a * a).sum::<f64>().sqrt();
+            let alpha = norm(xs) / norm(ys).max(f64::MIN_POSITIVE);
+            let ys: Vec<f64> = ys.iter().zip(xs).map(|(&y, &x)| (alpha * y).min(clip * x)).collect();
+
+            let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+            let (mean_x, mean_y) = (mean(xs), mean(&ys));
+            let cov: f64 = xs.iter().zip(&ys).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+            let var_x: f64 = xs.iter().map(|a| (a - mean_x).powi(2)).sum();
+            let var_y: f64 = ys.iter().map(|b| (b - mean_y).powi(2)).sum();
+            if var_x > 0.0 && var_y > 0.0 {
+                total += cov / (var_x * var_y).sqrt();
+            }
+            count += 1;
+        }
+    }
+    total / count as f64
+}
+
+/// Drop frames of both signals where the reference is more than 40 dB below its loudest
+/// frame, and overlap-add what is left
+fn remove_silent_frames(reference: &[f64], degraded: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let hop = STOI_FRAME / 2;
+    let window = Window::Hann.coefficients(STOI_FRAME);
+    let n = reference.len().min(degraded.len());
+    if n < STOI_FRAME {
+        return (reference[..n].to_vec(), degraded[..n].to_vec());
+    }
+    let starts: Vec<usize> = (0..=(n - STOI_FRAME) / hop).map(|k| k * hop).collect();
+    let energy_db = |start: usize| {
+        let energy: f64 = (0..STOI_FRAME).map(|i| (reference[start + i] * window[i]).powi(2)).sum();
+        10.0 * energy.max(1e-300).log10()
+    };
+    let energies: Vec<f64> = starts.iter().map(|&start| energy_db(start)).collect();
+    let loudest = energies.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+    let kept: Vec<usize> = starts
+        .iter()
+        .zip(&energies)
+        .filter(|(_, &e)| e > loudest - STOI_DYNAMIC_RANGE_DB)
+        .map(|(&start, _)| start)
+        .collect();
+
+    let overlap_add = |signal: &[f64]| {
+        let mut out = vec![0.0; (kept.len() + 1) * hop];
+        for (j, &start) in kept.iter().enumerate() {
+            for i in 0..STOI_FRAME {
+                out[j * hop + i] += signal[start + i] * window[i];
+            }
+        }
+        out
+    };
+    (overlap_add(reference), overlap_add(degraded))
+}
+
+/// Demodulator quality at one input SNR
+#[derive(Debug, Clone, Copy)]
+pub struct RobustnessPoint {
+    pub input_snr_db: f64,
+    pub mse: f64,
+    pub output_snr_db: f64,
+    pub correlation: f64,
+    /// [`stoi`] of the output against the unimpaired output
+    pub stoi: f64,
+}
+
+/// Sweep the input SNR and measure how far the demodulated output drifts from the unimpaired one
+///
+/// Each point passes `input` through `impairments` with `kind` noise at that SNR, runs
+/// `demodulate` on the result and compares it with `demodulate(input)`.
+pub fn robustness_curve(
+    input: &[f64],
+    sample_rate: f64,
+    input_snrs: &[f64],
+    impairments: &ChannelImpairments,
+    kind: NoiseKind,
+    demodulate: impl Fn(&[f64]) -> Vec<f64>,
+) -> Vec<RobustnessPoint> {
+    let progress = Progress::new(input_snrs.len() as u64 + 1, "Robustness sweep");
+    let reference = demodulate(input);
+    progress.inc(1);
+    input_snrs
+        .iter()
+        .map(|&snr| {
+            let received = impairments.with_noise(kind, snr).apply(input, sample_rate);
+            let output = demodulate(&received);
+            let result = compare_signals(&reference, &output, sample_rate);
+            progress.inc(1);
+            RobustnessPoint {
+                input_snr_db: snr,
+                mse: result.mse,
+                output_snr_db: result.snr_db,
+                correlation: result.correlation,
+                stoi: stoi(&reference, &output, sample_rate),
+            }
+        })
+        .collect()
+}
+
+/// Save robustness curves as CSV, one row per (method, input SNR)
+pub fn save_robustness_csv(curves: &[(&str, Vec<RobustnessPoint>)], filename: &str) -> Result<()> {
+    let mut content = String::from("method,input_snr_db,mse,output_snr_db,correlation,stoi\n");
+    for (method, points) in curves {
+        for p in points {
+            content.push_str(&format!(
+                "{},{:.2},{:.6e},{:.4},{:.6},{:.4}\n",
+                method, p.input_snr_db, p.mse, p.output_snr_db, p.correlation, p.stoi
+            ));
+        }
+    }
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Plot output SNR against input SNR for each method
+pub fn plot_robustness(curves: &[(&str, Vec<RobustnessPoint>)], target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let points = || curves.iter().flat_map(|(_, p)| p.iter());
+    let x_min = points().map(|p| p.input_snr_db).fold(f64::INFINITY, f64::min);
+    let x_max = points().map(|p| p.input_snr_db).fold(f64::NEG_INFINITY, f64::max);
+    let finite_snrs = || points().map(|p| p.output_snr_db).filter(|v| v.is_finite());
+    let y_min = finite_snrs().fold(f64::INFINITY, f64::min).min(x_min);
+    let y_max = finite_snrs().fold(f64::NEG_INFINITY, f64::max).max(x_max);
+
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Demodulator Robustness: Output SNR vs Input SNR", ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_min..x_max, (y_min - 5.0)..(y_max + 5.0))
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Input SNR (dB)")
+            .y_desc("Output SNR vs clean demodulation (dB)")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        for (index, (method, curve)) in curves.iter().enumerate() {
+            let color = Palette99::pick(index).to_rgba();
+            let series: Vec<(f64, f64)> = curve
+                .iter()
+                .filter(|p| p.output_snr_db.is_finite())
+                .map(|p| (p.input_snr_db, p.output_snr_db))
+                .collect();
+            chart
+                .draw_series(LineSeries::new(series.clone(), color.stroke_width(2)))
+                .map_err(DspError::plot)?
+                .label(*method)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            chart
+                .draw_series(series.into_iter().map(|(x, y)| Circle::new((x, y), 4, color.filled())))
+                .map_err(DspError::plot)?;
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Total harmonic distortion of a tone at `fundamental` Hz, as a power ratio
+///
+/// Harmonics 2..=`harmonics` are summed over their power; every line is integrated over ±3
+/// bins of a Hann-windowed FFT so leakage does not depend on where the tone falls in a bin.
+pub fn total_harmonic_distortion(signal: &[f64], sample_rate: f64, fundamental: f64, harmonics: usize) -> f64 {
+    let spectrum = hann_spectrum(signal);
+
+    let fundamental_power = line_power(&spectrum, sample_rate, fundamental);
+    let harmonic_power: f64 = (2..=harmonics)
+        .map(|h| h as f64 * fundamental)
+        .filter(|&f| f < sample_rate / 2.0)
+        .map(|f| line_power(&spectrum, sample_rate, f))
+        .sum();
+    if fundamental_power > 0.0 {
+        harmonic_power / fundamental_power
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Harmonics summed by [`thd`]: 2nd to 5th, the orders a demodulator's nonlinearity puts
+/// noticeable power into
+pub const THD_HARMONICS: usize = 5;
+
+/// Total harmonic distortion of a tone at `fundamental` Hz over harmonics 2..=[`THD_HARMONICS`],
+/// as a power ratio; see [`total_harmonic_distortion`] to choose the harmonic count
+pub fn thd(signal: &[f64], fundamental: f64, sample_rate: f64) -> f64 {
+    total_harmonic_distortion(signal, sample_rate, fundamental, THD_HARMONICS)
+}
+
+/// Level of each tone in `tones` relative to the tone at `reference` Hz (dB), measured like
+/// [`total_harmonic_distortion`]'s lines
+///
+/// For a probe whose baseband tones all have the same amplitude this is the demodulator's
+/// magnitude response at those frequencies, normalized to mid-band.
+pub fn tone_levels_db(signal: &[f64], sample_rate: f64, tones: &[f64], reference: f64) -> Vec<f64> {
+    let spectrum = hann_spectrum(signal);
+    let reference_power = line_power(&spectrum, sample_rate, reference).max(f64::MIN_POSITIVE);
+    tones
+        .iter()
+        .map(|&f| 10.0 * (line_power(&spectrum, sample_rate, f) / reference_power).max(1e-30).log10())
+        .collect()
+}
+
+/// FFT of a Hann-windowed signal
+fn hann_spectrum(signal: &[f64]) -> Vec<Complex<f64>> {
+    let n = signal.len();
+    let mut spectrum: Vec<Complex<f64>> = signal
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+            Complex::new(x * window, 0.0)
+        })
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+    spectrum
+}
+
+/// Power of the line at `frequency`, summed over ±3 bins of `spectrum`
+fn line_power(spectrum: &[Complex<f64>], sample_rate: f64, frequency: f64) -> f64 {
+    let n = spectrum.len();
+    let center = (frequency * n as f64 / sample_rate).round() as usize;
+    (center.saturating_sub(3)..=center + 3)
+        .filter(|&k| k < n / 2)
+        .map(|k| spectrum[k].norm_sqr())
+        .sum()
+}
+
+/// Compare every pair of demodulator outputs; entry [i][j] treats output i as the reference
+pub fn comparison_matrix(outputs: &[(&str, Vec<f64>)], sample_rate: f64) -> Vec<Vec<ComparisonResult>> {
+    outputs
+        .iter()
+        .map(|(_, a)| outputs.iter().map(|(_, b)| compare_signals(a, b, sample_rate)).collect())
+        .collect()
+}
+
+/// Save the cross-method matrix (normalized correlation) with each method's tone-test THD
+pub fn save_comparison_matrix(
+    names: &[&str],
+    matrix: &[Vec<ComparisonResult>],
+    thd: &[Option<f64>],
+    tone_test: &str,
+    filename: &str,
+) -> Result<()> {
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(8);
+    let mut content = String::new();
+    content.push_str("Q4 Cross-Method Comparison Matrix\n");
+    content.push_str("=================================\n\n");
+    content.push_str("Normalized correlation between demodulator outputs on the recording:\n\n");
+    content.push_str(&format!("{:width$}", "", width = width + 5));
+    for k in 0..names.len() {
+        content.push_str(&format!(" {:>7}", format!("[{:>2}]", k + 1)));
+    }
+    content.push('\n');
+    for (k, (name, row)) in names.iter().zip(matrix).enumerate() {
+        content.push_str(&format!("[{:>2}] {:width$}", k + 1, name, width = width));
+        for result in row {
+            content.push_str(&format!(" {:>+7.4}", result.correlation_normalized));
+        }
+        content.push('\n');
+    }
+
+    content.push_str(&format!("\nHarmonic distortion ({}):\n\n", tone_test));
+    for (name, thd) in names.iter().zip(thd) {
+        match thd {
+            Some(ratio) => content.push_str(&format!(
+                "  {:width$}  THD = {:>7.3} % ({:>7.2} dB)\n",
+                name,
+                100.0 * ratio.sqrt(),
+                10.0 * ratio.log10(),
+                width = width
+            )),
+            None => content.push_str(&format!("  {:width$}  THD = n/a\n", name, width = width)),
+        }
+    }
+    content.push_str("\nTHD is the RMS of harmonics 2-5 relative to the fundamental. Square-law\n");
+    content.push_str("detection without the square root keeps the m(t)^2 term of (1 + mu m)^2, whose\n");
+    content.push_str("second harmonic is mu / 4 of the fundamental.\n");
+
+    info!("{}", content.trim_end());
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Save every pair of the cross-method matrix as CSV, one row per (reference, method)
+pub fn save_matrix_csv(names: &[&str], matrix: &[Vec<ComparisonResult>], filename: &str) -> Result<()> {
+    let mut content = String::from(
+        "reference,method,mse,max_diff,correlation_normalized,snr_db,log_spectral_distance_db,coherence,band_snr_db\n",
+    );
+    for (reference, row) in names.iter().zip(matrix) {
+        for (method, c) in names.iter().zip(row) {
+            content.push_str(&format!(
+                "{},{},{:.6e},{:.6},{:.6},{:.4},{:.4},{:.6},{:.4}\n",
+                reference,
+                method,
+                c.mse,
+                c.max_diff,
+                c.correlation_normalized,
+                c.snr_db,
+                c.log_spectral_distance,
+                c.coherence,
+                c.band_snr_db
+            ));
+        }
+    }
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Heatmap of the cross-method normalized correlation, each cell labelled with its value
+///
+/// Rows are the reference method and columns the method compared against it; the scale
+/// runs from blue at -1 through white at 0 to red at +1, so inverted outputs stand out.
+pub fn plot_matrix_heatmap(names: &[&str], matrix: &[Vec<ComparisonResult>], target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let n = names.len();
+    if n == 0 {
+        return Ok(());
+    }
+    let color = |r: f64| {
+        let t = r.clamp(-1.0, 1.0);
+        let fade = (255.0 * (1.0 - t.abs())) as u8;
+        if t >= 0.0 {
+            RGBColor(255, fade, fade)
+        } else {
+            RGBColor(fade, fade, 255)
+        }
+    };
+
+    with_drawing_area!(target, (1200, 1000), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Cross-Method Agreement (Normalized Correlation)", ("sans-serif", 36))
+            .margin(20)
+            .x_label_area_size(60)
+            .y_label_area_size(220)
+            // A segmented 0..n range has a segment for n itself, so stop one short
+            .build_cartesian_2d((0..n - 1).into_segmented(), (0..n - 1).into_segmented())
+            .map_err(DspError::plot)?;
+        // Columns are numbered to save width; rows carry the full names and numbers
+        let label = |numbered: bool| {
+            move |v: &SegmentValue<usize>| match v {
+                SegmentValue::CenterOf(i) if numbered => format!("[{}] {}", i + 1, names[*i]),
+                SegmentValue::CenterOf(i) => format!("[{}]", i + 1),
+                _ => String::new(),
+            }
+        };
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_labels(n)
+            .y_labels(n)
+            .x_label_formatter(&label(false))
+            .y_label_formatter(&label(true))
+            .label_style(("sans-serif", 18))
+            .x_desc("Method")
+            .y_desc("Reference")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        let upper_edge = |i: usize| if i + 1 == n { SegmentValue::Last } else { SegmentValue::Exact(i + 1) };
+        for (y, row) in matrix.iter().enumerate() {
+            for (x, result) in row.iter().enumerate() {
+                let r = result.correlation_normalized;
+                let cell = [(SegmentValue::Exact(x), SegmentValue::Exact(y)), (upper_edge(x), upper_edge(y))];
+                chart
+                    .draw_series(std::iter::once(Rectangle::new(cell, color(r).filled())))
+                    .map_err(DspError::plot)?;
+                let style = ("sans-serif", 22).into_font().color(&BLACK).pos(Pos::new(HPos::Center, VPos::Center));
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        format!("{:+.3}", r),
+                        (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)),
+                        style,
+                    )))
+                    .map_err(DspError::plot)?;
+            }
+        }
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Overlay every method's output over `time_range` seconds (by default the first 2000
+/// samples), each scaled to a peak of 1 so waveform shape can be compared despite
+/// differing gains
+pub fn plot_overlay(
+    outputs: &[(&str, Vec<f64>)],
+    sample_rate: f64,
+    time_range: Option<(f64, f64)>,
+    target: impl Into<PlotTarget>,
+) -> Result<()> {
+    let target = target.into();
+    let n = outputs.iter().map(|(_, s)| s.len()).min().unwrap_or(0);
+    if n == 0 {
+        return Ok(());
+    }
+    let (start, end) = time_range.unwrap_or((0.0, 2000.0 / sample_rate));
+    let first = ((start * sample_rate).floor() as usize).min(n - 1);
+    let last = ((end * sample_rate).ceil() as usize).clamp(first + 1, n);
+    let time = |i: usize| i as f64 / sample_rate;
+    let colors = [BLUE, RED, RGBColor(0, 150, 0), MAGENTA, RGBColor(230, 140, 0), CYAN, BLACK];
+
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Demodulator Outputs (Peak-Normalized)", ("sans-serif", 40))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            .build_cartesian_2d(time(first)..time(last), -1.1..1.1)
+            .map_err(DspError::plot)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (s)")
+            .y_desc("Amplitude / peak")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        for (k, (name, signal)) in outputs.iter().enumerate() {
+            let window = &signal[first..last];
+            let peak = window.iter().fold(0.0f64, |max, &x| max.max(x.abs())).max(1e-12);
+            let color = colors[k % colors.len()];
+            chart
+                .draw_series(LineSeries::new(
+                    window.iter().enumerate().map(|(i, &x)| (time(first + i), x / peak)),
+                    &color,
+                ))
+                .map_err(DspError::plot)?
+                .label(*name)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// How well one demodulator output reconstructs the known clean baseband
+pub struct Reconstruction {
+    /// Delay of the output behind the reference, in samples
+    pub lag: isize,
+    /// Least-squares gain applied to the output before measuring the error
+    pub gain: f64,
+    /// Mean squared error against the reference after lag, DC and gain are removed
+    pub mse: f64,
+    /// Reference power over `mse`, in dB
+    pub snr_db: f64,
+}
+
+/// Score `output` against the ground-truth `reference` (e.g. signal_gen's clean m(t))
+///
+/// The output is first aligned by cross-correlation, then both have their mean removed
+/// and the output is scaled by the least-squares gain, so a filter delay, the carrier's
+/// DC term and the demodulator's gain are not counted as error. Whatever remains (noise,
+/// distortion, band-limiting, a missing square root) is.
+pub fn reconstruction(reference: &[f64], output: &[f64], sample_rate: f64) -> Reconstruction {
+    let lag = align(reference, output, sample_rate).lag;
+    let (reference, output) = shift_by_lag(lag, reference, output);
+    let n = reference.len().min(output.len()).max(1);
+    let mean = |x: &[f64]| x.iter().take(n).sum::<f64>() / n as f64;
+    let (mean_r, mean_o) = (mean(reference), mean(output));
+    let pairs = || reference.iter().zip(output).map(|(r, o)| (r - mean_r, o - mean_o));
+    let cross: f64 = pairs().map(|(r, o)| r * o).sum();
+    let output_energy: f64 = pairs().map(|(_, o)| o * o).sum();
+    let gain = if output_energy > 0.0 { cross / output_energy } else { 0.0 };
+    let mse = pairs().map(|(r, o)| (r - gain * o).powi(2)).sum::<f64>() / n as f64;
+    let power = pairs().map(|(r, _)| r * r).sum::<f64>() / n as f64;
+    Reconstruction {
+        lag,
+        gain,
+        mse,
+        snr_db: 10.0 * (power / mse).log10(),
+    }
+}
+
+/// Save each method's reconstruction of the ground-truth baseband
+pub fn save_ground_truth(names: &[&str], results: &[Reconstruction], sample_rate: f64, source: &str, filename: &str) -> Result<()> {
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(8);
+    let mut content = String::new();
+    content.push_str("Q4 Ground-Truth Evaluation
+");
+    content.push_str("==========================
+
+");
+    content.push_str(&format!("Reference: {}
+", source));
+    content.push_str("Each output is aligned, mean-removed and scaled by its least-squares gain first.
+
+");
+    content.push_str(&format!(
+        "{:width$}  {:>10}  {:>12}  {:>9}  {:>10}\n",
+        "Method",
+        "SNR (dB)",
+        "MSE",
+        "Gain",
+        "Lag (ms)",
+        width = width
+    ));
+    for (name, r) in names.iter().zip(results) {
+        content.push_str(&format!(
+            "{:width$}  {:>10.2}  {:>12.4e}  {:>9.4}  {:>10.3}\n",
+            name,
+            r.snr_db,
+            r.mse,
+            r.gain,
+            r.lag as f64 / sample_rate * 1000.0,
+            width = width
+        ));
+    }
+
+    info!("{}", content.trim_end());
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// One chain's output for the `--guard-band` probe, with the high-pass at f_d - `guard_band`
+pub struct GuardBandResult {
+    pub method: String,
+    pub guard_band: f64,
+    /// Score against the probe's clean baseband
+    pub reconstruction: Reconstruction,
+    /// Level of each probe tone relative to the reference tone (dB), see [`tone_levels_db`]
+    pub levels_db: Vec<f64>,
+}
+
+/// Table of the `--guard-band` probe results, also written to the log
+pub fn save_guard_band(results: &[GuardBandResult], tones: &[f64], reference: f64, filename: &str) -> Result<()> {
+    let width = results.iter().map(|r| r.method.len()).max().unwrap_or(0).max(6);
+    let mut content = String::new();
+    content.push_str("Q4 Guard Band Comparison\n");
+    content.push_str("========================\n\n");
+    content.push_str(&format!(
+        "DSB-SC probe: equal tones at {} and {:.0} Hz; each output is scored against the clean baseband\n",
+        tones.iter().map(|f| format!("{:.0}", f)).collect::<Vec<_>>().join(", "),
+        reference
+    ));
+    content.push_str(&format!(
+        "(aligned, least-squares gain) and its tone levels are relative to the {:.0} Hz tone (0 dB = flat).\n\n",
+        reference
+    ));
+    content.push_str(&format!("{:width$}  {:>10}  {:>9}", "Method", "Guard (Hz)", "SNR (dB)", width = width));
+    for f in tones {
+        content.push_str(&format!("  {:>9}", format!("{:.0} Hz", f)));
+    }
+    content.push('\n');
+    for r in results {
+        content.push_str(&format!(
+            "{:width$}  {:>10.1}  {:>9.2}",
+            r.method,
+            r.guard_band,
+            r.reconstruction.snr_db,
+            width = width
+        ));
+        for level in &r.levels_db {
+            content.push_str(&format!("  {:>9.2}", level));
+        }
+        content.push('\n');
+    }
+
+    info!("{}", content.trim_end());
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Q3 vs Q4 agreement for one filter order and low-pass cutoff
+pub struct SweepPoint {
+    pub order: usize,
+    pub cutoff: f64,
+    pub comparison: ComparisonResult,
+}
+
+/// Save a parameter sweep as CSV, one row per (order, cutoff)
+pub fn save_sweep_csv(points: &[SweepPoint], filename: &str) -> Result<()> {
+    let mut content = String::from(
+        "order,cutoff_hz,mse,max_diff,correlation,correlation_normalized,snr_db,log_spectral_distance_db,coherence,band_snr_db\n",
+    );
+    for p in points {
+        let c = &p.comparison;
+        content.push_str(&format!(
+            "{},{:.2},{:.6e},{:.6},{:.6},{:.6},{:.4},{:.4},{:.6},{:.4}\n",
+            p.order,
+            p.cutoff,
+            c.mse,
+            c.max_diff,
+            c.correlation,
+            c.correlation_normalized,
+            c.snr_db,
+            c.log_spectral_distance,
+            c.coherence,
+            c.band_snr_db
+        ));
+    }
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved: {}", filename);
+    Ok(())
+}
+
+/// Heatmap of the Q3-vs-Q4 SNR over the (cutoff, order) grid, each cell labelled with its value
+pub fn plot_sweep_heatmap(points: &[SweepPoint], target: impl Into<PlotTarget>) -> Result<()> {
+    let target = target.into();
+    let mut orders: Vec<usize> = points.iter().map(|p| p.order).collect();
+    orders.sort_unstable();
+    orders.dedup();
+    let mut cutoffs: Vec<f64> = points.iter().map(|p| p.cutoff).collect();
+    cutoffs.sort_by(|a, b| a.total_cmp(b));
+    cutoffs.dedup();
+    if orders.is_empty() {
+        return Ok(());
+    }
+
+    let finite = || points.iter().map(|p| p.comparison.snr_db).filter(|v| v.is_finite());
+    let lo = finite().fold(f64::INFINITY, f64::min);
+    let hi = finite().fold(f64::NEG_INFINITY, f64::max);
+    // Blue (worst) to red (best); diverged filters are grey
+    let color = |snr: f64| {
+        if !snr.is_finite() {
+            return RGBColor(160, 160, 160);
+        }
+        let t = if hi > lo { (snr - lo) / (hi - lo) } else { 1.0 };
+        RGBColor((255.0 * t) as u8, 60, (255.0 * (1.0 - t)) as u8)
+    };
+
+    with_drawing_area!(target, (1200, 800), |root| {
+        root.fill(&WHITE).map_err(DspError::plot)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Q3 vs Q4 Agreement (SNR, dB) by Filter Order and Cutoff", ("sans-serif", 36))
+            .margin(20)
+            .x_label_area_size(50)
+            .y_label_area_size(60)
+            // A segmented 0..n range has a segment for n itself, so stop one short
+            .build_cartesian_2d((0..cutoffs.len() - 1).into_segmented(), (0..orders.len() - 1).into_segmented())
+            .map_err(DspError::plot)?;
+        // Label each cell's centre with its grid value
+        let label = |values: Vec<String>| {
+            move |v: &SegmentValue<usize>| match v {
+                SegmentValue::CenterOf(i) => values.get(*i).cloned().unwrap_or_default(),
+                _ => String::new(),
+            }
+        };
+        let cutoff_label = label(cutoffs.iter().map(|c| format!("{:.0} Hz", c)).collect());
+        let order_label = label(orders.iter().map(|o| format!("order {}", o)).collect());
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_labels(cutoffs.len())
+            .y_labels(orders.len())
+            .x_label_formatter(&cutoff_label)
+            .y_label_formatter(&order_label)
+            .label_style(("sans-serif", 18))
+            .x_desc("Low-pass cutoff")
+            .y_desc("Butterworth order")
+            .draw()
+            .map_err(DspError::plot)?;
+
+        // The last segment has no following one to end at
+        let upper_edge = |i: usize, n: usize| if i + 1 == n { SegmentValue::Last } else { SegmentValue::Exact(i + 1) };
+        for p in points {
+            let x = cutoffs.iter().position(|&c| c == p.cutoff).unwrap();
+            let y = orders.iter().position(|&o| o == p.order).unwrap();
+            let snr = p.comparison.snr_db;
+            let cell = [
+                (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                (upper_edge(x, cutoffs.len()), upper_edge(y, orders.len())),
+            ];
+            chart
+                .draw_series(std::iter::once(Rectangle::new(cell, color(snr).filled())))
+                .map_err(DspError::plot)?;
+            let text = if snr.is_finite() { format!("{:.1}", snr) } else { "n/a".to_string() };
+            let style = ("sans-serif", 24).into_font().color(&WHITE).pos(Pos::new(HPos::Center, VPos::Center));
+            chart
+                .draw_series(std::iter::once(Text::new(text, (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)), style)))
+                .map_err(DspError::plot)?;
+        }
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsp_core::activity::ActivityDetector;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_robustness_curve_tracks_input_snr() {
+        let fs = 8000.0;
+        let input: Vec<f64> = (0..8000).map(|i| (2.0 * PI * 300.0 * i as f64 / fs).sin()).collect();
+        let snrs = [0.0, 10.0, 20.0];
+
+        // A pass-through "demodulator" sees exactly the channel noise
+        let curve = robustness_curve(&input, fs, &snrs, &ChannelImpairments::default(), NoiseKind::White, |x| x.to_vec());
+        for point in &curve {
+            assert!((point.output_snr_db - point.input_snr_db).abs() < 0.1, "{:?}", point);
+        }
+        assert!(curve[0].mse > curve[2].mse);
+    }
+
+    #[test]
+    fn test_thd_of_tone_with_known_harmonic() {
+        let fs = 8000.0;
+        let tone: Vec<f64> = (0..8000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (2.0 * PI * 500.0 * t).cos() + 0.1 * (2.0 * PI * 1000.0 * t).cos()
+            })
+            .collect();
+        let thd = total_harmonic_distortion(&tone, fs, 500.0, 5);
+        assert!((thd.sqrt() - 0.1).abs() < 1e-3, "THD {}", thd.sqrt());
+        let levels = tone_levels_db(&tone, fs, &[1000.0], 500.0);
+        assert!((levels[0] + 20.0).abs() < 1e-2, "level {}", levels[0]);
+
+        let matrix = comparison_matrix(&[("a", tone.clone()), ("b", tone)], fs);
+        assert!((matrix[0][1].correlation_normalized - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_thd_sums_default_harmonics() {
+        // 3rd harmonic at -26 dB is counted, the 7th lies beyond THD_HARMONICS
+        let fs = 8000.0;
+        let tone: Vec<f64> = (0..8000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (2.0 * PI * 400.0 * t).cos() + 0.05 * (2.0 * PI * 1200.0 * t).cos() + 0.2 * (2.0 * PI * 2800.0 * t).cos()
+            })
+            .collect();
+        let ratio = thd(&tone, 400.0, fs);
+        assert!((ratio.sqrt() - 0.05).abs() < 1e-3, "THD {}", ratio.sqrt());
+        assert_eq!(ratio, total_harmonic_distortion(&tone, fs, 400.0, THD_HARMONICS));
+        assert!(total_harmonic_distortion(&tone, fs, 400.0, 7).sqrt() > 0.2);
+    }
+
+    #[test]
+    fn test_time_range_from_args() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(time_range_from_args(&args(&["--compare-range", "0.5:0.75"])).unwrap(), Some((0.5, 0.75)));
+        assert_eq!(time_range_from_args(&args(&["--agc"])).unwrap(), None);
+        assert!(time_range_from_args(&args(&["--compare-range", "0.7:0.5"])).is_err());
+        assert!(time_range_from_args(&args(&["--compare-range"])).is_err());
+    }
+
+    #[test]
+    fn test_align_finds_delay() {
+        let fs = 8000.0;
+        let chirp: Vec<f64> = (0..4000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (2.0 * PI * (200.0 + 800.0 * t) * t).sin()
+            })
+            .collect();
+        // A copy delayed by 37 samples, as an IIR filter's group delay would
+        let delayed: Vec<f64> = std::iter::repeat_n(0.0, 37).chain(chirp.iter().copied()).take(4000).collect();
+
+        let alignment = align(&chirp, &delayed, fs);
+        assert_eq!(alignment.lag, 37);
+        assert!(alignment.correlation > 0.99, "{}", alignment.correlation);
+        assert_eq!(alignment.overlap, 4000 - 37);
+        assert!(alignment.aligned.mse < 1e-20 && compare_signals(&chirp, &delayed, fs).mse > 0.1);
+        assert_eq!(align(&delayed, &chirp, fs).lag, -37);
+    }
+
+    #[test]
+    fn test_spectral_metrics_ignore_out_of_band_difference() {
+        let fs = 16000.0;
+        let tone = |f: f64, a: f64| (0..16000).map(move |i| a * (2.0 * PI * f * i as f64 / fs).sin());
+        let q4: Vec<f64> = tone(440.0, 1.0).zip(tone(1300.0, 0.5)).map(|(a, b)| a + b).collect();
+        // Ringing at 6 kHz, outside the baseband, dominates the broadband MSE only
+        let q3: Vec<f64> = q4.iter().zip(tone(6000.0, 0.5)).map(|(a, b)| a + b).collect();
+
+        let result = compare_signals(&q4, &q3, fs);
+        assert!(result.snr_db < 10.0, "broadband SNR {}", result.snr_db);
+        assert!(result.band_snr_db > 60.0, "band SNR {}", result.band_snr_db);
+        assert!(result.log_spectral_distance < 0.01, "LSD {}", result.log_spectral_distance);
+        assert!(result.coherence > 0.999, "coherence {}", result.coherence);
+    }
+
+    #[test]
+    fn test_segmental_metrics_localize_transient() {
+        let fs = 8000.0;
+        let q4: Vec<f64> = (0..8000).map(|i| (2.0 * PI * 300.0 * i as f64 / fs).sin()).collect();
+        // Q3 matches except for a burst in the first 20 ms frame, like a filter transient
+        let mut q3 = q4.clone();
+        for x in q3.iter_mut().take(160) {
+            *x += 1.0;
+        }
+
+        let frames = segmental_metrics(&q4, &q3, fs, SEGMENT_SECONDS);
+        assert_eq!(frames.len(), 50);
+        assert!((frames[0].time - 0.01).abs() < 1e-12);
+        assert!(frames[0].snr_db < 0.0 && frames[1].snr_db.is_infinite());
+        assert!((frames[1].correlation - 1.0).abs() < 1e-12);
+        // One frame at about -3 dB among 49 clamped at 35 dB
+        let seg = segmental_snr(&frames);
+        assert!(seg > 33.0 && seg < 35.0, "{}", seg);
+    }
+
+    #[test]
+    fn test_compare_active_skips_silence() {
+        let fs = 8000.0;
+        // A tone in the middle second; Q3 adds a small residual hiss over the whole signal
+        let q4: Vec<f64> = (0..24000)
+            .map(|i| if (8000..16000).contains(&i) { (2.0 * PI * 300.0 * i as f64 / fs).sin() } else { 0.0 })
+            .collect();
+        let q3: Vec<f64> = q4.iter().enumerate().map(|(i, x)| x + 0.01 * (i as f64 * 2.1).sin()).collect();
+
+        let segments = ActivityDetector::default().detect(&q4, fs);
+        assert_eq!((segments.len(), segments[0].clone()), (1, 7200..16800));
+        let full = compare_signals(&q4, &q3, fs);
+        let active = compare_active(&q4, &q3, fs, &segments);
+        // The silent stretches outside the hangover carry no signal power, only the hiss:
+        // 10·log10(24000 / 9600) = 4 dB
+        assert!((active.snr_db - full.snr_db - 3.98).abs() < 0.05, "{} vs {}", active.snr_db, full.snr_db);
+        assert!((active.mse - 0.5 * 0.01 * 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stoi_ignores_gain_but_not_noise() {
+        let fs = 16000.0;
+        // Speech-like test signal: tones in several bands with independent syllable-rate envelopes
+        let speech: Vec<f64> = (0..32000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                [(300.0, 3.0), (800.0, 4.5), (1500.0, 5.0), (3000.0, 6.5)]
+                    .iter()
+                    .map(|&(f, rate)| (1.0 + (2.0 * PI * rate * t).sin()) * (2.0 * PI * f * t).sin())
+                    .sum::<f64>()
+            })
+            .collect();
+        let quieter: Vec<f64> = speech.iter().map(|x| 0.1 * x).collect();
+        let noisy: Vec<f64> = speech
+            .iter()
+            .zip(dsp_core::noise::white(speech.len(), 7))
+            .map(|(x, n)| x + 10.0 * n)
+            .collect();
+
+        assert!(stoi(&speech, &speech, fs) > 0.999);
+        assert!(stoi(&speech, &quieter, fs) > 0.999);
+        let score = stoi(&speech, &noisy, fs);
+        assert!(score < 0.6, "STOI {}", score);
+        assert!(stoi(&speech[..1000], &speech[..1000], fs).is_nan());
+    }
+
+    #[test]
+    fn test_band_metrics_localize_difference() {
+        let fs = 8000.0;
+        let tone = |f: f64| (0..16000).map(move |i| (2.0 * PI * f * i as f64 / fs).sin());
+        let reference: Vec<f64> = tone(300.0).zip(tone(1500.0)).map(|(a, b)| a + b).collect();
+        // The 1500 Hz component is halved; the 300 Hz one is untouched
+        let output: Vec<f64> = tone(300.0).zip(tone(1500.0)).map(|(a, b)| a + 0.5 * b).collect();
+        let bands = band_metrics(&reference, &output, fs, &REPORT_BANDS);
+        assert_eq!(bands.len(), REPORT_BANDS.len());
+        assert!(bands[2].snr_db > 30.0, "300 Hz band: {}", bands[2].snr_db);
+        assert!((bands[3].snr_db - 20.0 * 2.0f64.log10()).abs() < 0.1, "1500 Hz band: {}", bands[3].snr_db);
+        assert!(bands[3].log_spectral_distance > bands[2].log_spectral_distance);
+        assert_eq!((json_number(f64::INFINITY), json_number(1e-7)), ("null".to_string(), "0.0000001".to_string()));
+    }
+
+    #[test]
+    fn test_reconstruction_ignores_delay_gain_and_dc() {
+        let fs = 8000.0;
+        let reference: Vec<f64> = (0..8000).map(|i| (2.0 * PI * 300.0 * i as f64 / fs).sin()).collect();
+        // Delayed by 12 samples, with gain 0.3 and the DC left by an AM carrier
+        let output: Vec<f64> = (0..8000usize)
+            .map(|i| 0.5 + 0.3 * i.checked_sub(12).map_or(0.0, |j| reference[j]))
+            .collect();
+        let clean = reconstruction(&reference, &output, fs);
+        assert_eq!(clean.lag, 12);
+        assert!((clean.gain - 1.0 / 0.3).abs() < 1e-6);
+        assert!(clean.snr_db > 100.0);
+
+        // A square-law output without the square root is a genuine error
+        let squared: Vec<f64> = reference.iter().map(|x| (1.0 + 0.8 * x).powi(2)).collect();
+        let distorted = reconstruction(&reference, &squared, fs);
+        assert!((distorted.snr_db - 20.0 * 5.0f64.log10()).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_error_spectrum_locates_disagreement() {
+        let fs = 8000.0;
+        let tone = |f: f64, a: f64| (0..8000).map(move |i| a * (2.0 * PI * f * i as f64 / fs).sin());
+        let q3: Vec<f64> = tone(440.0, 1.0).collect();
+        // The two outputs differ only by a 0.01-amplitude tone at 1500 Hz
+        let q4: Vec<f64> = q3.iter().zip(tone(1500.0, 0.01)).map(|(a, b)| a + b).collect();
+
+        let spectrum = error_spectrum(&q4, &q3, fs);
+        let (peak_freq, peak_db) = spectrum.into_iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert!((peak_freq - 1500.0).abs() < 1.0, "peak at {} Hz", peak_freq);
+        assert!((peak_db + 40.0).abs() < 0.1, "peak {} dB", peak_db);
+    }
 }
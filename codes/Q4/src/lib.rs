@@ -0,0 +1,10 @@
+// Q4 frequency-domain demodulation modules
+
+pub mod audio_reader;
+pub mod audio_writer;
+pub mod comparator;
+pub mod spectrum_analyzer;
+
+// The FFT → ideal filters → IFFT chain and its gain calibration live in dsp_core, free of
+// file and plotting dependencies, so the browser build in `codes/wasm` runs the same code
+pub use dsp_core::{calibration, frequency_shifter, ideal_filter, pipeline};
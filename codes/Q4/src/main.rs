@@ -1,185 +1,1419 @@
-mod audio_reader;
-mod ideal_filter;
-mod frequency_shifter;
-mod spectrum_analyzer;
-mod audio_writer;
-mod comparator;
+use q4_frequency_domain_demodulation::calibration::{Calibration, GainCalibrator};
+use q4_frequency_domain_demodulation::pipeline::{self, EdgeGuard, SpectralPipeline};
+use q4_frequency_domain_demodulation::comparator::SweepPoint;
+use q4_frequency_domain_demodulation::{audio_reader, audio_writer, comparator, ideal_filter, spectrum_analyzer};
 
+use dsp_core::activity::{self, ActivityDetector};
+use dsp_core::agc::{self, AgcConfig};
+use dsp_core::batch::{self, BatchSummary};
+use dsp_core::butterworth::ButterworthFilter;
+use dsp_core::carrier_search::{CarrierSearch, CarrierSearchResult};
+use dsp_core::autocorrelation::{self, Periodicity};
+use dsp_core::channel::ChannelImpairments;
+use dsp_core::channels::ChannelMode;
+use dsp_core::html_plot::{self, InteractiveChart, InteractivePage};
+use dsp_core::checkpoint::Checkpoints;
+use dsp_core::clipping::{ClippingReport, Headroom};
+use dsp_core::demodulator::{TimeDomainDemodulator, BATCH_FILTER_ORDER};
+use dsp_core::denoise::{DenoiseMethod, DenoiseReport, Denoiser, DEFAULT_WIENER_SMOOTHING};
+use dsp_core::error::{DspError, Result};
+use dsp_core::logging;
+use dsp_core::loudness::Levels;
+use dsp_core::fir::FirFilter;
+use dsp_core::mat;
+use dsp_core::npy::{self, Npz};
+use dsp_core::parallel::{self, Task};
+use dsp_core::playback;
+use dsp_core::plot_output::{PlotOutput, PlotTarget};
+use dsp_core::post_filter::PostFilter;
+use dsp_core::resample::{self, decimate, resample};
+use dsp_core::reproducible::Reproducibility;
+use dsp_core::results::StageResults;
+use dsp_core::run_dir::{self, RunDirectory};
+use dsp_core::row;
+use dsp_core::signal_gen::{self, AmConfig, Baseband};
+use dsp_core::simd;
+use dsp_core::spectral_features::SpectralFeatures;
+use dsp_core::spectrum::{self, ShiftMethod};
+use dsp_core::watch::Watch;
+use dsp_core::wav_io::WavWriteOptions;
+use log::{error, info, warn};
 use num_complex::Complex;
+use q3_time_domain_demodulation::demodulator::{self, DemodMode, Demodulator};
+use q3_time_domain_demodulation::iir_filter;
+use q3_time_domain_demodulation::spectrum_analyzer as q3_spectrum_analyzer;
+use q3_time_domain_demodulation::ssb::Sideband;
+use std::path::{Path, PathBuf};
+
+/// Tone and modulation index of the synthetic AM signal behind the THD column
+const THD_TONE: f64 = 500.0;
+const THD_MOD_INDEX: f64 = 0.8;
+
+/// Length of the windowed-sinc filters in the FIR method of the comparison matrix
+const FIR_TAPS: usize = 255;
+
+/// Stretch at each end of the output (s) checked and plotted for clicks with the edge guard
+const EDGE_WINDOW: f64 = 0.02;
+
+/// Baseband tones of the `--guard-band` probe: three near the bottom of the message band, where
+/// the high-pass edge acts, and a mid-band reference the others are measured against
+const GUARD_PROBE_TONES: [f64; 3] = [80.0, 150.0, 300.0];
+const GUARD_REFERENCE_TONE: f64 = 1000.0;
+
+/// Input SNRs (dB) visited by `--robustness`
+const ROBUSTNESS_SNRS: [f64; 7] = [-5.0, 0.0, 5.0, 10.0, 15.0, 20.0, 30.0];
+
+/// Default `sweep` grid: Butterworth orders, and low-pass cutoffs as multiples of f_B
+const SWEEP_ORDERS: [usize; 5] = [2, 4, 6, 8, 10];
+const SWEEP_CUTOFF_FACTORS: [f64; 5] = [0.8, 0.9, 1.0, 1.1, 1.2];
+
+/// The recording to demodulate unless `--synthetic` is given
+const INPUT: &str = "../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav";
 
 fn main() {
-    println!("Q4: Frequency-Domain Demodulation");
-    println!("===================================");
-
-    // Step 1: Read Q1 results to get f_d, f_s, f_B
-    println!("\n[Step 1] Reading Q1 results...");
-    let (f_d, f_s, f_b) = match read_q1_results() {
-        Ok(params) => params,
-        Err(e) => {
-            eprintln!("Error reading Q1 results: {}", e);
-            return;
+    logging::init_from_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    if let Err(e) = run_or_watch() {
+        e.exit("Error");
+    }
+}
+
+/// Run once, or with `--watch` again whenever an input changes: the recording (or the
+/// `--synthetic` signal and its truth file), Q1's results and Q2's coefficients, or every
+/// WAV of the folder and its Q1 results with `--batch`
+fn run_or_watch() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(watch) = Watch::from_args(&args)? else {
+        return run();
+    };
+    let q2_filters = run_dir::upstream_path(&args, "../Q2/output", "Q2_filter_coefficients.txt")?.into();
+    let inputs: Vec<PathBuf> = match (batch::dir_from_args(&args)?, synthetic_from_args(&args)?) {
+        (Some(dir), _) => {
+            let files = batch::wav_files(&dir)?;
+            let results = files.iter().map(|file| batch::file_dir("../Q1/output", file).join("Q1_results.txt"));
+            results.chain(files.iter().cloned()).collect()
         }
+        (None, Some(name)) => ["_truth.txt", ".wav", "_baseband.wav"]
+            .iter()
+            .map(|suffix| PathBuf::from(format!("{}{}", name, suffix)))
+            .chain([q2_filters])
+            .collect(),
+        (None, None) => vec![
+            run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?.into(),
+            q2_filters,
+            INPUT.into(),
+        ],
+    };
+    watch.run(&inputs, run)
+}
+
+fn run() -> Result<()> {
+    info!("Q4: Frequency-Domain Demodulation");
+    info!("===================================");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let channel_mode = ChannelMode::from_args(&args)?;
+    if let Some(dir) = batch::dir_from_args(&args)? {
+        return run_batch(&dir, &args, channel_mode);
+    }
+    // `replot` subcommand: figures only, from the arrays of an earlier `--npz` run
+    if args.first().map(String::as_str) == Some("replot") {
+        return run_replot(&args);
+    }
+    let run_directory = RunDirectory::from_args(&args, "output")?;
+
+    // `--synthetic <name>` runs on signal_gen's output, with its truth file standing in for Q1
+    let synthetic = synthetic_from_args(&args)?;
+
+    // Step 1: Read Q1 results to get f_d, f_s, f_B (`--from-run <tag>` reads archived runs)
+    info!("\n[Step 1] Reading Q1 results...");
+    let parameters_path = match &synthetic {
+        Some(name) => format!("{}_truth.txt", name),
+        None => run_dir::upstream_path(&args, "../Q1/output", "Q1_results.txt")?,
     };
-    println!("  f_d = {:.4} Hz", f_d);
-    println!("  f_s = {:.4} Hz", f_s);
-    println!("  f_B = {:.4} Hz", f_b);
+    let q2_filters_path = run_dir::upstream_path(&args, "../Q2/output", "Q2_filter_coefficients.txt")?;
+    let (f_d, f_s, f_b) = read_parameters(&parameters_path)?;
+    info!("  f_d = {:.4} Hz", f_d);
+    info!("  f_s = {:.4} Hz", f_s);
+    info!("  f_B = {:.4} Hz", f_b);
 
     // Step 2: Read audio signal
-    println!("\n[Step 2] Reading audio signal...");
-    let audio_samples = match audio_reader::read_wav("../../工程设计问题-2022/工程设计题15. 调幅信号的解调/project.wav") {
-        Ok(samples) => samples,
-        Err(e) => {
-            eprintln!("Error reading audio: {}", e);
-            return;
+    info!("\n[Step 2] Reading audio signal...");
+    let input_path = match &synthetic {
+        Some(name) => format!("{}.wav", name),
+        None => INPUT.to_string(),
+    };
+    let (channels, source_spec) = audio_reader::read_wav_channels(&input_path, channel_mode)?;
+    let input_clipping = ClippingReport::detect(&channels);
+    if input_clipping.runs > 0 {
+        warn!(
+            "  Warning: the input looks clipped: {} sample(s) in {} flat top(s) at {:.2} dBFS, longest {} samples",
+            input_clipping.clipped_samples, input_clipping.runs, input_clipping.peak_dbfs, input_clipping.longest_run
+        );
+    }
+    // The clean m(t) written next to a synthetic input, for scoring each method against
+    let ground_truth = match &synthetic {
+        Some(name) => {
+            let path = format!("{}_baseband.wav", name);
+            let reference = audio_reader::read_wav(&path)?;
+            Some((path, reference))
+        }
+        None => None,
+    };
+    info!("  Channel mode: {} ({} channel(s) processed)", channel_mode, channels.len());
+    let output_options = WavWriteOptions::from_args(&args, &source_spec)?;
+    let output_rate = resample::output_rate_from_args(&args)?;
+    let decimation = resample::decimation_from_args(&args)?;
+    let agc = AgcConfig::from_args(&args)?;
+    let post_filter = PostFilter::from_args(&args)?;
+    let denoiser = Denoiser::from_args(&args)?;
+    let activity = ActivityDetector::from_args(&args)?;
+    let shift_method = ShiftMethod::from_args(&args)?;
+    let guard_band = demodulator::guard_band_from_args(&args)?;
+    if guard_band >= f_d {
+        return Err(DspError::invalid_value("--guard-band", &guard_band.to_string()));
+    }
+    let compare_range = comparator::time_range_from_args(&args)?;
+    let checkpoints = Checkpoints::from_args(&args)?;
+    let impairments = ChannelImpairments::from_args(&args)?;
+    let plots = PlotOutput::from_args(&args)?;
+
+    // The carrier product spans up to 2 f_d + f_B. If that passes Nyquist the circular
+    // frequency shift wraps it around, so the whole chain runs at a higher rate instead
+    let source_fs = f_s;
+    let source_samples = channels[0].clone();
+    let input_levels = Levels::measure(&channels, source_fs)?;
+    let mixer_top = 2.0 * f_d + f_b;
+    let (channels, f_s) = if mixer_top > f_s / 2.0 {
+        let work_fs = f_s * (2.0 * mixer_top / f_s).ceil();
+        info!(
+            "  2 f_d + f_B = {:.1} Hz exceeds Nyquist, upsampling to {:.0} Hz",
+            mixer_top, work_fs
+        );
+        let upsampled = channels.iter().map(|c| resample(c, f_s, work_fs)).collect::<Vec<_>>();
+        (upsampled, work_fs)
+    } else {
+        (channels, f_s)
+    };
+
+    // `--carrier-search`: refine Q1's f_d on the input itself before anything uses it. Q3's
+    // output, recomputed below for the comparison, keeps Q1's value as Q3 itself does
+    let q1_f_d = f_d;
+    let carrier_search = match CarrierSearch::from_args(&args)? {
+        Some(search) => {
+            info!("\n[Carrier search] {} around f_d = {:.4} Hz...", search, f_d);
+            let result = search.run(&channels[0], f_d, f_b, f_s);
+            let at_estimate = result.candidates[result.candidates.len() / 2];
+            let best = result.best();
+            info!("  Q1 estimate {:.4} Hz: concentration {:.6}", at_estimate.frequency, at_estimate.concentration);
+            info!("  Best grid point {:.4} Hz: concentration {:.6}", best.frequency, best.concentration);
+            if Some(&best) == result.candidates.first() || Some(&best) == result.candidates.last() {
+                warn!("  Best candidate is at the edge of the grid; widen it with --carrier-span");
+            }
+            std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+            comparator::save_carrier_search_csv(&result, "output/Q4_carrier_search.csv")?;
+            comparator::plot_carrier_search(&result, plots.target("output/Q4_carrier_search.png"))?;
+            Some(result)
+        }
+        None => None,
+    };
+    let f_d = match &carrier_search {
+        Some(result) => {
+            let refined = result.peak();
+            info!("  Carrier: f_d = {:.4} Hz ({:+.4} Hz from Q1)", refined, refined - q1_f_d);
+            refined
         }
+        None => f_d,
     };
+
+    // `sweep` subcommand: grid of filter orders and cutoffs instead of the step-by-step run
+    if args.first().map(String::as_str) == Some("sweep") {
+        info!("\n[Sweep] Q3 vs Q4 over filter order and low-pass cutoff...");
+        return run_parameter_sweep(&source_samples, source_fs, f_s, (f_d, f_b), shift_method, &args, &plots);
+    }
+
+    // The first selected channel drives the step-by-step report, spectra and comparison
+    let audio_samples = &channels[0];
     let n = audio_samples.len();
-    println!("  Number of samples: {}", n);
+    info!("  Number of samples: {}", n);
+
+    // Output gain: derived from the chain's stages, or matched to Q3's output level
+    let gain_calibrator = GainCalibrator::from_args(&args, (0.0, f_b))?;
+    // Optional fades and zero-padding against the FFT's wrap-around at the file ends
+    let edges = EdgeGuard::from_args(&args, f_s)?;
+    let stages = match edges {
+        Some(edges) => {
+            info!("  Edge guard: {}", edges);
+            demodulation_stages(f_d, f_b, guard_band, shift_method).with_edges(edges)
+        }
+        None => demodulation_stages(f_d, f_b, guard_band, shift_method),
+    };
+    let calibration = match gain_calibrator {
+        GainCalibrator::Analytic => GainCalibrator::analytic(stages.stages()),
+        GainCalibrator::RmsMatch { band } => {
+            let (hp_b, hp_a, lp_b, lp_a) = iir_filter::read_q2_filters(&q2_filters_path)?;
+            let q3 = TimeDomainDemodulator::new(q1_f_d, source_fs, (hp_b, hp_a), (lp_b, lp_a));
+            let q3_gain = q2_chain_gain(&q2_filters_path, q1_f_d)?.gain;
+            let q3_output: Vec<f64> = q3.demodulate(&source_samples).iter().map(|y| q3_gain * y).collect();
+            let reference = resample(&q3_output, source_fs, f_s);
+            GainCalibrator::rms_match(&stages.run(audio_samples, f_s).output, &reference, f_s, band)?
+        }
+    };
+    info!("  Output gain ({}): x{:.6}", gain_calibrator, calibration.gain);
+    for line in &calibration.derivation {
+        info!("    {}", line);
+    }
+
+    // Steps 3-7 run as one pipeline; the reports below walk through its stages
+    let mut pipeline = stages.clone().gain(calibration.gain);
+    if let Some(checkpoints) = checkpoints {
+        info!(
+            "  Checkpoints: {}{}",
+            checkpoints.dir.display(),
+            if checkpoints.resume { " (resuming)" } else { "" }
+        );
+        pipeline = pipeline.with_checkpoints(checkpoints);
+    }
+    let result = pipeline.run(audio_samples, f_s);
+    if result.resumed > 0 {
+        info!("  Resumed {} of {} spectra from checkpoints", result.resumed, result.stages.len() + 1);
+    }
 
     // Step 3: Compute FFT of input signal
-    println!("\n[Step 3] Computing FFT of input signal...");
-    let x_fft = compute_fft(&audio_samples);
-    println!("  FFT size: {}", x_fft.len());
+    info!("\n[Step 3] Computing FFT of input signal...");
+    let x_fft = &result.input;
+    info!("  FFT size: {}", x_fft.len());
 
     // Step 4: Apply ideal high-pass filter in frequency domain
-    println!("\n[Step 4] Applying ideal high-pass filter (fc = {:.4} Hz)...", f_d);
-    let xh_fft = ideal_filter::apply_highpass(&x_fft, f_d, f_s, n);
-    println!("  High-pass filtering complete");
+    info!("\n[Step 4] Applying ideal high-pass filter (fc = {:.4} Hz)...", f_d - guard_band);
+    let xh_fft = &result.stages[0].spectrum;
+    info!("  High-pass filtering complete");
 
     // Step 5: Frequency shift (equivalent to carrier multiplication in time domain)
-    println!("\n[Step 5] Performing frequency shift (±{:.4} Hz)...", f_d);
-    let xb_fft = frequency_shifter::frequency_shift(&xh_fft, f_d, f_s, n);
-    println!("  Frequency shift complete");
+    info!("\n[Step 5] Performing frequency shift (±{:.4} Hz)...", f_d);
+    let xb_fft = &result.stages[1].spectrum;
+    match shift_method {
+        ShiftMethod::NearestBin => info!(
+            "  Method: {} (rounding error {:+.4} Hz)",
+            shift_method,
+            spectrum::bin_rounding_error(f_d, f_s, n)
+        ),
+        ShiftMethod::Mixing => info!("  Method: {}", shift_method),
+    }
+    info!("  Frequency shift complete");
 
     // Step 6: Apply ideal low-pass filter
-    println!("\n[Step 6] Applying ideal low-pass filter (fc = {:.4} Hz)...", f_b);
-    let xl_fft = ideal_filter::apply_lowpass(&xb_fft, f_b, f_s, n);
-    println!("  Low-pass filtering complete");
-
-    // Step 7: Inverse FFT to get time-domain signal
-    println!("\n[Step 7] Computing IFFT to recover time-domain signal...");
-    let mut xl_samples = compute_ifft(&xl_fft);
-    println!("  Output samples: {}", xl_samples.len());
-    
-    // Apply gain compensation (multiply by 2 to match time-domain method)
-    for sample in xl_samples.iter_mut() {
-        *sample *= 2.0;
-    }
-    
+    info!("\n[Step 6] Applying ideal low-pass filter (fc = {:.4} Hz)...", f_b);
+    let xl_fft = &result.stages[2].spectrum;
+    info!("  Low-pass filtering complete");
+
+    // Step 7: Inverse FFT to get time-domain signal, with the calibrated output gain
+    info!("\n[Step 7] Computing IFFT to recover time-domain signal...");
+    let xl_samples = result.output;
+    info!("  Output samples: {}", xl_samples.len());
+
     let max_val = xl_samples.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
-    println!("  Signal max: {:.6}", max_val);
+    info!("  Signal max: {:.6}", max_val);
+
+    // With the edge guard, show what it did to the clicks at the file ends
+    if edges.is_some() {
+        let plain = demodulation_stages(f_d, f_b, guard_band, shift_method).gain(calibration.gain).run(audio_samples, f_s).output;
+        let window = (EDGE_WINDOW * f_s) as usize;
+        let (plain_start, plain_end) = pipeline::edge_clicks_db(&plain, window);
+        let (start, end) = pipeline::edge_clicks_db(&xl_samples, window);
+        info!(
+            "  Edge clicks (largest step within {:.0} ms of each end, relative to the rest): start {:.1} -> {:.1} dB, end {:.1} -> {:.1} dB",
+            EDGE_WINDOW * 1000.0,
+            plain_start,
+            start,
+            plain_end,
+            end
+        );
+        comparator::plot_edges(&plain, &xl_samples, f_s, EDGE_WINDOW, plots.target("output/Q4_edges.png"))?;
+    }
+
+    // Audition input, band-shifted intermediate and output with --play
+    if playback::requested(&args) {
+        info!("\n[Playback]");
+        let xb_samples = pipeline::ifft(xb_fft);
+        for (label, signal) in [
+            ("misdemodulated input x(t)", audio_samples.as_slice()),
+            ("after frequency shift x_b(t)", xb_samples.as_slice()),
+            ("demodulated output x_l(t)", xl_samples.as_slice()),
+        ] {
+            if let Err(e) = playback::play(label, signal, f_s) {
+                warn!("  Playback unavailable: {}", e);
+                break;
+            }
+        }
+    }
 
     // Step 8: Create output directory
-    std::fs::create_dir_all("output").expect("Failed to create output directory");
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
 
     // Step 9: Spectrum analysis for each stage
-    println!("\n[Step 8] Performing spectrum analysis...");
-    let original_spectrum = compute_magnitude_spectrum(&x_fft, f_s);
-    let xh_spectrum = compute_magnitude_spectrum(&xh_fft, f_s);
-    let xb_spectrum = compute_magnitude_spectrum(&xb_fft, f_s);
-    let xl_spectrum = compute_magnitude_spectrum(&xl_fft, f_s);
+    info!("\n[Step 8] Performing spectrum analysis...");
+    let magnitude = |spectrum: &[Complex<f64>]| compute_magnitude_spectrum(spectrum, f_s);
+    let ((original_spectrum, xh_spectrum), (xb_spectrum, xl_spectrum)) = rayon::join(
+        || rayon::join(|| magnitude(x_fft), || magnitude(xh_fft)),
+        || rayon::join(|| magnitude(xb_fft), || magnitude(xl_fft)),
+    );
+
+    // Objective spectral descriptors of the input and the demodulated output
+    let original_features = spectral_descriptors(&original_spectrum);
+    let xl_features = spectral_descriptors(&xl_spectrum);
+    for (label, features) in [("Original X(f)", original_features), ("Demodulated X_l(f)", xl_features)] {
+        if let Some(sf) = features {
+            info!(
+                "  {}: centroid {:.1} Hz, spread {:.1} Hz, flatness {:.4}, rolloff(85%) {:.1} Hz",
+                label, sf.centroid, sf.spread, sf.flatness, sf.rolloff
+            );
+        }
+    }
 
     // Step 10: Plot spectra
-    println!("\n[Step 9] Plotting spectra...");
-    spectrum_analyzer::plot_spectrum(&original_spectrum, "output/Q4_original_spectrum.png", "Original Signal X(f)");
-    spectrum_analyzer::plot_spectrum(&xh_spectrum, "output/Q4_xh_spectrum.png", "After Ideal High-Pass X_h(f)");
-    spectrum_analyzer::plot_spectrum(&xb_spectrum, "output/Q4_xb_spectrum.png", "After Frequency Shift X_b(f)");
-    spectrum_analyzer::plot_spectrum(&xl_spectrum, "output/Q4_xl_spectrum.png", "After Ideal Low-Pass X_l(f) - Demodulated");
+    info!("\n[Step 9] Plotting spectra...");
+    plot_stage_figures(
+        (audio_samples, &xl_samples),
+        [x_fft, xh_fft, xb_fft, xl_fft],
+        [&original_spectrum, &xh_spectrum, &xb_spectrum, &xl_spectrum],
+        (f_s, f_d, f_b),
+        compare_range,
+        &plots,
+    )?;
+    // Optional zoomable copies for inspecting the sideband peaks
+    if html_plot::requested(&args) {
+        save_interactive_plots(
+            "Q4: Frequency-Domain Demodulation",
+            &[
+                ("Original Signal X(f)", &original_spectrum),
+                ("After Ideal High-Pass X_h(f)", &xh_spectrum),
+                ("After Frequency Shift X_b(f)", &xb_spectrum),
+                ("After Ideal Low-Pass X_l(f)", &xl_spectrum),
+            ],
+            &[("input x(t)", audio_samples), ("demodulated x_l(t)", &xl_samples)],
+            f_s,
+            (f_d, f_b),
+            "output/Q4_interactive.html",
+        );
+    }
+    // Optional raw arrays for cross-checking each stage in NumPy or MATLAB
+    if npy::requested(&args) || mat::requested(&args) {
+        let frequencies: Vec<f64> = (0..n).map(|k| k as f64 * f_s / n as f64).collect();
+        let arrays = Npz::new()
+            .array("f_s", vec![f_s])
+            .array("f_d", vec![f_d])
+            .array("f_b", vec![f_b])
+            .array("x", audio_samples.as_slice())
+            .array("frequencies", frequencies)
+            .array("X", x_fft.as_slice())
+            .array("X_h", xh_fft.as_slice())
+            .array("X_b", xb_fft.as_slice())
+            .array("X_l", xl_fft.as_slice())
+            .array("x_l", xl_samples.as_slice());
+        let mut saved = Vec::new();
+        if npy::requested(&args) {
+            saved.push(("output/Q4_signals.npz", arrays.save("output/Q4_signals.npz")));
+        }
+        if mat::requested(&args) {
+            saved.push(("output/Q4_signals.mat", mat::save("output/Q4_signals.mat", &arrays.arrays)));
+        }
+        for (filename, result) in saved {
+            match result {
+                Ok(()) => info!("  Saved to: {}", filename),
+                Err(e) => error!("  Error saving arrays: {}", e),
+            }
+        }
+    }
 
     // Step 11: Save demodulated audio
-    println!("\n[Step 10] Saving demodulated audio...");
-    match audio_writer::write_wav("output/Q4_demodulated.wav", &xl_samples, f_s as u32) {
-        Ok(_) => println!("  Saved to: output/Q4_demodulated.wav"),
-        Err(e) => eprintln!("  Error saving audio: {}", e),
+    info!("\n[Step 10] Saving demodulated audio...");
+    // Remaining channels go through the same FFT-domain chain
+    let mut demodulated = vec![xl_samples.clone()];
+    let calibrated = stages.gain(calibration.gain);
+    demodulated.extend(parallel::map_signals(&channels[1..], |samples| calibrated.run(samples, f_s).output));
+    // Back to the input rate, or down to --decimate / --output-rate for a compact baseband file
+    // Optional clean-up of the brick-wall filters' DC thump and edge clicks, ahead of AGC
+    let demodulated = match post_filter {
+        Some(filter) => apply_post_filter(demodulated, f_s, &filter),
+        None => demodulated,
+    };
+    // Optional spectral subtraction of the hiss the chain passes along with the message
+    let demodulated = match denoiser {
+        Some(denoiser) => apply_denoiser(demodulated, f_s, f_b, &denoiser, &plots)?,
+        None => demodulated,
+    };
+    // Optional AGC evens out fades in the written audio; the analysis uses the raw output
+    let demodulated = match agc {
+        Some(config) => apply_agc(demodulated, f_s, &config, plots.target("output/Q4_agc_envelope.png"))?,
+        None => demodulated,
+    };
+    let (demodulated, decimated_fs) = decimate_output(demodulated, decimation, f_s, f_b);
+    let output_fs = output_rate.unwrap_or(if decimation > 1 { decimated_fs } else { source_fs });
+    let demodulated: Vec<Vec<f64>> = demodulated.iter().map(|c| resample(c, decimated_fs, output_fs)).collect();
+    let headroom = Headroom::measure(&demodulated, &output_options);
+    report_headroom(&headroom, calibration.gain);
+    match audio_writer::write_wav_channels("output/Q4_demodulated.wav", &demodulated, output_fs as u32, &output_options) {
+        Ok(_) => info!("  Saved to: output/Q4_demodulated.wav ({} Hz, {})", output_fs, output_options),
+        Err(e) => error!("  Error saving audio: {}", e),
     }
 
     // Step 12: Compare with Q3 results
-    println!("\n[Step 11] Comparing with Q3 results...");
-    if let Ok(q3_samples) = audio_reader::read_wav("../Q3/output/Q3_demodulated.wav") {
-        let comparison = comparator::compare_signals(&xl_samples, &q3_samples);
-        println!("  Q3 vs Q4 comparison:");
-        println!("    MSE: {:.6e}", comparison.mse);
-        println!("    Max difference: {:.6}", comparison.max_diff);
-        println!("    Correlation (original): {:.6}", comparison.correlation);
-        println!("    Correlation (normalized): {:.6}", comparison.correlation_normalized);
+    info!("\n[Step 11] Comparing with Q3 results...");
+    // Q3's chain is recomputed in process on the channel reported above, with Q2's filters,
+    // rather than read back from Q3_demodulated.wav (which may be resampled, normalized or AGC'd)
+    if let Ok((hp_b, hp_a, lp_b, lp_a)) = iir_filter::read_q2_filters(&q2_filters_path) {
+        let q3 = TimeDomainDemodulator::new(q1_f_d, source_fs, (hp_b, hp_a), (lp_b, lp_a));
+        // Q3's ×2 mixer leaves the message at half level behind its high-pass at f_d; the same
+        // analytic calibration as Q4's brings it to the message level, so the metrics below
+        // compare waveforms rather than a fixed 6 dB gain difference
+        let q3_gain = q2_chain_gain(&q2_filters_path, q1_f_d)?;
+        info!("  Q3 output gain (analytic): x{}", q3_gain.gain);
+        let mut q3_output: Vec<f64> = q3.demodulate(&source_samples).iter().map(|y| q3_gain.gain * y).collect();
+        // `--compensate-delay` removes Q3's mean passband group delay, as Q3's option does
+        if args.iter().any(|a| a == "--compensate-delay") {
+            let delay = q3.passband_group_delay(f_b);
+            info!("  Advancing Q3's output by its passband group delay: {:.2} samples", delay);
+            q3_output = demodulator::advance(&q3_output, delay.round() as usize);
+        }
+        let q3_samples = resample(&q3_output, source_fs, f_s);
+        let comparison = comparator::compare_signals(&xl_samples, &q3_samples, f_s);
+        info!("  Q3 vs Q4 comparison:");
+        info!("    MSE: {:.6e}", comparison.mse);
+        info!("    Max difference: {:.6}", comparison.max_diff);
+        info!("    Correlation (original): {:.6}", comparison.correlation);
+        info!("    Correlation (normalized): {:.6}", comparison.correlation_normalized);
+        info!(
+            "    Log-spectral distance: {:.2} dB, coherence: {:.4}, band-limited SNR: {:.2} dB ({:.0}-{:.0} Hz)",
+            comparison.log_spectral_distance,
+            comparison.coherence,
+            comparison.band_snr_db,
+            comparator::SPECTRAL_BAND.0,
+            comparator::SPECTRAL_BAND.1
+        );
+        // Q3's IIR filters delay its output; compare again with that lag removed
+        let alignment = comparator::align(&xl_samples, &q3_samples, f_s);
+        info!(
+            "  Lag-aligned (Q3 lags by {} samples = {:.3} ms, cross-correlation {:.6}):",
+            alignment.lag,
+            alignment.lag as f64 / f_s * 1000.0,
+            alignment.correlation
+        );
+        info!("    MSE: {:.6e}", alignment.aligned.mse);
+        info!("    Correlation (normalized): {:.6}", alignment.aligned.correlation_normalized);
+        info!("    SNR: {:.2} dB (raw {:.2} dB)", alignment.aligned.snr_db, comparison.snr_db);
+        info!(
+            "    Band-limited SNR: {:.2} dB, coherence: {:.4}",
+            alignment.aligned.band_snr_db, alignment.aligned.coherence
+        );
+        
+        // Per-frame agreement of the aligned outputs shows where they differ
+        let (aligned_q4, aligned_q3) = alignment.apply(&xl_samples, &q3_samples);
+        let frames = comparator::segmental_metrics(aligned_q4, aligned_q3, f_s, comparator::SEGMENT_SECONDS);
+        if let Some(worst) = frames.iter().min_by(|a, b| a.snr_db.total_cmp(&b.snr_db)) {
+            info!(
+                "    Segmental SNR: {:.2} dB; worst {:.0} ms frame at {:.3} s ({:.2} dB, correlation {:.4})",
+                comparator::segmental_snr(&frames),
+                comparator::SEGMENT_SECONDS * 1000.0,
+                worst.time,
+                worst.snr_db,
+                worst.correlation
+            );
+        }
         
-        // Save comparison results
-        comparator::save_comparison(&comparison, "output/Q4_comparison.txt");
+        // `--active-only`: the same metrics over the segments where Q4's output is active,
+        // so pauses that agree trivially (or carry only residual noise) are left out
+        if let Some(detector) = activity {
+            let segments = detector.detect(aligned_q4, f_s);
+            let active = comparator::compare_active(aligned_q4, aligned_q3, f_s, &segments);
+            info!(
+                "  Active segments only ({} segments, {:.1} % of the overlap):",
+                segments.len(),
+                100.0 * activity::active_samples(&segments) as f64 / aligned_q4.len().max(1) as f64
+            );
+            info!("    Correlation (normalized): {:.6}", active.correlation_normalized);
+            info!("    SNR: {:.2} dB, band-limited SNR: {:.2} dB", active.snr_db, active.band_snr_db);
+            comparator::save_active_comparison(
+                &alignment.aligned,
+                &active,
+                &segments,
+                aligned_q4.len(),
+                f_s,
+                "output/Q4_comparison_active.txt",
+            )?;
+        }
+
+        // Perceptual view: how intelligible Q3's output is, taking Q4's as the reference
+        let intelligibility = comparator::stoi(&xl_samples, &q3_samples, f_s);
+        info!("    Intelligibility (STOI of Q3 against Q4): {:.4}", intelligibility);
+        
+        // Where in the baseband the aligned outputs differ
+        let bands = comparator::band_metrics(aligned_q4, aligned_q3, f_s, &comparator::REPORT_BANDS);
+        for band in &bands {
+            info!(
+                "    {:>4.0}-{:<4.0} Hz: SNR {:>6.2} dB, coherence {:.4}, log-spectral distance {:.2} dB",
+                band.low, band.high, band.snr_db, band.coherence, band.log_spectral_distance
+            );
+        }
+
+        // Save comparison results, as a report and as JSON for scripts
+        comparator::save_comparison(&comparison, &alignment, intelligibility, "output/Q4_comparison.txt")?;
+        comparator::save_comparison_json(
+            &comparison,
+            &alignment,
+            &bands,
+            intelligibility,
+            f_s,
+            "output/Q4_comparison.json",
+        )?;
         
         // Plot full-time comparison (all samples)
-        comparator::plot_full_comparison(&xl_samples, &q3_samples, "output/Q4_vs_Q3_full_comparison.png");
+        comparator::plot_full_comparison(&xl_samples, &q3_samples, f_s, plots.target("output/Q4_vs_Q3_full_comparison.png"))?;
         
-        // Plot detailed comparison (first 2000 samples)
-        comparator::plot_comparison(&xl_samples, &q3_samples, "output/Q4_vs_Q3_comparison.png");
+        // Plot detailed comparison (first 2000 samples unless --compare-range is given)
+        comparator::plot_comparison(
+            &xl_samples,
+            &q3_samples,
+            f_s,
+            compare_range,
+            plots.target("output/Q4_vs_Q3_comparison.png"),
+        )?;
+
+        // Where the two methods disagree, in time and in frequency
+        comparator::plot_error(&xl_samples, &q3_samples, f_s, plots.target("output/Q4_vs_Q3_error.png"))?;
+        comparator::plot_segmental(&frames, plots.target("output/Q4_vs_Q3_segmental.png"))?;
+        let (worst_freq, worst_db) = comparator::error_spectrum(&xl_samples, &q3_samples, f_s)
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0.0, f64::NEG_INFINITY));
+        info!("    Largest spectral difference: {:.1} dB at {:.1} Hz", worst_db, worst_freq);
+    } else {
+        info!("  Warning: Could not read Q2's filter coefficients ({}) to recompute Q3's output", q2_filters_path);
+    }
+    info!("\n  Cross-method comparison matrix:");
+    let method_outputs = run_method_matrix(
+        &source_samples,
+        source_fs,
+        f_s,
+        (f_d, f_b, guard_band),
+        shift_method,
+        compare_range,
+        denoiser.unwrap_or_default(),
+        &q2_filters_path,
+        &plots,
+    )?;
+    // Loudness of the input, the written output and every method, to match levels by
+    let mut levels = vec![
+        ("input".to_string(), input_levels),
+        ("Q4_demodulated.wav".to_string(), Levels::measure(&demodulated, output_fs)?),
+    ];
+    for (name, output) in &method_outputs {
+        levels.push((name.clone(), Levels::measure(&[output], source_fs)?));
+    }
+    info!("\n  Levels (BS.1770 loudness, RMS, sample peak):");
+    for (signal, level) in &levels {
+        info!("    {:<28} {}", signal, level);
+    }
+    if let Some((path, reference)) = &ground_truth {
+        info!("\n  Ground-truth evaluation:");
+        let names: Vec<&str> = method_outputs.iter().map(|(name, _)| name.as_str()).collect();
+        let results: Vec<comparator::Reconstruction> = method_outputs
+            .iter()
+            .map(|(_, output)| comparator::reconstruction(reference, output, source_fs))
+            .collect();
+        comparator::save_ground_truth(&names, &results, source_fs, path, "output/Q4_ground_truth.txt")?;
+    }
+
+    // Step 13: Periodicity (pitch) analysis of the recovered baseband
+    info!("\n[Step 12] Analyzing periodicity of demodulated signal...");
+    let periodicity = autocorrelation::estimate_periodicity(&xl_samples, f_s, (50.0, 1000.0));
+    match periodicity {
+        Some(p) => info!(
+            "  Fundamental: {:.2} Hz (period {:.3} ms, strength {:.3})",
+            p.frequency,
+            p.period * 1000.0,
+            p.strength
+        ),
+        None => info!("  No periodicity found in 50-1000 Hz"),
+    }
+    save_periodicity(periodicity, "output/Q4_periodicity.txt")?;
+
+    // With a guard band, what it does to the lowest message frequencies of both chains
+    if guard_band > 0.0 {
+        info!("\n[Guard band] Probe tones with the high-pass at f_d and at f_d - {:.1} Hz...", guard_band);
+        run_guard_comparison(source_fs, f_s, (f_d, f_b, guard_band), shift_method)?;
+    }
+
+    // Optional: output quality of both demodulators against channel noise
+    if args.iter().any(|a| a == "--robustness") {
+        info!("\n[Robustness] Sweeping input SNR ({})...", describe_impairments(&impairments));
+        run_robustness_sweep(
+            &source_samples,
+            source_fs,
+            f_s,
+            (f_d, f_b, guard_band),
+            shift_method,
+            &impairments,
+            &q2_filters_path,
+            &plots,
+        )?;
+    }
+
+    // Step 14: Save analysis results
+    info!("\n[Step 13] Saving analysis results...");
+    save_results(
+        &original_spectrum,
+        &xh_spectrum,
+        &xb_spectrum,
+        &xl_spectrum,
+        (original_features, xl_features),
+        f_d,
+        f_s,
+        f_b,
+        carrier_search.as_ref(),
+        (gain_calibrator, &calibration),
+        &levels,
+        (&input_clipping, &headroom),
+        &Reproducibility::from_args(&args)?,
+    )?;
+
+    info!("\nQ4 Frequency-Domain Demodulation completed successfully!");
+    info!("Output files saved in: codes/Q4/output/");
+    if let Some(run_directory) = run_directory {
+        let dir = run_directory.finish("Q4", &[&parameters_path, &input_path], &args)?;
+        info!("Run archived to: codes/Q4/{} (output/latest points to it)", dir.display());
+    }
+    Ok(())
+}
+
+/// `--batch <dir>`: demodulate every WAV file in `dir` in the frequency domain with the
+/// f_d Q1 estimated for it
+///
+/// Each file's f_d, f_s and f_B come from `../Q1/output/batch/<name>/Q1_results.txt`
+/// (run `q1 --batch <dir>` first). The demodulated audio goes to
+/// `output/batch/<name>/Q4_demodulated.wav`. The summary row also holds the lag-aligned
+/// correlation with Q3's batch chain (8th-order Butterworth filters, with the same guard
+/// band), recomputed here. The summary is `output/Q4_batch_summary.csv`; a file that fails
+/// is recorded there and skipped.
+fn run_batch(dir: &Path, args: &[String], channel_mode: ChannelMode) -> Result<()> {
+    let files = batch::wav_files(dir)?;
+    let shift_method = ShiftMethod::from_args(args)?;
+    let guard_band = demodulator::guard_band_from_args(args)?;
+    let reproducibility = Reproducibility::from_args(args)?;
+    info!("\n[Batch] {} WAV file(s) in {}", files.len(), dir.display());
+
+    let columns = ["f_d_hz", "f_s_hz", "samples", "output_rms", "output_peak", "q3_correlation"];
+    let summary = BatchSummary::run(&files, &columns, |file| -> Result<Vec<String>> {
+        let q1_results = batch::file_dir("../Q1/output", file).join("Q1_results.txt");
+        let (f_d, f_s, f_b) = read_parameters(&q1_results.to_string_lossy())?;
+        let (channels, source_spec) = audio_reader::read_wav_channels(&file.to_string_lossy(), channel_mode)?;
+        if (source_spec.sample_rate as f64 - f_s).abs() > 0.5 {
+            return Err(DspError::Data(format!(
+                "{}: sample rate {} Hz differs from f_s = {} Hz in {}",
+                file.display(),
+                source_spec.sample_rate,
+                f_s,
+                q1_results.display()
+            )));
+        }
+        let demodulated: Vec<Vec<f64>> =
+            channels.iter().map(|channel| demodulate_fft(channel, f_d, f_s, f_b, guard_band, shift_method)).collect();
+
+        let out_dir = batch::file_dir("output", file);
+        std::fs::create_dir_all(&out_dir).map_err(|e| DspError::io(&out_dir, e))?;
+        let wav_path = out_dir.join("Q4_demodulated.wav");
+        let output_options = WavWriteOptions::from_args(args, &source_spec)?;
+        audio_writer::write_wav_channels(&wav_path.to_string_lossy(), &demodulated, f_s as u32, &output_options)?;
+        info!("  f_d = {:.4} Hz, saved to {}", f_d, wav_path.display());
+
+        let first = &demodulated[0];
+        let rms = (simd::energy(first) / first.len().max(1) as f64).sqrt();
+        let peak = first.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
+        let loudness = Levels::measure(&demodulated, f_s)?.integrated_lufs;
+        // Q3's batch chain on the same channel, recomputed rather than read from its batch output
+        let q3 = TimeDomainDemodulator::butterworth_guarded(BATCH_FILTER_ORDER, f_d, f_b, guard_band, f_s)
+            .demodulate(&channels[0]);
+        let q3_correlation = comparator::align(&q3, first, f_s).correlation;
+        StageResults::new("Q4")
+            .parameter("input", file.to_string_lossy().into_owned())
+            .parameter("f_d_hz", f_d)
+            .parameter("f_s_hz", f_s)
+            .parameter("f_b_hz", f_b)
+            .parameter("shift_method", shift_method.to_string())
+            .reproducibility(&reproducibility)
+            .metric("output_rms", rms)
+            .metric("output_peak", peak)
+            .metric("output_lufs", loudness)
+            .metric("q3_correlation", q3_correlation)
+            .save_with(out_dir.join("Q4_results.json"), &reproducibility)?;
+        Ok(vec![
+            format!("{:.4}", f_d),
+            format!("{}", f_s),
+            first.len().to_string(),
+            format!("{:.6e}", rms),
+            format!("{:.6e}", peak),
+            format!("{:.6}", q3_correlation),
+        ])
+    });
+
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    summary.save("output/Q4_batch_summary.csv")?;
+    info!(
+        "\n[Batch] {} succeeded, {} failed; summary saved to output/Q4_batch_summary.csv",
+        files.len() - summary.failures(),
+        summary.failures()
+    );
+    Ok(())
+}
+
+/// Frequency-domain chain without its output gain: ideal HPF at f_d - `guard_band`, shift by
+/// f_d, ideal LPF
+fn demodulation_stages(f_d: f64, f_b: f64, guard_band: f64, shift_method: ShiftMethod) -> SpectralPipeline {
+    SpectralPipeline::new().highpass(f_d - guard_band).shift_with(f_d, shift_method).lowpass(f_b)
+}
+
+/// Full frequency-domain chain with the analytic output gain
+fn demodulation_pipeline(f_d: f64, f_b: f64, guard_band: f64, shift_method: ShiftMethod) -> SpectralPipeline {
+    let stages = demodulation_stages(f_d, f_b, guard_band, shift_method);
+    let gain = GainCalibrator::analytic(stages.stages()).gain;
+    stages.gain(gain)
+}
+
+/// Analytic gain of Q3's coherent chain with Q2's filters, from the cutoffs Q2 recorded
+fn q2_chain_gain(q2_filters_path: &str, f_d: f64) -> Result<Calibration> {
+    let (highpass, lowpass) = iir_filter::read_q2_cutoffs(q2_filters_path)?;
+    Ok(GainCalibrator::coherent(highpass, f_d, lowpass))
+}
+
+/// Run the frequency-domain chain on one channel
+fn demodulate_fft(samples: &[f64], f_d: f64, f_s: f64, f_b: f64, guard_band: f64, shift_method: ShiftMethod) -> Vec<f64> {
+    demodulation_pipeline(f_d, f_b, guard_band, shift_method).run(samples, f_s).output
+}
+
+/// `--guard-band`: demodulate a DSB-SC probe with and without the guard band and score each
+/// output against the probe's clean baseband
+///
+/// The recording has no ground truth, so a probe at its f_d, f_B and rate stands in: tones at
+/// [`GUARD_PROBE_TONES`] and [`GUARD_REFERENCE_TONE`], run through Q4's ideal filters and
+/// through the 8th-order Butterworth chain Q2 designs, each with the high-pass at f_d and at
+/// f_d - guard band.
+fn run_guard_comparison(
+    source_fs: f64,
+    work_fs: f64,
+    (f_d, f_b, guard_band): (f64, f64, f64),
+    shift_method: ShiftMethod,
+) -> Result<()> {
+    let mut tones = GUARD_PROBE_TONES.to_vec();
+    tones.push(GUARD_REFERENCE_TONE);
+    let probe = signal_gen::generate(&AmConfig {
+        sample_rate: source_fs,
+        duration: 1.0,
+        offset: f_d,
+        bandwidth: f_b,
+        baseband: Baseband::Tones(tones),
+        ..AmConfig::default()
+    })?;
+
+    let mut results = Vec::new();
+    for guard in [0.0, guard_band] {
+        let upsampled = resample(&probe.samples, source_fs, work_fs);
+        let ideal = resample(&demodulate_fft(&upsampled, f_d, work_fs, f_b, guard, shift_method), work_fs, source_fs);
+        let butterworth = TimeDomainDemodulator::butterworth_guarded(BATCH_FILTER_ORDER, f_d, f_b, guard, source_fs)
+            .demodulate(&probe.samples);
+        for (method, output) in [("Q4 ideal filters", ideal), ("Q3 Butterworth", butterworth)] {
+            results.push(comparator::GuardBandResult {
+                method: method.to_string(),
+                guard_band: guard,
+                reconstruction: comparator::reconstruction(&probe.baseband, &output, source_fs),
+                levels_db: comparator::tone_levels_db(&output, source_fs, &GUARD_PROBE_TONES, GUARD_REFERENCE_TONE),
+            });
+        }
+    }
+    comparator::save_guard_band(&results, &GUARD_PROBE_TONES, GUARD_REFERENCE_TONE, "output/Q4_guard_band.txt")
+}
+
+/// Measure output SNR vs input SNR for the Q4 (and, if Q2 filters exist, Q3) chains
+///
+/// Both chains run at the recording rate `source_fs`; the FFT chain upsamples to
+/// `work_fs` internally when the mixer product needs it, as in the main run.
+#[allow(clippy::too_many_arguments)]
+fn run_robustness_sweep(
+    input: &[f64],
+    source_fs: f64,
+    work_fs: f64,
+    (f_d, f_b, guard_band): (f64, f64, f64),
+    shift_method: ShiftMethod,
+    impairments: &ChannelImpairments,
+    q2_filters_path: &str,
+    plots: &PlotOutput,
+) -> Result<()> {
+    let kind = impairments.noise.map(|(kind, _)| kind).unwrap_or_default();
+    let frequency_domain = |x: &[f64]| {
+        let upsampled = resample(x, source_fs, work_fs);
+        resample(&demodulate_fft(&upsampled, f_d, work_fs, f_b, guard_band, shift_method), work_fs, source_fs)
+    };
+    let mut curves = vec![(
+        "Q4 (Frequency-domain)",
+        comparator::robustness_curve(input, source_fs, &ROBUSTNESS_SNRS, impairments, kind, frequency_domain),
+    )];
+
+    match iir_filter::read_q2_filters(q2_filters_path) {
+        Ok(filters) => {
+            let time_domain = Demodulator::new(f_d, source_fs, filters);
+            let gain = q2_chain_gain(q2_filters_path, f_d)?.gain;
+            curves.push((
+                "Q3 (Time-domain)",
+                comparator::robustness_curve(input, source_fs, &ROBUSTNESS_SNRS, impairments, kind, |x| {
+                    time_domain.demodulate(x).iter().map(|y| gain * y).collect()
+                }),
+            ));
+        }
+        Err(e) => info!("  Skipping Q3 chain: {}", e),
+    }
+
+    for (method, points) in &curves {
+        info!("  {}:", method);
+        for p in points {
+            info!(
+                "    input {:>5.1} dB -> output {:>6.2} dB (MSE {:.3e}, correlation {:.4})",
+                p.input_snr_db, p.output_snr_db, p.mse, p.correlation
+            );
+        }
+    }
+    comparator::save_robustness_csv(&curves, "output/Q4_robustness.csv")?;
+    comparator::plot_robustness(&curves, plots.target("output/Q4_robustness.png"))
+}
+
+/// Rerun both chains over a grid of Butterworth orders (`--orders 2,4,6`) and low-pass
+/// cutoffs (`--cutoffs 2500,3000`), saving the Q3-vs-Q4 comparison of every combination
+///
+/// Q3's filters are designed here with the high-pass at f_d, instead of being read from Q2.
+/// The ideal filters of Q4 have no order, so its output depends only on the cutoff; the
+/// high-pass and shift are computed once and only the low-pass is redone per cutoff.
+fn run_parameter_sweep(
+    input: &[f64],
+    source_fs: f64,
+    work_fs: f64,
+    (f_d, f_b): (f64, f64),
+    shift_method: ShiftMethod,
+    args: &[String],
+    plots: &PlotOutput,
+) -> Result<()> {
+    let orders = list_from_args::<usize>(args, "--orders")?.unwrap_or(SWEEP_ORDERS.to_vec());
+    let cutoffs = list_from_args::<f64>(args, "--cutoffs")?
+        .unwrap_or(SWEEP_CUTOFF_FACTORS.iter().map(|k| k * f_b).collect());
+    if orders.contains(&0) {
+        return Err(DspError::Argument("Filter orders must be at least 1".to_string()));
+    }
+    if let Some(c) = cutoffs.iter().find(|&&c| c <= 0.0 || c >= source_fs / 2.0) {
+        return Err(DspError::Argument(format!("Cutoff {} Hz is outside (0, {}) Hz", c, source_fs / 2.0)));
+    }
+
+    let shifted = SpectralPipeline::new()
+        .highpass(f_d)
+        .shift_with(f_d, shift_method)
+        .run(&resample(input, source_fs, work_fs), work_fs);
+    let shifted = shifted.final_spectrum();
+
+    let mut points = Vec::new();
+    for &cutoff in &cutoffs {
+        let lowpassed = ideal_filter::apply_lowpass(shifted, cutoff, work_fs, shifted.len());
+        // The same analytic gains as the main run, for this grid point's cutoffs
+        let stages = SpectralPipeline::new().highpass(f_d).shift_with(f_d, shift_method).lowpass(cutoff);
+        let q4_gain = GainCalibrator::analytic(stages.stages()).gain;
+        let q3_gain = GainCalibrator::coherent(f_d, f_d, cutoff).gain;
+        let q4: Vec<f64> = pipeline::ifft(&lowpassed).iter().map(|x| q4_gain * x).collect();
+        let q4 = resample(&q4, work_fs, source_fs);
+        for &order in &orders {
+            let hp = ButterworthFilter::highpass(order, f_d, source_fs);
+            let lp = ButterworthFilter::lowpass(order, cutoff, source_fs);
+            let q3: Vec<f64> = Demodulator::new(f_d, source_fs, (hp.b, hp.a, lp.b, lp.a))
+                .demodulate(input)
+                .iter()
+                .map(|y| q3_gain * y)
+                .collect();
+            let comparison = comparator::compare_signals(&q4, &q3, source_fs);
+            info!(
+                "  order {:>2}, cutoff {:>7.1} Hz: SNR {:>6.2} dB, correlation {:.4}, MSE {:.3e}",
+                order, cutoff, comparison.snr_db, comparison.correlation_normalized, comparison.mse
+            );
+            points.push(SweepPoint {
+                order,
+                cutoff,
+                comparison,
+            });
+        }
+    }
+
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    comparator::save_sweep_csv(&points, "output/Q4_sweep.csv")?;
+    comparator::plot_sweep_heatmap(&points, plots.target("output/Q4_sweep_heatmap.png"))
+}
+
+/// `--synthetic <name>`: base name of a `signal_gen` run (`<name>.wav`, `<name>_truth.txt`
+/// and `<name>_baseband.wav`) to demodulate instead of the recording
+fn synthetic_from_args(args: &[String]) -> Result<Option<String>> {
+    let Some(position) = args.iter().position(|a| a == "--synthetic") else {
+        return Ok(None);
+    };
+    let name = args.get(position + 1).ok_or_else(|| DspError::Argument("--synthetic requires a signal_gen base name".to_string()))?;
+    Ok(Some(name.trim_end_matches(".wav").to_string()))
+}
+
+/// Comma-separated values after `name`, e.g. `--orders 2,4,8`
+fn list_from_args<T: std::str::FromStr>(args: &[String], name: &str) -> Result<Option<Vec<T>>> {
+    let Some(position) = args.iter().position(|a| a == name) else {
+        return Ok(None);
+    };
+    let value = args.get(position + 1).ok_or_else(|| DspError::missing_value(name))?;
+    value
+        .split(',')
+        .map(|v| v.trim().parse().map_err(|_| DspError::invalid_value(name, v)))
+        .collect::<Result<Vec<T>>>()
+        .map(Some)
+}
+
+/// Run every demodulator on the recording and on a synthetic AM tone, then save the
+/// pairwise output metrics (text, CSV and heatmap), an overlay of the outputs and each
+/// method's harmonic distortion
+///
+/// Returns each method's name and output on the recording.
+///
+/// Everything runs at the recording rate `source_fs`, which Q2's filters were designed for.
+/// Besides Q3's causal IIR chain, the coherent demodulator also runs with the same Q2
+/// filters applied forward-backward (zero-phase) and with linear-phase FIR filters, and
+/// the Q4 output goes through both denoisers with the `--denoise` settings.
+#[allow(clippy::too_many_arguments)]
+fn run_method_matrix(
+    input: &[f64],
+    source_fs: f64,
+    work_fs: f64,
+    (f_d, f_b, guard_band): (f64, f64, f64),
+    shift_method: ShiftMethod,
+    compare_range: Option<(f64, f64)>,
+    denoiser: Denoiser,
+    q2_filters_path: &str,
+    plots: &PlotOutput,
+) -> Result<Vec<(String, Vec<f64>)>> {
+    type Method<'a> = (&'a str, Box<dyn Fn(&[f64]) -> Vec<f64> + 'a>);
+    let q4 = move |x: &[f64]| {
+        let upsampled = resample(x, source_fs, work_fs);
+        resample(&demodulate_fft(&upsampled, f_d, work_fs, f_b, guard_band, shift_method), work_fs, source_fs)
+    };
+    let mut methods: Vec<Method> = vec![("Q4 frequency-domain", Box::new(q4))];
+    for (name, method) in [
+        ("Q4 + spectral subtraction", DenoiseMethod::SpectralSubtraction),
+        ("Q4 + Wiener", DenoiseMethod::Wiener { smoothing: DEFAULT_WIENER_SMOOTHING }),
+    ] {
+        let denoiser = Denoiser { method, ..denoiser };
+        // A signal too short to denoise is kept as it is
+        methods.push((
+            name,
+            Box::new(move |x: &[f64]| {
+                let output = q4(x);
+                denoiser.apply(&output, source_fs).map(|(denoised, _)| denoised).unwrap_or(output)
+            }),
+        ));
+    }
+    // The coherent chains below mix with 2 cos, which leaves the message at half level behind
+    // a high-pass at f_d; each takes the analytic gain of its own cutoffs so every output is
+    // at the message level and the metrics compare waveforms, not gains
+    let scaled = |output: Vec<f64>, gain: f64| output.into_iter().map(|y| gain * y).collect::<Vec<f64>>();
+    let fir_highpass = FirFilter::highpass(FIR_TAPS, f_d - guard_band, source_fs);
+    let fir_lowpass = FirFilter::lowpass(FIR_TAPS, f_b, source_fs);
+    let fir_gain = GainCalibrator::coherent(f_d - guard_band, f_d, f_b).gain;
+    methods.push((
+        "FIR coherent (linear phase)",
+        Box::new(move |x: &[f64]| {
+            let mixed = demodulator::multiply_with_carrier(&fir_highpass.apply(x), f_d, source_fs);
+            scaled(fir_lowpass.apply(&mixed), fir_gain)
+        }),
+    ));
+    let q2 = iir_filter::read_q2_filters(q2_filters_path)
+        .and_then(|filters| Ok((filters, iir_filter::read_q2_cutoffs(q2_filters_path)?)));
+    match q2 {
+        Ok((filters, (hp_cutoff, lp_cutoff))) => {
+            let (hp_b, hp_a, lp_b, lp_a) = filters.clone();
+            let q3_gain = GainCalibrator::coherent(hp_cutoff, f_d, lp_cutoff).gain;
+            methods.push((
+                "Q3 coherent (zero-phase)",
+                Box::new(move |x: &[f64]| {
+                    let highpassed = iir_filter::apply_filter_zero_phase(x, &hp_b, &hp_a);
+                    let mixed = demodulator::multiply_with_carrier(&highpassed, f_d, source_fs);
+                    scaled(iir_filter::apply_filter_zero_phase(&mixed, &lp_b, &lp_a), q3_gain)
+                }),
+            ));
+            let base = Demodulator::new(f_d, source_fs, filters);
+            // The phasing USB output keeps the upper sideband as a high-pass at f_d would; the
+            // square-law detectors have no local oscillator and so no mixer gain to undo
+            for (name, mode, gain) in [
+                ("Q3 coherent", DemodMode::Coherent, q3_gain),
+                ("Q3 SSB (USB)", DemodMode::Ssb(Sideband::Upper), GainCalibrator::coherent(f_d, f_d, lp_cutoff).gain),
+                ("Q3 square-law + sqrt", DemodMode::SquareLaw { sqrt: true }, 1.0),
+                ("Q3 square-law (no sqrt)", DemodMode::SquareLaw { sqrt: false }, 1.0),
+            ] {
+                let demodulator = base.clone().with_mode(mode);
+                methods.push((name, Box::new(move |x: &[f64]| scaled(demodulator.demodulate(x), gain))));
+            }
+        }
+        Err(e) => info!("  Skipping Q3 methods: {}", e),
+    }
+
+    let tone_config = AmConfig {
+        sample_rate: source_fs,
+        duration: 1.0,
+        offset: f_d,
+        modulation_index: Some(THD_MOD_INDEX),
+        bandwidth: f_b,
+        baseband: Baseband::Tones(vec![THD_TONE]),
+        ..AmConfig::default()
+    };
+    let tone = signal_gen::generate(&tone_config).ok();
+
+    let names: Vec<&str> = methods.iter().map(|(name, _)| *name).collect();
+    let outputs: Vec<(&str, Vec<f64>)> = methods.iter().map(|(name, method)| (*name, method(input))).collect();
+    let thd: Vec<Option<f64>> = methods
+        .iter()
+        .map(|(_, method)| {
+            tone.as_ref()
+                .map(|tone| comparator::thd(&method(&tone.samples), THD_TONE, source_fs))
+        })
+        .collect();
+    let matrix = comparator::comparison_matrix(&outputs, source_fs);
+    comparator::save_comparison_matrix(
+        &names,
+        &matrix,
+        &thd,
+        &format!("{} Hz tone, AM with carrier, mu = {}", THD_TONE, THD_MOD_INDEX),
+        "output/Q4_method_matrix.txt",
+    )?;
+    comparator::save_matrix_csv(&names, &matrix, "output/Q4_method_matrix.csv")?;
+    comparator::plot_matrix_heatmap(&names, &matrix, plots.target("output/Q4_method_matrix_heatmap.png"))?;
+    comparator::plot_overlay(&outputs, source_fs, compare_range, plots.target("output/Q4_method_overlay.png"))?;
+    Ok(outputs.into_iter().map(|(name, output)| (name.to_string(), output)).collect())
+}
+
+/// One-line summary of the non-noise impairments applied during the sweep
+fn describe_impairments(impairments: &ChannelImpairments) -> String {
+    let kind = impairments.noise.map(|(kind, _)| kind).unwrap_or_default();
+    let mut parts = vec![format!("{} noise", kind)];
+    for echo in &impairments.echoes {
+        parts.push(format!("echo {:.1} ms x {:.2}", echo.delay * 1000.0, echo.gain));
+    }
+    if impairments.clock_drift_ppm != 0.0 {
+        parts.push(format!("clock drift {} ppm", impairments.clock_drift_ppm));
+    }
+    parts.join(", ")
+}
+
+/// Run the post-filter over every channel, reporting what it changed
+fn apply_post_filter(channels: Vec<Vec<f64>>, f_s: f64, filter: &PostFilter) -> Vec<Vec<f64>> {
+    info!("  Post-filter: {}", filter);
+    channels
+        .iter()
+        .enumerate()
+        .map(|(index, channel)| {
+            let (output, report) = filter.apply(channel, f_s);
+            info!(
+                "    channel {}: DC {:+.2e} removed, {} click(s) repaired, {} sample(s) limited",
+                index + 1,
+                report.dc_removed,
+                report.clicks,
+                report.limited
+            );
+            output
+        })
+        .collect()
+}
+
+/// Log how the output fits into full scale, warning when the output gain pushes samples
+/// beyond it and when the WAV writer will clip them
+fn report_headroom(headroom: &Headroom, gain: f64) {
+    if headroom.over_full_scale > 0 {
+        warn!(
+            "  Warning: the x{:.4} output gain pushes {} sample(s) beyond full scale (peak {:+.2} dBFS)",
+            gain, headroom.over_full_scale, headroom.peak_dbfs
+        );
+    }
+    if headroom.clipped_on_write > 0 {
+        warn!(
+            "  Warning: {} sample(s) will be clipped in the WAV file; --normalize peak:<dB> scales the output instead",
+            headroom.clipped_on_write
+        );
     } else {
-        println!("  Warning: Could not read Q3 results for comparison");
+        info!(
+            "  Output peak {:+.2} dBFS, written with gain x{:.4}: {:.2} dB headroom",
+            headroom.peak_dbfs,
+            headroom.write_gain,
+            headroom.headroom_db()
+        );
+    }
+}
+
+/// Denoise every channel; the first channel's report and spectra are saved
+fn apply_denoiser(
+    channels: Vec<Vec<f64>>,
+    f_s: f64,
+    f_b: f64,
+    denoiser: &Denoiser,
+    plots: &PlotOutput,
+) -> Result<Vec<Vec<f64>>> {
+    info!("  Denoiser: {}", denoiser);
+    let mut outputs = Vec::with_capacity(channels.len());
+    for (index, channel) in channels.iter().enumerate() {
+        let (output, report) = denoiser.apply(channel, f_s)?;
+        info!(
+            "    channel {}: noise {:.3}-{:.3} s at {:.1} dBFS, reduced {:.1} dB, SNR {:.1} -> {:.1} dB",
+            index + 1,
+            report.noise_segment.0,
+            report.noise_segment.1,
+            report.noise_level_db,
+            report.noise_reduction_db,
+            report.snr_before_db,
+            report.snr_after_db
+        );
+        if index == 0 {
+            save_denoise_report(denoiser, &report, "output/Q4_denoise.txt")?;
+            spectrum_analyzer::FigureGrid::new("Denoising (Channel 1)", 3, 1)
+                .panel("Before: Average Spectrum", &report.spectrum_before)
+                .panel("Noise Profile", &report.noise_spectrum)
+                .panel("After: Average Spectrum", &report.spectrum_after)
+                .markers(&[f_b])
+                .save(plots.target("output/Q4_denoise_spectra.png"))?;
+        }
+        outputs.push(output);
     }
+    Ok(outputs)
+}
 
-    // Step 13: Save analysis results
-    println!("\n[Step 12] Saving analysis results...");
-    save_results(&original_spectrum, &xh_spectrum, &xb_spectrum, &xl_spectrum, f_d, f_s, f_b);
+fn save_denoise_report(denoiser: &Denoiser, report: &DenoiseReport, filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("Q4 Denoising\n");
+    content.push_str("============\n\n");
+    content.push_str(&format!("Settings: {}\n", denoiser));
+    content.push_str(&format!(
+        "Noise segment: {:.3} - {:.3} s, {:.2} dBFS\n\n",
+        report.noise_segment.0, report.noise_segment.1, report.noise_level_db
+    ));
+    content.push_str(&format!("Estimated SNR before: {:.2} dB\n", report.snr_before_db));
+    content.push_str(&format!("Estimated SNR after:  {:.2} dB\n", report.snr_after_db));
+    content.push_str(&format!("Noise segment power reduced by: {:.2} dB\n", report.noise_reduction_db));
+    content.push_str("\nThe SNR compares the mean power of the whole output, less that of the noise\n");
+    content.push_str("segment, with the noise segment's power; it assumes the noise is stationary.\n");
+    content.push_str("Spectra before and after: Q4_denoise_spectra.png\n");
 
-    println!("\nQ4 Frequency-Domain Demodulation completed successfully!");
-    println!("Output files saved in: codes/Q4/output/");
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
 }
 
-fn read_q1_results() -> Result<(f64, f64, f64), String> {
-    let content = std::fs::read_to_string("../Q1/output/Q1_results.txt")
-        .map_err(|e| format!("Failed to read Q1 results: {}", e))?;
+/// Write zoomable copies of the stage spectra (f_d and f_B marked) and of the input and
+/// output waveforms to one self-contained HTML page
+fn save_interactive_plots(
+    title: &str,
+    spectra: &[(&str, &[(f64, f64)])],
+    waveforms: &[(&str, &[f64])],
+    f_s: f64,
+    (f_d, f_b): (f64, f64),
+    filename: &str,
+) {
+    let mut page = InteractivePage::new(title);
+    for &(name, spectrum) in spectra {
+        page = page.chart(
+            InteractiveChart::new(name, "Frequency (Hz)", "Magnitude")
+                .series(name, spectrum.iter().copied())
+                .marker(f_d, "f_d")
+                .marker(f_b, "f_B"),
+        );
+    }
+    let mut waveform_chart = InteractiveChart::new("Waveforms", "Time (s)", "Amplitude");
+    for &(name, samples) in waveforms {
+        waveform_chart = waveform_chart.waveform(name, samples, f_s);
+    }
+    match page.chart(waveform_chart).save(filename) {
+        Ok(()) => info!("  Saved: {}", filename),
+        Err(e) => warn!("  Warning: {}", e),
+    }
+}
+
+/// Run AGC over every channel, reporting and plotting the first one
+fn apply_agc(channels: Vec<Vec<f64>>, f_s: f64, config: &AgcConfig, plot: PlotTarget) -> Result<Vec<Vec<f64>>> {
+    info!("  AGC: {}", config);
+    let results: Vec<_> = channels.iter().map(|c| agc::apply(c, f_s, config)).collect();
+    let (min, max) = results[0].gain_range_db();
+    info!("  AGC gain ranged from {:+.1} dB to {:+.1} dB", min, max);
+    q3_spectrum_analyzer::plot_envelopes(&channels[0], &results[0].output, f_s, plot, "Output Envelope Before/After AGC")?;
+    Ok(results.into_iter().map(|r| r.output).collect())
+}
+
+/// Decimate every demodulated channel by `factor`, returning the signals and their new rate
+fn decimate_output(channels: Vec<Vec<f64>>, factor: usize, f_s: f64, f_b: f64) -> (Vec<Vec<f64>>, f64) {
+    if factor <= 1 {
+        return (channels, f_s);
+    }
+    let decimated_fs = f_s / factor as f64;
+    let decimated: Vec<Vec<f64>> = channels.iter().map(|c| decimate(c, factor)).collect();
+    info!(
+        "  Decimated by {}: {:.0} Hz -> {:.0} Hz, {} -> {} samples per channel",
+        factor,
+        f_s,
+        decimated_fs,
+        channels[0].len(),
+        decimated[0].len()
+    );
+    if decimated_fs / 2.0 < f_b {
+        info!(
+            "  Warning: new Nyquist frequency {:.0} Hz is below f_B = {:.0} Hz, part of the baseband is removed",
+            decimated_fs / 2.0,
+            f_b
+        );
+    }
+    (decimated, decimated_fs)
+}
+
+
+/// f_d, f_s and f_B from Q1's results or a signal_gen truth file
+///
+/// The first line mentioning each parameter wins, so a later "f_d ends at" drift note in a
+/// truth file does not replace the offset. f_B defaults to the 4 kHz of the problem statement.
+fn read_parameters(path: &str) -> Result<(f64, f64, f64)> {
+    let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
 
     let mut f_d = None;
     let mut f_s = None;
+    let mut f_b = None;
 
+    let value = |line: &str| {
+        line.split('=')
+            .nth(1)
+            .and_then(|v| v.split_whitespace().next())
+            .and_then(|v| v.parse::<f64>().ok())
+    };
     for line in content.lines() {
-        if line.contains("频率偏差") || line.contains("f_d") {
-            if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().split_whitespace().next().unwrap_or("0").parse::<f64>() {
-                    f_d = Some(value);
-                }
-            }
-        } else if line.contains("采样频率") || line.contains("f_s") {
-            if let Some(value_str) = line.split('=').nth(1) {
-                if let Ok(value) = value_str.trim().split_whitespace().next().unwrap_or("0").parse::<f64>() {
-                    f_s = Some(value);
-                }
-            }
+        if f_b.is_none() && line.contains("f_B") {
+            f_b = value(line);
+        } else if f_d.is_none() && (line.contains("频率偏差") || line.contains("f_d")) {
+            f_d = value(line);
+        } else if f_s.is_none() && (line.contains("采样频率") || line.contains("f_s")) {
+            f_s = value(line);
         }
     }
 
-    let f_d = f_d.ok_or_else(|| "Could not find f_d in Q1 results".to_string())?;
-    let f_s = f_s.ok_or_else(|| "Could not find f_s in Q1 results".to_string())?;
-    let f_b = 4000.0; // Given in problem statement
+    let f_d = f_d.ok_or_else(|| DspError::Data(format!("{}: could not find f_d", path)))?;
+    let f_s = f_s.ok_or_else(|| DspError::Data(format!("{}: could not find f_s", path)))?;
+    let f_b = f_b.unwrap_or(4000.0); // Given in problem statement
 
     Ok((f_d, f_s, f_b))
 }
 
-fn compute_fft(samples: &[f64]) -> Vec<Complex<f64>> {
-    use rustfft::FftPlanner;
-    
-    let mut buffer: Vec<Complex<f64>> = samples
-        .iter()
-        .map(|&x| Complex::new(x, 0.0))
-        .collect();
-    
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(buffer.len());
-    fft.process(&mut buffer);
-    
-    buffer
+/// Draw the stage figures: the spectrum overview, the stage waveforms, the two-sided spectra
+/// of the input and after the shift, and the spectrogram before and after
+///
+/// `signals` are the input x(t) and the output x_l(t), `ffts` the stage spectra X, X_h, X_b
+/// and X_l, and `spectra` their one-sided magnitudes. The waveform zoom is `zoom` (s), or
+/// 10 ms around the output's peak. The step-by-step run and `replot` both draw through here.
+fn plot_stage_figures(
+    (audio_samples, xl_samples): (&[f64], &[f64]),
+    [x_fft, xh_fft, xb_fft, _]: [&[Complex<f64>]; 4],
+    [original_spectrum, xh_spectrum, xb_spectrum, xl_spectrum]: [&[(f64, f64)]; 4],
+    (f_s, f_d, f_b): (f64, f64, f64),
+    zoom: Option<(f64, f64)>,
+    plots: &PlotOutput,
+) -> Result<()> {
+    // Two-sided views make the sideband symmetry around ±f_d (input) and
+    // around 0 Hz (after the shift) visible directly
+    let (original_centered, xb_centered) = rayon::join(
+        || compute_centered_magnitude_spectrum(x_fft, f_s),
+        || compute_centered_magnitude_spectrum(xb_fft, f_s),
+    );
+    // Time-domain stages for the waveform figure
+    let (xh_samples, xb_samples) = rayon::join(|| pipeline::ifft(xh_fft), || pipeline::ifft(xb_fft));
+    let zoom = zoom.unwrap_or_else(|| {
+        q3_spectrum_analyzer::peak_window(xl_samples, f_s, q3_spectrum_analyzer::WAVEFORM_ZOOM_SECONDS)
+    });
+    // The figures are independent, so they are rendered in parallel
+    let stages_target = plots.target("output/Q4_spectrum_stages.png");
+    let waveforms_target = plots.target("output/Q4_stage_waveforms.png");
+    let original_target = plots.target("output/Q4_original_spectrum_centered.png");
+    let xb_target = plots.target("output/Q4_xb_spectrum_centered.png");
+    let spectrogram_target = plots.target("output/Q4_spectrogram_comparison.png");
+    let figures: Vec<Task> = vec![
+        // One overview of the four stages on shared axes, with f_d and f_B marked
+        Box::new(|| {
+            spectrum_analyzer::FigureGrid::new("Spectrum at Each Demodulation Stage", 2, 2)
+                .panel("Original Signal X(f)", original_spectrum)
+                .panel("After Ideal High-Pass X_h(f)", xh_spectrum)
+                .panel("After Frequency Shift X_b(f)", xb_spectrum)
+                .panel("After Ideal Low-Pass X_l(f) - Demodulated", xl_spectrum)
+                .markers(&[f_d, f_b])
+                .save(stages_target)
+        }),
+        // The same stages in time, whole and zoomed in
+        Box::new(|| {
+            q3_spectrum_analyzer::plot_stage_waveforms(
+                &[
+                    ("Original Signal x(t)", audio_samples),
+                    ("After Ideal High-Pass x_h(t)", &xh_samples),
+                    ("After Frequency Shift x_b(t)", &xb_samples),
+                    ("After Ideal Low-Pass x_l(t) - Demodulated", xl_samples),
+                ],
+                f_s,
+                zoom,
+                waveforms_target,
+                "Waveform at Each Demodulation Stage",
+            )
+        }),
+        Box::new(|| {
+            spectrum_analyzer::plot_spectrum_centered(
+                &original_centered,
+                original_target,
+                "Original Signal X(f), Two-Sided (±f_d marked)",
+                &[-f_d, f_d],
+            )
+        }),
+        Box::new(|| {
+            spectrum_analyzer::plot_spectrum_centered(&xb_centered, xb_target, "After Frequency Shift X_b(f), Two-Sided", &[])
+        }),
+        // Time-frequency view: the image band around f_d disappears, the 0-f_B baseband stays
+        Box::new(|| {
+            q3_spectrum_analyzer::plot_spectrogram_comparison(
+                audio_samples,
+                xl_samples,
+                f_s,
+                &[f_d, f_b],
+                spectrogram_target,
+                "Spectrogram Before/After Demodulation (dB)",
+            )
+        }),
+    ];
+    parallel::run_all(figures)
 }
 
-fn compute_ifft(spectrum: &[Complex<f64>]) -> Vec<f64> {
-    use rustfft::FftPlanner;
-    
-    let mut buffer = spectrum.to_vec();
-    
-    let mut planner = FftPlanner::new();
-    let ifft = planner.plan_fft_inverse(buffer.len());
-    ifft.process(&mut buffer);
-    
-    // Normalize and extract real part
-    let n = buffer.len() as f64;
-    buffer.iter().map(|c| c.re / n).collect()
+/// Arrays `replot` reads unless a file follows the subcommand
+const REPLOT_SOURCE: &str = "output/Q4_signals.npz";
+
+/// `replot` subcommand: redraw the stage figures from the arrays an earlier run saved with
+/// `--npz` (or `--mat`), under the current `--plot-*`, `--db-range` and `--compare-range`
+/// options, without reading the inputs or demodulating again
+fn run_replot(args: &[String]) -> Result<()> {
+    let source = match args.get(1) {
+        Some(path) if !path.starts_with('-') => path.as_str(),
+        _ => REPLOT_SOURCE,
+    };
+    info!("\n[Replot] Redrawing the stage figures from {}...", source);
+    let arrays = Npz::load_any(source)?;
+    let (f_s, f_d, f_b) = (arrays.scalar("f_s")?, arrays.scalar("f_d")?, arrays.scalar("f_b")?);
+    info!("  f_d = {:.4} Hz, f_s = {:.4} Hz, f_B = {:.4} Hz", f_d, f_s, f_b);
+    let ffts = [arrays.complex("X")?, arrays.complex("X_h")?, arrays.complex("X_b")?, arrays.complex("X_l")?];
+    let spectra: Vec<Vec<(f64, f64)>> = ffts.iter().map(|fft| compute_magnitude_spectrum(fft, f_s)).collect();
+    std::fs::create_dir_all("output").map_err(|e| DspError::io("output", e))?;
+    plot_stage_figures(
+        (arrays.real("x")?, arrays.real("x_l")?),
+        ffts,
+        [&spectra[0], &spectra[1], &spectra[2], &spectra[3]],
+        (f_s, f_d, f_b),
+        comparator::time_range_from_args(args)?,
+        &PlotOutput::from_args(args)?,
+    )
 }
 
 fn compute_magnitude_spectrum(spectrum: &[Complex<f64>], f_s: f64) -> Vec<(f64, f64)> {
@@ -195,55 +1429,81 @@ fn compute_magnitude_spectrum(spectrum: &[Complex<f64>], f_s: f64) -> Vec<(f64,
         .collect()
 }
 
+fn compute_centered_magnitude_spectrum(spectrum: &[Complex<f64>], f_s: f64) -> Vec<(f64, f64)> {
+    let n = spectrum.len();
+    let frequencies = spectrum::centered_frequencies(n, f_s);
+    let magnitudes = spectrum::fftshift(spectrum);
+
+    frequencies
+        .into_iter()
+        .zip(magnitudes.iter())
+        .map(|(freq, c)| (freq, c.norm() / n as f64))
+        .collect()
+}
+
+fn spectral_descriptors(spectrum: &[(f64, f64)]) -> Option<SpectralFeatures> {
+    let (frequencies, magnitude): (Vec<f64>, Vec<f64>) = spectrum.iter().copied().unzip();
+    SpectralFeatures::compute(&frequencies, &magnitude, 0.85)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn save_results(
     original: &[(f64, f64)],
     xh: &[(f64, f64)],
     xb: &[(f64, f64)],
     xl: &[(f64, f64)],
+    features: (Option<SpectralFeatures>, Option<SpectralFeatures>),
     f_d: f64,
     f_s: f64,
     f_b: f64,
-) {
+    carrier_search: Option<&CarrierSearchResult>,
+    (calibrator, calibration): (GainCalibrator, &Calibration),
+    levels: &[(String, Levels)],
+    (clipping, headroom): (&ClippingReport, &Headroom),
+    reproducibility: &Reproducibility,
+) -> Result<()> {
     let mut content = String::new();
     content.push_str("Q4 Frequency-Domain Demodulation Results\n");
     content.push_str("==========================================\n\n");
     content.push_str(&format!("Carrier frequency: f_d = {:.4} Hz\n", f_d));
+    if let Some(search) = carrier_search {
+        content.push_str(&format!(
+            "  Fine search from Q1's {:.4} Hz ({:+.4} Hz), {} candidates, best concentration {:.6}\n",
+            search.estimate,
+            f_d - search.estimate,
+            search.candidates.len(),
+            search.best().concentration
+        ));
+    }
     content.push_str(&format!("Sampling frequency: f_s = {:.4} Hz\n", f_s));
-    content.push_str(&format!("Baseband bandwidth: f_B = {:.4} Hz\n\n", f_b));
+    content.push_str(&format!("Baseband bandwidth: f_B = {:.4} Hz\n", f_b));
+    content.push_str(&format!("Output gain: x{:.6} ({})\n", calibration.gain, calibrator));
+    for line in &calibration.derivation {
+        content.push_str(&format!("  {}\n", line));
+    }
+    content.push('\n');
 
     // Spectral peaks for each stage
     content.push_str("Spectral Analysis:\n");
     content.push_str("------------------\n");
     
     // Original signal peak
-    let orig_peak = original.iter()
-        .filter(|(f, _)| *f > 1000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let orig_peak = stage_peak(original, (1000.0, f64::INFINITY), "X(f)")?;
     content.push_str(&format!("Original signal X(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         orig_peak.0, orig_peak.1));
     
     // After high-pass
-    let xh_peak = xh.iter()
-        .filter(|(f, _)| *f > f_d)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xh_peak = stage_peak(xh, (f_d, f64::INFINITY), "X_h(f)")?;
     content.push_str(&format!("After ideal high-pass X_h(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         xh_peak.0, xh_peak.1));
     
     // After frequency shift
-    let xb_peak = xb.iter()
-        .filter(|(f, _)| *f > 10.0 && *f < 5000.0)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xb_peak = stage_peak(xb, (10.0, 5000.0), "X_b(f)")?;
     content.push_str(&format!("After frequency shift X_b(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         xb_peak.0, xb_peak.1));
     
     // Demodulated signal peak
-    let xl_peak = xl.iter()
-        .filter(|(f, _)| *f > 10.0 && *f < f_b)
-        .max_by(|(_, mag1), (_, mag2)| mag1.partial_cmp(mag2).unwrap())
-        .unwrap();
+    let xl_peak = stage_peak(xl, (10.0, f_b), "X_l(f)")?;
     content.push_str(&format!("Demodulated signal X_l(f) peak: f = {:.2} Hz, magnitude = {:.6}\n", 
         xl_peak.0, xl_peak.1));
     
@@ -260,13 +1520,134 @@ fn save_results(
     content.push_str(&format!("\nEnergy analysis (0-{:.0} Hz band):\n", f_b));
     content.push_str(&format!("  Original signal energy: {:.6e}\n", energy_orig));
     content.push_str(&format!("  Demodulated signal energy: {:.6e}\n", energy_demod));
+
+    content.push_str("\nSpectral descriptors (0 - f_s/2):\n");
+    for (label, sf) in [("Original X(f)", features.0), ("Demodulated X_l(f)", features.1)] {
+        if let Some(sf) = sf {
+            content.push_str(&format!("  {}:\n", label));
+            content.push_str(&format!("    Centroid: {:.2} Hz\n", sf.centroid));
+            content.push_str(&format!("    Spread: {:.2} Hz\n", sf.spread));
+            content.push_str(&format!("    Flatness: {:.6}\n", sf.flatness));
+            content.push_str(&format!("    Rolloff ({:.0}%): {:.2} Hz\n", sf.rolloff_fraction * 100.0, sf.rolloff));
+        }
+    }
     
-    content.push_str(&format!("\nMethod characteristics:\n"));
+    content.push_str("\nLevels (integrated loudness per ITU-R BS.1770, RMS and sample peak):\n");
+    for (signal, level) in levels {
+        content.push_str(&format!("  {:<28} {}\n", signal, level));
+    }
+
+    content.push_str("\nClipping and headroom:\n");
+    content.push_str(&format!(
+        "  Input: {} clipped sample(s) in {} flat top(s), peak {:.2} dBFS\n",
+        clipping.clipped_samples, clipping.runs, clipping.peak_dbfs
+    ));
+    content.push_str(&format!(
+        "  Output before writing: peak {:+.2} dBFS, {} sample(s) beyond full scale\n",
+        headroom.peak_dbfs, headroom.over_full_scale
+    ));
+    content.push_str(&format!(
+        "  Written file: gain x{:.4}, headroom {:.2} dB, {} sample(s) clipped\n",
+        headroom.write_gain,
+        headroom.headroom_db(),
+        headroom.clipped_on_write
+    ));
+
+    content.push_str("\nMethod characteristics:\n");
     content.push_str("  - Uses ideal filters (brick-wall response)\n");
     content.push_str("  - Frequency-domain processing (no time-domain convolution)\n");
     content.push_str("  - Perfect frequency selectivity\n");
     content.push_str("  - No phase distortion from filters\n");
 
-    std::fs::write("output/Q4_results.txt", content).expect("Failed to save results");
-    println!("  Saved to: output/Q4_results.txt");
+    std::fs::write("output/Q4_results.txt", content).map_err(|e| DspError::io("output/Q4_results.txt", e))?;
+    info!("  Saved to: output/Q4_results.txt");
+
+    let peak_row = |stage: &str, peak: &(f64, f64)| row![("stage", stage), ("frequency_hz", peak.0), ("magnitude", peak.1)];
+    let feature_row = |signal: &str, sf: &SpectralFeatures| {
+        row![
+            ("signal", signal),
+            ("centroid_hz", sf.centroid),
+            ("spread_hz", sf.spread),
+            ("flatness", sf.flatness),
+            ("rolloff_hz", sf.rolloff)
+        ]
+    };
+    let mut results = StageResults::new("Q4")
+        .parameter("f_d_hz", f_d)
+        .parameter("f_s_hz", f_s)
+        .parameter("f_b_hz", f_b)
+        .parameter("gain_calibration", calibrator.to_string());
+    if let Some(search) = carrier_search {
+        let candidates = search
+            .candidates
+            .iter()
+            .map(|c| row![("frequency_hz", c.frequency), ("concentration", c.concentration)])
+            .collect();
+        results = results
+            .parameter("f_d_q1_hz", search.estimate)
+            .metric("carrier_concentration", search.best().concentration)
+            .table("carrier_search", candidates);
+    }
+    results
+        .metric("output_gain", calibration.gain)
+        .metric("baseband_energy_original", energy_orig)
+        .metric("baseband_energy_demodulated", energy_demod)
+        .metric("demodulated_peak_hz", xl_peak.0)
+        .metric("input_clipped_samples", clipping.clipped_samples)
+        .metric("input_clipped_runs", clipping.runs)
+        .metric("output_peak_dbfs", headroom.peak_dbfs)
+        .metric("output_over_full_scale", headroom.over_full_scale)
+        .metric("output_headroom_db", headroom.headroom_db())
+        .metric("output_clipped_on_write", headroom.clipped_on_write)
+        .table(
+            "peaks",
+            vec![
+                peak_row("X", orig_peak),
+                peak_row("X_h", xh_peak),
+                peak_row("X_b", xb_peak),
+                peak_row("X_l", xl_peak),
+            ],
+        )
+        .table(
+            "spectral_features",
+            [("X", features.0), ("X_l", features.1)]
+                .iter()
+                .filter_map(|(signal, sf)| sf.as_ref().map(|sf| feature_row(signal, sf)))
+                .collect(),
+        )
+        .table("levels", levels.iter().map(|(signal, level)| level.row(signal)).collect())
+        .reproducibility(reproducibility)
+        .save_with("output/Q4_results.json", reproducibility)?;
+    info!("  Saved to: output/Q4_results.json");
+    Ok(())
+}
+
+/// Largest spectral line strictly inside `(low, high)` Hz
+///
+/// An error names the stage when no bin falls in the band, e.g. for a signal too short to resolve it.
+fn stage_peak<'a>(spectrum: &'a [(f64, f64)], (low, high): (f64, f64), stage: &str) -> Result<&'a (f64, f64)> {
+    spectrum
+        .iter()
+        .filter(|(f, _)| *f > low && *f < high)
+        .max_by(|(_, mag1), (_, mag2)| mag1.total_cmp(mag2))
+        .ok_or_else(|| DspError::Data(format!("{} has no spectral line between {} and {} Hz", stage, low, high)))
+}
+
+fn save_periodicity(periodicity: Option<Periodicity>, filename: &str) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("Q4 Demodulated Signal Periodicity (Autocorrelation)\n");
+    content.push_str("=====================================================\n\n");
+    content.push_str("Search range: 50 - 1000 Hz\n");
+    match periodicity {
+        Some(p) => {
+            content.push_str(&format!("Fundamental frequency: {:.2} Hz\n", p.frequency));
+            content.push_str(&format!("Period: {:.3} ms ({:.2} samples)\n", p.period * 1000.0, p.lag));
+            content.push_str(&format!("Normalized autocorrelation at period: {:.4}\n", p.strength));
+        }
+        None => content.push_str("No autocorrelation peak found in the search range\n"),
+    }
+
+    std::fs::write(filename, content).map_err(|e| DspError::io(filename, e))?;
+    info!("  Saved to: {}", filename);
+    Ok(())
 }
@@ -1,41 +1,248 @@
+use dsp_core::error::{DspError, Result};
+use dsp_core::plot_output::PlotTarget;
+use dsp_core::plot_style::{PlotStyle, Rgb};
+use dsp_core::with_drawing_area;
+use log::{info, warn};
+use plotters::chart::MeshStyle;
+use plotters::coord::Shift;
 use plotters::prelude::*;
 
 /// Plot spectrum
-pub fn plot_spectrum(spectrum: &[(f64, f64)], filename: &str, title: &str) {
-    let root = BitMapBackend::new(filename, (1200, 800)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
+pub fn plot_spectrum(spectrum: &[(f64, f64)], target: impl Into<PlotTarget>, title: &str) -> Result<()> {
+    let target = target.into();
+    let style = target.style;
+    with_drawing_area!(target, style.size, |root| {
+        root.fill(&rgb(style.background)).map_err(DspError::plot)?;
     
-    // Find max magnitude for y-axis
-    let max_mag = spectrum.iter()
-        .map(|(_, m)| *m)
-        .fold(0.0f64, f64::max);
+        let axes = style.axes;
+        let max_freq = spectrum.last().ok_or_else(|| empty_spectrum(title))?.0;
+        let x_range = axes.freq(0.0..max_freq);
+        let visible = || spectrum.iter().filter(|(f, _)| x_range.start <= *f && *f <= x_range.end);
     
-    let max_freq = spectrum.last().unwrap().0;
+        // Find max magnitude in view for y-axis
+        let max_mag = visible()
+            .map(|(_, m)| *m)
+            .fold(0.0f64, f64::max);
+        let y_range = axes.magnitude(0.0..max_mag * 1.1);
     
-    let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(50)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_freq, 0.0..max_mag * 1.1)
-        .unwrap();
+        let mut chart = chart_builder(&root, title, &style)
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .map_err(DspError::plot)?;
     
-    chart
-        .configure_mesh()
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude")
-        .x_label_formatter(&|x| format!("{:.0}", x))
-        .y_label_formatter(&|y| format!("{:.3}", y))
-        .draw()
-        .unwrap();
+        style_mesh(&mut chart.configure_mesh(), &style)
+            .x_labels(axes.freq_labels(&x_range))
+            .y_labels(axes.magnitude_labels(&y_range))
+            .x_desc("Frequency (Hz)")
+            .y_desc("Magnitude")
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| axes.magnitude_label(*y, 3))
+            .draw()
+            .map_err(DspError::plot)?;
     
-    chart
-        .draw_series(LineSeries::new(
-            spectrum.iter().map(|(f, m)| (*f, *m)),
-            &BLUE,
-        ))
-        .unwrap();
+        chart
+            .draw_series(LineSeries::new(
+                visible().copied(),
+                rgb(style.color(0)).stroke_width(style.line_width),
+            ))
+            .map_err(DspError::plot)?;
     
-    root.present().unwrap();
-    println!("  Saved: {}", filename);
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Plot a two-sided spectrum centred on 0 Hz, with vertical markers (e.g. ±f_d)
+pub fn plot_spectrum_centered(spectrum: &[(f64, f64)], target: impl Into<PlotTarget>, title: &str, markers: &[f64]) -> Result<()> {
+    let target = target.into();
+    let style = target.style;
+    with_drawing_area!(target, style.size, |root| {
+        root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+
+        let axes = style.axes;
+        let min_freq = spectrum.first().ok_or_else(|| empty_spectrum(title))?.0;
+        let max_freq = spectrum.last().ok_or_else(|| empty_spectrum(title))?.0;
+        let x_range = axes.freq(min_freq..max_freq);
+        let visible = || spectrum.iter().filter(|(f, _)| x_range.start <= *f && *f <= x_range.end);
+
+        let max_mag = visible()
+            .map(|(_, m)| *m)
+            .fold(0.0f64, f64::max);
+        let y_range = axes.magnitude(0.0..max_mag * 1.1);
+
+        let mut chart = chart_builder(&root, title, &style)
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .map_err(DspError::plot)?;
+
+        style_mesh(&mut chart.configure_mesh(), &style)
+            .x_labels(axes.freq_labels(&x_range))
+            .y_labels(axes.magnitude_labels(&y_range))
+            .x_desc("Frequency (Hz)")
+            .y_desc("Magnitude")
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| axes.magnitude_label(*y, 3))
+            .draw()
+            .map_err(DspError::plot)?;
+
+        for &marker in markers.iter().filter(|f| **f >= x_range.start && **f <= x_range.end) {
+            chart
+                .draw_series(LineSeries::new(
+                    vec![(marker, y_range.start), (marker, y_range.end)],
+                    rgb(style.marker).stroke_width(style.line_width + 1),
+                ))
+                .map_err(DspError::plot)?;
+        }
+
+        chart
+            .draw_series(LineSeries::new(
+                visible().copied(),
+                rgb(style.color(0)).stroke_width(style.line_width),
+            ))
+            .map_err(DspError::plot)?;
+
+        root.present().map_err(DspError::plot)?;
+    })?;
+    info!("  Saved: {}", target.path.display());
+    Ok(())
+}
+
+/// Several spectra in one figure, on a grid of panels that share their axes
+///
+/// Every panel uses the same frequency and magnitude range, so the stages can be compared
+/// by eye; the axis descriptions appear once, along the bottom row and the left column.
+/// Panels fill the grid row by row.
+pub struct FigureGrid<'a> {
+    title: String,
+    rows: usize,
+    cols: usize,
+    panels: Vec<(String, &'a [(f64, f64)])>,
+    markers: Vec<f64>,
+}
+
+impl<'a> FigureGrid<'a> {
+    pub fn new(title: &str, rows: usize, cols: usize) -> Self {
+        FigureGrid {
+            title: title.to_string(),
+            rows: rows.max(1),
+            cols: cols.max(1),
+            panels: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    pub fn panel(mut self, title: &str, spectrum: &'a [(f64, f64)]) -> Self {
+        self.panels.push((title.to_string(), spectrum));
+        self
+    }
+
+    /// Vertical lines drawn in every panel, e.g. f_d and f_B
+    pub fn markers(mut self, markers: &[f64]) -> Self {
+        self.markers = markers.to_vec();
+        self
+    }
+
+    /// Draw the grid; panels beyond rows × cols are left out
+    pub fn save(&self, target: impl Into<PlotTarget>) -> Result<()> {
+        let target = target.into();
+        let style = target.style;
+        let axes = style.axes;
+        let points = || self.panels.iter().flat_map(|(_, spectrum)| spectrum.iter());
+        let min_freq = points().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let max_freq = points().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        if min_freq >= max_freq {
+            warn!("  Warning: no spectrum to plot for {}", target.path.display());
+            return Ok(());
+        }
+        let x_range = axes.freq(min_freq..max_freq);
+        let in_view = |f: f64| x_range.start <= f && f <= x_range.end;
+        let max_mag = points().filter(|p| in_view(p.0)).map(|p| p.1).fold(0.0f64, f64::max).max(1e-12) * 1.1;
+        let y_range = axes.magnitude(0.0..max_mag);
+
+        let (width, height) = style.size;
+        let default_size = (width, height * (self.rows as u32 + 1) / 2);
+        with_drawing_area!(target, default_size, |root| {
+            root.fill(&rgb(style.background)).map_err(DspError::plot)?;
+            let titled = root.titled(&self.title, (style.font, style.title_font_size)).map_err(DspError::plot)?;
+            let areas = titled.split_evenly((self.rows, self.cols));
+
+            for (index, (area, (title, spectrum))) in areas.iter().zip(&self.panels).enumerate() {
+                let bottom_row = index / self.cols == self.rows - 1 || index + self.cols >= self.panels.len();
+                let left_column = index % self.cols == 0;
+                let mut chart = ChartBuilder::on(area)
+                    .caption(title, (style.font, style.title_font_size * 2 / 3))
+                    .margin(style.margin)
+                    .x_label_area_size(style.x_label_area)
+                    .y_label_area_size(style.y_label_area)
+                    .build_cartesian_2d(x_range.clone(), y_range.clone())
+                    .map_err(DspError::plot)?;
+
+                style_mesh(&mut chart.configure_mesh(), &style)
+                    .x_labels(axes.freq_labels(&x_range))
+                    .y_labels(axes.magnitude_labels(&y_range))
+                    .x_desc(if bottom_row { "Frequency (Hz)" } else { "" })
+                    .y_desc(if left_column { "Magnitude" } else { "" })
+                    .x_label_formatter(&|x| format!("{:.0}", x))
+                    .y_label_formatter(&|y| axes.magnitude_label(*y, 4))
+                    .draw()
+                    .map_err(DspError::plot)?;
+
+                for &marker in self.markers.iter().filter(|f| in_view(**f)) {
+                    chart
+                        .draw_series(LineSeries::new(
+                            vec![(marker, y_range.start), (marker, y_range.end)],
+                            rgb(style.marker).mix(0.6).stroke_width(style.line_width),
+                        ))
+                        .map_err(DspError::plot)?;
+                }
+
+                chart
+                    .draw_series(LineSeries::new(
+                        spectrum.iter().copied().filter(|p| in_view(p.0)),
+                        rgb(style.color(0)).stroke_width(style.line_width),
+                    ))
+                    .map_err(DspError::plot)?;
+            }
+
+            root.present().map_err(DspError::plot)?;
+        })?;
+        info!("  Saved: {}", target.path.display());
+        Ok(())
+    }
+}
+
+fn rgb(Rgb(r, g, b): Rgb) -> RGBColor {
+    RGBColor(r, g, b)
+}
+
+/// Caption font, margin and label areas from the style
+fn chart_builder<'a, 'b, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    style: &PlotStyle,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .caption(title, (style.font, style.title_font_size))
+        .margin(style.margin)
+        .x_label_area_size(style.x_label_area)
+        .y_label_area_size(style.y_label_area);
+    builder
+}
+
+/// Tick label fonts and gridlines from the style
+fn style_mesh<'m, 'a, 'b, X: Ranged, Y: Ranged, DB: DrawingBackend>(
+    mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>,
+    style: &PlotStyle,
+) -> &'m mut MeshStyle<'a, 'b, X, Y, DB> {
+    mesh.label_style((style.font, style.label_font_size))
+        .axis_desc_style((style.font, style.label_font_size));
+    if !style.grid {
+        mesh.disable_mesh();
+    }
+    mesh
+}
+
+/// Error for a spectrum with nothing to plot
+fn empty_spectrum(title: &str) -> DspError {
+    DspError::Data(format!("{}: empty spectrum", title))
 }
@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsp_core::butterworth::ButterworthFilter;
+use dsp_core::fir::{overlap_add, FirFilter};
+use dsp_core::simd;
+use dsp_core::stft::{Stft, Window};
+use std::f64::consts::PI;
+
+/// Sample rate and length of the synthetic test signal, close to project.wav's
+const SAMPLE_RATE: f64 = 8000.0;
+const SIGNAL_LEN: usize = 32_000;
+
+/// A few tones on a 2 kHz carrier, enough structure that nothing is trivially zero
+fn test_signal() -> Vec<f64> {
+    (0..SIGNAL_LEN)
+        .map(|i| {
+            let t = i as f64 / SAMPLE_RATE;
+            let message = (2.0 * PI * 300.0 * t).sin() + 0.5 * (2.0 * PI * 750.0 * t).cos();
+            message * (2.0 * PI * 2000.0 * t).cos()
+        })
+        .collect()
+}
+
+fn butterworth_design(c: &mut Criterion) {
+    let mut group = c.benchmark_group("butterworth_design");
+    for order in [4, 8, 12] {
+        group.bench_with_input(BenchmarkId::new("lowpass", order), &order, |b, &order| {
+            b.iter(|| ButterworthFilter::lowpass(order, black_box(1000.0), SAMPLE_RATE))
+        });
+        group.bench_with_input(BenchmarkId::new("highpass", order), &order, |b, &order| {
+            b.iter(|| ButterworthFilter::highpass(order, black_box(1800.0), SAMPLE_RATE))
+        });
+    }
+    group.finish();
+}
+
+fn fir_convolution(c: &mut Criterion) {
+    let signal = test_signal();
+    let mut group = c.benchmark_group("fir_convolution");
+    group.sample_size(20);
+    for taps in [9, 31, 129, 513] {
+        let filter = FirFilter::lowpass(taps, 1000.0, SAMPLE_RATE);
+        group.bench_with_input(BenchmarkId::new("apply", taps), &filter, |b, filter| {
+            b.iter(|| filter.apply(black_box(&signal)))
+        });
+        group.bench_with_input(BenchmarkId::new("overlap_add", taps), &filter, |b, filter| {
+            b.iter(|| overlap_add(black_box(&signal), &filter.taps))
+        });
+    }
+    group.finish();
+}
+
+fn reductions(c: &mut Criterion) {
+    let signal = test_signal();
+    let other: Vec<f64> = signal.iter().map(|x| 0.9 * x + 0.01).collect();
+    let mut group = c.benchmark_group("reductions");
+    group.bench_function("dot/scalar", |b| {
+        b.iter(|| black_box(&signal).iter().zip(&other).map(|(x, y)| x * y).sum::<f64>())
+    });
+    group.bench_function("dot/simd", |b| b.iter(|| simd::dot(black_box(&signal), &other)));
+    group.bench_function("energy/scalar", |b| b.iter(|| black_box(&signal).iter().map(|x| x * x).sum::<f64>()));
+    group.bench_function("energy/simd", |b| b.iter(|| simd::energy(black_box(&signal))));
+    group.bench_function("squared_error/simd", |b| b.iter(|| simd::squared_error(black_box(&signal), &other)));
+    group.finish();
+}
+
+fn stft(c: &mut Criterion) {
+    let signal = test_signal();
+    let mut group = c.benchmark_group("stft");
+    group.sample_size(20);
+    for window_len in [256, 1024] {
+        group.bench_with_input(BenchmarkId::from_parameter(window_len), &window_len, |b, &len| {
+            b.iter(|| Stft::compute(black_box(&signal), SAMPLE_RATE, len, len / 4, Window::Hann))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, butterworth_design, fir_convolution, reductions, stft);
+criterion_main!(benches);
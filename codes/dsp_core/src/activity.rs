@@ -0,0 +1,181 @@
+use crate::error::{DspError, Result};
+use std::ops::Range;
+
+/// Energy-based detector of active segments: speech or music as opposed to silence or the
+/// background noise between passages
+///
+/// The signal is cut into short frames and each frame's mean power is taken in dB. The
+/// noise floor is the 10th percentile of those levels; frames more than `threshold_db`
+/// above it are active. Runs of active frames are widened by `hangover_seconds` on both
+/// sides, so soft onsets and decays stay in, and overlapping segments are merged.
+///
+/// The detector assumes the recording has pauses. When the frame levels span less than
+/// `threshold_db` between the 10th and 90th percentile there is nothing to separate, and
+/// the whole signal counts as active.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityDetector {
+    /// Frame length (s)
+    pub frame_seconds: f64,
+    /// Level above the noise floor that makes a frame active (dB)
+    pub threshold_db: f64,
+    /// Margin added before and after every active run (s)
+    pub hangover_seconds: f64,
+}
+
+/// Percentiles of the frame levels taken as the noise floor and as the active level
+const FLOOR_PERCENTILE: f64 = 0.1;
+const ACTIVE_PERCENTILE: f64 = 0.9;
+
+impl Default for ActivityDetector {
+    fn default() -> Self {
+        ActivityDetector {
+            frame_seconds: 0.02,
+            threshold_db: 10.0,
+            hangover_seconds: 0.1,
+        }
+    }
+}
+
+impl ActivityDetector {
+    /// Read `--active-only` (defaults) and `--activity-threshold <dB>` from command-line
+    /// arguments
+    ///
+    /// Returns `None` when neither is given.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut detector = ActivityDetector::default();
+        let mut enabled = false;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--active-only" => enabled = true,
+                "--activity-threshold" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--activity-threshold"))?;
+                    detector.threshold_db = value
+                        .parse()
+                        .ok()
+                        .filter(|&v: &f64| v > 0.0)
+                        .ok_or_else(|| DspError::invalid_value("--activity-threshold", value))?;
+                    enabled = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(enabled.then_some(detector))
+    }
+
+    /// Sample ranges of the active segments of `signal`, in order and non-overlapping
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::activity::{self, ActivityDetector};
+    ///
+    /// // One second of a tone between two seconds of near-silence
+    /// let fs = 8000.0;
+    /// let signal: Vec<f64> = (0..24000)
+    ///     .map(|n| if (8000..16000).contains(&n) { (n as f64 * 0.3).sin() } else { 1e-4 * (n as f64 * 1.7).sin() })
+    ///     .collect();
+    /// let segments = ActivityDetector::default().detect(&signal, fs);
+    /// assert_eq!(segments, vec![7200..16800]);
+    /// assert_eq!(activity::active_samples(&segments), 9600);
+    /// ```
+    pub fn detect(&self, signal: &[f64], sample_rate: f64) -> Vec<Range<usize>> {
+        let n = signal.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let frame = ((self.frame_seconds * sample_rate).round() as usize).max(1);
+        let levels: Vec<f64> = signal
+            .chunks(frame)
+            .map(|chunk| 10.0 * (chunk.iter().map(|x| x * x).sum::<f64>() / chunk.len() as f64 + 1e-20).log10())
+            .collect();
+        let mut sorted = levels.clone();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        let floor = percentile(FLOOR_PERCENTILE);
+        if percentile(ACTIVE_PERCENTILE) - floor < self.threshold_db {
+            return std::iter::once(0..n).collect();
+        }
+
+        let threshold = floor + self.threshold_db;
+        let hangover = (self.hangover_seconds * sample_rate).round() as usize;
+        let mut segments: Vec<Range<usize>> = Vec::new();
+        let mut i = 0;
+        while i < levels.len() {
+            if levels[i] <= threshold {
+                i += 1;
+                continue;
+            }
+            let first = i;
+            while i < levels.len() && levels[i] > threshold {
+                i += 1;
+            }
+            let start = (first * frame).saturating_sub(hangover);
+            let end = (i * frame + hangover).min(n);
+            match segments.last_mut() {
+                Some(last) if start <= last.end => last.end = end,
+                _ => segments.push(start..end),
+            }
+        }
+        segments
+    }
+}
+
+/// Total length of `segments` (samples)
+pub fn active_samples(segments: &[Range<usize>]) -> usize {
+    segments.iter().map(|s| s.len()).sum()
+}
+
+/// `signal` with everything outside `segments` set to zero, keeping its length and timing
+pub fn gate(signal: &[f64], segments: &[Range<usize>]) -> Vec<f64> {
+    let mut output = vec![0.0; signal.len()];
+    for segment in segments {
+        let segment = segment.start.min(signal.len())..segment.end.min(signal.len());
+        output[segment.clone()].copy_from_slice(&signal[segment]);
+    }
+    output
+}
+
+/// The samples of `signal` inside `segments`, joined end to end
+pub fn concatenate(signal: &[f64], segments: &[Range<usize>]) -> Vec<f64> {
+    segments
+        .iter()
+        .flat_map(|s| &signal[s.start.min(signal.len())..s.end.min(signal.len())])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise;
+
+    #[test]
+    fn test_bursts_in_noise() {
+        // Two 0.5 s bursts in noise 40 dB down; the gap between them is wider than the
+        // hangover, so they stay apart
+        let fs = 8000.0;
+        let hiss = noise::white(32000, 3);
+        let signal: Vec<f64> = hiss
+            .iter()
+            .enumerate()
+            .map(|(n, h)| {
+                let burst = (4000..8000).contains(&n) || (20000..24000).contains(&n);
+                0.005 * h + if burst { 0.5 * (n as f64 * 0.2).sin() } else { 0.0 }
+            })
+            .collect();
+        let segments = ActivityDetector::default().detect(&signal, fs);
+        assert_eq!(segments, vec![3200..8800, 19200..24800]);
+
+        let gated = gate(&signal, &segments);
+        assert_eq!(gated.len(), signal.len());
+        assert!(gated[..3200].iter().chain(&gated[8800..19200]).all(|&x| x == 0.0));
+        assert_eq!(gated[5000], signal[5000]);
+        assert_eq!(concatenate(&signal, &segments).len(), active_samples(&segments));
+
+        // Steady noise has no pauses to find: all of it is active
+        let whole = ActivityDetector::default().detect(&hiss, fs);
+        assert_eq!((whole.len(), whole[0].clone()), (1, 0..32000));
+        assert!(ActivityDetector::default().detect(&[], fs).is_empty());
+    }
+}
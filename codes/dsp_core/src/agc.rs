@@ -0,0 +1,169 @@
+use crate::error::{DspError, Result};
+
+/// Automatic gain control settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgcConfig {
+    /// Wanted envelope level (dBFS, peak)
+    pub target_db: f64,
+    /// Time constant for a rising envelope (s); short, so peaks are caught quickly
+    pub attack: f64,
+    /// Time constant for a falling envelope (s); long, so gain does not pump between syllables
+    pub release: f64,
+    /// Upper bound on the gain (dB), so silence and noise are not amplified to full level
+    pub max_gain_db: f64,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        AgcConfig {
+            target_db: -6.0,
+            attack: 0.005,
+            release: 0.3,
+            max_gain_db: 30.0,
+        }
+    }
+}
+
+impl AgcConfig {
+    /// Read `--agc` and the optional `--agc-target <dBFS>`, `--agc-attack <ms>`,
+    /// `--agc-release <ms>` and `--agc-max-gain <dB>` from command-line arguments
+    ///
+    /// Returns `None` when AGC is not requested; any `--agc-*` option implies `--agc`.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut config = AgcConfig::default();
+        let mut enabled = false;
+        let parse = |name: &str, value: &str| -> Result<f64> {
+            value.parse().map_err(|_| DspError::invalid_value(name, value))
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if name == "--agc" {
+                enabled = true;
+                continue;
+            }
+            if !matches!(name, "--agc-target" | "--agc-attack" | "--agc-release" | "--agc-max-gain") {
+                continue;
+            }
+            enabled = true;
+            let value = parse(name, iter.next().ok_or_else(|| DspError::missing_value(name))?)?;
+            match name {
+                "--agc-target" => config.target_db = value,
+                "--agc-attack" => config.attack = value / 1000.0,
+                "--agc-release" => config.release = value / 1000.0,
+                _ => config.max_gain_db = value,
+            }
+        }
+        if config.attack <= 0.0 || config.release <= 0.0 {
+            return Err(DspError::Argument("AGC attack and release times must be positive".to_string()));
+        }
+        Ok(enabled.then_some(config))
+    }
+}
+
+impl std::fmt::Display for AgcConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "target {:.1} dBFS, attack {:.1} ms, release {:.0} ms, max gain {:.0} dB",
+            self.target_db,
+            self.attack * 1000.0,
+            self.release * 1000.0,
+            self.max_gain_db
+        )
+    }
+}
+
+/// Output of [`apply`]
+#[derive(Debug, Clone)]
+pub struct AgcResult {
+    pub output: Vec<f64>,
+    /// Linear gain applied to each sample
+    pub gain: Vec<f64>,
+}
+
+impl AgcResult {
+    /// Smallest and largest gain applied (dB)
+    pub fn gain_range_db(&self) -> (f64, f64) {
+        let to_db = |g: f64| 20.0 * g.max(1e-12).log10();
+        let min = self.gain.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self.gain.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (to_db(min), to_db(max))
+    }
+}
+
+/// Peak envelope follower with separate attack and release time constants (s)
+pub fn envelope(signal: &[f64], sample_rate: f64, attack: f64, release: f64) -> Vec<f64> {
+    let coefficient = |tau: f64| 1.0 - (-1.0 / (tau * sample_rate)).exp();
+    let (attack, release) = (coefficient(attack), coefficient(release));
+    let mut level = 0.0;
+    signal
+        .iter()
+        .map(|&x| {
+            let rectified = x.abs();
+            let alpha = if rectified > level { attack } else { release };
+            level += alpha * (rectified - level);
+            level
+        })
+        .collect()
+}
+
+/// Feed-forward AGC: scale every sample by target / envelope, capped at the maximum gain
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::agc::{apply, AgcConfig};
+///
+/// // A quiet tone is brought up to the -6 dBFS target once the envelope has settled
+/// let fs = 8000.0;
+/// let quiet: Vec<f64> = (0..8000).map(|n| 0.05 * (n as f64 * 0.2).sin()).collect();
+/// let result = apply(&quiet, fs, &AgcConfig::default());
+/// let peak = result.output[6000..].iter().fold(0.0f64, |m, x| m.max(x.abs()));
+/// assert!((20.0 * peak.log10() + 6.0).abs() < 1.0);
+/// ```
+pub fn apply(signal: &[f64], sample_rate: f64, config: &AgcConfig) -> AgcResult {
+    let target = 10f64.powf(config.target_db / 20.0);
+    let max_gain = 10f64.powf(config.max_gain_db / 20.0);
+    let gain: Vec<f64> = envelope(signal, sample_rate, config.attack, config.release)
+        .iter()
+        .map(|&level| (target / level.max(1e-12)).min(max_gain))
+        .collect();
+    let output = signal.iter().zip(&gain).map(|(x, g)| x * g).collect();
+    AgcResult { output, gain }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_agc_levels_a_fade() {
+        // 300 Hz tone that fades by 20 dB half-way through
+        let fs = 8000.0;
+        let signal: Vec<f64> = (0..16000)
+            .map(|i| {
+                let amplitude = if i < 8000 { 0.5 } else { 0.05 };
+                amplitude * (2.0 * PI * 300.0 * i as f64 / fs).sin()
+            })
+            .collect();
+        let config = AgcConfig::default();
+        let result = apply(&signal, fs, &config);
+
+        // After the release has settled both halves sit near the -6 dBFS target
+        let peak = |x: &[f64]| x.iter().fold(0.0f64, |max, &v| max.max(v.abs()));
+        let target = 10f64.powf(-6.0 / 20.0);
+        for range in [4000..8000, 14000..16000] {
+            let level = peak(&result.output[range.clone()]);
+            assert!((level / target - 1.0).abs() < 0.1, "{:?}: {}", range, level);
+        }
+        let (min, max) = result.gain_range_db();
+        assert!(max - min > 19.0 && max <= config.max_gain_db);
+
+        let args: Vec<String> = ["--agc-release", "500"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(AgcConfig::from_args(&args).unwrap().unwrap().release, 0.5);
+        assert_eq!(AgcConfig::from_args(&[]).unwrap(), None);
+    }
+}
@@ -0,0 +1,155 @@
+use rustfft::{num_complex::Complex, FftPlanner};
+
+/// A local maximum of an autocorrelation sequence
+#[derive(Debug, Clone, Copy)]
+pub struct AcfPeak {
+    /// Lag in samples (parabolically interpolated, may be fractional)
+    pub lag: f64,
+    /// Normalized autocorrelation value at the peak
+    pub value: f64,
+}
+
+/// Dominant periodicity found in a signal
+#[derive(Debug, Clone, Copy)]
+pub struct Periodicity {
+    /// Period in seconds
+    pub period: f64,
+    /// Fundamental frequency in Hz (1 / period)
+    pub frequency: f64,
+    /// Period in samples
+    pub lag: f64,
+    /// Normalized autocorrelation at the period lag (1.0 = perfectly periodic)
+    pub strength: f64,
+}
+
+/// Compute the normalized (biased) autocorrelation r[k] / r[0] for lags 0..N-1
+///
+/// Uses the Wiener–Khinchin relation: the signal is zero-padded to a power of
+/// two of at least 2N (avoiding circular wrap-around), transformed, its power
+/// spectrum inverse-transformed, giving O(N log N) instead of O(N²).
+pub fn autocorrelation(signal: &[f64]) -> Vec<f64> {
+    let n = signal.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let fft_len = (2 * n).next_power_of_two();
+    let mut buffer: Vec<Complex<f64>> = signal
+        .iter()
+        .map(|&x| Complex::new(x, 0.0))
+        .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+        .take(fft_len)
+        .collect();
+
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_forward(fft_len).process(&mut buffer);
+    for c in buffer.iter_mut() {
+        *c = Complex::new(c.norm_sqr(), 0.0);
+    }
+    planner.plan_fft_inverse(fft_len).process(&mut buffer);
+
+    let r0 = buffer[0].re;
+    if r0 <= 0.0 {
+        return vec![0.0; n];
+    }
+    buffer[..n].iter().map(|c| c.re / r0).collect()
+}
+
+/// Find local maxima of an autocorrelation sequence within [min_lag, max_lag]
+///
+/// Peak lags are refined with three-point parabolic interpolation. Returns at
+/// most `num_peaks` peaks sorted by descending value.
+pub fn find_peaks(acf: &[f64], min_lag: usize, max_lag: usize, num_peaks: usize) -> Vec<AcfPeak> {
+    let start = min_lag.max(1);
+    let end = max_lag.min(acf.len().saturating_sub(2));
+
+    let mut peaks: Vec<AcfPeak> = (start..=end)
+        .filter(|&k| acf[k] > acf[k - 1] && acf[k] >= acf[k + 1])
+        .map(|k| {
+            let (y1, y2, y3) = (acf[k - 1], acf[k], acf[k + 1]);
+            let denom = y1 - 2.0 * y2 + y3;
+            let delta = if denom.abs() > 1e-15 { 0.5 * (y1 - y3) / denom } else { 0.0 };
+            AcfPeak {
+                lag: k as f64 + delta,
+                value: y2 - 0.25 * (y1 - y3) * delta,
+            }
+        })
+        .collect();
+
+    peaks.sort_by(|a, b| b.value.total_cmp(&a.value));
+    peaks.truncate(num_peaks);
+    peaks
+}
+
+/// Estimate the dominant periodicity of a signal within a frequency range
+///
+/// The search is restricted to lags fs/f_max ..= fs/f_min, and the strongest
+/// autocorrelation peak in that window is reported.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::autocorrelation::estimate_periodicity;
+///
+/// let fs = 8000.0;
+/// let x: Vec<f64> = (0..4000).map(|n| (2.0 * std::f64::consts::PI * 200.0 * n as f64 / fs).sin()).collect();
+/// let periodicity = estimate_periodicity(&x, fs, (50.0, 1000.0)).unwrap();
+/// assert!((periodicity.frequency - 200.0).abs() < 1.0);
+/// ```
+pub fn estimate_periodicity(
+    signal: &[f64],
+    sample_rate: f64,
+    frequency_range: (f64, f64),
+) -> Option<Periodicity> {
+    let (f_min, f_max) = frequency_range;
+    if f_min <= 0.0 || f_max <= f_min {
+        return None;
+    }
+
+    let acf = autocorrelation(signal);
+    let min_lag = (sample_rate / f_max).floor() as usize;
+    let max_lag = (sample_rate / f_min).ceil() as usize;
+    let peak = find_peaks(&acf, min_lag, max_lag, 1).into_iter().next()?;
+
+    Some(Periodicity {
+        period: peak.lag / sample_rate,
+        frequency: sample_rate / peak.lag,
+        lag: peak.lag,
+        strength: peak.value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_autocorrelation_matches_direct_sum() {
+        let signal: Vec<f64> = (0..64).map(|i| ((i * 7) % 11) as f64 - 5.0).collect();
+        let acf = autocorrelation(&signal);
+
+        let r0: f64 = signal.iter().map(|x| x * x).sum();
+        for lag in [0, 1, 5, 63] {
+            let direct: f64 = signal[..64 - lag]
+                .iter()
+                .zip(signal[lag..].iter())
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                / r0;
+            assert!((acf[lag] - direct).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_periodicity_of_tone() {
+        let sample_rate = 8000.0;
+        let signal: Vec<f64> = (0..4000)
+            .map(|i| (2.0 * PI * 220.0 * i as f64 / sample_rate).sin())
+            .collect();
+
+        let periodicity = estimate_periodicity(&signal, sample_rate, (50.0, 1000.0)).unwrap();
+        assert!((periodicity.frequency - 220.0).abs() < 1.0);
+        assert!(periodicity.strength > 0.9);
+    }
+}
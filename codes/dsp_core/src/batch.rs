@@ -0,0 +1,160 @@
+use crate::error::{DspError, Result};
+use crate::progress::Progress;
+use log::{error, info};
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of a stage's output directory holding one directory per batch input
+pub const BATCH_DIR: &str = "batch";
+
+/// Read `--batch <dir>` from command-line arguments
+pub fn dir_from_args(args: &[String]) -> Result<Option<PathBuf>> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--batch" {
+            let dir = iter.next().ok_or_else(|| DspError::missing_value("--batch"))?;
+            return Ok(Some(PathBuf::from(dir)));
+        }
+    }
+    Ok(None)
+}
+
+/// WAV files directly inside `dir` (any case of `.wav`), sorted by name
+pub fn wav_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| DspError::io(dir, e))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| DspError::io(dir, e))?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    if files.is_empty() {
+        return Err(DspError::Data(format!("{}: no WAV files found", dir.display())));
+    }
+    Ok(files)
+}
+
+/// File name of `input` without its extension, naming its batch output directory
+pub fn stem(input: &Path) -> String {
+    input.file_stem().map_or_else(|| "input".to_string(), |s| s.to_string_lossy().into_owned())
+}
+
+/// `<output>/batch/<stem>`, where a stage writes the per-file outputs for `input`
+///
+/// The same layout in every stage lets Q3 and Q4 find the f_d that Q1 estimated for each
+/// file at `../Q1/output/batch/<stem>/Q1_results.txt`.
+pub fn file_dir(output: &str, input: &Path) -> PathBuf {
+    Path::new(output).join(BATCH_DIR).join(stem(input))
+}
+
+/// One summary row per input file, written as CSV after a batch run
+///
+/// A file that fails is logged and recorded with its error message instead of stopping
+/// the batch, so one bad capture does not cost the results of the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSummary {
+    pub columns: Vec<String>,
+    /// Input file and either its column values or the error that stopped it
+    pub rows: Vec<(PathBuf, std::result::Result<Vec<String>, String>)>,
+}
+
+impl BatchSummary {
+    /// Run `process` on every file, collecting the values it returns for `columns`
+    pub fn run<E: std::fmt::Display>(
+        files: &[PathBuf],
+        columns: &[&str],
+        mut process: impl FnMut(&Path) -> std::result::Result<Vec<String>, E>,
+    ) -> Self {
+        let mut rows = Vec::with_capacity(files.len());
+        let progress = Progress::new(files.len() as u64, "Batch");
+        for (i, file) in files.iter().enumerate() {
+            info!("[{}/{}] {}", i + 1, files.len(), file.display());
+            let row = process(file).map_err(|e| e.to_string());
+            if let Err(e) = &row {
+                error!("  {}: {}", file.display(), e);
+            }
+            rows.push((file.clone(), row));
+            progress.inc(1);
+        }
+        progress.finish();
+        BatchSummary {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows,
+        }
+    }
+
+    /// Number of files that failed
+    pub fn failures(&self) -> usize {
+        self.rows.iter().filter(|(_, row)| row.is_err()).count()
+    }
+
+    /// CSV with `file`, `status` and the summary columns; failed rows leave the columns
+    /// empty and put the error message in `status`
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("file,status,{}\n", self.columns.join(","));
+        for (file, row) in &self.rows {
+            let file = csv_field(&file.display().to_string());
+            match row {
+                Ok(values) => {
+                    let values: Vec<String> = values.iter().map(|v| csv_field(v)).collect();
+                    out.push_str(&format!("{},ok,{}\n", file, values.join(",")));
+                }
+                Err(e) => {
+                    let empty = ",".repeat(self.columns.len());
+                    out.push_str(&format!("{},{}{}\n", file, csv_field(&format!("error: {}", e)), empty));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_csv()).map_err(|e| DspError::io(path, e))
+    }
+}
+
+/// Quote a CSV field when it contains a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_files_and_summary() {
+        let dir = std::env::temp_dir().join(format!("dsp_core_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.wav", "a.WAV", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let files = wav_files(&dir).unwrap();
+        let names: Vec<String> = files.iter().map(|f| stem(f)).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(file_dir("output", &files[1]), Path::new("output/batch/b"));
+
+        let summary = BatchSummary::run(&files, &["f_d_hz", "note"], |file| {
+            if stem(file) == "a" {
+                Ok(vec!["3000.1".to_string(), "x, y".to_string()])
+            } else {
+                Err("no carrier")
+            }
+        });
+        assert_eq!(summary.failures(), 1);
+        let csv = summary.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "file,status,f_d_hz,note");
+        assert!(lines[1].ends_with("a.WAV,ok,3000.1,\"x, y\""));
+        assert!(lines[2].ends_with("b.wav,error: no carrier,,"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(wav_files(&dir).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use dsp_core::error::{DspError, Result};
+use dsp_core::logging;
+use dsp_core::report::Report;
+use log::info;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: report [options]
+
+Collects the figures, parameter files, tables and comparison metrics in Q1-Q4's
+output directories into report.md and report.html in the codes directory.
+
+  --codes <dir>         Directory holding Q1..Q4 (default ..)";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    logging::init_from_args(&args);
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(e) = run(&args) {
+        if matches!(e, DspError::Argument(_)) {
+            eprintln!("{}", USAGE);
+        }
+        e.exit("Error");
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let mut codes = PathBuf::from("..");
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--codes" => {
+                let dir = iter
+                    .next()
+                    .ok_or_else(|| DspError::Argument("--codes requires a directory".to_string()))?;
+                codes = PathBuf::from(dir)
+            }
+            flag if logging::is_verbosity_flag(flag) => {}
+            other => return Err(DspError::Argument(format!("Unknown option '{}'", other))),
+        }
+    }
+
+    let report = Report::collect(&codes)?;
+    for section in &report.sections {
+        info!("{}: {} outputs", section.title, section.entries.len());
+    }
+    for (name, content) in [("report.md", report.to_markdown()), ("report.html", report.to_html())] {
+        let path = codes.join(name);
+        std::fs::write(&path, content).map_err(|e| DspError::io(&path, e))?;
+        info!("  Saved to: {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use dsp_core::channels::ChannelMode;
+use dsp_core::error::{DspError, Result};
+use dsp_core::logging;
+use dsp_core::resample::resample;
+use dsp_core::signal_gen::{self, AmConfig, Baseband};
+use dsp_core::wav_io::{self, WavWriteOptions};
+use log::info;
+
+const USAGE: &str = "\
+Usage: signal_gen [options]
+
+  --out <name>          Output base name, writes <name>.wav, <name>_truth.txt and the clean
+                        modulating signal <name>_baseband.wav (default am_test)
+  --fs <Hz>             Sample rate (default 22050)
+  --duration <s>        Length in seconds (default 1.5)
+  --carrier <Hz>        Transmitter carrier f_c (default 1000000)
+  --offset <Hz>         Carrier offset f_d (default 3000)
+  --phase <rad>         Carrier phase error (default 0)
+  --drift <Hz>          Linear carrier drift over the signal, f_d -> f_d + drift (default 0)
+  --mod-index <mu>      AM with carrier and index mu (default: DSB-SC)
+  --fm-deviation <Hz>   FM with this peak frequency deviation instead of AM
+  --bandwidth <Hz>      Baseband bandwidth f_B (default 4000)
+  --tones <f1,f2,...>   Baseband tones in Hz (default 225)
+  --chirp <f0:f1>       Baseband linear chirp from f0 to f1 Hz
+  --baseband <file.wav> Baseband from a recording (mixed to mono, band-limited to f_B)
+  --snr <dB>            Add Gaussian noise at this SNR
+  --noise <kind>        Noise shape: white (default), pink or brown
+  --seed <n>            Noise seed (default 1)";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    logging::init_from_args(&args);
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(e) = run(&args) {
+        if matches!(e, DspError::Argument(_)) {
+            eprintln!("{}", USAGE);
+        }
+        e.exit("Error");
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let mut config = AmConfig::default();
+    let mut out = "am_test".to_string();
+    let mut baseband_path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| DspError::missing_value(arg));
+        match arg.as_str() {
+            "--out" => out = value()?.clone(),
+            "--fs" => config.sample_rate = parse_number(value()?)?,
+            "--duration" => config.duration = parse_number(value()?)?,
+            "--carrier" => config.carrier = parse_number(value()?)?,
+            "--offset" => config.offset = parse_number(value()?)?,
+            "--phase" => config.phase = parse_number(value()?)?,
+            "--drift" => config.offset_drift = parse_number(value()?)?,
+            "--mod-index" => config.modulation_index = Some(parse_number(value()?)?),
+            "--fm-deviation" => config.fm_deviation = Some(parse_number(value()?)?),
+            "--bandwidth" => config.bandwidth = parse_number(value()?)?,
+            "--tones" => {
+                let tones = value()?
+                    .split(',')
+                    .map(parse_number)
+                    .collect::<Result<Vec<_>>>()?;
+                config.baseband = Baseband::Tones(tones);
+            }
+            "--chirp" => {
+                let spec = value()?;
+                let (start, end) = spec
+                    .split_once(':')
+                    .ok_or_else(|| DspError::Argument(format!("--chirp expects f0:f1, got '{}'", spec)))?;
+                config.baseband = Baseband::Chirp {
+                    start: parse_number(start)?,
+                    end: parse_number(end)?,
+                };
+            }
+            "--baseband" => baseband_path = Some(value()?.clone()),
+            "--snr" => config.snr_db = Some(parse_number(value()?)?),
+            "--noise" => config.noise_kind = value()?.parse().map_err(DspError::Argument)?,
+            "--seed" => {
+                let seed = value()?;
+                config.seed = seed.parse().map_err(|_| DspError::Argument(format!("Invalid seed '{}'", seed)))?;
+            }
+            flag if logging::is_verbosity_flag(flag) => {}
+            other => return Err(DspError::Argument(format!("Unknown option '{}'", other))),
+        }
+    }
+
+    // Loaded last so it sees the final --fs and --bandwidth
+    if let Some(path) = baseband_path {
+        config.baseband = Baseband::Samples(load_baseband(&path, &config)?);
+    }
+
+    let signal = signal_gen::generate(&config)?;
+    let wav_path = format!("{}.wav", out);
+    let truth_path = format!("{}_truth.txt", out);
+    let clean_path = format!("{}_baseband.wav", out);
+    let options = WavWriteOptions::default();
+    wav_io::write_channels(
+        &wav_path,
+        std::slice::from_ref(&signal.samples),
+        config.sample_rate as u32,
+        &options,
+    )?;
+    wav_io::write_channels(
+        &clean_path,
+        std::slice::from_ref(&signal.baseband),
+        config.sample_rate as u32,
+        &options,
+    )?;
+    std::fs::write(&truth_path, signal_gen::ground_truth(&config, &signal))
+        .map_err(|e| DspError::io(&truth_path, e))?;
+
+    info!(
+        "Synthetic AM signal: f_d = {:.3} Hz, f_s = {:.0} Hz, {} samples",
+        config.offset,
+        config.sample_rate,
+        signal.samples.len()
+    );
+    info!("  Saved to: {} ({})", wav_path, options);
+    info!("  Saved to: {} (clean baseband)", clean_path);
+    info!("  Saved to: {}", truth_path);
+    Ok(())
+}
+
+fn parse_number(text: &str) -> Result<f64> {
+    text.trim()
+        .parse()
+        .map_err(|_| DspError::Argument(format!("Invalid number '{}'", text)))
+}
+
+/// Read a recording, mix to mono, band-limit to f_B and convert to the output rate
+fn load_baseband(path: &str, config: &AmConfig) -> Result<Vec<f64>> {
+    let (samples, spec) = wav_io::read_interleaved(path)?;
+    let mono = ChannelMode::Mix.select(&samples, spec.channels as usize).remove(0);
+    // Passing through a 2 f_B rate keeps only content below f_B
+    let limited_rate = 2.0 * config.bandwidth;
+    let limited = resample(&mono, spec.sample_rate as f64, limited_rate);
+    Ok(resample(&limited, limited_rate, config.sample_rate))
+}
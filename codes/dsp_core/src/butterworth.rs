@@ -0,0 +1,178 @@
+use std::f64::consts::PI;
+
+/// Digital Butterworth filter designed by the bilinear transform with a pre-warped cutoff
+///
+/// `b` and `a` are the transfer-function coefficients, normalized so a[0] = 1.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::butterworth::ButterworthFilter;
+///
+/// let filter = ButterworthFilter::lowpass(4, 1000.0, 8000.0);
+/// assert_eq!(filter.a[0], 1.0);
+/// // Unity gain at DC: H(1) = Σb / Σa
+/// let dc_gain = filter.b.iter().sum::<f64>() / filter.a.iter().sum::<f64>();
+/// assert!((dc_gain - 1.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ButterworthFilter {
+    pub b: Vec<f64>,
+    pub a: Vec<f64>,
+    pub order: usize,
+    pub cutoff: f64,
+    pub sample_rate: f64,
+    pub filter_type: FilterType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    Lowpass,
+    Highpass,
+}
+
+impl ButterworthFilter {
+    pub fn lowpass(order: usize, cutoff: f64, sample_rate: f64) -> Self {
+        let (b, a) = design_butterworth_digital_lowpass(order, cutoff, sample_rate);
+        Self { b, a, order, cutoff, sample_rate, filter_type: FilterType::Lowpass }
+    }
+
+    pub fn highpass(order: usize, cutoff: f64, sample_rate: f64) -> Self {
+        let (b, a) = design_butterworth_digital_highpass(order, cutoff, sample_rate);
+        Self { b, a, order, cutoff, sample_rate, filter_type: FilterType::Highpass }
+    }
+}
+
+impl ButterworthFilter {
+    /// The same filter as second-order sections `[b0, b1, b2, a0, a1, a2]` (scipy's `sos`
+    /// layout), a first-order section padded with zeros for an odd order
+    ///
+    /// Each section has unity gain in the passband (at DC for a low-pass, at Nyquist for a
+    /// high-pass), so no intermediate signal grows beyond the input's level there; this is
+    /// the form to filter with in fixed point. The cascade equals `(b, a)` up to rounding.
+    pub fn sos(&self) -> Vec<[f64; 6]> {
+        let lp_cutoff = match self.filter_type {
+            FilterType::Lowpass => self.cutoff,
+            FilterType::Highpass => self.sample_rate / 2.0 - self.cutoff,
+        };
+        let wc = 2.0 * self.sample_rate * (PI * lp_cutoff / self.sample_rate).tan();
+        let poles: Vec<_> = butterworth_analog_poles(self.order).iter().map(|(re, im)| (re * wc, im * wc)).collect();
+        bilinear_sections(&poles, self.sample_rate)
+            .into_iter()
+            .map(|(b, a)| {
+                let gain = a.iter().sum::<f64>() / b.iter().sum::<f64>();
+                let mut section = [0.0; 6];
+                for (i, coef) in b.iter().enumerate() {
+                    section[i] = coef * gain;
+                }
+                section[3..3 + a.len()].copy_from_slice(&a);
+                // H_HP(z) = H_LP(-z), as in the transfer-function design
+                if self.filter_type == FilterType::Highpass {
+                    section[1] = -section[1];
+                    section[4] = -section[4];
+                }
+                section
+            })
+            .collect()
+    }
+}
+
+fn design_butterworth_digital_lowpass(order: usize, cutoff: f64, fs: f64) -> (Vec<f64>, Vec<f64>) {
+    // Pre-warp the cutoff frequency to compensate for bilinear transform distortion
+    let wc = 2.0 * fs * (PI * cutoff / fs).tan();
+    let poles = butterworth_analog_poles(order);
+    let scaled_poles: Vec<_> = poles.iter().map(|(re, im)| (re * wc, im * wc)).collect();
+    bilinear_transform_cascade(&scaled_poles, fs)
+}
+
+fn design_butterworth_digital_highpass(order: usize, cutoff: f64, fs: f64) -> (Vec<f64>, Vec<f64>) {
+    // Spectral inversion method: H_HP(z) = H_LP(-z)
+    // The cutoff frequency is mirrored about fs/4
+    // To get highpass with cutoff fc_hp, design lowpass with cutoff (fs/2 - fc_hp)
+    let lp_cutoff = fs / 2.0 - cutoff;
+    
+    // Design a lowpass filter at the mirrored cutoff frequency
+    let (b_lp, a_lp) = design_butterworth_digital_lowpass(order, lp_cutoff, fs);
+    
+    // Convert lowpass to highpass using spectral inversion: H_HP(z) = H_LP(-z)
+    // This means alternating the signs of coefficients with odd indices
+    let mut b_hp = b_lp.clone();
+    let mut a_hp = a_lp.clone();
+    
+    for (i, val) in b_hp.iter_mut().enumerate() {
+        if i % 2 == 1 {
+            *val = -*val;
+        }
+    }
+    
+    for (i, val) in a_hp.iter_mut().enumerate() {
+        if i % 2 == 1 {
+            *val = -*val;
+        }
+    }
+    
+    (b_hp, a_hp)
+}
+
+fn butterworth_analog_poles(order: usize) -> Vec<(f64, f64)> {
+    (0..order).map(|k| {
+        let theta = PI * (2.0 * k as f64 + order as f64 + 1.0) / (2.0 * order as f64);
+        (theta.cos(), theta.sin())
+    }).collect()
+}
+
+fn bilinear_transform_cascade(poles: &[(f64, f64)], fs: f64) -> (Vec<f64>, Vec<f64>) {
+    let mut b_total = vec![1.0];
+    let mut a_total = vec![1.0];
+    for (b_section, a_section) in bilinear_sections(poles, fs) {
+        b_total = convolve(&b_total, &b_section);
+        a_total = convolve(&a_total, &a_section);
+    }
+    let a0 = a_total[0];
+    for a in a_total.iter_mut() { *a /= a0; }
+    for b in b_total.iter_mut() { *b /= a0; }
+    let b_sum: f64 = b_total.iter().sum();
+    let a_sum: f64 = a_total.iter().sum();
+    let gain = a_sum / b_sum;
+    for b in b_total.iter_mut() { *b *= gain; }
+    (b_total, a_total)
+}
+
+/// Digital sections of the low-pass with analog `poles`, before any gain is applied
+fn bilinear_sections(poles: &[(f64, f64)], fs: f64) -> Vec<(Vec<f64>, Vec<f64>)> {
+    let t = 1.0 / fs;
+    let mut sections = Vec::new();
+    // Real poles give first-order sections; each complex pair gives one second-order
+    // section, built from its upper-half-plane member (the conjugate is implied). The
+    // scaled real pole keeps a rounding residue of sin(π) · wc in its imaginary part, so
+    // "real" is judged relative to the pole's size.
+    for &(pr1, pi1) in poles {
+        if pi1.abs() < 1e-10 * pr1.abs() {
+            let denom = 2.0 - pr1 * t;
+            let z_pole = (2.0 + pr1 * t) / denom;
+            sections.push((vec![1.0, 1.0], vec![1.0, -z_pole]));
+        } else if pi1 > 0.0 {
+            let b_section = vec![1.0, 2.0, 1.0];
+            let denom_re = 2.0 - pr1 * t;
+            let denom_im = -pi1 * t;
+            let denom_mag_sq = denom_re * denom_re + denom_im * denom_im;
+            let z1_re = ((2.0 + pr1 * t) * denom_re + pi1 * t * denom_im) / denom_mag_sq;
+            let z1_im = ((pi1 * t) * denom_re - (2.0 + pr1 * t) * denom_im) / denom_mag_sq;
+            let a1 = -2.0 * z1_re;
+            let a2 = z1_re * z1_re + z1_im * z1_im;
+            let a_section = vec![1.0, a1, a2];
+            sections.push((b_section, a_section));
+        }
+    }
+    sections
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, &a_val) in a.iter().enumerate() {
+        for (j, &b_val) in b.iter().enumerate() {
+            result[i + j] += a_val * b_val;
+        }
+    }
+    result
+}
@@ -0,0 +1,243 @@
+use crate::error::{DspError, Result};
+use crate::pipeline::{self, SpectralPipeline, Stage};
+use std::str::FromStr;
+
+/// Band RMS, relative to the whole signal's, below which a band counts as empty
+const BAND_FLOOR: f64 = 1e-9;
+
+/// How the output gain of the frequency-domain chain is chosen (`--gain-calibration`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GainCalibrator {
+    /// Undo the scaling of each stage, derived from the demodulation math
+    #[default]
+    Analytic,
+    /// Scale so the output RMS inside `band` (Hz) matches a reference's, e.g. Q3's output
+    RmsMatch { band: (f64, f64) },
+}
+
+/// Output gain and how it was obtained, one line per step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    pub gain: f64,
+    pub derivation: Vec<String>,
+}
+
+impl GainCalibrator {
+    /// Read `--gain-calibration <analytic|rms>` from command-line arguments; `rms` matches
+    /// over `band`
+    pub fn from_args(args: &[String], band: (f64, f64)) -> Result<Self> {
+        let mut calibrator = GainCalibrator::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--gain-calibration" {
+                let value = iter.next().ok_or_else(|| DspError::missing_value("--gain-calibration"))?;
+                calibrator = match value.parse().map_err(DspError::Argument)? {
+                    GainCalibrator::RmsMatch { .. } => GainCalibrator::RmsMatch { band },
+                    analytic => analytic,
+                };
+            }
+        }
+        Ok(calibrator)
+    }
+
+    /// Gain that makes `stages` pass the message m(t) of the DSB-SC input
+    /// x(t) = m(t) cos(2π f_d t) at unit level
+    ///
+    /// The shift computes (X(f - f_d) + X(f + f_d)) / 2, the spectrum of x(t) cos(2π f_d t) =
+    /// m(t)/2 + m(t)/2 cos(4π f_d t); the low-pass keeps m(t)/2, so each shift halves the
+    /// message. The low-pass and a high-pass far enough below the next shift's f_d pass the
+    /// band unchanged, but a high-pass at or above that f_d removes the lower sideband: only
+    /// the upper one is shifted down, and the message comes out at half the level once more.
+    /// A cutoff between f_d - f_B (the low-pass after the shift) and f_d removes the lower
+    /// sideband of the message above f_d - cutoff only; it counts as 1/2, so the message
+    /// below that frequency comes out twice as loud. Gain stages count as they are.
+    pub fn analytic(stages: &[Stage]) -> Calibration {
+        let mut derivation = Vec::new();
+        let mut level = 1.0;
+        for (i, stage) in stages.iter().enumerate() {
+            let (factor, reason) = match stage {
+                Stage::Highpass(cutoff) => highpass_factor(*cutoff, &stages[i + 1..]),
+                Stage::Lowpass(_) => (1.0, "ideal filter, unit passband gain"),
+                Stage::Shift { .. } => (0.5, "cos² = 1/2 + 1/2 cos(2θ): the message comes out at half level"),
+                Stage::Gain(gain) => (*gain, "fixed gain"),
+            };
+            level *= factor;
+            derivation.push(format!("{}: x{} ({})", stage, factor, reason));
+        }
+        let gain = 1.0 / level;
+        derivation.push(format!("message level {} -> output gain {} = 1 / {}", level, gain, level));
+        Calibration { gain, derivation }
+    }
+
+    /// Gain that brings a time-domain coherent chain to the level of the calibrated
+    /// frequency-domain one: a high-pass at `highpass_cutoff`, mixing with 2 cos(2π f_d t)
+    /// and a low-pass at `f_b`, as in [`TimeDomainDemodulator`](crate::demodulator::TimeDomainDemodulator)
+    ///
+    /// The filters count as ideal and the mixer as a shift followed by its factor 2, so with
+    /// the high-pass at f_d the chain passes the message at half level and the gain is 2. A
+    /// phasing USB demodulator keeps the upper sideband as a high-pass at f_d does and takes
+    /// the same gain.
+    pub fn coherent(highpass_cutoff: f64, f_d: f64, f_b: f64) -> Calibration {
+        let stages = SpectralPipeline::new().highpass(highpass_cutoff).shift(f_d).lowpass(f_b).gain(2.0);
+        Self::analytic(stages.stages())
+    }
+
+    /// Gain that brings the RMS of `output` inside `band` to that of `reference`
+    ///
+    /// The band RMS comes from the spectrum, so the time lag between two demodulators (Q3's
+    /// IIR filters delay its output) does not affect it.
+    pub fn rms_match(output: &[f64], reference: &[f64], f_s: f64, band: (f64, f64)) -> Result<Calibration> {
+        let ((output_rms, output_total), (reference_rms, reference_total)) =
+            (band_rms(output, f_s, band), band_rms(reference, f_s, band));
+        // Rounding noise alone is no level to match
+        if output_rms <= BAND_FLOOR * output_total || reference_rms <= BAND_FLOOR * reference_total {
+            return Err(DspError::Data(format!(
+                "no signal in the {:.0}-{:.0} Hz calibration band",
+                band.0, band.1
+            )));
+        }
+        let gain = reference_rms / output_rms;
+        let derivation = vec![
+            format!("reference RMS in {:.0}-{:.0} Hz: {:.6e}", band.0, band.1, reference_rms),
+            format!("uncalibrated output RMS in the same band: {:.6e}", output_rms),
+            format!("output gain {:.6} = {:.6e} / {:.6e}", gain, reference_rms, output_rms),
+        ];
+        Ok(Calibration { gain, derivation })
+    }
+}
+
+/// Message level after an ideal high-pass at `cutoff` followed by `rest`, on a DSB input
+fn highpass_factor(cutoff: f64, rest: &[Stage]) -> (f64, &'static str) {
+    let mut after_shift = rest.iter().skip_while(|s| !matches!(s, Stage::Shift { .. }));
+    let f_d = match after_shift.next() {
+        Some(Stage::Shift { f_d, .. }) => *f_d,
+        _ => return (1.0, "ideal filter, unit passband gain"),
+    };
+    let f_b = after_shift.find_map(|s| match s {
+        Stage::Lowpass(f_b) => Some(*f_b),
+        _ => None,
+    });
+    match f_b {
+        Some(f_b) if cutoff <= f_d - f_b => (1.0, "ideal filter below both sidebands, unit passband gain"),
+        _ if cutoff >= f_d => (0.5, "cutoff at or above f_d removes the lower sideband: half the message"),
+        _ => (0.5, "cutoff inside the lower sideband removes most of it: half the message above f_d - cutoff"),
+    }
+}
+
+impl FromStr for GainCalibrator {
+    type Err = String;
+
+    /// `analytic` or `rms` (the band is filled in by [`GainCalibrator::from_args`])
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "analytic" => Ok(GainCalibrator::Analytic),
+            "rms" | "rms-match" => Ok(GainCalibrator::RmsMatch { band: (0.0, 0.0) }),
+            _ => Err(format!("Unknown gain calibration '{}' (expected analytic or rms)", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for GainCalibrator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GainCalibrator::Analytic => write!(f, "analytic"),
+            GainCalibrator::RmsMatch { band } => write!(f, "RMS match over {:.0}-{:.0} Hz", band.0, band.1),
+        }
+    }
+}
+
+/// RMS of the part of `signal` inside `band` (Hz) and of the whole signal, by Parseval
+/// over the FFT bins
+fn band_rms(signal: &[f64], f_s: f64, (low, high): (f64, f64)) -> (f64, f64) {
+    let n = signal.len();
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let (mut band, mut total) = (0.0, 0.0);
+    for (k, x) in pipeline::fft(signal).iter().enumerate() {
+        // Both halves of the spectrum, by the magnitude of each bin's frequency
+        let f = k.min(n - k) as f64 * f_s / n as f64;
+        if f >= low && f <= high {
+            band += x.norm_sqr();
+        }
+        total += x.norm_sqr();
+    }
+    let scale = 1.0 / (n as f64 * n as f64);
+    ((band * scale).sqrt(), (total * scale).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demodulator::TimeDomainDemodulator;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_analytic_and_rms_gains() {
+        // A 200 Hz message on a 2 kHz carrier comes out at unit level with the analytic gain
+        let (f_s, f_d) = (8000.0, 2000.0);
+        let message: Vec<f64> = (0..8000).map(|k| (2.0 * PI * 200.0 * k as f64 / f_s).cos()).collect();
+        let signal: Vec<f64> =
+            message.iter().enumerate().map(|(k, m)| m * (2.0 * PI * f_d * k as f64 / f_s).cos()).collect();
+        let measured = |stages: &SpectralPipeline| {
+            let uncalibrated = stages.run(&signal, f_s).output;
+            GainCalibrator::rms_match(&uncalibrated, &message, f_s, (0.0, 500.0)).unwrap().gain
+        };
+
+        // Q4's chain: the high-pass at f_d keeps the upper sideband only, so the message is
+        // halved twice
+        let stages = SpectralPipeline::new().highpass(f_d).shift(f_d).lowpass(500.0);
+        let calibration = GainCalibrator::analytic(stages.stages());
+        assert_eq!(calibration.gain, 4.0);
+        assert_eq!(calibration.derivation.len(), 4);
+        assert!((measured(&stages) - 4.0).abs() < 1e-9, "gain {}", measured(&stages));
+
+        // A high-pass below both sidebands leaves the shift as the only halving
+        let guarded = SpectralPipeline::new().highpass(1500.0).shift(f_d).lowpass(500.0);
+        assert_eq!(GainCalibrator::analytic(guarded.stages()).gain, 2.0);
+        assert!((measured(&guarded) - 2.0).abs() < 1e-9, "gain {}", measured(&guarded));
+
+        let uncalibrated = stages.run(&signal, f_s).output;
+        assert!(GainCalibrator::rms_match(&uncalibrated, &message, f_s, (3000.0, 3500.0)).is_err());
+
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(GainCalibrator::from_args(&args(""), (0.0, 4000.0)).unwrap(), GainCalibrator::Analytic);
+        assert_eq!(
+            GainCalibrator::from_args(&args("--gain-calibration rms"), (0.0, 4000.0)).unwrap(),
+            GainCalibrator::RmsMatch { band: (0.0, 4000.0) }
+        );
+        assert!(GainCalibrator::from_args(&args("--gain-calibration x2"), (0.0, 4000.0)).is_err());
+    }
+
+    #[test]
+    fn test_coherent_gain_matches_frequency_domain_level() {
+        // DSB-SC tone: a 1.5 kHz message on a 3 kHz carrier, so the Butterworth high-pass at
+        // f_d passes the upper sideband and all but 0.4 % of the lower one is gone
+        let (f_s, f_d, f_b) = (16000.0, 3000.0, 2000.0);
+        let signal: Vec<f64> = (0..16000)
+            .map(|k| (2.0 * PI * 1500.0 * k as f64 / f_s).cos() * (2.0 * PI * f_d * k as f64 / f_s).cos())
+            .collect();
+        let rms = |x: &[f64]| {
+            // Middle half only, past the IIR filters' start-up transient
+            let middle = &x[x.len() / 4..3 * x.len() / 4];
+            (middle.iter().map(|v| v * v).sum::<f64>() / middle.len() as f64).sqrt()
+        };
+
+        let stages = SpectralPipeline::new().highpass(f_d).shift(f_d).lowpass(f_b);
+        let frequency_domain = stages.clone().gain(GainCalibrator::analytic(stages.stages()).gain).run(&signal, f_s).output;
+        let coherent = GainCalibrator::coherent(f_d, f_d, f_b);
+        assert_eq!(coherent.gain, 2.0);
+        let time_domain: Vec<f64> = TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s)
+            .demodulate(&signal)
+            .iter()
+            .map(|y| coherent.gain * y)
+            .collect();
+        // Both come out at the message's level, 1/√2
+        assert!((rms(&frequency_domain) - 0.5f64.sqrt()).abs() < 1e-6, "FFT chain RMS {}", rms(&frequency_domain));
+        let ratio = rms(&time_domain) / rms(&frequency_domain);
+        assert!((ratio - 1.0).abs() < 0.01, "time/frequency-domain level ratio {}", ratio);
+
+        // A guard band of f_b keeps both sidebands, leaving only the mixer's own factor 2
+        assert_eq!(GainCalibrator::coherent(f_d - f_b, f_d, f_b).gain, 1.0);
+    }
+}
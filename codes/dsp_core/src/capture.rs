@@ -0,0 +1,242 @@
+use crate::error::{DspError, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Samples per block when `--live-block` is not given
+pub const DEFAULT_BLOCK: usize = 1024;
+
+/// Blocks that may wait for processing before new ones are dropped
+///
+/// This bounds the latency of a live run to about `DEFAULT_QUEUE` blocks: a processing
+/// stall loses audio instead of falling further and further behind the input.
+pub const DEFAULT_QUEUE: usize = 8;
+
+/// Mono samples from a live source, and when the last of them arrived
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub samples: Vec<f64>,
+    pub captured: Instant,
+}
+
+/// Live audio in blocks of a fixed size, from an input device or a recording replayed at
+/// its own pace
+///
+/// Blocks are handed over through a bounded queue; when it is full the newest block is
+/// dropped and counted (see [`DEFAULT_QUEUE`]).
+pub struct LiveSource {
+    /// Device name, or the replayed file
+    pub name: String,
+    pub sample_rate: f64,
+    blocks: Receiver<Block>,
+    dropped: Arc<AtomicUsize>,
+    /// Capture stops when the stream is dropped
+    #[cfg(feature = "capture")]
+    _stream: Option<cpal::Stream>,
+}
+
+/// Producer side of a [`LiveSource`]: cuts incoming samples into blocks and queues them
+struct BlockSender {
+    queue: SyncSender<Block>,
+    dropped: Arc<AtomicUsize>,
+    block: usize,
+    pending: Vec<f64>,
+    /// Wait for room in the queue instead of dropping blocks
+    lossless: bool,
+}
+
+impl BlockSender {
+    /// Queue every complete block; false once the source was dropped
+    fn push(&mut self, samples: impl IntoIterator<Item = f64>) -> bool {
+        for x in samples {
+            self.pending.push(x);
+            if self.pending.len() < self.block {
+                continue;
+            }
+            let block = Block {
+                samples: std::mem::replace(&mut self.pending, Vec::with_capacity(self.block)),
+                captured: Instant::now(),
+            };
+            let sent = if self.lossless {
+                self.queue.send(block).is_ok()
+            } else {
+                match self.queue.try_send(block) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        true
+                    }
+                    Err(TrySendError::Disconnected(_)) => false,
+                }
+            };
+            if !sent {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl LiveSource {
+    fn channel(name: String, sample_rate: f64, block: usize, queue: usize, lossless: bool) -> (BlockSender, LiveSource) {
+        let (sender, receiver) = mpsc::sync_channel(queue.max(1));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let block = block.max(1);
+        let sender = BlockSender {
+            queue: sender,
+            dropped: Arc::clone(&dropped),
+            block,
+            pending: Vec::with_capacity(block),
+            lossless,
+        };
+        let source = LiveSource {
+            name,
+            sample_rate,
+            blocks: receiver,
+            dropped,
+            #[cfg(feature = "capture")]
+            _stream: None,
+        };
+        (sender, source)
+    }
+
+    /// Replay `samples` as if they were being captured: one block every block duration when
+    /// `realtime`, otherwise as fast as they are consumed and without dropping any
+    ///
+    /// The replay is the way to try a live run without an input device, and its input is
+    /// known exactly. A final partial block is not sent.
+    pub fn replay(name: &str, samples: Vec<f64>, sample_rate: f64, block: usize, queue: usize, realtime: bool) -> Self {
+        let (mut sender, source) = Self::channel(name.to_string(), sample_rate, block, queue, !realtime);
+        let block = sender.block;
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            for (i, chunk) in samples.chunks(block).enumerate() {
+                if realtime {
+                    // Each block becomes available once its last sample would have arrived
+                    let due = Duration::from_secs_f64(((i + 1) * block) as f64 / sample_rate);
+                    if let Some(wait) = due.checked_sub(start.elapsed()) {
+                        std::thread::sleep(wait);
+                    }
+                }
+                if !sender.push(chunk.iter().copied()) {
+                    return;
+                }
+            }
+        });
+        source
+    }
+
+    /// Next block, waiting for it; `None` once a replay has ended
+    pub fn next_block(&self) -> Option<Block> {
+        self.blocks.recv().ok()
+    }
+
+    /// Blocks lost so far because processing fell behind
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Capture from the input device whose name contains `name` (the default input device
+    /// without one), mixed down to mono
+    ///
+    /// Capturing what the computer plays needs a loopback device, such as a PulseAudio or
+    /// PipeWire "Monitor of ..." source. Needs the `capture` feature; without it an error
+    /// explaining how to enable it is returned.
+    #[cfg(feature = "capture")]
+    pub fn device(name: Option<&str>, block: usize, queue: usize) -> Result<Self> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        use cpal::SampleFormat;
+
+        let host = cpal::default_host();
+        let device = match name {
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| DspError::Other(format!("Cannot list audio input devices: {}", e)))?
+                .find(|device| device.name().is_ok_and(|n| n.contains(name)))
+                .ok_or_else(|| DspError::Argument(format!("No audio input device matching '{}'", name)))?,
+            None => host
+                .default_input_device()
+                .ok_or_else(|| DspError::Other("No audio input device".to_string()))?,
+        };
+        let config = device
+            .default_input_config()
+            .map_err(|e| DspError::Other(format!("Cannot configure audio input: {}", e)))?;
+        let device_name = device.name().unwrap_or_else(|_| "audio input".to_string());
+        let sample_rate = config.sample_rate().0 as f64;
+        let (sender, mut source) = Self::channel(device_name, sample_rate, block, queue, false);
+
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, sender),
+            SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, sender),
+            SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, sender),
+            SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, sender),
+            other => Err(DspError::Unsupported(format!("audio input sample format {} is not supported", other))),
+        }?;
+        stream
+            .play()
+            .map_err(|e| DspError::Other(format!("Cannot start audio capture: {}", e)))?;
+        source._stream = Some(stream);
+        Ok(source)
+    }
+
+    #[cfg(not(feature = "capture"))]
+    pub fn device(_name: Option<&str>, _block: usize, _queue: usize) -> Result<Self> {
+        Err(DspError::Unsupported(
+            "built without audio capture; rebuild with `--features capture`".to_string(),
+        ))
+    }
+}
+
+/// Input stream that mixes every frame of `T` samples down to one f64 sample
+#[cfg(feature = "capture")]
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, mut sender: BlockSender) -> Result<cpal::Stream>
+where
+    T: cpal::SizedSample,
+    f64: cpal::FromSample<T>,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels.max(1) as usize;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let frames = data.chunks(channels);
+                sender.push(frames.map(|frame| frame.iter().map(|&s| s.to_sample::<f64>()).sum::<f64>() / channels as f64));
+            },
+            |e| log::error!("Audio capture error: {}", e),
+            None,
+        )
+        .map_err(|e| DspError::Other(format!("Cannot open audio input stream: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_blocks_and_drops() {
+        let samples: Vec<f64> = (0..1000).map(|n| n as f64).collect();
+        let source = LiveSource::replay("ramp", samples.clone(), 8000.0, 256, 2, false);
+        let mut received = Vec::new();
+        while let Some(block) = source.next_block() {
+            assert_eq!(block.samples.len(), 256);
+            received.extend(block.samples);
+        }
+        // The partial block at the end is not sent
+        assert_eq!(received, samples[..768]);
+        assert_eq!(source.dropped(), 0);
+
+        // A consumer that does not keep up loses blocks instead of falling behind
+        let (mut sender, source) = LiveSource::channel("test".to_string(), 8000.0, 4, 2, false);
+        assert!(sender.push((0..20).map(|n| n as f64)));
+        assert_eq!(source.dropped(), 3);
+        assert_eq!(source.next_block().unwrap().samples, [0.0, 1.0, 2.0, 3.0]);
+        drop(source);
+        assert!(!sender.push([0.0; 4]));
+
+        assert!(cfg!(feature = "capture") || matches!(LiveSource::device(None, 256, 2), Err(DspError::Unsupported(_))));
+    }
+}
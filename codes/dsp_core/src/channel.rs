@@ -0,0 +1,167 @@
+use crate::error::{DspError, Result};
+use crate::noise::{self, NoiseKind};
+use crate::reproducible::{Reproducibility, DEFAULT_SEED};
+use crate::resample::resample;
+
+/// One delayed, attenuated copy of the signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Echo {
+    /// Delay in seconds
+    pub delay: f64,
+    /// Linear gain relative to the direct path
+    pub gain: f64,
+}
+
+/// Transmission-channel impairments: additive noise, multipath and clock drift
+///
+/// (Unrelated to [`crate::channels`], which selects the audio channels of a file.)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChannelImpairments {
+    /// Additive noise shape and SNR in dB; `None` for no noise
+    pub noise: Option<(NoiseKind, f64)>,
+    /// Multipath echoes added to the direct path
+    pub echoes: Vec<Echo>,
+    /// Sample-clock error of the recorder in parts per million
+    pub clock_drift_ppm: f64,
+    /// Noise generator seed
+    pub seed: u64,
+}
+
+impl ChannelImpairments {
+    /// Read `--noise <white|pink|brown>`, `--snr <dB>`, `--echo <delay_ms:gain>` (repeatable),
+    /// `--drift-ppm <ppm>` and `--seed <n>` from command-line arguments
+    ///
+    /// `--noise` without `--snr` is an error; `--snr` alone means white noise. The noise
+    /// seed is the `noise` stream of [`Reproducibility`], so `--seed` moves it together with
+    /// the run's other random streams.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut impairments = ChannelImpairments {
+            seed: Reproducibility::from_args(args)?.stream_seed("noise", DEFAULT_SEED),
+            ..Default::default()
+        };
+        let mut kind = None;
+        let mut snr = None;
+        let parse = |name: &str, value: &str| -> Result<f64> {
+            value.parse().map_err(|_| DspError::invalid_value(name, value))
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if !matches!(name, "--noise" | "--snr" | "--echo" | "--drift-ppm") {
+                continue;
+            }
+            let value = iter.next().ok_or_else(|| DspError::missing_value(name))?;
+            match name {
+                "--noise" => kind = Some(value.parse::<NoiseKind>().map_err(DspError::Argument)?),
+                "--snr" => snr = Some(parse(name, value)?),
+                "--echo" => {
+                    let (delay, gain) = value
+                        .split_once(':')
+                        .ok_or_else(|| {
+                            DspError::Argument(format!("--echo expects delay_ms:gain, got '{}'", value))
+                        })?;
+                    impairments.echoes.push(Echo {
+                        delay: parse(name, delay)? / 1000.0,
+                        gain: parse(name, gain)?,
+                    });
+                }
+                _ => impairments.clock_drift_ppm = parse(name, value)?,
+            }
+        }
+
+        impairments.noise = match (kind, snr) {
+            (kind, Some(snr)) => Some((kind.unwrap_or_default(), snr)),
+            (Some(_), None) => return Err(DspError::Argument("--noise requires --snr".to_string())),
+            (None, None) => None,
+        };
+        Ok(impairments)
+    }
+
+    /// Same impairments with the noise replaced by `kind` at `snr_db`
+    pub fn with_noise(&self, kind: NoiseKind, snr_db: f64) -> Self {
+        ChannelImpairments {
+            noise: Some((kind, snr_db)),
+            ..self.clone()
+        }
+    }
+
+    /// Pass a signal through the channel: multipath, then clock drift, then receiver noise
+    pub fn apply(&self, signal: &[f64], sample_rate: f64) -> Vec<f64> {
+        let mut output = add_echoes(signal, sample_rate, &self.echoes);
+        if self.clock_drift_ppm != 0.0 {
+            output = apply_clock_drift(&output, self.clock_drift_ppm);
+        }
+        if let Some((kind, snr_db)) = self.noise {
+            output = add_noise(&output, kind, snr_db, self.seed);
+        }
+        output
+    }
+}
+
+/// Add noise of the given shape so that signal power / noise power equals `snr_db`
+pub fn add_noise(signal: &[f64], kind: NoiseKind, snr_db: f64, seed: u64) -> Vec<f64> {
+    // Unit-power noise, so the gain is the wanted noise RMS
+    let noise = noise::generate(kind, signal.len(), seed);
+    let signal_power = signal.iter().map(|v| v * v).sum::<f64>() / signal.len().max(1) as f64;
+    let gain = (signal_power / 10f64.powf(snr_db / 10.0)).sqrt();
+    signal.iter().zip(&noise).map(|(s, n)| s + gain * n).collect()
+}
+
+/// Add delayed copies of the signal (output keeps the input length)
+pub fn add_echoes(signal: &[f64], sample_rate: f64, echoes: &[Echo]) -> Vec<f64> {
+    let mut output = signal.to_vec();
+    for echo in echoes {
+        let delay = (echo.delay * sample_rate).round() as usize;
+        for i in delay..output.len() {
+            output[i] += echo.gain * signal[i - delay];
+        }
+    }
+    output
+}
+
+/// Model a recorder whose sample clock runs `ppm` parts per million fast
+///
+/// A fast clock takes more samples of the same waveform, so every frequency in the
+/// recording appears scaled by 1 / (1 + ppm * 1e-6) when played at the nominal rate.
+pub fn apply_clock_drift(signal: &[f64], ppm: f64) -> Vec<f64> {
+    resample(signal, 1.0, 1.0 + ppm * 1e-6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_noise_snr_echo_and_drift() {
+        let fs = 8000.0;
+        let signal: Vec<f64> = (0..16000).map(|i| (2.0 * PI * 440.0 * i as f64 / fs).sin()).collect();
+
+        for kind in [NoiseKind::White, NoiseKind::Pink, NoiseKind::Brown] {
+            let noisy = add_noise(&signal, kind, 10.0, 7);
+            let noise_power = noisy.iter().zip(&signal).map(|(y, x)| (y - x).powi(2)).sum::<f64>() / 16000.0;
+            let snr = 10.0 * (0.5 / noise_power).log10();
+            assert!((snr - 10.0).abs() < 0.1, "{}: snr = {}", kind, snr);
+        }
+
+        let impulse: Vec<f64> = (0..100).map(|i| if i == 0 { 1.0 } else { 0.0 }).collect();
+        let echoed = add_echoes(&impulse, 1000.0, &[Echo { delay: 0.01, gain: 0.5 }]);
+        assert_eq!(echoed[10], 0.5);
+
+        let drifted = apply_clock_drift(&signal, 100.0);
+        assert_eq!(drifted.len(), 16002);
+
+        let args: Vec<String> = ["--noise", "pink", "--snr", "20", "--echo", "2:0.3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let parsed = ChannelImpairments::from_args(&args).unwrap();
+        assert_eq!(parsed.noise, Some((NoiseKind::Pink, 20.0)));
+        assert_eq!(parsed.echoes, vec![Echo { delay: 0.002, gain: 0.3 }]);
+        assert_eq!(parsed.seed, DEFAULT_SEED);
+        let seeded = ChannelImpairments::from_args(&[args, vec!["--seed".to_string(), "42".to_string()]].concat()).unwrap();
+        let reproducibility = Reproducibility { seed: Some(42), deterministic: false };
+        assert_eq!(seeded.seed, reproducibility.stream_seed("noise", DEFAULT_SEED));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::{DspError, Result};
+use std::str::FromStr;
+
+/// How a multi-channel recording is turned into the signals that get processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelMode {
+    /// First channel only
+    Left,
+    /// Second channel only (falls back to the first for mono input)
+    Right,
+    /// Average of all channels
+    #[default]
+    Mix,
+    /// Every channel separately, so each can be demodulated on its own
+    Both,
+}
+
+impl FromStr for ChannelMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "left" => Ok(ChannelMode::Left),
+            "right" => Ok(ChannelMode::Right),
+            "mix" => Ok(ChannelMode::Mix),
+            "both" => Ok(ChannelMode::Both),
+            other => Err(format!(
+                "Unknown channel mode '{}' (expected left, right, mix or both)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ChannelMode::Left => "left",
+            ChannelMode::Right => "right",
+            ChannelMode::Mix => "mix",
+            ChannelMode::Both => "both",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ChannelMode {
+    /// Read `--channels <mode>` / `--channels=<mode>` from command-line arguments, default `Mix`
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut mode = ChannelMode::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(value) = arg.strip_prefix("--channels=") {
+                mode = value.parse().map_err(DspError::Argument)?;
+            } else if arg == "--channels" {
+                let value = iter.next().ok_or_else(|| DspError::missing_value("--channels"))?;
+                mode = value.parse().map_err(DspError::Argument)?;
+            }
+        }
+        Ok(mode)
+    }
+
+    /// Split interleaved samples into the channels selected by this mode
+    ///
+    /// Returns one signal for `Left`, `Right` and `Mix`, and `num_channels`
+    /// signals for `Both`.
+    pub fn select(self, interleaved: &[f64], num_channels: usize) -> Vec<Vec<f64>> {
+        let num_channels = num_channels.max(1);
+        let channel = |c: usize| -> Vec<f64> {
+            interleaved
+                .chunks_exact(num_channels)
+                .map(|frame| frame[c])
+                .collect()
+        };
+        match self {
+            ChannelMode::Left => vec![channel(0)],
+            ChannelMode::Right => vec![channel(1.min(num_channels - 1))],
+            ChannelMode::Mix => vec![interleaved
+                .chunks_exact(num_channels)
+                .map(|frame| frame.iter().sum::<f64>() / num_channels as f64)
+                .collect()],
+            ChannelMode::Both => (0..num_channels).map(channel).collect(),
+        }
+    }
+}
+
+/// Interleave per-channel signals into frames (`L R L R ...`), truncating to the shortest
+pub fn interleave(channels: &[Vec<f64>]) -> Vec<f64> {
+    let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+    (0..len)
+        .flat_map(|i| channels.iter().map(move |ch| ch[i]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_and_interleave() {
+        let stereo = [1.0, 10.0, 2.0, 20.0, 3.0, 30.0];
+        assert_eq!(ChannelMode::Left.select(&stereo, 2), vec![vec![1.0, 2.0, 3.0]]);
+        assert_eq!(ChannelMode::Right.select(&stereo, 2), vec![vec![10.0, 20.0, 30.0]]);
+        assert_eq!(ChannelMode::Mix.select(&stereo, 2), vec![vec![5.5, 11.0, 16.5]]);
+
+        let both = ChannelMode::Both.select(&stereo, 2);
+        assert_eq!(both.len(), 2);
+        assert_eq!(interleave(&both), stereo.to_vec());
+
+        // Mono input: every mode yields the single channel
+        assert_eq!(ChannelMode::Right.select(&[1.0, 2.0], 1), vec![vec![1.0, 2.0]]);
+
+        let args = vec!["--channels".to_string(), "Both".to_string()];
+        assert_eq!(ChannelMode::from_args(&args).unwrap(), ChannelMode::Both);
+        assert!(ChannelMode::from_args(&["--channels=up".to_string()]).is_err());
+    }
+}
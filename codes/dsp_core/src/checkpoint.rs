@@ -0,0 +1,192 @@
+use crate::error::{DspError, Result};
+use crate::npy::{self, Array};
+use crate::reproducible::StableHasher;
+use rustfft::num_complex::Complex;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// On-disk format of a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckpointFormat {
+    /// NumPy `.npy` (complex128), loadable with `numpy.load`
+    #[default]
+    Npy,
+    /// Text, one `re,im` pair per line
+    Csv,
+}
+
+impl CheckpointFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            CheckpointFormat::Npy => "npy",
+            CheckpointFormat::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for CheckpointFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "npy" => Ok(CheckpointFormat::Npy),
+            "csv" => Ok(CheckpointFormat::Csv),
+            other => Err(format!("Unknown checkpoint format '{}' (expected npy or csv)", other)),
+        }
+    }
+}
+
+/// Where intermediate spectra are written and whether they are read back
+///
+/// Each file is named after its stage and a key that covers the input and every stage
+/// parameter up to that point, so a changed parameter never picks up a stale file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoints {
+    pub dir: PathBuf,
+    pub format: CheckpointFormat,
+    /// Load existing checkpoints instead of recomputing them
+    pub resume: bool,
+}
+
+impl Checkpoints {
+    /// Read `--checkpoint <dir>`, `--checkpoint-format <npy|csv>` and `--resume` from
+    /// command-line arguments
+    ///
+    /// Returns `None` when no checkpoint directory is given.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut dir = None;
+        let mut format = CheckpointFormat::default();
+        let mut resume = false;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--checkpoint" => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| DspError::Argument("--checkpoint requires a directory".to_string()))?;
+                    dir = Some(PathBuf::from(value))
+                }
+                "--checkpoint-format" => {
+                    format = iter
+                        .next()
+                        .ok_or_else(|| DspError::missing_value("--checkpoint-format"))?
+                        .parse()
+                        .map_err(DspError::Argument)?
+                }
+                "--resume" => resume = true,
+                _ => {}
+            }
+        }
+        match dir {
+            Some(dir) => Ok(Some(Checkpoints { dir, format, resume })),
+            None if resume => Err(DspError::Argument("--resume requires --checkpoint <dir>".to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// File for checkpoint `name` under `key`
+    pub fn path(&self, name: &str, key: u64) -> PathBuf {
+        self.dir.join(format!("{}_{:016x}.{}", name, key, self.format.extension()))
+    }
+
+    /// Write a checkpoint, creating the directory if needed
+    pub fn save(&self, name: &str, key: u64, data: &[Complex<f64>]) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| DspError::io(&self.dir, e))?;
+        let path = self.path(name, key);
+        match self.format {
+            CheckpointFormat::Npy => npy::save(&path, data)?,
+            CheckpointFormat::Csv => write_csv(&path, data)?,
+        }
+        Ok(path)
+    }
+
+    /// Read a checkpoint back; `None` when not resuming or when it does not exist yet
+    pub fn load(&self, name: &str, key: u64) -> Result<Option<Vec<Complex<f64>>>> {
+        let path = self.path(name, key);
+        if !self.resume || !path.exists() {
+            return Ok(None);
+        }
+        let data = match self.format {
+            CheckpointFormat::Npy => match npy::load(&path)? {
+                Array::Complex(data) => data,
+                Array::Real(_) => {
+                    return Err(DspError::Data(format!("{}: expected a complex128 array", path.display())))
+                }
+            },
+            CheckpointFormat::Csv => read_csv(&path)?,
+        };
+        Ok(Some(data))
+    }
+}
+
+/// Chain `part` onto a parent key
+pub fn key(parent: u64, part: &impl Hash) -> u64 {
+    let mut hasher = StableHasher::default();
+    parent.hash(&mut hasher);
+    part.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Key of a sampled signal, from its exact sample values and rate
+pub fn signal_key(samples: &[f64], sample_rate: f64) -> u64 {
+    let bits: Vec<u64> = samples.iter().map(|x| x.to_bits()).collect();
+    key(sample_rate.to_bits(), &bits)
+}
+
+/// Write `re,im` lines with a header; `{:e}` keeps every digit so a reload is exact
+pub fn write_csv(path: &Path, data: &[Complex<f64>]) -> Result<()> {
+    let mut content = String::from("re,im\n");
+    for c in data {
+        content.push_str(&format!("{:e},{:e}\n", c.re, c.im));
+    }
+    std::fs::write(path, content).map_err(|e| DspError::io(path, e))
+}
+
+/// Read a file written by [`write_csv`]
+pub fn read_csv(path: &Path) -> Result<Vec<Complex<f64>>> {
+    let content = std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))?;
+    content
+        .lines()
+        .skip(1)
+        .enumerate()
+        .map(|(i, line)| {
+            let mut fields = line.split(',').map(|v| v.trim().parse::<f64>());
+            match (fields.next(), fields.next()) {
+                (Some(Ok(re)), Some(Ok(im))) => Ok(Complex::new(re, im)),
+                _ => Err(DspError::Data(format!("{}: invalid line {}", path.display(), i + 2))),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npy_and_csv_round_trip() {
+        let data: Vec<Complex<f64>> = (0..37).map(|k| Complex::new(k as f64 / 7.0, -(k as f64).sqrt())).collect();
+        let dir = std::env::temp_dir().join(format!("dsp_core_checkpoint_{}", std::process::id()));
+        for format in [CheckpointFormat::Npy, CheckpointFormat::Csv] {
+            let checkpoints = Checkpoints {
+                dir: dir.clone(),
+                format,
+                resume: true,
+            };
+            let path = checkpoints.save("stage", 42, &data).unwrap();
+            assert_eq!(checkpoints.load("stage", 42).unwrap(), Some(data.clone()));
+            assert_eq!(checkpoints.load("stage", 43).unwrap(), None);
+            if format == CheckpointFormat::Npy {
+                // Data starts on a 64-byte boundary as the format requires
+                assert_eq!((std::fs::metadata(&path).unwrap().len() - 16 * 37) % 64, 0);
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let args: Vec<String> = ["--resume", "--checkpoint", "ckpt"].iter().map(|s| s.to_string()).collect();
+        assert!(Checkpoints::from_args(&args).unwrap().unwrap().resume);
+        assert!(Checkpoints::from_args(&args[..1]).is_err());
+        assert_ne!(key(1, &"lowpass 3000"), key(1, &"lowpass 3500"));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::wav_io::WavWriteOptions;
+
+/// Shortest run of samples stuck at the peak that counts as clipping
+///
+/// A waveform passes through its maximum in one or two samples; a recorder or converter
+/// that runs out of range holds it for longer.
+pub const MIN_CLIPPED_RUN: usize = 3;
+
+/// Samples within this fraction of the peak count as "at the peak", about one 16-bit step
+/// at full scale
+const PEAK_TOLERANCE: f64 = 1e-4;
+
+/// Flat tops found by [`ClippingReport::detect`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClippingReport {
+    /// Samples in runs of at least [`MIN_CLIPPED_RUN`] at the channel's peak magnitude
+    pub clipped_samples: usize,
+    /// Number of such runs
+    pub runs: usize,
+    /// Longest run (samples)
+    pub longest_run: usize,
+    /// Largest magnitude over all channels (dBFS)
+    pub peak_dbfs: f64,
+}
+
+impl ClippingReport {
+    /// Look for runs of [`MIN_CLIPPED_RUN`] or more consecutive samples of one sign within
+    /// 0.01 % of each channel's peak magnitude
+    ///
+    /// This finds clipping at full scale, and also clipping at an earlier stage that was
+    /// scaled down afterwards: either way the flat tops sit at the peak.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::clipping::ClippingReport;
+    ///
+    /// // A sine driven 6 dB into the converter's limits
+    /// let clipped: Vec<f64> = (0..1000).map(|n| (2.0 * (n as f64 * 0.05).sin()).clamp(-1.0, 1.0)).collect();
+    /// let report = ClippingReport::detect(&[clipped]);
+    /// assert!(report.runs > 0 && report.clipped_samples > 300);
+    ///
+    /// let clean: Vec<f64> = (0..1000).map(|n| 0.9 * (n as f64 * 0.05).sin()).collect();
+    /// assert_eq!(ClippingReport::detect(&[clean]).runs, 0);
+    /// ```
+    pub fn detect<C: AsRef<[f64]>>(channels: &[C]) -> Self {
+        let mut report = ClippingReport::default();
+        let mut overall_peak = 0.0f64;
+        for channel in channels {
+            let channel = channel.as_ref();
+            let peak = channel.iter().fold(0.0f64, |max, x| max.max(x.abs()));
+            overall_peak = overall_peak.max(peak);
+            if peak == 0.0 {
+                continue;
+            }
+            let threshold = peak * (1.0 - PEAK_TOLERANCE);
+            let mut close_run = |run: usize| {
+                if run >= MIN_CLIPPED_RUN {
+                    report.clipped_samples += run;
+                    report.runs += 1;
+                    report.longest_run = report.longest_run.max(run);
+                }
+            };
+            let mut run = 0;
+            let mut sign = 0.0;
+            for &x in channel {
+                if x.abs() >= threshold && (run == 0 || x.signum() == sign) {
+                    run += 1;
+                    sign = x.signum();
+                } else {
+                    close_run(run);
+                    run = if x.abs() >= threshold { 1 } else { 0 };
+                    sign = x.signum();
+                }
+            }
+            close_run(run);
+        }
+        report.peak_dbfs = 20.0 * overall_peak.log10();
+        report
+    }
+}
+
+/// How an output fits into full scale before it is written, from [`Headroom::measure`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Headroom {
+    /// Largest magnitude of the signal as computed (dBFS; above 0 means over full scale)
+    pub peak_dbfs: f64,
+    /// Samples beyond full scale as computed
+    pub over_full_scale: usize,
+    /// Gain the WAV writer applies (`--normalize`)
+    pub write_gain: f64,
+    /// Largest magnitude after that gain (dBFS); the headroom left in the file is its negative
+    pub written_peak_dbfs: f64,
+    /// Samples the writer will clip to full scale
+    pub clipped_on_write: usize,
+}
+
+impl Headroom {
+    /// Measure `channels` against full scale, before and after the normalization gain that
+    /// [`write_channels`](crate::wav_io::write_channels) applies with `options`
+    pub fn measure(channels: &[Vec<f64>], options: &WavWriteOptions) -> Self {
+        let samples = || channels.iter().flatten().map(|x| x.abs());
+        let peak = samples().fold(0.0f64, f64::max);
+        let write_gain = options.normalize.gain(channels);
+        Headroom {
+            peak_dbfs: 20.0 * peak.log10(),
+            over_full_scale: samples().filter(|&x| x > 1.0).count(),
+            write_gain,
+            written_peak_dbfs: 20.0 * (peak * write_gain).log10(),
+            clipped_on_write: samples().filter(|&x| x * write_gain > 1.0).count(),
+        }
+    }
+
+    /// Headroom left in the written file (dB); negative when samples are clipped
+    pub fn headroom_db(&self) -> f64 {
+        -self.written_peak_dbfs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wav_io::Normalize;
+
+    #[test]
+    fn test_clipping_runs_and_headroom() {
+        // Two flat tops of 4 and 5 samples, one lone peak sample that is not clipping
+        let signal = vec![0.1, 0.5, 0.8, 0.8, 0.8, 0.8, 0.3, -0.8, 0.0, -0.8, -0.8, -0.8, -0.8, -0.8, 0.2];
+        let report = ClippingReport::detect(&[&signal]);
+        assert_eq!((report.runs, report.clipped_samples, report.longest_run), (2, 9, 5));
+        assert!((report.peak_dbfs - 20.0 * 0.8f64.log10()).abs() < 1e-12);
+
+        // A run that changes sign is two short runs
+        assert_eq!(ClippingReport::detect(&[vec![0.8, 0.8, -0.8, -0.8, 0.0]]).runs, 0);
+        assert_eq!(ClippingReport::detect(&[vec![0.0; 10]]).runs, 0);
+
+        // Peak normalization rescales an output over full scale; no normalization clips it
+        let loud = vec![vec![0.5, 1.6, -2.0, 0.9]];
+        let none = WavWriteOptions {
+            normalize: Normalize::None,
+            ..Default::default()
+        };
+        let headroom = Headroom::measure(&loud, &none);
+        assert_eq!((headroom.over_full_scale, headroom.clipped_on_write), (2, 2));
+        assert!((headroom.peak_dbfs - 6.0206).abs() < 1e-3 && headroom.headroom_db() < 0.0);
+        let peak = Headroom::measure(&loud, &WavWriteOptions::default());
+        assert_eq!((peak.over_full_scale, peak.clipped_on_write), (2, 0));
+        assert!((peak.headroom_db() - 0.4455).abs() < 1e-3);
+    }
+}
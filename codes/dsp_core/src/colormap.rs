@@ -0,0 +1,155 @@
+use crate::plot_style::Rgb;
+use std::str::FromStr;
+
+/// Colour scale of 2D intensity plots such as spectrograms, from low (0) to high (1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    /// Black → blue → red → yellow
+    #[default]
+    Heat,
+    /// Perceptually uniform dark blue → green → yellow; stays readable in greyscale print
+    Viridis,
+    /// Perceptually uniform black → purple → orange → pale yellow
+    Magma,
+    Gray,
+}
+
+const HEAT: [Rgb; 4] = [Rgb(0, 0, 0), Rgb(30, 40, 160), Rgb(220, 40, 40), Rgb(255, 230, 80)];
+
+// Sampled at ninths of the matplotlib maps of the same name
+const VIRIDIS: [Rgb; 9] = [
+    Rgb(68, 1, 84),
+    Rgb(71, 45, 123),
+    Rgb(59, 82, 139),
+    Rgb(44, 114, 142),
+    Rgb(33, 145, 140),
+    Rgb(40, 174, 128),
+    Rgb(94, 201, 98),
+    Rgb(173, 220, 48),
+    Rgb(253, 231, 37),
+];
+
+const MAGMA: [Rgb; 9] = [
+    Rgb(0, 0, 4),
+    Rgb(28, 16, 68),
+    Rgb(79, 18, 123),
+    Rgb(129, 37, 129),
+    Rgb(181, 54, 122),
+    Rgb(229, 80, 100),
+    Rgb(251, 135, 97),
+    Rgb(254, 194, 135),
+    Rgb(252, 253, 191),
+];
+
+const GRAY: [Rgb; 2] = [Rgb(0, 0, 0), Rgb(255, 255, 255)];
+
+impl Colormap {
+    fn stops(self) -> &'static [Rgb] {
+        match self {
+            Colormap::Heat => &HEAT,
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Gray => &GRAY,
+        }
+    }
+
+    /// Colour for `level`, clamped to 0..=1 and interpolated linearly between the stops
+    pub fn color(self, level: f64) -> Rgb {
+        let stops = self.stops();
+        let position = level.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let i = (position as usize).min(stops.len() - 2);
+        let t = position - i as f64;
+        let (Rgb(r0, g0, b0), Rgb(r1, g1, b1)) = (stops[i], stops[i + 1]);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "heat" => Ok(Colormap::Heat),
+            "viridis" => Ok(Colormap::Viridis),
+            "magma" => Ok(Colormap::Magma),
+            "gray" | "grey" => Ok(Colormap::Gray),
+            other => Err(format!("Unknown colormap '{}' (expected heat, viridis, magma or gray)", other)),
+        }
+    }
+}
+
+/// Levels, in dB relative to the loudest value of a plot, that span a colour scale
+///
+/// Anything below `min` gets the lowest colour and anything above `max` the highest, so
+/// lowering `max` below 0 dB lets a strong carrier saturate while weak baseband detail
+/// spreads over the rest of the scale.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::colormap::DbRange;
+///
+/// let range: DbRange = "-70:-10".parse().unwrap();
+/// assert_eq!(range.level(-40.0), 0.5);
+/// assert_eq!(range.level(0.0), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DbRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl DbRange {
+    pub fn new(min: f64, max: f64) -> Self {
+        DbRange { min, max }
+    }
+
+    /// Position of `db` in the range, clipped to 0..=1
+    pub fn level(&self, db: f64) -> f64 {
+        ((db - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    /// Level in dB at `level` (0..=1) of the range, for labelling a colour bar or axis
+    pub fn db_at(&self, level: f64) -> f64 {
+        self.min + level * (self.max - self.min)
+    }
+}
+
+impl FromStr for DbRange {
+    type Err = String;
+
+    /// `<min>:<max>` in dB, e.g. `-70:-10`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid dB range '{}' (expected <min>:<max>, e.g. -70:-10)", s);
+        let (min, max) = s.split_once(':').ok_or_else(invalid)?;
+        let parse = |v: &str| v.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+        match (parse(min), parse(max)) {
+            (Some(min), Some(max)) if min < max => Ok(DbRange::new(min, max)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colormap_and_db_range() {
+        assert_eq!(Colormap::Viridis.color(0.0), VIRIDIS[0]);
+        assert_eq!(Colormap::Magma.color(2.0), MAGMA[8]);
+        assert_eq!(Colormap::Gray.color(0.5), Rgb(128, 128, 128));
+        assert_eq!(Colormap::Heat.color(-1.0), Rgb(0, 0, 0));
+        assert_eq!("Grey".parse::<Colormap>(), Ok(Colormap::Gray));
+        assert!("jet".parse::<Colormap>().is_err());
+
+        let range: DbRange = "-70:-10".parse().unwrap();
+        assert_eq!(range.level(-40.0), 0.5);
+        // The carrier peak at 0 dB saturates instead of stretching the scale
+        assert_eq!((range.level(0.0), range.level(-90.0)), (1.0, 0.0));
+        assert_eq!(range.db_at(0.25), -55.0);
+        assert!("-10:-70".parse::<DbRange>().is_err());
+        assert!("-70".parse::<DbRange>().is_err());
+    }
+}
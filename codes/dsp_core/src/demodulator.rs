@@ -0,0 +1,333 @@
+use crate::butterworth::ButterworthFilter;
+use crate::error::{DspError, Result};
+use crate::filter_chain;
+use crate::iir::{self, StreamingFilter};
+use std::f64::consts::PI;
+
+/// Butterworth order of the filters designed per file in batch mode, where Q2's filters,
+/// designed for one recording, do not apply
+pub const BATCH_FILTER_ORDER: usize = 8;
+
+/// Read `--guard-band <Hz>` from command-line arguments: how far below f_d the high-pass
+/// cutoff sits (default 0, the cutoff at f_d)
+///
+/// A Butterworth high-pass is already 3 dB down at its cutoff, so with the cutoff at f_d it
+/// also attenuates the lowest message frequencies, just above the carrier. Moving the cutoff
+/// down keeps them, at the price of letting the edge of the lower sideband through as well.
+pub fn guard_band_from_args(args: &[String]) -> Result<f64> {
+    let mut guard_band = 0.0;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--guard-band" {
+            let value = iter.next().ok_or_else(|| DspError::missing_value("--guard-band"))?;
+            guard_band = value
+                .parse()
+                .ok()
+                .filter(|&hz: &f64| hz >= 0.0 && hz.is_finite())
+                .ok_or_else(|| DspError::invalid_value("--guard-band", value))?;
+        }
+    }
+    Ok(guard_band)
+}
+
+/// Multiply signal with a local oscillator 2 cos(2*pi*f_d*t + phase)
+///
+/// A DSB-SC input m(t) cos(2*pi*f_d*t) comes out as m(t) cos(phase) after the low-pass,
+/// so a phase error scales the recovered amplitude by cos(phase).
+pub fn mix_with_carrier(signal: &[f64], f_d: f64, phase: f64, f_s: f64) -> Vec<f64> {
+    signal
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let t = i as f64 / f_s;
+            let carrier = (2.0 * PI * f_d * t + phase).cos();
+            // Multiply by 2 to compensate for the 1/2 factor from cos²(x) = (1 + cos(2x))/2
+            x * carrier * 2.0
+        })
+        .collect()
+}
+
+/// `signal` moved `samples` earlier, keeping its length: the first samples are dropped
+/// and zeros fill the end
+///
+/// Advancing a filtered signal by its group delay lines it up with the unfiltered one.
+pub fn advance(signal: &[f64], samples: usize) -> Vec<f64> {
+    let samples = samples.min(signal.len());
+    signal[samples..].iter().copied().chain(std::iter::repeat_n(0.0, samples)).collect()
+}
+
+/// End-to-end gain of a [`TimeDomainDemodulator`] for one probe tone, from
+/// [`TimeDomainDemodulator::probe`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeTone {
+    /// Frequency of the unit-amplitude input tone (Hz)
+    pub frequency: f64,
+    /// Output RMS over input RMS, measured once the filters have settled (dB)
+    pub measured_db: f64,
+    /// The same ratio predicted from the high-pass and low-pass responses (dB)
+    pub predicted_db: f64,
+}
+
+/// Q3's coherent demodulation chain: IIR high-pass, local-oscillator mixing, IIR low-pass
+///
+/// `hp` and `lp` are `(b, a)` transfer-function coefficients, usually Q2's filters. Holding
+/// the chain as a value lets other stages, such as Q4's comparison, recompute Q3's output in
+/// process instead of reading it back from `Q3_demodulated.wav`.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::demodulator::TimeDomainDemodulator;
+/// use std::f64::consts::PI;
+///
+/// // A 500 Hz tone shifted up by f_d = 3 kHz comes back at 500 Hz with its amplitude
+/// let (f_d, f_b, f_s) = (3000.0, 1000.0, 22050.0);
+/// let x: Vec<f64> = (0..22050).map(|n| (2.0 * PI * (f_d + 500.0) * n as f64 / f_s).cos()).collect();
+/// let output = TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s).demodulate(&x);
+/// let peak = output[11025..].iter().fold(0.0f64, |m, y| m.max(y.abs()));
+/// assert!((peak - 1.0).abs() < 0.05, "peak {}", peak);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeDomainDemodulator {
+    /// High-pass `(b, a)` that removes the low-frequency residue before mixing
+    pub hp: (Vec<f64>, Vec<f64>),
+    /// Low-pass `(b, a)` that keeps the baseband after mixing
+    pub lp: (Vec<f64>, Vec<f64>),
+    /// Local oscillator frequency (Hz)
+    pub f_d: f64,
+    /// Local oscillator phase (rad)
+    pub phase: f64,
+    /// Sample rate (Hz)
+    pub f_s: f64,
+}
+
+impl TimeDomainDemodulator {
+    /// Chain with a zero-phase local oscillator
+    pub fn new(f_d: f64, f_s: f64, hp: (Vec<f64>, Vec<f64>), lp: (Vec<f64>, Vec<f64>)) -> Self {
+        TimeDomainDemodulator { hp, lp, f_d, phase: 0.0, f_s }
+    }
+
+    /// Chain with Butterworth filters of `order`: high-pass at f_d, low-pass at f_b
+    ///
+    /// Q3 designs its batch filters this way, with [`BATCH_FILTER_ORDER`].
+    pub fn butterworth(order: usize, f_d: f64, f_b: f64, f_s: f64) -> Self {
+        Self::butterworth_guarded(order, f_d, f_b, 0.0, f_s)
+    }
+
+    /// [`butterworth`](Self::butterworth) with the high-pass at f_d - `guard_band` (see
+    /// [`guard_band_from_args`]); the local oscillator stays at f_d
+    pub fn butterworth_guarded(order: usize, f_d: f64, f_b: f64, guard_band: f64, f_s: f64) -> Self {
+        let highpass = ButterworthFilter::highpass(order, f_d - guard_band, f_s);
+        let lowpass = ButterworthFilter::lowpass(order, f_b, f_s);
+        Self::new(f_d, f_s, (highpass.b, highpass.a), (lowpass.b, lowpass.a))
+    }
+
+    pub fn highpass(&self, signal: &[f64]) -> Vec<f64> {
+        iir::apply_filter(signal, &self.hp.0, &self.hp.1)
+    }
+
+    /// Mix with the fixed local oscillator
+    pub fn mix(&self, signal: &[f64]) -> Vec<f64> {
+        mix_with_carrier(signal, self.f_d, self.phase, self.f_s)
+    }
+
+    pub fn lowpass(&self, signal: &[f64]) -> Vec<f64> {
+        iir::apply_filter(signal, &self.lp.0, &self.lp.1)
+    }
+
+    /// High-pass, mix and low-pass `signal`
+    pub fn demodulate(&self, signal: &[f64]) -> Vec<f64> {
+        self.lowpass(&self.mix(&self.highpass(signal)))
+    }
+
+    /// Run a unit cosine at `frequency` (Hz), `num_samples` long, through the chain and
+    /// measure the output level over the second half
+    ///
+    /// The high-pass gain H_hp(f) is applied to both mixing products, at |f - f_d| and
+    /// f + f_d, which the low-pass then weights; a probe exactly at f_d mixes down to DC,
+    /// where the level depends on the high-pass phase and the local-oscillator phase. The
+    /// prediction adds the powers of the two products, so a measured level that departs
+    /// from it points at a transient or a numerical problem in the filters.
+    pub fn probe(&self, frequency: f64, num_samples: usize) -> ProbeTone {
+        let tone: Vec<f64> =
+            (0..num_samples).map(|n| (2.0 * PI * frequency * n as f64 / self.f_s).cos()).collect();
+        let output = self.demodulate(&tone);
+        let settled = &output[num_samples / 2..];
+        let rms = (settled.iter().map(|y| y * y).sum::<f64>() / settled.len().max(1) as f64).sqrt();
+
+        let h_hp = filter_chain::response(&self.hp.0, &self.hp.1, frequency, self.f_s);
+        let h_lp = |f: f64| filter_chain::response(&self.lp.0, &self.lp.1, f, self.f_s).norm();
+        let difference = if frequency == self.f_d {
+            // cos(θ - φ) of DC, θ the high-pass phase and φ the local-oscillator phase
+            (h_hp.norm() * (h_hp.arg() - self.phase).cos() * h_lp(0.0)).powi(2)
+        } else {
+            (h_hp.norm() * h_lp(frequency - self.f_d)).powi(2) / 2.0
+        };
+        let sum = (h_hp.norm() * h_lp(frequency + self.f_d)).powi(2) / 2.0;
+
+        // Input RMS of a unit cosine is 1/√2
+        let db = |rms: f64| 20.0 * (rms * std::f64::consts::SQRT_2).max(1e-12).log10();
+        ProbeTone { frequency, measured_db: db(rms), predicted_db: db((difference + sum).sqrt()) }
+    }
+
+    /// Average group delay (samples) of the chain over the baseband 0 < f <= `f_b`
+    ///
+    /// A baseband component at f passes the high-pass at f_d + f and the low-pass at f, so
+    /// it is delayed by the sum of the two filters' group delays there; the mean over the
+    /// baseband is the one delay [`advance`] removes. Frequencies whose upper sideband lies
+    /// beyond Nyquist are left out.
+    pub fn passband_group_delay(&self, f_b: f64) -> f64 {
+        const POINTS: usize = 256;
+        let delays: Vec<f64> = (1..=POINTS)
+            .map(|k| f_b * k as f64 / POINTS as f64)
+            .filter(|&f| self.f_d + f < self.f_s / 2.0)
+            .map(|f| {
+                filter_chain::group_delay(&self.hp.0, &self.hp.1, self.f_d + f, self.f_s)
+                    + filter_chain::group_delay(&self.lp.0, &self.lp.1, f, self.f_s)
+            })
+            .collect();
+        delays.iter().sum::<f64>() / delays.len().max(1) as f64
+    }
+
+    /// The same chain for a signal that arrives block by block
+    pub fn streaming(&self) -> StreamingDemodulator {
+        StreamingDemodulator {
+            highpass: StreamingFilter::new(&self.hp.0, &self.hp.1),
+            lowpass: StreamingFilter::new(&self.lp.0, &self.lp.1),
+            f_d: self.f_d,
+            phase: self.phase,
+            f_s: self.f_s,
+            position: 0,
+        }
+    }
+}
+
+/// [`TimeDomainDemodulator`] that keeps the filter states and the local-oscillator phase
+/// between blocks, for live input
+///
+/// Demodulating a signal block by block gives the same output as
+/// [`TimeDomainDemodulator::demodulate`] on the whole signal, whatever the block sizes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingDemodulator {
+    highpass: StreamingFilter,
+    lowpass: StreamingFilter,
+    f_d: f64,
+    phase: f64,
+    f_s: f64,
+    /// Samples demodulated so far: the local oscillator's time origin is the first sample
+    position: u64,
+}
+
+impl StreamingDemodulator {
+    /// Demodulate the next block of the signal
+    pub fn process(&mut self, block: &[f64]) -> Vec<f64> {
+        let mut mixed = self.highpass.process(block);
+        for (i, x) in mixed.iter_mut().enumerate() {
+            let t = (self.position + i as u64) as f64 / self.f_s;
+            *x *= 2.0 * (2.0 * PI * self.f_d * t + self.phase).cos();
+        }
+        self.position += block.len() as u64;
+        self.lowpass.process(&mixed)
+    }
+
+    /// Samples demodulated so far
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_band_lowers_the_highpass_only() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(guard_band_from_args(&args("--snr 10")).unwrap(), 0.0);
+        assert_eq!(guard_band_from_args(&args("--guard-band 200")).unwrap(), 200.0);
+        assert!(guard_band_from_args(&args("--guard-band -5")).is_err());
+
+        let (f_d, f_b, f_s) = (3000.0, 4000.0, 22050.0);
+        assert_eq!(
+            TimeDomainDemodulator::butterworth_guarded(8, f_d, f_b, 0.0, f_s),
+            TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s)
+        );
+        let guarded = TimeDomainDemodulator::butterworth_guarded(8, f_d, f_b, 200.0, f_s);
+        let expected = ButterworthFilter::highpass(8, 2800.0, f_s);
+        assert_eq!(guarded.hp, (expected.b, expected.a));
+        assert_eq!((guarded.f_d, guarded.lp), (f_d, TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s).lp));
+    }
+
+    #[test]
+    fn test_probe_tones_match_the_filter_responses() {
+        let (f_d, f_b, f_s) = (3000.0, 4000.0, 22050.0);
+        let mut chain = TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s);
+        chain.phase = 0.4;
+        for (frequency, expected_db) in [
+            // Removed by the high-pass
+            (f_d - 2000.0, None),
+            // High-pass cutoff, mixed down to DC
+            (f_d, None),
+            // Mid-band: both filters pass, the sum product at 8 kHz is stopped
+            (f_d + f_b / 2.0, Some(0.0)),
+            // Low-pass cutoff
+            (f_d + f_b, Some(-3.01)),
+            // 2f_d mixes to f_d, inside the baseband: an image passes at full level
+            (2.0 * f_d, Some(0.0)),
+        ] {
+            let probe = chain.probe(frequency, 22050);
+            assert!((probe.measured_db - probe.predicted_db).abs() < 0.1, "{:?}", probe);
+            if let Some(expected) = expected_db {
+                assert!((probe.measured_db - expected).abs() < 0.1, "{:?}", probe);
+            }
+        }
+        assert!(chain.probe(f_d - 2000.0, 22050).measured_db < -60.0);
+    }
+
+    #[test]
+    fn test_passband_group_delay_lines_up_the_output() {
+        // A 400 Hz message on the carrier; the chain output lags it by about the delay
+        let (f_d, f_b, f_s) = (3000.0, 1000.0, 22050.0);
+        let message = |n: usize| (2.0 * PI * 400.0 * n as f64 / f_s).cos();
+        let x: Vec<f64> = (0..8000).map(|n| message(n) * (2.0 * PI * f_d * n as f64 / f_s).cos()).collect();
+        let chain = TimeDomainDemodulator::butterworth(8, f_d, f_b, f_s);
+        let delay = chain.passband_group_delay(f_b);
+        assert!(delay > 5.0 && delay < 100.0, "{}", delay);
+
+        let output = advance(&chain.demodulate(&x), delay.round() as usize);
+        assert_eq!(output.len(), x.len());
+        let correlation = |y: &[f64]| -> f64 {
+            let m: Vec<f64> = (2000..6000).map(message).collect();
+            let dot: f64 = y[2000..6000].iter().zip(&m).map(|(a, b)| a * b).sum();
+            dot / (y[2000..6000].iter().map(|v| v * v).sum::<f64>() * m.iter().map(|v| v * v).sum::<f64>()).sqrt()
+        };
+        assert!(correlation(&output) > 0.95, "{}", correlation(&output));
+        assert!(correlation(&output) > correlation(&chain.demodulate(&x)));
+
+        assert_eq!(advance(&[1.0, 2.0, 3.0], 1), [2.0, 3.0, 0.0]);
+        assert_eq!(advance(&[1.0, 2.0], 5), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_streaming_matches_whole_signal() {
+        let (f_d, f_b, f_s) = (3000.0, 1000.0, 22050.0);
+        let x: Vec<f64> = (0..5000)
+            .map(|n| {
+                let t = n as f64 / f_s;
+                (2.0 * PI * 400.0 * t).cos() * (2.0 * PI * f_d * t).cos()
+            })
+            .collect();
+        let mut chain = TimeDomainDemodulator::butterworth(4, f_d, f_b, f_s);
+        chain.phase = 0.3;
+        let mut streaming = chain.streaming();
+        let mut streamed = Vec::new();
+        for block in x.chunks(333) {
+            streamed.extend(streaming.process(block));
+        }
+        assert_eq!(streaming.position(), 5000);
+        let whole = chain.demodulate(&x);
+        for (s, w) in streamed.iter().zip(&whole) {
+            assert!((s - w).abs() < 1e-12, "{} != {}", s, w);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::error::{DspError, Result};
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+
+/// How [`Denoiser`] turns the noise profile into a gain per bin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DenoiseMethod {
+    /// G = sqrt(max(1 - α |N|² / |X|², β))
+    SpectralSubtraction,
+    /// G = ξ / (1 + ξ), with the a priori SNR ξ from decision-directed estimation:
+    /// ξ = a |Ŝ_prev|² / |N|² + (1 - a) max(|X|² / |N|² - 1, 0), where Ŝ_prev is the
+    /// previous frame's output. The smoothing `a` keeps ξ from following every random
+    /// fluctuation of the noise, which is where musical noise comes from.
+    Wiener { smoothing: f64 },
+}
+
+/// Smoothing of the decision-directed estimate (Ephraim and Malah)
+pub const DEFAULT_WIENER_SMOOTHING: f64 = 0.98;
+
+impl DenoiseMethod {
+    /// Parse `spectral` or `wiener`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spectral" => Some(DenoiseMethod::SpectralSubtraction),
+            "wiener" => Some(DenoiseMethod::Wiener {
+                smoothing: DEFAULT_WIENER_SMOOTHING,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DenoiseMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DenoiseMethod::SpectralSubtraction => write!(f, "spectral subtraction"),
+            DenoiseMethod::Wiener { smoothing } => write!(f, "Wiener (decision-directed, a = {})", smoothing),
+        }
+    }
+}
+
+/// STFT noise reduction for demodulated audio: spectral subtraction or a Wiener filter
+///
+/// The signal is cut into frames of `frame_len` samples with 50 % overlap, each weighted by
+/// a square-root Hann window before the FFT and again after the inverse FFT, so that with
+/// every gain at 1 the overlap-add gives back the input exactly. The noise power of each
+/// bin is the average over the frames of a segment that holds only noise: one given on the
+/// command line, or else the quietest stretch of the recording. Each bin is then scaled by a
+/// gain from [`DenoiseMethod`]. For spectral subtraction α over-subtracts to keep residual
+/// noise peaks ("musical noise") down; for both methods the floor β (G² ≥ β) stops bins
+/// from being switched off completely. The phase is left as it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    pub method: DenoiseMethod,
+    /// Frame length in samples (even)
+    pub frame_len: usize,
+    /// Over-subtraction factor α of spectral subtraction
+    pub over_subtraction: f64,
+    /// Spectral floor β, as a power ratio
+    pub floor: f64,
+    /// Noise-only segment `(start, end)` in seconds; `None` searches for the quietest one
+    pub noise_segment: Option<(f64, f64)>,
+}
+
+// Defaults used by `--denoise`
+const DEFAULT_FRAME_LEN: usize = 1024;
+const DEFAULT_OVER_SUBTRACTION: f64 = 2.0;
+const DEFAULT_FLOOR_DB: f64 = -20.0;
+/// Length of the noise segment the automatic search looks for (s)
+const NOISE_SEARCH_SECONDS: f64 = 0.25;
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser {
+            method: DenoiseMethod::SpectralSubtraction,
+            frame_len: DEFAULT_FRAME_LEN,
+            over_subtraction: DEFAULT_OVER_SUBTRACTION,
+            floor: 10f64.powf(DEFAULT_FLOOR_DB / 10.0),
+            noise_segment: None,
+        }
+    }
+}
+
+/// What [`Denoiser::apply`] did, with the average spectra before and after
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenoiseReport {
+    /// Noise segment the profile came from (s)
+    pub noise_segment: (f64, f64),
+    /// Mean power of the noise segment relative to full scale (dB)
+    pub noise_level_db: f64,
+    /// Estimated SNR of the input and of the output (dB): the mean power of the whole
+    /// signal minus that of the noise segment, over the noise segment's power
+    pub snr_before_db: f64,
+    pub snr_after_db: f64,
+    /// Drop of the noise segment's power (dB)
+    pub noise_reduction_db: f64,
+    /// Average amplitude spectrum `(Hz, magnitude)` over all frames, before and after
+    pub spectrum_before: Vec<(f64, f64)>,
+    pub spectrum_after: Vec<(f64, f64)>,
+    /// Amplitude spectrum of the noise profile
+    pub noise_spectrum: Vec<(f64, f64)>,
+}
+
+impl Denoiser {
+    /// Read `--denoise` (defaults, automatic noise segment) and the individual
+    /// `--denoise-method <spectral|wiener>`, `--noise-segment <start>:<end>` (s) and
+    /// `--over-subtraction <α>` options from command-line arguments
+    ///
+    /// Returns `None` when none of them is given.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut denoiser = Denoiser::default();
+        let mut enabled = false;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--denoise" => enabled = true,
+                "--denoise-method" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--denoise-method"))?;
+                    denoiser.method =
+                        DenoiseMethod::parse(value).ok_or_else(|| DspError::invalid_value("--denoise-method", value))?;
+                    enabled = true;
+                }
+                "--noise-segment" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--noise-segment"))?;
+                    let invalid = || DspError::invalid_value("--noise-segment", value);
+                    let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+                    let start: f64 = start.trim().parse().map_err(|_| invalid())?;
+                    let end: f64 = end.trim().parse().map_err(|_| invalid())?;
+                    if !(start >= 0.0 && end > start) {
+                        return Err(invalid());
+                    }
+                    denoiser.noise_segment = Some((start, end));
+                    enabled = true;
+                }
+                "--over-subtraction" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--over-subtraction"))?;
+                    denoiser.over_subtraction = value
+                        .parse()
+                        .ok()
+                        .filter(|&v: &f64| v > 0.0)
+                        .ok_or_else(|| DspError::invalid_value("--over-subtraction", value))?;
+                    enabled = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(enabled.then_some(denoiser))
+    }
+
+    /// Denoise `signal`, returning the output (same length) and a report
+    ///
+    /// Fails when the signal is shorter than two frames or the noise segment does not hold
+    /// a whole frame of the signal.
+    pub fn apply(&self, signal: &[f64], sample_rate: f64) -> Result<(Vec<f64>, DenoiseReport)> {
+        let n = self.frame_len.max(4) & !1;
+        let hop = n / 2;
+        if signal.len() < n {
+            return Err(DspError::Data(format!(
+                "{} samples are too few to denoise with {}-sample frames",
+                signal.len(),
+                n
+            )));
+        }
+
+        // Frame i covers padded[i hop .. i hop + n], i.e. signal[(i - 1) hop .. (i + 1) hop]
+        let num_frames = signal.len().div_ceil(hop) + 1;
+        let mut padded = vec![0.0; (num_frames + 1) * hop];
+        padded[hop..hop + signal.len()].copy_from_slice(signal);
+        let window: Vec<f64> = (0..n).map(|i| (PI * i as f64 / n as f64).sin()).collect();
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(n);
+        let ifft = planner.plan_fft_inverse(n);
+        let spectra: Vec<Vec<Complex<f64>>> = (0..num_frames)
+            .map(|i| {
+                let mut buffer: Vec<Complex<f64>> =
+                    (0..n).map(|k| Complex::new(padded[i * hop + k] * window[k], 0.0)).collect();
+                fft.process(&mut buffer);
+                buffer
+            })
+            .collect();
+
+        // Frames lying wholly inside the signal; the first and last include zero padding
+        let interior = 1..signal.len() / hop;
+        let (first, last) = match self.noise_segment {
+            Some((start, end)) => {
+                let first = ((start * sample_rate / hop as f64).ceil() as usize + 1).max(interior.start);
+                let last = ((end * sample_rate / hop as f64).floor() as usize).min(interior.end);
+                if first >= last {
+                    return Err(DspError::Data(format!(
+                        "noise segment {}-{} s does not hold a whole {}-sample frame of the {:.3} s signal",
+                        start,
+                        end,
+                        n,
+                        signal.len() as f64 / sample_rate
+                    )));
+                }
+                (first, last)
+            }
+            None => {
+                let energy: Vec<f64> = spectra.iter().map(|s| s.iter().map(|x| x.norm_sqr()).sum()).collect();
+                let count = ((NOISE_SEARCH_SECONDS * sample_rate / hop as f64) as usize)
+                    .saturating_sub(1)
+                    .clamp(1, interior.len());
+                let first = (interior.start..=interior.end - count)
+                    .min_by(|&a, &b| {
+                        let sum = |i: usize| energy[i..i + count].iter().sum::<f64>();
+                        sum(a).total_cmp(&sum(b))
+                    })
+                    .unwrap_or(interior.start);
+                (first, first + count)
+            }
+        };
+        let noise: Vec<f64> = (0..n)
+            .map(|k| spectra[first..last].iter().map(|s| s[k].norm_sqr()).sum::<f64>() / (last - first) as f64)
+            .collect();
+
+        let mut output = vec![0.0; padded.len()];
+        let mut magnitude_before = vec![0.0; hop + 1];
+        let mut magnitude_after = vec![0.0; hop + 1];
+        // |Ŝ|² of the previous frame for the decision-directed estimate; none before the first
+        let mut previous: Option<Vec<f64>> = None;
+        for (i, spectrum) in spectra.iter().enumerate() {
+            let mut buffer: Vec<Complex<f64>> = spectrum
+                .iter()
+                .enumerate()
+                .map(|(k, &x)| {
+                    let power = x.norm_sqr();
+                    if power == 0.0 {
+                        return x;
+                    }
+                    let gain_squared = match self.method {
+                        DenoiseMethod::SpectralSubtraction => 1.0 - self.over_subtraction * noise[k] / power,
+                        DenoiseMethod::Wiener { smoothing } => {
+                            let noise = noise[k].max(f64::MIN_POSITIVE);
+                            let posterior = (power / noise - 1.0).max(0.0);
+                            let prior = match &previous {
+                                Some(previous) => smoothing * previous[k] / noise + (1.0 - smoothing) * posterior,
+                                None => posterior,
+                            };
+                            (prior / (1.0 + prior)).powi(2)
+                        }
+                    };
+                    x * gain_squared.max(self.floor).sqrt()
+                })
+                .collect();
+            previous = Some(buffer.iter().map(|x| x.norm_sqr()).collect());
+            for k in 0..=hop {
+                magnitude_before[k] += spectrum[k].norm();
+                magnitude_after[k] += buffer[k].norm();
+            }
+            ifft.process(&mut buffer);
+            for k in 0..n {
+                output[i * hop + k] += buffer[k].re * window[k] / n as f64;
+            }
+        }
+        let output = output[hop..hop + signal.len()].to_vec();
+
+        // One-sided amplitude: a full-scale sine reads 1 wherever it lies
+        let scale = 2.0 / window.iter().sum::<f64>();
+        let spectrum = |values: &[f64], divisor: f64| -> Vec<(f64, f64)> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(k, v)| (k as f64 * sample_rate / n as f64, v * scale / divisor))
+                .collect()
+        };
+        let noise_amplitude: Vec<f64> = noise[..=hop].iter().map(|p| p.sqrt()).collect();
+
+        // Sample range of the noise frames, for the SNR estimates
+        let noise_range = (first - 1) * hop..((last - 1) * hop + hop).min(signal.len());
+        let mean_power = |x: &[f64]| x.iter().map(|v| v * v).sum::<f64>() / x.len().max(1) as f64;
+        let snr_db = |x: &[f64]| {
+            let noise = mean_power(&x[noise_range.clone()]).max(1e-30);
+            10.0 * ((mean_power(x) - noise).max(1e-30) / noise).log10()
+        };
+        let noise_before = mean_power(&signal[noise_range.clone()]).max(1e-30);
+        let noise_after = mean_power(&output[noise_range.clone()]).max(1e-30);
+        let report = DenoiseReport {
+            noise_segment: (noise_range.start as f64 / sample_rate, noise_range.end as f64 / sample_rate),
+            noise_level_db: 10.0 * noise_before.log10(),
+            snr_before_db: snr_db(signal),
+            snr_after_db: snr_db(&output),
+            noise_reduction_db: 10.0 * (noise_before / noise_after).log10(),
+            spectrum_before: spectrum(&magnitude_before, num_frames as f64),
+            spectrum_after: spectrum(&magnitude_after, num_frames as f64),
+            noise_spectrum: spectrum(&noise_amplitude, 1.0),
+        };
+        Ok((output, report))
+    }
+}
+
+impl std::fmt::Display for Denoiser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, {}-sample frames, ",
+            self.method,
+            self.frame_len
+        )?;
+        if self.method == DenoiseMethod::SpectralSubtraction {
+            write!(f, "over-subtraction {}, ", self.over_subtraction)?;
+        }
+        write!(f, "floor {:.0} dB, noise from ", 10.0 * self.floor.log10())?;
+        match self.noise_segment {
+            Some((start, end)) => write!(f, "{}-{} s", start, end),
+            None => write!(f, "the quietest {} s", NOISE_SEARCH_SECONDS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise;
+
+    #[test]
+    fn test_unity_gain_reconstructs_the_input() {
+        let signal: Vec<f64> = (0..5000).map(|i| (i as f64 * 0.01).sin() + 0.1 * (i as f64 * 0.7).cos()).collect();
+        let denoiser = Denoiser {
+            method: DenoiseMethod::SpectralSubtraction,
+            frame_len: 256,
+            over_subtraction: 0.0,
+            floor: 1.0,
+            noise_segment: Some((0.0, 0.1)),
+        };
+        let (output, _) = denoiser.apply(&signal, 8000.0).unwrap();
+        assert_eq!(output.len(), signal.len());
+        assert!(signal.iter().zip(&output).all(|(x, y)| (x - y).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_noise_is_reduced_and_the_tone_kept() {
+        // Half a second of noise alone, then a 440 Hz tone in the same noise
+        let fs = 8000.0;
+        let hiss = noise::white(16000, 7);
+        let signal: Vec<f64> = hiss
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                let tone = if i >= 4000 { 0.5 * (2.0 * PI * 440.0 * i as f64 / fs).sin() } else { 0.0 };
+                tone + 0.02 * h
+            })
+            .collect();
+
+        // The automatic search finds the noise-only start; both methods keep the tone at
+        // its level
+        let power = |x: &[f64]| x.iter().map(|v| v * v).sum::<f64>() / x.len() as f64;
+        for method in [DenoiseMethod::SpectralSubtraction, DenoiseMethod::Wiener { smoothing: DEFAULT_WIENER_SMOOTHING }] {
+            let denoiser = Denoiser {
+                method,
+                ..Denoiser::default()
+            };
+            let (output, report) = denoiser.apply(&signal, fs).unwrap();
+            assert!(report.noise_segment.1 <= 0.5, "{}: {:?}", method, report.noise_segment);
+            assert!(report.noise_reduction_db > 10.0, "{}: {}", method, report.noise_reduction_db);
+            assert!(report.snr_after_db > report.snr_before_db + 10.0, "{}: {:?}", method, report);
+            let ratio = power(&output[6000..14000]) / power(&signal[6000..14000]);
+            assert!((ratio - 1.0).abs() < 0.05, "{}: {}", method, ratio);
+        }
+
+        // A segment with no whole frame in it is refused
+        let short = Denoiser {
+            noise_segment: Some((0.0, 0.05)),
+            ..Denoiser::default()
+        };
+        assert!(short.apply(&signal, fs).is_err());
+    }
+}
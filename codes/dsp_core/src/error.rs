@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Error type of every fallible `dsp_core` function, and of the Q1–Q4 programs
+///
+/// Each variant maps to a process exit code (see [`DspError::exit_code`]), so a program's
+/// `main` can print the error and exit with a status that tells scripts what went wrong.
+#[derive(Debug, Error)]
+pub enum DspError {
+    /// Bad command-line option or parameter value
+    #[error("{0}")]
+    Argument(String),
+    /// Reading or writing a file failed
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A file or signal could not be interpreted: malformed header, missing field, empty spectrum
+    #[error("{0}")]
+    Data(String),
+    /// Drawing a figure failed
+    #[error("plotting failed: {0}")]
+    Plot(String),
+    /// The build lacks a feature that was asked for (`playback`, `capture`, `pdf`)
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type Result<T, E = DspError> = std::result::Result<T, E>;
+
+impl DspError {
+    /// Attach the file name to an I/O error
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Self {
+        DspError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    /// Attach the file name to a WAV reading or writing error
+    ///
+    /// I/O failures keep their [`std::io::Error`]; a malformed file becomes [`DspError::Data`].
+    pub fn wav(path: impl AsRef<Path>, error: hound::Error) -> Self {
+        match error {
+            hound::Error::IoError(source) => DspError::io(path, source),
+            other => DspError::Data(format!("{}: {}", path.as_ref().display(), other)),
+        }
+    }
+
+    /// Prefix the message of a [`DspError::Data`] error, e.g. with the file it came from
+    pub fn context(self, prefix: impl std::fmt::Display) -> Self {
+        match self {
+            DspError::Data(message) => DspError::Data(format!("{}: {}", prefix, message)),
+            other => other,
+        }
+    }
+
+    /// `<option> requires a value`
+    pub fn missing_value(option: &str) -> Self {
+        DspError::Argument(format!("{} requires a value", option))
+    }
+
+    /// `Invalid value '<value>' for <option>`
+    pub fn invalid_value(option: &str, value: &str) -> Self {
+        DspError::Argument(format!("Invalid value '{}' for {}", value, option))
+    }
+
+    /// Wrap a plotting backend error; usable as `.map_err(DspError::plot)`
+    pub fn plot(error: impl std::fmt::Display) -> Self {
+        DspError::Plot(error.to_string())
+    }
+
+    /// Process exit status, following the BSD `sysexits.h` codes
+    ///
+    /// 64 usage error, 65 bad input data, 66 missing input file, 69 feature unavailable,
+    /// 70 internal (plotting) failure, 74 other I/O error, 1 anything else.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DspError::Argument(_) => 64,
+            DspError::Data(_) => 65,
+            DspError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound => 66,
+            DspError::Unsupported(_) => 69,
+            DspError::Plot(_) => 70,
+            DspError::Io { .. } => 74,
+            DspError::Other(_) => 1,
+        }
+    }
+
+    /// Print `error` to stderr with `prefix` and exit with its [`exit_code`](Self::exit_code)
+    pub fn exit(&self, prefix: &str) -> ! {
+        exit(self, prefix)
+    }
+}
+
+/// [`DspError::exit_code`] for an error of any type, as held in a `Box<dyn Error>`
+///
+/// Plain I/O and WAV errors get the codes of the matching [`DspError`] variants; any other
+/// error exits with 1.
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    let io_code = |source: &std::io::Error| if source.kind() == std::io::ErrorKind::NotFound { 66 } else { 74 };
+    if let Some(error) = error.downcast_ref::<DspError>() {
+        error.exit_code()
+    } else if let Some(source) = error.downcast_ref::<std::io::Error>() {
+        io_code(source)
+    } else if let Some(error) = error.downcast_ref::<hound::Error>() {
+        match error {
+            hound::Error::IoError(source) => io_code(source),
+            _ => 65,
+        }
+    } else {
+        1
+    }
+}
+
+/// Print `error` to stderr with `prefix` and exit with its [`exit_code`]
+pub fn exit(error: &(dyn std::error::Error + 'static), prefix: &str) -> ! {
+    eprintln!("{}: {}", prefix, error);
+    std::process::exit(exit_code(error))
+}
+
+impl From<String> for DspError {
+    fn from(message: String) -> Self {
+        DspError::Other(message)
+    }
+}
+
+impl From<&str> for DspError {
+    fn from(message: &str) -> Self {
+        DspError::Other(message.to_string())
+    }
+}
+
+/// For the Q3/Q4 helpers that still report errors as plain messages
+impl From<DspError> for String {
+    fn from(error: DspError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_and_exit_codes() {
+        let missing = DspError::io("../Q1/output/Q1_results.txt", std::io::ErrorKind::NotFound.into());
+        assert!(missing.to_string().starts_with("../Q1/output/Q1_results.txt: "));
+        assert_eq!(missing.exit_code(), 66);
+        assert_eq!(DspError::io("out.wav", std::io::ErrorKind::PermissionDenied.into()).exit_code(), 74);
+        assert_eq!(DspError::Argument("--agc requires a value".into()).exit_code(), 64);
+        assert_eq!(DspError::plot("font not found").to_string(), "plotting failed: font not found");
+
+        let message: String = DspError::Data("no f_d line".into()).into();
+        assert_eq!(message, "no f_d line");
+        assert_eq!(DspError::from("oops").exit_code(), 1);
+
+        let boxed: Box<dyn std::error::Error> = Box::new(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(exit_code(boxed.as_ref()), 66);
+        let boxed: Box<dyn std::error::Error> = DspError::Data("empty spectrum".into()).into();
+        assert_eq!(exit_code(boxed.as_ref()), 65);
+        let boxed: Box<dyn std::error::Error> = "unknown option".into();
+        assert_eq!(exit_code(boxed.as_ref()), 1);
+    }
+}
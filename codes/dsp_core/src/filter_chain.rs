@@ -0,0 +1,284 @@
+use crate::butterworth::ButterworthFilter;
+use crate::error::Result;
+use crate::iir::{self, StreamingFilter};
+use rustfft::num_complex::Complex;
+use std::f64::consts::PI;
+
+/// Samples pushed through every stage before the next block is read
+const BLOCK: usize = 4096;
+
+/// One filter of a [`FilterChain`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterStage {
+    /// Transfer function B(z) / A(z), as designed by `butterworth` or read from Q2
+    TransferFunction { b: Vec<f64>, a: Vec<f64> },
+    /// Second-order sections `[b0, b1, b2, a0, a1, a2]` in series, scipy's `sos` layout
+    Sos(Vec<[f64; 6]>),
+}
+
+impl FilterStage {
+    /// `(b, a)` of every section: one for a transfer function, one per SOS row
+    fn sections(&self) -> Vec<(&[f64], &[f64])> {
+        match self {
+            FilterStage::TransferFunction { b, a } => vec![(b, a)],
+            FilterStage::Sos(sos) => sos.iter().map(|s| (&s[..3], &s[3..])).collect(),
+        }
+    }
+}
+
+/// Filters applied in series, e.g. high-pass → notch → low-pass
+///
+/// The chain is built like [`SpectralPipeline`](crate::pipeline::SpectralPipeline): each
+/// call appends a stage. [`apply`](Self::apply) runs the signal through every stage block by
+/// block, so the intermediate signals are never held in full; [`response_at`](Self::response_at)
+/// is the product of the stage responses and [`transfer_function`](Self::transfer_function)
+/// the single `(b, a)` with the same effect.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::butterworth::ButterworthFilter;
+/// use dsp_core::filter_chain::FilterChain;
+///
+/// let fs = 8000.0;
+/// let chain = FilterChain::new()
+///     .butterworth(&ButterworthFilter::highpass(4, 300.0, fs))
+///     .notch(1000.0, 10.0, fs)
+///     .butterworth(&ButterworthFilter::lowpass(4, 3000.0, fs));
+/// let h = chain.response_at(&[1000.0, 2000.0], fs);
+/// assert!(h[0].norm() < 1e-9);
+/// assert!((h[1].norm() - 1.0).abs() < 0.05);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterChain {
+    stages: Vec<FilterStage>,
+}
+
+impl FilterChain {
+    /// An empty chain, which passes the signal unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the transfer function B(z) / A(z)
+    pub fn transfer_function_stage(mut self, b: &[f64], a: &[f64]) -> Self {
+        self.stages.push(FilterStage::TransferFunction { b: b.to_vec(), a: a.to_vec() });
+        self
+    }
+
+    /// Append a designed Butterworth filter
+    pub fn butterworth(self, filter: &ButterworthFilter) -> Self {
+        self.transfer_function_stage(&filter.b, &filter.a)
+    }
+
+    /// Append second-order sections `[b0, b1, b2, a0, a1, a2]`, e.g. from
+    /// `scipy.signal.butter(..., output="sos")`
+    pub fn sos(mut self, sections: &[[f64; 6]]) -> Self {
+        self.stages.push(FilterStage::Sos(sections.to_vec()));
+        self
+    }
+
+    /// Append a second-order notch that removes `frequency` (Hz) with quality factor `q`
+    /// (centre frequency over -3 dB width), unity gain at DC and Nyquist
+    pub fn notch(self, frequency: f64, q: f64, sample_rate: f64) -> Self {
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        self.transfer_function_stage(&[1.0, -2.0 * cos, 1.0], &[1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// The stages, in the order the signal passes them
+    pub fn stages(&self) -> &[FilterStage] {
+        &self.stages
+    }
+
+    /// Check every stage with [`iir::validate`]
+    pub fn validate(&self) -> Result<()> {
+        for (i, stage) in self.stages.iter().enumerate() {
+            for (b, a) in stage.sections() {
+                iir::validate(b, a).map_err(|e| e.context(format!("filter chain stage {}", i + 1)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Filter `x` through every stage in one pass, starting at rest
+    ///
+    /// Equal to applying the stages one after another with [`iir::apply_filter`], but each
+    /// block goes through the whole chain before the next is read.
+    pub fn apply(&self, x: &[f64]) -> Result<Vec<f64>> {
+        self.validate()?;
+        let mut filters: Vec<StreamingFilter> = self
+            .stages
+            .iter()
+            .flat_map(|stage| stage.sections())
+            .map(|(b, a)| StreamingFilter::new(b, a))
+            .collect();
+        let mut output = x.to_vec();
+        let mut scratch = vec![0.0; BLOCK.min(x.len())];
+        for block in output.chunks_mut(BLOCK) {
+            for filter in filters.iter_mut() {
+                let scratch = &mut scratch[..block.len()];
+                filter.process_into(block, scratch);
+                block.copy_from_slice(scratch);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Complex response of the whole chain at each of `frequencies` (Hz)
+    pub fn response_at(&self, frequencies: &[f64], sample_rate: f64) -> Vec<Complex<f64>> {
+        frequencies
+            .iter()
+            .map(|&f| {
+                self.stages
+                    .iter()
+                    .flat_map(|stage| stage.sections())
+                    .map(|(b, a)| response(b, a, f, sample_rate))
+                    .product()
+            })
+            .collect()
+    }
+
+    /// Group delay of the whole chain at each of `frequencies` (Hz), in samples: the sum of
+    /// the stage delays
+    pub fn group_delay_at(&self, frequencies: &[f64], sample_rate: f64) -> Vec<f64> {
+        frequencies
+            .iter()
+            .map(|&f| {
+                self.stages
+                    .iter()
+                    .flat_map(|stage| stage.sections())
+                    .map(|(b, a)| group_delay(b, a, f, sample_rate))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Response on `num_points / 2 + 1` frequencies from 0 to Nyquist, the grid of Q2's
+    /// `FilterResponse::compute`, for plotting
+    pub fn response(&self, sample_rate: f64, num_points: usize) -> (Vec<f64>, Vec<Complex<f64>>) {
+        let frequencies: Vec<f64> =
+            (0..=num_points / 2).map(|k| k as f64 * sample_rate / num_points as f64).collect();
+        let h = self.response_at(&frequencies, sample_rate);
+        (frequencies, h)
+    }
+
+    /// The single `(b, a)` of the chain, normalized so a[0] = 1: the stage polynomials
+    /// multiplied out
+    ///
+    /// High combined orders lose precision in this form, so [`apply`](Self::apply) filters
+    /// stage by stage instead.
+    pub fn transfer_function(&self) -> Result<(Vec<f64>, Vec<f64>)> {
+        self.validate()?;
+        let (mut b, mut a) = (vec![1.0], vec![1.0]);
+        for (stage_b, stage_a) in self.stages.iter().flat_map(|stage| stage.sections()) {
+            b = convolve(&b, stage_b);
+            a = convolve(&a, stage_a);
+        }
+        let a0 = a[0];
+        Ok((b.iter().map(|c| c / a0).collect(), a.iter().map(|c| c / a0).collect()))
+    }
+}
+
+/// H(e^jω) = B(e^jω) / A(e^jω) of one filter at `frequency` (Hz)
+pub fn response(b: &[f64], a: &[f64], frequency: f64, sample_rate: f64) -> Complex<f64> {
+    let omega = 2.0 * PI * frequency / sample_rate;
+    let polynomial = |coefficients: &[f64]| -> Complex<f64> {
+        coefficients.iter().enumerate().map(|(k, &c)| c * Complex::from_polar(1.0, -omega * k as f64)).sum()
+    };
+    polynomial(b) / polynomial(a)
+}
+
+/// Group delay -d(arg H)/dω of one filter at `frequency` (Hz), in samples
+///
+/// For a polynomial C(e^jω) = Σ c[k] e^-jωk the delay is Re(Σ k c[k] e^-jωk / C), and the
+/// filter's is that of B minus that of A. It is undefined on a zero of B, e.g. at DC for a
+/// high-pass.
+pub fn group_delay(b: &[f64], a: &[f64], frequency: f64, sample_rate: f64) -> f64 {
+    let omega = 2.0 * PI * frequency / sample_rate;
+    let delay = |coefficients: &[f64]| -> f64 {
+        let (mut c, mut ramped) = (Complex::new(0.0, 0.0), Complex::new(0.0, 0.0));
+        for (k, &coefficient) in coefficients.iter().enumerate() {
+            let term = coefficient * Complex::from_polar(1.0, -omega * k as f64);
+            c += term;
+            ramped += term * k as f64;
+        }
+        (ramped / c).re
+    };
+    delay(b) - delay(a)
+}
+
+fn convolve(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; x.len() + y.len() - 1];
+    for (i, &xi) in x.iter().enumerate() {
+        for (j, &yj) in y.iter().enumerate() {
+            result[i + j] += xi * yj;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iir::apply_filter;
+
+    #[test]
+    fn test_chain_matches_stages_in_sequence() {
+        let fs = 8000.0;
+        let highpass = ButterworthFilter::highpass(4, 500.0, fs);
+        let lowpass = ButterworthFilter::lowpass(6, 2500.0, fs);
+        // One biquad section of a low-pass, as scipy's sos rows
+        let section = ButterworthFilter::lowpass(2, 3000.0, fs);
+        let row = [section.b[0], section.b[1], section.b[2], section.a[0], section.a[1], section.a[2]];
+        let notch = FilterChain::new().notch(1000.0, 5.0, fs);
+        let chain = FilterChain::new()
+            .butterworth(&highpass)
+            .notch(1000.0, 5.0, fs)
+            .sos(&[row])
+            .butterworth(&lowpass);
+        assert_eq!(chain.stages().len(), 4);
+
+        let signal: Vec<f64> = (0..10000).map(|n| ((n * 7919) % 23) as f64 - 11.0).collect();
+        let mut expected = apply_filter(&signal, &highpass.b, &highpass.a);
+        for (b, a) in chain.stages()[1..].iter().flat_map(|stage| stage.sections()) {
+            expected = apply_filter(&expected, b, a);
+        }
+        let output = chain.apply(&signal).unwrap();
+        assert_eq!(output.len(), signal.len());
+        for (y, e) in output.iter().zip(&expected) {
+            assert!((y - e).abs() < 1e-9);
+        }
+
+        // The combined response is the product of the stages', also for the multiplied-out form
+        let frequencies = [0.0, 800.0, 1000.0, 1500.0, 2700.0, 3999.0];
+        let (b, a) = chain.transfer_function().unwrap();
+        assert_eq!((b.len(), a.len()), (4 + 2 + 2 + 6 + 1, 15));
+        for (&f, h) in frequencies.iter().zip(chain.response_at(&frequencies, fs)) {
+            let stages = response(&highpass.b, &highpass.a, f, fs)
+                * notch.response_at(&[f], fs)[0]
+                * response(&section.b, &section.a, f, fs)
+                * response(&lowpass.b, &lowpass.a, f, fs);
+            assert!((h - stages).norm() < 1e-9);
+            assert!((h - response(&b, &a, f, fs)).norm() < 1e-9);
+        }
+        let (grid, h) = chain.response(fs, 64);
+        assert_eq!((grid.len(), grid[32]), (33, 4000.0));
+        assert!(h[8].norm() < 1e-9, "notch at 1000 Hz: {}", h[8]);
+
+        // Group delay: the derivative of the unwrapped phase, summed over the stages
+        let (f, df) = (1500.0, 0.01);
+        let phase = |f: f64| chain.response_at(&[f], fs)[0].arg();
+        let numeric = -(phase(f + df) - phase(f - df)) / (2.0 * PI * 2.0 * df / fs);
+        let delay = chain.group_delay_at(&[f], fs)[0];
+        assert!((delay - numeric).abs() < 1e-4, "{} vs {}", delay, numeric);
+        // A pure delay of 3 samples
+        assert!((group_delay(&[0.0, 0.0, 0.0, 1.0], &[1.0], 700.0, fs) - 3.0).abs() < 1e-12);
+
+        // An empty chain passes the signal; a bad stage is reported with its position
+        assert_eq!(FilterChain::new().apply(&signal).unwrap(), signal);
+        let error = chain.clone().transfer_function_stage(&[1.0], &[0.0]).apply(&signal).unwrap_err();
+        assert!(error.to_string().contains("stage 5"), "{}", error);
+    }
+}
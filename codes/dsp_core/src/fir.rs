@@ -0,0 +1,184 @@
+use crate::butterworth::FilterType;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+
+/// Tap count from which [`FirFilter::apply`] convolves by FFT overlap-add
+///
+/// Below it the direct sum is cheaper than two FFTs per block.
+pub const OVERLAP_ADD_MIN_TAPS: usize = 16;
+
+/// Linear-phase FIR filter designed by the window method (Blackman-windowed sinc)
+///
+/// The tap count is made odd so the filter has a centre tap and a whole-sample delay.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::fir::FirFilter;
+///
+/// // A high-pass removes a DC offset; away from the edges only the tone is left
+/// let signal: Vec<f64> = (0..2000).map(|n| 0.5 + (n as f64 * 0.3).sin()).collect();
+/// let filter = FirFilter::highpass(201, 100.0, 8000.0);
+/// let filtered = filter.apply(&signal);
+/// let mean = filtered[500..1500].iter().sum::<f64>() / 1000.0;
+/// assert!(mean.abs() < 0.01);
+/// assert_eq!(filter.delay(), 100);
+/// ```
+pub struct FirFilter {
+    pub taps: Vec<f64>,
+    pub cutoff: f64,
+    pub sample_rate: f64,
+    pub filter_type: FilterType,
+}
+
+impl FirFilter {
+    pub fn lowpass(num_taps: usize, cutoff: f64, sample_rate: f64) -> Self {
+        let taps = windowed_sinc(num_taps, cutoff / sample_rate);
+        Self { taps, cutoff, sample_rate, filter_type: FilterType::Lowpass }
+    }
+
+    /// High-pass by spectral inversion of the low-pass with the same cutoff
+    pub fn highpass(num_taps: usize, cutoff: f64, sample_rate: f64) -> Self {
+        let mut taps = windowed_sinc(num_taps, cutoff / sample_rate);
+        let centre = taps.len() / 2;
+        for tap in taps.iter_mut() {
+            *tap = -*tap;
+        }
+        taps[centre] += 1.0;
+        Self { taps, cutoff, sample_rate, filter_type: FilterType::Highpass }
+    }
+
+    /// Group delay in samples, the same at every frequency
+    pub fn delay(&self) -> usize {
+        self.taps.len() / 2
+    }
+
+    /// Filter `signal` with the group delay removed, so the output lines up with the input
+    ///
+    /// Samples beyond either end count as zero; the output has the input's length. Long
+    /// filters go through [`overlap_add`], which gives the same samples up to rounding.
+    pub fn apply(&self, signal: &[f64]) -> Vec<f64> {
+        if self.taps.len() >= OVERLAP_ADD_MIN_TAPS && !signal.is_empty() {
+            let full = overlap_add(signal, &self.taps);
+            return full[self.delay()..self.delay() + signal.len()].to_vec();
+        }
+        let delay = self.delay() as isize;
+        (0..signal.len() as isize)
+            .map(|n| {
+                self.taps
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, &h)| {
+                        let index = n + delay - k as isize;
+                        signal.get(usize::try_from(index).ok()?).map(|&x| h * x)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Full linear convolution of `signal` with `taps` by FFT overlap-add
+///
+/// The signal is cut into blocks that fill an FFT of four times the filter length once
+/// the filter tail is added, each block is multiplied by the filter spectrum, and the
+/// tails of neighbouring blocks are added. The output has `signal.len() + taps.len() - 1`
+/// samples, or none if either input is empty.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::fir::overlap_add;
+///
+/// let y = overlap_add(&[1.0, 2.0, 3.0], &[1.0, 1.0]);
+/// let expected = [1.0, 3.0, 5.0, 3.0];
+/// assert!(y.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-12));
+/// ```
+pub fn overlap_add(signal: &[f64], taps: &[f64]) -> Vec<f64> {
+    if signal.is_empty() || taps.is_empty() {
+        return Vec::new();
+    }
+    let fft_len = (4 * taps.len()).next_power_of_two();
+    let block = fft_len - taps.len() + 1;
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(fft_len);
+    let inverse = planner.plan_fft_inverse(fft_len);
+
+    let mut response = vec![Complex::new(0.0, 0.0); fft_len];
+    for (bin, &h) in response.iter_mut().zip(taps) {
+        bin.re = h;
+    }
+    forward.process(&mut response);
+
+    let mut output = vec![0.0; signal.len() + taps.len() - 1];
+    let mut buffer = vec![Complex::new(0.0, 0.0); fft_len];
+    for (index, chunk) in signal.chunks(block).enumerate() {
+        buffer.fill(Complex::new(0.0, 0.0));
+        for (bin, &x) in buffer.iter_mut().zip(chunk) {
+            bin.re = x;
+        }
+        forward.process(&mut buffer);
+        for (bin, h) in buffer.iter_mut().zip(&response) {
+            *bin *= h;
+        }
+        inverse.process(&mut buffer);
+        // The inverse FFT is unnormalized
+        let tail = &buffer[..chunk.len() + taps.len() - 1];
+        for (out, bin) in output[index * block..].iter_mut().zip(tail) {
+            *out += bin.re / fft_len as f64;
+        }
+    }
+    output
+}
+
+/// Blackman-windowed sinc low-pass with cutoff `fc` in cycles per sample and unity DC gain
+fn windowed_sinc(num_taps: usize, fc: f64) -> Vec<f64> {
+    let num_taps = num_taps.max(3) | 1;
+    let centre = (num_taps / 2) as f64;
+    let taps: Vec<f64> = (0..num_taps)
+        .map(|i| {
+            let d = i as f64 - centre;
+            let sinc = if d == 0.0 { 2.0 * fc } else { (2.0 * PI * fc * d).sin() / (PI * d) };
+            let phase = 2.0 * PI * i as f64 / (num_taps - 1) as f64;
+            sinc * (0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos())
+        })
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    taps.into_iter().map(|h| h / sum).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fir_passes_and_stops_without_delay() {
+        let fs = 8000.0;
+        let tone = |f: f64| (0..4000).map(|i| (2.0 * PI * f * i as f64 / fs).sin()).collect::<Vec<f64>>();
+        let rms = |v: &[f64]| (v[500..3500].iter().map(|x| x * x).sum::<f64>() / 3000.0).sqrt();
+
+        let lowpass = FirFilter::lowpass(200, 1000.0, fs);
+        assert_eq!((lowpass.taps.len(), lowpass.delay()), (201, 100));
+        let passed = lowpass.apply(&tone(300.0));
+        // Zero delay: the output matches the input sample for sample in the passband
+        let input = tone(300.0);
+        assert!((500..3500).all(|i| (passed[i] - input[i]).abs() < 1e-3));
+        assert!(rms(&lowpass.apply(&tone(2500.0))) < 1e-3);
+
+        let highpass = FirFilter::highpass(201, 1000.0, fs);
+        assert!(rms(&highpass.apply(&tone(300.0))) < 1e-3);
+        assert!((rms(&highpass.apply(&tone(2500.0))) - 0.5f64.sqrt()).abs() < 1e-3);
+
+        // Overlap-add matches the direct convolution sum across several blocks
+        let signal: Vec<f64> = (0..3000).map(|i| ((i * i) % 97) as f64 / 97.0 - 0.5).collect();
+        let full = overlap_add(&signal, &lowpass.taps);
+        assert_eq!(full.len(), signal.len() + lowpass.taps.len() - 1);
+        for (m, &y) in full.iter().enumerate().step_by(37) {
+            let direct: f64 = (0..lowpass.taps.len())
+                .filter(|&k| k <= m && m - k < signal.len())
+                .map(|k| lowpass.taps[k] * signal[m - k])
+                .sum();
+            assert!((y - direct).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::{DspError, Result};
+use crate::filter_chain::FilterChain;
+
+/// Sample format of a [`FixedPointCascade`]: signed fractions in [-1, 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QFormat {
+    /// 16-bit words with 15 fractional bits (CMSIS-DSP `q15_t`)
+    Q15,
+    /// 32-bit words with 31 fractional bits (CMSIS-DSP `q31_t`)
+    Q31,
+}
+
+impl QFormat {
+    /// Fractional bits of a sample
+    pub fn frac_bits(self) -> u32 {
+        match self {
+            QFormat::Q15 => 15,
+            QFormat::Q31 => 31,
+        }
+    }
+
+    fn max(self) -> i64 {
+        (1 << self.frac_bits()) - 1
+    }
+
+    fn min(self) -> i64 {
+        -(1 << self.frac_bits())
+    }
+
+    /// Round `x` to the nearest representable sample, saturating at full scale; the flag
+    /// says whether it saturated
+    pub fn quantize(self, x: f64) -> (i64, bool) {
+        let scaled = (x * (1u64 << self.frac_bits()) as f64).round();
+        if scaled > self.max() as f64 {
+            (self.max(), true)
+        } else if scaled < self.min() as f64 {
+            (self.min(), true)
+        } else {
+            (scaled as i64, false)
+        }
+    }
+
+    /// The value of sample `q`
+    pub fn to_f64(self, q: i64) -> f64 {
+        q as f64 / (1u64 << self.frac_bits()) as f64
+    }
+}
+
+impl std::fmt::Display for QFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QFormat::Q15 => write!(f, "Q15"),
+            QFormat::Q31 => write!(f, "Q31"),
+        }
+    }
+}
+
+/// Biquad cascade in integer arithmetic, the way a fixed-point DSP runs it
+///
+/// Samples are Q15 or Q31 words. Biquad coefficients reach ±2 (b1 and a1 of a sharp
+/// section), so they are stored with one fractional bit less (Q2.14 / Q2.30) in a word of
+/// the same width. Each section is a Direct Form I: the five products go into a wide
+/// accumulator, which is rounded back to the sample format and saturated at full scale
+/// instead of wrapping around. The section outputs of a Butterworth [`sos`] stay below the
+/// input level in the passband, so saturation means the input itself was too loud.
+///
+/// [`sos`]: crate::butterworth::ButterworthFilter::sos
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::butterworth::ButterworthFilter;
+/// use dsp_core::fixed_point::{self, QFormat};
+///
+/// let sos = ButterworthFilter::lowpass(4, 1000.0, 8000.0).sos();
+/// let x: Vec<f64> = (0..4000).map(|n| 0.5 * (n as f64 * 0.3).sin()).collect();
+/// let q15 = fixed_point::compare(&sos, &x, QFormat::Q15).unwrap();
+/// let q31 = fixed_point::compare(&sos, &x, QFormat::Q31).unwrap();
+/// assert!(q15.snr_db > 50.0 && q31.snr_db > q15.snr_db + 60.0);
+/// assert_eq!(q15.saturations, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedPointCascade {
+    pub format: QFormat,
+    /// Quantized `[b0, b1, b2, a1, a2]` of each section, normalized by a0
+    pub sections: Vec<[i64; 5]>,
+}
+
+/// Result of [`FixedPointCascade::apply`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedPointOutput {
+    /// Output samples converted back to floating point
+    pub output: Vec<f64>,
+    /// Input samples and section outputs that were clipped to full scale
+    pub saturations: usize,
+}
+
+impl FixedPointCascade {
+    /// Quantize second-order sections `[b0, b1, b2, a0, a1, a2]`
+    ///
+    /// Fails when a coefficient, divided by a0, falls outside [-2, 2).
+    pub fn new(sos: &[[f64; 6]], format: QFormat) -> Result<Self> {
+        let coefficients = CoefficientFormat(format);
+        let sections = sos
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                if s[3] == 0.0 || !s.iter().all(|c| c.is_finite()) {
+                    return Err(DspError::Data(format!("section {}: invalid coefficients {:?}", i + 1, s)));
+                }
+                let mut section = [0i64; 5];
+                for (q, &c) in section.iter_mut().zip([s[0], s[1], s[2], s[4], s[5]].iter()) {
+                    let (value, saturated) = coefficients.quantize(c / s[3]);
+                    if saturated {
+                        return Err(DspError::Data(format!(
+                            "section {}: coefficient {} does not fit the {} coefficient range [-2, 2)",
+                            i + 1,
+                            c / s[3],
+                            format
+                        )));
+                    }
+                    *q = value;
+                }
+                Ok(section)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(FixedPointCascade { format, sections })
+    }
+
+    /// The quantized coefficients as sections `[b0, b1, b2, 1, a1, a2]`, e.g. to plot the
+    /// response the fixed-point filter really has
+    pub fn quantized_sos(&self) -> Vec<[f64; 6]> {
+        let coefficients = CoefficientFormat(self.format);
+        self.sections
+            .iter()
+            .map(|s| {
+                let c = |q: i64| coefficients.to_f64(q);
+                [c(s[0]), c(s[1]), c(s[2]), 1.0, c(s[3]), c(s[4])]
+            })
+            .collect()
+    }
+
+    /// Filter `x` (full scale ±1) through the cascade, starting at rest
+    pub fn apply(&self, x: &[f64]) -> FixedPointOutput {
+        let shift = self.format.frac_bits() - 1;
+        let round = 1i128 << (shift - 1);
+        let (min, max) = (self.format.min() as i128, self.format.max() as i128);
+        let mut saturations = 0;
+        // x[n-1], x[n-2], y[n-1], y[n-2] of each section
+        let mut states = vec![[0i64; 4]; self.sections.len()];
+        let output = x
+            .iter()
+            .map(|&sample| {
+                let (mut value, saturated) = self.format.quantize(sample);
+                saturations += saturated as usize;
+                for (s, state) in self.sections.iter().zip(states.iter_mut()) {
+                    let [x1, x2, y1, y2] = *state;
+                    let acc = s[0] as i128 * value as i128 + s[1] as i128 * x1 as i128 + s[2] as i128 * x2 as i128
+                        - s[3] as i128 * y1 as i128
+                        - s[4] as i128 * y2 as i128;
+                    let y = (acc + round) >> shift;
+                    let y = if y > max || y < min {
+                        saturations += 1;
+                        y.clamp(min, max)
+                    } else {
+                        y
+                    } as i64;
+                    *state = [value, x1, y, y1];
+                    value = y;
+                }
+                self.format.to_f64(value)
+            })
+            .collect();
+        FixedPointOutput { output, saturations }
+    }
+}
+
+/// Coefficient words of a [`QFormat`]: the same width with one integer bit more (Q2.14 or
+/// Q2.30), range [-2, 2)
+#[derive(Debug, Clone, Copy)]
+struct CoefficientFormat(QFormat);
+
+impl CoefficientFormat {
+    fn scale(self) -> f64 {
+        (1u64 << (self.0.frac_bits() - 1)) as f64
+    }
+
+    fn quantize(self, c: f64) -> (i64, bool) {
+        let scaled = (c * self.scale()).round();
+        if scaled > self.0.max() as f64 || scaled < self.0.min() as f64 {
+            (0, true)
+        } else {
+            (scaled as i64, false)
+        }
+    }
+
+    fn to_f64(self, q: i64) -> f64 {
+        q as f64 / self.scale()
+    }
+}
+
+/// How closely a fixed-point cascade follows the `f64` one, from [`compare`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedPointComparison {
+    pub format: QFormat,
+    /// Floating-point output power over the power of the difference (dB)
+    pub snr_db: f64,
+    /// Largest sample difference
+    pub max_error: f64,
+    /// Samples clipped to full scale
+    pub saturations: usize,
+}
+
+/// Filter `x` through `sos` in `f64` ([`FilterChain`]) and in `format`, and measure the
+/// fixed-point result against the floating-point one
+///
+/// The error combines coefficient quantization, which moves the poles, and the rounding
+/// of every section output, which adds noise; Q31 leaves about 90 dB more headroom than Q15.
+pub fn compare(sos: &[[f64; 6]], x: &[f64], format: QFormat) -> Result<FixedPointComparison> {
+    let reference = FilterChain::new().sos(sos).apply(x)?;
+    let fixed = FixedPointCascade::new(sos, format)?.apply(x);
+    let signal: f64 = reference.iter().map(|r| r * r).sum();
+    let (noise, max_error) = reference
+        .iter()
+        .zip(&fixed.output)
+        .fold((0.0, 0.0f64), |(noise, max), (r, y)| (noise + (r - y) * (r - y), max.max((r - y).abs())));
+    Ok(FixedPointComparison {
+        format,
+        snr_db: 10.0 * (signal / noise).log10(),
+        max_error,
+        saturations: fixed.saturations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::butterworth::ButterworthFilter;
+
+    #[test]
+    fn test_quantization_and_saturation() {
+        assert_eq!(QFormat::Q15.quantize(0.5), (16384, false));
+        assert_eq!(QFormat::Q15.quantize(1.0), (32767, true));
+        assert_eq!(QFormat::Q15.quantize(-1.0), (-32768, false));
+        assert_eq!(QFormat::Q31.quantize(-3.0), (i32::MIN as i64, true));
+        assert_eq!(QFormat::Q15.to_f64(-16384), -0.5);
+
+        // Coefficients keep one integer bit: 1.5 fits, 2.5 does not
+        let cascade = FixedPointCascade::new(&[[0.5, 1.0, 0.5, 1.0, -1.5, 0.6]], QFormat::Q15).unwrap();
+        assert_eq!(cascade.sections[0], [8192, 16384, 8192, -24576, 9830]);
+        assert_eq!(cascade.quantized_sos()[0][4], -1.5);
+        assert!(FixedPointCascade::new(&[[1.0, 0.0, 0.0, 1.0, -2.5, 1.0]], QFormat::Q31).is_err());
+        assert!(FixedPointCascade::new(&[[1.0, 0.0, 0.0, 0.0, 0.0, 0.0]], QFormat::Q31).is_err());
+    }
+
+    #[test]
+    fn test_cascade_follows_floating_point() {
+        let sos = ButterworthFilter::highpass(8, 3000.0, 22050.0).sos();
+        let x: Vec<f64> = (0..22050).map(|n| 0.4 * (n as f64 * 1.1).sin() + 0.3 * (n as f64 * 0.2).cos()).collect();
+        let q15 = compare(&sos, &x, QFormat::Q15).unwrap();
+        let q31 = compare(&sos, &x, QFormat::Q31).unwrap();
+        assert!(q15.snr_db > 30.0 && q15.snr_db < 90.0, "{:?}", q15);
+        assert!(q31.snr_db > 120.0, "{:?}", q31);
+        assert_eq!((q15.saturations, q31.saturations), (0, 0));
+
+        // Too loud: the input is clipped, but the output stays within full scale
+        let loud: Vec<f64> = x.iter().map(|v| v * 3.0).collect();
+        let output = FixedPointCascade::new(&sos, QFormat::Q15).unwrap().apply(&loud);
+        assert!(output.saturations > 0);
+        assert!(output.output.iter().all(|y| (-1.0..1.0).contains(y)));
+    }
+}
@@ -1,4 +1,5 @@
-use num_complex::Complex;
+use crate::spectrum::{self, ShiftMethod};
+use rustfft::num_complex::Complex;
 
 /// Perform frequency shift: X_b(f) = X_h(f - f_d) + X_h(f + f_d)
 /// This is equivalent to multiplying by cos(2πf_d·t) in time domain
@@ -15,19 +16,37 @@ pub fn frequency_shift(
     let mut result = vec![Complex::new(0.0, 0.0); n];
     
     // For each output frequency bin, find contributions from shifted input
-    for i in 0..n {
+    for (i, bin) in result.iter_mut().enumerate() {
         // Contribution from f - f_d (shift down)
         let idx_minus = (i as isize + shift_bins).rem_euclid(n as isize) as usize;
-        result[i] = result[i] + spectrum[idx_minus] * 0.5;
+        *bin += spectrum[idx_minus] * 0.5;
         
         // Contribution from f + f_d (shift up)
         let idx_plus = (i as isize - shift_bins).rem_euclid(n as isize) as usize;
-        result[i] = result[i] + spectrum[idx_plus] * 0.5;
+        *bin += spectrum[idx_plus] * 0.5;
     }
     
     result
 }
 
+/// Frequency shift by exactly `f_d` or by the nearest whole bin, depending on `method`
+///
+/// Rounding to a bin is off by up to f_s / 2n, about 0.35 Hz for the recording here and
+/// more for short signals; `ShiftMethod::Mixing` multiplies by the carrier in the time domain
+/// instead, so the result matches cos(2πf_d·t) mixing for any f_d.
+pub fn frequency_shift_with(
+    spectrum: &[Complex<f64>],
+    f_d: f64,
+    f_s: f64,
+    n: usize,
+    method: ShiftMethod,
+) -> Vec<Complex<f64>> {
+    match method {
+        ShiftMethod::NearestBin => frequency_shift(spectrum, f_d, f_s, n),
+        ShiftMethod::Mixing => spectrum::shift_and_add(&spectrum[..n], f_d, f_s, method),
+    }
+}
+
 /// Alternative implementation using explicit frequency mapping
 pub fn frequency_shift_explicit(
     spectrum: &[Complex<f64>],
@@ -38,7 +57,7 @@ pub fn frequency_shift_explicit(
     let df = f_s / n as f64;
     let mut result = vec![Complex::new(0.0, 0.0); n];
     
-    for i in 0..n {
+    for (i, bin) in result.iter_mut().enumerate() {
         // Current frequency
         let f_i = if i <= n / 2 {
             i as f64 * df
@@ -56,10 +75,10 @@ pub fn frequency_shift_explicit(
         
         // Accumulate contributions
         if idx_minus < n {
-            result[i] = result[i] + spectrum[idx_minus] * 0.5;
+            *bin += spectrum[idx_minus] * 0.5;
         }
         if idx_plus < n {
-            result[i] = result[i] + spectrum[idx_plus] * 0.5;
+            *bin += spectrum[idx_plus] * 0.5;
         }
     }
     
@@ -0,0 +1,82 @@
+use rustfft::{num_complex::Complex, FftPlanner};
+
+/// Analytic signal x(t) + j H{x}(t), computed in the frequency domain
+///
+/// Negative-frequency bins are zeroed and positive ones doubled; DC and (for even
+/// lengths) the Nyquist bin are kept as they are. The FFT treats the signal as
+/// periodic, so the first and last few milliseconds are less accurate.
+pub fn analytic_signal(signal: &[f64]) -> Vec<Complex<f64>> {
+    let n = signal.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut planner = FftPlanner::<f64>::new();
+    let mut spectrum: Vec<Complex<f64>> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    planner.plan_fft_forward(n).process(&mut spectrum);
+
+    let positive_end = n.div_ceil(2);
+    for bin in spectrum.iter_mut().take(positive_end).skip(1) {
+        *bin *= 2.0;
+    }
+    for bin in spectrum.iter_mut().skip(n / 2 + 1) {
+        *bin = Complex::new(0.0, 0.0);
+    }
+
+    planner.plan_fft_inverse(n).process(&mut spectrum);
+    let scale = 1.0 / n as f64;
+    spectrum.iter().map(|z| z * scale).collect()
+}
+
+/// Hilbert transform H{x}: every frequency component delayed by 90°, so cos becomes sin
+pub fn hilbert(signal: &[f64]) -> Vec<f64> {
+    analytic_signal(signal).iter().map(|z| z.im).collect()
+}
+
+/// Instantaneous amplitude |x + j H{x}|
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::hilbert::envelope;
+/// use std::f64::consts::PI;
+///
+/// // AM tone: a 2 kHz carrier with a slow 1 + 0.5 cos(...) envelope
+/// let fs = 8000.0;
+/// let a = |n: usize| 1.0 + 0.5 * (2.0 * PI * 5.0 * n as f64 / fs).cos();
+/// let x: Vec<f64> = (0..8000).map(|n| a(n) * (2.0 * PI * 2000.0 * n as f64 / fs).cos()).collect();
+/// let env = envelope(&x);
+/// assert!((4000..4100).all(|n| (env[n] - a(n)).abs() < 0.01));
+/// ```
+pub fn envelope(signal: &[f64]) -> Vec<f64> {
+    analytic_signal(signal).iter().map(|z| z.norm()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_hilbert_of_cosine_is_sine() {
+        // Whole number of periods, so the circular FFT has no edge effects
+        let fs = 8000.0;
+        for n in [8000, 8001] {
+            let f = if n % 2 == 0 { 440.0 } else { 8.0 * fs / n as f64 };
+            let cosine: Vec<f64> = (0..n).map(|i| (2.0 * PI * f * i as f64 / fs).cos()).collect();
+            let transformed = hilbert(&cosine);
+            for (i, h) in transformed.iter().enumerate() {
+                let expected = (2.0 * PI * f * i as f64 / fs).sin();
+                assert!((h - expected).abs() < 1e-9, "n = {}, sample {}: {} vs {}", n, i, h, expected);
+            }
+        }
+
+        let am: Vec<f64> = (0..8000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (1.0 + 0.5 * (2.0 * PI * 10.0 * t).cos()) * (2.0 * PI * 1000.0 * t).cos()
+            })
+            .collect();
+        let env = envelope(&am);
+        assert!((env[0] - 1.5).abs() < 1e-9 && (env[400] - 0.5).abs() < 1e-9);
+    }
+}
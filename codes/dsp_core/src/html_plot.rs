@@ -0,0 +1,463 @@
+use crate::error::{DspError, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Line colours, assigned to series in order
+const PALETTE: [&str; 6] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf"];
+
+/// Whether `--html` was given on the command line
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--html")
+}
+
+/// One line of an [`InteractiveChart`]; `x` must be ascending
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub color: String,
+}
+
+/// A zoomable x-y line chart
+///
+/// Drag across the plot to zoom into an x range, use the mouse wheel to zoom around the
+/// cursor and double-click to reset. The y axis follows the visible data, and the readout
+/// below the chart gives the value under the cursor and the visible peak of every series,
+/// e.g. the exact frequency of a sideband peak.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractiveChart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub series: Vec<Series>,
+    /// Dashed vertical lines with a label, e.g. at ±f_d
+    pub markers: Vec<(f64, String)>,
+}
+
+impl InteractiveChart {
+    pub fn new(title: &str, x_label: &str, y_label: &str) -> Self {
+        InteractiveChart {
+            title: title.to_string(),
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            series: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    /// Add a series of (x, y) points in ascending x; non-finite y values leave a gap
+    pub fn series(mut self, name: &str, points: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        let (x, y) = points.into_iter().unzip();
+        let color = PALETTE[self.series.len() % PALETTE.len()].to_string();
+        self.series.push(Series {
+            name: name.to_string(),
+            x,
+            y,
+            color,
+        });
+        self
+    }
+
+    /// Add a waveform sampled at `sample_rate`, on a time axis in seconds
+    pub fn waveform(self, name: &str, samples: &[f64], sample_rate: f64) -> Self {
+        self.series(name, samples.iter().enumerate().map(|(i, &v)| (i as f64 / sample_rate, v)))
+    }
+
+    pub fn marker(mut self, x: f64, label: &str) -> Self {
+        self.markers.push((x, label.to_string()));
+        self
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"title\":");
+        write_string(out, &self.title);
+        out.push_str(",\"xlabel\":");
+        write_string(out, &self.x_label);
+        out.push_str(",\"ylabel\":");
+        write_string(out, &self.y_label);
+        out.push_str(",\"series\":[");
+        for (i, series) in self.series.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"name\":");
+            write_string(out, &series.name);
+            out.push_str(",\"color\":");
+            write_string(out, &series.color);
+            out.push_str(",\"x\":");
+            write_numbers(out, &series.x);
+            out.push_str(",\"y\":");
+            write_numbers(out, &series.y);
+            out.push('}');
+        }
+        out.push_str("],\"markers\":[");
+        for (i, (x, label)) in self.markers.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"x\":");
+            write_numbers(out, &[*x]);
+            out.push_str(",\"label\":");
+            write_string(out, label);
+            out.push('}');
+        }
+        out.push_str("]}");
+    }
+}
+
+/// A self-contained HTML page of [`InteractiveChart`]s
+///
+/// Data and script are inline, so the file opens offline in any browser and can be
+/// attached to a report as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractivePage {
+    pub title: String,
+    pub charts: Vec<InteractiveChart>,
+}
+
+impl InteractivePage {
+    pub fn new(title: &str) -> Self {
+        InteractivePage {
+            title: title.to_string(),
+            charts: Vec::new(),
+        }
+    }
+
+    pub fn chart(mut self, chart: InteractiveChart) -> Self {
+        self.charts.push(chart);
+        self
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut data = String::from("[");
+        for (i, chart) in self.charts.iter().enumerate() {
+            if i > 0 {
+                data.push(',');
+            }
+            chart.write_json(&mut data);
+        }
+        data.push(']');
+
+        let title = escape_html(&self.title);
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(html, "<title>{}</title>", title);
+        let _ = writeln!(html, "<style>{}</style>", STYLE);
+        html.push_str("</head>\n<body>\n");
+        let _ = writeln!(html, "<h1>{}</h1>", title);
+        html.push_str("<p class=\"hint\">Drag to zoom into a range, scroll to zoom around the cursor, ");
+        html.push_str("double-click to reset.</p>\n<div id=\"charts\"></div>\n");
+        let _ = writeln!(html, "<script>\nconst CHARTS = {};\n{}</script>", data, SCRIPT);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_html()).map_err(|e| DspError::io(path, e))
+    }
+}
+
+/// JSON string literal; `<` is escaped too, so no value can close the `<script>` element
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c == '<' || c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// JSON number array at single precision, which keeps the file small; non-finite values
+/// become `null`
+fn write_numbers(out: &mut String, values: &[f64]) {
+    out.push('[');
+    for (i, &v) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if v.is_finite() {
+            let _ = write!(out, "{}", v as f32);
+        } else {
+            out.push_str("null");
+        }
+    }
+    out.push(']');
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 20px 40px; color: #222; }
+h1 { font-size: 22px; }
+h2 { font-size: 17px; margin: 28px 0 6px; }
+.hint { color: #666; font-size: 13px; }
+.legend span { display: inline-block; margin-right: 18px; font-size: 13px; }
+.legend i { display: inline-block; width: 18px; height: 3px; margin-right: 6px; vertical-align: middle; }
+canvas { display: block; cursor: crosshair; }
+.info { font: 12px monospace; color: #444; min-height: 32px; white-space: pre; }
+";
+
+const SCRIPT: &str = r#"
+function niceStep(span, count) {
+  const raw = span / count;
+  const power = Math.pow(10, Math.floor(Math.log10(raw)));
+  const f = raw / power;
+  return (f < 1.5 ? 1 : f < 3.5 ? 2 : f < 7.5 ? 5 : 10) * power;
+}
+function fmt(v, step) {
+  if (Math.abs(v) < step * 1e-6) v = 0;
+  if (v !== 0 && (Math.abs(v) >= 1e6 || Math.abs(v) < 1e-4)) return v.toExponential(3);
+  return v.toFixed(Math.min(6, Math.max(0, -Math.floor(Math.log10(step)))));
+}
+function lowerBound(xs, x) {
+  let lo = 0, hi = xs.length;
+  while (lo < hi) { const mid = (lo + hi) >> 1; if (xs[mid] < x) lo = mid + 1; else hi = mid; }
+  return lo;
+}
+function visible(s, view) {
+  return [Math.max(0, lowerBound(s.x, view[0]) - 1), Math.min(s.x.length, lowerBound(s.x, view[1]) + 1)];
+}
+
+function Chart(container, spec) {
+  const margin = { left: 80, right: 20, top: 12, bottom: 46 };
+  const height = 380;
+  let full = [Infinity, -Infinity];
+  for (const s of spec.series) {
+    if (s.x.length) { full[0] = Math.min(full[0], s.x[0]); full[1] = Math.max(full[1], s.x[s.x.length - 1]); }
+  }
+  if (!(full[0] < full[1])) full = [0, 1];
+  let view = full.slice(), drag = null, hover = null;
+
+  const title = document.createElement('h2');
+  title.textContent = spec.title;
+  const legend = document.createElement('div');
+  legend.className = 'legend';
+  for (const s of spec.series) {
+    const item = document.createElement('span');
+    const swatch = document.createElement('i');
+    swatch.style.background = s.color;
+    item.appendChild(swatch);
+    item.appendChild(document.createTextNode(s.name));
+    legend.appendChild(item);
+  }
+  const canvas = document.createElement('canvas');
+  const info = document.createElement('div');
+  info.className = 'info';
+  for (const el of [title, legend, canvas, info]) container.appendChild(el);
+
+  function plotWidth() { return canvas.clientWidth - margin.left - margin.right; }
+  function toX(px) { return view[0] + (px - margin.left) / plotWidth() * (view[1] - view[0]); }
+
+  function yRange() {
+    let lo = Infinity, hi = -Infinity;
+    for (const s of spec.series) {
+      const [a, b] = visible(s, view);
+      for (let i = a; i < b; i++) {
+        const y = s.y[i];
+        if (y === null) continue;
+        if (y < lo) lo = y;
+        if (y > hi) hi = y;
+      }
+    }
+    if (!(lo < hi)) { const c = isFinite(lo) ? lo : 0; return [c - 1, c + 1]; }
+    const pad = 0.05 * (hi - lo);
+    return [lo - pad, hi + pad];
+  }
+
+  function draw() {
+    const ratio = window.devicePixelRatio || 1;
+    const width = container.clientWidth;
+    canvas.width = width * ratio;
+    canvas.height = height * ratio;
+    canvas.style.width = width + 'px';
+    canvas.style.height = height + 'px';
+    const ctx = canvas.getContext('2d');
+    ctx.setTransform(ratio, 0, 0, ratio, 0, 0);
+    const pw = width - margin.left - margin.right, ph = height - margin.top - margin.bottom;
+    const [y0, y1] = yRange();
+    const X = x => margin.left + (x - view[0]) / (view[1] - view[0]) * pw;
+    const Y = y => margin.top + (1 - (y - y0) / (y1 - y0)) * ph;
+    const line = (ax, ay, bx, by) => { ctx.beginPath(); ctx.moveTo(ax, ay); ctx.lineTo(bx, by); ctx.stroke(); };
+
+    ctx.clearRect(0, 0, width, height);
+    ctx.font = '12px sans-serif';
+    ctx.strokeStyle = '#e4e4e4';
+    ctx.fillStyle = '#333';
+    ctx.lineWidth = 1;
+    const xs = niceStep(view[1] - view[0], Math.max(2, Math.floor(pw / 110)));
+    ctx.textAlign = 'center';
+    ctx.textBaseline = 'top';
+    for (let v = Math.ceil(view[0] / xs) * xs; v <= view[1]; v += xs) {
+      line(X(v), margin.top, X(v), margin.top + ph);
+      ctx.fillText(fmt(v, xs), X(v), margin.top + ph + 5);
+    }
+    const ys = niceStep(y1 - y0, 6);
+    ctx.textAlign = 'right';
+    ctx.textBaseline = 'middle';
+    for (let v = Math.ceil(y0 / ys) * ys; v <= y1; v += ys) {
+      line(margin.left, Y(v), margin.left + pw, Y(v));
+      ctx.fillText(fmt(v, ys), margin.left - 6, Y(v));
+    }
+    ctx.textAlign = 'center';
+    ctx.textBaseline = 'bottom';
+    ctx.fillText(spec.xlabel, margin.left + pw / 2, height - 2);
+    ctx.save();
+    ctx.translate(14, margin.top + ph / 2);
+    ctx.rotate(-Math.PI / 2);
+    ctx.textBaseline = 'middle';
+    ctx.fillText(spec.ylabel, 0, 0);
+    ctx.restore();
+    ctx.strokeStyle = '#888';
+    ctx.strokeRect(margin.left, margin.top, pw, ph);
+
+    ctx.save();
+    ctx.beginPath();
+    ctx.rect(margin.left, margin.top, pw, ph);
+    ctx.clip();
+    ctx.setLineDash([5, 4]);
+    ctx.strokeStyle = '#888';
+    ctx.textAlign = 'left';
+    ctx.textBaseline = 'top';
+    for (const m of spec.markers) {
+      if (m.x < view[0] || m.x > view[1]) continue;
+      line(X(m.x), margin.top, X(m.x), margin.top + ph);
+      ctx.fillText(m.label, X(m.x) + 4, margin.top + 4);
+    }
+    ctx.setLineDash([]);
+
+    // Every pixel column draws the first, lowest, highest and last sample that falls in it,
+    // so long signals stay fast without hiding narrow peaks
+    for (const s of spec.series) {
+      ctx.strokeStyle = s.color;
+      ctx.lineWidth = 1.2;
+      ctx.beginPath();
+      const [a, b] = visible(s, view);
+      let column = null, first, low, high, last, pen = false;
+      const flush = () => {
+        if (column === null) return;
+        if (pen) ctx.lineTo(column, Y(first)); else ctx.moveTo(column, Y(first));
+        ctx.lineTo(column, Y(low));
+        ctx.lineTo(column, Y(high));
+        ctx.lineTo(column, Y(last));
+        pen = true;
+      };
+      for (let i = a; i < b; i++) {
+        const y = s.y[i];
+        if (y === null) { flush(); column = null; pen = false; continue; }
+        const c = Math.round(X(s.x[i]));
+        if (c !== column) { flush(); column = c; first = low = high = last = y; continue; }
+        if (y < low) low = y;
+        if (y > high) high = y;
+        last = y;
+      }
+      flush();
+      ctx.stroke();
+    }
+
+    if (drag && drag.to !== undefined) {
+      ctx.fillStyle = 'rgba(70, 130, 180, 0.2)';
+      ctx.fillRect(Math.min(drag.from, drag.to), margin.top, Math.abs(drag.to - drag.from), ph);
+    }
+    if (hover !== null) {
+      ctx.strokeStyle = '#555';
+      line(hover, margin.top, hover, margin.top + ph);
+    }
+    ctx.restore();
+    describe();
+  }
+
+  function describe() {
+    const lines = [];
+    const x = hover === null ? null : toX(hover);
+    for (const s of spec.series) {
+      const [a, b] = visible(s, view);
+      let peak = -1;
+      for (let i = a; i < b; i++) {
+        if (s.x[i] >= view[0] && s.x[i] <= view[1] && s.y[i] !== null && (peak < 0 || s.y[i] > s.y[peak])) peak = i;
+      }
+      let text = s.name + ': ';
+      if (x !== null && s.x.length) {
+        let i = Math.min(s.x.length - 1, lowerBound(s.x, x));
+        if (i > 0 && x - s.x[i - 1] < s.x[i] - x) i--;
+        text += 'at ' + s.x[i].toPrecision(7) + ' -> ' + (s.y[i] === null ? '-' : s.y[i].toPrecision(5)) + ', ';
+      }
+      text += peak < 0 ? 'no data in view' : 'visible peak ' + s.y[peak].toPrecision(5) + ' at ' + s.x[peak].toPrecision(7);
+      lines.push(text);
+    }
+    info.textContent = lines.join('\n');
+  }
+
+  function clampView(lo, hi) {
+    const span = Math.min(hi - lo, full[1] - full[0]);
+    lo = Math.max(full[0], Math.min(lo, full[1] - span));
+    view = [lo, lo + span];
+  }
+
+  canvas.addEventListener('mousedown', e => { drag = { from: e.offsetX }; });
+  canvas.addEventListener('mousemove', e => {
+    hover = e.offsetX;
+    if (drag) drag.to = e.offsetX;
+    draw();
+  });
+  canvas.addEventListener('mouseup', () => {
+    if (drag && drag.to !== undefined && Math.abs(drag.to - drag.from) > 4) {
+      const a = toX(Math.min(drag.from, drag.to)), b = toX(Math.max(drag.from, drag.to));
+      clampView(a, b);
+    }
+    drag = null;
+    draw();
+  });
+  canvas.addEventListener('mouseleave', () => { hover = null; drag = null; draw(); });
+  canvas.addEventListener('dblclick', () => { view = full.slice(); draw(); });
+  canvas.addEventListener('wheel', e => {
+    e.preventDefault();
+    const factor = e.deltaY > 0 ? 1.25 : 0.8;
+    const c = toX(e.offsetX);
+    clampView(c - (c - view[0]) * factor, c + (view[1] - c) * factor);
+    draw();
+  }, { passive: false });
+  window.addEventListener('resize', draw);
+  draw();
+}
+
+for (const spec of CHARTS) {
+  const div = document.createElement('div');
+  document.getElementById('charts').appendChild(div);
+  Chart(div, spec);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_embeds_escaped_data() {
+        let page = InteractivePage::new("Q4 <spectra>").chart(
+            InteractiveChart::new("X(f) \"two-sided\"", "Frequency (Hz)", "Magnitude (dB)")
+                .series("X", [(0.0, 1.5), (1.0, f64::NEG_INFINITY), (2.0, 0.25)])
+                .waveform("x", &[0.5, -0.5], 2.0)
+                .marker(3500.0, "f_d </script>"),
+        );
+        let html = page.to_html();
+        assert!(html.contains("<title>Q4 &lt;spectra&gt;</title>"));
+        assert!(html.contains("\"x\":[0,1,2],\"y\":[1.5,null,0.25]"));
+        assert!(html.contains("\"x\":[0,0.5],\"y\":[0.5,-0.5]"));
+        assert!(html.contains("\"title\":\"X(f) \\\"two-sided\\\"\""));
+        // Only the page's own script element is closed
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert_eq!(page.charts[0].series[1].color, PALETTE[1]);
+        assert!(requested(&["--html".to_string()]));
+    }
+}
@@ -1,4 +1,4 @@
-use num_complex::Complex;
+use rustfft::num_complex::Complex;
 
 /// Apply ideal high-pass filter in frequency domain
 /// H_h(f) = 0 for |f| < f_c, 1 for |f| >= f_c
@@ -11,7 +11,7 @@ pub fn apply_highpass(
     let df = f_s / n as f64;
     let mut result = spectrum.to_vec();
     
-    for i in 0..n {
+    for (i, bin) in result.iter_mut().enumerate().take(n) {
         // Calculate frequency for this bin
         let freq = if i <= n / 2 {
             i as f64 * df
@@ -21,7 +21,7 @@ pub fn apply_highpass(
         
         // Apply ideal high-pass filter
         if freq.abs() < f_c {
-            result[i] = Complex::new(0.0, 0.0);
+            *bin = Complex::new(0.0, 0.0);
         }
     }
     
@@ -39,7 +39,7 @@ pub fn apply_lowpass(
     let df = f_s / n as f64;
     let mut result = spectrum.to_vec();
     
-    for i in 0..n {
+    for (i, bin) in result.iter_mut().enumerate().take(n) {
         // Calculate frequency for this bin
         let freq = if i <= n / 2 {
             i as f64 * df
@@ -49,7 +49,7 @@ pub fn apply_lowpass(
         
         // Apply ideal low-pass filter
         if freq.abs() > f_c {
-            result[i] = Complex::new(0.0, 0.0);
+            *bin = Complex::new(0.0, 0.0);
         }
     }
     
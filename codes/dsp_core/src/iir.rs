@@ -0,0 +1,286 @@
+use crate::error::{DspError, Result};
+use crate::progress::Progress;
+
+/// Samples filtered between progress bar updates
+const PROGRESS_CHUNK: usize = 1 << 16;
+
+/// Apply IIR filter using the transposed Direct Form II structure
+/// y[n] = sum(b[i] * x[n-i]) - sum(a[j] * y[n-j]) for j > 0
+///
+/// The coefficients are normalized by a[0] once up front, and the filter keeps one state
+/// word per order instead of shifting input and output histories every sample: each
+/// sample costs a single fused pass over `b`, `a` and the state, which the compiler
+/// vectorizes for the 8th-order filters from Q2.
+pub fn apply_filter(input: &[f64], b: &[f64], a: &[f64]) -> Vec<f64> {
+    let n = input.len();
+    let mut output = vec![0.0; n];
+    let mut filter = StreamingFilter::new(b, a);
+
+    let progress = Progress::new(n as u64, "IIR filtering");
+    for (block_index, (block_in, block_out)) in input
+        .chunks(PROGRESS_CHUNK)
+        .zip(output.chunks_mut(PROGRESS_CHUNK))
+        .enumerate()
+    {
+        if block_index > 0 {
+            progress.inc(PROGRESS_CHUNK as u64);
+        }
+        filter.process_into(block_in, block_out);
+    }
+
+    output
+}
+
+/// Check that `(b, a)` describe a filter: neither empty, every coefficient finite and
+/// a[0] nonzero
+pub fn validate(b: &[f64], a: &[f64]) -> Result<()> {
+    if b.is_empty() || a.is_empty() {
+        return Err(DspError::Data("filter has no b or no a coefficients".to_string()));
+    }
+    if let Some(c) = b.iter().chain(a).find(|c| !c.is_finite()) {
+        return Err(DspError::Data(format!("filter coefficient {} is not finite", c)));
+    }
+    if a[0] == 0.0 {
+        return Err(DspError::Data("filter coefficient a[0] is zero".to_string()));
+    }
+    Ok(())
+}
+
+/// Filter `x` through B(z) / A(z), like `scipy.signal.lfilter(b, a, x)`
+///
+/// Takes any designed filter: a Butterworth from Q2, a notch, or an FIR with `a = [1.0]`.
+/// The filter starts at rest; [`lfilter_with_state`] starts it elsewhere, and
+/// [`StreamingFilter`] filters a signal that arrives in blocks.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::iir::lfilter;
+///
+/// // y[n] = x[n] + 0.5 y[n-1]
+/// let y = lfilter(&[1.0], &[1.0, -0.5], &[1.0, 0.0, 0.0]).unwrap();
+/// assert_eq!(y, [1.0, 0.5, 0.25]);
+/// ```
+pub fn lfilter(b: &[f64], a: &[f64], x: &[f64]) -> Result<Vec<f64>> {
+    validate(b, a)?;
+    Ok(apply_filter(x, b, a))
+}
+
+/// [`lfilter`] from the delay-line state `zi`, returning the output and the final state
+///
+/// The state has max(len(b), len(a)) - 1 entries in scipy's layout (`zi` of
+/// `scipy.signal.lfilter`). Passing each block's final state as the next block's `zi`
+/// filters a long signal in blocks exactly as in one call; [`lfilter_zi`] gives the state
+/// that starts a signal without a transient.
+pub fn lfilter_with_state(b: &[f64], a: &[f64], x: &[f64], zi: &[f64]) -> Result<(Vec<f64>, Vec<f64>)> {
+    validate(b, a)?;
+    let mut filter = StreamingFilter::new(b, a);
+    filter.set_state(zi)?;
+    let y = filter.process(x);
+    Ok((y, filter.state().to_vec()))
+}
+
+/// Steady-state delay-line state for a unit step, like `scipy.signal.lfilter_zi`
+///
+/// Scaled by x[0], it starts [`lfilter_with_state`] as if the input had been at x[0]
+/// forever, so a signal with an offset does not ring at the start. Fails for a filter with
+/// a pole at z = 1, which has no steady state.
+pub fn lfilter_zi(b: &[f64], a: &[f64]) -> Result<Vec<f64>> {
+    validate(b, a)?;
+    let filter = StreamingFilter::new(b, a);
+    let (b, a) = (&filter.b, &filter.a);
+    let n = b.len() - 1;
+    // (I - Cᵀ) zi = b[1..] - a[1..] b[0], C the companion matrix of a
+    let mut m: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            let mut row = vec![0.0; n + 1];
+            row[i] = 1.0;
+            row[0] += a[i + 1];
+            if i + 1 < n {
+                row[i + 1] -= 1.0;
+            }
+            row[n] = b[i + 1] - a[i + 1] * b[0];
+            row
+        })
+        .collect();
+    // Gaussian elimination with partial pivoting
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs())).unwrap();
+        if m[pivot][col].abs() < 1e-12 {
+            return Err(DspError::Data("filter has a pole at z = 1, so no steady state".to_string()));
+        }
+        m.swap(col, pivot);
+        let (upper, lower) = m.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *x -= factor * p;
+            }
+        }
+    }
+    let mut zi = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| m[row][k] * zi[k]).sum();
+        zi[row] = (m[row][n] - sum) / m[row][row];
+    }
+    Ok(zi)
+}
+
+/// [`apply_filter`] for a signal that arrives block by block, e.g. from a live capture
+///
+/// The filter state carries over from one block to the next, so feeding a signal in blocks
+/// of any size gives exactly what [`apply_filter`] gives for the whole signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingFilter {
+    b: Vec<f64>,
+    a: Vec<f64>,
+    /// state[k] holds the partial sum of the terms delayed by k + 1 samples, plus a zero
+    /// pad so the update reads state[k + 1] without a bounds special case
+    state: Vec<f64>,
+}
+
+impl StreamingFilter {
+    /// Filter with the `(b, a)` coefficients normalized by a[0], at rest
+    pub fn new(b: &[f64], a: &[f64]) -> Self {
+        let order = b.len().max(a.len());
+        let mut b_norm = vec![0.0; order];
+        let mut a_norm = vec![0.0; order];
+        for (norm, &coef) in b_norm.iter_mut().zip(b) {
+            *norm = coef / a[0];
+        }
+        for (norm, &coef) in a_norm.iter_mut().zip(a) {
+            *norm = coef / a[0];
+        }
+        StreamingFilter { b: b_norm, a: a_norm, state: vec![0.0; order] }
+    }
+
+    /// Delay-line state after the last block, in scipy's `zi`/`zf` layout (see
+    /// [`lfilter_with_state`])
+    pub fn state(&self) -> &[f64] {
+        &self.state[..self.state.len().saturating_sub(1)]
+    }
+
+    /// Continue from the delay-line state `zi` instead of the current one
+    pub fn set_state(&mut self, zi: &[f64]) -> Result<()> {
+        let n = self.state.len().saturating_sub(1);
+        if zi.len() != n {
+            return Err(DspError::Data(format!("filter state has {} values, expected {}", zi.len(), n)));
+        }
+        self.state[..n].copy_from_slice(zi);
+        Ok(())
+    }
+
+    /// Filter the next block of the signal
+    pub fn process(&mut self, block: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0; block.len()];
+        self.process_into(block, &mut output);
+        output
+    }
+
+    /// Filter the next block of the signal into `output`, which must be as long as `block`
+    pub fn process_into(&mut self, block: &[f64], output: &mut [f64]) {
+        let order = self.state.len();
+        let (b, a, state) = (&self.b, &self.a, &mut self.state);
+        for (&x, out) in block.iter().zip(output.iter_mut()) {
+            let y = b[0] * x + state[0];
+            for k in 1..order {
+                state[k - 1] = b[k] * x - a[k] * y + state[k];
+            }
+            *out = y;
+        }
+    }
+
+    /// Forget the past input, as if the filter had just been created
+    pub fn reset(&mut self) {
+        self.state.iter_mut().for_each(|s| *s = 0.0);
+    }
+}
+
+/// Forward-backward filtering: no phase shift, magnitude response squared
+///
+/// The signal is filtered, reversed, filtered again and reversed back, so each
+/// component comes out aligned with the input but attenuated twice in dB.
+pub fn apply_filter_zero_phase(input: &[f64], b: &[f64], a: &[f64]) -> Vec<f64> {
+    let mut forward = apply_filter(input, b, a);
+    forward.reverse();
+    let mut output = apply_filter(&forward, b, a);
+    output.reverse();
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_filter() {
+        // Simple moving average filter: y[n] = 0.5*x[n] + 0.5*x[n-1]
+        let b = vec![0.5, 0.5];
+        let a = vec![1.0];
+        let input = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        
+        let output = apply_filter(&input, &b, &a);
+        
+        // Expected: [0.5, 1.5, 2.5, 3.5, 4.5]
+        assert!((output[0] - 0.5).abs() < 1e-10);
+        assert!((output[1] - 1.5).abs() < 1e-10);
+        assert!((output[2] - 2.5).abs() < 1e-10);
+
+        // Forward-backward, the two-tap average becomes symmetric: 0.25, 0.5, 0.25
+        let output = apply_filter_zero_phase(&input, &b, &a);
+        assert!((output[1] - 2.0).abs() < 1e-10);
+        assert!((output[2] - 3.0).abs() < 1e-10);
+
+        // One-pole recursion with a[0] != 1: y[n] = (x[n] + y[n-1]) / 2, impulse response 2^-n
+        let output = apply_filter(&[1.0, 0.0, 0.0, 0.0], &[1.0], &[2.0, -1.0]);
+        for (i, y) in output.iter().enumerate() {
+            assert!((y - 0.5f64.powi(i as i32 + 1)).abs() < 1e-12);
+        }
+
+        // Block by block, with uneven block sizes, matches the whole signal at once
+        let (b, a) = (vec![0.2, 0.3, 0.1], vec![1.0, -0.5, 0.25]);
+        let signal: Vec<f64> = (0..100).map(|n| ((n * 7919) % 23) as f64 - 11.0).collect();
+        let mut filter = StreamingFilter::new(&b, &a);
+        let mut streamed = Vec::new();
+        for block in signal.chunks(13).chain(std::iter::once(&[][..])) {
+            streamed.extend(filter.process(block));
+        }
+        assert_eq!(streamed, apply_filter(&signal, &b, &a));
+        filter.reset();
+        assert_eq!(filter.process(&signal[..5]), apply_filter(&signal[..5], &b, &a));
+    }
+
+    #[test]
+    fn test_lfilter_state_and_steady_state() {
+        let lowpass = crate::butterworth::ButterworthFilter::lowpass(4, 1000.0, 8000.0);
+        let (b, a) = (&lowpass.b, &lowpass.a);
+        let signal: Vec<f64> = (0..200).map(|n| ((n * 7919) % 23) as f64 - 11.0).collect();
+        assert_eq!(lfilter(b, a, &signal).unwrap(), apply_filter(&signal, b, a));
+
+        // Carrying the final state from block to block is the same as one call
+        let mut zi = vec![0.0; 4];
+        let mut blocks = Vec::new();
+        for block in signal.chunks(37) {
+            let (y, zf) = lfilter_with_state(b, a, block, &zi).unwrap();
+            blocks.extend(y);
+            zi = zf;
+        }
+        for (x, y) in blocks.iter().zip(apply_filter(&signal, b, a)) {
+            assert!((x - y).abs() < 1e-12);
+        }
+
+        // The steady state of a unit-DC-gain low-pass passes a constant without a transient
+        let zi: Vec<f64> = lfilter_zi(b, a).unwrap().iter().map(|z| z * 3.0).collect();
+        let (y, _) = lfilter_with_state(b, a, &[3.0; 50], &zi).unwrap();
+        assert!(y.iter().all(|y| (y - 3.0).abs() < 1e-9), "{:?}", &y[..5]);
+        // scipy.signal.lfilter_zi([1, 2], [1, -0.5]) = [5]
+        assert_eq!(lfilter_zi(&[1.0, 2.0], &[1.0, -0.5]).unwrap(), [5.0]);
+
+        assert!(lfilter(&[1.0], &[0.0, 1.0], &signal).is_err());
+        assert!(lfilter(&[], &[1.0], &signal).is_err());
+        assert!(lfilter_with_state(b, a, &signal, &[0.0; 3]).is_err());
+        // An integrator has no steady state
+        assert!(lfilter_zi(&[1.0], &[1.0, -1.0]).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Shared DSP building blocks used by the Q1–Q4 programs.
+//!
+//! Every module is public, so the crate also works as a small teaching library on its
+//! own. A typical chain designs a filter, filters a signal and looks at its spectrum:
+//!
+//! ```
+//! use dsp_core::butterworth::ButterworthFilter;
+//! use dsp_core::fir::FirFilter;
+//! use dsp_core::stft::{Stft, Window};
+//! use std::f64::consts::PI;
+//!
+//! // A 440 Hz tone with an unwanted 3 kHz tone on top
+//! let fs = 8000.0;
+//! let signal: Vec<f64> = (0..8000)
+//!     .map(|n| {
+//!         let t = n as f64 / fs;
+//!         (2.0 * PI * 440.0 * t).sin() + 0.5 * (2.0 * PI * 3000.0 * t).sin()
+//!     })
+//!     .collect();
+//!
+//! // Design: an IIR Butterworth gives transfer-function coefficients ...
+//! let iir = ButterworthFilter::lowpass(4, 1000.0, fs);
+//! assert_eq!((iir.b.len(), iir.a.len()), (5, 5));
+//!
+//! // ... and a linear-phase FIR filters a signal directly, without delaying it
+//! let filtered = FirFilter::lowpass(101, 1000.0, fs).apply(&signal);
+//! assert_eq!(filtered.len(), signal.len());
+//!
+//! // Spectrum: average the STFT frames and find the strongest bin
+//! let stft = Stft::compute(&filtered, fs, 1024, 512, Window::Hann);
+//! let magnitude = stft.magnitude();
+//! let average: Vec<f64> = (0..stft.frequencies.len())
+//!     .map(|bin| magnitude.iter().map(|frame| frame[bin]).sum::<f64>())
+//!     .collect();
+//! let peak = (0..average.len()).max_by(|&i, &j| average[i].total_cmp(&average[j])).unwrap();
+//! assert!((stft.frequencies[peak] - 440.0).abs() <= stft.bin_width());
+//! ```
+//!
+//! The module-level items carry smaller examples of their own; `cargo test --doc` runs
+//! them all.
+
+pub mod activity;
+pub mod agc;
+pub mod autocorrelation;
+pub mod batch;
+pub mod butterworth;
+pub mod calibration;
+pub mod capture;
+pub mod carrier_search;
+pub mod channel;
+pub mod channels;
+pub mod clipping;
+pub mod checkpoint;
+pub mod colormap;
+pub mod demodulator;
+pub mod denoise;
+pub mod error;
+pub mod filter_chain;
+pub mod fir;
+pub mod fixed_point;
+pub mod frequency_shifter;
+pub mod hilbert;
+pub mod html_plot;
+pub mod ideal_filter;
+pub mod iir;
+pub mod logging;
+pub mod loudness;
+pub mod mat;
+pub mod min_max;
+pub mod noise;
+pub mod npy;
+pub mod parallel;
+pub mod peaks;
+pub mod pipeline;
+pub mod plot_output;
+pub mod plot_style;
+pub mod playback;
+pub mod post_filter;
+pub mod progress;
+pub mod report;
+pub mod reproducible;
+pub mod resample;
+pub mod results;
+pub mod run_dir;
+pub mod signal_gen;
+pub mod simd;
+pub mod spectral_features;
+pub mod spectrum;
+pub mod stft;
+pub mod watch;
+pub mod wav_io;
@@ -0,0 +1,100 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Console logger behind the `log` macros of the Q1–Q4 programs and their libraries
+///
+/// Info and more verbose records go to stdout as bare lines, so the default output reads
+/// like plain progress messages; warnings and errors go to stderr.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Error | Level::Warn => eprintln!("{}", record.args()),
+            Level::Info => println!("{}", record.args()),
+            Level::Debug | Level::Trace => println!("[{}] {}", record.target(), record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+/// Verbosity from `-v`/`--verbose` and `-q`/`--quiet`, each of which may repeat (`-vv`, `-qq`)
+///
+/// The default is `Info`; every `v` moves one level towards `Trace`, every `q` one towards
+/// `Off` (`-q` keeps warnings, `-qq` only errors, `-qqq` nothing).
+pub fn level_from_args(args: &[String]) -> LevelFilter {
+    let mut verbosity: i32 = 0;
+    for arg in args {
+        match arg.as_str() {
+            "--verbose" => verbosity += 1,
+            "--quiet" => verbosity -= 1,
+            short if is_verbosity_flag(short) => {
+                verbosity += short.matches('v').count() as i32;
+                verbosity -= short.matches('q').count() as i32;
+            }
+            _ => {}
+        }
+    }
+    match verbosity {
+        i32::MIN..=-3 => LevelFilter::Off,
+        -2 => LevelFilter::Error,
+        -1 => LevelFilter::Warn,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Whether `arg` is one of the verbosity flags read by [`level_from_args`]
+///
+/// Programs with strict option parsing use this to skip them.
+pub fn is_verbosity_flag(arg: &str) -> bool {
+    match arg {
+        "--verbose" | "--quiet" => true,
+        _ => arg
+            .strip_prefix('-')
+            .is_some_and(|rest| !rest.is_empty() && (rest.bytes().all(|b| b == b'v') || rest.bytes().all(|b| b == b'q'))),
+    }
+}
+
+/// Install the console logger at `level`
+///
+/// Only the first call installs it; later calls just change the level.
+pub fn init(level: LevelFilter) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
+
+/// Install the console logger at the level given by `-v`/`-q` in `args`
+pub fn init_from_args(args: &[String]) {
+    init(level_from_args(args));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(level_from_args(&args(&[])), LevelFilter::Info);
+        assert_eq!(level_from_args(&args(&["-v"])), LevelFilter::Debug);
+        assert_eq!(level_from_args(&args(&["-vv"])), LevelFilter::Trace);
+        assert_eq!(level_from_args(&args(&["--verbose", "-v", "-v"])), LevelFilter::Trace);
+        assert_eq!(level_from_args(&args(&["-q"])), LevelFilter::Warn);
+        assert_eq!(level_from_args(&args(&["--quiet", "-q"])), LevelFilter::Error);
+        assert_eq!(level_from_args(&args(&["-qqq"])), LevelFilter::Off);
+        assert_eq!(level_from_args(&args(&["-v", "-q", "--agc"])), LevelFilter::Info);
+        assert!(!is_verbosity_flag("-vq"));
+        assert!(!is_verbosity_flag("-"));
+    }
+}
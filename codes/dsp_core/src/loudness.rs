@@ -0,0 +1,187 @@
+use crate::error::Result;
+use crate::filter_chain::FilterChain;
+use crate::results::Row;
+use crate::row;
+use std::f64::consts::PI;
+
+/// Gating block length and step of ITU-R BS.1770 (s)
+const BLOCK_SECONDS: f64 = 0.4;
+const STEP_SECONDS: f64 = 0.1;
+/// Absolute gate (LUFS) and relative gate below the ungated loudness (LU)
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+/// The two K-weighting biquads of BS.1770 at `sample_rate`, as sections
+/// `[b0, b1, b2, a0, a1, a2]`: a +4 dB high shelf above about 1.7 kHz (the head) and the
+/// RLB high-pass at 38 Hz
+///
+/// The analog prototypes are mapped with the bilinear transform, so at 48 kHz the
+/// coefficients are those tabulated in the standard and other rates get the same curve.
+pub fn k_weighting(sample_rate: f64) -> Vec<[f64; 6]> {
+    // High shelf
+    let (gain_db, f0, q) = (3.999843853973347, 1681.974450955533, 0.7071752369554196);
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = [
+        vh + vb * k / q + k * k,
+        2.0 * (k * k - vh),
+        vh - vb * k / q + k * k,
+        1.0 + k / q + k * k,
+        2.0 * (k * k - 1.0),
+        1.0 - k / q + k * k,
+    ];
+    // RLB high-pass, unnormalized numerator as in the standard
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = [a0, -2.0 * a0, a0, a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k];
+    vec![shelf, highpass]
+}
+
+/// Loudness and level of a signal, from [`Levels::measure`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Levels {
+    /// Gated integrated loudness (LUFS); −∞ for silence or less than one 400 ms block
+    pub integrated_lufs: f64,
+    /// RMS over all samples and channels, relative to full scale (a full-scale sine reads
+    /// −3.01 dBFS)
+    pub rms_dbfs: f64,
+    /// Largest absolute sample value (dBFS)
+    pub peak_dbfs: f64,
+}
+
+impl Levels {
+    /// Measure `channels` (full scale ±1) at `sample_rate`
+    ///
+    /// The integrated loudness follows BS.1770-4: the K-weighted mean square of each channel
+    /// in 400 ms blocks with 75 % overlap, summed over the channels (all weighted 1, as for
+    /// left/right), then averaged over the blocks that pass the −70 LUFS absolute gate and the
+    /// relative gate 10 LU below the loudness of those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::loudness::Levels;
+    ///
+    /// // A full-scale 997 Hz sine reads −3.01 LUFS, the reference point of the standard
+    /// let fs = 48000.0;
+    /// let sine: Vec<f64> = (0..3 * 48000).map(|n| (2.0 * std::f64::consts::PI * 997.0 * n as f64 / fs).sin()).collect();
+    /// let levels = Levels::measure(&[sine], fs).unwrap();
+    /// assert!((levels.integrated_lufs + 3.01).abs() < 0.02);
+    /// assert!((levels.rms_dbfs + 3.01).abs() < 0.01 && levels.peak_dbfs.abs() < 0.01);
+    /// ```
+    pub fn measure<C: AsRef<[f64]>>(channels: &[C], sample_rate: f64) -> Result<Self> {
+        let channels: Vec<&[f64]> = channels.iter().map(AsRef::as_ref).collect();
+        let samples = channels.iter().map(|c| c.len()).sum::<usize>().max(1) as f64;
+        let energy: f64 = channels.iter().flat_map(|c| c.iter()).map(|x| x * x).sum();
+        let peak = channels.iter().flat_map(|c| c.iter()).fold(0.0f64, |max, x| max.max(x.abs()));
+
+        let chain = FilterChain::new().sos(&k_weighting(sample_rate));
+        let weighted = channels.iter().map(|c| chain.apply(c)).collect::<Result<Vec<_>>>()?;
+        let block = (BLOCK_SECONDS * sample_rate).round() as usize;
+        let step = (STEP_SECONDS * sample_rate).round() as usize;
+        let len = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+        // Sum over the channels of each block's mean square
+        let blocks: Vec<f64> = if block == 0 || len < block {
+            Vec::new()
+        } else {
+            (0..=(len - block) / step)
+                .map(|j| {
+                    weighted
+                        .iter()
+                        .map(|c| c[j * step..j * step + block].iter().map(|x| x * x).sum::<f64>() / block as f64)
+                        .sum()
+                })
+                .collect()
+        };
+        let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+        let gated_mean = |gate: f64| {
+            let passed: Vec<f64> = blocks.iter().copied().filter(|&z| loudness(z) > gate).collect();
+            if passed.is_empty() {
+                0.0
+            } else {
+                passed.iter().sum::<f64>() / passed.len() as f64
+            }
+        };
+        let relative_gate = loudness(gated_mean(ABSOLUTE_GATE)) + RELATIVE_GATE;
+
+        Ok(Levels {
+            integrated_lufs: loudness(gated_mean(relative_gate.max(ABSOLUTE_GATE))),
+            rms_dbfs: 10.0 * (energy / samples).log10(),
+            peak_dbfs: 20.0 * peak.log10(),
+        })
+    }
+
+    /// A results-table row `signal, integrated_lufs, rms_dbfs, peak_dbfs`
+    pub fn row(&self, signal: &str) -> Row {
+        row![
+            ("signal", signal),
+            ("integrated_lufs", self.integrated_lufs),
+            ("rms_dbfs", self.rms_dbfs),
+            ("peak_dbfs", self.peak_dbfs)
+        ]
+    }
+}
+
+impl std::fmt::Display for Levels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} LUFS, RMS {:.1} dBFS, peak {:.1} dBFS",
+            self.integrated_lufs, self.rms_dbfs, self.peak_dbfs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_k_weighting_matches_the_standard_at_48k() {
+        let sections = k_weighting(48000.0);
+        let normalized: Vec<f64> = sections.iter().flat_map(|s| s.iter().map(move |c| c / s[3])).collect();
+        let expected = [
+            1.53512485958697,
+            -2.69169618940638,
+            1.19839281085285,
+            1.0,
+            -1.69065929318241,
+            0.73248077421585,
+            1.0,
+            -2.0,
+            1.0,
+            1.0,
+            -1.99004745483398,
+            0.99007225036621,
+        ];
+        for (c, e) in normalized.iter().zip(&expected) {
+            assert!((c - e).abs() < 1e-6, "{} vs {}", c, e);
+        }
+    }
+
+    #[test]
+    fn test_gating_ignores_silence() {
+        let fs = 44100.0;
+        let tone: Vec<f64> = (0..4 * 44100).map(|n| 0.1 * (2.0 * PI * 997.0 * n as f64 / fs).sin()).collect();
+        let alone = Levels::measure(&[&tone], fs).unwrap();
+        assert!((alone.integrated_lufs + 23.01).abs() < 0.05, "{}", alone);
+
+        // Silence after the tone lowers the RMS by 3 dB; the gate drops the silent blocks, and
+        // only the few straddling the end of the tone pull the loudness down a little
+        let mut padded = tone.clone();
+        padded.extend(vec![0.0; 4 * 44100]);
+        let gated = Levels::measure(&[padded], fs).unwrap();
+        assert!((gated.integrated_lufs - alone.integrated_lufs).abs() < 0.3, "{} vs {}", gated, alone);
+        assert!((alone.rms_dbfs - gated.rms_dbfs - 3.01).abs() < 0.01);
+
+        // Two equal channels add 3 dB of loudness
+        let stereo = Levels::measure(&[&tone, &tone], fs).unwrap();
+        assert!((stereo.integrated_lufs - alone.integrated_lufs - 3.01).abs() < 0.05);
+
+        // Too short to fill a block, or silent: no loudness
+        assert_eq!(Levels::measure(&[vec![0.5; 1000]], fs).unwrap().integrated_lufs, f64::NEG_INFINITY);
+        assert_eq!(Levels::measure(&[vec![0.0; 44100]], fs).unwrap().integrated_lufs, f64::NEG_INFINITY);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::{DspError, Result};
+use crate::npy::Array;
+use rustfft::num_complex::Complex;
+use std::path::Path;
+
+/// Whether `--mat` was given on the command line
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--mat")
+}
+
+// Data types and the array class used in a level 5 MAT-file
+const MI_INT8: u32 = 1;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_DOUBLE: u32 = 9;
+const MI_MATRIX: u32 = 14;
+const MX_DOUBLE_CLASS: u32 = 6;
+const COMPLEX_FLAG: u32 = 0x0800;
+
+/// Named arrays as a MATLAB level 5 MAT-file, loadable with `load` in MATLAB or Octave
+///
+/// Every array becomes an N×1 double column vector (complex for spectra). Data is not
+/// compressed, so files are readable by any MATLAB since 5.0.
+pub fn to_bytes(arrays: &[(String, Array)]) -> Result<Vec<u8>> {
+    let mut header = String::from("MATLAB 5.0 MAT-file, Platform: dsp_core");
+    header.push_str(&" ".repeat(116 - header.len()));
+    let mut bytes = header.into_bytes();
+    // No subsystem data; version 0x0100; "IM" marks little-endian
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend_from_slice(&0x0100u16.to_le_bytes());
+    bytes.extend_from_slice(b"IM");
+
+    for (name, array) in arrays {
+        let valid = name.len() <= 63
+            && name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(DspError::Data(format!("'{}' is not a valid MATLAB variable name", name)));
+        }
+        let rows = i32::try_from(array.len()).map_err(|_| DspError::Data(format!("{}: array too large for .mat", name)))?;
+
+        let mut matrix = Vec::new();
+        let flags = match array {
+            Array::Real(_) => MX_DOUBLE_CLASS,
+            Array::Complex(_) => MX_DOUBLE_CLASS | COMPLEX_FLAG,
+        };
+        element(&mut matrix, MI_UINT32, &[flags.to_le_bytes(), [0; 4]].concat());
+        element(&mut matrix, MI_INT32, &[rows.to_le_bytes(), 1i32.to_le_bytes()].concat());
+        element(&mut matrix, MI_INT8, name.as_bytes());
+        match array {
+            Array::Real(values) => element(&mut matrix, MI_DOUBLE, &doubles(values.iter().copied())),
+            Array::Complex(values) => {
+                element(&mut matrix, MI_DOUBLE, &doubles(values.iter().map(|c| c.re)));
+                element(&mut matrix, MI_DOUBLE, &doubles(values.iter().map(|c| c.im)));
+            }
+        }
+        let size = u32::try_from(matrix.len()).map_err(|_| DspError::Data(format!("{}: array too large for .mat", name)))?;
+        bytes.extend_from_slice(&MI_MATRIX.to_le_bytes());
+        bytes.extend_from_slice(&size.to_le_bytes());
+        bytes.extend_from_slice(&matrix);
+    }
+    Ok(bytes)
+}
+
+/// Read the double vectors of a MAT-file written by [`to_bytes`]
+///
+/// Other classes, compressed variables and big-endian files are rejected.
+pub fn from_bytes(bytes: &[u8]) -> Result<Vec<(String, Array)>> {
+    if bytes.len() < 128 || !bytes.starts_with(b"MATLAB 5.0 MAT-file") || &bytes[126..128] != b"IM" {
+        return Err(DspError::Data("not a little-endian level 5 MAT-file".to_string()));
+    }
+    let mut arrays = Vec::new();
+    let mut rest = &bytes[128..];
+    while !rest.is_empty() {
+        let (kind, matrix, next) = next_element(rest)?;
+        if kind != MI_MATRIX {
+            return Err(DspError::Data(format!("unsupported data element type {}", kind)));
+        }
+        rest = next;
+
+        let (_, flags, fields) = next_element(matrix)?;
+        let (_, dims, fields) = next_element(fields)?;
+        let (_, name, fields) = next_element(fields)?;
+        let name = String::from_utf8_lossy(name).to_string();
+        let flags = flags
+            .get(..4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| DspError::Data("truncated array flags".to_string()))?;
+        if flags & 0xff != MX_DOUBLE_CLASS || dims.len() != 8 {
+            return Err(DspError::Data(format!("{}: only double vectors are supported", name)));
+        }
+        let (_, real, fields) = next_element(fields)?;
+        let real: Vec<f64> = real.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect();
+        let array = if flags & COMPLEX_FLAG != 0 {
+            let (_, imag, _) = next_element(fields)?;
+            let imag = imag.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap()));
+            Array::Complex(real.iter().zip(imag).map(|(&re, im)| Complex::new(re, im)).collect())
+        } else {
+            Array::Real(real)
+        };
+        arrays.push((name, array));
+    }
+    Ok(arrays)
+}
+
+pub fn save(path: impl AsRef<Path>, arrays: &[(String, Array)]) -> Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, to_bytes(arrays)?).map_err(|e| DspError::io(path, e))
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<(String, Array)>> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| DspError::io(path, e))?;
+    from_bytes(&bytes).map_err(|e| e.context(path.display()))
+}
+
+fn doubles(values: impl Iterator<Item = f64>) -> Vec<u8> {
+    values.flat_map(f64::to_le_bytes).collect()
+}
+
+/// Append a tagged data element, padded to a multiple of 8 bytes
+fn element(out: &mut Vec<u8>, kind: u32, data: &[u8]) {
+    out.extend_from_slice(&kind.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    out.resize(out.len() + (8 - data.len() % 8) % 8, 0);
+}
+
+/// Split off the first data element: (type, data, remaining bytes)
+fn next_element(bytes: &[u8]) -> Result<(u32, &[u8], &[u8])> {
+    let truncated = || DspError::Data("truncated data element".to_string());
+    let word = |i: usize| bytes.get(i..i + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+    let tag = word(0).ok_or_else(truncated)?;
+    // Small data element format: type and size share the first word, data fills the second
+    if tag >> 16 != 0 {
+        let size = (tag >> 16) as usize;
+        return Ok((tag & 0xffff, bytes.get(4..4 + size.min(4)).ok_or_else(truncated)?, &bytes[8.min(bytes.len())..]));
+    }
+    let size = word(4).ok_or_else(truncated)? as usize;
+    let data = bytes.get(8..8 + size).ok_or_else(truncated)?;
+    let padded = if tag == MI_MATRIX { size } else { size.div_ceil(8) * 8 };
+    Ok((tag, data, bytes.get(8 + padded..).unwrap_or(&[])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mat_round_trip() {
+        let b = vec![0.25, 0.5, 0.25];
+        let spectrum: Vec<Complex<f64>> = (0..5).map(|k| Complex::new(k as f64, -0.5 * k as f64)).collect();
+        let arrays = vec![
+            ("b".to_string(), Array::from(b)),
+            ("X_h".to_string(), Array::from(spectrum)),
+            ("empty".to_string(), Array::Real(Vec::new())),
+        ];
+        let bytes = to_bytes(&arrays).unwrap();
+        assert_eq!(bytes.len() % 8, 0);
+        // "b": flags 16 + dims 16 + name 16 (padded from 1 byte) + data 8+24
+        assert_eq!(u32::from_le_bytes(bytes[132..136].try_into().unwrap()), 16 + 16 + 16 + 32);
+        assert_eq!(from_bytes(&bytes).unwrap(), arrays);
+
+        assert!(to_bytes(&[("2x".to_string(), Array::Real(vec![1.0]))]).is_err());
+        assert!(from_bytes(&bytes[..100]).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+/// Smallest and largest sample of one plot column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    /// Index of the column's first sample
+    pub start: usize,
+    /// One past the column's last sample
+    pub end: usize,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Column {
+    /// Centre of the column in samples, for placing it on a time axis
+    pub fn center(&self) -> f64 {
+        (self.start + self.end - 1) as f64 / 2.0
+    }
+}
+
+/// Reduce `samples` to at most `columns` min/max pairs, one per horizontal pixel
+///
+/// Runs differ in length by at most one sample and together cover every sample, so no peak
+/// is lost the way it is when a long signal is drawn as a polyline or strided. With fewer
+/// samples than columns each sample is its own column.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::min_max::min_max_columns;
+///
+/// // A one-sample spike survives the reduction to two columns
+/// let columns = min_max_columns(&[0.0, 0.1, 5.0, 0.0, -0.2, 0.1], 2);
+/// assert_eq!((columns[0].min, columns[0].max), (0.0, 5.0));
+/// assert_eq!((columns[1].min, columns[1].max), (-0.2, 0.1));
+/// ```
+pub fn min_max_columns(samples: &[f64], columns: usize) -> Vec<Column> {
+    let n = samples.len();
+    let columns = columns.clamp(1, n.max(1));
+    (0..columns)
+        .map(|k| (k * n / columns, (k + 1) * n / columns))
+        .filter(|(start, end)| end > start)
+        .map(|(start, end)| {
+            let run = &samples[start..end];
+            Column {
+                start,
+                end,
+                min: run.iter().copied().fold(f64::INFINITY, f64::min),
+                max: run.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            }
+        })
+        .collect()
+}
+
+/// Closed outline of the band between the minima and maxima, as a polygon: maxima left to
+/// right, then minima right to left, with `x` mapping a sample position to the x axis
+pub fn band_outline(columns: &[Column], x: impl Fn(f64) -> f64) -> Vec<(f64, f64)> {
+    let upper = columns.iter().map(|c| (x(c.center()), c.max));
+    let lower = columns.iter().rev().map(|c| (x(c.center()), c.min));
+    upper.chain(lower).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_keep_every_peak() {
+        let mut samples = vec![0.0; 1000];
+        samples[333] = 5.0;
+        samples[334] = -4.0;
+        let columns = min_max_columns(&samples, 7);
+        assert_eq!(columns.len(), 7);
+        assert_eq!((columns[0].start, columns[6].end), (0, 1000));
+        assert!(columns.windows(2).all(|w| w[0].end == w[1].start));
+        assert_eq!(columns.iter().map(|c| c.max).fold(f64::MIN, f64::max), 5.0);
+        assert_eq!(columns.iter().map(|c| c.min).fold(f64::MAX, f64::min), -4.0);
+
+        // Short signals keep one column per sample
+        assert_eq!(min_max_columns(&[1.0, 2.0], 100).len(), 2);
+        assert!(min_max_columns(&[], 100).is_empty());
+        assert_eq!(band_outline(&columns, |i| i).len(), 14);
+    }
+}
@@ -0,0 +1,183 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// Spectral shape of a noise signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoiseKind {
+    /// Flat spectrum
+    #[default]
+    White,
+    /// 1/f spectrum (-3 dB per octave)
+    Pink,
+    /// 1/f^2 spectrum (-6 dB per octave), integrated white noise
+    Brown,
+}
+
+impl FromStr for NoiseKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" => Ok(NoiseKind::White),
+            "pink" => Ok(NoiseKind::Pink),
+            "brown" | "brownian" | "red" => Ok(NoiseKind::Brown),
+            other => Err(format!("Unknown noise kind '{}' (expected white, pink or brown)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for NoiseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoiseKind::White => write!(f, "white"),
+            NoiseKind::Pink => write!(f, "pink"),
+            NoiseKind::Brown => write!(f, "brown"),
+        }
+    }
+}
+
+/// Seeded standard normal generator (xorshift64 + Box-Muller)
+///
+/// The same seed always gives the same sequence, so noisy tests are reproducible.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::noise::GaussianSource;
+///
+/// let (mut a, mut b) = (GaussianSource::new(7), GaussianSource::new(7));
+/// assert_eq!(a.sample(), b.sample());
+/// ```
+pub struct GaussianSource {
+    state: u64,
+    spare: Option<f64>,
+}
+
+impl GaussianSource {
+    pub fn new(seed: u64) -> Self {
+        GaussianSource {
+            state: seed.max(1),
+            spare: None,
+        }
+    }
+
+    /// Uniform in (0, 1]
+    fn uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        ((self.state >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Next sample with zero mean and unit variance
+    pub fn sample(&mut self) -> f64 {
+        if let Some(z) = self.spare.take() {
+            return z;
+        }
+        let r = (-2.0 * self.uniform().ln()).sqrt();
+        let theta = 2.0 * PI * self.uniform();
+        self.spare = Some(r * theta.sin());
+        r * theta.cos()
+    }
+}
+
+/// `len` samples of noise with the given shape, scaled to unit mean power
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::noise::{generate, NoiseKind};
+///
+/// let pink = generate(NoiseKind::Pink, 10_000, 1);
+/// let power = pink.iter().map(|x| x * x).sum::<f64>() / pink.len() as f64;
+/// assert!((power - 1.0).abs() < 1e-9);
+/// ```
+pub fn generate(kind: NoiseKind, len: usize, seed: u64) -> Vec<f64> {
+    let noise = match kind {
+        NoiseKind::White => white(len, seed),
+        NoiseKind::Pink => pink(len, seed),
+        NoiseKind::Brown => brown(len, seed),
+    };
+    normalize_power(noise)
+}
+
+/// Gaussian white noise with unit variance
+pub fn white(len: usize, seed: u64) -> Vec<f64> {
+    let mut rng = GaussianSource::new(seed);
+    (0..len).map(|_| rng.sample()).collect()
+}
+
+/// Pink noise from white noise through Paul Kellett's 7-pole 1/f filter
+///
+/// Accurate to about 0.05 dB above f_s / 2000; not normalized.
+pub fn pink(len: usize, seed: u64) -> Vec<f64> {
+    let mut rng = GaussianSource::new(seed);
+    let mut b = [0.0f64; 7];
+    (0..len)
+        .map(|_| {
+            let w = rng.sample();
+            b[0] = 0.99886 * b[0] + w * 0.0555179;
+            b[1] = 0.99332 * b[1] + w * 0.0750759;
+            b[2] = 0.96900 * b[2] + w * 0.1538520;
+            b[3] = 0.86650 * b[3] + w * 0.3104856;
+            b[4] = 0.55000 * b[4] + w * 0.5329522;
+            b[5] = -0.7616 * b[5] - w * 0.0168980;
+            let pink = b.iter().sum::<f64>() + w * 0.5362;
+            b[6] = w * 0.115926;
+            pink
+        })
+        .collect()
+}
+
+/// Brownian noise: white noise through a slightly leaky integrator
+///
+/// The leak puts the 1/f^2 corner near f_s / 3000 so the output cannot wander off
+/// without bound; not normalized.
+pub fn brown(len: usize, seed: u64) -> Vec<f64> {
+    const LEAK: f64 = 0.998;
+    let mut rng = GaussianSource::new(seed);
+    let mut level = 0.0;
+    (0..len)
+        .map(|_| {
+            level = LEAK * level + rng.sample();
+            level
+        })
+        .collect()
+}
+
+fn normalize_power(mut noise: Vec<f64>) -> Vec<f64> {
+    let power = noise.iter().map(|x| x * x).sum::<f64>() / noise.len().max(1) as f64;
+    if power > 0.0 {
+        let gain = power.sqrt().recip();
+        noise.iter_mut().for_each(|x| *x *= gain);
+    }
+    noise
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators_are_seeded_and_shaped() {
+        let lag1 = |x: &[f64]| {
+            x.windows(2).map(|w| w[0] * w[1]).sum::<f64>() / x.iter().map(|v| v * v).sum::<f64>()
+        };
+
+        let mut correlations = Vec::new();
+        for kind in [NoiseKind::White, NoiseKind::Pink, NoiseKind::Brown] {
+            let noise = generate(kind, 50_000, 3);
+            assert_eq!(noise, generate(kind, 50_000, 3), "{} is not reproducible", kind);
+            assert_ne!(noise, generate(kind, 50_000, 4));
+
+            let power = noise.iter().map(|x| x * x).sum::<f64>() / noise.len() as f64;
+            assert!((power - 1.0).abs() < 1e-9);
+            correlations.push(lag1(&noise));
+        }
+
+        // Redder spectra are smoother from one sample to the next
+        assert!(correlations[0].abs() < 0.02, "white: {}", correlations[0]);
+        assert!(correlations[1] > correlations[0] + 0.2, "pink: {}", correlations[1]);
+        assert!(correlations[2] > 0.99, "brown: {}", correlations[2]);
+    }
+}
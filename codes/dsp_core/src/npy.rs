@@ -0,0 +1,346 @@
+use crate::error::{DspError, Result};
+use rustfft::num_complex::Complex;
+use std::path::Path;
+
+/// Whether `--npz` was given on the command line
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--npz")
+}
+
+/// A 1-D array in one of the dtypes NumPy and this crate exchange
+#[derive(Debug, Clone, PartialEq)]
+pub enum Array {
+    /// float64 (`<f8`), for signals, frequency axes and magnitudes
+    Real(Vec<f64>),
+    /// complex128 (`<c16`), for spectra
+    Complex(Vec<Complex<f64>>),
+}
+
+impl From<&[f64]> for Array {
+    fn from(values: &[f64]) -> Self {
+        Array::Real(values.to_vec())
+    }
+}
+
+impl From<Vec<f64>> for Array {
+    fn from(values: Vec<f64>) -> Self {
+        Array::Real(values)
+    }
+}
+
+impl From<&[Complex<f64>]> for Array {
+    fn from(values: &[Complex<f64>]) -> Self {
+        Array::Complex(values.to_vec())
+    }
+}
+
+impl From<Vec<Complex<f64>>> for Array {
+    fn from(values: Vec<Complex<f64>>) -> Self {
+        Array::Complex(values)
+    }
+}
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+impl Array {
+    pub fn len(&self) -> usize {
+        match self {
+            Array::Real(values) => values.len(),
+            Array::Complex(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn descr(&self) -> &'static str {
+        match self {
+            Array::Real(_) => "<f8",
+            Array::Complex(_) => "<c16",
+        }
+    }
+
+    /// The array in NumPy `.npy` format (version 1.0, little-endian, C order)
+    pub fn to_npy(&self) -> Vec<u8> {
+        let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': ({},), }}", self.descr(), self.len());
+        // Magic, version and length take 10 bytes; the header is padded so data starts on 64 bytes
+        let padding = 63 - (10 + header.len()) % 64;
+        header.push_str(&" ".repeat(padding));
+        header.push('\n');
+
+        let mut bytes = Vec::with_capacity(10 + header.len() + 16 * self.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        match self {
+            Array::Real(values) => values.iter().for_each(|v| bytes.extend_from_slice(&v.to_le_bytes())),
+            Array::Complex(values) => values.iter().for_each(|c| {
+                bytes.extend_from_slice(&c.re.to_le_bytes());
+                bytes.extend_from_slice(&c.im.to_le_bytes());
+            }),
+        }
+        bytes
+    }
+
+    /// Parse a 1-D float64 or complex128 `.npy` file, as written by [`to_npy`](Self::to_npy)
+    /// or `numpy.save`
+    pub fn from_npy(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 10 || &bytes[..6] != MAGIC || bytes[6] != 1 {
+            return Err(DspError::Data("not a version 1 .npy file".to_string()));
+        }
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        let header = bytes
+            .get(10..10 + header_len)
+            .ok_or_else(|| DspError::Data("truncated header".to_string()))?;
+        let header = std::str::from_utf8(header).map_err(|_| DspError::Data("header is not text".to_string()))?;
+        if !header.contains("'fortran_order': False") {
+            return Err(DspError::Data("expected a C-order array".to_string()));
+        }
+        let shape = header
+            .split("'shape': (")
+            .nth(1)
+            .and_then(|rest| rest.split([',', ')']).next())
+            .and_then(|n| n.trim().parse::<usize>().ok())
+            .ok_or_else(|| DspError::Data("expected a 1-D shape".to_string()))?;
+
+        let data = &bytes[10 + header_len..];
+        let value = |chunk: &[u8]| f64::from_le_bytes(chunk.try_into().unwrap());
+        let (width, array) = if header.contains("'descr': '<f8'") {
+            (8, Array::Real(data.chunks_exact(8).map(value).collect()))
+        } else if header.contains("'descr': '<c16'") {
+            (16, Array::Complex(data.chunks_exact(16).map(|c| Complex::new(value(&c[..8]), value(&c[8..]))).collect()))
+        } else {
+            return Err(DspError::Data("expected a little-endian float64 or complex128 array".to_string()));
+        };
+        if data.len() != width * shape {
+            return Err(DspError::Data("data length does not match shape".to_string()));
+        }
+        Ok(array)
+    }
+}
+
+/// Write one array as a `.npy` file
+pub fn save(path: impl AsRef<Path>, array: impl Into<Array>) -> Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, array.into().to_npy()).map_err(|e| DspError::io(path, e))
+}
+
+/// Read a `.npy` file
+pub fn load(path: impl AsRef<Path>) -> Result<Array> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| DspError::io(path, e))?;
+    Array::from_npy(&bytes).map_err(|e| e.context(path.display()))
+}
+
+/// Named arrays in one `.npz` archive, the format of `numpy.savez`
+///
+/// Entries are stored uncompressed, so `numpy.load(path)["x_l"]` reads them directly.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Npz {
+    pub arrays: Vec<(String, Array)>,
+}
+
+impl Npz {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn array(mut self, name: &str, array: impl Into<Array>) -> Self {
+        self.arrays.push((name.to_string(), array.into()));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Array> {
+        self.arrays.iter().find(|(n, _)| n == name).map(|(_, a)| a)
+    }
+
+    /// The float64 array `name`; an error names it when it is missing or complex
+    pub fn real(&self, name: &str) -> Result<&[f64]> {
+        match self.get(name) {
+            Some(Array::Real(values)) => Ok(values),
+            Some(Array::Complex(_)) => Err(DspError::Data(format!("array '{}' is complex, expected float64", name))),
+            None => Err(DspError::Data(format!("no array '{}'", name))),
+        }
+    }
+
+    /// The complex128 array `name`; an error names it when it is missing or real
+    pub fn complex(&self, name: &str) -> Result<&[Complex<f64>]> {
+        match self.get(name) {
+            Some(Array::Complex(values)) => Ok(values),
+            Some(Array::Real(_)) => Err(DspError::Data(format!("array '{}' is float64, expected complex", name))),
+            None => Err(DspError::Data(format!("no array '{}'", name))),
+        }
+    }
+
+    /// The first element of the float64 array `name`, for parameters stored as one-element
+    /// arrays such as `f_s`
+    pub fn scalar(&self, name: &str) -> Result<f64> {
+        self.real(name)?
+            .first()
+            .copied()
+            .ok_or_else(|| DspError::Data(format!("array '{}' is empty", name)))
+    }
+
+    /// The archive as a ZIP file holding `<name>.npy` per array
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        // DOS date 1980-01-01, time 00:00, so identical arrays give identical files
+        const DATE: u16 = (1 << 5) | 1;
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+        for (name, array) in &self.arrays {
+            let file = format!("{}.npy", name);
+            let data = array.to_npy();
+            let size = u32::try_from(data.len()).map_err(|_| DspError::Data(format!("{}: array too large for .npz", name)))?;
+            let offset = u32::try_from(bytes.len()).map_err(|_| DspError::Data("archive too large for .npz".to_string()))?;
+            let crc = crc32(&data);
+            // Fields shared by the local and the central header, from "version needed" on
+            let mut common = Vec::new();
+            for field in [20u16, 0, 0, 0, DATE] {
+                common.extend_from_slice(&field.to_le_bytes());
+            }
+            for field in [crc, size, size] {
+                common.extend_from_slice(&field.to_le_bytes());
+            }
+            common.extend_from_slice(&(file.len() as u16).to_le_bytes());
+            common.extend_from_slice(&0u16.to_le_bytes());
+
+            bytes.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+            bytes.extend_from_slice(&common);
+            bytes.extend_from_slice(file.as_bytes());
+            bytes.extend_from_slice(&data);
+
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes());
+            directory.extend_from_slice(&common);
+            // Comment length, disk number, internal and external attributes
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(file.as_bytes());
+        }
+        let count = self.arrays.len() as u16;
+        let directory_offset = bytes.len() as u32;
+        bytes.extend_from_slice(&directory);
+        bytes.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&count.to_le_bytes());
+        bytes.extend_from_slice(&count.to_le_bytes());
+        bytes.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&directory_offset.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Read an archive of uncompressed float64/complex128 arrays, such as one written by
+    /// [`save`](Self::save) or `numpy.savez` (not `numpy.savez_compressed`)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let u16_at = |i: usize| bytes.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+        let u32_at = |i: usize| bytes.get(i..i + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+        let truncated = || DspError::Data("truncated .npz archive".to_string());
+
+        // The end-of-directory record closes the file; its comment is normally empty
+        let end = (0..bytes.len().saturating_sub(21))
+            .rev()
+            .find(|&i| bytes[i..i + 4] == 0x0605_4b50u32.to_le_bytes())
+            .ok_or_else(|| DspError::Data("not a .npz (ZIP) archive".to_string()))?;
+        let count = u16_at(end + 10).ok_or_else(truncated)?;
+        let mut entry = u32_at(end + 16).ok_or_else(truncated)?;
+
+        let mut npz = Npz::new();
+        for _ in 0..count {
+            if u32_at(entry) != Some(0x0201_4b50) {
+                return Err(DspError::Data("corrupt .npz central directory".to_string()));
+            }
+            let method = u16_at(entry + 10).ok_or_else(truncated)?;
+            let size = u32_at(entry + 20).ok_or_else(truncated)?;
+            let name_len = u16_at(entry + 28).ok_or_else(truncated)?;
+            let extra_len = u16_at(entry + 30).ok_or_else(truncated)?;
+            let comment_len = u16_at(entry + 32).ok_or_else(truncated)?;
+            let local = u32_at(entry + 42).ok_or_else(truncated)?;
+            let file = bytes.get(entry + 46..entry + 46 + name_len).ok_or_else(truncated)?;
+            let file = String::from_utf8_lossy(file).to_string();
+            if method != 0 {
+                return Err(DspError::Data(format!("{}: compressed entries are not supported", file)));
+            }
+
+            let start = local + 30 + u16_at(local + 26).ok_or_else(truncated)? + u16_at(local + 28).ok_or_else(truncated)?;
+            let data = bytes.get(start..start + size).ok_or_else(truncated)?;
+            let array = Array::from_npy(data).map_err(|e| e.context(&file))?;
+            npz.arrays.push((file.trim_end_matches(".npy").to_string(), array));
+            entry += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(npz)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()?).map_err(|e| DspError::io(path, e))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| DspError::io(path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| e.context(path.display()))
+    }
+
+    /// Read the arrays a `--npz` or `--mat` run saved: a `.mat` file through
+    /// [`mat::load`](crate::mat::load), anything else as an `.npz` archive
+    pub fn load_any(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mat")) {
+            Ok(Npz { arrays: crate::mat::load(path)? })
+        } else {
+            Self::load(path)
+        }
+    }
+}
+
+/// CRC-32 (IEEE, reflected), the checksum ZIP stores for each entry
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npy_and_npz_round_trip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let signal: Vec<f64> = (0..25).map(|k| (k as f64 * 0.3).sin()).collect();
+        let spectrum: Vec<Complex<f64>> = (0..37).map(|k| Complex::new(k as f64 / 7.0, -(k as f64).sqrt())).collect();
+        for array in [Array::from(signal.clone()), Array::from(spectrum.clone())] {
+            let bytes = array.to_npy();
+            let data_len = match &array {
+                Array::Real(values) => 8 * values.len(),
+                Array::Complex(values) => 16 * values.len(),
+            };
+            // Data starts on a 64-byte boundary as the format requires
+            assert_eq!((bytes.len() - data_len) % 64, 0);
+            assert_eq!(Array::from_npy(&bytes).unwrap(), array);
+        }
+
+        let npz = Npz::new().array("x", signal.as_slice()).array("X", spectrum.as_slice()).array("empty", Vec::<f64>::new());
+        let path = std::env::temp_dir().join(format!("dsp_core_npz_{}.npz", std::process::id()));
+        npz.save(&path).unwrap();
+        let loaded = Npz::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, npz);
+        assert_eq!(loaded.get("X"), Some(&Array::Complex(spectrum.clone())));
+        assert_eq!(loaded.real("x").unwrap(), signal.as_slice());
+        assert_eq!(loaded.complex("X").unwrap(), spectrum.as_slice());
+        assert_eq!(loaded.scalar("x").unwrap(), 0.0);
+        assert!(loaded.real("X").is_err() && loaded.complex("x").is_err() && loaded.real("missing").is_err());
+        assert!(loaded.scalar("empty").is_err());
+        assert!(Npz::from_bytes(b"not a zip archive at all").is_err());
+    }
+}
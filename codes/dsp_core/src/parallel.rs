@@ -0,0 +1,56 @@
+use crate::error::Result;
+use rayon::prelude::*;
+
+/// One independent unit of work, such as rendering a figure or writing a file
+pub type Task<'a> = Box<dyn FnOnce() -> Result<()> + Send + 'a>;
+
+/// Run independent tasks on rayon's thread pool and wait for all of them
+///
+/// Every task runs even if another one fails; the first error in task order is returned.
+/// The pool size follows `RAYON_NUM_THREADS` (default: one thread per core).
+pub fn run_all(tasks: Vec<Task<'_>>) -> Result<()> {
+    let results: Vec<Result<()>> = tasks.into_par_iter().map(|task| task()).collect();
+    results.into_iter().collect()
+}
+
+/// Apply `f` to every signal (e.g. each channel of a stereo recording) in parallel,
+/// keeping their order
+pub fn map_signals<S, T, F>(signals: &[S], f: F) -> Vec<T>
+where
+    S: AsRef<[f64]> + Sync,
+    T: Send,
+    F: Fn(&[f64]) -> T + Sync + Send,
+{
+    signals.par_iter().map(|signal| f(signal.as_ref())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DspError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_run_all_runs_every_task() {
+        let done = AtomicUsize::new(0);
+        let tasks: Vec<Task> = (0..4)
+            .map(|i| {
+                let done = &done;
+                Box::new(move || {
+                    done.fetch_add(1, Ordering::SeqCst);
+                    if i == 2 {
+                        Err(DspError::Data(format!("task {}", i)))
+                    } else {
+                        Ok(())
+                    }
+                }) as Task
+            })
+            .collect();
+        let error = run_all(tasks).unwrap_err();
+        assert_eq!(error.to_string(), "task 2");
+        assert_eq!(done.load(Ordering::SeqCst), 4);
+
+        let channels = vec![vec![1.0, 2.0], vec![3.0], vec![]];
+        assert_eq!(map_signals(&channels, |c| c.len()), vec![2, 1, 0]);
+    }
+}
@@ -0,0 +1,189 @@
+/// Peaks weaker than this share of the strongest one are ignored by [`estimate_offset`]
+pub const PEAK_THRESHOLD: f64 = 0.1;
+
+/// Peaks [`estimate_offset`] pairs up
+pub const MAX_PEAKS: usize = 5;
+
+/// Minimum spacing between two peaks found by [`estimate_offset`] (bins)
+pub const MIN_PEAK_DISTANCE: usize = 20;
+
+/// Two peaks whose magnitudes differ by less than this ratio can be a sideband pair
+pub const PAIR_MIN_RATIO: f64 = 0.9;
+
+/// Two sideband peaks symmetric about the frequency offset f_d
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymmetricPair {
+    /// Lower sideband peak (Hz)
+    pub lower_freq: f64,
+    pub lower_mag: f64,
+    pub lower_index: usize,
+    /// Upper sideband peak (Hz)
+    pub upper_freq: f64,
+    pub upper_mag: f64,
+    pub upper_index: usize,
+    /// Axis of symmetry, i.e. the frequency offset f_d (Hz)
+    pub axis: f64,
+    /// Baseband frequency of the pair (Hz)
+    pub baseband: f64,
+}
+
+/// Frequency offset of a spectrum and the peaks it was found from ([`estimate_offset`])
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetEstimate {
+    /// Estimated f_d (Hz)
+    pub f_d: f64,
+    /// Strongest peak in the search range, parabolically interpolated (Hz)
+    pub peak: f64,
+    /// Peaks considered for pairing, strongest first, as (frequency, magnitude, index)
+    pub peaks: Vec<(f64, f64, usize)>,
+    /// Pair whose axis gave f_d; `None` when f_d is the strongest peak
+    pub pair: Option<SymmetricPair>,
+}
+
+/// Strongest bin within `search_range` (Hz) as (frequency, magnitude, index); index 0 when
+/// no bin in range has a positive magnitude
+pub fn peak_in_range(frequencies: &[f64], magnitude: &[f64], (low, high): (f64, f64)) -> (f64, f64, usize) {
+    let mut peak = (0.0, 0.0, 0);
+    for (i, (&freq, &mag)) in frequencies.iter().zip(magnitude).enumerate() {
+        if freq >= low && freq <= high && mag > peak.1 {
+            peak = (freq, mag, i);
+        }
+    }
+    peak
+}
+
+/// Local maxima above `threshold`, strongest first: at most `num_peaks` of them, each at
+/// least `min_distance` bins from every stronger one, as (frequency, magnitude, index)
+pub fn detect_peaks(
+    frequencies: &[f64],
+    magnitude: &[f64],
+    num_peaks: usize,
+    min_distance: usize,
+    threshold: f64,
+) -> Vec<(f64, f64, usize)> {
+    let n = magnitude.len();
+    let mut peaks: Vec<(f64, f64, usize)> = (1..n.saturating_sub(1))
+        .filter(|&i| magnitude[i] > magnitude[i - 1] && magnitude[i] > magnitude[i + 1] && magnitude[i] > threshold)
+        .map(|i| (frequencies[i], magnitude[i], i))
+        .collect();
+    peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut kept: Vec<(f64, f64, usize)> = Vec::new();
+    for peak in peaks {
+        if kept.len() >= num_peaks {
+            break;
+        }
+        if kept.iter().all(|&(_, _, index)| index.abs_diff(peak.2) >= min_distance) {
+            kept.push(peak);
+        }
+    }
+    kept
+}
+
+/// Every pair of `peaks` below `max_freq` whose smaller magnitude is more than `min_ratio`
+/// of the larger one
+pub fn symmetric_pairs(peaks: &[(f64, f64, usize)], max_freq: f64, min_ratio: f64) -> Vec<SymmetricPair> {
+    let mut pairs = Vec::new();
+    for (i, &a) in peaks.iter().enumerate() {
+        for &b in &peaks[i + 1..] {
+            if a.0 > max_freq || b.0 > max_freq || a.1.min(b.1) / a.1.max(b.1) <= min_ratio {
+                continue;
+            }
+            let (lower, upper) = if a.0 < b.0 { (a, b) } else { (b, a) };
+            pairs.push(SymmetricPair {
+                lower_freq: lower.0,
+                lower_mag: lower.1,
+                lower_index: lower.2,
+                upper_freq: upper.0,
+                upper_mag: upper.1,
+                upper_index: upper.2,
+                axis: (lower.0 + upper.0) / 2.0,
+                baseband: (upper.0 - lower.0) / 2.0,
+            });
+        }
+    }
+    pairs
+}
+
+/// The pair with the strongest peak
+pub fn best_pair(pairs: &[SymmetricPair]) -> Option<SymmetricPair> {
+    pairs
+        .iter()
+        .max_by(|a, b| a.lower_mag.max(a.upper_mag).total_cmp(&b.lower_mag.max(b.upper_mag)))
+        .copied()
+}
+
+/// Vertex of the parabola through the peak bin and its two neighbours (Hz); the bin itself
+/// at either end of the spectrum
+pub fn parabolic_peak(frequencies: &[f64], magnitude: &[f64], index: usize) -> f64 {
+    if index == 0 || index + 1 >= magnitude.len() {
+        return frequencies.get(index).copied().unwrap_or(0.0);
+    }
+    let (a, b, c) = (magnitude[index - 1], magnitude[index], magnitude[index + 1]);
+    let curvature = a - 2.0 * b + c;
+    if curvature >= 0.0 {
+        return frequencies[index];
+    }
+    let offset = 0.5 * (a - c) / curvature;
+    frequencies[index] + offset * (frequencies[index + 1] - frequencies[index])
+}
+
+/// Q1's estimate of the frequency offset from a single-sided magnitude spectrum
+///
+/// The strongest peak between 10 Hz and min(10 kHz, `max_freq`) sets the scale; the
+/// [`MAX_PEAKS`] peaks above [`PEAK_THRESHOLD`] of it are paired by magnitude, and the
+/// axis of the strongest pair below `pair_max_freq` is f_d. A DSB-SC spectrum has no
+/// carrier line, so the strongest peak alone would land on a sideband; it is only the
+/// fallback when no pair is found.
+pub fn estimate_offset(frequencies: &[f64], magnitude: &[f64], pair_max_freq: f64) -> OffsetEstimate {
+    let max_freq = frequencies.last().copied().unwrap_or(0.0);
+    let (_, peak_mag, peak_index) = peak_in_range(frequencies, magnitude, (10.0, 10000.0f64.min(max_freq)));
+    let peak = parabolic_peak(frequencies, magnitude, peak_index);
+    let peaks = detect_peaks(frequencies, magnitude, MAX_PEAKS, MIN_PEAK_DISTANCE, peak_mag * PEAK_THRESHOLD);
+    let pair = best_pair(&symmetric_pairs(&peaks, pair_max_freq, PAIR_MIN_RATIO));
+    OffsetEstimate { f_d: pair.map_or(peak, |pair| pair.axis), peak, peaks, pair }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sideband_pair_gives_the_offset() {
+        // 1 Hz bins; two equal sidebands at 3000 ± 400 Hz and a weaker tone at 1000 Hz
+        let frequencies: Vec<f64> = (0..=5000).map(|k| k as f64).collect();
+        let magnitude: Vec<f64> = frequencies
+            .iter()
+            .map(|&f| match f as usize {
+                2600 => 1.0,
+                3400 => 0.97,
+                1000 => 0.5,
+                3399 | 3401 => 0.4,
+                _ => 1e-3,
+            })
+            .collect();
+        let peaks = detect_peaks(&frequencies, &magnitude, 5, 20, 0.1);
+        assert_eq!(peaks.iter().map(|p| p.2).collect::<Vec<_>>(), [2600, 3400, 1000]);
+        assert_eq!(symmetric_pairs(&peaks, 5000.0, 0.9).len(), 1);
+        assert!(symmetric_pairs(&peaks, 3000.0, 0.9).is_empty());
+
+        let estimate = estimate_offset(&frequencies, &magnitude, 5000.0);
+        assert_eq!(estimate.f_d, 3000.0);
+        assert_eq!(estimate.pair.unwrap().baseband, 400.0);
+        assert_eq!(estimate.peak, 2600.0);
+
+        // Without a pair the strongest peak is the estimate, between bins where it leans
+        let single: Vec<f64> = frequencies
+            .iter()
+            .map(|&f| match f as usize {
+                1999 => 0.5,
+                2000 => 1.0,
+                2001 => 0.75,
+                _ => 1e-3,
+            })
+            .collect();
+        let estimate = estimate_offset(&frequencies, &single, 5000.0);
+        assert_eq!(estimate.pair, None);
+        assert!((estimate.f_d - (2000.0 + 1.0 / 6.0)).abs() < 1e-9, "f_d {}", estimate.f_d);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::checkpoint::{self, Checkpoints};
+use crate::error::{DspError, Result};
+use crate::spectrum::ShiftMethod;
+use crate::{frequency_shifter, ideal_filter};
+use log::warn;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+
+/// One frequency-domain operation of a [`SpectralPipeline`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    /// Ideal high-pass, zeroes |f| < cutoff (Hz)
+    Highpass(f64),
+    /// Ideal low-pass, zeroes |f| > cutoff (Hz)
+    Lowpass(f64),
+    /// X(f - f_d) / 2 + X(f + f_d) / 2, i.e. mixing with cos(2π f_d t)
+    Shift { f_d: f64, method: ShiftMethod },
+    /// Constant gain
+    Gain(f64),
+}
+
+impl Stage {
+    fn apply(&self, spectrum: &[Complex<f64>], f_s: f64) -> Vec<Complex<f64>> {
+        let n = spectrum.len();
+        match *self {
+            Stage::Highpass(cutoff) => ideal_filter::apply_highpass(spectrum, cutoff, f_s, n),
+            Stage::Lowpass(cutoff) => ideal_filter::apply_lowpass(spectrum, cutoff, f_s, n),
+            Stage::Shift { f_d, method } => frequency_shifter::frequency_shift_with(spectrum, f_d, f_s, n, method),
+            Stage::Gain(gain) => spectrum.iter().map(|x| x * gain).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Highpass(cutoff) => write!(f, "ideal high-pass (fc = {:.4} Hz)", cutoff),
+            Stage::Lowpass(cutoff) => write!(f, "ideal low-pass (fc = {:.4} Hz)", cutoff),
+            Stage::Shift { f_d, method } => write!(f, "frequency shift (±{:.4} Hz, {})", f_d, method),
+            Stage::Gain(gain) => write!(f, "gain x{}", gain),
+        }
+    }
+}
+
+/// Edge handling of [`SpectralPipeline::run`] (`--edge-taper <ms>`, `--edge-pad <ms>`)
+///
+/// The FFT treats the signal as one period of a periodic block, so a brick-wall filter's
+/// ringing from the last samples wraps around onto the first and the jump between the two
+/// ends rings as well: the output clicks at both ends. Fading the signal in and out removes
+/// the jump, and zeros added on both sides give the ringing room to decay before it wraps;
+/// the padding is discarded again from the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EdgeGuard {
+    /// Raised-cosine fade-in and fade-out length (samples)
+    pub taper: usize,
+    /// Zeros added before and after the signal and discarded from the output (samples)
+    pub padding: usize,
+}
+
+impl EdgeGuard {
+    /// Read `--edge-taper <ms>` and `--edge-pad <ms>` from command-line arguments; `--edge-guard`
+    /// alone uses a 10 ms taper and 100 ms of padding
+    ///
+    /// Returns `None` when none of them is given.
+    pub fn from_args(args: &[String], f_s: f64) -> Result<Option<Self>> {
+        let mut guard: Option<EdgeGuard> = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let field = match arg.as_str() {
+                "--edge-guard" => {
+                    let default = EdgeGuard { taper: ms_to_samples(10.0, f_s), padding: ms_to_samples(100.0, f_s) };
+                    guard = guard.or(Some(default));
+                    continue;
+                }
+                "--edge-taper" | "--edge-pad" => arg.as_str(),
+                _ => continue,
+            };
+            let value = iter.next().ok_or_else(|| DspError::missing_value(field))?;
+            let ms: f64 = value
+                .parse()
+                .ok()
+                .filter(|ms: &f64| ms.is_finite() && *ms >= 0.0)
+                .ok_or_else(|| DspError::invalid_value(field, value))?;
+            let edges = guard.get_or_insert_with(EdgeGuard::default);
+            match field {
+                "--edge-taper" => edges.taper = ms_to_samples(ms, f_s),
+                _ => edges.padding = ms_to_samples(ms, f_s),
+            }
+        }
+        Ok(guard)
+    }
+
+    /// `samples` faded in and out and surrounded by the padding
+    pub fn prepare(&self, samples: &[f64]) -> Vec<f64> {
+        let n = samples.len();
+        let taper = self.taper.min(n / 2);
+        let mut out = vec![0.0; n + 2 * self.padding];
+        for (i, &x) in samples.iter().enumerate() {
+            let distance = i.min(n - 1 - i);
+            let fade = if distance < taper {
+                0.5 * (1.0 - (PI * (distance as f64 + 0.5) / taper as f64).cos())
+            } else {
+                1.0
+            };
+            out[self.padding + i] = x * fade;
+        }
+        out
+    }
+
+    /// The samples of a padded output that line up with the original signal
+    pub fn discard(&self, output: &[f64]) -> Vec<f64> {
+        let end = output.len().saturating_sub(self.padding);
+        output[self.padding.min(end)..end].to_vec()
+    }
+}
+
+impl std::fmt::Display for EdgeGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-sample fades, {} samples of padding per side", self.taper, self.padding)
+    }
+}
+
+fn ms_to_samples(ms: f64, f_s: f64) -> usize {
+    (ms * 1e-3 * f_s).round() as usize
+}
+
+/// Largest step |x[n] - x[n-1]| within `len` samples of the start and of the end of `signal`,
+/// in dB relative to the largest step in between; wrap-around clicks show up here
+pub fn edge_clicks_db(signal: &[f64], len: usize) -> (f64, f64) {
+    let steps: Vec<f64> = signal.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+    let len = len.min(steps.len() / 3);
+    let max = |steps: &[f64]| steps.iter().fold(0.0f64, |m, &x| m.max(x));
+    let interior = max(&steps[len..steps.len() - len]).max(1e-300);
+    let db = |x: f64| 20.0 * (x.max(1e-300) / interior).log10();
+    (db(max(&steps[..len])), db(max(&steps[steps.len() - len..])))
+}
+
+/// Spectrum after one pipeline stage
+#[derive(Debug, Clone)]
+pub struct StageOutput {
+    pub stage: Stage,
+    pub spectrum: Vec<Complex<f64>>,
+}
+
+/// Everything one [`SpectralPipeline::run`] produced
+#[derive(Debug, Clone)]
+pub struct PipelineResult {
+    /// FFT of the input, padded when an [`EdgeGuard`] is set
+    pub input: Vec<Complex<f64>>,
+    /// Spectrum after each stage, in pipeline order
+    pub stages: Vec<StageOutput>,
+    /// Real part of the IFFT of the last spectrum, without the padding
+    pub output: Vec<f64>,
+    /// Number of spectra (input included) read from checkpoints instead of computed
+    pub resumed: usize,
+}
+
+impl PipelineResult {
+    /// Spectrum after the last stage (the input spectrum for an empty pipeline)
+    pub fn final_spectrum(&self) -> &[Complex<f64>] {
+        self.stages.last().map_or(&self.input, |s| &s.spectrum)
+    }
+}
+
+/// FFT → stages → IFFT, built up one stage at a time
+///
+/// Q4's demodulator is
+/// `SpectralPipeline::new().highpass(f_d).shift(f_d).lowpass(f_b).gain(4.0)`, with the gain
+/// from [`GainCalibrator`](crate::calibration::GainCalibrator); stages can be added, dropped or reordered without touching
+/// the FFT bookkeeping. Nothing on this path reads files or plots, so the browser build runs
+/// the same chain.
+#[derive(Debug, Clone, Default)]
+pub struct SpectralPipeline {
+    stages: Vec<Stage>,
+    checkpoints: Option<Checkpoints>,
+    edges: Option<EdgeGuard>,
+}
+
+impl SpectralPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an arbitrary stage
+    pub fn stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    pub fn highpass(self, cutoff: f64) -> Self {
+        self.stage(Stage::Highpass(cutoff))
+    }
+
+    pub fn lowpass(self, cutoff: f64) -> Self {
+        self.stage(Stage::Lowpass(cutoff))
+    }
+
+    /// Shift by ±f_d, rounded to the nearest bin
+    pub fn shift(self, f_d: f64) -> Self {
+        self.shift_with(f_d, ShiftMethod::default())
+    }
+
+    pub fn shift_with(self, f_d: f64, method: ShiftMethod) -> Self {
+        self.stage(Stage::Shift { f_d, method })
+    }
+
+    pub fn gain(self, gain: f64) -> Self {
+        self.stage(Stage::Gain(gain))
+    }
+
+    /// Dump the input spectrum and every stage's spectrum, and with `resume` read back the
+    /// ones an earlier run already computed for the same input and stage parameters
+    ///
+    /// Only the stages after the first changed parameter are recomputed, e.g. when trying
+    /// several low-pass cutoffs. Checkpoint I/O errors are reported and the run carries on.
+    pub fn with_checkpoints(mut self, checkpoints: Checkpoints) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// Fade and pad the signal before the FFT and drop the padding after the IFFT
+    pub fn with_edges(mut self, edges: EdgeGuard) -> Self {
+        self.edges = Some(edges);
+        self
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Run the stages on `samples` taken at `f_s` Hz
+    pub fn run(&self, samples: &[f64], f_s: f64) -> PipelineResult {
+        let mut resumed = 0;
+        let prepared = self.edges.map(|edges| edges.prepare(samples));
+        let samples = prepared.as_deref().unwrap_or(samples);
+        // Keys chain the input with each stage's parameters; only computed when checkpointing
+        let mut key = self.checkpoints.as_ref().map_or(0, |_| checkpoint::signal_key(samples, f_s));
+        let input = self.checkpointed("input", key, &mut resumed, || fft(samples));
+
+        let mut stages: Vec<StageOutput> = Vec::with_capacity(self.stages.len());
+        for (i, &stage) in self.stages.iter().enumerate() {
+            key = checkpoint::key(key, &format!("{:?}", stage));
+            let previous = stages.last().map_or(&input, |s| &s.spectrum);
+            let spectrum =
+                self.checkpointed(&format!("stage{}", i + 1), key, &mut resumed, || stage.apply(previous, f_s));
+            stages.push(StageOutput { stage, spectrum });
+        }
+        let output = ifft(stages.last().map_or(&input, |s| &s.spectrum));
+        let output = match self.edges {
+            Some(edges) => edges.discard(&output),
+            None => output,
+        };
+        PipelineResult {
+            input,
+            stages,
+            output,
+            resumed,
+        }
+    }
+
+    /// Load checkpoint `name` if resuming and present, otherwise compute (and save) it
+    fn checkpointed(
+        &self,
+        name: &str,
+        key: u64,
+        resumed: &mut usize,
+        compute: impl FnOnce() -> Vec<Complex<f64>>,
+    ) -> Vec<Complex<f64>> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return compute();
+        };
+        match checkpoints.load(name, key) {
+            Ok(Some(spectrum)) => {
+                *resumed += 1;
+                return spectrum;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("  Warning: ignoring checkpoint: {}", e),
+        }
+        let spectrum = compute();
+        if let Err(e) = checkpoints.save(name, key, &spectrum) {
+            warn!("  Warning: could not write checkpoint: {}", e);
+        }
+        spectrum
+    }
+}
+
+/// Complex FFT of a real signal
+pub fn fft(samples: &[f64]) -> Vec<Complex<f64>> {
+    let mut buffer: Vec<Complex<f64>> = samples.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    FftPlanner::new().plan_fft_forward(buffer.len()).process(&mut buffer);
+    buffer
+}
+
+/// Normalized inverse FFT, real part
+pub fn ifft(spectrum: &[Complex<f64>]) -> Vec<f64> {
+    let mut buffer = spectrum.to_vec();
+    FftPlanner::new().plan_fft_inverse(buffer.len()).process(&mut buffer);
+    let n = buffer.len() as f64;
+    buffer.iter().map(|c| c.re / n).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_pipeline_demodulates_am_tone() {
+        // 200 Hz tone on a 2 kHz carrier, 1 s at 8 kHz so every frequency sits on a bin
+        let (f_s, f_d) = (8000.0, 2000.0);
+        let message = |k: usize| (2.0 * PI * 200.0 * k as f64 / f_s).cos();
+        let signal: Vec<f64> = (0..8000).map(|k| message(k) * (2.0 * PI * f_d * k as f64 / f_s).cos()).collect();
+
+        let pipeline = SpectralPipeline::new().highpass(f_d - 500.0).shift(f_d).lowpass(500.0).gain(2.0);
+        let result = pipeline.run(&signal, f_s);
+        assert_eq!(result.stages.len(), pipeline.stages().len());
+        assert_eq!(result.stages[1].stage, Stage::Shift { f_d, method: ShiftMethod::NearestBin });
+        let error = result
+            .output
+            .iter()
+            .enumerate()
+            .map(|(k, y)| (y - message(k)).abs())
+            .fold(0.0f64, f64::max);
+        assert!(error < 1e-9, "error {}", error);
+
+        // Without stages the pipeline is an FFT round trip
+        let identity = SpectralPipeline::new().run(&signal, f_s);
+        assert_eq!(identity.final_spectrum().len(), signal.len());
+        assert!(identity.output.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_edge_guard_suppresses_wrap_around_clicks() {
+        // A tone that does not fit a whole number of periods jumps where the ends meet
+        let f_s = 8000.0;
+        let signal: Vec<f64> = (0..8000).map(|k| (2.0 * PI * 203.7 * k as f64 / f_s).sin()).collect();
+        let pipeline = SpectralPipeline::new().lowpass(1000.0);
+        let plain = pipeline.run(&signal, f_s).output;
+
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(EdgeGuard::from_args(&args("--snr 10"), f_s).unwrap(), None);
+        assert!(EdgeGuard::from_args(&args("--edge-taper -1"), f_s).is_err());
+        let edges = EdgeGuard::from_args(&args("--edge-taper 10 --edge-pad 100"), f_s).unwrap().unwrap();
+        assert_eq!(edges, EdgeGuard { taper: 80, padding: 800 });
+
+        let result = pipeline.clone().with_edges(edges).run(&signal, f_s);
+        assert_eq!(result.output.len(), signal.len());
+        assert_eq!(result.input.len(), signal.len() + 1600);
+        let (plain_start, plain_end) = edge_clicks_db(&plain, 40);
+        let (start, end) = edge_clicks_db(&result.output, 40);
+        assert!(plain_start > -1.0 && plain_end > -1.0, "{} {}", plain_start, plain_end);
+        assert!(start < plain_start - 10.0 && end < plain_end - 10.0, "{} {}", start, end);
+        // Away from the edges the output is unchanged
+        assert!(result.output[2000..6000].iter().zip(&plain[2000..6000]).all(|(a, b)| (a - b).abs() < 1e-2));
+    }
+
+    #[test]
+    fn test_resume_recomputes_only_changed_stages() {
+        let signal: Vec<f64> = (0..1024).map(|k| (0.3 * k as f64).sin()).collect();
+        let checkpoints = Checkpoints {
+            dir: std::env::temp_dir().join(format!("dsp_core_pipeline_checkpoint_{}", std::process::id())),
+            format: Default::default(),
+            resume: true,
+        };
+        let with_cutoff = |cutoff: f64| {
+            SpectralPipeline::new()
+                .highpass(500.0)
+                .shift(1000.0)
+                .lowpass(cutoff)
+                .with_checkpoints(checkpoints.clone())
+                .run(&signal, 8000.0)
+        };
+        let first = with_cutoff(300.0);
+        assert_eq!(first.resumed, 0);
+
+        // Input, high-pass and shift come from disk; only the new low-pass runs
+        let second = with_cutoff(400.0);
+        assert_eq!(second.resumed, 3);
+        assert_eq!(second.stages[1].spectrum, first.stages[1].spectrum);
+        assert_eq!(with_cutoff(400.0).resumed, 4);
+        std::fs::remove_dir_all(&checkpoints.dir).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+use crate::error::{DspError, Result};
+use log::info;
+
+/// Whether `--play` was given on the command line
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--play")
+}
+
+/// Play a mono signal on the default output device and block until it finishes
+///
+/// The signal is scaled to a 0.5 peak first, so intermediate signals with large
+/// gains (e.g. after carrier multiplication) play at a comfortable level.
+/// Needs the `playback` feature; without it an error explaining how to enable it is returned.
+pub fn play(label: &str, samples: &[f64], sample_rate: f64) -> Result<()> {
+    let peak = samples.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
+    let gain = if peak > 0.0 { 0.5 / peak } else { 1.0 };
+    let scaled: Vec<f32> = samples.iter().map(|&x| (x * gain) as f32).collect();
+    info!(
+        "  Playing {} ({:.2} s)...",
+        label,
+        samples.len() as f64 / sample_rate
+    );
+    play_buffer(scaled, sample_rate.round() as u32)
+}
+
+#[cfg(feature = "playback")]
+fn play_buffer(samples: Vec<f32>, sample_rate: u32) -> Result<()> {
+    use rodio::buffer::SamplesBuffer;
+    use rodio::{OutputStream, Sink};
+
+    let (_stream, handle) = OutputStream::try_default()
+        .map_err(|e| DspError::Other(format!("No audio output device: {}", e)))?;
+    let sink = Sink::try_new(&handle).map_err(|e| DspError::Other(format!("Failed to open audio sink: {}", e)))?;
+    sink.append(SamplesBuffer::new(1, sample_rate, samples));
+    sink.sleep_until_end();
+    Ok(())
+}
+
+#[cfg(not(feature = "playback"))]
+fn play_buffer(_samples: Vec<f32>, _sample_rate: u32) -> Result<()> {
+    Err(DspError::Unsupported(
+        "built without audio playback; rebuild with `--features playback`".to_string(),
+    ))
+}
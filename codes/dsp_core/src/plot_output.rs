@@ -0,0 +1,378 @@
+use crate::error::{DspError, Result};
+use crate::plot_style::{AxisOptions, PlotStyle};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Image format of a plot, chosen from the output file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlotFormat {
+    #[default]
+    Png,
+    /// Vector output for report figures
+    Svg,
+    /// Rendered as SVG and converted; needs the `pdf` feature
+    Pdf,
+}
+
+impl PlotFormat {
+    /// Format for `path`: `.svg` and `.pdf` by extension, PNG for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()) {
+            Some(ext) if ext == "svg" => PlotFormat::Svg,
+            Some(ext) if ext == "pdf" => PlotFormat::Pdf,
+            _ => PlotFormat::Png,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlotFormat::Png => "png",
+            PlotFormat::Svg => "svg",
+            PlotFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl FromStr for PlotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(PlotFormat::Png),
+            "svg" => Ok(PlotFormat::Svg),
+            "pdf" => Ok(PlotFormat::Pdf),
+            other => Err(format!("Unknown plot format '{}' (expected png, svg or pdf)", other)),
+        }
+    }
+}
+
+/// Resolution PDF pages are laid out at when none is given: 96 px per inch, the CSS pixel
+pub const DEFAULT_DPI: f64 = 96.0;
+
+/// Where one plot goes, how big it is and how it looks
+///
+/// Plotting functions take `impl Into<PlotTarget>`, so a plain file name still works and
+/// gets the default style. The backend follows the file extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotTarget {
+    pub path: PathBuf,
+    /// Canvas size (px), overriding the plot's default
+    pub size: Option<(u32, u32)>,
+    /// Pixels per inch; sets the physical page size of a PDF
+    pub dpi: f64,
+    pub style: PlotStyle,
+}
+
+impl PlotTarget {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        PlotTarget {
+            path: path.into(),
+            size: None,
+            dpi: DEFAULT_DPI,
+            style: PlotStyle::default(),
+        }
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    pub fn style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn format(&self) -> PlotFormat {
+        PlotFormat::from_path(&self.path)
+    }
+
+    /// Canvas size: the override if any, otherwise `default`
+    pub fn size_or(&self, default: (u32, u32)) -> (u32, u32) {
+        self.size.unwrap_or(default)
+    }
+}
+
+impl From<&str> for PlotTarget {
+    fn from(path: &str) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+impl From<String> for PlotTarget {
+    fn from(path: String) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+impl From<&String> for PlotTarget {
+    fn from(path: &String) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+impl From<&Path> for PlotTarget {
+    fn from(path: &Path) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+impl From<PathBuf> for PlotTarget {
+    fn from(path: PathBuf) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+impl From<&PathBuf> for PlotTarget {
+    fn from(path: &PathBuf) -> Self {
+        PlotTarget::new(path)
+    }
+}
+
+/// Plot settings from the command line, applied to every plot a program writes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlotOutput {
+    /// Replaces the extension of every plot file; `None` keeps the program's PNGs
+    pub format: Option<PlotFormat>,
+    pub size: Option<(u32, u32)>,
+    pub dpi: Option<f64>,
+    pub style: PlotStyle,
+}
+
+impl PlotOutput {
+    /// Read `--plot-format <png|svg|pdf>`, `--plot-size <W>x<H>`, `--plot-dpi <n>`,
+    /// `--plot-style <default|report>`, `--colormap <heat|viridis|magma|gray>`,
+    /// `--db-range <min>:<max>` and the axis options of [`AxisOptions`] from command-line
+    /// arguments: `--freq-range <min>:<max>`, `--freq-tick <Hz>`, `--mag-range <min>:<max>`,
+    /// `--mag-range-db <min>:<max>`, `--mag-tick <step>` and `--sci-ticks`
+    ///
+    /// The colormap, dB range and axis options apply on top of the chosen style. Asking for PDF in a build without the `pdf` feature is an error here, before any
+    /// processing, rather than at the first plot.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut output = PlotOutput::default();
+        let (mut colormap, mut db_range) = (None, None);
+        let mut axes = AxisOptions::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if name == "--sci-ticks" {
+                axes.scientific = true;
+                continue;
+            }
+            if !matches!(
+                name,
+                "--plot-format"
+                    | "--plot-size"
+                    | "--plot-dpi"
+                    | "--plot-style"
+                    | "--colormap"
+                    | "--db-range"
+                    | "--freq-range"
+                    | "--freq-tick"
+                    | "--mag-range"
+                    | "--mag-range-db"
+                    | "--mag-tick"
+            ) {
+                continue;
+            }
+            let value = iter.next().ok_or_else(|| DspError::missing_value(name))?;
+            let invalid = || DspError::invalid_value(name, value);
+            let range = || {
+                let (min, max) = value.split_once(':')?;
+                let parse = |v: &str| v.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+                Some((parse(min)?, parse(max)?)).filter(|(min, max)| min < max)
+            };
+            let step = || value.parse().ok().filter(|&v: &f64| v > 0.0 && v.is_finite());
+            match name {
+                "--plot-format" => output.format = Some(value.parse().map_err(DspError::Argument)?),
+                "--plot-style" => output.style = value.parse().map_err(DspError::Argument)?,
+                "--colormap" => colormap = Some(value.parse().map_err(DspError::Argument)?),
+                "--db-range" => db_range = Some(value.parse().map_err(DspError::Argument)?),
+                "--freq-range" => axes.freq_range = Some(range().ok_or_else(invalid)?),
+                "--mag-range" => axes.magnitude_range = Some(range().ok_or_else(invalid)?),
+                "--mag-range-db" => axes.magnitude_db_range = Some(range().ok_or_else(invalid)?),
+                "--freq-tick" => axes.freq_tick = Some(step().ok_or_else(invalid)?),
+                "--mag-tick" => axes.magnitude_tick = Some(step().ok_or_else(invalid)?),
+                "--plot-size" => {
+                    let (w, h) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+                    let parse = |v: &str| v.trim().parse::<u32>().ok().filter(|&v| v > 0);
+                    output.size = Some((parse(w).ok_or_else(invalid)?, parse(h).ok_or_else(invalid)?));
+                }
+                _ => output.dpi = Some(value.parse().ok().filter(|&v: &f64| v > 0.0).ok_or_else(invalid)?),
+            }
+        }
+        if let Some(colormap) = colormap {
+            output.style.colormap = colormap;
+        }
+        if db_range.is_some() {
+            output.style.db_range = db_range;
+        }
+        output.style.axes = axes;
+        if output.format == Some(PlotFormat::Pdf) && !cfg!(feature = "pdf") {
+            return Err(DspError::Unsupported(
+                "--plot-format pdf needs PDF support; rebuild with `--features pdf`".to_string(),
+            ));
+        }
+        Ok(output)
+    }
+
+    /// Target for a plot the program would write to `path`
+    pub fn target(&self, path: &str) -> PlotTarget {
+        let mut path = PathBuf::from(path);
+        if let Some(format) = self.format {
+            path.set_extension(format.extension());
+        }
+        PlotTarget {
+            path,
+            size: self.size,
+            dpi: self.dpi.unwrap_or(DEFAULT_DPI),
+            style: self.style,
+        }
+    }
+}
+
+/// Convert SVG source to a one-page PDF of the same size, at `dpi` pixels per inch
+///
+/// Text is laid out with the system fonts. Needs the `pdf` feature; without it an error
+/// explaining how to enable it is returned.
+#[cfg(feature = "pdf")]
+pub fn svg_to_pdf(svg: &str, path: &Path, dpi: f64) -> Result<()> {
+    use std::collections::BTreeSet;
+    use svg2pdf::usvg::{self, fontdb, PostProcessingSteps, TreeParsing, TreePostProc};
+
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    let has_family = |fonts: &fontdb::Database, name: &str| {
+        fonts.faces().any(|face| face.families.iter().any(|(f, _)| f == name))
+    };
+    // fontdb maps sans-serif to Arial, which many Linux systems lack
+    if !has_family(&fonts, "Arial") && has_family(&fonts, "DejaVu Sans") {
+        fonts.set_sans_serif_family("DejaVu Sans");
+    }
+    // Text in a missing family would be dropped, so let it fall back to sans-serif
+    let mut svg = svg.to_string();
+    let families: BTreeSet<String> = svg
+        .split("font-family=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .filter(|family| !family.contains(',') && !matches!(*family, "sans-serif" | "serif" | "monospace"))
+        .map(str::to_string)
+        .collect();
+    for family in families.iter().filter(|family| !has_family(&fonts, family)) {
+        let attribute = format!("font-family=\"{}\"", family);
+        svg = svg.replace(&attribute, &format!("font-family=\"{}, sans-serif\"", family));
+    }
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).map_err(DspError::plot)?;
+    tree.postprocess(PostProcessingSteps::default(), &fonts);
+    let options = svg2pdf::Options {
+        dpi: dpi as f32,
+        ..Default::default()
+    };
+    std::fs::write(path, svg2pdf::convert_tree(&tree, options)).map_err(|e| DspError::io(path, e))
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn svg_to_pdf(_svg: &str, _path: &Path, _dpi: f64) -> Result<()> {
+    Err(DspError::Unsupported("built without PDF output; rebuild with `--features pdf`".to_string()))
+}
+
+/// Run `$body` with `$root` bound to a plotters drawing area for `$target`
+///
+/// The backend follows the target's extension: `BitMapBackend` for PNG, `SVGBackend` for
+/// SVG, and for PDF an in-memory SVG that is converted with [`svg_to_pdf`] afterwards.
+/// `$default_size` applies when the target has no size of its own. The body is expanded
+/// once per backend, so it may use `?` and `return` like the surrounding function; it
+/// should end with `$root.present()`. Evaluates to `Result<(), DspError>`, the outcome of
+/// the PDF conversion.
+///
+/// This expands in the calling crate, which must depend on `plotters`.
+#[macro_export]
+macro_rules! with_drawing_area {
+    ($target:expr, $default_size:expr, |$root:ident| $body:block) => {{
+        let target: &$crate::plot_output::PlotTarget = &$target;
+        let size = target.size_or($default_size);
+        match target.format() {
+            $crate::plot_output::PlotFormat::Png => {
+                let $root = ::plotters::prelude::IntoDrawingArea::into_drawing_area(
+                    ::plotters::prelude::BitMapBackend::new(&target.path, size),
+                );
+                $body;
+                Ok::<(), $crate::error::DspError>(())
+            }
+            $crate::plot_output::PlotFormat::Svg => {
+                let $root = ::plotters::prelude::IntoDrawingArea::into_drawing_area(
+                    ::plotters::prelude::SVGBackend::new(&target.path, size),
+                );
+                $body;
+                Ok::<(), $crate::error::DspError>(())
+            }
+            $crate::plot_output::PlotFormat::Pdf => {
+                let mut svg = String::new();
+                {
+                    let $root = ::plotters::prelude::IntoDrawingArea::into_drawing_area(
+                        ::plotters::prelude::SVGBackend::with_string(&mut svg, size),
+                    );
+                    $body;
+                }
+                $crate::plot_output::svg_to_pdf(&svg, &target.path, target.dpi)
+            }
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colormap::{Colormap, DbRange};
+
+    #[test]
+    fn test_plot_output_from_args() {
+        let args: Vec<String> = ["--plot-format", "svg", "--plot-size", "1600x900", "--plot-dpi", "300"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let output = PlotOutput::from_args(&args).unwrap();
+        let target = output.target("output/Q4_spectrum.png");
+        assert_eq!(target.path, PathBuf::from("output/Q4_spectrum.svg"));
+        assert_eq!(target.format(), PlotFormat::Svg);
+        assert_eq!(target.size_or((1200, 800)), (1600, 900));
+        assert_eq!(target.dpi, 300.0);
+
+        // Without options every plot keeps its name and default size
+        let target = PlotOutput::default().target("a.png");
+        assert_eq!((target.format(), target.size_or((1200, 800))), (PlotFormat::Png, (1200, 800)));
+        assert_eq!(PlotTarget::from("figure.PDF").format(), PlotFormat::Pdf);
+        assert!(PlotOutput::from_args(&args[2..3]).is_err());
+        let report = PlotOutput::from_args(&["--plot-style".to_string(), "report".to_string()]).unwrap();
+        assert_eq!(report.target("a.png").style, PlotStyle::report());
+        assert!(PlotOutput::from_args(&["--plot-size".to_string(), "1600".to_string()]).is_err());
+
+        // Colormap and dB range override the chosen style wherever they appear
+        let args: Vec<String> = ["--colormap", "magma", "--db-range", "-70:-10", "--plot-style", "report"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let style = PlotOutput::from_args(&args).unwrap().style;
+        assert_eq!((style.colormap, style.db_range), (Colormap::Magma, Some(DbRange::new(-70.0, -10.0))));
+        assert_eq!(style.title_font_size, PlotStyle::report().title_font_size);
+
+        // Axis options, e.g. to zoom on the carrier
+        let args: Vec<String> = ["--sci-ticks", "--freq-range", "3000:3500", "--freq-tick", "50", "--plot-style", "report"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let axes = PlotOutput::from_args(&args).unwrap().style.axes;
+        assert_eq!((axes.freq_range, axes.freq_tick, axes.scientific), (Some((3000.0, 3500.0)), Some(50.0), true));
+        assert_eq!(axes.magnitude_range, None);
+        for bad in [["--freq-range", "3500:3000"], ["--mag-tick", "0"], ["--mag-range-db", "-60"]] {
+            let bad: Vec<String> = bad.iter().map(|s| s.to_string()).collect();
+            assert!(PlotOutput::from_args(&bad).is_err());
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::colormap::{Colormap, DbRange};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// An sRGB colour, converted to the plotting library's colour type by each program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+pub const WHITE: Rgb = Rgb(255, 255, 255);
+pub const BLACK: Rgb = Rgb(0, 0, 0);
+pub const BLUE: Rgb = Rgb(0, 0, 255);
+pub const RED: Rgb = Rgb(255, 0, 0);
+pub const GREEN: Rgb = Rgb(0, 255, 0);
+pub const CYAN: Rgb = Rgb(0, 255, 255);
+pub const MAGENTA: Rgb = Rgb(255, 0, 255);
+
+/// Corner or edge of the chart the series legend is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LegendPosition {
+    UpperLeft,
+    #[default]
+    UpperRight,
+    MiddleLeft,
+    MiddleRight,
+    LowerLeft,
+    LowerRight,
+}
+
+/// Axis limits and tick spacing of spectrum and frequency-response plots
+///
+/// Every field left at `None` keeps what the plot picks from its data. Time-domain plots
+/// keep their own axes, and linear and dB magnitude axes have separate limits because one
+/// run usually draws both.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AxisOptions {
+    /// Frequency axis limits (Hz)
+    pub freq_range: Option<(f64, f64)>,
+    /// Distance between frequency ticks (Hz)
+    pub freq_tick: Option<f64>,
+    /// Limits of linear magnitude axes
+    pub magnitude_range: Option<(f64, f64)>,
+    /// Limits of dB magnitude axes
+    pub magnitude_db_range: Option<(f64, f64)>,
+    /// Distance between magnitude ticks, in the unit of the axis
+    pub magnitude_tick: Option<f64>,
+    /// Magnitude tick labels in scientific notation, e.g. `2.50e-3`
+    pub scientific: bool,
+}
+
+/// Tick labels the plotting library places on an axis unless told otherwise
+const DEFAULT_TICKS: usize = 10;
+/// Most ticks asked for on one axis, so a fine spacing over a wide range stays legible
+const MAX_TICKS: usize = 50;
+
+impl AxisOptions {
+    /// Frequency axis: the `--freq-range` limits, or `default`
+    pub fn freq(&self, default: Range<f64>) -> Range<f64> {
+        self.freq_range.map_or(default, |(min, max)| min..max)
+    }
+
+    /// Linear magnitude axis: the `--mag-range` limits, or `default`
+    pub fn magnitude(&self, default: Range<f64>) -> Range<f64> {
+        self.magnitude_range.map_or(default, |(min, max)| min..max)
+    }
+
+    /// dB magnitude axis: the `--mag-range-db` limits, or `default`
+    pub fn magnitude_db(&self, default: Range<f64>) -> Range<f64> {
+        self.magnitude_db_range.map_or(default, |(min, max)| min..max)
+    }
+
+    /// Number of labels to ask for on a frequency axis spanning `range`
+    ///
+    /// The plotting library rounds the spacing to 1, 2 or 5 times a power of ten, so a tick
+    /// that is one of those lands exactly.
+    pub fn freq_labels(&self, range: &Range<f64>) -> usize {
+        tick_count(self.freq_tick, range)
+    }
+
+    /// Number of labels to ask for on a magnitude axis spanning `range`
+    pub fn magnitude_labels(&self, range: &Range<f64>) -> usize {
+        tick_count(self.magnitude_tick, range)
+    }
+
+    /// Magnitude tick label: `decimals` digits after the point, or in scientific notation
+    /// with three significant digits when asked for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::plot_style::AxisOptions;
+    ///
+    /// let fixed = AxisOptions::default();
+    /// assert_eq!(fixed.magnitude_label(0.0025, 3), "0.003");
+    /// let scientific = AxisOptions { scientific: true, ..fixed };
+    /// assert_eq!(scientific.magnitude_label(0.0025, 4), "2.50e-3");
+    /// ```
+    pub fn magnitude_label(&self, value: f64, decimals: usize) -> String {
+        if self.scientific {
+            scientific_label(&value)
+        } else {
+            format!("{:.*}", decimals, value)
+        }
+    }
+}
+
+/// Tick label in scientific notation with three significant digits, in the signature the
+/// plotting library takes for label formatters
+pub fn scientific_label(value: &f64) -> String {
+    format!("{:.2e}", value)
+}
+
+fn tick_count(step: Option<f64>, range: &Range<f64>) -> usize {
+    match step {
+        Some(step) => (((range.end - range.start).abs() / step).round() as usize + 1).clamp(2, MAX_TICKS),
+        None => DEFAULT_TICKS,
+    }
+}
+
+/// Look shared by every plot, so figures from Q1, Q2 and Q4 match in a report
+///
+/// The visualizers read it from [`PlotTarget::style`](crate::plot_output::PlotTarget);
+/// curves take their colours from `palette` in order, and reference lines such as ±f_d
+/// use `marker`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotStyle {
+    /// Canvas size (px) when the target sets none
+    pub size: (u32, u32),
+    pub font: &'static str,
+    pub title_font_size: u32,
+    /// Tick labels and axis descriptions
+    pub label_font_size: u32,
+    pub margin: u32,
+    pub x_label_area: u32,
+    pub y_label_area: u32,
+    pub background: Rgb,
+    pub palette: [Rgb; 5],
+    pub marker: Rgb,
+    pub line_width: u32,
+    /// Draw gridlines behind the curves
+    pub grid: bool,
+    pub legend: LegendPosition,
+    /// Colour scale of spectrograms and other 2D intensity plots
+    pub colormap: Colormap,
+    /// dB levels the colour scale spans, relative to the loudest value; `None` leaves each
+    /// plot its own default
+    pub db_range: Option<DbRange>,
+    /// Axis limits and ticks of spectra and frequency responses
+    pub axes: AxisOptions,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        PlotStyle {
+            size: (1200, 600),
+            font: "sans-serif",
+            title_font_size: 30,
+            label_font_size: 15,
+            margin: 10,
+            x_label_area: 50,
+            y_label_area: 80,
+            background: WHITE,
+            palette: [BLUE, RED, GREEN, CYAN, MAGENTA],
+            marker: RED,
+            line_width: 1,
+            grid: true,
+            legend: LegendPosition::default(),
+            colormap: Colormap::default(),
+            db_range: None,
+            axes: AxisOptions::default(),
+        }
+    }
+}
+
+impl PlotStyle {
+    /// Larger text and thicker lines that stay legible when a figure is scaled down in print
+    pub fn report() -> Self {
+        PlotStyle {
+            size: (1600, 900),
+            title_font_size: 44,
+            label_font_size: 24,
+            margin: 20,
+            x_label_area: 70,
+            y_label_area: 110,
+            palette: [
+                Rgb(31, 119, 180),
+                Rgb(214, 39, 40),
+                Rgb(44, 160, 44),
+                Rgb(255, 127, 14),
+                Rgb(148, 103, 189),
+            ],
+            marker: Rgb(214, 39, 40),
+            line_width: 2,
+            ..PlotStyle::default()
+        }
+    }
+
+    /// Colour of the `index`-th curve, cycling through the palette
+    pub fn color(&self, index: usize) -> Rgb {
+        self.palette[index % self.palette.len()]
+    }
+}
+
+impl FromStr for PlotStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "default" => Ok(PlotStyle::default()),
+            "report" => Ok(PlotStyle::report()),
+            other => Err(format!("Unknown plot style '{}' (expected default or report)", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style_presets() {
+        let style: PlotStyle = "Report".parse().unwrap();
+        assert_eq!(style, PlotStyle::report());
+        assert_eq!(style.font, PlotStyle::default().font);
+        assert_eq!(style.color(5), style.color(0));
+        assert!("poster".parse::<PlotStyle>().is_err());
+    }
+
+    #[test]
+    fn test_axis_options() {
+        let axes = AxisOptions {
+            freq_range: Some((3000.0, 3500.0)),
+            freq_tick: Some(50.0),
+            magnitude_tick: Some(1e-4),
+            ..AxisOptions::default()
+        };
+        assert_eq!(axes.freq(0.0..11025.0), 3000.0..3500.0);
+        assert_eq!(axes.magnitude(0.0..1.0), 0.0..1.0);
+        assert_eq!(axes.freq_labels(&(3000.0..3500.0)), 11);
+        // A spacing far below the span is capped rather than drawing thousands of labels
+        assert_eq!(axes.magnitude_labels(&(0.0..1.0)), MAX_TICKS);
+        assert_eq!(AxisOptions::default().freq_labels(&(0.0..1.0)), DEFAULT_TICKS);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::{DspError, Result};
+
+/// Clean-up stages for demodulated audio: DC blocker, de-clicker and peak limiter
+///
+/// Each stage is optional; `None` skips it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostFilter {
+    /// DC blocker corner frequency (Hz)
+    pub dc_cutoff: Option<f64>,
+    /// Click threshold: multiple of the local RMS of the second difference
+    pub declick_threshold: Option<f64>,
+    /// Limiter ceiling in dB above the signal RMS (a crest-factor limit, so it does not depend
+    /// on the absolute output level)
+    pub limit_crest_db: Option<f64>,
+}
+
+// Defaults used by `--post-filter`
+const DEFAULT_DC_CUTOFF: f64 = 20.0;
+const DEFAULT_DECLICK_THRESHOLD: f64 = 6.0;
+const DEFAULT_LIMIT_CREST_DB: f64 = 15.0;
+
+/// What [`PostFilter::apply`] changed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PostFilterReport {
+    /// DC level removed (mean of the input)
+    pub dc_removed: f64,
+    /// Number of click regions repaired
+    pub clicks: usize,
+    /// Number of samples the limiter reduced
+    pub limited: usize,
+}
+
+impl PostFilter {
+    /// Read `--post-filter` (all stages with defaults) and the individual `--dc-block <Hz>`,
+    /// `--declick <k>` and `--limit <dB>` options from command-line arguments
+    ///
+    /// Returns `None` when no stage is requested.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut filter = PostFilter {
+            dc_cutoff: None,
+            declick_threshold: None,
+            limit_crest_db: None,
+        };
+        let positive = |name: &str, value: &str| -> Result<f64> {
+            value
+                .parse()
+                .ok()
+                .filter(|&v: &f64| v > 0.0)
+                .ok_or_else(|| DspError::invalid_value(name, value))
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if name == "--post-filter" {
+                filter.dc_cutoff.get_or_insert(DEFAULT_DC_CUTOFF);
+                filter.declick_threshold.get_or_insert(DEFAULT_DECLICK_THRESHOLD);
+                filter.limit_crest_db.get_or_insert(DEFAULT_LIMIT_CREST_DB);
+                continue;
+            }
+            if !matches!(name, "--dc-block" | "--declick" | "--limit") {
+                continue;
+            }
+            let value = positive(name, iter.next().ok_or_else(|| DspError::missing_value(name))?)?;
+            match name {
+                "--dc-block" => filter.dc_cutoff = Some(value),
+                "--declick" => filter.declick_threshold = Some(value),
+                _ => filter.limit_crest_db = Some(value),
+            }
+        }
+        let any =
+            filter.dc_cutoff.is_some() || filter.declick_threshold.is_some() || filter.limit_crest_db.is_some();
+        Ok(any.then_some(filter))
+    }
+
+    /// Run the enabled stages in order: DC blocker, de-clicker, limiter
+    pub fn apply(&self, signal: &[f64], sample_rate: f64) -> (Vec<f64>, PostFilterReport) {
+        let mut report = PostFilterReport::default();
+        let mut output = signal.to_vec();
+        if let Some(cutoff) = self.dc_cutoff {
+            report.dc_removed = output.iter().sum::<f64>() / output.len().max(1) as f64;
+            output = dc_block(&output, cutoff, sample_rate);
+        }
+        if let Some(threshold) = self.declick_threshold {
+            let (repaired, clicks) = declick(&output, sample_rate, threshold);
+            output = repaired;
+            report.clicks = clicks;
+        }
+        if let Some(crest_db) = self.limit_crest_db {
+            let rms = (output.iter().map(|x| x * x).sum::<f64>() / output.len().max(1) as f64).sqrt();
+            let ceiling = rms * 10f64.powf(crest_db / 20.0);
+            let (limited, count) = limit(&output, ceiling);
+            output = limited;
+            report.limited = count;
+        }
+        (output, report)
+    }
+}
+
+impl std::fmt::Display for PostFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut stages = Vec::new();
+        if let Some(cutoff) = self.dc_cutoff {
+            stages.push(format!("DC blocker {} Hz", cutoff));
+        }
+        if let Some(threshold) = self.declick_threshold {
+            stages.push(format!("de-click x{}", threshold));
+        }
+        if let Some(crest) = self.limit_crest_db {
+            stages.push(format!("limiter {} dB over RMS", crest));
+        }
+        write!(f, "{}", stages.join(", "))
+    }
+}
+
+/// One-pole DC blocker y[n] = x[n] - x[n-1] + R y[n-1], corner near `cutoff` Hz
+///
+/// The filter starts from the first sample's value, so a constant offset does not
+/// produce a step at the beginning.
+pub fn dc_block(signal: &[f64], cutoff: f64, sample_rate: f64) -> Vec<f64> {
+    let r = (-2.0 * std::f64::consts::PI * cutoff / sample_rate).exp();
+    let mut previous_x = signal.first().copied().unwrap_or(0.0);
+    let mut y = 0.0;
+    signal
+        .iter()
+        .map(|&x| {
+            y = x - previous_x + r * y;
+            previous_x = x;
+            y
+        })
+        .collect()
+}
+
+/// Find samples whose second difference exceeds `threshold` times its local RMS (50 ms
+/// window) and bridge each such region, widened by 1 ms, with a straight line
+///
+/// Music and speech have little energy near f_s / 2 where the second difference is
+/// largest, so isolated spikes and filter-edge ringing stand out. Returns the repaired
+/// signal and the number of regions.
+pub fn declick(signal: &[f64], sample_rate: f64, threshold: f64) -> (Vec<f64>, usize) {
+    let n = signal.len();
+    if n < 3 {
+        return (signal.to_vec(), 0);
+    }
+    let mut curvature = vec![0.0; n];
+    for i in 1..n - 1 {
+        curvature[i] = signal[i] - 0.5 * (signal[i - 1] + signal[i + 1]);
+    }
+
+    // Centred moving RMS from a prefix sum of squares
+    let half = ((0.025 * sample_rate) as usize).max(1);
+    let mut prefix = vec![0.0; n + 1];
+    for i in 0..n {
+        prefix[i + 1] = prefix[i] + curvature[i] * curvature[i];
+    }
+    let flagged: Vec<bool> = (0..n)
+        .map(|i| {
+            let (lo, hi) = (i.saturating_sub(half), (i + half + 1).min(n));
+            let rms = ((prefix[hi] - prefix[lo]) / (hi - lo) as f64).sqrt();
+            rms > 0.0 && curvature[i].abs() > threshold * rms
+        })
+        .collect();
+
+    let pad = ((0.001 * sample_rate) as usize).max(1);
+    let mut output = signal.to_vec();
+    let mut clicks = 0;
+    let mut i = 0;
+    while i < n {
+        if !flagged[i] {
+            i += 1;
+            continue;
+        }
+        // Extend while flags keep appearing within the padding
+        let mut end = i;
+        while end + 1 < n && flagged[end + 1..(end + 1 + pad).min(n)].iter().any(|&f| f) {
+            end += 1;
+        }
+        let start = i.saturating_sub(pad);
+        let stop = (end + pad).min(n - 1);
+        let (a, b) = (output[start], output[stop]);
+        let span = (stop - start).max(1) as f64;
+        for (k, sample) in output[start..=stop].iter_mut().enumerate() {
+            *sample = a + (k as f64 / span) * (b - a);
+        }
+        clicks += 1;
+        i = stop + 1;
+    }
+    (output, clicks)
+}
+
+/// Soft limiter: linear up to half the ceiling, then a tanh knee that approaches `ceiling`
+///
+/// The knee has unit slope where it joins the linear part, so quiet passages are untouched
+/// and loud peaks are rounded off instead of clipped. Returns the signal and the number of
+/// samples above the knee.
+pub fn limit(signal: &[f64], ceiling: f64) -> (Vec<f64>, usize) {
+    let knee = 0.5 * ceiling;
+    let mut count = 0;
+    let output = signal
+        .iter()
+        .map(|&x| {
+            if x.abs() <= knee {
+                return x;
+            }
+            count += 1;
+            let excess = (x.abs() - knee) / (ceiling - knee);
+            x.signum() * (knee + (ceiling - knee) * excess.tanh())
+        })
+        .collect();
+    (output, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_dc_block_declick_and_limit() {
+        let fs = 8000.0;
+        let tone: Vec<f64> = (0..8000).map(|i| 0.3 * (2.0 * PI * 300.0 * i as f64 / fs).sin()).collect();
+
+        // DC offset settles out within a few time constants of the 20 Hz corner
+        let offset: Vec<f64> = tone.iter().map(|x| x + 0.5).collect();
+        let blocked = dc_block(&offset, 20.0, fs);
+        let tail_mean = blocked[4000..].iter().sum::<f64>() / 4000.0;
+        assert!(tail_mean.abs() < 1e-3, "mean {}", tail_mean);
+
+        // One spike is found and bridged; the clean tone triggers nothing
+        let mut clicked = tone.clone();
+        clicked[3000] += 1.0;
+        let (repaired, clicks) = declick(&clicked, fs, DEFAULT_DECLICK_THRESHOLD);
+        assert_eq!(clicks, 1);
+        assert!((repaired[3000] - tone[3000]).abs() < 0.1);
+        assert_eq!(declick(&tone, fs, DEFAULT_DECLICK_THRESHOLD).1, 0);
+
+        let (limited, count) = limit(&[0.1, 0.4, 5.0, -5.0], 0.5);
+        assert_eq!(count, 3);
+        assert_eq!(limited[0], 0.1);
+        assert!(limited[2] <= 0.5 && limited[2] > 0.49 && limited[3] == -limited[2]);
+
+        let args = vec!["--post-filter".to_string(), "--limit".to_string(), "10".to_string()];
+        let filter = PostFilter::from_args(&args).unwrap().unwrap();
+        assert_eq!(filter.limit_crest_db, Some(10.0));
+        assert_eq!(filter.dc_cutoff, Some(DEFAULT_DC_CUTOFF));
+    }
+}
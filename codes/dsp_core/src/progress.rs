@@ -0,0 +1,89 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use log::LevelFilter;
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Set while a visible bar is on screen, so loops nested inside it (an STFT inside a
+/// Monte-Carlo trial, say) stay silent instead of fighting over the terminal line
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Progress bar on stderr for a long loop
+///
+/// The bar is drawn only when stderr is a terminal and info messages are enabled (no `-q`),
+/// so redirected output and quiet batch runs stay clean, and only the outermost bar is
+/// shown. It is cleared when finished or dropped.
+pub struct Progress {
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Bar over `len` steps, labelled with `message`
+    pub fn new(len: u64, message: impl Into<Cow<'static, str>>) -> Self {
+        let bar = Self::claim().then(|| {
+            let style = ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len} ({eta})")
+                .expect("valid progress template")
+                .progress_chars("=> ");
+            ProgressBar::new(len).with_style(style).with_message(message)
+        });
+        Progress { bar }
+    }
+
+    /// Spinner for a single step of unknown duration, such as one large FFT
+    pub fn spinner(message: impl Into<Cow<'static, str>>) -> Self {
+        let bar = Self::claim().then(|| {
+            let bar = ProgressBar::new_spinner().with_message(message);
+            bar.enable_steady_tick(Duration::from_millis(100));
+            bar
+        });
+        Progress { bar }
+    }
+
+    /// Whether this bar is drawn
+    pub fn is_visible(&self) -> bool {
+        self.bar.is_some()
+    }
+
+    /// Advance by `delta` steps
+    pub fn inc(&self, delta: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+        }
+    }
+
+    /// Clear the bar from the terminal
+    pub fn finish(self) {}
+
+    /// Take the terminal line if a bar should be drawn and none is showing yet
+    fn claim() -> bool {
+        let wanted = log::max_level() >= LevelFilter::Info && std::io::stderr().is_terminal();
+        wanted && !ACTIVE.swap(true, Ordering::AcqRel)
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+            ACTIVE.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_without_terminal() {
+        // Test output is captured, so stderr is never a terminal here
+        if !std::io::stderr().is_terminal() {
+            let progress = Progress::new(10, "test");
+            assert!(!progress.is_visible());
+            progress.inc(5);
+            progress.finish();
+            assert!(!ACTIVE.load(Ordering::Acquire));
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use crate::error::{DspError, Result};
+use crate::html_plot::escape_html;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Output directory and section heading of each question, in report order
+pub const QUESTIONS: [(&str, &str); 4] = [
+    ("Q1", "Q1: Spectrum Analysis and Frequency Offset Estimation"),
+    ("Q2", "Q2: Butterworth Filter Design"),
+    ("Q3", "Q3: Time-Domain Demodulation"),
+    ("Q4", "Q4: Frequency-Domain Demodulation"),
+];
+
+/// CSV rows shown per table; spectra and waveforms run to thousands of rows
+const MAX_TABLE_ROWS: usize = 12;
+
+/// Lines of a text or JSON file shown before it is cut off
+const MAX_TEXT_LINES: usize = 80;
+
+/// Captions of the known outputs; anything else is captioned from its file name
+const CAPTIONS: &[(&str, &str)] = &[
+    ("Q1_results.txt", "Estimated frequency offset f_d, its uncertainty and the sampling rate"),
+    ("Q1_results.json", "Q1 parameters and metrics in the shared results schema"),
+    ("Q1_waveform.png", "Waveform of the recording"),
+    ("Q1_spectrum_full.png", "Magnitude spectrum of the recording"),
+    ("Q1_spectrum_db.png", "Magnitude spectrum of the recording in dB"),
+    ("Q1_spectrum_lowfreq.png", "Low-frequency detail of the spectrum"),
+    ("Q1_spectrum_centered.png", "Two-sided spectrum centred on 0 Hz"),
+    ("Q1_energy_distribution.png", "Spectral energy per frequency band"),
+    ("Q1_offset_track.png", "Frequency offset tracked over time"),
+    ("Q1_waterfall.png", "Waterfall of short-time spectra"),
+    ("Q1_bench_estimator.png", "Offset estimator error on synthetic signals"),
+    ("Q1_peaks.csv", "Strongest spectral peaks"),
+    ("Q1_summary.csv", "Summary of the spectrum analysis"),
+    ("Q2_filter_coefficients.txt", "Coefficients of the Butterworth high-pass and low-pass filters"),
+    ("Q2_frequency_response.txt", "Gain of both filters at key frequencies"),
+    ("Q2_attenuation.csv", "Filter gains at f_d ± f_B and at the detected peaks"),
+    ("Q2_results.json", "Q2 parameters and metrics in the shared results schema"),
+    ("Q2_combined_magnitude.png", "Magnitude responses of both filters"),
+    ("Q2_highpass_magnitude.png", "High-pass magnitude response"),
+    ("Q2_highpass_magnitude_db.png", "High-pass magnitude response in dB"),
+    ("Q2_highpass_phase.png", "High-pass phase response"),
+    ("Q2_lowpass_magnitude.png", "Low-pass magnitude response"),
+    ("Q2_lowpass_magnitude_db.png", "Low-pass magnitude response in dB"),
+    ("Q2_lowpass_phase.png", "Low-pass phase response"),
+    ("Q3_results.txt", "Time-domain demodulation parameters and signal statistics"),
+    ("Q3_results.json", "Q3 parameters and metrics in the shared results schema"),
+    ("Q3_fixed_point.txt", "Q15 and Q31 fixed-point filters against floating point"),
+    ("Q3_chain_verification.txt", "End-to-end gain of the time-domain chain for probe tones"),
+    ("Q3_summary.txt", "Summary of the time-domain demodulation"),
+    ("Q3_original_spectrum.png", "Spectrum of the input X(f)"),
+    ("Q3_xh_spectrum.png", "Spectrum after the high-pass filter X_h(f)"),
+    ("Q3_xb_spectrum.png", "Spectrum after mixing with the local oscillator X_b(f)"),
+    ("Q3_xl_spectrum.png", "Spectrum after the low-pass filter X_l(f)"),
+    ("Q3_stage_waveforms.png", "Waveforms of the four stages of the time-domain demodulator"),
+    ("Q3_spectrogram_comparison.png", "Spectrograms of the input and the demodulated output"),
+    ("Q3_error_comparison.png", "Demodulated spectra of the three incorrect schemes"),
+    ("Q3_demodulated.wav", "Demodulated audio (time domain)"),
+    ("Q4_results.txt", "Frequency-domain demodulation parameters and spectral descriptors"),
+    ("Q4_results.json", "Q4 parameters and metrics in the shared results schema"),
+    ("Q4_comparison.txt", "Q3 vs Q4 comparison metrics"),
+    ("Q4_comparison.json", "Q3 vs Q4 comparison metrics, machine-readable"),
+    ("Q4_comparison_active.txt", "Q3 vs Q4 comparison metrics over the active segments only"),
+    ("Q4_spectrum_stages.png", "Spectra of the four stages of the frequency-domain demodulator"),
+    ("Q4_stage_waveforms.png", "Waveforms of the four stages of the frequency-domain demodulator"),
+    ("Q4_original_spectrum.png", "Spectrum of the input X(f)"),
+    ("Q4_xh_spectrum.png", "Spectrum after the ideal high-pass X_h(f)"),
+    ("Q4_xb_spectrum.png", "Spectrum after the frequency shift X_b(f)"),
+    ("Q4_xl_spectrum.png", "Spectrum after the ideal low-pass X_l(f)"),
+    ("Q4_vs_Q3_comparison.png", "Q3 and Q4 outputs, detail view"),
+    ("Q4_vs_Q3_full_comparison.png", "Q3 and Q4 outputs over the whole recording"),
+    ("Q4_vs_Q3_error.png", "Difference between the Q4 and Q3 outputs in time and frequency"),
+    ("Q4_vs_Q3_segmental.png", "Per-frame SNR and correlation of the aligned outputs"),
+    ("Q4_method_matrix.txt", "Agreement and harmonic distortion of all demodulation methods"),
+    ("Q4_method_matrix.csv", "Pairwise metrics of all demodulation methods"),
+    ("Q4_method_matrix_heatmap.png", "Normalized correlation between demodulation methods"),
+    ("Q4_method_overlay.png", "Outputs of all demodulation methods, peak-normalized"),
+    ("Q4_denoise.txt", "Noise profile and SNR before and after denoising"),
+    ("Q4_denoise_spectra.png", "Average output spectrum before and after denoising"),
+    ("Q4_demodulated.wav", "Demodulated audio (frequency domain)"),
+];
+
+/// What an output file is shown as
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    /// An image (PNG or SVG), or a PDF plot that can only be linked
+    Figure,
+    /// Text or JSON, shown verbatim up to [`MAX_TEXT_LINES`]
+    Text { lines: Vec<String>, omitted: usize },
+    /// A CSV file, shown as a table of its first [`MAX_TABLE_ROWS`] rows
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+        omitted: usize,
+    },
+    Audio,
+    /// An interactive HTML page
+    Page,
+}
+
+/// One output file; `path` is relative to the `codes` directory, with `/` separators
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: String,
+    pub caption: String,
+    pub item: Item,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub entries: Vec<Entry>,
+}
+
+/// Every output of Q1–Q4 in one document, with numbered and captioned figures and tables
+///
+/// Links are relative to the `codes` directory, so the Markdown and HTML versions belong
+/// there, next to the question folders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+impl Report {
+    /// Read `<codes_dir>/Q*/output`; questions without an output directory are left out
+    ///
+    /// Within a section, results and parameters come first, then tables, figures, audio
+    /// and interactive pages, each sorted by file name.
+    pub fn collect(codes_dir: &Path) -> Result<Self> {
+        let mut sections = Vec::new();
+        for (question, title) in QUESTIONS {
+            let dir = codes_dir.join(question).join("output");
+            let Ok(listing) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut names: Vec<String> = listing
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect();
+            names.sort();
+
+            let mut entries = Vec::new();
+            for name in names {
+                let file = dir.join(&name);
+                let item = match extension(&name).as_str() {
+                    "png" | "svg" | "pdf" => Item::Figure,
+                    "wav" => Item::Audio,
+                    "html" => Item::Page,
+                    "txt" | "json" => {
+                        let content = read(&file)?;
+                        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+                        let omitted = lines.len().saturating_sub(MAX_TEXT_LINES);
+                        Item::Text {
+                            lines: lines.into_iter().take(MAX_TEXT_LINES).collect(),
+                            omitted,
+                        }
+                    }
+                    "csv" => {
+                        let content = read(&file)?;
+                        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+                        let split = |line: &str| line.split(',').map(|cell| cell.trim().to_string()).collect();
+                        let header = lines.next().map(split).unwrap_or_default();
+                        let rows: Vec<Vec<String>> = lines.map(split).collect();
+                        let omitted = rows.len().saturating_sub(MAX_TABLE_ROWS);
+                        Item::Table {
+                            header,
+                            rows: rows.into_iter().take(MAX_TABLE_ROWS).collect(),
+                            omitted,
+                        }
+                    }
+                    _ => continue,
+                };
+                entries.push(Entry {
+                    path: format!("{}/output/{}", question, name),
+                    caption: caption(&name),
+                    item,
+                });
+            }
+            entries.sort_by_key(|entry| rank(&entry.item));
+            sections.push(Section {
+                title: title.to_string(),
+                entries,
+            });
+        }
+
+        if sections.is_empty() {
+            return Err(DspError::Data(format!("No Q1-Q4 output directories under {}", codes_dir.display())));
+        }
+        Ok(Report {
+            title: "AM Signal Demodulation: Results".to_string(),
+            sections,
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# {}\n", self.title);
+        for section in &self.sections {
+            let _ = writeln!(md, "- [{}](#{})", section.title, anchor(&section.title));
+        }
+
+        let (mut figures, mut tables) = (0, 0);
+        for section in &self.sections {
+            let _ = writeln!(md, "\n## {}", section.title);
+            if section.entries.is_empty() {
+                md.push_str("\nNo outputs yet; run this question's program first.\n");
+            }
+            for entry in &section.entries {
+                let file = file_name(&entry.path);
+                match &entry.item {
+                    Item::Figure if extension(file) == "pdf" => {
+                        figures += 1;
+                        let _ = writeln!(md, "\n**Figure {}.** {} ([{}]({}))", figures, entry.caption, file, entry.path);
+                    }
+                    Item::Figure => {
+                        figures += 1;
+                        let _ = writeln!(md, "\n![{}]({})\n", entry.caption, entry.path);
+                        let _ = writeln!(md, "**Figure {}.** {} (`{}`)", figures, entry.caption, file);
+                    }
+                    Item::Text { lines, omitted } => {
+                        let _ = writeln!(md, "\n**{}** (`{}`)\n", entry.caption, file);
+                        md.push_str("```text\n");
+                        for line in lines {
+                            let _ = writeln!(md, "{}", line);
+                        }
+                        md.push_str("```\n");
+                        if *omitted > 0 {
+                            let _ = writeln!(md, "\n*{} more lines in [{}]({}).*", omitted, file, entry.path);
+                        }
+                    }
+                    Item::Table { header, rows, omitted } => {
+                        tables += 1;
+                        let _ = writeln!(md, "\n**Table {}.** {} (`{}`)\n", tables, entry.caption, file);
+                        let row = |cells: &[String]| {
+                            let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                            format!("| {} |", cells.join(" | "))
+                        };
+                        let _ = writeln!(md, "{}", row(header));
+                        let _ = writeln!(md, "|{}", "---|".repeat(header.len().max(1)));
+                        for cells in rows {
+                            let _ = writeln!(md, "{}", row(cells));
+                        }
+                        if *omitted > 0 {
+                            let _ = writeln!(md, "\n*{} more rows in [{}]({}).*", omitted, file, entry.path);
+                        }
+                    }
+                    Item::Audio | Item::Page => {
+                        let _ = writeln!(md, "\n- [{}]({}): {}", file, entry.path, entry.caption);
+                    }
+                }
+            }
+        }
+        md
+    }
+
+    /// A standalone page; images and audio are linked, not embedded
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = escape_html(&self.title);
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(html, "<title>{}</title>", title);
+        let _ = writeln!(html, "<style>{}</style>", STYLE);
+        html.push_str("</head>\n<body>\n");
+        let _ = writeln!(html, "<h1>{}</h1>\n<ul>", title);
+        for section in &self.sections {
+            let _ = writeln!(
+                html,
+                "<li><a href=\"#{}\">{}</a></li>",
+                anchor(&section.title),
+                escape_html(&section.title)
+            );
+        }
+        html.push_str("</ul>\n");
+
+        let (mut figures, mut tables) = (0, 0);
+        for section in &self.sections {
+            let _ = writeln!(
+                html,
+                "<h2 id=\"{}\">{}</h2>",
+                anchor(&section.title),
+                escape_html(&section.title)
+            );
+            if section.entries.is_empty() {
+                html.push_str("<p>No outputs yet; run this question's program first.</p>\n");
+            }
+            for entry in &section.entries {
+                let caption = escape_html(&entry.caption);
+                let path = escape_attribute(&entry.path);
+                let file = escape_html(file_name(&entry.path));
+                match &entry.item {
+                    Item::Figure => {
+                        figures += 1;
+                        let media = if extension(&entry.path) == "pdf" {
+                            format!("<a href=\"{}\">{}</a>", path, file)
+                        } else {
+                            format!("<img src=\"{}\" alt=\"{}\">", path, escape_attribute(&entry.caption))
+                        };
+                        let _ = writeln!(
+                            html,
+                            "<figure>{}<figcaption><b>Figure {}.</b> {} (<code>{}</code>)</figcaption></figure>",
+                            media, figures, caption, file
+                        );
+                    }
+                    Item::Text { lines, omitted } => {
+                        let _ = writeln!(html, "<h3>{} (<a href=\"{}\">{}</a>)</h3>", caption, path, file);
+                        let _ = writeln!(html, "<pre>{}</pre>", escape_html(&lines.join("\n")));
+                        if *omitted > 0 {
+                            let _ = writeln!(html, "<p class=\"more\">{} more lines in the file.</p>", omitted);
+                        }
+                    }
+                    Item::Table { header, rows, omitted } => {
+                        tables += 1;
+                        let _ = writeln!(
+                            html,
+                            "<table>\n<caption><b>Table {}.</b> {} (<a href=\"{}\">{}</a>)</caption>",
+                            tables, caption, path, file
+                        );
+                        let cells = |cells: &[String], tag: &str| {
+                            cells.iter().map(|c| format!("<{0}>{1}</{0}>", tag, escape_html(c))).collect::<String>()
+                        };
+                        let _ = writeln!(html, "<tr>{}</tr>", cells(header, "th"));
+                        for row in rows {
+                            let _ = writeln!(html, "<tr>{}</tr>", cells(row, "td"));
+                        }
+                        html.push_str("</table>\n");
+                        if *omitted > 0 {
+                            let _ = writeln!(html, "<p class=\"more\">{} more rows in the file.</p>", omitted);
+                        }
+                    }
+                    Item::Audio => {
+                        let _ = writeln!(
+                            html,
+                            "<p><b>{}</b> (<code>{}</code>)<br><audio controls src=\"{}\"></audio></p>",
+                            caption, file, path
+                        );
+                    }
+                    Item::Page => {
+                        let _ = writeln!(html, "<p><a href=\"{}\">{}</a>: {}</p>", path, file, caption);
+                    }
+                }
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| DspError::io(path, e))
+}
+
+fn extension(name: &str) -> String {
+    name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default()
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn rank(item: &Item) -> u8 {
+    match item {
+        Item::Text { .. } => 0,
+        Item::Table { .. } => 1,
+        Item::Figure => 2,
+        Item::Audio => 3,
+        Item::Page => 4,
+    }
+}
+
+/// Known caption, or the file name without the question prefix, e.g.
+/// `Q3_error_case1_spectrum.png` becomes "Error case1 spectrum"
+fn caption(name: &str) -> String {
+    if let Some((_, caption)) = CAPTIONS.iter().find(|(known, _)| *known == name) {
+        return caption.to_string();
+    }
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    let stem = stem.split_once('_').map_or(stem, |(prefix, rest)| {
+        if prefix.len() == 2 && prefix.starts_with('Q') {
+            rest
+        } else {
+            stem
+        }
+    });
+    let words = stem.replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name.to_string(),
+    }
+}
+
+/// Heading id shared by both formats, e.g. "q1-spectrum-analysis-and-frequency-offset-estimation"
+fn anchor(title: &str) -> String {
+    let mut id = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if c == ' ' || c == '-' {
+            id.push('-');
+        }
+    }
+    id
+}
+
+fn escape_attribute(value: &str) -> String {
+    escape_html(value).replace('"', "&quot;")
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 1100px; margin: 20px auto; padding: 0 20px; color: #222; }
+h1 { font-size: 24px; }
+h2 { font-size: 20px; margin-top: 40px; border-bottom: 1px solid #ccc; }
+h3 { font-size: 15px; margin-top: 24px; }
+figure { margin: 24px 0; }
+figure img { max-width: 100%; border: 1px solid #eee; }
+figcaption, caption { font-size: 14px; color: #444; }
+caption { caption-side: top; text-align: left; padding-bottom: 6px; }
+table { border-collapse: collapse; margin: 24px 0 8px; font-size: 13px; }
+th, td { border: 1px solid #ccc; padding: 3px 8px; text-align: right; }
+pre { background: #f6f6f6; padding: 10px; font-size: 13px; overflow-x: auto; }
+.more { font-size: 13px; color: #666; }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_and_render() {
+        let codes = std::env::temp_dir().join(format!("dsp_core_report_{}", std::process::id()));
+        let output = codes.join("Q1").join("output");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("Q1_results.txt"), "f_d = 3000 Hz\n").unwrap();
+        std::fs::write(output.join("Q1_waveform.png"), b"").unwrap();
+        std::fs::write(output.join("Q1_extra_plot.png"), b"").unwrap();
+        let rows: String = (0..20).map(|i| format!("{},{}\n", i, i * i)).collect();
+        std::fs::write(output.join("Q1_peaks.csv"), format!("Frequency (Hz),Magnitude <dB>\n{}", rows)).unwrap();
+        std::fs::write(output.join("notes.bin"), b"").unwrap();
+
+        let report = Report::collect(&codes).unwrap();
+        std::fs::remove_dir_all(&codes).unwrap();
+        assert_eq!(report.sections.len(), 1);
+        let entries = &report.sections[0].entries;
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["Q1/output/Q1_results.txt", "Q1/output/Q1_peaks.csv", "Q1/output/Q1_extra_plot.png", "Q1/output/Q1_waveform.png"]
+        );
+        assert_eq!(entries[2].caption, "Extra plot");
+        assert!(matches!(&entries[1].item, Item::Table { rows, omitted: 8, .. } if rows.len() == MAX_TABLE_ROWS));
+
+        let md = report.to_markdown();
+        assert!(md.contains("![Waveform of the recording](Q1/output/Q1_waveform.png)"));
+        assert!(md.contains("**Figure 2.** Waveform of the recording"));
+        assert!(md.contains("| Frequency (Hz) | Magnitude <dB> |"));
+        assert!(md.contains("*8 more rows in [Q1_peaks.csv](Q1/output/Q1_peaks.csv).*"));
+        let html = report.to_html();
+        assert!(html.contains("<th>Magnitude &lt;dB&gt;</th>"));
+        assert!(html.contains("<h2 id=\"q1-spectrum-analysis-and-frequency-offset-estimation\">"));
+        assert!(Report::collect(&codes).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::{DspError, Result};
+use std::path::{Path, PathBuf};
+
+/// Base seed when none is given, the value every generator used before `--seed` existed
+pub const DEFAULT_SEED: u64 = 1;
+
+/// Random seeds and deterministic-mode settings of one run
+///
+/// Every random stream (channel noise, dither, Monte-Carlo trials) draws its seed from
+/// here, so `--seed` changes all of them at once and the same seed reproduces a run
+/// exactly. Without `--seed` each stream keeps its historical fixed seed, so the default
+/// outputs do not change.
+///
+/// `--deterministic` additionally records the seed in the results and writes a copy of
+/// each results file whose name carries the [parameter hash](crate::results::StageResults::parameter_hash),
+/// so two runs with the same inputs and settings produce byte-identical files under the
+/// same names.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::reproducible::Reproducibility;
+///
+/// let args: Vec<String> = ["--seed", "42"].iter().map(|s| s.to_string()).collect();
+/// let seeded = Reproducibility::from_args(&args).unwrap();
+/// assert_ne!(seeded.stream_seed("noise", 1), seeded.stream_seed("dither", 1));
+/// assert_eq!(Reproducibility::default().stream_seed("noise", 1), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Reproducibility {
+    /// Base seed from `--seed`; `None` keeps each stream's default
+    pub seed: Option<u64>,
+    pub deterministic: bool,
+}
+
+impl Reproducibility {
+    /// Read `--seed <n>` and `--deterministic` from command-line arguments
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut reproducibility = Reproducibility::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--seed"))?;
+                    reproducibility.seed =
+                        Some(value.parse().map_err(|_| DspError::Argument(format!("Invalid seed '{}'", value)))?);
+                }
+                "--deterministic" => reproducibility.deterministic = true,
+                _ => {}
+            }
+        }
+        Ok(reproducibility)
+    }
+
+    /// Base seed of the run
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or(DEFAULT_SEED)
+    }
+
+    /// Seed of the random stream called `stream`
+    ///
+    /// Without `--seed` this is `default`; with it, the base seed mixed with the stream
+    /// name, so streams stay independent of each other but all follow the base seed.
+    pub fn stream_seed(&self, stream: &str, default: u64) -> u64 {
+        match self.seed {
+            Some(seed) => stable_hash_with(seed, stream.as_bytes()).max(1),
+            None => default,
+        }
+    }
+
+    /// Where deterministic mode writes the hash-tagged copy of `path`: `Q1_results.json`
+    /// becomes `Q1_results.<hash>.json`; `None` outside deterministic mode
+    pub fn tagged_path(&self, path: &Path, hash: &str) -> Option<PathBuf> {
+        if !self.deterministic {
+            return None;
+        }
+        let stem = path.file_stem()?.to_string_lossy();
+        let name = match path.extension() {
+            Some(ext) => format!("{}.{}.{}", stem, hash, ext.to_string_lossy()),
+            None => format!("{}.{}", stem, hash),
+        };
+        Some(path.with_file_name(name))
+    }
+}
+
+impl std::fmt::Display for Reproducibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.seed {
+            Some(seed) => write!(f, "seed {}", seed)?,
+            None => write!(f, "default seeds")?,
+        }
+        if self.deterministic {
+            write!(f, ", deterministic")?;
+        }
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a hash of `bytes`
+///
+/// Unlike `std`'s `DefaultHasher`, the value is fixed by the algorithm and does not change
+/// between Rust releases or platforms, so it can go into file names and metadata.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    stable_hash_with(0xcbf2_9ce4_8422_2325, bytes)
+}
+
+/// [`Hasher`](std::hash::Hasher) running [`stable_hash`] over everything written to it
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(stable_hash(b""))
+    }
+}
+
+impl std::hash::Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = stable_hash_with(self.0, bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn stable_hash_with(basis: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(basis, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::Hasher;
+
+    #[test]
+    fn test_seeds_and_tagged_paths() {
+        // Reference values of FNV-1a
+        assert_eq!(stable_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        let mut hasher = StableHasher::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), stable_hash(b"a"));
+
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let default = Reproducibility::from_args(&args("--snr 10")).unwrap();
+        assert_eq!(default.stream_seed("dither", 7), 7);
+        assert_eq!(default.tagged_path(Path::new("out/Q1_results.json"), "ab12"), None);
+
+        let seeded = Reproducibility::from_args(&args("--seed 42 --deterministic")).unwrap();
+        assert_eq!(seeded.seed(), 42);
+        assert_ne!(seeded.stream_seed("dither", 7), seeded.stream_seed("noise", 7));
+        assert_eq!(seeded.stream_seed("dither", 7), Reproducibility { seed: Some(42), deterministic: false }.stream_seed("dither", 0));
+        assert_eq!(
+            seeded.tagged_path(Path::new("out/Q1_results.json"), "ab12"),
+            Some(PathBuf::from("out/Q1_results.ab12.json"))
+        );
+        assert!(Reproducibility::from_args(&args("--seed x")).is_err());
+    }
+}
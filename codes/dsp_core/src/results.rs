@@ -0,0 +1,303 @@
+use crate::error::{DspError, Result};
+use crate::reproducible::{self, Reproducibility};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Identifies the layout written by [`StageResults`]; bump it when the layout changes
+pub const SCHEMA: &str = "dsp_core.results.v2";
+
+/// A JSON value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// Non-finite numbers are written as `null`, since JSON has no infinity or NaN
+    Number(f64),
+    Text(String),
+    List(Vec<Value>),
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl From<&[f64]> for Value {
+    fn from(values: &[f64]) -> Self {
+        Value::List(values.iter().map(|&v| Value::Number(v)).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// One row of a results table, as (column, value) pairs
+pub type Row = Vec<(String, Value)>;
+
+/// Machine-readable results of one stage (Q1–Q4), next to its human-readable `.txt`
+///
+/// Every stage writes the same layout, so results can be diffed between runs, kept under
+/// version control and loaded by other tools:
+///
+/// ```text
+/// {
+///   "schema": "dsp_core.results.v2",
+///   "stage": "Q1",
+///   "parameter_hash": "5f0c…",                 stable hash of the stage and its parameters
+///   "parameters": { "f_s_hz": 22050, ... },   inputs and settings the stage used
+///   "metrics": { "f_d_hz": 3000.18, ... },     scalar results it measured
+///   "tables": { "peaks": [ { ... }, ... ] }    lists of records, e.g. peaks or bands
+/// }
+/// ```
+///
+/// Names use snake_case with a unit suffix (`_hz`, `_db`, `_s`) where there is a unit.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::results::StageResults;
+/// use dsp_core::row;
+///
+/// let results = StageResults::new("Q1")
+///     .parameter("f_s_hz", 22050.0)
+///     .metric("f_d_hz", 3000.18)
+///     .table("peaks", vec![row![("frequency_hz", 2775.5), ("index", 3935usize)]]);
+/// assert!(results.to_json().contains("\"f_d_hz\": 3000.18"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageResults {
+    pub stage: String,
+    pub parameters: Vec<(String, Value)>,
+    pub metrics: Vec<(String, Value)>,
+    pub tables: Vec<(String, Vec<Row>)>,
+}
+
+impl StageResults {
+    pub fn new(stage: &str) -> Self {
+        StageResults {
+            stage: stage.to_string(),
+            parameters: Vec::new(),
+            metrics: Vec::new(),
+            tables: Vec::new(),
+        }
+    }
+
+    pub fn parameter(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.parameters.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn metric(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.metrics.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn table(mut self, name: &str, rows: Vec<Row>) -> Self {
+        self.tables.push((name.to_string(), rows));
+        self
+    }
+
+    /// Record the seed and deterministic mode among the parameters, when either was given
+    pub fn reproducibility(mut self, reproducibility: &Reproducibility) -> Self {
+        if let Some(seed) = reproducibility.seed {
+            self = self.parameter("seed", seed.to_string());
+        }
+        if reproducibility.deterministic {
+            self = self.parameter("deterministic", true);
+        }
+        self
+    }
+
+    /// Hex FNV-1a hash of the stage name and parameters, identical for two runs with the
+    /// same inputs and settings whatever their results
+    pub fn parameter_hash(&self) -> String {
+        let mut text = self.stage.clone();
+        for (key, value) in &self.parameters {
+            let _ = write!(text, "\n{}={}", key, value.to_json());
+        }
+        format!("{:016x}", reproducible::stable_hash(text.as_bytes()))
+    }
+
+    /// Pretty-printed JSON, one parameter, metric or table row per line so diffs stay small
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n");
+        let _ = writeln!(out, "  \"schema\": {},", json_string(SCHEMA));
+        let _ = writeln!(out, "  \"stage\": {},", json_string(&self.stage));
+        let _ = writeln!(out, "  \"parameter_hash\": {},", json_string(&self.parameter_hash()));
+        for (name, fields) in [("parameters", &self.parameters), ("metrics", &self.metrics)] {
+            let _ = write!(out, "  \"{}\": {{", name);
+            for (i, (key, value)) in fields.iter().enumerate() {
+                let _ = write!(out, "{}\n    {}: {}", if i > 0 { "," } else { "" }, json_string(key), value.to_json());
+            }
+            out.push_str(if fields.is_empty() { "},\n" } else { "\n  },\n" });
+        }
+        out.push_str("  \"tables\": {");
+        for (i, (name, rows)) in self.tables.iter().enumerate() {
+            let _ = write!(out, "{}\n    {}: [", if i > 0 { "," } else { "" }, json_string(name));
+            for (j, row) in rows.iter().enumerate() {
+                let _ = write!(out, "{}\n      {}", if j > 0 { "," } else { "" }, object(row));
+            }
+            out.push_str(if rows.is_empty() { "]" } else { "\n    ]" });
+        }
+        out.push_str(if self.tables.is_empty() { "}\n}\n" } else { "\n  }\n}\n" });
+        out
+    }
+
+    /// Newline-delimited JSON: one record per parameter, metric and table row, each
+    /// tagged with the stage and section, so the files of all stages can be concatenated
+    /// into one stream
+    pub fn to_ndjson(&self) -> String {
+        let mut out = String::new();
+        let tag = |section: &str| format!("\"schema\":{},\"stage\":{},\"section\":{}", json_string(SCHEMA), json_string(&self.stage), json_string(section));
+        for (section, fields) in [("parameters", &self.parameters), ("metrics", &self.metrics)] {
+            for (key, value) in fields {
+                let _ = writeln!(out, "{{{},\"name\":{},\"value\":{}}}", tag(section), json_string(key), value.to_json());
+            }
+        }
+        for (name, rows) in &self.tables {
+            for row in rows {
+                let fields = object(row);
+                let _ = writeln!(out, "{{{},\"table\":{},\"row\":{}}}", tag("tables"), json_string(name), fields);
+            }
+        }
+        out
+    }
+
+    /// Write [`to_ndjson`](Self::to_ndjson) for a `.ndjson` path, [`to_json`](Self::to_json) otherwise
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let content = if path.extension().is_some_and(|ext| ext == "ndjson") {
+            self.to_ndjson()
+        } else {
+            self.to_json()
+        };
+        std::fs::write(path, content).map_err(|e| DspError::io(path, e))
+    }
+
+    /// [`save`](Self::save), plus in deterministic mode a copy named after the
+    /// [parameter hash](Self::parameter_hash), e.g. `Q1_results.5f0c….json`
+    pub fn save_with(&self, path: impl AsRef<Path>, reproducibility: &Reproducibility) -> Result<()> {
+        let path = path.as_ref();
+        self.save(path)?;
+        match reproducibility.tagged_path(path, &self.parameter_hash()) {
+            Some(tagged) => self.save(tagged),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Value {
+    fn to_json(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(v) if v.is_finite() => v.to_string(),
+            Value::Number(_) => "null".to_string(),
+            Value::Text(s) => json_string(s),
+            Value::List(values) => {
+                let items: Vec<String> = values.iter().map(Value::to_json).collect();
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+fn object(fields: &[(String, Value)]) -> String {
+    let items: Vec<String> = fields.iter().map(|(k, v)| format!("{}: {}", json_string(k), v.to_json())).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Build a table row: `row![("frequency_hz", f), ("magnitude", m)]`
+#[macro_export]
+macro_rules! row {
+    ($(($name:expr, $value:expr)),* $(,)?) => {
+        vec![$(($name.to_string(), $crate::results::Value::from($value))),*]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_and_ndjson() {
+        let results = StageResults::new("Q1")
+            .parameter("f_s_hz", 22050.0)
+            .parameter("window", "Hann \"periodic\"")
+            .metric("f_d_hz", 3000.25)
+            .metric("snr_db", f64::INFINITY)
+            .metric("music_hz", None::<f64>)
+            .table("peaks", vec![row![("frequency_hz", 2775.5), ("index", 3935usize)]])
+            .table("bands", vec![]);
+        let json = results.to_json();
+        assert!(json.starts_with("{\n  \"schema\": \"dsp_core.results.v2\",\n  \"stage\": \"Q1\",\n  \"parameter_hash\": \""));
+        // The hash follows the parameters only
+        let hash = results.parameter_hash();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, results.clone().metric("extra", 1.0).parameter_hash());
+        assert_ne!(hash, results.clone().parameter("order", 8usize).parameter_hash());
+        assert!(json.contains("\n    \"window\": \"Hann \\\"periodic\\\"\""));
+        assert!(json.contains("\"snr_db\": null,\n    \"music_hz\": null\n  },"));
+        assert!(json.contains("\"peaks\": [\n      {\"frequency_hz\": 2775.5, \"index\": 3935}\n    ],\n    \"bands\": []\n  }\n}\n"));
+        assert_eq!(StageResults::new("Q2").to_json().matches("{}").count(), 3);
+
+        let ndjson = results.to_ndjson();
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[5],
+            "{\"schema\":\"dsp_core.results.v2\",\"stage\":\"Q1\",\"section\":\"tables\",\"table\":\"peaks\",\"row\":{\"frequency_hz\": 2775.5, \"index\": 3935}}"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::error::{DspError, Result};
+use crate::reproducible::stable_hash;
+use crate::results::json_string;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Identifies the layout of `manifest.json`; bump it when the layout changes
+pub const MANIFEST_SCHEMA: &str = "dsp_core.manifest.v1";
+
+/// Name of the link in the output directory that points at the newest run
+pub const LATEST: &str = "latest";
+
+/// Coarse file-system clocks can stamp a file slightly before the run started
+const MTIME_SLACK: Duration = Duration::from_secs(1);
+
+/// Archive of one run under `output/run-<tag>/`
+///
+/// The stages keep writing into `output/` as before, so downstream stages and the report
+/// find the newest files where they always did. At the end of the run, [`finish`](Self::finish)
+/// copies every file the run wrote into `output/run-<tag>/`, adds a `manifest.json` listing
+/// the inputs, arguments, git commit and produced files, and points `output/latest` at the
+/// new directory. Earlier runs stay untouched in their own directories.
+#[derive(Debug, Clone)]
+pub struct RunDirectory {
+    /// Output directory the stage writes into, e.g. `output`
+    pub output: PathBuf,
+    /// `run-<tag>` directory name without the prefix
+    pub tag: String,
+    started: SystemTime,
+}
+
+impl RunDirectory {
+    /// Read `--run-dir` (tagged with the UTC start time) or `--run-tag <tag>` from
+    /// command-line arguments
+    ///
+    /// Returns `None` when neither is given, so nothing is archived.
+    pub fn from_args(args: &[String], output: impl Into<PathBuf>) -> Result<Option<Self>> {
+        let mut tag = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--run-dir" => tag = tag.or_else(|| Some(timestamp_tag(SystemTime::now()))),
+                "--run-tag" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--run-tag"))?;
+                    if value.is_empty() || value == LATEST || value.contains(['/', '\\']) || value.starts_with('.') {
+                        return Err(DspError::invalid_value("--run-tag", value));
+                    }
+                    tag = Some(value.clone());
+                }
+                _ => {}
+            }
+        }
+        Ok(tag.map(|tag| RunDirectory {
+            output: output.into(),
+            tag,
+            started: SystemTime::now(),
+        }))
+    }
+
+    /// `output/run-<tag>`
+    pub fn path(&self) -> PathBuf {
+        self.output.join(format!("run-{}", self.tag))
+    }
+
+    /// Copy the files written since the run started, write the manifest and update
+    /// `output/latest`; returns the run directory
+    ///
+    /// Only regular files directly in the output directory are archived.
+    pub fn finish(&self, stage: &str, inputs: &[&str], args: &[String]) -> Result<PathBuf> {
+        let dir = self.path();
+        std::fs::create_dir_all(&dir).map_err(|e| DspError::io(&dir, e))?;
+
+        let mut produced = Vec::new();
+        let entries = std::fs::read_dir(&self.output).map_err(|e| DspError::io(&self.output, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| DspError::io(&self.output, e))?;
+            let metadata = entry.metadata().map_err(|e| DspError::io(entry.path(), e))?;
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            if metadata.is_file() && modified + MTIME_SLACK >= self.started {
+                let target = dir.join(entry.file_name());
+                std::fs::copy(entry.path(), &target).map_err(|e| DspError::io(&target, e))?;
+                produced.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        produced.sort();
+
+        let manifest = self.manifest(stage, inputs, args, &produced)?;
+        let manifest_path = dir.join("manifest.json");
+        std::fs::write(&manifest_path, manifest).map_err(|e| DspError::io(&manifest_path, e))?;
+        self.link_latest()?;
+        Ok(dir)
+    }
+
+    fn manifest(&self, stage: &str, inputs: &[&str], args: &[String], produced: &[String]) -> Result<String> {
+        let started = self.started.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let git = git_commit().map_or("null".to_string(), |commit| json_string(&commit));
+        let arguments: Vec<String> = args.iter().map(|a| json_string(a)).collect();
+
+        let mut out = String::from("{\n");
+        let _ = writeln!(out, "  \"schema\": {},", json_string(MANIFEST_SCHEMA));
+        let _ = writeln!(out, "  \"stage\": {},", json_string(stage));
+        let _ = writeln!(out, "  \"tag\": {},", json_string(&self.tag));
+        let _ = writeln!(out, "  \"started_unix_s\": {},", started);
+        let _ = writeln!(out, "  \"git_commit\": {},", git);
+        let _ = writeln!(out, "  \"arguments\": [{}],", arguments.join(", "));
+        out.push_str("  \"inputs\": [");
+        for (i, input) in inputs.iter().enumerate() {
+            let _ = write!(out, "{}\n    {}", if i > 0 { "," } else { "" }, file_entry(input, Path::new(input))?);
+        }
+        out.push_str(if inputs.is_empty() { "],\n" } else { "\n  ],\n" });
+        out.push_str("  \"files\": [");
+        for (i, name) in produced.iter().enumerate() {
+            let _ = write!(out, "{}\n    {}", if i > 0 { "," } else { "" }, file_entry(name, &self.path().join(name))?);
+        }
+        out.push_str(if produced.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+        Ok(out)
+    }
+
+    /// Point `output/latest` at this run, replacing the previous link
+    fn link_latest(&self) -> Result<()> {
+        let link = self.output.join(LATEST);
+        if link.symlink_metadata().is_ok() {
+            std::fs::remove_file(&link).map_err(|e| DspError::io(&link, e))?;
+        }
+        let target = format!("run-{}", self.tag);
+        #[cfg(unix)]
+        let linked = std::os::unix::fs::symlink(&target, &link);
+        // Without symlinks, `latest` is a text file holding the directory name
+        #[cfg(not(unix))]
+        let linked = std::fs::write(&link, &target);
+        linked.map_err(|e| DspError::io(&link, e))
+    }
+}
+
+/// Path of `file` in an upstream stage's output directory `dir`, following `--from-run <tag>`
+///
+/// Without `--from-run` this is `dir/file`, the newest files; `--from-run latest` reads
+/// the newest archived run and `--from-run <tag>` a specific one.
+pub fn upstream_path(args: &[String], dir: &str, file: &str) -> Result<String> {
+    let mut run = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--from-run" {
+            run = Some(iter.next().ok_or_else(|| DspError::missing_value("--from-run"))?);
+        }
+    }
+    Ok(match run {
+        Some(tag) if tag == LATEST => format!("{}/{}/{}", dir, LATEST, file),
+        Some(tag) => format!("{}/run-{}/{}", dir, tag, file),
+        None => format!("{}/{}", dir, file),
+    })
+}
+
+/// `{"path": ..., "bytes": ..., "fnv1a": ...}` for one file
+fn file_entry(name: &str, path: &Path) -> Result<String> {
+    let content = std::fs::read(path).map_err(|e| DspError::io(path, e))?;
+    Ok(format!(
+        "{{\"path\": {}, \"bytes\": {}, \"fnv1a\": \"{:016x}\"}}",
+        json_string(name),
+        content.len(),
+        stable_hash(&content)
+    ))
+}
+
+/// Commit of the working tree, if git is available and this is a checkout
+fn git_commit() -> Option<String> {
+    let output = std::process::Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC
+fn timestamp_tag(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", year, month, day, rem / 3600, rem / 60 % 60, rem % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_directory_archives_new_files() {
+        assert_eq!(timestamp_tag(UNIX_EPOCH), "19700101-000000");
+        assert_eq!(timestamp_tag(UNIX_EPOCH + Duration::from_secs(1_709_210_096)), "20240229-123456");
+
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert!(RunDirectory::from_args(&args("--snr 10"), "output").unwrap().is_none());
+        assert!(RunDirectory::from_args(&args("--run-tag ../x"), "output").is_err());
+        assert_eq!(upstream_path(&args(""), "../Q1/output", "a.txt").unwrap(), "../Q1/output/a.txt");
+        assert_eq!(upstream_path(&args("--from-run latest"), "o", "a.txt").unwrap(), "o/latest/a.txt");
+        assert_eq!(upstream_path(&args("--from-run t1"), "o", "a.txt").unwrap(), "o/run-t1/a.txt");
+
+        let output = std::env::temp_dir().join(format!("dsp_core_run_dir_{}", std::process::id()));
+        std::fs::create_dir_all(&output).unwrap();
+        let run = RunDirectory::from_args(&args("--run-tag t1"), &output).unwrap().unwrap();
+        std::fs::write(output.join("Q1_results.txt"), "f_d = 1 Hz\n").unwrap();
+        let dir = run.finish("Q1", &[], &args("--run-tag t1")).unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.join("Q1_results.txt")).unwrap(), "f_d = 1 Hz\n");
+        let manifest = std::fs::read_to_string(dir.join("manifest.json")).unwrap();
+        assert!(manifest.starts_with("{\n  \"schema\": \"dsp_core.manifest.v1\",\n  \"stage\": \"Q1\",\n  \"tag\": \"t1\",\n"));
+        assert!(manifest.contains("\"arguments\": [\"--run-tag\", \"t1\"],\n  \"inputs\": [],\n"));
+        assert!(manifest.contains("{\"path\": \"Q1_results.txt\", \"bytes\": 11, \"fnv1a\": \""));
+        #[cfg(unix)]
+        assert_eq!(std::fs::read_link(output.join(LATEST)).unwrap(), PathBuf::from("run-t1"));
+        std::fs::remove_dir_all(&output).unwrap();
+    }
+}
@@ -0,0 +1,291 @@
+use crate::error::{DspError, Result};
+use crate::noise::{self, NoiseKind};
+use std::f64::consts::PI;
+
+/// Modulating (baseband) content m(t)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Baseband {
+    /// Sum of equal-amplitude sine tones (Hz)
+    Tones(Vec<f64>),
+    /// Linear chirp sweeping from `start` to `end` Hz over the whole duration
+    Chirp { start: f64, end: f64 },
+    /// Arbitrary samples at the output rate (e.g. speech read from a WAV file)
+    Samples(Vec<f64>),
+}
+
+/// Parameters of a synthetic misdemodulated AM recording
+///
+/// The transmitter uses carrier f_c; the receiver's local oscillator sits at f_c - f_d,
+/// so after its lowpass the recording is x(t) = a(t) cos(2 pi f_d t + phase), with
+/// a(t) = 1 + mu m(t) for AM with carrier or a(t) = m(t) for DSB-SC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmConfig {
+    /// Output sample rate f_s (Hz)
+    pub sample_rate: f64,
+    /// Length in seconds (ignored for `Baseband::Samples`, which sets its own length)
+    pub duration: f64,
+    /// Transmitter carrier f_c (Hz), recorded in the ground truth only
+    pub carrier: f64,
+    /// Carrier offset f_d between transmitter and receiver (Hz)
+    pub offset: f64,
+    /// Carrier phase error (rad)
+    pub phase: f64,
+    /// Linear drift of the carrier offset over the whole signal (Hz); f_d is the starting value
+    pub offset_drift: f64,
+    /// Modulation index mu; `None` generates DSB-SC (no carrier line)
+    pub modulation_index: Option<f64>,
+    /// Peak frequency deviation (Hz) for FM, x(t) = cos(2 pi f_d t + 2 pi dev ∫m + phase);
+    /// overrides `modulation_index`
+    pub fm_deviation: Option<f64>,
+    /// Baseband bandwidth f_B (Hz); tones and chirps must stay below it
+    pub bandwidth: f64,
+    pub baseband: Baseband,
+    /// Signal-to-noise ratio of the added Gaussian noise (dB); `None` for a clean signal
+    pub snr_db: Option<f64>,
+    /// Spectral shape of the added noise
+    pub noise_kind: NoiseKind,
+    /// Seed of the noise generator, so runs are reproducible
+    pub seed: u64,
+}
+
+impl Default for AmConfig {
+    /// Roughly the setting of the homework recording: f_s = 22050 Hz, f_d = 3000 Hz, f_B = 4000 Hz
+    fn default() -> Self {
+        AmConfig {
+            sample_rate: 22050.0,
+            duration: 1.5,
+            carrier: 1_000_000.0,
+            offset: 3000.0,
+            phase: 0.0,
+            offset_drift: 0.0,
+            modulation_index: None,
+            fm_deviation: None,
+            bandwidth: 4000.0,
+            baseband: Baseband::Tones(vec![225.0]),
+            snr_db: None,
+            noise_kind: NoiseKind::White,
+            seed: 1,
+        }
+    }
+}
+
+/// Output of [`generate`]
+#[derive(Debug, Clone)]
+pub struct GeneratedSignal {
+    /// Recording with noise
+    pub samples: Vec<f64>,
+    /// Recording without noise
+    pub clean: Vec<f64>,
+    /// Modulating signal m(t), peak-normalized to 1
+    pub baseband: Vec<f64>,
+    /// Mean power of `clean`
+    pub signal_power: f64,
+    /// Mean power of the added noise (0 for a clean signal)
+    pub noise_power: f64,
+}
+
+/// Synthesize the misdemodulated AM recording described by `config`
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::signal_gen::{generate, AmConfig};
+///
+/// let config = AmConfig { duration: 0.5, snr_db: Some(20.0), ..AmConfig::default() };
+/// let signal = generate(&config).unwrap();
+/// assert_eq!(signal.samples.len(), 11025);
+/// let snr_db = 10.0 * (signal.signal_power / signal.noise_power).log10();
+/// assert!((snr_db - 20.0).abs() < 0.01);
+/// ```
+pub fn generate(config: &AmConfig) -> Result<GeneratedSignal> {
+    let fs = config.sample_rate;
+    if fs <= 0.0 {
+        return Err(DspError::Argument(format!("Sample rate must be positive, got {}", fs)));
+    }
+    // FM occupies about f_d ± (deviation + f_B) (Carson's rule)
+    let top_offset =
+        config.offset.max(config.offset + config.offset_drift) + config.fm_deviation.unwrap_or(0.0);
+    if top_offset + config.bandwidth >= fs / 2.0 {
+        return Err(DspError::Argument(format!(
+            "f_d + f_B = {:.1} Hz does not fit below the Nyquist frequency {:.1} Hz",
+            top_offset + config.bandwidth,
+            fs / 2.0
+        )));
+    }
+
+    let n = (config.duration * fs).round() as usize;
+    let t = |i: usize| i as f64 / fs;
+    let mut baseband: Vec<f64> = match &config.baseband {
+        Baseband::Tones(tones) => {
+            if let Some(&f) = tones.iter().find(|&&f| f <= 0.0 || f > config.bandwidth) {
+                let message = format!("Tone {} Hz is outside (0, f_B = {}] Hz", f, config.bandwidth);
+                return Err(DspError::Argument(message));
+            }
+            (0..n)
+                .map(|i| tones.iter().map(|&f| (2.0 * PI * f * t(i)).sin()).sum())
+                .collect()
+        }
+        Baseband::Chirp { start, end } => {
+            if start.max(*end) > config.bandwidth {
+                return Err(DspError::Argument(format!("Chirp exceeds f_B = {} Hz", config.bandwidth)));
+            }
+            let sweep = (end - start) / config.duration;
+            (0..n)
+                .map(|i| (2.0 * PI * (start * t(i) + 0.5 * sweep * t(i) * t(i))).sin())
+                .collect()
+        }
+        Baseband::Samples(samples) => samples.clone(),
+    };
+    let peak = baseband.iter().fold(0.0f64, |max, &x| max.max(x.abs()));
+    if peak > 0.0 {
+        baseband.iter_mut().for_each(|x| *x /= peak);
+    }
+
+    // Carrier frequency f_d + drift * t / T integrates to the phase below
+    let sweep = config.offset_drift / (baseband.len().max(1) as f64 / fs);
+    let mut fm_phase = 0.0;
+    let mut previous = baseband.first().copied().unwrap_or(0.0);
+    let clean: Vec<f64> = baseband
+        .iter()
+        .enumerate()
+        .map(|(i, &m)| {
+            let carrier_phase = 2.0 * PI * (config.offset * t(i) + 0.5 * sweep * t(i) * t(i));
+            if let Some(deviation) = config.fm_deviation {
+                // Trapezoidal integration keeps the instantaneous frequency aligned with m(t)
+                fm_phase += PI * deviation * (previous + m) / fs;
+                previous = m;
+                return (carrier_phase + fm_phase + config.phase).cos();
+            }
+            let envelope = match config.modulation_index {
+                Some(mu) => 1.0 + mu * m,
+                None => m,
+            };
+            envelope * (carrier_phase + config.phase).cos()
+        })
+        .collect();
+    let signal_power = clean.iter().map(|x| x * x).sum::<f64>() / clean.len().max(1) as f64;
+
+    let (samples, noise_power) = match config.snr_db {
+        Some(snr_db) => {
+            let noise_power = signal_power / 10f64.powf(snr_db / 10.0);
+            let sigma = noise_power.sqrt();
+            let noise = noise::generate(config.noise_kind, clean.len(), config.seed);
+            let noisy = clean.iter().zip(&noise).map(|(&x, &n)| x + sigma * n).collect();
+            (noisy, noise_power)
+        }
+        None => (clean.clone(), 0.0),
+    };
+
+    Ok(GeneratedSignal {
+        samples,
+        clean,
+        baseband,
+        signal_power,
+        noise_power,
+    })
+}
+
+/// Ground-truth parameter file for a generated signal
+///
+/// The f_d and f_s lines use the same `name = value Hz` layout as `Q1_results.txt`,
+/// so the file can stand in for Q1's output when checking Q3/Q4 on synthetic data.
+pub fn ground_truth(config: &AmConfig, signal: &GeneratedSignal) -> String {
+    let mut content = String::new();
+    content.push_str("Synthetic AM Test Signal Ground Truth\n");
+    content.push_str("=====================================\n\n");
+    content.push_str(&format!("Frequency offset: f_d = {:.6} Hz\n", config.offset));
+    content.push_str(&format!("Sampling frequency: f_s = {:.1} Hz\n", config.sample_rate));
+    content.push_str(&format!("Transmitter carrier: f_c = {:.3} Hz\n", config.carrier));
+    content.push_str(&format!(
+        "Receiver local oscillator = {:.3} Hz\n",
+        config.carrier - config.offset
+    ));
+    content.push_str(&format!("Carrier phase error = {:.6} rad\n", config.phase));
+    if config.offset_drift != 0.0 {
+        content.push_str(&format!(
+            "Carrier drift = {:+.3} Hz (f_d ends at {:.6} Hz)\n",
+            config.offset_drift,
+            config.offset + config.offset_drift
+        ));
+    }
+    content.push_str(&format!("Baseband bandwidth: f_B = {:.1} Hz\n", config.bandwidth));
+    match (config.fm_deviation, config.modulation_index) {
+        (Some(deviation), _) => content.push_str(&format!("Modulation: FM, peak deviation = {:.3} Hz\n", deviation)),
+        (None, Some(mu)) => content.push_str(&format!("Modulation: AM with carrier, index = {:.4}\n", mu)),
+        (None, None) => content.push_str("Modulation: DSB-SC\n"),
+    }
+    match &config.baseband {
+        Baseband::Tones(tones) => {
+            let list: Vec<String> = tones.iter().map(|f| format!("{:.3}", f)).collect();
+            content.push_str(&format!("Baseband: tones at {} Hz\n", list.join(", ")));
+        }
+        Baseband::Chirp { start, end } => {
+            content.push_str(&format!("Baseband: linear chirp {:.1} -> {:.1} Hz\n", start, end))
+        }
+        Baseband::Samples(_) => content.push_str("Baseband: external recording\n"),
+    }
+    content.push_str(&format!("Samples: {}\n", signal.samples.len()));
+    match config.snr_db {
+        Some(snr) => content.push_str(&format!(
+            "SNR = {:.2} dB ({} Gaussian noise, seed {})\n",
+            snr, config.noise_kind, config.seed
+        )),
+        None => content.push_str("SNR = inf (no noise)\n"),
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_snr_and_validation() {
+        let config = AmConfig {
+            snr_db: Some(10.0),
+            modulation_index: Some(0.5),
+            ..AmConfig::default()
+        };
+        let signal = generate(&config).unwrap();
+        assert_eq!(signal.samples.len(), 33075);
+
+        // Measured noise power matches the requested SNR within 0.2 dB
+        let measured = signal
+            .samples
+            .iter()
+            .zip(&signal.clean)
+            .map(|(x, c)| (x - c) * (x - c))
+            .sum::<f64>()
+            / signal.samples.len() as f64;
+        let snr = 10.0 * (signal.signal_power / measured).log10();
+        assert!((snr - 10.0).abs() < 0.2, "snr = {}", snr);
+
+        // Same seed, same noise
+        assert_eq!(generate(&config).unwrap().samples, signal.samples);
+
+        let too_wide = AmConfig { offset: 8000.0, ..AmConfig::default() };
+        assert!(generate(&too_wide).is_err());
+        assert!(ground_truth(&config, &signal).contains("f_d = 3000.000000 Hz"));
+    }
+
+    #[test]
+    fn test_dsb_sc_with_carrier_drift() {
+        // DSB-SC of a constant baseband is a bare carrier, so zero crossings give its frequency
+        let config = AmConfig {
+            duration: 2.0,
+            offset: 1000.0,
+            offset_drift: 10.0,
+            baseband: Baseband::Samples(vec![1.0; 44100]),
+            ..AmConfig::default()
+        };
+        let signal = generate(&config).unwrap();
+        let frequency = |range: std::ops::Range<usize>| {
+            let x = &signal.clean[range.clone()];
+            let crossings = x.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+            crossings as f64 / (range.len() as f64 / config.sample_rate)
+        };
+        assert!((frequency(0..4410) - 1000.5).abs() < 1.5);
+        assert!((frequency(39690..44100) - 1009.5).abs() < 1.5);
+        assert!(ground_truth(&config, &signal).contains("f_d ends at 1010.000000 Hz"));
+    }
+}
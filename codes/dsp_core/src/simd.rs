@@ -0,0 +1,111 @@
+/// Accumulators per kernel, the width of an `f64x4` vector
+///
+/// Each kernel walks its slices in blocks of `LANES` with one accumulator per lane, so the
+/// additions are independent and LLVM lowers a block to packed `f64x4` arithmetic (AVX2, or
+/// two SSE2 registers) on stable Rust; a plain `iter().sum()` has to add in order and stays
+/// scalar. The lane sums are combined at the end, so results differ from the sequential sum
+/// only by rounding.
+pub const LANES: usize = 4;
+
+/// Fold `a` and `b` block by block: `f(acc, x, y)` updates one lane, the tail goes into lane 0
+#[inline(always)]
+fn fold2(a: &[f64], b: &[f64], f: impl Fn(f64, f64, f64) -> f64) -> f64 {
+    let n = a.len().min(b.len());
+    let (a, b) = (&a[..n], &b[..n]);
+    let mut acc = [0.0; LANES];
+    let blocks_a = a.chunks_exact(LANES);
+    let blocks_b = b.chunks_exact(LANES);
+    let (tail_a, tail_b) = (blocks_a.remainder(), blocks_b.remainder());
+    for (x, y) in blocks_a.zip(blocks_b) {
+        for lane in 0..LANES {
+            acc[lane] = f(acc[lane], x[lane], y[lane]);
+        }
+    }
+    for (&x, &y) in tail_a.iter().zip(tail_b) {
+        acc[0] = f(acc[0], x, y);
+    }
+    (acc[0] + acc[2]) + (acc[1] + acc[3])
+}
+
+/// Sum of `x`
+pub fn sum(x: &[f64]) -> f64 {
+    fold2(x, x, |acc, v, _| acc + v)
+}
+
+/// Dot product over the common length of `a` and `b`
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::simd;
+///
+/// assert_eq!(simd::dot(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), 32.0);
+/// assert_eq!(simd::energy(&[3.0, 4.0]), 25.0);
+/// ```
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    fold2(a, b, |acc, x, y| acc + x * y)
+}
+
+/// Energy (sum of squares) of `x`
+pub fn energy(x: &[f64]) -> f64 {
+    fold2(x, x, |acc, v, _| acc + v * v)
+}
+
+/// Sum of squared differences over the common length of `a` and `b`
+pub fn squared_error(a: &[f64], b: &[f64]) -> f64 {
+    fold2(a, b, |acc, x, y| {
+        let diff = x - y;
+        acc + diff * diff
+    })
+}
+
+/// `Σ (a - mean_a)(b - mean_b)`, the unnormalized covariance
+pub fn centered_dot(a: &[f64], mean_a: f64, b: &[f64], mean_b: f64) -> f64 {
+    fold2(a, b, |acc, x, y| acc + (x - mean_a) * (y - mean_b))
+}
+
+/// Largest `|a - b|` over the common length of `a` and `b`
+pub fn max_abs_diff(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    let mut acc = [0.0f64; LANES];
+    let blocks_a = a[..n].chunks_exact(LANES);
+    let blocks_b = b[..n].chunks_exact(LANES);
+    let (tail_a, tail_b) = (blocks_a.remainder(), blocks_b.remainder());
+    for (x, y) in blocks_a.zip(blocks_b) {
+        for lane in 0..LANES {
+            acc[lane] = acc[lane].max((x[lane] - y[lane]).abs());
+        }
+    }
+    for (&x, &y) in tail_a.iter().zip(tail_b) {
+        acc[0] = acc[0].max((x - y).abs());
+    }
+    acc[0].max(acc[1]).max(acc[2].max(acc[3]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        // Lengths around the block size exercise the tail handling
+        for n in [0, 1, 3, 4, 5, 17, 1000] {
+            let a: Vec<f64> = (0..n).map(|i| (i as f64 * 0.37).sin()).collect();
+            let b: Vec<f64> = (0..n).map(|i| (i as f64 * 0.11).cos() - 0.2).collect();
+            let close = |x: f64, y: f64| (x - y).abs() <= 1e-12 * (1.0 + y.abs());
+
+            assert!(close(sum(&a), a.iter().sum()));
+            assert!(close(dot(&a, &b), a.iter().zip(&b).map(|(x, y)| x * y).sum()));
+            assert!(close(energy(&a), a.iter().map(|x| x * x).sum()));
+            assert!(close(squared_error(&a, &b), a.iter().zip(&b).map(|(x, y)| (x - y).powi(2)).sum()));
+            assert!(close(
+                centered_dot(&a, 0.1, &b, -0.3),
+                a.iter().zip(&b).map(|(x, y)| (x - 0.1) * (y + 0.3)).sum()
+            ));
+            let max = a.iter().zip(&b).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max);
+            assert_eq!(max_abs_diff(&a, &b), max);
+        }
+        // Mismatched lengths use the common prefix
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[1.0, 1.0]), 3.0);
+    }
+}
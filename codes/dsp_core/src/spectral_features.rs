@@ -0,0 +1,105 @@
+/// Standard descriptors of a one-sided magnitude spectrum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralFeatures {
+    /// Magnitude-weighted mean frequency (Hz)
+    pub centroid: f64,
+    /// Magnitude-weighted standard deviation around the centroid (Hz)
+    pub spread: f64,
+    /// Geometric over arithmetic mean of the power spectrum, 0 (tonal) ..= 1 (white)
+    pub flatness: f64,
+    /// Frequency below which `rolloff_fraction` of the magnitude sum lies (Hz)
+    pub rolloff: f64,
+    /// Fraction used for `rolloff`, typically 0.85
+    pub rolloff_fraction: f64,
+}
+
+impl SpectralFeatures {
+    /// Compute the descriptors over matching `frequencies` / `magnitude` slices
+    ///
+    /// Pass the one-sided spectrum (0 ..= fs/2); a full FFT would double-count
+    /// the mirrored half. Returns `None` for an empty or all-zero spectrum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::spectral_features::SpectralFeatures;
+    ///
+    /// // All energy at 1000 Hz: a pure tone has no spread and zero flatness
+    /// let frequencies = [0.0, 500.0, 1000.0, 1500.0];
+    /// let features = SpectralFeatures::compute(&frequencies, &[0.0, 0.0, 1.0, 0.0], 0.85).unwrap();
+    /// assert_eq!(features.centroid, 1000.0);
+    /// assert_eq!(features.spread, 0.0);
+    /// ```
+    pub fn compute(frequencies: &[f64], magnitude: &[f64], rolloff_fraction: f64) -> Option<Self> {
+        let n = frequencies.len().min(magnitude.len());
+        let total: f64 = magnitude[..n].iter().sum();
+        if n == 0 || total <= 0.0 {
+            return None;
+        }
+
+        let centroid = frequencies[..n]
+            .iter()
+            .zip(&magnitude[..n])
+            .map(|(f, m)| f * m)
+            .sum::<f64>()
+            / total;
+        let spread = (frequencies[..n]
+            .iter()
+            .zip(&magnitude[..n])
+            .map(|(f, m)| (f - centroid).powi(2) * m)
+            .sum::<f64>()
+            / total)
+            .sqrt();
+
+        // Floor the power so a single empty bin does not send the geometric mean to zero
+        let floor = 1e-20;
+        let power: Vec<f64> = magnitude[..n].iter().map(|m| (m * m).max(floor)).collect();
+        let log_mean = power.iter().map(|p| p.ln()).sum::<f64>() / n as f64;
+        let arith_mean = power.iter().sum::<f64>() / n as f64;
+        let flatness = log_mean.exp() / arith_mean;
+
+        let target = rolloff_fraction * total;
+        let mut cumulative = 0.0;
+        let mut rolloff = frequencies[n - 1];
+        for (f, m) in frequencies[..n].iter().zip(&magnitude[..n]) {
+            cumulative += m;
+            if cumulative >= target {
+                rolloff = *f;
+                break;
+            }
+        }
+
+        Some(SpectralFeatures {
+            centroid,
+            spread,
+            flatness,
+            rolloff,
+            rolloff_fraction,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tone_versus_flat_spectrum() {
+        let frequencies: Vec<f64> = (0..101).map(|k| k as f64 * 10.0).collect();
+
+        let mut tone = vec![0.0; 101];
+        tone[30] = 1.0;
+        let tonal = SpectralFeatures::compute(&frequencies, &tone, 0.85).unwrap();
+        assert!((tonal.centroid - 300.0).abs() < 1e-9);
+        assert!(tonal.spread.abs() < 1e-9);
+        assert!(tonal.flatness < 1e-6);
+        assert_eq!(tonal.rolloff, 300.0);
+
+        let flat = SpectralFeatures::compute(&frequencies, &[1.0; 101], 0.85).unwrap();
+        assert!((flat.centroid - 500.0).abs() < 1e-9);
+        assert!((flat.flatness - 1.0).abs() < 1e-12);
+        assert_eq!(flat.rolloff, 850.0);
+
+        assert!(SpectralFeatures::compute(&frequencies, &[0.0; 101], 0.85).is_none());
+    }
+}
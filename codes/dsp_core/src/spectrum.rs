@@ -0,0 +1,189 @@
+use crate::error::{DspError, Result};
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// How a spectrum is moved by a frequency that need not fall on a bin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShiftMethod {
+    /// Circular shift by the nearest whole number of bins; the error is up to half a bin
+    #[default]
+    NearestBin,
+    /// Back to the time domain, multiply by cos(2π shift t), FFT again; exact for any shift
+    Mixing,
+}
+
+impl ShiftMethod {
+    /// Read `--shift-method <bin|exact>` from command-line arguments
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut method = ShiftMethod::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--shift-method" {
+                method = iter
+                    .next()
+                    .ok_or_else(|| DspError::missing_value("--shift-method"))?
+                    .parse()
+                    .map_err(DspError::Argument)?;
+            }
+        }
+        Ok(method)
+    }
+}
+
+impl FromStr for ShiftMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bin" | "nearest-bin" => Ok(ShiftMethod::NearestBin),
+            "exact" | "mixing" => Ok(ShiftMethod::Mixing),
+            other => Err(format!("Unknown shift method '{}' (expected bin or exact)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for ShiftMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShiftMethod::NearestBin => write!(f, "nearest bin"),
+            ShiftMethod::Mixing => write!(f, "exact (time-domain mixing)"),
+        }
+    }
+}
+
+/// Difference between `shift_hz` and the nearest whole-bin shift of an `n`-point FFT (Hz)
+pub fn bin_rounding_error(shift_hz: f64, sample_rate: f64, n: usize) -> f64 {
+    let df = sample_rate / n as f64;
+    (shift_hz / df).round() * df - shift_hz
+}
+
+/// (X(f - shift) + X(f + shift)) / 2 of a full FFT `spectrum`, i.e. the spectrum of
+/// x(t) cos(2π shift t)
+pub fn shift_and_add(
+    spectrum: &[Complex<f64>],
+    shift_hz: f64,
+    sample_rate: f64,
+    method: ShiftMethod,
+) -> Vec<Complex<f64>> {
+    let n = spectrum.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    match method {
+        ShiftMethod::NearestBin => {
+            let shift = (shift_hz * n as f64 / sample_rate).round() as isize;
+            (0..n as isize)
+                .map(|i| {
+                    let down = spectrum[(i + shift).rem_euclid(n as isize) as usize];
+                    let up = spectrum[(i - shift).rem_euclid(n as isize) as usize];
+                    (down + up) * 0.5
+                })
+                .collect()
+        }
+        ShiftMethod::Mixing => {
+            let mut planner = FftPlanner::<f64>::new();
+            let mut signal = spectrum.to_vec();
+            planner.plan_fft_inverse(n).process(&mut signal);
+            // 1/n from the inverse transform
+            let scale = 1.0 / n as f64;
+            for (k, x) in signal.iter_mut().enumerate() {
+                *x *= scale * (2.0 * PI * shift_hz * k as f64 / sample_rate).cos();
+            }
+            planner.plan_fft_forward(n).process(&mut signal);
+            signal
+        }
+    }
+}
+
+/// Rotate a full FFT output so the zero-frequency bin sits in the middle
+///
+/// Matches `numpy.fft.fftshift`: for length `n` the first `(n + 1) / 2` bins
+/// (non-negative frequencies) move to the end.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::spectrum::{centered_frequencies, fftshift};
+///
+/// assert_eq!(fftshift(&[0, 1, 2, 3]), [2, 3, 0, 1]);
+/// assert_eq!(centered_frequencies(4, 8.0), [-4.0, -2.0, 0.0, 2.0]);
+/// ```
+pub fn fftshift<T: Clone>(spectrum: &[T]) -> Vec<T> {
+    let split = spectrum.len().div_ceil(2);
+    spectrum[split..]
+        .iter()
+        .chain(spectrum[..split].iter())
+        .cloned()
+        .collect()
+}
+
+/// Frequency axis matching [`fftshift`] output, from about −fs/2 to +fs/2 (Hz)
+pub fn centered_frequencies(n: usize, sample_rate: f64) -> Vec<f64> {
+    let df = sample_rate / n as f64;
+    let half = (n / 2) as f64;
+    (0..n).map(|k| (k as f64 - half) * df).collect()
+}
+
+/// Single-sided magnitude spectrum |X[k]| / N of a real signal for k = 0..=N/2, with its
+/// frequencies (Hz); the input of [`crate::peaks::estimate_offset`]
+pub fn one_sided_magnitude(samples: &[f64], sample_rate: f64) -> (Vec<f64>, Vec<f64>) {
+    let n = samples.len();
+    let mut buffer: Vec<Complex<f64>> = samples.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+    let bins = if n == 0 { 0 } else { n / 2 + 1 };
+    let frequencies = (0..bins).map(|k| k as f64 * sample_rate / n as f64).collect();
+    let magnitude = buffer[..bins].iter().map(|x| x.norm() / n as f64).collect();
+    (frequencies, magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_shift_moves_tone_off_the_bin_grid() {
+        // 1 s at 1 kHz: bins are 1 Hz apart, the shift is 100.4 Hz
+        let (fs, n) = (1000.0, 1000);
+        let mut spectrum: Vec<Complex<f64>> = (0..n)
+            .map(|k| Complex::new((2.0 * PI * 200.0 * k as f64 / fs).cos(), 0.0))
+            .collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+        assert!((bin_rounding_error(100.4, fs, n) + 0.4).abs() < 1e-9);
+
+        // Time-domain reference: x(t) cos(2π 100.4 t) has lines at 99.6 and 300.4 Hz
+        let mut expected: Vec<Complex<f64>> = (0..n)
+            .map(|k| {
+                let t = k as f64 / fs;
+                Complex::new((2.0 * PI * 200.0 * t).cos() * (2.0 * PI * 100.4 * t).cos(), 0.0)
+            })
+            .collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut expected);
+        let exact = shift_and_add(&spectrum, 100.4, fs, ShiftMethod::Mixing);
+        let worst = exact.iter().zip(&expected).map(|(a, b)| (a - b).norm()).fold(0.0, f64::max);
+        assert!(worst < 1e-6, "max bin error {}", worst);
+
+        // Rounding to 100 Hz puts all the energy on the 100 and 300 Hz bins instead
+        let rounded = shift_and_add(&spectrum, 100.4, fs, ShiftMethod::NearestBin);
+        assert!((rounded[100].norm() - n as f64 / 4.0).abs() < 1e-6);
+        assert_eq!("exact".parse::<ShiftMethod>().unwrap(), ShiftMethod::Mixing);
+    }
+
+    #[test]
+    fn test_fftshift_even_and_odd() {
+        assert_eq!(fftshift(&[0, 1, 2, 3]), vec![2, 3, 0, 1]);
+        assert_eq!(fftshift(&[0, 1, 2, 3, 4]), vec![3, 4, 0, 1, 2]);
+        assert_eq!(centered_frequencies(4, 4.0), vec![-2.0, -1.0, 0.0, 1.0]);
+        assert_eq!(centered_frequencies(5, 5.0), vec![-2.0, -1.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_one_sided_magnitude_of_a_tone() {
+        // A unit cosine on bin 5 shows up as two half-amplitude bins, one of them kept
+        let samples: Vec<f64> = (0..64).map(|n| (2.0 * PI * 5.0 * n as f64 / 64.0).cos()).collect();
+        let (frequencies, magnitude) = one_sided_magnitude(&samples, 128.0);
+        assert_eq!((frequencies.len(), frequencies[5]), (33, 10.0));
+        assert!((magnitude[5] - 0.5).abs() < 1e-12);
+        assert!(magnitude.iter().enumerate().all(|(k, &m)| k == 5 || m < 1e-12));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::progress::Progress;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+
+/// Analysis window applied to each STFT frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+}
+
+impl Window {
+    /// Window coefficients of length `len`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::stft::Window;
+    ///
+    /// let hann = Window::Hann.coefficients(5);
+    /// assert!(hann[0].abs() < 1e-12 && (hann[2] - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn coefficients(self, len: usize) -> Vec<f64> {
+        if len <= 1 {
+            return vec![1.0; len];
+        }
+        let denom = (len - 1) as f64;
+        (0..len)
+            .map(|i| {
+                let phase = 2.0 * PI * i as f64 / denom;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 * (1.0 - phase.cos()),
+                    Window::Hamming => 0.54 - 0.46 * phase.cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Short-time Fourier transform of a real signal (one-sided spectra)
+#[derive(Debug, Clone)]
+pub struct Stft {
+    /// Centre time of each frame (s)
+    pub times: Vec<f64>,
+    /// Frequency of each bin, 0 ..= fs/2 (Hz)
+    pub frequencies: Vec<f64>,
+    /// Complex one-sided spectrum of each frame
+    pub spectra: Vec<Vec<Complex<f64>>>,
+    pub window_len: usize,
+    pub hop: usize,
+    pub sample_rate: f64,
+}
+
+impl Stft {
+    /// Compute the STFT with frames of `window_len` samples advanced by `hop`
+    ///
+    /// Only complete frames are analysed; a signal shorter than one window
+    /// yields a single zero-padded frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsp_core::stft::{Stft, Window};
+    ///
+    /// let fs = 8000.0;
+    /// let tone: Vec<f64> = (0..4096).map(|n| (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / fs).cos()).collect();
+    /// let stft = Stft::compute(&tone, fs, 256, 128, Window::Hann);
+    /// assert_eq!(stft.frequencies.len(), 129);
+    /// // 1000 Hz falls exactly on bin 32 of a 256-point frame
+    /// let frame = &stft.magnitude()[0];
+    /// let peak = (0..frame.len()).max_by(|&i, &j| frame[i].total_cmp(&frame[j])).unwrap();
+    /// assert_eq!(stft.frequencies[peak], 1000.0);
+    /// ```
+    pub fn compute(signal: &[f64], sample_rate: f64, window_len: usize, hop: usize, window: Window) -> Self {
+        let window_len = window_len.max(2);
+        let hop = hop.max(1);
+        let coefficients = window.coefficients(window_len);
+        let num_bins = window_len / 2 + 1;
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(window_len);
+
+        let num_frames = if signal.len() <= window_len {
+            1
+        } else {
+            (signal.len() - window_len) / hop + 1
+        };
+
+        let progress = Progress::new(num_frames as u64, "STFT");
+        let mut times = Vec::with_capacity(num_frames);
+        let mut spectra = Vec::with_capacity(num_frames);
+        for frame in 0..num_frames {
+            let start = frame * hop;
+            let mut buffer: Vec<Complex<f64>> = (0..window_len)
+                .map(|i| {
+                    let x = signal.get(start + i).copied().unwrap_or(0.0);
+                    Complex::new(x * coefficients[i], 0.0)
+                })
+                .collect();
+            fft.process(&mut buffer);
+            buffer.truncate(num_bins);
+
+            times.push((start as f64 + window_len as f64 / 2.0) / sample_rate);
+            spectra.push(buffer);
+            progress.inc(1);
+        }
+
+        let frequencies = (0..num_bins)
+            .map(|k| k as f64 * sample_rate / window_len as f64)
+            .collect();
+
+        Stft {
+            times,
+            frequencies,
+            spectra,
+            window_len,
+            hop,
+            sample_rate,
+        }
+    }
+
+    /// Magnitude of each frame, normalised by the window length
+    pub fn magnitude(&self) -> Vec<Vec<f64>> {
+        let scale = 1.0 / self.window_len as f64;
+        self.spectra
+            .iter()
+            .map(|frame| frame.iter().map(|c| c.norm() * scale).collect())
+            .collect()
+    }
+
+    /// Frequency resolution of each frame (Hz)
+    pub fn bin_width(&self) -> f64 {
+        self.sample_rate / self.window_len as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stft_frames_and_peak() {
+        let sample_rate = 1000.0;
+        let signal: Vec<f64> = (0..1000)
+            .map(|i| (2.0 * PI * 125.0 * i as f64 / sample_rate).sin())
+            .collect();
+
+        let stft = Stft::compute(&signal, sample_rate, 128, 64, Window::Hann);
+        assert_eq!(stft.spectra.len(), (1000 - 128) / 64 + 1);
+        assert_eq!(stft.frequencies.len(), 65);
+
+        for frame in stft.magnitude() {
+            let peak_bin = frame
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .map(|(k, _)| k)
+                .unwrap();
+            assert_eq!(peak_bin, 16); // 125 Hz / (1000 / 128) Hz per bin
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::error::{DspError, Result};
+use crate::reproducible::stable_hash;
+use log::{error, info};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Poll interval when `--watch-interval` is not given
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Rerun a stage whenever one of its input files changes (`--watch`)
+///
+/// Files are polled rather than watched through OS notifications, so it works the same on
+/// every platform and needs no extra dependency. A file counts as changed only when its
+/// content changes: a stage that rewrites an identical `Q1_results.txt` does not trigger
+/// the stages after it. Running every stage with `--watch` in its own terminal therefore
+/// reruns exactly the stages downstream of an edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watch {
+    pub interval: Duration,
+}
+
+/// What is known about one watched file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Snapshot {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl Snapshot {
+    /// `None` while the file is missing or unreadable
+    fn take(path: &Path, previous: Option<Snapshot>) -> Option<Snapshot> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let (modified, len) = (metadata.modified().ok(), metadata.len());
+        // Only hash the content again when the cheap checks say the file was touched
+        if let Some(previous) = previous.filter(|p| p.modified == modified && p.len == len) {
+            return Some(previous);
+        }
+        let hash = stable_hash(&std::fs::read(path).ok()?);
+        Some(Snapshot { modified, len, hash })
+    }
+}
+
+impl Watch {
+    /// Read `--watch` and `--watch-interval <ms>` from command-line arguments
+    ///
+    /// Returns `None` without `--watch`.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let mut enabled = false;
+        let mut interval = DEFAULT_INTERVAL;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--watch" => enabled = true,
+                "--watch-interval" => {
+                    let value = iter.next().ok_or_else(|| DspError::missing_value("--watch-interval"))?;
+                    let ms: u64 = value
+                        .parse()
+                        .ok()
+                        .filter(|&ms| ms > 0)
+                        .ok_or_else(|| DspError::invalid_value("--watch-interval", value))?;
+                    interval = Duration::from_millis(ms);
+                }
+                _ => {}
+            }
+        }
+        Ok(enabled.then_some(Watch { interval }))
+    }
+
+    /// Run `stage`, then run it again after every change to `inputs`, until interrupted
+    ///
+    /// A failing run is logged and the watch goes on, so a half-written input or a bad
+    /// parameter only costs one run.
+    pub fn run<E: std::fmt::Display>(&self, inputs: &[PathBuf], stage: impl FnMut() -> std::result::Result<(), E>) -> Result<()> {
+        self.run_until(inputs, stage, |_| false)
+    }
+
+    /// [`run`](Self::run), stopping once `done(runs)` is true after a run
+    fn run_until<E: std::fmt::Display>(
+        &self,
+        inputs: &[PathBuf],
+        mut stage: impl FnMut() -> std::result::Result<(), E>,
+        mut done: impl FnMut(usize) -> bool,
+    ) -> Result<()> {
+        if inputs.is_empty() {
+            return Err(DspError::Argument("--watch: this stage has no input files to watch".to_string()));
+        }
+        let mut snapshots: Vec<Option<Snapshot>> = inputs.iter().map(|path| Snapshot::take(path, None)).collect();
+        let mut runs = 0;
+        loop {
+            if let Err(e) = stage() {
+                error!("Run failed: {}", e);
+            }
+            runs += 1;
+            if done(runs) {
+                return Ok(());
+            }
+            info!("\nWatching {} file(s) for changes (Ctrl-C to stop)...", inputs.len());
+            for path in inputs {
+                info!("  {}", path.display());
+            }
+
+            // Wait for a change, then until the changed files stop changing, so a file that
+            // is still being written is read once it is complete
+            let mut changed = Vec::new();
+            loop {
+                std::thread::sleep(self.interval);
+                let current: Vec<Option<Snapshot>> =
+                    inputs.iter().zip(&snapshots).map(|(path, &previous)| Snapshot::take(path, previous)).collect();
+                let differs: Vec<usize> = (0..inputs.len()).filter(|&i| current[i] != snapshots[i]).collect();
+                let settled = differs.is_empty() && !changed.is_empty();
+                for &i in &differs {
+                    // Touched but identical content is not a change
+                    if current[i].map(|s| s.hash) != snapshots[i].map(|s| s.hash) && !changed.contains(&i) {
+                        changed.push(i);
+                    }
+                }
+                snapshots = current;
+                if settled {
+                    break;
+                }
+            }
+            for &i in &changed {
+                info!("\nChanged: {}", inputs[i].display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reruns_on_content_changes_only() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(Watch::from_args(&args("--snr 10")).unwrap(), None);
+        assert!(Watch::from_args(&args("--watch --watch-interval 0")).is_err());
+
+        let path = std::env::temp_dir().join(format!("dsp_core_watch_{}.txt", std::process::id()));
+        std::fs::write(&path, "f_d = 1 Hz\n").unwrap();
+        let watch = Watch::from_args(&args("--watch --watch-interval 10")).unwrap().unwrap();
+        assert_eq!(watch.interval, Duration::from_millis(10));
+
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                // Same content first: must not trigger a run
+                std::thread::sleep(Duration::from_millis(50));
+                std::fs::write(&path, "f_d = 1 Hz\n").unwrap();
+                std::thread::sleep(Duration::from_millis(100));
+                std::fs::write(&path, "f_d = 2 Hz\n").unwrap();
+            })
+        };
+        let mut seen = Vec::new();
+        watch
+            .run_until(
+                std::slice::from_ref(&path),
+                || -> Result<()> {
+                    seen.push(std::fs::read_to_string(&path).unwrap());
+                    Ok(())
+                },
+                |runs| runs == 2,
+            )
+            .unwrap();
+        writer.join().unwrap();
+        assert_eq!(seen, ["f_d = 1 Hz\n", "f_d = 2 Hz\n"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,392 @@
+use crate::error::{DspError, Result};
+use crate::reproducible::Reproducibility;
+use std::str::FromStr;
+
+pub use hound::WavSpec;
+
+/// Sample format used when writing a WAV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WavFormat {
+    /// 16-bit signed PCM
+    #[default]
+    Int16,
+    /// 24-bit signed PCM
+    Int24,
+    /// 32-bit IEEE float
+    Float32,
+}
+
+impl FromStr for WavFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "16" | "i16" | "int16" => Ok(WavFormat::Int16),
+            "24" | "i24" | "int24" => Ok(WavFormat::Int24),
+            "32f" | "f32" | "float" | "float32" => Ok(WavFormat::Float32),
+            other => Err(format!(
+                "Unknown output format '{}' (expected 16, 24, f32 or source)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for WavFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WavFormat::Int16 => "16-bit PCM",
+            WavFormat::Int24 => "24-bit PCM",
+            WavFormat::Float32 => "32-bit float",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl WavFormat {
+    /// Closest writable format to an input file's spec (32-bit integer input maps to 24-bit)
+    pub fn from_spec(spec: &WavSpec) -> Self {
+        match (spec.sample_format, spec.bits_per_sample) {
+            (hound::SampleFormat::Float, _) => WavFormat::Float32,
+            (hound::SampleFormat::Int, bits) if bits <= 16 => WavFormat::Int16,
+            _ => WavFormat::Int24,
+        }
+    }
+
+    /// Read `--bit-depth <16|24|f32|source>` from command-line arguments, default 16-bit
+    ///
+    /// `source` keeps the format of the input file described by `source`.
+    pub fn from_args(args: &[String], source: &WavSpec) -> Result<Self> {
+        let mut format = WavFormat::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let value = if let Some(value) = arg.strip_prefix("--bit-depth=") {
+                value
+            } else if arg == "--bit-depth" {
+                iter.next().ok_or_else(|| DspError::missing_value("--bit-depth"))?
+            } else {
+                continue;
+            };
+            format = if value.eq_ignore_ascii_case("source") {
+                WavFormat::from_spec(source)
+            } else {
+                value.parse().map_err(DspError::Argument)?
+            };
+        }
+        Ok(format)
+    }
+
+    /// WAV header for this format
+    pub fn spec(self, channels: u16, sample_rate: u32) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            WavFormat::Int16 => (16, hound::SampleFormat::Int),
+            WavFormat::Int24 => (24, hound::SampleFormat::Int),
+            WavFormat::Float32 => (32, hound::SampleFormat::Float),
+        };
+        WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+/// Read a WAV file of any integer or float format as interleaved samples in [-1.0, 1.0]
+pub fn read_interleaved(filename: &str) -> Result<(Vec<f64>, WavSpec)> {
+    let file = std::fs::File::open(filename).map_err(|e| DspError::io(filename, e))?;
+    read_interleaved_from(std::io::BufReader::new(file), filename)
+}
+
+/// [`read_interleaved`] from any reader, e.g. the bytes of an uploaded file; `name` only
+/// labels errors
+pub fn read_interleaved_from<R: std::io::Read>(reader: R, name: &str) -> Result<(Vec<f64>, WavSpec)> {
+    let reader = hound::WavReader::new(reader).map_err(|e| DspError::wav(name, e))?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .map(|s| s.map(|v| v as f64))
+            .collect::<Result<Vec<_>, _>>(),
+        hound::SampleFormat::Int => {
+            let full_scale = (1i64 << (spec.bits_per_sample - 1)) as f64;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f64 / full_scale))
+                .collect::<Result<Vec<_>, _>>()
+        }
+    }
+    .map_err(|e| DspError::wav(name, e))?;
+
+    Ok((samples, spec))
+}
+
+/// Gain applied to the signal before it is written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalize {
+    /// Scale so the largest sample sits at this level in dBFS
+    Peak(f64),
+    /// Scale so the RMS level sits at this level in dBFS (samples over full scale are clipped)
+    Rms(f64),
+    /// Write samples unchanged (values outside [-1.0, 1.0] are clipped)
+    None,
+}
+
+impl Default for Normalize {
+    /// Peak at 0.95 full scale (about -0.45 dBFS)
+    fn default() -> Self {
+        Normalize::Peak(20.0 * 0.95f64.log10())
+    }
+}
+
+impl FromStr for Normalize {
+    type Err = String;
+
+    /// Parses `none`, `peak:<dB>` or `rms:<dB>` (e.g. `peak:-1`, `rms:-20`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        if s == "none" {
+            return Ok(Normalize::None);
+        }
+        let (kind, level) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid normalization '{}' (expected none, peak:<dB> or rms:<dB>)", s))?;
+        let level: f64 = level
+            .parse()
+            .map_err(|_| format!("Invalid normalization level '{}'", level))?;
+        match kind {
+            "peak" => Ok(Normalize::Peak(level)),
+            "rms" => Ok(Normalize::Rms(level)),
+            other => Err(format!("Unknown normalization '{}' (expected none, peak or rms)", other)),
+        }
+    }
+}
+
+impl Normalize {
+    /// Linear gain that applies this normalization to `channels`
+    pub fn gain(self, channels: &[Vec<f64>]) -> f64 {
+        let samples = || channels.iter().flatten();
+        match self {
+            Normalize::Peak(db) => {
+                let peak = samples().fold(0.0f64, |max, &x| max.max(x.abs()));
+                if peak > 0.0 { 10f64.powf(db / 20.0) / peak } else { 1.0 }
+            }
+            Normalize::Rms(db) => {
+                let count = samples().count();
+                let rms = (samples().map(|x| x * x).sum::<f64>() / count.max(1) as f64).sqrt();
+                if rms > 0.0 { 10f64.powf(db / 20.0) / rms } else { 1.0 }
+            }
+            Normalize::None => 1.0,
+        }
+    }
+}
+
+/// Format, gain and dithering used by [`write_channels`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WavWriteOptions {
+    pub format: WavFormat,
+    pub normalize: Normalize,
+    /// Add TPDF dither before quantizing to 16-bit (ignored for 24-bit and float output)
+    pub dither: bool,
+    /// Seed of the dither generator
+    pub dither_seed: u64,
+}
+
+impl Default for WavWriteOptions {
+    fn default() -> Self {
+        WavWriteOptions {
+            format: WavFormat::default(),
+            normalize: Normalize::default(),
+            dither: true,
+            dither_seed: DITHER_SEED,
+        }
+    }
+}
+
+impl WavWriteOptions {
+    /// Read `--bit-depth`, `--normalize <none|peak:dB|rms:dB>` and `--no-dither` from command-line
+    /// arguments; the dither follows `--seed`
+    pub fn from_args(args: &[String], source: &WavSpec) -> Result<Self> {
+        let mut options = WavWriteOptions {
+            format: WavFormat::from_args(args, source)?,
+            dither_seed: Reproducibility::from_args(args)?.stream_seed("dither", DITHER_SEED),
+            ..WavWriteOptions::default()
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(value) = arg.strip_prefix("--normalize=") {
+                options.normalize = value.parse().map_err(DspError::Argument)?;
+            } else if arg == "--normalize" {
+                let value = iter.next().ok_or_else(|| DspError::missing_value("--normalize"))?;
+                options.normalize = value.parse().map_err(DspError::Argument)?;
+            } else if arg == "--no-dither" {
+                options.dither = false;
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl std::fmt::Display for WavWriteOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format)?;
+        match self.normalize {
+            Normalize::Peak(db) => write!(f, ", peak {:.2} dBFS", db)?,
+            Normalize::Rms(db) => write!(f, ", RMS {:.2} dBFS", db)?,
+            Normalize::None => write!(f, ", no normalization")?,
+        }
+        if self.dither && self.format == WavFormat::Int16 {
+            write!(f, ", TPDF dither")?;
+        }
+        Ok(())
+    }
+}
+
+/// Default dither seed, used unless `--seed` is given
+const DITHER_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Triangular-PDF dither source (sum of two uniform variables, ±1 LSB)
+///
+/// A seeded xorshift generator keeps repeated runs bit-identical.
+struct TpdfDither {
+    state: u64,
+}
+
+impl TpdfDither {
+    fn new(seed: u64) -> Self {
+        TpdfDither { state: seed.max(1) }
+    }
+
+    fn uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Dither value in LSB units, in (-1, 1)
+    fn next(&mut self) -> f64 {
+        self.uniform() - self.uniform()
+    }
+}
+
+/// Write one or more channels as an interleaved WAV file
+///
+/// All channels share one gain so their relative levels are kept.
+///
+/// # Examples
+///
+/// ```
+/// use dsp_core::wav_io::{read_interleaved, write_channels, Normalize, WavFormat, WavWriteOptions};
+///
+/// let path = std::env::temp_dir().join("dsp_core_doc_write_channels.wav");
+/// let path = path.to_str().unwrap();
+/// let options = WavWriteOptions { format: WavFormat::Float32, normalize: Normalize::None, ..Default::default() };
+/// write_channels(path, &[vec![0.5, -0.25]], 8000, &options).unwrap();
+/// let (samples, spec) = read_interleaved(path).unwrap();
+/// assert_eq!((samples, spec.sample_rate), (vec![0.5, -0.25], 8000));
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub fn write_channels(
+    filename: &str,
+    channels: &[Vec<f64>],
+    sample_rate: u32,
+    options: &WavWriteOptions,
+) -> Result<()> {
+    let file = std::fs::File::create(filename).map_err(|e| DspError::io(filename, e))?;
+    write_channels_to(std::io::BufWriter::new(file), filename, channels, sample_rate, options)
+}
+
+/// [`write_channels`] to any seekable writer, e.g. a `Cursor<Vec<u8>>` for a download;
+/// `name` only labels errors
+pub fn write_channels_to<W: std::io::Write + std::io::Seek>(
+    writer: W,
+    name: &str,
+    channels: &[Vec<f64>],
+    sample_rate: u32,
+    options: &WavWriteOptions,
+) -> Result<()> {
+    let spec = options.format.spec(channels.len().max(1) as u16, sample_rate);
+    let mut writer = hound::WavWriter::new(writer, spec).map_err(|e| DspError::wav(name, e))?;
+
+    let scale = options.normalize.gain(channels);
+    let mut dither = (options.dither && options.format == WavFormat::Int16).then(|| TpdfDither::new(options.dither_seed));
+
+    for sample in crate::channels::interleave(channels) {
+        let normalized = (sample * scale).clamp(-1.0, 1.0);
+        let result = match options.format {
+            WavFormat::Int16 => {
+                let noise = dither.as_mut().map_or(0.0, TpdfDither::next);
+                let value = (normalized * 32767.0 + noise).round().clamp(-32768.0, 32767.0);
+                writer.write_sample(value as i16)
+            }
+            WavFormat::Int24 => writer.write_sample((normalized * 8_388_607.0).round() as i32),
+            WavFormat::Float32 => writer.write_sample(normalized as f32),
+        };
+        result.map_err(|e| DspError::wav(name, e))?;
+    }
+
+    writer.finalize().map_err(|e| DspError::wav(name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_formats() {
+        let signal: Vec<f64> = (0..256).map(|i| (i as f64 * 0.1).sin()).collect();
+        let path = std::env::temp_dir().join(format!("dsp_core_wav_io_{}.wav", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        for format in [WavFormat::Int16, WavFormat::Int24, WavFormat::Float32] {
+            let options = WavWriteOptions { format, dither: false, ..Default::default() };
+            write_channels(path, std::slice::from_ref(&signal), 8000, &options).unwrap();
+            let (samples, spec) = read_interleaved(path).unwrap();
+            assert_eq!(WavFormat::from_spec(&spec), format);
+            assert_eq!(samples.len(), signal.len());
+
+            let peak = signal.iter().fold(0.0f64, |m, &x| m.max(x.abs()));
+            let tolerance = if format == WavFormat::Int16 { 1e-4 } else { 1e-6 };
+            for (read, orig) in samples.iter().zip(&signal) {
+                assert!((read - orig * 0.95 / peak).abs() < tolerance);
+            }
+        }
+        std::fs::remove_file(path).ok();
+
+        // The same bytes in memory
+        let options = WavWriteOptions { format: WavFormat::Float32, normalize: Normalize::None, ..Default::default() };
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        write_channels_to(&mut bytes, "memory", std::slice::from_ref(&signal), 8000, &options).unwrap();
+        let (samples, spec) = read_interleaved_from(bytes.get_ref().as_slice(), "memory").unwrap();
+        assert_eq!(spec.sample_rate, 8000);
+        assert!(samples.iter().zip(&signal).all(|(read, orig)| (read - orig).abs() < 1e-6));
+        assert!(read_interleaved_from(&b"RIFF"[..], "memory").is_err());
+    }
+
+    #[test]
+    fn test_normalize_and_dither() {
+        let signal: Vec<f64> = (0..4000).map(|i| (i as f64 * 0.05).sin()).collect();
+        let channels = [signal];
+
+        // RMS of a full-scale sine is -3.01 dBFS, so -20 dBFS needs gain 0.1 / (1 / sqrt 2)
+        let gain = Normalize::Rms(-20.0).gain(&channels);
+        assert!((gain - 0.1 * 2f64.sqrt()).abs() < 1e-3);
+        assert!((Normalize::Peak(0.0).gain(&channels) - 1.0).abs() < 1e-3);
+        assert_eq!("rms:-20".parse::<Normalize>().unwrap(), Normalize::Rms(-20.0));
+
+        // A sine at 0.4 LSB rounds to silence without dither but survives with it
+        let tiny: Vec<f64> = channels[0].iter().map(|x| x * 0.4 / 32767.0).collect();
+        let path = std::env::temp_dir().join(format!("dsp_core_dither_{}.wav", std::process::id()));
+        let path = path.to_str().unwrap();
+        for dither in [false, true] {
+            let options = WavWriteOptions { normalize: Normalize::None, dither, ..Default::default() };
+            write_channels(path, std::slice::from_ref(&tiny), 8000, &options).unwrap();
+            let (samples, _) = read_interleaved(path).unwrap();
+            let nonzero = samples.iter().filter(|&&x| x != 0.0).count();
+            assert_eq!(nonzero > 0, dither);
+        }
+        std::fs::remove_file(path).ok();
+    }
+}
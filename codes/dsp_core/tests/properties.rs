@@ -0,0 +1,111 @@
+//! Property-based tests of filter design invariants
+
+use dsp_core::butterworth::{ButterworthFilter, FilterType};
+use dsp_core::filter_chain::{self, FilterChain};
+use dsp_core::fir::FirFilter;
+use dsp_core::simd;
+use proptest::prelude::*;
+
+/// Whether every root of `a[0] z^m + a[1] z^(m-1) + … + a[m]` lies strictly inside the unit
+/// circle, by the Schur–Cohn step-down recursion: the filter is stable exactly when every
+/// reflection coefficient has magnitude below one
+fn poles_inside_unit_circle(a: &[f64]) -> bool {
+    let mut poly: Vec<f64> = a.iter().map(|&c| c / a[0]).collect();
+    while poly.len() > 1 {
+        let m = poly.len() - 1;
+        let k = poly[m];
+        if k.abs() >= 1.0 {
+            return false;
+        }
+        poly = (0..m).map(|j| (poly[j] - k * poly[m - j]) / (1.0 - k * k)).collect();
+    }
+    true
+}
+
+fn design(filter_type: FilterType, order: usize, cutoff: f64, sample_rate: f64) -> ButterworthFilter {
+    match filter_type {
+        FilterType::Lowpass => ButterworthFilter::lowpass(order, cutoff, sample_rate),
+        FilterType::Highpass => ButterworthFilter::highpass(order, cutoff, sample_rate),
+    }
+}
+
+fn filter_type() -> impl Strategy<Value = FilterType> {
+    prop_oneof![Just(FilterType::Lowpass), Just(FilterType::Highpass)]
+}
+
+proptest! {
+    #[test]
+    fn butterworth_designs_are_stable(
+        filter_type in filter_type(),
+        order in 1usize..=10,
+        relative_cutoff in 0.02f64..0.48,
+        sample_rate in 1000.0f64..48000.0,
+    ) {
+        let filter = design(filter_type, order, relative_cutoff * sample_rate, sample_rate);
+        prop_assert_eq!(filter.a.len(), order + 1);
+        prop_assert_eq!(filter.b.len(), order + 1);
+        prop_assert!(poles_inside_unit_circle(&filter.a), "unstable: a = {:?}", filter.a);
+
+        // Unity gain in the passband: at DC for the low-pass, at Nyquist for the high-pass
+        let alternating = |c: &[f64]| c.iter().enumerate().map(|(i, x)| if i % 2 == 0 { *x } else { -x }).sum::<f64>();
+        let gain = match filter_type {
+            FilterType::Lowpass => filter.b.iter().sum::<f64>() / filter.a.iter().sum::<f64>(),
+            FilterType::Highpass => alternating(&filter.b) / alternating(&filter.a),
+        };
+        prop_assert!((gain - 1.0).abs() < 1e-6, "passband gain {}", gain);
+    }
+
+    #[test]
+    fn butterworth_sections_match_transfer_function(
+        filter_type in filter_type(),
+        order in 1usize..=10,
+        relative_cutoff in 0.02f64..0.48,
+    ) {
+        let sample_rate = 8000.0;
+        let filter = design(filter_type, order, relative_cutoff * sample_rate, sample_rate);
+        let sos = filter.sos();
+        prop_assert_eq!(sos.len(), order.div_ceil(2));
+        let frequencies: Vec<f64> = (0..=16).map(|k| k as f64 * sample_rate / 32.0).collect();
+        let cascade = FilterChain::new().sos(&sos).response_at(&frequencies, sample_rate);
+        // The multiplied-out (b, a) is the less precise of the two at high orders
+        for (&f, h) in frequencies.iter().zip(cascade) {
+            let expected = filter_chain::response(&filter.b, &filter.a, f, sample_rate);
+            prop_assert!((h - expected).norm() < 1e-4, "{} Hz: {} vs {}", f, h, expected);
+        }
+    }
+
+    #[test]
+    fn fir_designs_have_linear_phase(
+        filter_type in filter_type(),
+        num_taps in 3usize..400,
+        relative_cutoff in 0.01f64..0.49,
+    ) {
+        let sample_rate = 8000.0;
+        let cutoff = relative_cutoff * sample_rate;
+        let filter = match filter_type {
+            FilterType::Lowpass => FirFilter::lowpass(num_taps, cutoff, sample_rate),
+            FilterType::Highpass => FirFilter::highpass(num_taps, cutoff, sample_rate),
+        };
+        let taps = &filter.taps;
+        // Odd length with a centre tap, symmetric about it: constant group delay
+        prop_assert_eq!(taps.len() % 2, 1);
+        prop_assert_eq!(filter.delay() * 2 + 1, taps.len());
+        for i in 0..taps.len() / 2 {
+            prop_assert!((taps[i] - taps[taps.len() - 1 - i]).abs() < 1e-15);
+        }
+        let dc_gain: f64 = taps.iter().sum();
+        let expected = if matches!(filter_type, FilterType::Lowpass) { 1.0 } else { 0.0 };
+        prop_assert!((dc_gain - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vector_kernels_match_sequential_sums(
+        pairs in prop::collection::vec((-1e3f64..1e3, -1e3f64..1e3), 0..300),
+    ) {
+        let (a, b): (Vec<f64>, Vec<f64>) = pairs.into_iter().unzip();
+        let scale = 1e-12 * (1.0 + a.iter().chain(&b).map(|x| x * x).sum::<f64>());
+        prop_assert!((simd::dot(&a, &b) - a.iter().zip(&b).map(|(x, y)| x * y).sum::<f64>()).abs() <= scale);
+        prop_assert!((simd::energy(&a) - a.iter().map(|x| x * x).sum::<f64>()).abs() <= scale);
+        prop_assert!(simd::squared_error(&a, &b) >= 0.0);
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+/// Regenerate `include/dsp_core.h` from the `extern "C"` items of `src/lib.rs`
+///
+/// The header is committed so scripts can use it without a Rust toolchain; cbindgen only
+/// rewrites it when its content changes.
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        include_guard: Some("DSP_CORE_H".to_string()),
+        autogen_warning: Some("/* Generated by cbindgen from codes/ffi/src/lib.rs; do not edit */".to_string()),
+        cpp_compat: true,
+        // size_t lengths, as MATLAB and ctypes expect
+        usize_is_size_t: true,
+        ..Default::default()
+    };
+    let result = cbindgen::Builder::new().with_crate(&crate_dir).with_config(config).generate();
+    match result {
+        Ok(bindings) => {
+            bindings.write_to_file(Path::new(&crate_dir).join("include/dsp_core.h"));
+        }
+        // A syntax error shows up in the compile itself; keep the old header meanwhile
+        Err(e) => println!("cargo:warning=dsp_core.h not regenerated: {}", e),
+    }
+}
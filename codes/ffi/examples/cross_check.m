@@ -0,0 +1,60 @@
+% Cross-check the Rust implementation against MATLAB through the C ABI.
+%
+%   cd codes/ffi; cargo build --release      (in a shell)
+%   cd codes/ffi/examples; cross_check        (in MATLAB)
+%
+% Needs a C compiler configured for MATLAB (`mex -setup`) for loadlibrary, and the Signal
+% Processing Toolbox for butter.
+
+here = fileparts(mfilename('fullpath'));
+root = fullfile(here, '..');
+if ispc
+    library = fullfile(root, 'target', 'release', 'dsp_core_ffi.dll');
+elseif ismac
+    library = fullfile(root, 'target', 'release', 'libdsp_core_ffi.dylib');
+else
+    library = fullfile(root, 'target', 'release', 'libdsp_core_ffi.so');
+end
+if ~libisloaded('dsp_core_ffi')
+    loadlibrary(library, fullfile(root, 'include', 'dsp_core.h'), 'alias', 'dsp_core_ffi');
+end
+DSP_METHOD_FFT = 0;
+DSP_METHOD_IIR = 1;
+
+[x, fs] = audioread(fullfile(root, '..', 'project.wav'));
+x = mean(x, 2);
+n = numel(x);
+
+% Frequency offset
+estimate = libstruct('DspOffsetEstimate');
+status = calllib('dsp_core_ffi', 'dsp_estimate_offset', x, n, fs, estimate);
+check(status);
+fprintf('f_d = %.4f Hz (pair: %d)\n', estimate.f_d, estimate.pair_found);
+
+% Butterworth design against butter
+order = 8;
+f_b = 4000;
+[status, b, a] = calllib('dsp_core_ffi', 'dsp_design_butterworth', order, f_b, fs, 0, ...
+    zeros(order + 1, 1), zeros(order + 1, 1));
+check(status);
+[b_ref, a_ref] = butter(order, f_b / (fs / 2), 'low');
+fprintf('low-pass %g Hz: max |b - b_matlab| = %.3e, max |a - a_matlab| = %.3e\n', ...
+    f_b, max(abs(b(:) - b_ref(:))), max(abs(a(:) - a_ref(:))));
+
+% Both demodulators
+[status, y_fft] = calllib('dsp_core_ffi', 'dsp_demodulate', x, n, fs, estimate.f_d, f_b, ...
+    DSP_METHOD_FFT, order, zeros(n, 1));
+check(status);
+[status, y_iir] = calllib('dsp_core_ffi', 'dsp_demodulate', x, n, fs, estimate.f_d, f_b, ...
+    DSP_METHOD_IIR, order, zeros(n, 1));
+check(status);
+c = corrcoef(y_fft, y_iir);
+fprintf('FFT chain vs IIR chain: correlation %.4f\n', c(1, 2));
+
+unloadlibrary('dsp_core_ffi');
+
+function check(status)
+    if status ~= 0
+        error('dsp_core error %d: %s', status, calllib('dsp_core_ffi', 'dsp_last_error'));
+    end
+end
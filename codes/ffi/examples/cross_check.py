@@ -0,0 +1,97 @@
+"""Cross-check the Rust implementation against scipy through the C ABI.
+
+    cd codes/ffi && cargo build --release
+    python3 examples/cross_check.py ../project.wav
+
+Needs numpy and scipy. Prints f_d estimated by Rust, compares the Butterworth design with
+scipy.signal.butter and the demodulated signals with a scipy version of Q3's chain.
+"""
+
+import ctypes
+import sys
+from pathlib import Path
+
+import numpy as np
+from scipy import signal
+from scipy.io import wavfile
+
+DSP_METHOD_FFT = 0
+DSP_METHOD_IIR = 1
+
+
+class DspOffsetEstimate(ctypes.Structure):
+    _fields_ = [
+        ("f_d", ctypes.c_double),
+        ("peak", ctypes.c_double),
+        ("pair_found", ctypes.c_int32),
+        ("lower_freq", ctypes.c_double),
+        ("upper_freq", ctypes.c_double),
+    ]
+
+
+def load_library():
+    target = Path(__file__).resolve().parent.parent / "target" / "release"
+    name = {"win32": "dsp_core_ffi.dll", "darwin": "libdsp_core_ffi.dylib"}.get(sys.platform, "libdsp_core_ffi.so")
+    lib = ctypes.CDLL(str(target / name))
+    doubles = np.ctypeslib.ndpointer(dtype=np.float64, flags="C_CONTIGUOUS")
+    lib.dsp_last_error.restype = ctypes.c_char_p
+    lib.dsp_estimate_offset.argtypes = [doubles, ctypes.c_size_t, ctypes.c_double, ctypes.POINTER(DspOffsetEstimate)]
+    lib.dsp_design_butterworth.argtypes = [ctypes.c_size_t, ctypes.c_double, ctypes.c_double, ctypes.c_int32, doubles, doubles]
+    lib.dsp_demodulate.argtypes = [
+        doubles, ctypes.c_size_t, ctypes.c_double, ctypes.c_double, ctypes.c_double,
+        ctypes.c_uint32, ctypes.c_size_t, doubles,
+    ]
+    for function in (lib.dsp_estimate_offset, lib.dsp_design_butterworth, lib.dsp_demodulate):
+        function.restype = ctypes.c_int32
+    return lib
+
+
+def check(lib, status):
+    if status != 0:
+        raise RuntimeError(f"dsp_core error {status}: {lib.dsp_last_error().decode()}")
+
+
+def main():
+    path = sys.argv[1] if len(sys.argv) > 1 else "../project.wav"
+    fs, data = wavfile.read(path)
+    x = data.astype(np.float64)
+    if data.dtype.kind == "i":
+        x /= np.iinfo(data.dtype).max + 1
+    if x.ndim > 1:
+        x = x.mean(axis=1)
+    x = np.ascontiguousarray(x)
+    lib = load_library()
+
+    estimate = DspOffsetEstimate()
+    check(lib, lib.dsp_estimate_offset(x, len(x), fs, ctypes.byref(estimate)))
+    print(f"f_d = {estimate.f_d:.4f} Hz (pair: {bool(estimate.pair_found)})")
+
+    order, f_b = 8, 4000.0
+    for highpass, cutoff in ((1, estimate.f_d), (0, f_b)):
+        b, a = np.zeros(order + 1), np.zeros(order + 1)
+        check(lib, lib.dsp_design_butterworth(order, cutoff, fs, highpass, b, a))
+        b_ref, a_ref = signal.butter(order, cutoff, "highpass" if highpass else "lowpass", fs=fs)
+        kind = "high-pass" if highpass else "low-pass"
+        print(f"{kind} {cutoff:.1f} Hz: max |b - b_scipy| = {np.abs(b - b_ref).max():.3e}, "
+              f"max |a - a_scipy| = {np.abs(a - a_ref).max():.3e}")
+
+    outputs = {}
+    for name, method in (("fft", DSP_METHOD_FFT), ("iir", DSP_METHOD_IIR)):
+        y = np.zeros_like(x)
+        check(lib, lib.dsp_demodulate(x, len(x), fs, estimate.f_d, f_b, method, order, y))
+        outputs[name] = y
+
+    # Q3's chain with scipy filters: high-pass, mix with cos(2π f_d t), low-pass
+    t = np.arange(len(x)) / fs
+    hp = signal.butter(order, estimate.f_d, "highpass", fs=fs)
+    lp = signal.butter(order, f_b, "lowpass", fs=fs)
+    mixed = signal.lfilter(*hp, x) * 2 * np.cos(2 * np.pi * estimate.f_d * t)
+    reference = signal.lfilter(*lp, mixed)
+    error = np.abs(outputs["iir"] - reference).max() / np.abs(reference).max()
+    print(f"IIR chain vs scipy: max relative difference {error:.3e}")
+    correlation = np.corrcoef(outputs["fft"], outputs["iir"])[0, 1]
+    print(f"FFT chain vs IIR chain: correlation {correlation:.4f}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,47 @@
+/* Estimate f_d of a synthetic DSB signal and demodulate it through the C ABI.
+ *
+ *   cargo build --release
+ *   cc -Iinclude examples/demo.c target/release/libdsp_core_ffi.a -lm -lpthread -ldl -o demo
+ *   ./demo
+ */
+#include <math.h>
+#include <stdio.h>
+
+#include "dsp_core.h"
+
+#define FS 16000.0
+#define N 16000
+
+static double received[N];
+static double output[N];
+
+int main(void) {
+    /* A 400 Hz message on a 3 kHz carrier */
+    for (size_t n = 0; n < N; n++) {
+        double t = n / FS;
+        received[n] = cos(2 * M_PI * 400 * t) * cos(2 * M_PI * 3000 * t);
+    }
+
+    DspOffsetEstimate estimate;
+    if (dsp_estimate_offset(received, N, FS, &estimate) != 0) {
+        fprintf(stderr, "estimate failed: %s\n", dsp_last_error());
+        return 1;
+    }
+    printf("f_d = %.4f Hz (pair %s: %.2f / %.2f Hz)\n", estimate.f_d, estimate.pair_found ? "found" : "not found",
+           estimate.lower_freq, estimate.upper_freq);
+
+    double b[5], a[5];
+    if (dsp_design_butterworth(4, 1000.0, FS, 0, b, a) != 0) {
+        fprintf(stderr, "design failed: %s\n", dsp_last_error());
+        return 1;
+    }
+    printf("4th-order low-pass at 1 kHz: b[0] = %.6e, a[1] = %.6f\n", b[0], a[1]);
+
+    int status = dsp_demodulate(received, N, FS, estimate.f_d, 1000.0, DSP_METHOD_FFT, 0, output);
+    if (status != 0) {
+        fprintf(stderr, "demodulation failed (%d): %s\n", status, dsp_last_error());
+        return status;
+    }
+    printf("output[%d] = %.4f (message %.4f)\n", N / 2, output[N / 2], cos(2 * M_PI * 400 * (N / 2) / FS));
+    return 0;
+}
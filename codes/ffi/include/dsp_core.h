@@ -0,0 +1,114 @@
+#ifndef DSP_CORE_H
+#define DSP_CORE_H
+
+/* Generated by cbindgen from codes/ffi/src/lib.rs; do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * [`dsp_demodulate`] method: Q4's FFT → ideal high-pass → shift → ideal low-pass → IFFT
+ */
+#define DSP_METHOD_FFT 0
+
+/**
+ * [`dsp_demodulate`] method: Q3's Butterworth high-pass → mixing → Butterworth low-pass
+ */
+#define DSP_METHOD_IIR 1
+
+/**
+ * Highest Butterworth order accepted; beyond it the transfer-function coefficients lose
+ * too much precision to be useful
+ */
+#define DSP_MAX_ORDER 20
+
+/**
+ * Result of [`dsp_estimate_offset`]
+ */
+typedef struct DspOffsetEstimate {
+  /**
+   * Estimated frequency offset (Hz)
+   */
+  double f_d;
+  /**
+   * Strongest spectral peak, parabolically interpolated (Hz)
+   */
+  double peak;
+  /**
+   * 1 when f_d is the axis of a sideband pair, 0 when it fell back to the strongest peak
+   */
+  int32_t pair_found;
+  /**
+   * Lower and upper sideband of the pair (Hz); 0 without a pair
+   */
+  double lower_freq;
+  double upper_freq;
+} DspOffsetEstimate;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Message of the last failed call on this thread, or an empty string
+ *
+ * The pointer stays valid until the next call on the same thread.
+ */
+const char *dsp_last_error(void);
+
+/**
+ * Estimate the frequency offset f_d of `samples` the way Q1 does: the axis of the strongest
+ * pair of equal sideband peaks below 5 kHz, or the strongest peak when there is no pair
+ *
+ * # Safety
+ *
+ * `samples` must point to `len` doubles and `estimate` to a writable [`DspOffsetEstimate`].
+ */
+int32_t dsp_estimate_offset(const double *samples,
+                            size_t len,
+                            double sample_rate,
+                            struct DspOffsetEstimate *estimate);
+
+/**
+ * Design a digital Butterworth low-pass (`highpass` = 0) or high-pass filter (bilinear
+ * transform, pre-warped cutoff), normalized so a[0] = 1
+ *
+ * # Safety
+ *
+ * `b` and `a` must each point to `order + 1` writable doubles.
+ */
+int32_t dsp_design_butterworth(size_t order,
+                               double cutoff,
+                               double sample_rate,
+                               int32_t highpass,
+                               double *b,
+                               double *a);
+
+/**
+ * Demodulate `samples` with a local oscillator at `f_d` and keep the baseband up to `f_b`
+ *
+ * [`DSP_METHOD_FFT`] is Q4's ideal-filter chain with the gain from
+ * `GainCalibrator::analytic`, 4 for the high-pass at f_d (`order` is ignored);
+ * [`DSP_METHOD_IIR`] is Q3's chain with Butterworth filters of `order`.
+ *
+ * # Safety
+ *
+ * `samples` must point to `len` doubles and `output` to `len` writable doubles.
+ */
+int32_t dsp_demodulate(const double *samples,
+                       size_t len,
+                       double sample_rate,
+                       double f_d,
+                       double f_b,
+                       uint32_t method,
+                       size_t order,
+                       double *output);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DSP_CORE_H */
@@ -0,0 +1,63 @@
+"""Demodulate project.wav with both Rust demodulators and compare against scipy.
+
+    cd codes/python
+    pip install maturin && maturin develop --release
+    pip install numpy scipy matplotlib
+    python examples/compare_scipy.py ../project.wav
+
+Writes compare_scipy.png: the spectrum with the estimated f_d, and the Q3 (IIR) and Q4 (FFT)
+outputs next to the same Q3 chain built from scipy.signal.
+"""
+
+import sys
+
+import matplotlib.pyplot as plt
+import numpy as np
+from scipy import signal
+
+import dsp_core_py as dsp
+
+path = sys.argv[1] if len(sys.argv) > 1 else "../project.wav"
+audio = dsp.AudioData.open(path)
+fs = audio.sample_rate
+x = np.asarray(audio.samples)
+print(audio)
+
+spectrum = audio.fft()
+estimate = spectrum.estimate_offset()
+f_d, f_b, order = estimate.f_d, 4000.0, 8
+print(estimate)
+
+# Filter design: dsp_core against scipy.signal.butter
+for rust in (dsp.ButterworthFilter.highpass(order, f_d, fs), dsp.ButterworthFilter.lowpass(order, f_b, fs)):
+    b, a = signal.butter(order, rust.cutoff, rust.filter_type, fs=fs)
+    print(f"{rust}: max |Δb| = {np.abs(np.asarray(rust.b) - b).max():.2e}, "
+          f"max |Δa| = {np.abs(np.asarray(rust.a) - a).max():.2e}")
+
+q3 = dsp.TimeDomainDemodulator(f_d, f_b, fs, order=order)
+q4 = dsp.FrequencyDomainDemodulator(f_d, f_b, fs)
+y_q3 = np.asarray(q3.demodulate(x))
+y_q4 = np.asarray(q4.demodulate(x))
+
+t = np.arange(len(x)) / fs
+hp_b, hp_a = q3.highpass_coefficients
+lp_b, lp_a = q3.lowpass_coefficients
+y_scipy = signal.lfilter(lp_b, lp_a, signal.lfilter(hp_b, hp_a, x) * 2 * np.cos(2 * np.pi * f_d * t))
+print(f"Q3 chain vs scipy.lfilter: max |Δy| = {np.abs(y_q3 - y_scipy).max():.2e}")
+print(f"Q3 vs Q4 output: correlation {np.corrcoef(y_q3, y_q4)[0, 1]:.4f}")
+
+frequencies, magnitude = spectrum.one_sided()
+fig, (top, bottom) = plt.subplots(2, 1, figsize=(10, 7))
+top.semilogy(frequencies, magnitude, linewidth=0.6)
+top.axvline(f_d, color="tab:red", linestyle="--", label=f"f_d = {f_d:.2f} Hz")
+top.set(xlim=(0, 8000), xlabel="Frequency (Hz)", ylabel="|X(f)| / N", title="Input spectrum")
+top.legend()
+window = (t > 0.5) & (t < 0.55)
+bottom.plot(t[window], y_q3[window], label="Q3 (IIR)")
+bottom.plot(t[window], y_scipy[window], "--", label="Q3 chain in scipy")
+bottom.plot(t[window], y_q4[window], label="Q4 (FFT)")
+bottom.set(xlabel="Time (s)", ylabel="Amplitude", title="Demodulated")
+bottom.legend()
+fig.tight_layout()
+fig.savefig("compare_scipy.png", dpi=150)
+print("Saved compare_scipy.png")
@@ -0,0 +1,531 @@
+//! Python bindings for dsp_core: read a recording, look at its spectrum, design the
+//! Butterworth filters and run either demodulator from a notebook, then plot with
+//! Matplotlib or compare with scipy.
+//!
+//! Arrays go in as any sequence of floats (a list or a NumPy array) and come back as
+//! lists; `numpy.asarray` turns them into arrays. Errors become `ValueError`, `OSError` or
+//! `NotImplementedError` with the message the command-line programs print.
+
+use dsp_core::butterworth::{self, FilterType};
+use dsp_core::calibration::GainCalibrator;
+use dsp_core::demodulator;
+use dsp_core::error::DspError;
+use dsp_core::iir;
+use dsp_core::peaks;
+use dsp_core::pipeline::{self, SpectralPipeline};
+use dsp_core::spectrum;
+use dsp_core::wav_io::{self, Normalize, WavFormat, WavWriteOptions};
+use num_complex::Complex;
+use pyo3::exceptions::{PyFileNotFoundError, PyNotImplementedError, PyOSError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+/// Highest Butterworth order accepted, as in the C bindings
+const MAX_ORDER: usize = 20;
+
+fn py_err(error: DspError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        DspError::Argument(_) | DspError::Data(_) => PyValueError::new_err(message),
+        DspError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound => {
+            PyFileNotFoundError::new_err(message)
+        }
+        DspError::Io { .. } => PyOSError::new_err(message),
+        DspError::Unsupported(_) => PyNotImplementedError::new_err(message),
+        DspError::Plot(_) | DspError::Other(_) => PyRuntimeError::new_err(message),
+    }
+}
+
+fn check_frequency(name: &str, value: f64, sample_rate: f64) -> PyResult<()> {
+    if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+        return Err(PyValueError::new_err(format!("sample_rate must be positive, got {}", sample_rate)));
+    }
+    if !(value > 0.0 && value < sample_rate / 2.0) {
+        return Err(PyValueError::new_err(format!(
+            "{} must be between 0 and {} Hz, got {}",
+            name,
+            sample_rate / 2.0,
+            value
+        )));
+    }
+    Ok(())
+}
+
+fn check_order(order: usize) -> PyResult<()> {
+    if order == 0 || order > MAX_ORDER {
+        return Err(PyValueError::new_err(format!("order must be 1 to {}, got {}", MAX_ORDER, order)));
+    }
+    Ok(())
+}
+
+/// A mono recording: `samples` in [-1, 1] at `sample_rate` Hz
+#[pyclass(module = "dsp_core_py", get_all)]
+#[derive(Debug, Clone)]
+pub struct AudioData {
+    pub samples: Vec<f64>,
+    pub sample_rate: u32,
+    pub num_samples: usize,
+    /// Channels of the file it was read from; they are averaged into `samples`
+    pub channels: u16,
+}
+
+#[pymethods]
+impl AudioData {
+    #[new]
+    fn new(samples: Vec<f64>, sample_rate: u32) -> Self {
+        let num_samples = samples.len();
+        AudioData { samples, sample_rate, num_samples, channels: 1 }
+    }
+
+    /// Read a WAV file of any PCM or float format, averaging its channels
+    #[staticmethod]
+    fn open(path: PathBuf) -> PyResult<Self> {
+        let (interleaved, spec) = wav_io::read_interleaved(&path.to_string_lossy()).map_err(py_err)?;
+        let channels = spec.channels.max(1);
+        let samples: Vec<f64> = interleaved
+            .chunks(channels as usize)
+            .map(|frame| frame.iter().sum::<f64>() / frame.len() as f64)
+            .collect();
+        let num_samples = samples.len();
+        Ok(AudioData { samples, sample_rate: spec.sample_rate, num_samples, channels })
+    }
+
+    /// Write a WAV file; `bit_depth` is `"16"`, `"24"` or `"f32"` and `normalize` is `"none"`,
+    /// `"peak:<dB>"` or `"rms:<dB>"` (default: peak at 0.95, like the programs' outputs)
+    #[pyo3(signature = (path, bit_depth = "16", normalize = None))]
+    fn save(&self, path: PathBuf, bit_depth: &str, normalize: Option<&str>) -> PyResult<()> {
+        let format: WavFormat = bit_depth.parse().map_err(PyValueError::new_err)?;
+        let normalize: Normalize = match normalize {
+            Some(normalize) => normalize.parse().map_err(PyValueError::new_err)?,
+            None => Normalize::default(),
+        };
+        let options = WavWriteOptions { format, normalize, ..Default::default() };
+        wav_io::write_channels(&path.to_string_lossy(), std::slice::from_ref(&self.samples), self.sample_rate, &options)
+            .map_err(py_err)
+    }
+
+    /// Length in seconds
+    #[getter]
+    fn duration(&self) -> f64 {
+        self.num_samples as f64 / self.sample_rate as f64
+    }
+
+    fn fft(&self) -> FftResult {
+        FftResult::compute(self.samples.clone(), self.sample_rate as f64)
+    }
+
+    fn __len__(&self) -> usize {
+        self.num_samples
+    }
+
+    fn __repr__(&self) -> String {
+        format!("AudioData({} samples, {} Hz, {:.3} s)", self.num_samples, self.sample_rate, self.duration())
+    }
+}
+
+/// Two-sided FFT of a real signal, laid out like Q1's `FftResult`: bin k is at
+/// k·sample_rate/N Hz and `magnitude` is |X[k]| / N
+#[pyclass(module = "dsp_core_py", get_all)]
+#[derive(Debug, Clone)]
+pub struct FftResult {
+    pub spectrum: Vec<Complex<f64>>,
+    pub frequencies: Vec<f64>,
+    pub magnitude: Vec<f64>,
+    pub phase: Vec<f64>,
+    pub sample_rate: f64,
+}
+
+#[pymethods]
+impl FftResult {
+    #[staticmethod]
+    fn compute(samples: Vec<f64>, sample_rate: f64) -> Self {
+        let n = samples.len();
+        let spectrum = pipeline::fft(&samples);
+        FftResult {
+            frequencies: (0..n).map(|k| k as f64 * sample_rate / n as f64).collect(),
+            magnitude: spectrum.iter().map(|x| x.norm() / n as f64).collect(),
+            phase: spectrum.iter().map(|x| x.arg()).collect(),
+            spectrum,
+            sample_rate,
+        }
+    }
+
+    /// `(frequencies, magnitude)` from 0 Hz to the Nyquist frequency, for plotting
+    fn one_sided(&self) -> (Vec<f64>, Vec<f64>) {
+        let bins = if self.spectrum.is_empty() { 0 } else { self.spectrum.len() / 2 + 1 };
+        (self.frequencies[..bins].to_vec(), self.magnitude[..bins].to_vec())
+    }
+
+    /// Q1's f_d estimate from this spectrum (see `estimate_offset`)
+    #[pyo3(signature = (pair_max_freq = 5000.0))]
+    fn estimate_offset(&self, pair_max_freq: f64) -> OffsetEstimate {
+        let (frequencies, magnitude) = self.one_sided();
+        peaks::estimate_offset(&frequencies, &magnitude, pair_max_freq).into()
+    }
+
+    /// Inverse FFT of `spectrum`, real part
+    fn ifft(&self) -> Vec<f64> {
+        pipeline::ifft(&self.spectrum)
+    }
+
+    fn __len__(&self) -> usize {
+        self.spectrum.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let n = self.spectrum.len();
+        format!("FftResult({} bins, {:.4} Hz resolution)", n, self.sample_rate / n.max(1) as f64)
+    }
+}
+
+/// Frequency offset found by `estimate_offset`
+#[pyclass(module = "dsp_core_py", get_all)]
+#[derive(Debug, Clone)]
+pub struct OffsetEstimate {
+    /// Estimated f_d (Hz)
+    pub f_d: f64,
+    /// Strongest peak, parabolically interpolated (Hz)
+    pub peak: f64,
+    /// Peaks that were paired, strongest first, as (frequency, magnitude, bin)
+    pub peaks: Vec<(f64, f64, usize)>,
+    /// (lower, upper) sideband frequencies of the pair f_d is the axis of; None when f_d
+    /// is the strongest peak
+    pub pair: Option<(f64, f64)>,
+}
+
+impl From<peaks::OffsetEstimate> for OffsetEstimate {
+    fn from(estimate: peaks::OffsetEstimate) -> Self {
+        OffsetEstimate {
+            f_d: estimate.f_d,
+            peak: estimate.peak,
+            peaks: estimate.peaks,
+            pair: estimate.pair.map(|pair| (pair.lower_freq, pair.upper_freq)),
+        }
+    }
+}
+
+#[pymethods]
+impl OffsetEstimate {
+    fn __repr__(&self) -> String {
+        match self.pair {
+            Some((lower, upper)) => format!("OffsetEstimate(f_d={:.4}, pair=({:.2}, {:.2}))", self.f_d, lower, upper),
+            None => format!("OffsetEstimate(f_d={:.4}, pair=None)", self.f_d),
+        }
+    }
+}
+
+/// Estimate the frequency offset f_d of `samples` as Q1 does: the axis of the strongest pair
+/// of equal sideband peaks below `pair_max_freq`, or the strongest peak without a pair
+#[pyfunction]
+#[pyo3(signature = (samples, sample_rate, pair_max_freq = 5000.0))]
+fn estimate_offset(samples: Vec<f64>, sample_rate: f64, pair_max_freq: f64) -> PyResult<OffsetEstimate> {
+    if samples.is_empty() {
+        return Err(PyValueError::new_err("samples is empty"));
+    }
+    let (frequencies, magnitude) = spectrum::one_sided_magnitude(&samples, sample_rate);
+    Ok(peaks::estimate_offset(&frequencies, &magnitude, pair_max_freq).into())
+}
+
+/// Digital Butterworth filter (bilinear transform, pre-warped cutoff); `b` and `a` match
+/// `scipy.signal.butter(order, cutoff, btype, fs=sample_rate)`
+#[pyclass(module = "dsp_core_py", name = "ButterworthFilter")]
+#[derive(Debug, Clone)]
+pub struct PyButterworthFilter {
+    inner: butterworth::ButterworthFilter,
+}
+
+#[pymethods]
+impl PyButterworthFilter {
+    #[staticmethod]
+    fn lowpass(order: usize, cutoff: f64, sample_rate: f64) -> PyResult<Self> {
+        check_order(order)?;
+        check_frequency("cutoff", cutoff, sample_rate)?;
+        Ok(PyButterworthFilter { inner: butterworth::ButterworthFilter::lowpass(order, cutoff, sample_rate) })
+    }
+
+    #[staticmethod]
+    fn highpass(order: usize, cutoff: f64, sample_rate: f64) -> PyResult<Self> {
+        check_order(order)?;
+        check_frequency("cutoff", cutoff, sample_rate)?;
+        Ok(PyButterworthFilter { inner: butterworth::ButterworthFilter::highpass(order, cutoff, sample_rate) })
+    }
+
+    #[getter]
+    fn b(&self) -> Vec<f64> {
+        self.inner.b.clone()
+    }
+
+    #[getter]
+    fn a(&self) -> Vec<f64> {
+        self.inner.a.clone()
+    }
+
+    #[getter]
+    fn order(&self) -> usize {
+        self.inner.order
+    }
+
+    #[getter]
+    fn cutoff(&self) -> f64 {
+        self.inner.cutoff
+    }
+
+    #[getter]
+    fn sample_rate(&self) -> f64 {
+        self.inner.sample_rate
+    }
+
+    /// `"lowpass"` or `"highpass"`, scipy's `btype`
+    #[getter]
+    fn filter_type(&self) -> &'static str {
+        match self.inner.filter_type {
+            FilterType::Lowpass => "lowpass",
+            FilterType::Highpass => "highpass",
+        }
+    }
+
+    /// Causal filtering, like `scipy.signal.lfilter(b, a, x)`
+    fn filter(&self, x: Vec<f64>) -> Vec<f64> {
+        iir::apply_filter(&x, &self.inner.b, &self.inner.a)
+    }
+
+    /// Forward-backward zero-phase filtering, like `scipy.signal.filtfilt(b, a, x)` up to
+    /// the edge handling
+    fn filtfilt(&self, x: Vec<f64>) -> Vec<f64> {
+        iir::apply_filter_zero_phase(&x, &self.inner.b, &self.inner.a)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ButterworthFilter.{}(order={}, cutoff={}, sample_rate={})",
+            self.filter_type(),
+            self.inner.order,
+            self.inner.cutoff,
+            self.inner.sample_rate
+        )
+    }
+}
+
+/// Q3's demodulator: Butterworth high-pass at f_d - guard_band, mixing with
+/// 2 cos(2π f_d t), Butterworth low-pass at f_b
+#[pyclass(module = "dsp_core_py", name = "TimeDomainDemodulator")]
+#[derive(Debug, Clone)]
+pub struct PyTimeDomainDemodulator {
+    inner: demodulator::TimeDomainDemodulator,
+    #[pyo3(get)]
+    f_b: f64,
+    #[pyo3(get)]
+    order: usize,
+}
+
+#[pymethods]
+impl PyTimeDomainDemodulator {
+    #[new]
+    #[pyo3(signature = (f_d, f_b, sample_rate, order = demodulator::BATCH_FILTER_ORDER, guard_band = 0.0))]
+    fn new(f_d: f64, f_b: f64, sample_rate: f64, order: usize, guard_band: f64) -> PyResult<Self> {
+        check_order(order)?;
+        check_frequency("f_d - guard_band", f_d - guard_band, sample_rate)?;
+        check_frequency("f_b", f_b, sample_rate)?;
+        let inner = demodulator::TimeDomainDemodulator::butterworth_guarded(order, f_d, f_b, guard_band, sample_rate);
+        Ok(PyTimeDomainDemodulator { inner, f_b, order })
+    }
+
+    #[getter]
+    fn f_d(&self) -> f64 {
+        self.inner.f_d
+    }
+
+    #[getter]
+    fn sample_rate(&self) -> f64 {
+        self.inner.f_s
+    }
+
+    /// Local-oscillator phase (rad); a phase error θ scales the output by cos θ
+    #[getter]
+    fn phase(&self) -> f64 {
+        self.inner.phase
+    }
+
+    #[setter]
+    fn set_phase(&mut self, phase: f64) {
+        self.inner.phase = phase;
+    }
+
+    /// High-pass `(b, a)`
+    #[getter]
+    fn highpass_coefficients(&self) -> (Vec<f64>, Vec<f64>) {
+        self.inner.hp.clone()
+    }
+
+    /// Low-pass `(b, a)`
+    #[getter]
+    fn lowpass_coefficients(&self) -> (Vec<f64>, Vec<f64>) {
+        self.inner.lp.clone()
+    }
+
+    fn highpass(&self, x: Vec<f64>) -> Vec<f64> {
+        self.inner.highpass(&x)
+    }
+
+    fn mix(&self, x: Vec<f64>) -> Vec<f64> {
+        self.inner.mix(&x)
+    }
+
+    fn lowpass(&self, x: Vec<f64>) -> Vec<f64> {
+        self.inner.lowpass(&x)
+    }
+
+    /// High-pass, mix and low-pass
+    fn demodulate(&self, x: Vec<f64>) -> Vec<f64> {
+        self.inner.demodulate(&x)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TimeDomainDemodulator(f_d={}, f_b={}, sample_rate={}, order={})",
+            self.inner.f_d, self.f_b, self.inner.f_s, self.order
+        )
+    }
+}
+
+/// Q4's demodulator: FFT, ideal high-pass at f_d - guard_band, shift by ±f_d, ideal
+/// low-pass at f_b, gain, IFFT
+#[pyclass(module = "dsp_core_py", name = "FrequencyDomainDemodulator")]
+#[derive(Debug, Clone)]
+pub struct PyFrequencyDomainDemodulator {
+    pipeline: SpectralPipeline,
+    #[pyo3(get)]
+    f_d: f64,
+    #[pyo3(get)]
+    f_b: f64,
+    #[pyo3(get)]
+    sample_rate: f64,
+    #[pyo3(get)]
+    gain: f64,
+}
+
+#[pymethods]
+impl PyFrequencyDomainDemodulator {
+    /// Without `gain`, Q4's analytic calibration restores the message at unit level: ×4 for
+    /// the high-pass at f_d, ×2 once a guard band of at least f_b keeps the lower sideband
+    #[new]
+    #[pyo3(signature = (f_d, f_b, sample_rate, gain = None, guard_band = 0.0))]
+    fn new(f_d: f64, f_b: f64, sample_rate: f64, gain: Option<f64>, guard_band: f64) -> PyResult<Self> {
+        check_frequency("f_d - guard_band", f_d - guard_band, sample_rate)?;
+        check_frequency("f_b", f_b, sample_rate)?;
+        let stages = SpectralPipeline::new().highpass(f_d - guard_band).shift(f_d).lowpass(f_b);
+        let gain = gain.unwrap_or_else(|| GainCalibrator::analytic(stages.stages()).gain);
+        let pipeline = stages.gain(gain);
+        Ok(PyFrequencyDomainDemodulator { pipeline, f_d, f_b, sample_rate, gain })
+    }
+
+    fn demodulate(&self, x: Vec<f64>) -> Vec<f64> {
+        self.pipeline.run(&x, self.sample_rate).output
+    }
+
+    /// `[(name, spectrum), ...]`: the input spectrum, then the spectrum after each stage
+    /// (two-sided, bin k at k·sample_rate/N Hz)
+    fn spectra(&self, x: Vec<f64>) -> Vec<(String, Vec<Complex<f64>>)> {
+        let result = self.pipeline.run(&x, self.sample_rate);
+        std::iter::once(("input".to_string(), result.input))
+            .chain(result.stages.into_iter().map(|stage| (stage.stage.to_string(), stage.spectrum)))
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FrequencyDomainDemodulator(f_d={}, f_b={}, sample_rate={}, gain={})",
+            self.f_d, self.f_b, self.sample_rate, self.gain
+        )
+    }
+}
+
+#[pymodule]
+fn dsp_core_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<AudioData>()?;
+    m.add_class::<FftResult>()?;
+    m.add_class::<OffsetEstimate>()?;
+    m.add_class::<PyButterworthFilter>()?;
+    m.add_class::<PyTimeDomainDemodulator>()?;
+    m.add_class::<PyFrequencyDomainDemodulator>()?;
+    m.add_function(wrap_pyfunction!(estimate_offset, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_module_from_python() {
+        Python::attach(|py| {
+            let dsp = pyo3::wrap_pymodule!(dsp_core_py)(py);
+            let path = std::env::temp_dir().join(format!("dsp_core_py_{}.wav", std::process::id()));
+            let path = path.to_string_lossy().into_owned();
+            // One namespace for globals and locals, so comprehensions see the script's names
+            let namespace = PyDict::new(py);
+            namespace.set_item("dsp", dsp).unwrap();
+            namespace.set_item("path", &path).unwrap();
+            let script = CString::new(
+                r#"
+import math
+fs = 16000
+message = [math.cos(2 * math.pi * 400 * n / fs) for n in range(fs)]
+received = [m * math.cos(2 * math.pi * 3000 * n / fs) for n, m in enumerate(message)]
+
+audio = dsp.AudioData(received, fs)
+audio.save(path, bit_depth="f32", normalize="none")
+audio = dsp.AudioData.open(path)
+assert (len(audio), audio.sample_rate, audio.channels) == (fs, fs, 1)
+assert max(abs(a - b) for a, b in zip(audio.samples, received)) < 1e-6
+
+spectrum = audio.fft()
+assert len(spectrum.spectrum) == fs and isinstance(spectrum.spectrum[2600], complex)
+assert abs(spectrum.magnitude[2600] - 0.25) < 1e-6
+estimate = spectrum.estimate_offset()
+assert abs(estimate.f_d - 3000) < 0.5 and estimate.pair == (2600.0, 3400.0), estimate
+assert abs(dsp.estimate_offset(received, fs).f_d - estimate.f_d) < 1e-9
+
+lowpass = dsp.ButterworthFilter.lowpass(4, 1000.0, 8000.0)
+assert (len(lowpass.b), lowpass.a[0], lowpass.filter_type) == (5, 1.0, "lowpass")
+assert abs(sum(lowpass.b) / sum(lowpass.a) - 1) < 1e-9
+
+q4 = dsp.FrequencyDomainDemodulator(3000.0, 1000.0, fs)
+output = q4.demodulate(received)
+# Only the upper sideband passes the high-pass at f_d; the analytic gain of 4 counts that
+assert q4.gain == 4.0
+assert max(abs(y - m) for y, m in zip(output, message)) < 1e-6
+assert dsp.FrequencyDomainDemodulator(3000.0, 1000.0, fs, guard_band=1000.0).gain == 2.0
+names = [name for name, _ in q4.spectra(received)]
+assert names[0] == "input" and len(names) == 5, names
+
+q3 = dsp.TimeDomainDemodulator(3000.0, 1000.0, fs, order=6)
+assert q3.demodulate(received) == q3.lowpass(q3.mix(q3.highpass(received)))
+
+for bad in (lambda: dsp.ButterworthFilter.lowpass(4, 5000.0, 8000.0),
+            lambda: dsp.TimeDomainDemodulator(3000.0, 1000.0, fs, order=0),
+            lambda: audio.save(path, bit_depth="12")):
+    try:
+        bad()
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("no ValueError")
+try:
+    dsp.AudioData.open(path + ".missing")
+except FileNotFoundError:
+    pass
+"#,
+            )
+            .unwrap();
+            let result = py.run(&script, Some(&namespace), None);
+            std::fs::remove_file(&path).ok();
+            if let Err(e) = result {
+                e.print(py);
+                panic!("Python check failed: {}", e);
+            }
+        });
+    }
+}
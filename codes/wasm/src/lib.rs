@@ -0,0 +1,190 @@
+//! Browser build of the demodulation: the bytes of a WAV file in, the estimated frequency
+//! offset and the demodulated signal out.
+//!
+//! The estimate is Q1's sideband-pair search ([`dsp_core::peaks::estimate_offset`]) and
+//! the demodulator is Q4's FFT → ideal high-pass → shift → ideal low-pass → IFFT chain
+//! ([`dsp_core::pipeline::SpectralPipeline`]), so the page and the command-line programs
+//! give the same answer. Nothing here touches the filesystem: files come in and go out
+//! as byte arrays.
+
+use dsp_core::calibration::GainCalibrator;
+use dsp_core::error::{DspError, Result};
+use dsp_core::peaks::{self, OffsetEstimate};
+use dsp_core::pipeline::SpectralPipeline;
+use dsp_core::spectrum;
+use dsp_core::wav_io::{self, Normalize, WavFormat, WavWriteOptions};
+use std::io::Cursor;
+use wasm_bindgen::prelude::*;
+
+/// Baseband bandwidth f_B when the page does not set one (Hz), the value Q1 reports
+pub const DEFAULT_BANDWIDTH: f64 = 4000.0;
+
+/// Highest sideband pair Q1 accepts as the offset (Hz)
+pub const PAIR_MAX_FREQ: f64 = 5000.0;
+
+/// Mono samples of WAV file bytes (channels are averaged) and their sample rate
+pub fn decode(bytes: &[u8]) -> Result<(Vec<f64>, f64)> {
+    let (interleaved, spec) = wav_io::read_interleaved_from(bytes, "uploaded WAV")?;
+    let channels = spec.channels.max(1) as usize;
+    let samples: Vec<f64> = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f64>() / channels as f64)
+        .collect();
+    if samples.is_empty() {
+        return Err(DspError::Data("uploaded WAV: no samples".to_string()));
+    }
+    Ok((samples, spec.sample_rate as f64))
+}
+
+/// 16-bit WAV file bytes of `samples`, peak-normalized like the programs' outputs
+pub fn encode(samples: &[f64], sample_rate: f64) -> Result<Vec<u8>> {
+    let mut bytes = Cursor::new(Vec::new());
+    let options = WavWriteOptions { format: WavFormat::Int16, ..Default::default() };
+    wav_io::write_channels_to(&mut bytes, "demodulated WAV", &[samples.to_vec()], sample_rate as u32, &options)?;
+    Ok(bytes.into_inner())
+}
+
+/// Q4's demodulator with its analytic gain from [`GainCalibrator`]: the high-pass at f_d
+/// and the shift each halve the message, so ×4
+pub fn demodulate_samples(samples: &[f64], sample_rate: f64, f_d: f64, bandwidth: f64) -> Vec<f64> {
+    let stages = SpectralPipeline::new().highpass(f_d).shift(f_d).lowpass(bandwidth);
+    let gain = GainCalibrator::analytic(stages.stages()).gain;
+    stages.gain(gain).run(samples, sample_rate).output
+}
+
+/// Result of [`demodulate`], read by the page through its getters
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Demodulation {
+    estimate: OffsetEstimate,
+    f_d: f64,
+    bandwidth: f64,
+    sample_rate: f64,
+    samples: Vec<f64>,
+}
+
+impl Demodulation {
+    /// Estimate f_d from the spectrum unless `f_d` is given, then demodulate
+    pub fn run(bytes: &[u8], bandwidth: Option<f64>, f_d: Option<f64>) -> Result<Self> {
+        let (input, sample_rate) = decode(bytes)?;
+        let bandwidth = bandwidth.unwrap_or(DEFAULT_BANDWIDTH);
+        let nyquist = sample_rate / 2.0;
+        for (name, value) in [("bandwidth", Some(bandwidth)), ("f_d", f_d)] {
+            if value.is_some_and(|v| !(v > 0.0 && v < nyquist)) {
+                return Err(DspError::Argument(format!(
+                    "{} must be between 0 and {} Hz, got {}",
+                    name,
+                    nyquist,
+                    value.unwrap_or_default()
+                )));
+            }
+        }
+        let (frequencies, magnitude) = spectrum::one_sided_magnitude(&input, sample_rate);
+        let estimate = peaks::estimate_offset(&frequencies, &magnitude, PAIR_MAX_FREQ);
+        let f_d = f_d.unwrap_or(estimate.f_d);
+        let samples = demodulate_samples(&input, sample_rate, f_d, bandwidth);
+        Ok(Demodulation { estimate, f_d, bandwidth, sample_rate, samples })
+    }
+
+    /// The spectrum estimate, also when `f_d` was overridden
+    pub fn estimate(&self) -> &OffsetEstimate {
+        &self.estimate
+    }
+
+    pub fn output(&self) -> &[f64] {
+        &self.samples
+    }
+}
+
+#[wasm_bindgen]
+impl Demodulation {
+    /// Frequency offset the signal was demodulated with (Hz)
+    #[wasm_bindgen(getter)]
+    pub fn f_d(&self) -> f64 {
+        self.f_d
+    }
+
+    /// Offset estimated from the spectrum (Hz)
+    #[wasm_bindgen(getter)]
+    pub fn estimated_f_d(&self) -> f64 {
+        self.estimate.f_d
+    }
+
+    /// Baseband frequency of the sideband pair the estimate came from; undefined when the
+    /// strongest peak was used instead (Hz)
+    #[wasm_bindgen(getter)]
+    pub fn pair_baseband(&self) -> Option<f64> {
+        self.estimate.pair.map(|pair| pair.baseband)
+    }
+
+    /// Low-pass cutoff f_B (Hz)
+    #[wasm_bindgen(getter)]
+    pub fn bandwidth(&self) -> f64 {
+        self.bandwidth
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Output scaled to a 0.95 peak, for a Web Audio `AudioBuffer`
+    pub fn samples(&self) -> Vec<f32> {
+        let gain = Normalize::default().gain(std::slice::from_ref(&self.samples));
+        self.samples.iter().map(|&x| (x * gain) as f32).collect()
+    }
+
+    /// Output as 16-bit WAV file bytes, for a download link
+    pub fn wav(&self) -> std::result::Result<Vec<u8>, JsError> {
+        Ok(encode(&self.samples, self.sample_rate)?)
+    }
+}
+
+/// Demodulate the WAV file `bytes`; `bandwidth` defaults to [`DEFAULT_BANDWIDTH`] and `f_d`
+/// to the estimate from the spectrum
+#[wasm_bindgen]
+pub fn demodulate(bytes: &[u8], bandwidth: Option<f64>, f_d: Option<f64>) -> std::result::Result<Demodulation, JsError> {
+    Ok(Demodulation::run(bytes, bandwidth, f_d)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_demodulates_wav_bytes() {
+        // 400 Hz message on a 3 kHz carrier: sidebands at 2600 and 3400 Hz
+        let f_s = 16000.0;
+        let message: Vec<f64> = (0..16000).map(|n| 0.5 * (2.0 * PI * 400.0 * n as f64 / f_s).cos()).collect();
+        let received: Vec<f64> =
+            message.iter().enumerate().map(|(n, m)| m * (2.0 * PI * 3000.0 * n as f64 / f_s).cos()).collect();
+        let bytes = encode(&received, f_s).unwrap();
+
+        let result = Demodulation::run(&bytes, None, None).unwrap();
+        assert!((result.f_d() - 3000.0).abs() < 0.5, "f_d {}", result.f_d());
+        assert!((result.pair_baseband().unwrap() - 400.0).abs() < 0.5);
+        assert_eq!(result.sample_rate(), f_s);
+
+        // The analytic gain counts the upper sideband kept by the high-pass, so the message
+        // comes back at the level of the (peak-normalized) upload
+        let output = result.output();
+        assert_eq!(output.len(), message.len());
+        let dot: f64 = output.iter().zip(&message).map(|(y, m)| y * m).sum();
+        let energy = |x: &[f64]| x.iter().map(|v| v * v).sum::<f64>();
+        let correlation = dot / (energy(output) * energy(&message)).sqrt();
+        assert!(correlation > 0.99, "correlation {}", correlation);
+        let level = energy(output) / energy(&message) / (energy(&decode(&bytes).unwrap().0) / energy(&received));
+        assert!((level - 1.0).abs() < 1e-3, "level {}", level);
+
+        let decoded = decode(&result.wav().unwrap()).unwrap();
+        assert_eq!(decoded.0.len(), output.len());
+        assert!(result.samples().iter().all(|x| x.abs() <= 0.95 + 1e-6));
+
+        // A given f_d overrides the estimate, and nonsense is refused
+        let forced = Demodulation::run(&bytes, Some(1000.0), Some(2990.0)).unwrap();
+        assert_eq!((forced.f_d(), forced.estimated_f_d().round()), (2990.0, 3000.0));
+        assert!(Demodulation::run(&bytes, Some(9000.0), None).is_err());
+        assert!(Demodulation::run(b"not a wav", None, None).is_err());
+    }
+}